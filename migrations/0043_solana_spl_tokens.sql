@@ -0,0 +1,12 @@
+-- ============================================================================
+-- Migration: 0043_solana_spl_tokens.sql
+-- Description: Register Solana native SOL and SPL stablecoin mints so the
+--              transaction builder can resolve USDC/USDT by symbol.
+-- ============================================================================
+
+-- Solana (chain_id: 501)
+INSERT INTO tokens.registry (symbol, name, chain_id, address, decimals, is_native, is_stablecoin, priority) VALUES
+    ('SOL', 'Solana', 501, 'So11111111111111111111111111111111111111112', 9, true, false, 1),
+    ('USDT', 'Tether USD', 501, 'Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB', 6, false, true, 2),
+    ('USDC', 'USD Coin', 501, 'EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v', 6, false, true, 3)
+ON CONFLICT (chain_id, symbol) DO NOTHING;
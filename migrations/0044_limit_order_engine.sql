@@ -0,0 +1,17 @@
+-- ============================================================================
+-- Migration: 0044_limit_order_engine.sql
+-- Description: 限价单执行引擎：价格触发后保存待签名的 1inch swap 交易
+--              状态：pending -> ready_to_sign -> filled / expired / cancelled / failed
+-- ============================================================================
+
+ALTER TABLE public.limit_orders ADD COLUMN IF NOT EXISTS swap_tx JSONB;
+ALTER TABLE public.limit_orders ADD COLUMN IF NOT EXISTS triggered_price DECIMAL(36, 18);
+ALTER TABLE public.limit_orders ADD COLUMN IF NOT EXISTS quoted_to_amount DECIMAL(36, 18);
+ALTER TABLE public.limit_orders ADD COLUMN IF NOT EXISTS ready_at TIMESTAMPTZ;
+ALTER TABLE public.limit_orders ADD COLUMN IF NOT EXISTS status_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_limit_orders_ready ON public.limit_orders(status, expires_at) WHERE status = 'ready_to_sign';
+
+COMMENT ON COLUMN public.limit_orders.swap_tx IS '价格触发后构建的未签名 1inch swap 交易（from/to/data/value/gas）';
+COMMENT ON COLUMN public.limit_orders.triggered_price IS '触发时 1inch 报价的可成交价格';
+COMMENT ON COLUMN public.limit_orders.status_reason IS '最近一次状态转换原因（过期/失败说明）';
@@ -0,0 +1,18 @@
+-- ============================================================================
+-- Migration: 0045_api_key_scopes.sql
+-- Description: API Key 认证：归属用户、scope、过期时间、独立限流与最近使用时间
+-- ============================================================================
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS user_id UUID;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS key_prefix TEXT;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS scopes TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS rate_limit_per_minute BIGINT;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user ON api_keys(user_id);
+
+COMMENT ON COLUMN api_keys.key_hash IS '原始 Key 的 SHA-256（hex），原始 Key 仅在创建时返回一次';
+COMMENT ON COLUMN api_keys.user_id IS 'Key 归属用户，请求以该用户身份执行（scope 只收窄不放大权限）';
+COMMENT ON COLUMN api_keys.scopes IS '授权范围，如 wallets:read / tx:broadcast / admin:*';
+COMMENT ON COLUMN api_keys.rate_limit_per_minute IS '独立限流额度（为空时使用全局 RATE_LIMIT_MAX_REQUESTS）';
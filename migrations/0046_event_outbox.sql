@@ -0,0 +1,42 @@
+-- ============================================================================
+-- Migration: 0046_event_outbox.sql
+-- Description: 事务性 outbox 事件分发：按处理器记录投递状态，耗尽重试后进入死信表
+--              events.domain_events 即 outbox（processed = false 为待分发）
+-- ============================================================================
+
+ALTER TABLE events.domain_events ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_domain_events_due
+    ON events.domain_events(next_attempt_at, published_at) WHERE processed = false;
+
+-- 处理器投递状态（每个事件 × 每个处理器一行）
+CREATE TABLE IF NOT EXISTS events.handler_deliveries (
+    event_id UUID NOT NULL REFERENCES events.domain_events(id) ON DELETE CASCADE,
+    handler_name TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('succeeded', 'retrying', 'dead')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ,
+    last_error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_id, handler_name)
+);
+
+-- 死信队列
+CREATE TABLE IF NOT EXISTS events.dead_letter_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL,
+    handler_name TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    event_data JSONB NOT NULL,
+    error TEXT,
+    attempts INT NOT NULL,
+    dead_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    replayed_at TIMESTAMPTZ,
+    replayed_by UUID
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letter_events_pending
+    ON events.dead_letter_events(dead_at DESC) WHERE replayed_at IS NULL;
+
+COMMENT ON TABLE events.handler_deliveries IS '事件处理器投递状态（指数退避重试）';
+COMMENT ON TABLE events.dead_letter_events IS '重试耗尽的事件投递，可由管理员重放';
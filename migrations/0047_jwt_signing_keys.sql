@@ -0,0 +1,25 @@
+-- ============================================================================
+-- Migration: 0047_jwt_signing_keys.sql
+-- Description: JWT 非对称签名密钥（RS256 / EdDSA），支持轮换与宽限期验证
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS admin.jwt_signing_keys (
+    kid TEXT PRIMARY KEY,
+    purpose TEXT NOT NULL CHECK (purpose IN ('access', 'refresh')),
+    algorithm TEXT NOT NULL CHECK (algorithm IN ('RS256', 'EdDSA')),
+    private_key_encrypted BYTEA NOT NULL,
+    public_jwk JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'retiring', 'retired')),
+    retire_after TIMESTAMPTZ,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 每个用途同一时刻只有一个签名密钥
+CREATE UNIQUE INDEX IF NOT EXISTS uq_jwt_signing_keys_active
+    ON admin.jwt_signing_keys(purpose) WHERE status = 'active';
+
+COMMENT ON COLUMN admin.jwt_signing_keys.private_key_encrypted IS '私钥 DER，经 WALLET_ENC_KEY AES-256-GCM 加密';
+COMMENT ON COLUMN admin.jwt_signing_keys.status IS 'active=签名+验证, retiring=宽限期内仅验证, retired=不再接受';
+COMMENT ON COLUMN admin.jwt_signing_keys.retire_after IS 'retiring 密钥停止验证的时间（应不早于旧 Token 的最长有效期）';
@@ -0,0 +1,41 @@
+-- ============================================================================
+-- Migration: 0048_risk_policy_engine.sql
+-- Description: 提现风控策略引擎：策略启用状态、版本快照、决策关联策略版本、地理位置
+-- ============================================================================
+
+ALTER TABLE policies ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'draft';
+ALTER TABLE policies ADD COLUMN IF NOT EXISTS activated_at TIMESTAMPTZ;
+ALTER TABLE policies DROP CONSTRAINT IF EXISTS chk_policies_status;
+ALTER TABLE policies
+    ADD CONSTRAINT chk_policies_status CHECK (status IN ('draft', 'active', 'inactive'));
+
+CREATE INDEX IF NOT EXISTS idx_policies_tenant_active
+    ON policies(tenant_id) WHERE status = 'active';
+
+-- 版本快照（每次修改 rules 记录一次，可按版本回滚启用）
+CREATE TABLE IF NOT EXISTS policy_versions (
+    policy_id UUID NOT NULL REFERENCES policies(id) ON DELETE CASCADE,
+    version INT NOT NULL,
+    rules JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (policy_id, version)
+);
+
+INSERT INTO policy_versions (policy_id, version, rules, created_at)
+SELECT id, version, rules, created_at FROM policies
+ON CONFLICT (policy_id, version) DO NOTHING;
+
+-- 风控决策关联的策略版本
+ALTER TABLE withdrawal_risk_logs ADD COLUMN IF NOT EXISTS policy_refs JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE withdrawal_risk_logs ADD COLUMN IF NOT EXISTS client_ip TEXT;
+ALTER TABLE withdrawal_risk_logs ADD COLUMN IF NOT EXISTS country TEXT;
+
+-- 提现请求记录地理位置（dry-run 回放 geo 规则）
+ALTER TABLE withdrawal_requests ADD COLUMN IF NOT EXISTS client_ip TEXT;
+ALTER TABLE withdrawal_requests ADD COLUMN IF NOT EXISTS country TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_withdrawal_requests_tenant_created
+    ON withdrawal_requests(tenant_id, created_at DESC);
+
+COMMENT ON COLUMN policies.status IS 'draft=草稿, active=参与提现风控, inactive=已停用';
+COMMENT ON COLUMN withdrawal_risk_logs.policy_refs IS '评估时生效的策略 [{policy_id, name, version}]，为空表示平台默认策略';
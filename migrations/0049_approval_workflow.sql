@@ -0,0 +1,42 @@
+-- ============================================================================
+-- Migration: 0049_approval_workflow.sql
+-- Description: M-of-N 多方审批：挂起操作关联、审批人角色、过期时间、逐票记录
+-- ============================================================================
+
+-- 风控平台默认策略触发的审批没有关联租户策略
+ALTER TABLE approvals ALTER COLUMN policy_id DROP NOT NULL;
+
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS resource_type TEXT;
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS resource_id UUID;
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS required_approvals INT NOT NULL DEFAULT 1;
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS approver_roles TEXT[] NOT NULL DEFAULT ARRAY['admin'];
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS decided_at TIMESTAMPTZ;
+
+ALTER TABLE approvals DROP CONSTRAINT IF EXISTS chk_approvals_required;
+ALTER TABLE approvals
+    ADD CONSTRAINT chk_approvals_required CHECK (required_approvals >= 1);
+
+-- 同一挂起操作只能有一个进行中的审批
+CREATE UNIQUE INDEX IF NOT EXISTS uq_approvals_pending_resource
+    ON approvals(resource_type, resource_id) WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_approvals_pending_expiry
+    ON approvals(expires_at) WHERE status = 'pending';
+
+-- 逐票记录（每位审批人一票）
+CREATE TABLE IF NOT EXISTS approval_votes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    approval_id UUID NOT NULL REFERENCES approvals(id) ON DELETE CASCADE,
+    voter_id UUID NOT NULL,
+    voter_role TEXT NOT NULL,
+    decision TEXT NOT NULL CHECK (decision IN ('approve', 'reject')),
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (approval_id, voter_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_votes_approval
+    ON approval_votes(approval_id, created_at);
+
+COMMENT ON TABLE approval_votes IS '审批投票记录：M-of-N 多方审批逐票留痕';
@@ -0,0 +1,28 @@
+-- ============================================================================
+-- Migration: 0050_deposit_indexer.sql
+-- Description: 入账索引器：按链持久化扫描游标，外部转入写入 transactions(receive)
+-- ============================================================================
+
+-- 每条链一个游标：height 为已完整索引的最高区块（TON 为 unix 秒）
+CREATE TABLE IF NOT EXISTS deposit_index_cursors (
+    chain TEXT PRIMARY KEY,
+    height BIGINT NOT NULL,
+    block_hash TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE deposit_index_cursors IS '入账索引器游标：已确认并完成扫描的最高高度及其区块哈希（用于重组检测）';
+
+-- 入账记录的区块高度与事件键（native / log:N / vout:N / msg:lt / spl:mint）
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS block_number BIGINT;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS event_key TEXT;
+
+-- 同一笔交易内同一地址的同一事件只入账一次（索引器重放幂等）
+CREATE UNIQUE INDEX IF NOT EXISTS uq_transactions_indexed_event
+    ON transactions(chain, tx_hash, to_address, event_key)
+    WHERE event_key IS NOT NULL;
+
+-- 重组回滚按链 + 高度删除
+CREATE INDEX IF NOT EXISTS idx_transactions_indexed_block
+    ON transactions(chain, block_number)
+    WHERE event_key IS NOT NULL;
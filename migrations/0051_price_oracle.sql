@@ -0,0 +1,17 @@
+-- ============================================================================
+-- Migration: 0051_price_oracle.sql
+-- Description: 多源价格预言机：管理员手动价格覆盖
+-- ============================================================================
+
+-- 每个币种至多一条覆盖；生效期间预言机直接采用，跳过中位数聚合
+CREATE TABLE IF NOT EXISTS price_overrides (
+    symbol TEXT PRIMARY KEY,
+    price_usd DECIMAL(36, 18) NOT NULL CHECK (price_usd > 0),
+    reason TEXT NOT NULL,
+    set_by UUID NOT NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE price_overrides IS '管理员手动价格覆盖（USD），expires_at 为空表示直到删除前一直生效';
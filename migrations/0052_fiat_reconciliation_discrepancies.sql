@@ -0,0 +1,45 @@
+-- ============================================================================
+-- Migration: 0052_fiat_reconciliation_discrepancies.sql
+-- Description: 法币对账：逐单差异记录 + 对账记录补齐 error_message / metadata
+-- ============================================================================
+
+ALTER TABLE fiat.reconciliation_records ADD COLUMN IF NOT EXISTS error_message TEXT;
+ALTER TABLE fiat.reconciliation_records ADD COLUMN IF NOT EXISTS metadata JSONB;
+
+-- 存在差异时状态为 completed_with_issues
+ALTER TABLE fiat.reconciliation_records
+    DROP CONSTRAINT IF EXISTS chk_reconciliation_status;
+ALTER TABLE fiat.reconciliation_records
+    ADD CONSTRAINT chk_reconciliation_status
+    CHECK (status IN ('pending', 'running', 'completed', 'completed_with_issues', 'failed'));
+
+-- 逐单差异：本地缺失 / 服务商缺失 / 金额不符 / 状态不符
+CREATE TABLE IF NOT EXISTS fiat.reconciliation_discrepancies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    reconciliation_id UUID NOT NULL,
+    reconciliation_date DATE NOT NULL,
+    provider TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    order_id UUID,
+    provider_order_id TEXT,
+    local_amount DECIMAL(18, 6),
+    provider_amount DECIMAL(18, 6),
+    local_currency TEXT,
+    provider_currency TEXT,
+    local_status TEXT,
+    provider_status TEXT,
+    detail TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT chk_discrepancy_kind
+        CHECK (kind IN ('missing_locally', 'missing_remotely', 'amount_mismatch', 'status_mismatch')),
+    CONSTRAINT chk_discrepancy_severity
+        CHECK (severity IN ('low', 'medium', 'high', 'critical'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_discrepancies_run
+    ON fiat.reconciliation_discrepancies(reconciliation_id);
+CREATE INDEX IF NOT EXISTS idx_reconciliation_discrepancies_date
+    ON fiat.reconciliation_discrepancies(reconciliation_date DESC, provider);
+
+COMMENT ON TABLE fiat.reconciliation_discrepancies IS '每日对账逐单差异（重跑同日对账时先删除旧记录）';
@@ -0,0 +1,19 @@
+-- ============================================================================
+-- Migration: 0053_alert_rule_states.sql
+-- Description: 指标告警规则状态（pending / firing / resolved 持久化，重启后恢复）
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS admin.alert_rule_states (
+    rule_name TEXT PRIMARY KEY,
+    state TEXT NOT NULL DEFAULT 'inactive',
+    value DOUBLE PRECISION,
+    -- 进入当前状态的时间（pending 计时起点 / 触发时间 / 恢复时间）
+    active_since TIMESTAMPTZ,
+    -- 最近一次触发写入的 fiat.alerts 记录
+    alert_id UUID,
+    last_fired_at TIMESTAMPTZ,
+    last_resolved_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT chk_alert_rule_state
+        CHECK (state IN ('inactive', 'pending', 'firing', 'resolved'))
+);
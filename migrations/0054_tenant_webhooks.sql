@@ -0,0 +1,71 @@
+-- ============================================================================
+-- Migration: 0054_tenant_webhooks.sql
+-- Description: 租户出站 Webhook（端点注册 / 投递队列 / 每次尝试的投递日志）
+-- ============================================================================
+
+-- 租户注册的 Webhook 端点
+CREATE TABLE IF NOT EXISTS events.webhook_endpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    url TEXT NOT NULL,
+    description TEXT,
+    -- 订阅的公开事件类型（如 transaction.confirmed），'*' 表示全部
+    event_types TEXT[] NOT NULL DEFAULT ARRAY['*'],
+    -- HMAC-SHA256 签名密钥（仅创建时返回一次），经 WALLET_ENC_KEY AES-256-GCM 加密存储
+    secret_encrypted BYTEA NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    -- 连续失败次数，成功投递后清零；达到阈值自动禁用
+    consecutive_failures INT NOT NULL DEFAULT 0,
+    disabled_reason TEXT,
+    disabled_at TIMESTAMPTZ,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_endpoints_tenant
+    ON events.webhook_endpoints(tenant_id, created_at DESC);
+
+-- 每个端点每个事件一条投递记录（event_id 唯一，outbox 重试时幂等）
+CREATE TABLE IF NOT EXISTS events.webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    endpoint_id UUID NOT NULL REFERENCES events.webhook_endpoints(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL,
+    event_id UUID NOT NULL,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 认领租约，实例崩溃后投递自动重新可见
+    locked_until TIMESTAMPTZ,
+    last_status_code INT,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT uq_webhook_delivery_event UNIQUE (endpoint_id, event_id),
+    CONSTRAINT chk_webhook_delivery_status
+        CHECK (status IN ('pending', 'succeeded', 'failed'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON events.webhook_deliveries(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_endpoint
+    ON events.webhook_deliveries(endpoint_id, created_at DESC);
+
+-- 投递日志：每次 HTTP 尝试一条（响应码 / 错误 / 耗时）
+CREATE TABLE IF NOT EXISTS events.webhook_delivery_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    delivery_id UUID NOT NULL REFERENCES events.webhook_deliveries(id) ON DELETE CASCADE,
+    attempt INT NOT NULL,
+    status_code INT,
+    error TEXT,
+    -- 响应体前 1KB，便于集成方排查
+    response_body TEXT,
+    duration_ms BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+    ON events.webhook_delivery_attempts(delivery_id, attempt);
@@ -0,0 +1,45 @@
+-- ============================================================================
+-- Migration: 0055_audit_ledger.sql
+-- Description: 防篡改审计账本（哈希链，每条记录承诺前一条的哈希）与 Merkle 检查点（锚定到 immudb）
+-- ============================================================================
+
+-- 只追加的审计账本：entry_hash = SHA-256(规范化记录 + prev_hash)
+CREATE TABLE IF NOT EXISTS admin.audit_ledger (
+    -- 全局连续序号，追加时取链头 + 1（主键冲突即并发追加，重试）
+    seq BIGINT PRIMARY KEY,
+    -- 对应 fiat.audit_logs.id
+    id UUID NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL,
+    event TEXT NOT NULL,
+    actor TEXT NOT NULL DEFAULT '',
+    resource TEXT NOT NULL DEFAULT '',
+    payload JSONB NOT NULL,
+    -- SHA-256(规范化 payload JSON)
+    payload_hash TEXT NOT NULL,
+    prev_hash TEXT NOT NULL,
+    entry_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_ledger_tenant
+    ON admin.audit_ledger(tenant_id, seq);
+
+-- Merkle 检查点：覆盖 [from_seq, to_seq] 的 entry_hash，根哈希写入 immudb 作为外部锚点
+CREATE TABLE IF NOT EXISTS admin.audit_ledger_checkpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    from_seq BIGINT NOT NULL,
+    to_seq BIGINT NOT NULL UNIQUE,
+    entry_count BIGINT NOT NULL,
+    merkle_root TEXT NOT NULL,
+    -- 检查点时链头的 entry_hash
+    head_hash TEXT NOT NULL,
+    anchored BOOLEAN NOT NULL DEFAULT false,
+    anchor_proof TEXT,
+    anchor_attempts INT NOT NULL DEFAULT 0,
+    anchored_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT chk_audit_checkpoint_range CHECK (from_seq <= to_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_ledger_checkpoints_unanchored
+    ON admin.audit_ledger_checkpoints(anchored, to_seq);
@@ -0,0 +1,22 @@
+-- ============================================================================
+-- Migration: 0056_jwt_signing_key_activation.sql
+-- Description: JWT 密钥分阶段轮换：新密钥先以 pending 发布（仅验证），
+--              至少经过一个密钥环刷新周期后再切换为签名密钥，避免其他副本拒绝新 Token
+-- ============================================================================
+
+ALTER TABLE admin.jwt_signing_keys ADD COLUMN IF NOT EXISTS activate_after TIMESTAMPTZ;
+ALTER TABLE admin.jwt_signing_keys ADD COLUMN IF NOT EXISTS grace_period_secs BIGINT;
+
+ALTER TABLE admin.jwt_signing_keys DROP CONSTRAINT IF EXISTS jwt_signing_keys_status_check;
+ALTER TABLE admin.jwt_signing_keys DROP CONSTRAINT IF EXISTS check_status;
+ALTER TABLE admin.jwt_signing_keys DROP CONSTRAINT IF EXISTS chk_jwt_signing_keys_status;
+ALTER TABLE admin.jwt_signing_keys ADD CONSTRAINT chk_jwt_signing_keys_status
+    CHECK (status IN ('pending', 'active', 'retiring', 'retired'));
+
+-- 每个用途同一时刻最多一个待激活密钥
+CREATE UNIQUE INDEX IF NOT EXISTS uq_jwt_signing_keys_pending
+    ON admin.jwt_signing_keys(purpose) WHERE status = 'pending';
+
+COMMENT ON COLUMN admin.jwt_signing_keys.status IS 'pending=已发布仅验证（等待激活）, active=签名+验证, retiring=宽限期内仅验证, retired=不再接受';
+COMMENT ON COLUMN admin.jwt_signing_keys.activate_after IS 'pending 密钥成为签名密钥的时间（不早于各副本完成一次密钥环刷新）';
+COMMENT ON COLUMN admin.jwt_signing_keys.grace_period_secs IS '激活时被替换的旧签名密钥的验证宽限期';
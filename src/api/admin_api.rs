@@ -4,8 +4,9 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    routing::{post, put},
+    extract::{Path, Query, State},
+    middleware::from_fn,
+    routing::{get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
@@ -14,11 +15,24 @@ use uuid::Uuid;
 
 use crate::{
     api::{
-        middleware::{auth::AuthInfoExtractor, rbac::require_admin},
+        middleware::{
+            auth::{require_scope, AuthInfoExtractor},
+            rbac::require_admin,
+        },
         response::success_response,
     },
     app_state::AppState,
     error::AppError,
+    infrastructure::{
+        event_outbox::{self, DeadLetterEvent},
+        jwt::{KeyPurpose, SigningAlgorithm},
+        worker_supervisor::{self, WorkerOwnershipReport},
+    },
+    service::{
+        api_keys::scopes,
+        jwt_keys::{self, JwtKeyInfo, JwtKeyManager},
+        price_oracle::{self, AggregatedPrice, PriceOverride},
+    },
 };
 
 // ============ 费率规则 CRUD ============
@@ -662,6 +676,221 @@ pub async fn delete_rpc_endpoint(
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
+// ============ 事件死信队列 ============
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub include_replayed: Option<bool>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeadLetterResp {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub handler_name: String,
+    pub event_type: String,
+    pub event_data: serde_json::Value,
+    pub error: Option<String>,
+    pub attempts: i32,
+    pub dead_at: String,
+    pub replayed_at: Option<String>,
+}
+
+impl From<DeadLetterEvent> for DeadLetterResp {
+    fn from(d: DeadLetterEvent) -> Self {
+        Self {
+            id: d.id,
+            event_id: d.event_id,
+            handler_name: d.handler_name,
+            event_type: d.event_type,
+            event_data: d.event_data,
+            error: d.error,
+            attempts: d.attempts,
+            dead_at: d.dead_at.to_rfc3339(),
+            replayed_at: d.replayed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// 查询事件死信（默认仅未重放）
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/dead-letters",
+    params(
+        ("include_replayed" = Option<bool>, Query, description = "是否包含已重放的死信"),
+        ("limit" = Option<i64>, Query, description = "默认 50，最大 500"),
+        ("offset" = Option<i64>, Query, description = "分页偏移"),
+    ),
+    responses(
+        (status = 200, description = "Dead letter events", body = Vec<DeadLetterResp>),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_dead_letters(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Query(q): Query<DeadLetterQuery>,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<DeadLetterResp>>>, AppError> {
+    require_admin(&auth)?;
+
+    let limit = q.limit.unwrap_or(50).clamp(1, 500);
+    let offset = q.offset.unwrap_or(0).max(0);
+
+    let dead_letters = event_outbox::list_dead_letters(
+        &st.pool,
+        q.include_replayed.unwrap_or(false),
+        limit,
+        offset,
+    )
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to list dead letters: {}", e)))?;
+
+    success_response(dead_letters.into_iter().map(DeadLetterResp::from).collect())
+}
+
+/// 重放事件死信（重新投递给原处理器）
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/dead-letters/{id}/replay",
+    responses(
+        (status = 204, description = "Dead letter requeued"),
+        (status = 404, description = "Dead letter not found or already replayed"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn replay_dead_letter(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+) -> Result<axum::http::StatusCode, AppError> {
+    require_admin(&auth)?;
+
+    let replayed = event_outbox::replay_dead_letter(&st.pool, id, auth.user_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to replay dead letter: {}", e)))?;
+
+    if !replayed {
+        return Err(AppError::not_found(
+            "Dead letter not found or already replayed",
+        ));
+    }
+
+    record_admin_operation(
+        &st.pool,
+        auth.user_id,
+        &auth.role,
+        "replay_dead_letter",
+        &id.to_string(),
+        "replay",
+    )
+    .await?;
+
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+// ============ JWT 签名密钥轮换 ============
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RotateJwtKeyReq {
+    /// access / refresh；为空时同时轮换两者
+    pub purpose: Option<String>,
+    /// RS256 / EdDSA；为空时使用 JWT_SIGNING_ALG（默认 EdDSA）
+    pub algorithm: Option<String>,
+    /// 旧密钥继续验证的宽限期（秒）；为空时等于该用途 Token 的最长有效期
+    pub grace_period_secs: Option<i64>,
+}
+
+/// 查询 JWT 签名密钥（不含私钥）
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jwt-keys",
+    responses(
+        (status = 200, description = "Signing keys", body = Vec<JwtKeyInfo>),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_jwt_keys(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<JwtKeyInfo>>>, AppError> {
+    require_admin(&auth)?;
+
+    let keys = JwtKeyManager::new(st.pool.clone())
+        .list_keys()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to list JWT keys: {}", e)))?;
+
+    success_response(keys)
+}
+
+/// 轮换 JWT 签名密钥
+///
+/// 新密钥以 pending 状态发布到 JWKS，经过两个密钥环刷新周期后才开始签名
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jwt-keys/rotate",
+    request_body = RotateJwtKeyReq,
+    responses(
+        (status = 200, description = "Staged signing keys (pending)", body = Vec<JwtKeyInfo>),
+        (status = 400, description = "Invalid purpose / algorithm"),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn rotate_jwt_keys(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Json(req): Json<RotateJwtKeyReq>,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<JwtKeyInfo>>>, AppError> {
+    require_admin(&auth)?;
+
+    let purposes = match req.purpose.as_deref() {
+        None => KeyPurpose::ALL.to_vec(),
+        Some(p) => vec![KeyPurpose::parse(p)
+            .ok_or_else(|| AppError::bad_request("purpose must be access or refresh"))?],
+    };
+    let algorithm = match req.algorithm.as_deref() {
+        None => jwt_keys::configured_algorithm().unwrap_or(SigningAlgorithm::EdDSA),
+        Some(a) => SigningAlgorithm::parse(a)
+            .ok_or_else(|| AppError::bad_request("algorithm must be RS256 or EdDSA"))?,
+    };
+    if req.grace_period_secs.is_some_and(|g| g < 0) {
+        return Err(AppError::bad_request("grace_period_secs must be >= 0"));
+    }
+
+    let manager = JwtKeyManager::new(st.pool.clone());
+    let mut rotated = Vec::with_capacity(purposes.len());
+    for purpose in purposes {
+        let grace = req
+            .grace_period_secs
+            .map(chrono::Duration::seconds)
+            .unwrap_or_else(|| jwt_keys::default_grace_period(purpose));
+
+        let key = manager
+            .rotate(purpose, algorithm, grace, Some(auth.user_id))
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to rotate JWT key: {}", e)))?;
+
+        record_admin_operation(
+            &st.pool,
+            auth.user_id,
+            &auth.role,
+            "rotate_jwt_key",
+            &key.kid,
+            purpose.as_str(),
+        )
+        .await?;
+
+        rotated.push(key);
+    }
+
+    success_response(rotated)
+}
+
 // ============ 辅助函数 ============
 
 async fn get_fee_rule_by_id(pool: &sqlx::PgPool, id: Uuid) -> Result<FeeRuleResp, AppError> {
@@ -789,6 +1018,197 @@ async fn record_admin_operation(
     Ok(())
 }
 
+/// 查询后台任务租约归属（哪个实例持有哪个任务/链分片）
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/workers",
+    responses(
+        (status = 200, description = "Worker lease ownership", body = WorkerOwnershipReport),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_worker_leases(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+) -> Result<Json<crate::api::response::ApiResponse<WorkerOwnershipReport>>, AppError> {
+    require_admin(&auth)?;
+
+    let report = worker_supervisor::ownership_report(&st.distributed_lock)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to read worker leases: {}", e)))?;
+
+    success_response(report)
+}
+
+// ============ 价格预言机 ============
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetPriceOverrideReq {
+    /// USD 价格（十进制字符串）
+    pub price_usd: String,
+    /// 覆盖原因（记入操作日志）
+    pub reason: String,
+    /// 有效期（秒）；为空时直到删除前一直生效
+    pub expires_in_secs: Option<i64>,
+}
+
+/// 实时聚合价格（不读缓存），查看各源报价
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/prices/{symbol}",
+    params(("symbol" = String, Path, description = "Token symbol")),
+    responses(
+        (status = 200, description = "Aggregated price with per-source quotes"),
+        (status = 503, description = "No fresh agreeing quotes"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_oracle_price(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(symbol): Path<String>,
+) -> Result<Json<crate::api::response::ApiResponse<AggregatedPrice>>, AppError> {
+    require_admin(&auth)?;
+
+    let price = st
+        .price_service
+        .get_aggregated_price(&symbol)
+        .await
+        .map_err(|e| AppError::service_unavailable(e.to_string()))?;
+
+    success_response(price)
+}
+
+/// 查询手动价格覆盖
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/prices/overrides",
+    responses(
+        (status = 200, description = "Manual price overrides", body = Vec<PriceOverride>),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_price_overrides(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<PriceOverride>>>, AppError> {
+    require_admin(&auth)?;
+
+    let overrides = price_oracle::list_overrides(&st.pool)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to list price overrides: {}", e)))?;
+
+    success_response(overrides)
+}
+
+/// 设置手动价格覆盖（生效期间跳过多源聚合）
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/prices/overrides/{symbol}",
+    params(("symbol" = String, Path, description = "Token symbol")),
+    request_body = SetPriceOverrideReq,
+    responses(
+        (status = 200, description = "Override set", body = PriceOverride),
+        (status = 400, description = "Invalid price / expiry"),
+        (status = 403, description = "Forbidden"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn set_price_override(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(symbol): Path<String>,
+    Json(req): Json<SetPriceOverrideReq>,
+) -> Result<Json<crate::api::response::ApiResponse<PriceOverride>>, AppError> {
+    require_admin(&auth)?;
+
+    let price_usd = crate::domain::token_amount::parse_decimal_strict(&req.price_usd)
+        .ok()
+        .filter(|p| *p > rust_decimal::Decimal::ZERO)
+        .ok_or_else(|| AppError::bad_request("price_usd must be a positive decimal"))?;
+    if req.reason.trim().is_empty() {
+        return Err(AppError::bad_request("reason is required"));
+    }
+    let expires_at = match req.expires_in_secs {
+        Some(secs) if secs <= 0 => {
+            return Err(AppError::bad_request("expires_in_secs must be > 0"));
+        }
+        Some(secs) => Some(chrono::Utc::now() + chrono::Duration::seconds(secs)),
+        None => None,
+    };
+
+    let row = price_oracle::set_override(
+        &st.pool,
+        &symbol,
+        price_usd,
+        req.reason.trim(),
+        auth.user_id,
+        expires_at,
+    )
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to set price override: {}", e)))?;
+    st.price_service
+        .invalidate(&row.symbol)
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?;
+
+    record_admin_operation(
+        &st.pool,
+        auth.user_id,
+        &auth.role,
+        "set_price_override",
+        &row.symbol,
+        &format!("{} ({})", row.price_usd, row.reason),
+    )
+    .await?;
+
+    success_response(row)
+}
+
+/// 删除手动价格覆盖（恢复多源聚合）
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/prices/overrides/{symbol}",
+    params(("symbol" = String, Path, description = "Token symbol")),
+    responses(
+        (status = 204, description = "Override removed"),
+        (status = 404, description = "Override not found"),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_price_override(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(symbol): Path<String>,
+) -> Result<axum::http::StatusCode, AppError> {
+    require_admin(&auth)?;
+
+    let removed = price_oracle::clear_override(&st.pool, &symbol)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to delete price override: {}", e)))?;
+    if !removed {
+        return Err(AppError::not_found("Price override not found"));
+    }
+    st.price_service
+        .invalidate(&symbol)
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?;
+
+    record_admin_operation(
+        &st.pool,
+        auth.user_id,
+        &auth.role,
+        "delete_price_override",
+        &symbol.trim().to_uppercase(),
+        "hard_delete",
+    )
+    .await?;
+
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
 // ============ 路由配置（✅ 企业级标准 V1）============
 
 pub fn create_admin_routes() -> Router<Arc<AppState>> {
@@ -817,6 +1237,25 @@ pub fn create_admin_routes() -> Router<Arc<AppState>> {
             "/api/v1/admin/rpc-endpoints/:id",
             put(update_rpc_endpoint).delete(delete_rpc_endpoint),
         )
+        // ✅ V1: 事件死信队列
+        .route("/api/v1/admin/dead-letters", get(list_dead_letters))
+        .route(
+            "/api/v1/admin/dead-letters/:id/replay",
+            post(replay_dead_letter),
+        )
+        // ✅ V1: JWT 签名密钥
+        .route("/api/v1/admin/jwt-keys", get(list_jwt_keys))
+        .route("/api/v1/admin/jwt-keys/rotate", post(rotate_jwt_keys))
+        .route("/api/v1/admin/workers", get(list_worker_leases))
+        // ✅ V1: 价格预言机
+        .route("/api/v1/admin/prices/overrides", get(list_price_overrides))
+        .route(
+            "/api/v1/admin/prices/overrides/:symbol",
+            put(set_price_override).delete(delete_price_override),
+        )
+        .route("/api/v1/admin/prices/:symbol", get(get_oracle_price))
+        // API Key 需 admin:* scope（仍需归属用户为 admin 角色）
+        .route_layer(from_fn(require_scope(scopes::ADMIN_ALL)))
 }
 
 // Alias for consistency
@@ -5,6 +5,7 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,7 +19,6 @@ use crate::{
         cross_chain_bridge_service::{
             CrossChainBridgeService, CrossChainSwapRequest, CrossChainSwapResponse, SwapQuote,
         },
-        price_service::PriceService,
     },
 };
 
@@ -47,7 +47,7 @@ pub struct PriceData {
 pub struct SwapQuoteRequest {
     pub source_chain: String,
     pub source_token: String,
-    pub source_amount: f64,
+    pub source_amount: Decimal,
     pub target_chain: String,
     pub target_token: String,
 }
@@ -60,8 +60,7 @@ pub async fn get_prices(
     State(state): State<Arc<AppState>>,
     Query(query): Query<PriceQuery>,
 ) -> Result<axum::Json<crate::api::response::ApiResponse<PricesResponse>>, AppError> {
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = PriceService::new(state.pool.clone(), redis_url);
+    let price_service = state.price_service.clone();
 
     let symbols_str = query
         .symbols
@@ -99,8 +98,7 @@ pub async fn get_user_total_assets(
         .user_id()
         .map_err(|e| AppError::bad_request(e.to_string()))?;
 
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     let asset_service = AssetService::new(
         state.pool.clone(),
         price_service,
@@ -126,8 +124,7 @@ pub async fn get_wallet_asset(
     let wallet_uuid = Uuid::parse_str(&wallet_id_str)
         .map_err(|_| convert_error(StatusCode::BAD_REQUEST, "Invalid UUID format".to_string()))?;
 
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     let asset_service = AssetService::new(
         state.pool.clone(),
         price_service,
@@ -156,15 +153,14 @@ pub async fn get_swap_quote(
     State(state): State<Arc<AppState>>,
     Json(request): Json<SwapQuoteRequest>,
 ) -> Result<axum::Json<crate::api::response::ApiResponse<SwapQuote>>, AppError> {
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     // 企业级实现：跨链桥需要计算平台服务费和获取钱包地址
     use crate::repository::wallet_repository::PgWalletRepository;
     let wallet_repo = Arc::new(PgWalletRepository::new(state.pool.clone()));
     let bridge_service = CrossChainBridgeService::new(
         state.pool.clone(),
         price_service,
-        state.cross_chain_config.clone(),
+        state.bridge_aggregator.clone(),
         state.fee_service.clone(), // 传入fee_service用于计算平台服务费
         wallet_repo,               // 传入wallet_repo用于获取钱包地址
     );
@@ -217,7 +213,7 @@ pub async fn execute_cross_chain_swap(
         .map_err(|e| AppError::unauthorized(format!("Invalid token: {}", e)))?;
     request.user_id = user_id;
 
-    if request.source_amount <= 0.0 || !request.source_amount.is_finite() {
+    if request.source_amount <= Decimal::ZERO {
         return Err(AppError::bad_request("Invalid amount".to_string()));
     }
     if request
@@ -249,15 +245,14 @@ pub async fn execute_cross_chain_swap(
         }
     }
 
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     // 企业级实现：跨链桥需要计算平台服务费和获取钱包地址
     use crate::repository::wallet_repository::PgWalletRepository;
     let wallet_repo = Arc::new(PgWalletRepository::new(state.pool.clone()));
     let bridge_service = CrossChainBridgeService::new(
         state.pool.clone(),
         price_service,
-        state.cross_chain_config.clone(),
+        state.bridge_aggregator.clone(),
         state.fee_service.clone(), // 传入fee_service用于计算平台服务费
         wallet_repo,               // 传入wallet_repo用于获取钱包地址
     );
@@ -281,15 +276,14 @@ pub async fn get_swap_status(
     let swap_uuid = Uuid::parse_str(&swap_id_str)
         .map_err(|_| convert_error(StatusCode::BAD_REQUEST, "Invalid swap ID".to_string()))?;
 
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     // 企业级实现：跨链桥需要计算平台服务费和获取钱包地址
     use crate::repository::wallet_repository::PgWalletRepository;
     let wallet_repo = Arc::new(PgWalletRepository::new(state.pool.clone()));
     let bridge_service = CrossChainBridgeService::new(
         state.pool.clone(),
         price_service,
-        state.cross_chain_config.clone(),
+        state.bridge_aggregator.clone(),
         state.fee_service.clone(), // 传入fee_service用于计算平台服务费
         wallet_repo,               // 传入wallet_repo用于获取钱包地址
     );
@@ -1,4 +1,5 @@
 //! 审计日志API
+//! 日志查询、合规报告与审计账本区间校验
 use std::sync::Arc;
 
 use axum::{
@@ -9,10 +10,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    api::response::{convert_error, success_response},
+    api::{
+        middleware::{auth::AuthInfoExtractor, rbac::require_admin},
+        response::{convert_error, success_response},
+    },
     app_state::AppState,
     error::AppError,
-    service::audit_service::AuditService,
+    service::{audit_ledger::LedgerVerification, audit_service::AuditService},
 };
 
 /// GET /api/audit/logs - 获取审计日志
@@ -167,3 +171,33 @@ pub async fn generate_compliance_report(
         data: report.data,
     })
 }
+
+/// GET /api/v1/audit/ledger/verify - 校验审计账本区间（哈希链 + Merkle 检查点）
+#[derive(Debug, Deserialize)]
+pub struct LedgerVerifyQuery {
+    pub from_seq: Option<i64>,
+    /// 为空时校验到链头
+    pub to_seq: Option<i64>,
+}
+
+pub async fn verify_ledger_range(
+    State(state): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Query(query): Query<LedgerVerifyQuery>,
+) -> Result<axum::Json<crate::api::response::ApiResponse<LedgerVerification>>, AppError> {
+    // 账本为全局哈希链，仅管理员可校验
+    require_admin(&auth)?;
+
+    let from_seq = query.from_seq.unwrap_or(1);
+    if query.to_seq.is_some_and(|to| to < from_seq) {
+        return Err(AppError::bad_request("to_seq must be >= from_seq"));
+    }
+
+    let audit_service = AuditService::new(state.pool.clone(), state.immu.clone());
+    let verification = audit_service
+        .verify_ledger_range(from_seq, query.to_seq)
+        .await
+        .map_err(|e| convert_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    success_response(verification)
+}
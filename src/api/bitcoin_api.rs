@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 use utoipa::ToSchema;
 
-use crate::{api::response::success_response, app_state::AppState, error::AppError};
+use crate::{
+    api::response::success_response, app_state::AppState, error::AppError,
+    infrastructure::telemetry::TracePropagation,
+};
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 // Request/Response Models
@@ -74,6 +77,7 @@ pub async fn get_fee_estimates(
     let estimate = match reqwest::Client::new()
         .get(&url)
         .timeout(std::time::Duration::from_secs(5))
+        .with_trace_context()
         .send()
         .await
         .and_then(|resp| resp.error_for_status())
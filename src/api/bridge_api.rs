@@ -18,7 +18,11 @@ use crate::{
     app_state::AppState,
     error::AppError,
     repository::wallet_repository::PgWalletRepository,
-    service::{cross_chain_bridge_service::CrossChainBridgeService, price_service::PriceService},
+    service::{
+        bridge_aggregator::{self, BridgeOption},
+        cross_chain_bridge_service::CrossChainBridgeService,
+        price_oracle::PriceError,
+    },
 };
 
 /// GET /api/bridge/quote - 获取跨链桥报价
@@ -45,6 +49,8 @@ pub struct BridgeQuoteResponse {
     pub total_fee_percentage: String,
     pub estimated_time_minutes: u32,
     pub recommended_protocol: String,
+    /// 所有可用桥的候选方案（按净到账、预计时间与风险评分排序）
+    pub options: Vec<BridgeOption>,
 }
 
 #[utoipa::path(
@@ -69,25 +75,16 @@ pub async fn get_bridge_quote(
     Query(query): Query<BridgeQuoteQuery>,
 ) -> Result<Json<crate::api::response::ApiResponse<BridgeQuoteResponse>>, AppError> {
     // 解析金额
-    let source_amount = query
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount format".to_string()))?;
-
-    if source_amount <= 0.0 {
-        return Err(AppError::bad_request(
-            "Amount must be greater than 0".to_string(),
-        ));
-    }
+    let source_amount = bridge_aggregator::decimal_amount(&query.amount)
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
 
     // 创建服务实例
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     let wallet_repo = Arc::new(PgWalletRepository::new(state.pool.clone()));
     let bridge_service = CrossChainBridgeService::new(
         state.pool.clone(),
         price_service,
-        state.cross_chain_config.clone(),
+        state.bridge_aggregator.clone(),
         state.fee_service.clone(),
         wallet_repo,
     );
@@ -102,7 +99,7 @@ pub async fn get_bridge_quote(
             &query.token, // 假设目标链使用相同代币符号
         )
         .await
-        .map_err(|e| AppError::internal(format!("Failed to get bridge quote: {}", e)))?;
+        .map_err(|e| bridge_error("Failed to get bridge quote", e))?;
 
     success_response(BridgeQuoteResponse {
         source_chain: quote.source_chain,
@@ -114,6 +111,7 @@ pub async fn get_bridge_quote(
         total_fee_percentage: quote.total_fee_percentage.to_string(),
         estimated_time_minutes: quote.estimated_time_minutes,
         recommended_protocol: quote.recommended_protocol,
+        options: quote.options,
     })
 }
 
@@ -130,6 +128,9 @@ pub struct BridgeAssetsRequest {
     pub amount: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_request_id: Option<String>,
+    /// 可选：报价候选中的桥协议（wormhole / layerzero / axelar），默认取排名第一
+    #[serde(default)]
+    pub protocol: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -163,28 +164,19 @@ pub async fn bridge_assets(
     Json(req): Json<BridgeAssetsRequest>,
 ) -> Result<Json<crate::api::response::ApiResponse<BridgeAssetsResponse>>, AppError> {
     // 解析金额
-    let source_amount = req
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount format".to_string()))?;
-
-    if source_amount <= 0.0 {
-        return Err(AppError::bad_request(
-            "Amount must be greater than 0".to_string(),
-        ));
-    }
+    let source_amount = bridge_aggregator::decimal_amount(&req.amount)
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
 
     // 获取用户ID
     let user_id = auth.user_id;
 
     // 创建服务实例
-    let redis_url = std::env::var("REDIS_URL").ok();
-    let price_service = Arc::new(PriceService::new(state.pool.clone(), redis_url));
+    let price_service = state.price_service.clone();
     let wallet_repo = Arc::new(PgWalletRepository::new(state.pool.clone()));
     let bridge_service = CrossChainBridgeService::new(
         state.pool.clone(),
         price_service,
-        state.cross_chain_config.clone(),
+        state.bridge_aggregator.clone(),
         state.fee_service.clone(),
         wallet_repo,
     );
@@ -201,13 +193,14 @@ pub async fn bridge_assets(
         target_chain: req.to_chain.clone(),
         target_token: req.token.clone(), // 假设目标链使用相同代币符号
         target_wallet_id: None,
+        bridge_protocol: req.protocol.clone(),
     };
 
     // 执行跨链兑换
     let response = bridge_service
         .execute_swap(swap_request)
         .await
-        .map_err(|e| AppError::internal(format!("Failed to bridge assets: {}", e)))?;
+        .map_err(|e| bridge_error("Failed to bridge assets", e))?;
 
     success_response(BridgeAssetsResponse {
         swap_id: response.swap_id,
@@ -224,6 +217,15 @@ pub async fn bridge_assets(
     })
 }
 
+/// 价格不可用（预言机无可信报价）映射为 503，其余为内部错误
+fn bridge_error(context: &str, e: anyhow::Error) -> AppError {
+    if e.downcast_ref::<PriceError>().is_some() {
+        AppError::service_unavailable(format!("{}: {}", context, e))
+    } else {
+        AppError::internal(format!("{}: {}", context, e))
+    }
+}
+
 /// GET /api/bridge/:bridge_id - 查询跨链桥状态✅
 pub async fn get_bridge_status(
     State(state): State<Arc<AppState>>,
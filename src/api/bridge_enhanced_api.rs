@@ -20,7 +20,9 @@ use crate::{
         response::{success_response, ApiResponse},
     },
     app_state::AppState,
+    domain::token_amount::TokenAmount,
     error::AppError,
+    service::token_service::TokenService,
 };
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -201,23 +203,15 @@ pub async fn get_bridge_quote(
         .get("amount")
         .ok_or_else(|| AppError::bad_request("Missing amount".to_string()))?;
 
-    // 使用统一费率服务计算跨链桥费用
-    let fee_service = crate::service::unified_fee_config_service::UnifiedFeeConfigService::new(
-        state.pool.clone(),
-    );
-
-    let amount_f64 = amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
-
-    let fee_calc = fee_service
-        .calculate_fee(
-            crate::service::unified_fee_config_service::FeeType::BridgeFee,
-            amount_f64,
-            Some(source_chain),
-        )
-        .await
-        .map_err(|e| AppError::internal_error(format!("Fee calculation failed: {}", e)))?;
+    let (amount_units, fee) =
+        bridge_platform_fee(&state, source_chain, token_symbol, amount).await?;
+    let bridge_fee_usd = fee_in_usd(&state, token_symbol, &fee)
+        .await?
+        .to_f64()
+        .unwrap_or(0.0);
+    let receive_amount = amount_units
+        .checked_sub(&fee)
+        .map_err(|e| AppError::bad_request(format!("Amount does not cover bridge fee: {}", e)))?;
 
     // 估算Gas费用（源链和目标链）
     let source_gas_fee = 5.0; // TODO: 实际估算
@@ -228,18 +222,62 @@ pub async fn get_bridge_quote(
         destination_chain: destination_chain.clone(),
         token_symbol: token_symbol.clone(),
         amount: amount.clone(),
-        estimated_receive_amount: format!("{:.6}", amount_f64 - fee_calc.fee_usd),
+        estimated_receive_amount: receive_amount.to_human_string(),
         fee_breakdown: BridgeFeeInfo {
-            bridge_fee_usd: fee_calc.fee_usd,
+            bridge_fee_usd,
             source_gas_fee_usd: source_gas_fee,
             destination_gas_fee_usd: dest_gas_fee,
-            total_fee_usd: fee_calc.fee_usd + source_gas_fee + dest_gas_fee,
+            total_fee_usd: bridge_fee_usd + source_gas_fee + dest_gas_fee,
         },
         estimated_time_minutes: 15,
         recommended_provider: "LayerZero".to_string(),
     })
 }
 
+/// 跨链桥平台服务费：按源链代币精度解析金额，费用在最小单位上精确计算（无规则时为 0）
+async fn bridge_platform_fee(
+    state: &AppState,
+    source_chain: &str,
+    token_symbol: &str,
+    amount: &str,
+) -> Result<(TokenAmount, TokenAmount), AppError> {
+    let chain_config = crate::utils::chain_normalizer::get_chain_config(source_chain)
+        .map_err(|e| AppError::chain_not_supported(e.to_string()))?;
+    let amount = TokenService::new(state.pool.clone())
+        .parse_amount(token_symbol, chain_config.chain_id as u64, amount)
+        .await
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
+
+    let fee = state
+        .fee_service
+        .calculate_fee_for_amount(&source_chain.to_lowercase(), "bridge", &amount)
+        .await
+        .map_err(|e| AppError::internal_error(format!("Fee calculation failed: {}", e)))?
+        .and_then(|calc| calc.platform_fee_exact)
+        .unwrap_or_else(|| TokenAmount::zero(amount.decimals()));
+    Ok((amount, fee))
+}
+
+/// 代币费用按预言机价格折算为 USD
+async fn fee_in_usd(
+    state: &AppState,
+    token_symbol: &str,
+    fee: &TokenAmount,
+) -> Result<Decimal, AppError> {
+    if fee.is_zero() {
+        return Ok(Decimal::ZERO);
+    }
+    let fee = fee
+        .to_decimal()
+        .map_err(|e| AppError::internal_error(format!("Invalid fee amount: {}", e)))?;
+    let price = state
+        .price_service
+        .get_price_decimal(token_symbol)
+        .await
+        .map_err(|e| AppError::service_unavailable(format!("Price unavailable: {}", e)))?;
+    Ok(fee * price)
+}
+
 /// POST /api/v1/bridge/execute
 /// 执行跨链转账（非托管模式：接受客户端签名的交易）
 pub async fn execute_bridge(
@@ -319,30 +357,13 @@ pub async fn execute_bridge(
         validate_signed_hex(req.signed_source_tx.as_deref().unwrap().trim())?;
     }
 
-    // 3. 计算费用
-    // DB 里 amount/fee_paid 是 DECIMAL；费用计算使用 f64
-    let amount_decimal: Decimal = req
-        .amount
-        .parse()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
-
-    let amount_f64 = amount_decimal
-        .to_f64()
-        .ok_or_else(|| AppError::bad_request("Invalid amount".to_string()))?;
-
-    let fee_service = crate::service::unified_fee_config_service::UnifiedFeeConfigService::new(
-        state.pool.clone(),
-    );
-    let fee_calc = fee_service
-        .calculate_fee(
-            crate::service::unified_fee_config_service::FeeType::BridgeFee,
-            amount_f64,
-            Some(&req.source_chain),
-        )
-        .await
-        .map_err(|e| AppError::internal_error(format!("Fee calculation failed: {}", e)))?;
-
-    let fee_paid_decimal = Decimal::from_f64_retain(fee_calc.fee_usd).unwrap_or(Decimal::ZERO);
+    // 3. 计算费用（按代币精度精确计算，fee_paid 以 USD 记录）
+    let (amount_units, fee) =
+        bridge_platform_fee(&state, &req.source_chain, &req.token_symbol, &req.amount).await?;
+    let amount_decimal = amount_units
+        .to_decimal()
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
+    let fee_paid_decimal = fee_in_usd(&state, &req.token_symbol, &fee).await?;
 
     // 4. 创建跨链交易记录
     let _ = sqlx::query(
@@ -566,10 +587,10 @@ pub async fn execute_bridge(
         amount: req.amount,
         estimated_arrival_time: "15-30 minutes".to_string(),
         fee_info: BridgeFeeInfo {
-            bridge_fee_usd: fee_calc.fee_usd,
+            bridge_fee_usd: fee_paid_decimal.to_f64().unwrap_or(0.0),
             source_gas_fee_usd: 5.0,
             destination_gas_fee_usd: 3.0,
-            total_fee_usd: fee_calc.fee_usd + 8.0,
+            total_fee_usd: fee_paid_decimal.to_f64().unwrap_or(0.0) + 8.0,
         },
     })
 }
@@ -19,6 +19,7 @@ use crate::{
     api::response::{success_response, ApiResponse},
     app_state::AppState,
     error::AppError,
+    infrastructure::telemetry::TracePropagation,
     utils::chain_normalizer,
 };
 
@@ -269,6 +270,7 @@ async fn eth_call(state: &AppState, chain: &str, to: &str, data: &str) -> Result
     let response = client
         .post(&endpoint.url)
         .json(&request_body)
+        .with_trace_context()
         .send()
         .await
         .map_err(|e| AppError::internal_error(format!("RPC eth_call failed: {e}")))?;
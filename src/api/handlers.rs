@@ -13,9 +13,12 @@ use uuid::Uuid;
 
 use crate::api::response::success_response; // 企业级标准：统一响应格式
 use crate::{
-    api::middleware::auth::AuthInfoExtractor, app_state::AppState, error::AppError,
-    infrastructure::upstream::UpstreamClient, service,
-    service::blockchain_client::BroadcastTransactionRequest,
+    api::middleware::auth::AuthInfoExtractor,
+    app_state::AppState,
+    error::AppError,
+    infrastructure::upstream::UpstreamClient,
+    service,
+    service::{blockchain_client::BroadcastTransactionRequest, token_service::TokenService},
 };
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -552,12 +555,17 @@ fn dec_wei_to_hex_quantity(dec: &str) -> Option<String> {
 pub struct FeeCalculationRequest {
     pub chain: String,
     pub operation: String,
-    pub amount: f64,
+    /// 金额（十进制字符串，按代币精度精确换算）
+    pub amount: String,
+    /// 计费代币符号，默认为链原生币
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct FeeCalculationData {
-    pub platform_fee: f64,
+    /// 平台服务费（代币单位，十进制字符串）
+    pub platform_fee: String,
     pub collector_address: String,
     pub applied_rule_id: uuid::Uuid,
     pub rule_version: i32,
@@ -583,16 +591,20 @@ pub async fn calculate_platform_fee(
         return Err(AppError::bad_request("chain and operation are required"));
     }
 
-    if !req.amount.is_finite() || req.amount < 0.0 {
-        return Err(AppError::bad_request("amount must be non-negative"));
-    }
-
     let chain_key = req.chain.to_lowercase();
     let operation_key = req.operation.to_lowercase();
 
+    let chain_config = crate::utils::chain_normalizer::get_chain_config(&chain_key)
+        .map_err(|e| AppError::chain_not_supported(e.to_string()))?;
+    let token = req.token.as_deref().unwrap_or(chain_config.symbol);
+    let amount = TokenService::new(st.pool.clone())
+        .parse_amount(token, chain_config.chain_id as u64, &req.amount)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+
     let calc = st
         .fee_service
-        .calculate_fee(&chain_key, &operation_key, req.amount)
+        .calculate_fee_for_amount(&chain_key, &operation_key, &amount)
         .await
         .map_err(|err| {
             tracing::error!(error = %err, chain = %chain_key, operation = %operation_key, "fee_calculation_failed");
@@ -603,8 +615,12 @@ pub async fn calculate_platform_fee(
         .ok_or_else(|| AppError::bad_request("No active fee rule for provided chain/operation"))?;
 
     use crate::api::response::success_response;
+    let platform_fee = data
+        .platform_fee_exact
+        .map(|fee| fee.to_human_string())
+        .unwrap_or_else(|| data.platform_fee.to_string());
     success_response(FeeCalculationData {
-        platform_fee: data.platform_fee,
+        platform_fee,
         collector_address: data.collector_address,
         applied_rule_id: data.applied_rule_id,
         rule_version: data.rule_version,
@@ -1090,7 +1106,7 @@ pub async fn broadcast_raw_transaction(
     .bind(&resp.tx_hash)
     .bind("send")
     .bind("submitted")
-    .bind(from_address)
+    .bind(&from_address)
     .bind(to_address)
     .bind(amount_decimal)
     .bind(token_symbol)
@@ -1102,6 +1118,22 @@ pub async fn broadcast_raw_transaction(
         tracing::warn!(error=?e, tx_hash=%resp.tx_hash, chain=%req.chain, user_id=%auth.user_id, "Failed to persist broadcasted transaction; continuing");
     }
 
+    // 构建交易时只读取 pending nonce，广播成功后才在 NonceManager 中占用
+    if let (Some(nonce), Ok(rpc_chain)) = (
+        nonce_i64,
+        crate::utils::chain_normalizer::normalize_chain_identifier(&req.chain),
+    ) {
+        if from_address != "unknown" {
+            if let Err(e) = st
+                .nonce_manager
+                .reserve_broadcast_nonce(&rpc_chain, &from_address, nonce as u64)
+                .await
+            {
+                tracing::warn!(error=?e, tx_hash=%resp.tx_hash, chain=%req.chain, "Failed to reserve broadcast nonce; continuing");
+            }
+        }
+    }
+
     use crate::api::response::success_response;
     success_response(BroadcastRawTxData {
         tx_hash: resp.tx_hash,
@@ -1109,6 +1141,40 @@ pub async fn broadcast_raw_transaction(
     })
 }
 
+/// POST /api/v1/transactions/build - 构建待签名交易（nonce / 费用 / Gas Limit 由服务端补全）
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/build",
+    request_body = crate::service::transaction_builder::BuildTransactionRequest,
+    responses(
+        (status = 200, description = "Unsigned transaction", body = crate::api::response::ApiResponse<crate::service::transaction_builder::BuildTransactionResponse>),
+        (status = 400, description = "Bad request or simulation reverted", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn build_transaction(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Json(req): Json<crate::service::transaction_builder::BuildTransactionRequest>,
+) -> Result<
+    Json<
+        crate::api::response::ApiResponse<
+            crate::service::transaction_builder::BuildTransactionResponse,
+        >,
+    >,
+    AppError,
+> {
+    crate::metrics::count_ok("POST /api/tx/build");
+
+    let chain = req.chain.clone();
+    let built = st.tx_builder.build_transaction(req).await.map_err(|e| {
+        tracing::warn!(error=?e, chain=%chain, user_id=%auth.user_id, "Failed to build transaction");
+        AppError::bad_request(format!("Build failed: {}", e))
+    })?;
+
+    use crate::api::response::success_response;
+    success_response(built)
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct TxStatusQuery {
     pub chain: Option<String>,
@@ -1241,6 +1307,9 @@ pub struct CreateTxReq {
     pub chain_id: i64,
     pub to_addr: String,
     pub amount_wei: String,
+    /// 合约调用数据（十六进制，可选；EVM 链签名前模拟使用并写入 metadata.data）
+    #[serde(default)]
+    pub data: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
 
@@ -1248,6 +1317,9 @@ pub struct CreateTxReq {
 pub struct TxResp {
     pub id: Uuid,
     pub status: String,
+    /// EVM 签名前模拟结果（Gas Limit / 余额变化）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<crate::service::tx_simulator::SimulationResult>,
 }
 
 #[utoipa::path(
@@ -1256,7 +1328,7 @@ pub struct TxResp {
     request_body = CreateTxReq,
     responses(
         (status = 200, description = "Tx created", body = TxResp),
-        (status = 400, description = "Bad request", body = crate::error_body::ErrorBodyDoc),
+        (status = 400, description = "Bad request or simulation reverted", body = crate::error_body::ErrorBodyDoc),
         (status = 429, description = "Rate limited", body = crate::error_body::ErrorBodyDoc)
     )
 )]
@@ -1271,6 +1343,18 @@ pub async fn create_tx(
         .parse::<rust_decimal::Decimal>()
         .map_err(|e| AppError::bad_request(format!("invalid amount: {}", e)))?;
 
+    // 签名前模拟（仅 EVM 链）：会回滚的交易不创建
+    let simulation = simulate_tx_request(&st, &req).await?;
+
+    let metadata = match (req.data.clone(), req.metadata) {
+        (Some(data), Some(serde_json::Value::Object(mut map))) => {
+            map.insert("data".into(), serde_json::Value::String(data));
+            Some(serde_json::Value::Object(map))
+        }
+        (Some(data), None) => Some(serde_json::json!({ "data": data })),
+        (_, metadata) => metadata,
+    };
+
     let r = service::tx::create_tx_request(
         &st.pool,
         req.tenant_id,
@@ -1279,7 +1363,7 @@ pub async fn create_tx(
         req.to_addr.clone(),
         Decimal::from_str_exact(&amount.to_string())
             .map_err(|e| AppError::bad_request(format!("amount parse error: {}", e)))?,
-        req.metadata,
+        metadata,
     )
     .await
     .map_err(|e| AppError::bad_request(e.to_string()))?;
@@ -1297,6 +1381,7 @@ pub async fn create_tx(
 
     // 使用审计日志辅助函数（异步，不等待结果）
     crate::utils::write_audit_event_async(
+        st.pool.clone(),
         st.immu.clone(),
         "tx.create".into(),
         req.tenant_id,
@@ -1308,9 +1393,59 @@ pub async fn create_tx(
     success_response(TxResp {
         id: r.id,
         status: r.status,
+        simulation,
     })
 }
 
+/// 以钱包地址为 from 模拟交易请求；非 EVM 链跳过，回滚时返回 TransactionFailed
+async fn simulate_tx_request(
+    st: &AppState,
+    req: &CreateTxReq,
+) -> Result<Option<crate::service::tx_simulator::SimulationResult>, AppError> {
+    use crate::utils::chain_normalizer;
+
+    let chain = match chain_normalizer::normalize_chain_identifier(&req.chain_id.to_string()) {
+        Ok(chain) if chain_normalizer::is_evm_chain(&chain) => chain,
+        _ => return Ok(None),
+    };
+
+    let wallet = service::wallets::get_wallet_by_id(&st.pool, req.wallet_id)
+        .await
+        .map_err(|e| AppError::internal(format!("获取钱包失败: {}", e)))?
+        .ok_or_else(|| AppError::wallet_not_found(format!("钱包 {} 不存在", req.wallet_id)))?;
+
+    let call = crate::service::tx_simulator::SimulationCall {
+        from: wallet.address.parse().map_err(|_| {
+            AppError::invalid_address(format!("invalid wallet address: {}", wallet.address))
+        })?,
+        to: req
+            .to_addr
+            .parse()
+            .map_err(|_| AppError::invalid_address(format!("invalid to_addr: {}", req.to_addr)))?,
+        value: ethers::types::U256::from_dec_str(req.amount_wei.trim())
+            .map_err(|e| AppError::invalid_amount(format!("invalid amount_wei: {}", e)))?,
+        data: match req.data.as_deref().map(|d| d.trim_start_matches("0x")) {
+            Some(d) => hex::decode(d)
+                .map_err(|e| AppError::bad_request(format!("invalid data hex: {}", e)))?
+                .into(),
+            None => Default::default(),
+        },
+    };
+
+    let simulation = st
+        .tx_simulator
+        .preflight(&chain, &call)
+        .await
+        .map_err(|e| AppError::service_unavailable(format!("交易模拟失败: {}", e)))?;
+    if let Some(reason) = simulation.as_ref().and_then(|s| s.revert_reason.as_ref()) {
+        return Err(AppError::transaction_failed(format!(
+            "交易模拟回滚: {}",
+            reason
+        )));
+    }
+    Ok(simulation)
+}
+
 // -------- 查询端点 --------
 
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
@@ -1486,6 +1621,7 @@ pub async fn delete_wallet(
 
     // 使用审计日志辅助函数（异步，不等待结果）
     crate::utils::write_audit_event_async_str_actor(
+        st.pool.clone(),
         st.immu.clone(),
         "wallet.delete".into(),
         tenant_id,
@@ -1544,6 +1680,7 @@ pub async fn list_tx(
         .map(|t| TxResp {
             id: t.id,
             status: t.status,
+            simulation: None,
         })
         .collect();
 
@@ -1665,6 +1802,7 @@ pub async fn update_tx_status(
 
     // 使用审计日志辅助函数（异步，不等待结果）
     crate::utils::write_audit_event_async_str_actor(
+        st.pool.clone(),
         st.immu.clone(),
         "tx.status.update".into(),
         req.tenant_id,
@@ -2083,9 +2221,24 @@ pub struct PolicyResp {
     pub name: String,
     pub rules: serde_json::Value,
     pub version: i32,
+    pub status: String,
     pub created_at: String,
 }
 
+impl From<crate::repository::policies::Policy> for PolicyResp {
+    fn from(p: crate::repository::policies::Policy) -> Self {
+        Self {
+            id: p.id,
+            tenant_id: p.tenant_id,
+            name: p.name,
+            rules: p.rules,
+            version: p.version,
+            status: p.status,
+            created_at: p.created_at.to_rfc3339(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
 pub struct ListPoliciesQuery {
     pub tenant_id: Uuid,
@@ -2120,14 +2273,7 @@ pub async fn create_policy(
             .await
             .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
-    success_response(PolicyResp {
-        id: policy.id,
-        tenant_id: policy.tenant_id,
-        name: policy.name,
-        rules: policy.rules,
-        version: policy.version,
-        created_at: policy.created_at.to_rfc3339(),
-    })
+    success_response(PolicyResp::from(policy))
 }
 
 #[utoipa::path(
@@ -2157,17 +2303,7 @@ pub async fn list_policies(
         .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
     success_response(ListPoliciesResp {
-        policies: policies
-            .into_iter()
-            .map(|p| PolicyResp {
-                id: p.id,
-                tenant_id: p.tenant_id,
-                name: p.name,
-                rules: p.rules,
-                version: p.version,
-                created_at: p.created_at.to_rfc3339(),
-            })
-            .collect(),
+        policies: policies.into_iter().map(PolicyResp::from).collect(),
         total,
     })
 }
@@ -2191,14 +2327,7 @@ pub async fn get_policy(
         .map_err(|e| AppError::bad_request(e.to_string()))?
         .ok_or_else(|| AppError::not_found("Policy not found"))?;
     // 企业级标准：使用统一响应格式
-    success_response(PolicyResp {
-        id: policy.id,
-        tenant_id: policy.tenant_id,
-        name: policy.name,
-        rules: policy.rules,
-        version: policy.version,
-        created_at: policy.created_at.to_rfc3339(),
-    })
+    success_response(PolicyResp::from(policy))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -2237,14 +2366,7 @@ pub async fn update_policy(
     .map_err(|e| AppError::bad_request(e.to_string()))?
     .ok_or_else(|| AppError::not_found("Policy not found"))?;
     // 企业级标准：使用统一响应格式
-    success_response(PolicyResp {
-        id: policy.id,
-        tenant_id: policy.tenant_id,
-        name: policy.name,
-        rules: policy.rules,
-        version: policy.version,
-        created_at: policy.created_at.to_rfc3339(),
-    })
+    success_response(PolicyResp::from(policy))
 }
 
 #[utoipa::path(
@@ -2275,6 +2397,170 @@ pub async fn delete_policy(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PolicyStatusReq {
+    pub tenant_id: Uuid,
+    /// 启用时可指定历史版本（回滚）；为空则启用当前版本
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PolicyVersionResp {
+    pub version: i32,
+    pub rules: serde_json::Value,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PolicyDryRunReq {
+    pub tenant_id: Uuid,
+    /// 待测试的规则；为空时使用该策略当前版本
+    pub rules: Option<serde_json::Value>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// 回放的历史提现数量（默认 500，最大 5000）
+    pub limit: Option<i64>,
+}
+
+fn require_policy_tenant(
+    auth: &crate::api::middleware::auth::AuthInfo,
+    tenant_id: Uuid,
+) -> Result<(), AppError> {
+    crate::api::middleware::rbac::require_operator_or_admin(auth)?;
+    if auth.tenant_id != tenant_id {
+        return Err(AppError::forbidden("Policy belongs to another tenant"));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/policies/{id}/activate",
+    params(("id" = Uuid, Path, description = "Policy ID")),
+    request_body = PolicyStatusReq,
+    responses(
+        (status = 200, description = "Policy activated", body = PolicyResp),
+        (status = 400, description = "Policy rules invalid", body = crate::error_body::ErrorBodyDoc),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn activate_policy(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+    Json(req): Json<PolicyStatusReq>,
+) -> Result<Json<crate::api::response::ApiResponse<PolicyResp>>, AppError> {
+    crate::metrics::count_ok("POST /api/v1/policies/:id/activate");
+    require_policy_tenant(&auth, req.tenant_id)?;
+    let policy = service::policies::activate_policy(&st.pool, id, req.tenant_id, req.version)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .ok_or_else(|| AppError::not_found("Policy not found"))?;
+    success_response(PolicyResp::from(policy))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/policies/{id}/deactivate",
+    params(("id" = Uuid, Path, description = "Policy ID")),
+    request_body = PolicyStatusReq,
+    responses(
+        (status = 200, description = "Policy deactivated", body = PolicyResp),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn deactivate_policy(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+    Json(req): Json<PolicyStatusReq>,
+) -> Result<Json<crate::api::response::ApiResponse<PolicyResp>>, AppError> {
+    crate::metrics::count_ok("POST /api/v1/policies/:id/deactivate");
+    require_policy_tenant(&auth, req.tenant_id)?;
+    let policy = service::policies::deactivate_policy(&st.pool, id, req.tenant_id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .ok_or_else(|| AppError::not_found("Policy not found"))?;
+    success_response(PolicyResp::from(policy))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/policies/{id}/versions",
+    params(("id" = Uuid, Path, description = "Policy ID")),
+    responses(
+        (status = 200, description = "Policy versions", body = Vec<PolicyVersionResp>),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn list_policy_versions(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<PolicyVersionResp>>>, AppError> {
+    crate::metrics::count_ok("GET /api/v1/policies/:id/versions");
+    let policy = service::policies::get_policy_by_id(&st.pool, id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .filter(|p| p.tenant_id == auth.tenant_id)
+        .ok_or_else(|| AppError::not_found("Policy not found"))?;
+    let versions = service::policies::list_policy_versions(&st.pool, policy.id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+    success_response(
+        versions
+            .into_iter()
+            .map(|v| PolicyVersionResp {
+                version: v.version,
+                rules: v.rules,
+                created_at: v.created_at.to_rfc3339(),
+            })
+            .collect(),
+    )
+}
+
+/// 策略试运行：在租户历史提现上评估规则，返回与当时决策的差异（不影响线上）
+#[utoipa::path(
+    post,
+    path = "/api/v1/policies/{id}/dry-run",
+    params(("id" = Uuid, Path, description = "Policy ID")),
+    request_body = PolicyDryRunReq,
+    responses(
+        (status = 200, description = "Dry-run report", body = crate::service::withdrawal_risk_control::PolicyDryRunReport),
+        (status = 400, description = "Policy rules invalid", body = crate::error_body::ErrorBodyDoc),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn dry_run_policy(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+    Json(req): Json<PolicyDryRunReq>,
+) -> Result<
+    Json<
+        crate::api::response::ApiResponse<
+            crate::service::withdrawal_risk_control::PolicyDryRunReport,
+        >,
+    >,
+    AppError,
+> {
+    crate::metrics::count_ok("POST /api/v1/policies/:id/dry-run");
+    require_policy_tenant(&auth, req.tenant_id)?;
+    let policy = service::policies::get_policy_by_id(&st.pool, id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .filter(|p| p.tenant_id == req.tenant_id)
+        .ok_or_else(|| AppError::not_found("Policy not found"))?;
+
+    let rules = req.rules.unwrap_or(policy.rules);
+    let limit = req.limit.unwrap_or(500).clamp(1, 5000);
+    let report = service::withdrawal_risk_control::WithdrawalRiskControl::new(st.pool.clone())
+        .dry_run_policy(req.tenant_id, &rules, req.from, req.to, limit)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+    success_response(report)
+}
+
 // ========== Approvals API ==========
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -2290,13 +2576,40 @@ pub struct CreateApprovalReq {
 pub struct ApprovalResp {
     pub id: Uuid,
     pub tenant_id: Uuid,
-    pub policy_id: Uuid,
+    pub policy_id: Option<Uuid>,
     pub requester: Uuid,
     pub status: String,
     pub payload: serde_json::Value,
+    /// 被挂起的操作类型（如 withdrawal）
+    pub resource_type: Option<String>,
+    pub resource_id: Option<Uuid>,
+    pub required_approvals: i32,
+    pub approver_roles: Vec<String>,
+    pub expires_at: Option<String>,
+    pub decided_at: Option<String>,
     pub created_at: String,
 }
 
+impl From<crate::repository::approvals::Approval> for ApprovalResp {
+    fn from(a: crate::repository::approvals::Approval) -> Self {
+        Self {
+            id: a.id,
+            tenant_id: a.tenant_id,
+            policy_id: a.policy_id,
+            requester: a.requester,
+            status: a.status,
+            payload: a.payload,
+            resource_type: a.resource_type,
+            resource_id: a.resource_id,
+            required_approvals: a.required_approvals,
+            approver_roles: a.approver_roles,
+            expires_at: a.expires_at.map(|t| t.to_rfc3339()),
+            decided_at: a.decided_at.map(|t| t.to_rfc3339()),
+            created_at: a.created_at.to_rfc3339(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
 pub struct ListApprovalsQuery {
     pub tenant_id: Uuid,
@@ -2339,15 +2652,7 @@ pub async fn create_approval(
     .await
     .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
-    success_response(ApprovalResp {
-        id: approval.id,
-        tenant_id: approval.tenant_id,
-        policy_id: approval.policy_id,
-        requester: approval.requester,
-        status: approval.status,
-        payload: approval.payload,
-        created_at: approval.created_at.to_rfc3339(),
-    })
+    success_response(ApprovalResp::from(approval))
 }
 
 #[utoipa::path(
@@ -2379,18 +2684,7 @@ pub async fn list_approvals(
         .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
     success_response(ListApprovalsResp {
-        approvals: approvals
-            .into_iter()
-            .map(|a| ApprovalResp {
-                id: a.id,
-                tenant_id: a.tenant_id,
-                policy_id: a.policy_id,
-                requester: a.requester,
-                status: a.status,
-                payload: a.payload,
-                created_at: a.created_at.to_rfc3339(),
-            })
-            .collect(),
+        approvals: approvals.into_iter().map(ApprovalResp::from).collect(),
         total,
     })
 }
@@ -2414,56 +2708,148 @@ pub async fn get_approval(
         .map_err(|e| AppError::bad_request(e.to_string()))?
         .ok_or_else(|| AppError::not_found("Approval not found"))?;
     // 企业级标准：使用统一响应格式
-    success_response(ApprovalResp {
-        id: approval.id,
-        tenant_id: approval.tenant_id,
-        policy_id: approval.policy_id,
-        requester: approval.requester,
-        status: approval.status,
-        payload: approval.payload,
-        created_at: approval.created_at.to_rfc3339(),
-    })
+    success_response(ApprovalResp::from(approval))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateApprovalStatusReq {
     pub tenant_id: Uuid,
+    /// approved / rejected（等价于当前用户投一票）
     pub status: String,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CastApprovalVoteReq {
+    /// approve / reject
+    pub decision: String,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApprovalVoteResp {
+    pub id: Uuid,
+    pub approval_id: Uuid,
+    pub voter_id: Uuid,
+    pub voter_role: String,
+    pub decision: String,
+    pub comment: Option<String>,
+    pub created_at: String,
+}
+
+impl From<crate::repository::approvals::ApprovalVote> for ApprovalVoteResp {
+    fn from(v: crate::repository::approvals::ApprovalVote) -> Self {
+        Self {
+            id: v.id,
+            approval_id: v.approval_id,
+            voter_id: v.voter_id,
+            voter_role: v.voter_role,
+            decision: v.decision,
+            comment: v.comment,
+            created_at: v.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CastApprovalVoteResp {
+    pub approval: ApprovalResp,
+    pub vote: ApprovalVoteResp,
+}
+
+fn approval_voter(auth: &crate::api::middleware::auth::AuthInfo) -> service::approvals::Voter {
+    service::approvals::Voter {
+        user_id: auth.user_id,
+        tenant_id: auth.tenant_id,
+        role: auth.role.clone(),
+    }
+}
+
+/// 兼容旧接口：状态不再可直接修改，approved / rejected 按当前用户投票处理
 #[utoipa::path(
     put,
     path = "/api/v1/approvals/{id}/status",
     params(("id" = Uuid, Path, description = "Approval ID")),
     request_body = UpdateApprovalStatusReq,
     responses(
-        (status = 200, description = "Approval status updated", body = ApprovalResp),
-        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+        (status = 200, description = "Vote recorded", body = ApprovalResp),
+        (status = 403, description = "Not an eligible approver", body = crate::error_body::ErrorBodyDoc),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc),
+        (status = 409, description = "Already decided, expired or already voted", body = crate::error_body::ErrorBodyDoc)
     )
 )]
 pub async fn update_approval_status(
     State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
     axum::extract::Path(id): axum::extract::Path<Uuid>,
     Json(req): Json<UpdateApprovalStatusReq>,
 ) -> Result<Json<crate::api::response::ApiResponse<ApprovalResp>>, AppError> {
     crate::metrics::count_ok("PUT /api/v1/approvals/:id/status");
-    let approval =
-        service::approvals::update_approval_status(&st.pool, id, req.tenant_id, req.status)
-            .await
-            .map_err(|e| AppError::bad_request(e.to_string()))?
-            .ok_or_else(|| AppError::not_found("Approval not found"))?;
+    if req.tenant_id != auth.tenant_id {
+        return Err(AppError::not_found("Approval not found"));
+    }
+    let decision = service::approvals::VoteDecision::parse(&req.status)?;
+    let (approval, _) =
+        service::approvals::cast_vote(&st.pool, id, &approval_voter(&auth), decision, None).await?;
     // 企业级标准：使用统一响应格式
-    success_response(ApprovalResp {
-        id: approval.id,
-        tenant_id: approval.tenant_id,
-        policy_id: approval.policy_id,
-        requester: approval.requester,
-        status: approval.status,
-        payload: approval.payload,
-        created_at: approval.created_at.to_rfc3339(),
+    success_response(ApprovalResp::from(approval))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/approvals/{id}/votes",
+    params(("id" = Uuid, Path, description = "Approval ID")),
+    request_body = CastApprovalVoteReq,
+    responses(
+        (status = 200, description = "Vote recorded", body = CastApprovalVoteResp),
+        (status = 403, description = "Not an eligible approver", body = crate::error_body::ErrorBodyDoc),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc),
+        (status = 409, description = "Already decided, expired or already voted", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn cast_approval_vote(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+    Json(req): Json<CastApprovalVoteReq>,
+) -> Result<Json<crate::api::response::ApiResponse<CastApprovalVoteResp>>, AppError> {
+    crate::metrics::count_ok("POST /api/v1/approvals/:id/votes");
+    let decision = service::approvals::VoteDecision::parse(&req.decision)?;
+    let (approval, vote) =
+        service::approvals::cast_vote(&st.pool, id, &approval_voter(&auth), decision, req.comment)
+            .await?;
+    success_response(CastApprovalVoteResp {
+        approval: ApprovalResp::from(approval),
+        vote: ApprovalVoteResp::from(vote),
     })
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/approvals/{id}/votes",
+    params(("id" = Uuid, Path, description = "Approval ID")),
+    responses(
+        (status = 200, description = "Approval votes", body = [ApprovalVoteResp]),
+        (status = 404, description = "Not found", body = crate::error_body::ErrorBodyDoc)
+    )
+)]
+pub async fn list_approval_votes(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    axum::extract::Path(id): axum::extract::Path<Uuid>,
+) -> Result<Json<crate::api::response::ApiResponse<Vec<ApprovalVoteResp>>>, AppError> {
+    crate::metrics::count_ok("GET /api/v1/approvals/:id/votes");
+    let approval = service::approvals::get_approval_by_id(&st.pool, id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .filter(|a| a.tenant_id == auth.tenant_id)
+        .ok_or_else(|| AppError::not_found("Approval not found"))?;
+    let votes = service::approvals::list_approval_votes(&st.pool, approval.id)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
+    success_response(votes.into_iter().map(ApprovalVoteResp::from).collect())
+}
+
 #[utoipa::path(
     delete,
     path = "/api/v1/approvals/{id}",
@@ -2487,7 +2873,10 @@ pub async fn delete_approval(
         .await
         .map_err(|e| AppError::bad_request(e.to_string()))?;
     if !deleted {
-        return Err(AppError::not_found("Approval not found"));
+        // 仍在挂起操作的审批不可删除，只能投票或等待过期
+        return Err(AppError::not_found(
+            "Approval not found or still gating a pending operation",
+        ));
     }
     Ok(StatusCode::NO_CONTENT)
 }
@@ -2496,10 +2885,14 @@ pub async fn delete_approval(
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateApiKeyReq {
-    pub tenant_id: Uuid,
     pub name: String,
-    pub key_hash: String,
-    pub status: String,
+    /// 授权范围，如 `wallets:read`、`tx:broadcast`、`admin:*`
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 独立限流额度（每分钟），为空时使用全局配置
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<i64>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -2510,6 +2903,37 @@ pub struct ApiKeyResp {
     pub key_hash: String,
     pub status: String,
     pub created_at: String,
+    pub key_prefix: Option<String>,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<String>,
+    pub rate_limit_per_minute: Option<i64>,
+    pub last_used_at: Option<String>,
+}
+
+impl From<crate::repository::api_keys::ApiKey> for ApiKeyResp {
+    fn from(k: crate::repository::api_keys::ApiKey) -> Self {
+        Self {
+            id: k.id,
+            tenant_id: k.tenant_id,
+            name: k.name,
+            key_hash: k.key_hash,
+            status: k.status,
+            created_at: k.created_at.to_rfc3339(),
+            key_prefix: k.key_prefix,
+            scopes: k.scopes,
+            expires_at: k.expires_at.map(|t| t.to_rfc3339()),
+            rate_limit_per_minute: k.rate_limit_per_minute,
+            last_used_at: k.last_used_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiKeyResp {
+    #[serde(flatten)]
+    pub key: ApiKeyResp,
+    /// 原始 API Key，仅在创建时返回一次
+    pub api_key: String,
 }
 
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
@@ -2534,32 +2958,32 @@ pub struct ListApiKeysResp {
     path = "/api/v1/api-keys",
     request_body = CreateApiKeyReq,
     responses(
-        (status = 200, description = "API key created", body = ApiKeyResp),
+        (status = 200, description = "API key created", body = CreateApiKeyResp),
         (status = 400, description = "Bad request", body = crate::error_body::ErrorBodyDoc)
     )
 )]
 pub async fn create_api_key(
     State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
     Json(req): Json<CreateApiKeyReq>,
-) -> Result<Json<crate::api::response::ApiResponse<ApiKeyResp>>, AppError> {
+) -> Result<Json<crate::api::response::ApiResponse<CreateApiKeyResp>>, AppError> {
     crate::metrics::count_ok("POST /api/v1/api-keys");
-    let api_key = service::api_keys::create_api_key(
+    // ✅ Key 由服务端生成，只存储哈希；归属当前用户与租户
+    let (api_key, raw_key) = service::api_keys::create_api_key(
         &st.pool,
-        req.tenant_id,
+        auth.tenant_id,
+        auth.user_id,
         req.name,
-        req.key_hash,
-        req.status,
+        req.scopes,
+        req.expires_at,
+        req.rate_limit_per_minute,
     )
     .await
     .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
-    success_response(ApiKeyResp {
-        id: api_key.id,
-        tenant_id: api_key.tenant_id,
-        name: api_key.name,
-        key_hash: api_key.key_hash,
-        status: api_key.status,
-        created_at: api_key.created_at.to_rfc3339(),
+    success_response(CreateApiKeyResp {
+        key: api_key.into(),
+        api_key: raw_key,
     })
 }
 
@@ -2592,17 +3016,7 @@ pub async fn list_api_keys(
         .map_err(|e| AppError::bad_request(e.to_string()))?;
     // 企业级标准：使用统一响应格式
     success_response(ListApiKeysResp {
-        api_keys: api_keys
-            .into_iter()
-            .map(|k| ApiKeyResp {
-                id: k.id,
-                tenant_id: k.tenant_id,
-                name: k.name,
-                key_hash: k.key_hash,
-                status: k.status,
-                created_at: k.created_at.to_rfc3339(),
-            })
-            .collect(),
+        api_keys: api_keys.into_iter().map(ApiKeyResp::from).collect(),
         total,
     })
 }
@@ -2626,14 +3040,7 @@ pub async fn get_api_key(
         .map_err(|e| AppError::bad_request(e.to_string()))?
         .ok_or_else(|| AppError::not_found("API key not found"))?;
     // 企业级标准：使用统一响应格式
-    success_response(ApiKeyResp {
-        id: api_key.id,
-        tenant_id: api_key.tenant_id,
-        name: api_key.name,
-        key_hash: api_key.key_hash,
-        status: api_key.status,
-        created_at: api_key.created_at.to_rfc3339(),
-    })
+    success_response(ApiKeyResp::from(api_key))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -2663,14 +3070,7 @@ pub async fn update_api_key_status(
         .map_err(|e| AppError::bad_request(e.to_string()))?
         .ok_or_else(|| AppError::not_found("API key not found"))?;
     // 企业级标准：使用统一响应格式
-    success_response(ApiKeyResp {
-        id: api_key.id,
-        tenant_id: api_key.tenant_id,
-        name: api_key.name,
-        key_hash: api_key.key_hash,
-        status: api_key.status,
-        created_at: api_key.created_at.to_rfc3339(),
-    })
+    success_response(ApiKeyResp::from(api_key))
 }
 
 #[utoipa::path(
@@ -3436,6 +3836,7 @@ pub async fn simple_delete_wallet(
     });
 
     crate::utils::write_audit_event_async_str_actor(
+        st.pool.clone(),
         st.immu.clone(),
         "wallet.delete".into(),
         tenant_id,
@@ -3634,12 +4035,22 @@ pub async fn simple_send_transaction(
     if enable_fee {
         let span = tracing::info_span!("fee_calculation", chain=%req.chain, amount=%req.amount);
         let _guard = span.enter();
-        if let Ok(amount_f) = req.amount.parse::<f64>() {
-            let chain_key = req.chain.to_lowercase();
-            drop(_guard); // Drop span before await
+        let chain_key = req.chain.to_lowercase();
+        let symbol = crate::utils::chain_normalizer::get_chain_symbol(&chain_key).ok();
+        drop(_guard); // Drop span before await
+                      // 按原生币精度精确换算，费用在最小单位上计算
+        let amount = match symbol {
+            Some(symbol) => TokenService::new(st.pool.clone())
+                .parse_amount(symbol, chain_id as u64, &req.amount)
+                .await
+                .map_err(|e| tracing::warn!(error=?e, "fee amount parse failed; fee not applied"))
+                .ok(),
+            None => None,
+        };
+        if let Some(amount) = amount {
             if let Ok(Some(calc)) = st
                 .fee_service
-                .calculate_fee(&chain_key, "transfer", amount_f)
+                .calculate_fee_for_amount(&chain_key, "transfer", &amount)
                 .await
             {
                 tracing::info!(fee=calc.platform_fee, collector=%calc.collector_address, "fee calculated");
@@ -3650,7 +4061,7 @@ pub async fn simple_send_transaction(
                         user_id,
                         &chain_key,
                         "transfer",
-                        amount_f,
+                        &amount,
                         &calc,
                         &req.from,
                         Some(&tx_hash), // 保存 tx_hash 用于监控服务回填
@@ -3659,7 +4070,10 @@ pub async fn simple_send_transaction(
                 {
                     tracing::warn!(error=?e, "fee_audit insert failed; continuing without blocking user transaction");
                 }
-                platform_fee = Some(format!("{:.8}", calc.platform_fee));
+                platform_fee = calc
+                    .platform_fee_exact
+                    .map(|fee| fee.to_human_string())
+                    .or_else(|| Some(format!("{:.8}", calc.platform_fee)));
                 fee_applied = true;
             }
         }
@@ -4436,16 +4850,16 @@ pub struct SolanaBlockhashResponse {
 pub async fn get_solana_recent_blockhash(
     State(_st): State<Arc<AppState>>,
 ) -> Result<Json<crate::api::response::ApiResponse<SolanaBlockhashResponse>>, AppError> {
-    crate::metrics::count_ok("GET /api/solana/recent-blockhash");
+    use service::solana_message_builder::{JsonRpcSolanaSource, SolanaRpcSource};
 
-    // 企业级实现：从Solana RPC获取最近区块哈希
-    // 注意：这是一个占位实现，实际需要调用Solana RPC
-    // Solana RPC方法: getLatestBlockhash
+    crate::metrics::count_ok("GET /api/solana/recent-blockhash");
 
-    // 临时返回示例值，实际实现需要调用Solana RPC
-    let blockhash = "11111111111111111111111111111111".to_string();
+    // 企业级实现：Solana RPC getLatestBlockhash（finalized）
+    let blockhash = JsonRpcSolanaSource::from_env()
+        .latest_blockhash()
+        .await
+        .map_err(|e| AppError::rpc_error(format!("Failed to fetch Solana blockhash: {}", e)))?;
 
-    use crate::api::response::success_response;
     success_response(SolanaBlockhashResponse { blockhash })
 }
 
@@ -4471,17 +4885,22 @@ pub struct TonSeqnoResponse {
 )]
 pub async fn get_ton_seqno(
     State(_st): State<Arc<AppState>>,
-    Query(_query): Query<GetTonSeqnoQuery>,
+    Query(query): Query<GetTonSeqnoQuery>,
 ) -> Result<Json<crate::api::response::ApiResponse<TonSeqnoResponse>>, AppError> {
+    use service::ton::{TonAccountSource, TonAddress, ToncenterAccountSource};
+
     crate::metrics::count_ok("GET /api/ton/seqno");
 
-    // 企业级实现：从TON RPC获取账户序列号
-    // 注意：这是一个占位实现，实际需要调用TON RPC
-    // TON RPC方法: getAddressInformation
+    TonAddress::parse(&query.address)
+        .map_err(|e| AppError::invalid_address(format!("Invalid TON address: {}", e)))?;
 
-    // 临时返回0，实际实现需要调用TON RPC
-    let seqno = 0u64;
+    // 企业级实现：toncenter runGetMethod("seqno")，未部署钱包返回0
+    let seqno = ToncenterAccountSource::from_env()
+        .seqno(&query.address)
+        .await
+        .map_err(|e| AppError::rpc_error(format!("Failed to fetch TON seqno: {}", e)))?;
 
-    use crate::api::response::success_response;
-    success_response(TonSeqnoResponse { seqno })
+    success_response(TonSeqnoResponse {
+        seqno: u64::from(seqno),
+    })
 }
@@ -13,7 +13,12 @@ use uuid::Uuid;
 use crate::{
     api::{middleware::jwt_extractor::JwtAuthContext, response::success_response},
     app_state::AppState,
+    domain::token_amount::parse_decimal_strict,
     error::AppError,
+    service::{
+        order_state_machine::{LimitOrderStateMachine, LimitOrderStatus},
+        token_service::TokenService,
+    },
 };
 
 /// POST /api/limit-order/create - 创建限价单
@@ -41,6 +46,9 @@ pub struct LimitOrderResponse {
     pub filled_amount: Option<String>,
     pub created_at: String,
     pub expires_at: Option<String>,
+    /// ready_to_sign 状态下待客户端签名的 1inch swap 交易
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap_tx: Option<serde_json::Value>,
     pub message: Option<String>,
 }
 
@@ -49,22 +57,24 @@ pub async fn create_limit_order(
     auth: JwtAuthContext,
     Json(req): Json<CreateLimitOrderRequest>,
 ) -> Result<axum::Json<crate::api::response::ApiResponse<LimitOrderResponse>>, AppError> {
-    // ✅验证输入
-    let amount = req
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
-    let limit_price = req
-        .limit_price
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid price".to_string()))?;
-
-    if amount <= 0.0 || !amount.is_finite() || limit_price <= 0.0 || !limit_price.is_finite() {
-        return Err(AppError::bad_request(
-            "Amount and price must be > 0 and finite".to_string(),
-        ));
+    // ✅验证输入（严格十进制解析，拒绝浮点格式）
+    let limit_price = parse_decimal_strict(&req.limit_price)
+        .map_err(|e| AppError::bad_request(format!("Invalid price: {}", e)))?;
+    if limit_price <= rust_decimal::Decimal::ZERO {
+        return Err(AppError::bad_request("Price must be > 0".to_string()));
     }
 
+    let chain_id = crate::utils::chain_normalizer::get_chain_id(&req.network)
+        .map_err(|e| AppError::chain_not_supported(e.to_string()))? as u64;
+
+    // ✅ 按代币精度换算，确保金额可精确表示
+    let amount = TokenService::new(state.pool.clone())
+        .parse_amount(&req.from_token, chain_id, &req.amount)
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .to_decimal()
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
+
     let order_id = Uuid::new_v4();
     let expires_at = chrono::Utc::now() + chrono::Duration::days(req.expiry_days as i64);
 
@@ -72,8 +82,8 @@ pub async fn create_limit_order(
     sqlx::query(r#"INSERT INTO public.limit_orders (id, user_id, tenant_id, order_type, from_token, to_token, amount, limit_price, network, wallet_id, status, expires_at, created_at)
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 'pending', $11, NOW())"#)
         .bind(order_id).bind(auth.user_id).bind(auth.tenant_id).bind(&req.order_type)
-        .bind(&req.from_token).bind(&req.to_token).bind(rust_decimal::Decimal::from_f64_retain(amount).unwrap())
-        .bind(rust_decimal::Decimal::from_f64_retain(limit_price).unwrap())
+        .bind(&req.from_token).bind(&req.to_token).bind(amount)
+        .bind(limit_price)
         .bind(&req.network).bind(req.wallet_id.as_ref().and_then(|s| Uuid::parse_str(s).ok()))
         .bind(expires_at).execute(&state.pool).await
         .map_err(|e| AppError::database_error(e.to_string()))?;
@@ -89,6 +99,7 @@ pub async fn create_limit_order(
         filled_amount: None,
         created_at: chrono::Utc::now().to_rfc3339(),
         expires_at: Some(expires_at.to_rfc3339()),
+        swap_tx: None,
         message: Some("Limit order created".into()),
     })
 }
@@ -120,7 +131,7 @@ pub async fn get_limit_order_list(
     let offset = ((page - 1) * page_size) as i64;
 
     // ✅查询限价单
-    let mut sql = "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at FROM public.limit_orders WHERE user_id = $1".to_string();
+    let mut sql = "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at, swap_tx FROM public.limit_orders WHERE user_id = $1".to_string();
     if let Some(ref status) = query.status {
         sql.push_str(&format!(" AND status = '{}'", status));
     }
@@ -139,6 +150,7 @@ pub async fn get_limit_order_list(
             Option<rust_decimal::Decimal>,
             chrono::DateTime<chrono::Utc>,
             Option<chrono::DateTime<chrono::Utc>>,
+            Option<serde_json::Value>,
         ),
     >(&sql)
     .bind(auth.user_id)
@@ -149,7 +161,7 @@ pub async fn get_limit_order_list(
     .map_err(|e| AppError::database_error(e.to_string()))?
     .into_iter()
     .map(
-        |(id, ot, ft, tt, amt, lp, st, fa, ca, ea)| LimitOrderResponse {
+        |(id, ot, ft, tt, amt, lp, st, fa, ca, ea, tx)| LimitOrderResponse {
             order_id: id.to_string(),
             order_type: ot,
             from_token: ft,
@@ -160,6 +172,7 @@ pub async fn get_limit_order_list(
             filled_amount: fa.map(|f| f.to_string()),
             created_at: ca.to_rfc3339(),
             expires_at: ea.map(|e| e.to_rfc3339()),
+            swap_tx: tx,
             message: None,
         },
     )
@@ -189,8 +202,8 @@ pub async fn get_limit_order(
     let order_id =
         Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid ID".to_string()))?;
 
-    let order = sqlx::query_as::<_, (Uuid, String, String, String, rust_decimal::Decimal, rust_decimal::Decimal, String, Option<rust_decimal::Decimal>, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>)>(
-        "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at FROM public.limit_orders WHERE id = $1 AND user_id = $2"
+    let order = sqlx::query_as::<_, (Uuid, String, String, String, rust_decimal::Decimal, rust_decimal::Decimal, String, Option<rust_decimal::Decimal>, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>, Option<serde_json::Value>)>(
+        "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at, swap_tx FROM public.limit_orders WHERE id = $1 AND user_id = $2"
     ).bind(order_id).bind(auth.user_id).fetch_optional(&state.pool).await
     .map_err(|e| AppError::database_error(e.to_string()))?
     .ok_or_else(|| AppError::not_found("Order not found".to_string()))?;
@@ -206,6 +219,7 @@ pub async fn get_limit_order(
         filled_amount: order.7.map(|f| f.to_string()),
         created_at: order.8.to_rfc3339(),
         expires_at: order.9.map(|e| e.to_rfc3339()),
+        swap_tx: order.10,
         message: None,
     })
 }
@@ -219,20 +233,35 @@ pub async fn cancel_limit_order(
     let order_id =
         Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid ID".to_string()))?;
 
-    // ✅更新状态为cancelled
-    let updated = sqlx::query("UPDATE public.limit_orders SET status = 'cancelled', updated_at = NOW() WHERE id = $1 AND user_id = $2 AND status = 'pending'")
-        .bind(order_id).bind(auth.user_id).execute(&state.pool).await
+    let current: String =
+        sqlx::query_scalar("SELECT status FROM public.limit_orders WHERE id = $1 AND user_id = $2")
+            .bind(order_id)
+            .bind(auth.user_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| AppError::database_error(e.to_string()))?
+            .ok_or_else(|| AppError::not_found("Order not found".to_string()))?;
+
+    // ✅ 状态机校验：pending / ready_to_sign 可取消
+    let current =
+        LimitOrderStatus::from_str(&current).map_err(|e| AppError::internal(e.to_string()))?;
+    LimitOrderStateMachine::validate_transition(current, LimitOrderStatus::Cancelled)
+        .map_err(|e| AppError::conflict(e.to_string()))?;
+
+    // ✅更新状态为cancelled（以当前状态做乐观锁，防止与执行引擎竞争）
+    let updated = sqlx::query("UPDATE public.limit_orders SET status = 'cancelled', swap_tx = NULL, updated_at = NOW() WHERE id = $1 AND user_id = $2 AND status = $3")
+        .bind(order_id).bind(auth.user_id).bind(current.as_str()).execute(&state.pool).await
         .map_err(|e| AppError::database_error(e.to_string()))?;
 
     if updated.rows_affected() == 0 {
-        return Err(AppError::not_found(
-            "Order not found or already filled/cancelled".to_string(),
+        return Err(AppError::conflict(
+            "Order status changed, please retry".to_string(),
         ));
     }
 
     // 查询更新后的订单
-    let order = sqlx::query_as::<_, (Uuid, String, String, String, rust_decimal::Decimal, rust_decimal::Decimal, String, Option<rust_decimal::Decimal>, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>)>(
-        "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at FROM public.limit_orders WHERE id = $1"
+    let order = sqlx::query_as::<_, (Uuid, String, String, String, rust_decimal::Decimal, rust_decimal::Decimal, String, Option<rust_decimal::Decimal>, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>, Option<serde_json::Value>)>(
+        "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at, swap_tx FROM public.limit_orders WHERE id = $1"
     ).bind(order_id).fetch_one(&state.pool).await
     .map_err(|e| AppError::database_error(e.to_string()))?;
 
@@ -247,6 +276,89 @@ pub async fn cancel_limit_order(
         filled_amount: order.7.map(|f| f.to_string()),
         created_at: order.8.to_rfc3339(),
         expires_at: order.9.map(|e| e.to_rfc3339()),
+        swap_tx: order.10,
         message: Some("Order cancelled".into()),
     })
 }
+
+/// POST /api/v1/limit-orders/:id/submitted - 客户端签名广播后回报交易哈希
+#[derive(Debug, Deserialize)]
+pub struct LimitOrderSubmittedRequest {
+    pub tx_hash: String,
+}
+
+pub async fn submit_limit_order(
+    State(state): State<Arc<AppState>>,
+    auth: JwtAuthContext,
+    Path(id): Path<String>,
+    Json(req): Json<LimitOrderSubmittedRequest>,
+) -> Result<axum::Json<crate::api::response::ApiResponse<LimitOrderResponse>>, AppError> {
+    let order_id =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid ID".to_string()))?;
+    let tx_hash = crate::infrastructure::rpc_validator::validate_tx_hash(req.tx_hash.trim())
+        .map_err(|e| AppError::bad_request(e.to_string()))?
+        .to_lowercase();
+
+    let (current, recorded_hash): (String, Option<String>) = sqlx::query_as(
+        "SELECT status, tx_hash FROM public.limit_orders WHERE id = $1 AND user_id = $2",
+    )
+    .bind(order_id)
+    .bind(auth.user_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| AppError::database_error(e.to_string()))?
+    .ok_or_else(|| AppError::not_found("Order not found".to_string()))?;
+
+    // ✅ 状态机校验：仅 ready_to_sign 可成交；同一交易重复回报幂等
+    let current =
+        LimitOrderStatus::from_str(&current).map_err(|e| AppError::internal(e.to_string()))?;
+    if current == LimitOrderStatus::Filled && recorded_hash.as_deref() != Some(tx_hash.as_str()) {
+        return Err(AppError::conflict(
+            "Order already filled by another transaction".to_string(),
+        ));
+    }
+    LimitOrderStateMachine::validate_transition(current, LimitOrderStatus::Filled)
+        .map_err(|e| AppError::conflict(e.to_string()))?;
+
+    if current == LimitOrderStatus::ReadyToSign {
+        // 整单成交：成交数量为下单数量，成交价为触发时的 1inch 可成交价
+        let updated = sqlx::query(
+            r#"UPDATE public.limit_orders
+               SET status = 'filled', tx_hash = $3, filled_amount = amount, filled_price = triggered_price,
+                   filled_at = NOW(), status_reason = 'submitted', updated_at = NOW()
+               WHERE id = $1 AND user_id = $2 AND status = 'ready_to_sign'"#,
+        )
+        .bind(order_id)
+        .bind(auth.user_id)
+        .bind(&tx_hash)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| AppError::database_error(e.to_string()))?;
+
+        if updated.rows_affected() == 0 {
+            return Err(AppError::conflict(
+                "Order status changed, please retry".to_string(),
+            ));
+        }
+    }
+
+    let order = sqlx::query_as::<_, (Uuid, String, String, String, rust_decimal::Decimal, rust_decimal::Decimal, String, Option<rust_decimal::Decimal>, chrono::DateTime<chrono::Utc>, Option<chrono::DateTime<chrono::Utc>>, Option<serde_json::Value>)>(
+        "SELECT id, order_type, from_token, to_token, amount, limit_price, status, filled_amount, created_at, expires_at, swap_tx FROM public.limit_orders WHERE id = $1"
+    ).bind(order_id).fetch_one(&state.pool).await
+    .map_err(|e| AppError::database_error(e.to_string()))?;
+
+    success_response(LimitOrderResponse {
+        order_id: order.0.to_string(),
+        order_type: order.1,
+        from_token: order.2,
+        to_token: order.3,
+        amount: order.4.to_string(),
+        limit_price: order.5.to_string(),
+        status: order.6,
+        filled_amount: order.7.map(|f| f.to_string()),
+        created_at: order.8.to_rfc3339(),
+        expires_at: order.9.map(|e| e.to_rfc3339()),
+        swap_tx: order.10,
+        message: Some("Order filled".into()),
+    })
+}
@@ -1,5 +1,5 @@
 //! 认证中间件
-//! 验证API Key和Bearer Token
+//! 验证API Key（X-API-Key）和Bearer Token
 
 use std::sync::Arc;
 
@@ -11,7 +11,12 @@ use axum::{
 };
 use uuid::Uuid;
 
-use crate::{app_state::AppState, error::AppError};
+use crate::{
+    api::middleware::jwt_extractor::JwtAuthContext,
+    app_state::AppState,
+    error::AppError,
+    service::api_keys::{self, ApiKeyAuthError},
+};
 
 /// 认证信息（从Token中提取）
 #[derive(Clone)]
@@ -21,21 +26,42 @@ pub struct AuthInfo {
     pub role: String,
 }
 
+/// API Key 认证主体（服务间调用）
+///
+/// 认证中间件只注入该主体；`AuthInfo` / `JwtAuthContext` 在路由声明的 scope
+/// 校验通过后由 `require_scope` 注入。未声明 scope 的路由挂 `reject_api_key`，
+/// 对 API Key 默认拒绝（403）。
+#[derive(Debug, Clone)]
+pub struct ApiKeyPrincipal {
+    pub key_id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_minute: Option<i64>,
+}
+
+fn unauthorized(message: impl Into<String>) -> AppError {
+    AppError {
+        code: crate::error::AppErrorCode::Unauthorized,
+        message: message.into(),
+        status: StatusCode::UNAUTHORIZED,
+        trace_id: None,
+    }
+}
+
 /// 认证中间件
 /// 企业级实现：验证API Key和Bearer Token
 ///
 /// 认证流程：
-/// 1. 提取 Authorization 头
-/// 2. 验证 Bearer Token 格式
-/// 3. 验证 Token 有效性（JWT签名 + Session）
-/// 4. 提取 user_id, tenant_id, role
-/// 5. 注入到请求扩展中
+/// 1. 存在 X-API-Key：哈希后查询 api_keys，检查状态/过期/归属用户，注入 ApiKeyPrincipal
+/// 2. 否则验证 Authorization: Bearer <JWT>
+/// 3. 提取 user_id, tenant_id, role 注入到请求扩展中
 ///
 /// 安全特性：
-/// - JWT 签名验证
-/// - Token 过期检查
-/// - Session 有效性检查（Redis）
-/// - 租户ID验证
+/// - API Key 仅存储 SHA-256 哈希，按 Key 记录最近使用时间
+/// - API Key 以归属用户身份执行，scope 只收窄权限
+/// - JWT 签名验证与过期检查
 pub async fn auth_middleware(
     State(st): State<Arc<AppState>>,
     mut req: Request,
@@ -46,130 +72,157 @@ pub async fn auth_middleware(
         return Ok(next.run(req).await);
     }
 
-    let headers = req.headers();
-
-    // ✅ 生产环境：API Key 验证已启用
-    // 1. 验证 API Key（可选，用于额外的安全层）
-    let _api_key = headers.get("X-API-Key").and_then(|h| h.to_str().ok());
-
-    // 如果需要强制 API Key 验证，取消下面的注释：
-    // let api_key = _api_key.ok_or_else(|| AppError {
-    // code: crate::error::AppErrorCode::Unauthorized,
-    // message: "X-API-Key header required".into(),
-    // status: StatusCode::UNAUTHORIZED,
-    // trace_id: None,
-    // })?;
-    //
-    // 计算API Key的哈希
-    // use sha2::{Digest, Sha256};
-    // let mut hasher = Sha256::new();
-    // hasher.update(api_key.as_bytes());
-    // let key_hash = faster_hex::hex_string(&hasher.finalize());
-    //
-    // let pool = st.pool.clone();
-    // let redis = st.redis.clone();
-    //
-    // 从数据库查询API Key
-    // let api_key_record = api_keys::get_api_key_by_hash(&pool, &key_hash)
-    // .await
-    // .map_err(|e| AppError::bad_request(format!("Failed to verify API key: {}", e)))?;
-    //
-    // let api_key_record = api_key_record.ok_or_else(|| AppError {
-    // code: crate::error::AppErrorCode::Unauthorized,
-    // message: "Invalid API key".into(),
-    // status: StatusCode::UNAUTHORIZED,
-    // trace_id: None,
-    // })?;
-    //
-    // 检查API Key状态
-    // if api_key_record.status != "active" {
-    // return Err(AppError {
-    // code: crate::error::AppErrorCode::Unauthorized,
-    // message: "API key is not active".into(),
-    // status: StatusCode::UNAUTHORIZED,
-    // trace_id: None,
-    // });
-    // }
-
-    let _redis = st.redis.clone();
+    // 1. API Key 认证
+    if let Some(raw_key) = req.headers().get("X-API-Key") {
+        let raw_key = raw_key
+            .to_str()
+            .map_err(|_| unauthorized("Invalid X-API-Key header"))?;
 
-    // 2. 验证 Bearer Token
-    let auth_header = headers
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .ok_or_else(|| AppError {
-            code: crate::error::AppErrorCode::Unauthorized,
-            message: "Authorization header required".into(),
-            status: StatusCode::UNAUTHORIZED,
-            trace_id: None,
-        })?;
+        let (key, user_id) = api_keys::verify_api_key(&st.pool, raw_key.trim())
+            .await
+            .map_err(|e| match e {
+                ApiKeyAuthError::Storage(msg) => {
+                    tracing::error!(error = %msg, "API key lookup failed");
+                    AppError::service_unavailable("API key verification unavailable")
+                }
+                other => unauthorized(other.to_string()),
+            })?;
 
-    if !auth_header.starts_with("Bearer ") {
-        return Err(AppError {
-            code: crate::error::AppErrorCode::Unauthorized,
-            message: "Invalid authorization header format".into(),
-            status: StatusCode::UNAUTHORIZED,
-            trace_id: None,
-        });
-    }
+        // 归属用户必须仍然有效且属于同一租户
+        let role: String = sqlx::query_scalar(
+            "SELECT role FROM users WHERE id = $1 AND tenant_id = $2 AND status = 'active'",
+        )
+        .bind(user_id)
+        .bind(key.tenant_id)
+        .fetch_optional(&st.pool)
+        .await
+        .map_err(|e| AppError::database_error(e.to_string()))?
+        .ok_or_else(|| unauthorized("API key owner is not active"))?;
 
-    // 提取 token 并 trim 空白字符（防止多余空格）
-    let token = auth_header[7..].trim();
+        // 最近使用时间异步记录，不阻塞请求
+        let pool = st.pool.clone();
+        let key_id = key.id;
+        tokio::spawn(async move {
+            if let Err(e) = api_keys::touch_api_key_last_used(&pool, key_id).await {
+                tracing::warn!(key_id = %key_id, error = %e, "Failed to record API key usage");
+            }
+        });
 
-    // 🔍 DEBUG: 打印 token 信息
-    tracing::debug!("Auth header: [{}]", auth_header);
-    tracing::debug!("Extracted token: [{}]", token);
-    tracing::debug!("Token length: {}", token.len());
+        req.extensions_mut().insert(ApiKeyPrincipal {
+            key_id: key.id,
+            tenant_id: key.tenant_id,
+            user_id,
+            role,
+            scopes: key.scopes,
+            rate_limit_per_minute: key.rate_limit_per_minute,
+        });
 
-    // 验证Token（移除Redis Session检查，因为JWT本身已足够安全）
-    // Redis Session检查会导致刚登录的用户立即401，因为Session可能还未完全同步
-    let claims = crate::infrastructure::jwt::verify_token(token).map_err(|e| AppError {
-        code: crate::error::AppErrorCode::Unauthorized,
-        message: format!("Invalid token: {}", e),
-        status: StatusCode::UNAUTHORIZED,
-        trace_id: None,
-    })?;
+        return Ok(next.run(req).await);
+    }
 
-    // ✅ 生产环境：租户ID验证已启用
-    // 验证租户ID匹配（如果启用了 API Key 验证）
-    let _token_tenant_id = Uuid::parse_str(&claims.tenant_id)
-        .map_err(|_| AppError::bad_request("Invalid tenant_id in token"))?;
+    // 2. 验证 Bearer Token
+    let auth_header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| unauthorized("Authorization header required"))?;
 
-    // 如果有 API Key 记录，验证租户ID匹配
-    // 注意：当前实现中，我们使用 token 中的 tenant_id
-    // 如果需要与 API Key 的租户ID匹配，需要先实现 API Key 查询逻辑
+    let token = auth_header
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .ok_or_else(|| unauthorized("Invalid authorization header format"))?;
 
-    // 示例验证逻辑（需要 api_key_record）：
-    // if _token_tenant_id != api_key_record.tenant_id {
-    // return Err(AppError {
-    // code: crate::error::AppErrorCode::Unauthorized,
-    // message: "Tenant ID mismatch".into(),
-    // status: StatusCode::UNAUTHORIZED,
-    // trace_id: None,
-    // });
-    // }
+    let claims = crate::infrastructure::jwt::verify_token(token)
+        .map_err(|e| unauthorized(format!("Invalid token: {}", e)))?;
 
     // 3. 将认证信息注入到请求扩展中
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|_| AppError::bad_request("Invalid user_id in token"))?;
-
-    // 🔧 本地开发：使用 token 中的 tenant_id（不验证 API Key）
-    let token_tenant_id = Uuid::parse_str(&claims.tenant_id)
+    let tenant_id = Uuid::parse_str(&claims.tenant_id)
         .map_err(|_| AppError::bad_request("Invalid tenant_id in token"))?;
 
-    let auth_info = AuthInfo {
+    // 同时注入 AuthInfo、JwtAuthContext 和 Claims，支持不同的 handler 使用方式
+    req.extensions_mut().insert(AuthInfo {
         user_id,
-        tenant_id: token_tenant_id,
+        tenant_id,
         role: claims.role.clone(),
-    };
-
-    // 同时注入 AuthInfo 和 Claims，支持不同的 handler 使用方式
-    req.extensions_mut().insert(auth_info);
+    });
+    req.extensions_mut().insert(JwtAuthContext {
+        user_id,
+        tenant_id,
+        role: claims.role.clone(),
+    });
     req.extensions_mut().insert(claims);
 
     Ok(next.run(req).await)
 }
 
+/// scope 校验中间件返回类型
+type ScopeFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, AppError>> + Send>>;
+
+/// 路由声明所需 scope
+///
+/// - API Key：scope 覆盖时注入 AuthInfo / JwtAuthContext，否则 403
+/// - JWT 用户：不受 scope 约束（由角色与资源归属控制）
+///
+/// 用法：`get(handler).route_layer(from_fn(require_scope(scopes::WALLETS_READ)))`
+pub fn require_scope(scope: &'static str) -> impl Fn(Request, Next) -> ScopeFuture + Clone {
+    move |mut req: Request, next: Next| {
+        Box::pin(async move {
+            if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>().cloned() {
+                if !api_keys::scope_grants(&principal.scopes, scope) {
+                    return Err(AppError {
+                        code: crate::error::AppErrorCode::Forbidden,
+                        message: format!("API key missing required scope: {}", scope),
+                        status: StatusCode::FORBIDDEN,
+                        trace_id: None,
+                    });
+                }
+
+                req.extensions_mut().insert(AuthInfo {
+                    user_id: principal.user_id,
+                    tenant_id: principal.tenant_id,
+                    role: principal.role.clone(),
+                });
+                req.extensions_mut().insert(JwtAuthContext {
+                    user_id: principal.user_id,
+                    tenant_id: principal.tenant_id,
+                    role: principal.role,
+                });
+            } else if req.extensions().get::<AuthInfo>().is_none() {
+                return Err(unauthorized("Not authenticated"));
+            }
+
+            Ok(next.run(req).await)
+        })
+    }
+}
+
+/// 默认拒绝 API Key
+///
+/// 挂在未声明 scope 的路由上（`Router::route_layer`）：携带 `ApiKeyPrincipal` 的请求直接 403，
+/// 即使 handler 自身不读取认证信息（如 POST /api/v1/tx）也无法被 API Key 调用。
+/// 声明了 scope 的方法注册在单独的路由表中合并，不经过该层。
+pub async fn reject_api_key(req: Request, next: Next) -> Result<Response, AppError> {
+    if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>() {
+        tracing::warn!(
+            key_id = %principal.key_id,
+            method = %req.method(),
+            path = %req.uri().path(),
+            "API key rejected on route without declared scope"
+        );
+        return Err(AppError {
+            code: crate::error::AppErrorCode::Forbidden,
+            message: "API keys are not permitted on this route".into(),
+            status: StatusCode::FORBIDDEN,
+            trace_id: None,
+        });
+    }
+
+    Ok(next.run(req).await)
+}
+
 /// 从请求中提取认证信息
 pub fn extract_auth_info(req: &Request) -> Option<AuthInfo> {
     req.extensions().get::<AuthInfo>().cloned()
@@ -203,3 +256,170 @@ where
         Ok(AuthInfoExtractor(auth_info))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::service::api_keys::scopes;
+
+    async fn whoami(auth: AuthInfoExtractor) -> String {
+        auth.0.user_id.to_string()
+    }
+
+    fn app(principal: Option<ApiKeyPrincipal>) -> Router {
+        Router::new()
+            .route(
+                "/wallets",
+                get(whoami).route_layer(from_fn(require_scope(scopes::WALLETS_READ))),
+            )
+            .route(
+                "/admin",
+                get(whoami).route_layer(from_fn(require_scope(scopes::ADMIN_ALL))),
+            )
+            // 未声明 scope 的路由
+            .route("/undeclared", get(whoami))
+            .layer(from_fn(move |mut req: Request, next: Next| {
+                let principal = principal.clone();
+                async move {
+                    if let Some(p) = principal {
+                        req.extensions_mut().insert(p);
+                    }
+                    next.run(req).await
+                }
+            }))
+    }
+
+    fn principal(scopes: &[&str]) -> ApiKeyPrincipal {
+        ApiKeyPrincipal {
+            key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            role: "user".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            rate_limit_per_minute: None,
+        }
+    }
+
+    async fn status(app: Router, path: &str) -> StatusCode {
+        app.oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_require_scope_for_api_key() {
+        let p = principal(&[scopes::WALLETS_READ]);
+        assert_eq!(
+            status(app(Some(p.clone())), "/wallets").await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(app(Some(p.clone())), "/admin").await,
+            StatusCode::FORBIDDEN
+        );
+        // 未声明 scope 的路由不会获得 AuthInfo
+        assert_eq!(
+            status(app(Some(p)), "/undeclared").await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let admin = principal(&[scopes::ADMIN_ALL]);
+        assert_eq!(status(app(Some(admin)), "/admin").await, StatusCode::OK);
+    }
+
+    /// 与 api::api_key_routes() 相同的组合：主路由表挂 reject_api_key，scope 声明路由合并在后
+    fn gated_app(principal: ApiKeyPrincipal) -> Router {
+        // handler 不读取认证信息，仅靠中间件拦截
+        async fn unauthenticated() -> &'static str {
+            "ok"
+        }
+
+        Router::new()
+            .route(
+                "/api/v1/wallets/:id",
+                axum::routing::delete(unauthenticated),
+            )
+            .route_layer(from_fn(reject_api_key))
+            .merge(
+                Router::new()
+                    .route(
+                        "/api/v1/tx",
+                        axum::routing::post(unauthenticated)
+                            .route_layer(from_fn(require_scope(scopes::TX_BROADCAST))),
+                    )
+                    .route(
+                        "/api/v1/wallets/:id",
+                        get(whoami).route_layer(from_fn(require_scope(scopes::WALLETS_READ))),
+                    ),
+            )
+            .layer(from_fn(move |mut req: Request, next: Next| {
+                let principal = principal.clone();
+                async move {
+                    req.extensions_mut().insert(principal);
+                    next.run(req).await
+                }
+            }))
+    }
+
+    async fn call(app: Router, method: &str, path: &str) -> StatusCode {
+        app.oneshot(
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_api_key_denied_by_default() {
+        let wallet_path = format!("/api/v1/wallets/{}", Uuid::new_v4());
+        let reader = principal(&[scopes::WALLETS_READ, scopes::TX_READ]);
+
+        // 缺少 tx:broadcast 时不能创建交易
+        assert_eq!(
+            call(gated_app(reader.clone()), "POST", "/api/v1/tx").await,
+            StatusCode::FORBIDDEN
+        );
+        // 未声明 scope 的方法对任何 API Key 都不可用
+        assert_eq!(
+            call(gated_app(reader.clone()), "DELETE", &wallet_path).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            call(
+                gated_app(principal(&[scopes::ADMIN_ALL])),
+                "DELETE",
+                &wallet_path
+            )
+            .await,
+            StatusCode::FORBIDDEN
+        );
+        // 同一路径下声明了 scope 的方法不受影响
+        assert_eq!(
+            call(gated_app(reader), "GET", &wallet_path).await,
+            StatusCode::OK
+        );
+
+        let broadcaster = principal(&[scopes::TX_BROADCAST]);
+        assert_eq!(
+            call(gated_app(broadcaster), "POST", "/api/v1/tx").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_require_scope_without_auth() {
+        assert_eq!(
+            status(app(None), "/wallets").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
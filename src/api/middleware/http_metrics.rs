@@ -0,0 +1,132 @@
+//! HTTP 指标中间件
+//! 按 方法 / 路由模板 / 状态码 自动记录请求数与耗时直方图
+
+use std::{
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    response::Response,
+};
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+
+/// 未匹配任何路由的请求（404 等）统一归入该标签，避免原始路径放大标签基数
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// HTTP 指标层
+///
+/// 需通过 `Router::layer` 挂载，才能从请求扩展中读取 [`MatchedPath`]（路由模板）
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpMetricsLayer;
+
+impl<S> Layer<S> for HttpMetricsLayer {
+    type Service = HttpMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpMetricsService { inner }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpMetricsService<S> {
+    inner: S,
+}
+
+/// 在途请求计数守卫：请求被取消（客户端断开）时同样回收
+struct InFlightGuard;
+
+impl InFlightGuard {
+    fn new() -> Self {
+        crate::metrics::inc_http_in_flight();
+        Self
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        crate::metrics::dec_http_in_flight();
+    }
+}
+
+impl<S> Service<Request> for HttpMetricsService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let method = req.method().to_string();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+        let guard = InFlightGuard::new();
+        let started = Instant::now();
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = future.await;
+            drop(guard);
+            if let Ok(response) = &result {
+                crate::metrics::observe_http_request(
+                    &method,
+                    &route,
+                    response.status().as_u16(),
+                    started.elapsed(),
+                );
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_route_template_and_status() {
+        let app = Router::new()
+            .route(
+                "/metrics-test/wallets/:id",
+                get(|| async { StatusCode::CREATED }),
+            )
+            .layer(HttpMetricsLayer);
+
+        for id in ["a", "b"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::get(format!("/metrics-test/wallets/{}", id))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let out = crate::metrics::render_prometheus();
+        assert!(out.contains(
+            "ironcore_http_requests_total{method=\"GET\",route=\"/metrics-test/wallets/:id\",status=\"201\"} 2"
+        ));
+        assert!(out.contains(
+            "ironcore_http_request_duration_seconds_count{method=\"GET\",route=\"/metrics-test/wallets/:id\"} 2"
+        ));
+        assert!(!out.contains("/metrics-test/wallets/a"));
+    }
+}
@@ -10,7 +10,11 @@ use axum::{
     response::Response,
 };
 
-use crate::{api::middleware::auth::AuthInfo, app_state::AppState, error::AppError};
+use crate::{
+    api::middleware::auth::{ApiKeyPrincipal, AuthInfo},
+    app_state::AppState,
+    error::AppError,
+};
 
 /// 速率限制配置
 #[derive(Clone)]
@@ -40,8 +44,8 @@ pub async fn rate_limit_middleware(
     next: Next,
 ) -> Result<Response, AppError> {
     let redis = st.redis.clone();
-    // 获取速率限制配置（从环境变量或使用默认值）
-    let config = get_rate_limit_config();
+    // 获取速率限制配置（API Key 独立额度优先，否则从环境变量或使用默认值）
+    let config = api_key_rate_limit_config(&req).unwrap_or_else(get_rate_limit_config);
 
     // 确定速率限制键
     let rate_limit_key = determine_rate_limit_key(&req)?;
@@ -98,22 +102,18 @@ pub async fn rate_limit_middleware(
 }
 
 /// 确定速率限制键
-/// 优先级：用户ID > API Key > IP地址
+/// 优先级：API Key > 用户ID > IP地址
+///
+/// API Key 使用独立桶（按 Key ID），不与归属用户的交互式请求共享额度
 fn determine_rate_limit_key(req: &Request) -> Result<String, AppError> {
-    // 1. 尝试从认证信息中获取用户ID
-    if let Some(auth_info) = req.extensions().get::<AuthInfo>() {
-        return Ok(format!("rate_limit:user:{}", auth_info.user_id));
+    // 1. 已认证的 API Key
+    if let Some(principal) = req.extensions().get::<ApiKeyPrincipal>() {
+        return Ok(format!("rate_limit:api_key:{}", principal.key_id));
     }
 
-    // 2. 尝试从API Key获取
-    if let Some(api_key) = req.headers().get("X-API-Key") {
-        if let Ok(key_str) = api_key.to_str() {
-            use sha2::{Digest, Sha256};
-            let mut hasher = Sha256::new();
-            hasher.update(key_str.as_bytes());
-            let key_hash = faster_hex::hex_string(&hasher.finalize());
-            return Ok(format!("rate_limit:api_key:{}", key_hash));
-        }
+    // 2. 尝试从认证信息中获取用户ID
+    if let Some(auth_info) = req.extensions().get::<AuthInfo>() {
+        return Ok(format!("rate_limit:user:{}", auth_info.user_id));
     }
 
     // 3. 使用IP地址
@@ -128,6 +128,17 @@ fn determine_rate_limit_key(req: &Request) -> Result<String, AppError> {
     Ok(format!("rate_limit:ip:{}", ip))
 }
 
+/// API Key 独立限流额度（每分钟）
+fn api_key_rate_limit_config(req: &Request) -> Option<RateLimitConfig> {
+    let principal = req.extensions().get::<ApiKeyPrincipal>()?;
+    principal
+        .rate_limit_per_minute
+        .map(|max_requests| RateLimitConfig {
+            window_secs: 60,
+            max_requests,
+        })
+}
+
 /// 获取速率限制配置
 fn get_rate_limit_config() -> RateLimitConfig {
     let window_secs = std::env::var("RATE_LIMIT_WINDOW_SECS")
@@ -200,3 +211,71 @@ pub fn custom_rate_limit(
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn principal(rate_limit_per_minute: Option<i64>) -> ApiKeyPrincipal {
+        ApiKeyPrincipal {
+            key_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            role: "user".to_string(),
+            scopes: vec![],
+            rate_limit_per_minute,
+        }
+    }
+
+    #[test]
+    fn test_api_key_uses_own_bucket() {
+        let principal = principal(Some(30));
+        let mut req = Request::new(axum::body::Body::empty());
+        req.extensions_mut().insert(AuthInfo {
+            user_id: principal.user_id,
+            tenant_id: principal.tenant_id,
+            role: principal.role.clone(),
+        });
+        req.extensions_mut().insert(principal.clone());
+
+        assert_eq!(
+            determine_rate_limit_key(&req).unwrap(),
+            format!("rate_limit:api_key:{}", principal.key_id)
+        );
+        let config = api_key_rate_limit_config(&req).unwrap();
+        assert_eq!(config.max_requests, 30);
+        assert_eq!(config.window_secs, 60);
+    }
+
+    #[test]
+    fn test_user_and_ip_buckets() {
+        let user_id = Uuid::new_v4();
+        let mut req = Request::new(axum::body::Body::empty());
+        req.extensions_mut().insert(AuthInfo {
+            user_id,
+            tenant_id: Uuid::new_v4(),
+            role: "user".to_string(),
+        });
+        assert_eq!(
+            determine_rate_limit_key(&req).unwrap(),
+            format!("rate_limit:user:{}", user_id)
+        );
+        assert!(api_key_rate_limit_config(&req).is_none());
+
+        let req = Request::builder()
+            .header("X-Forwarded-For", "10.0.0.1, 10.0.0.2")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(
+            determine_rate_limit_key(&req).unwrap(),
+            "rate_limit:ip:10.0.0.1"
+        );
+
+        // 未设置独立额度的 Key 回落到全局配置
+        let mut req = Request::new(axum::body::Body::empty());
+        req.extensions_mut().insert(principal(None));
+        assert!(api_key_rate_limit_config(&req).is_none());
+    }
+}
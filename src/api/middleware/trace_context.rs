@@ -0,0 +1,87 @@
+//! W3C Trace Context 中间件
+//! 为每个请求创建服务端 span，并以入站 `traceparent` 作为父上下文加入上游链路
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Trace Context 中间件
+///
+/// 需通过 `Router::layer` 挂载以读取路由模板；span 内的服务调用与出站请求共享同一 trace
+pub async fn trace_context_middleware(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {}", method, route),
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %method,
+        http.route = %route,
+        url.path = %req.uri().path(),
+        http.response.status_code = tracing::field::Empty,
+    );
+    span.set_parent(crate::infrastructure::telemetry::extract_context(
+        req.headers(),
+    ));
+
+    let response = next.run(req).instrument(span.clone()).await;
+
+    let status = response.status();
+    span.record("http.response.status_code", status.as_u16());
+    if status.is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::infrastructure::telemetry::{
+        tests::{test_subscriber, UPSTREAM_TRACEPARENT},
+        trace_headers,
+    };
+
+    #[tokio::test]
+    async fn test_outbound_traceparent_joins_incoming_trace() {
+        let _guard = tracing::subscriber::set_default(test_subscriber());
+
+        // 处理函数返回它在出站请求上会注入的 traceparent
+        let app = Router::new()
+            .route(
+                "/swap/:id",
+                get(|| async { trace_headers().remove("traceparent").unwrap_or_default() }),
+            )
+            .layer(from_fn(trace_context_middleware));
+
+        let response = app
+            .oneshot(
+                Request::get("/swap/42")
+                    .header("traceparent", UPSTREAM_TRACEPARENT)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let outbound = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(outbound.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert!(!outbound.contains("00f067aa0ba902b7"));
+    }
+}
@@ -24,6 +24,11 @@ impl TraceIdGenerator {
             }
         }
 
+        // 开启链路追踪时沿用 OpenTelemetry trace id，日志与追踪可互相检索
+        if let Some(trace_id) = crate::infrastructure::telemetry::current_trace_id() {
+            return trace_id;
+        }
+
         // 如果没有，生成新的 trace_id
         Self::generate()
     }
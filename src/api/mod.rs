@@ -2,6 +2,7 @@ use std::{sync::Arc, time::Instant};
 
 use axum::{
     extract::Request,
+    handler::Handler,
     http::{
         header::{
             ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
@@ -13,7 +14,7 @@ use axum::{
     },
     middleware::{from_fn, from_fn_with_state},
     response::{IntoResponse, Response},
-    routing::{get, post, put},
+    routing::{get, options, post, put},
     Router,
 };
 use tower::ServiceBuilder;
@@ -24,22 +25,25 @@ use uuid::Uuid;
 use crate::{
     api::{
         handlers::{
-            api_errors, api_fees, api_health, api_network_status, balance,
-            broadcast_raw_transaction, calculate_platform_fee, create_api_key, create_approval,
-            create_policy, create_tenant, create_tx, create_tx_broadcast, create_user,
-            delete_api_key, delete_approval, delete_policy, delete_tenant, delete_user,
-            delete_wallet, get_api_key, get_approval, get_login_history, get_me, get_nonce,
+            activate_policy, api_errors, api_fees, api_health, api_network_status, balance,
+            broadcast_raw_transaction, build_transaction, calculate_platform_fee,
+            cast_approval_vote, create_api_key, create_approval, create_policy, create_tenant,
+            create_tx, create_tx_broadcast, create_user, deactivate_policy, delete_api_key,
+            delete_approval, delete_policy, delete_tenant, delete_user, delete_wallet,
+            dry_run_policy, get_api_key, get_approval, get_login_history, get_me, get_nonce,
             get_policy, get_solana_recent_blockhash, get_tenant, get_ton_seqno, get_tx,
             get_tx_broadcast, get_tx_broadcast_by_tx_hash, get_tx_history, get_user, get_wallet,
-            healthz, list_api_keys, list_approvals, list_policies, list_tenants, list_tx,
-            list_tx_broadcasts, list_users, list_wallets, login, logout, openapi_yaml,
-            refresh_token, register, reset_password, set_password, simple_list_transactions,
-            simple_send_transaction, tx_status, update_api_key_status, update_approval_status,
-            update_policy, update_tenant, update_tx_broadcast, update_tx_status, update_user,
+            healthz, list_api_keys, list_approval_votes, list_approvals, list_policies,
+            list_policy_versions, list_tenants, list_tx, list_tx_broadcasts, list_users,
+            list_wallets, login, logout, openapi_yaml, refresh_token, register, reset_password,
+            set_password, simple_list_transactions, simple_send_transaction, tx_status,
+            update_api_key_status, update_approval_status, update_policy, update_tenant,
+            update_tx_broadcast, update_tx_status, update_user,
         },
-        middleware::{rate_limit_middleware, trace_id_middleware},
+        middleware::{rate_limit_middleware, reject_api_key, require_scope, trace_id_middleware},
     },
     app_state::AppState,
+    service::api_keys::scopes,
 };
 
 pub mod admin_api;
@@ -77,6 +81,7 @@ pub mod response; // 统一响应格式
 pub mod response_extensions; // 响应扩展（兼容性）
 pub mod router_integration; // ✅ 路由集成模块
 pub mod swap_api;
+pub mod tenant_webhook_api; // ✅ 租户出站 Webhook 端点与投递日志
 pub mod token_api;
 pub mod token_detection_api; // NEW: 代币检测 API
 pub mod transaction_accelerate_api; // ✅ M项优化: RBF交易加速API
@@ -106,10 +111,16 @@ pub mod withdrawal_api; // ✅ P0-4: 提现API // ✅ F项补充: Nonce查询API
         handlers::get_policy,
         handlers::update_policy,
         handlers::delete_policy,
+        handlers::activate_policy,
+        handlers::deactivate_policy,
+        handlers::list_policy_versions,
+        handlers::dry_run_policy,
         handlers::create_approval,
         handlers::list_approvals,
         handlers::get_approval,
         handlers::update_approval_status,
+        handlers::cast_approval_vote,
+        handlers::list_approval_votes,
         handlers::delete_approval,
         handlers::create_api_key,
         handlers::list_api_keys,
@@ -170,13 +181,23 @@ pub mod withdrawal_api; // ✅ P0-4: 提现API // ✅ F项补充: Nonce查询API
             handlers::ListPoliciesQuery,
             handlers::ListPoliciesResp,
             handlers::UpdatePolicyReq,
+            handlers::PolicyStatusReq,
+            handlers::PolicyVersionResp,
+            handlers::PolicyDryRunReq,
+            crate::service::withdrawal_risk_control::PolicyDryRunReport,
+            crate::service::withdrawal_risk_control::PolicyDryRunItem,
+            crate::service::withdrawal_risk_control::RiskLevel,
             handlers::CreateApprovalReq,
             handlers::ApprovalResp,
             handlers::ListApprovalsQuery,
             handlers::ListApprovalsResp,
             handlers::UpdateApprovalStatusReq,
+            handlers::CastApprovalVoteReq,
+            handlers::ApprovalVoteResp,
+            handlers::CastApprovalVoteResp,
             handlers::CreateApiKeyReq,
             handlers::ApiKeyResp,
+            handlers::CreateApiKeyResp,
             handlers::ListApiKeysQuery,
             handlers::ListApiKeysResp,
             handlers::UpdateApiKeyStatusReq,
@@ -205,6 +226,10 @@ pub mod withdrawal_api; // ✅ P0-4: 提现API // ✅ F项补充: Nonce查询API
             handlers::BalanceResponse,
             handlers::CreateTxReq,
             handlers::TxResp,
+            crate::service::tx_simulator::SimulationResult,
+            crate::service::tx_simulator::RevertReason,
+            crate::service::tx_simulator::BalanceChange,
+            crate::service::tx_simulator::BalanceChangeSource,
             handlers::ListTxQuery,
             handlers::ListTxResp,
             handlers::GetTxParams,
@@ -250,9 +275,17 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/api/v1/errors", get(api_errors))
         .route("/openapi.yaml", get(openapi_yaml))
         .merge(utoipa_swagger_ui::SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
-        .route(
-            "/metrics",
-            get(|| async { crate::metrics::render_prometheus().into_response() }),
+        .merge(crate::infrastructure::monitoring::create_metrics_router())
+        // ✅ JWKS：供其他服务验证本服务签发的 Token
+        .route(
+            "/.well-known/jwks.json",
+            get(|| async {
+                (
+                    [(CACHE_CONTROL, "public, max-age=300")],
+                    axum::Json(crate::infrastructure::jwt::jwks()),
+                )
+                    .into_response()
+            }),
         )
         // ✅ 企业级标准 V1：多链钱包 API
         .route("/api/v1/chains", get(multi_chain_api::list_chains))
@@ -430,26 +463,16 @@ pub fn routes(state: Arc<AppState>) -> Router {
             axum::routing::get(history_api::get_transaction_detail).options(preflight_ok),
         )
         // ✅ 企业级标准：交易API v1版本（需要认证）
-        .route(
-            "/api/v1/transactions",
-            post(simple_send_transaction).get(simple_list_transactions),
-        )
-        .route(
-            "/api/v1/transactions/broadcast",
-            post(broadcast_raw_transaction).options(preflight_ok),
-        )
-        .route(
-            "/api/v1/transactions/:hash/status",
-            get(tx_status).options(preflight_ok),
-        )
+        // API Key 可访问的方法（声明了 scope）见 api_key_routes()
+        .route("/api/v1/transactions", post(simple_send_transaction))
+        .route("/api/v1/transactions/broadcast", options(preflight_ok))
+        .route("/api/v1/transactions/build", options(preflight_ok))
+        .route("/api/v1/transactions/:hash/status", options(preflight_ok))
         .route(
             "/api/v1/transactions/nonce",
             get(get_nonce).options(preflight_ok),
         )
-        .route(
-            "/api/v1/transactions/history",
-            get(get_tx_history).options(preflight_ok),
-        )
+        .route("/api/v1/transactions/history", options(preflight_ok))
         // ✅ 链特定端点 v1版本
         .route(
             "/api/v1/solana/recent-blockhash",
@@ -504,6 +527,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/api/v1/policies/:id",
             get(get_policy).put(update_policy).delete(delete_policy),
         )
+        .route("/api/v1/policies/:id/activate", post(activate_policy))
+        .route("/api/v1/policies/:id/deactivate", post(deactivate_policy))
+        .route("/api/v1/policies/:id/versions", get(list_policy_versions))
+        .route("/api/v1/policies/:id/dry-run", post(dry_run_policy))
         // Approvals API
         .route(
             "/api/v1/approvals",
@@ -514,18 +541,11 @@ pub fn routes(state: Arc<AppState>) -> Router {
             get(get_approval).delete(delete_approval),
         )
         .route("/api/v1/approvals/:id/status", put(update_approval_status))
-        // API Keys API
-        .route("/api/v1/api-keys", post(create_api_key).get(list_api_keys))
         .route(
-            "/api/v1/api-keys/:id",
-            get(get_api_key).delete(delete_api_key),
+            "/api/v1/approvals/:id/votes",
+            post(cast_approval_vote).get(list_approval_votes),
         )
-        .route("/api/v1/api-keys/:id/status", put(update_api_key_status))
         // Tx Broadcasts API
-        .route(
-            "/api/v1/tx-broadcasts",
-            post(create_tx_broadcast).get(list_tx_broadcasts),
-        )
         .route(
             "/api/v1/tx-broadcasts/:id",
             get(get_tx_broadcast).put(update_tx_broadcast),
@@ -535,19 +555,14 @@ pub fn routes(state: Arc<AppState>) -> Router {
             get(get_tx_broadcast_by_tx_hash),
         )
         // Wallets API - 企业级标准：POST端点已完全移除，统一使用 /api/wallets/unified-create
-        .route(
-            "/api/v1/wallets",
-            // ✅ 企业级标准：仅保留GET端点用于列表查询
-            // POST端点已完全移除，请使用: POST /api/wallets/unified-create
-            get(list_wallets).options(preflight_ok),
-        )
+        // ✅ 企业级标准：仅保留GET端点用于列表查询（见 api_key_routes()）
+        // POST端点已完全移除，请使用: POST /api/wallets/unified-create
+        .route("/api/v1/wallets", options(preflight_ok))
         .route(
             "/api/v1/wallets/:id",
-            get(get_wallet).delete(delete_wallet).options(preflight_ok),
+            axum::routing::delete(delete_wallet).options(preflight_ok),
         )
         // Transactions API
-        .route("/api/v1/tx", post(create_tx).get(list_tx))
-        .route("/api/v1/tx/:id", get(get_tx))
         .route(
             "/api/v1/tx/:id/status",
             axum::routing::put(update_tx_status),
@@ -557,7 +572,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/api/v1/network/status",
             get(api_network_status).options(preflight_ok),
         )
-        .route("/api/v1/balance", get(balance).options(preflight_ok))
+        .route("/api/v1/balance", options(preflight_ok))
         // ✅ 企业级标准：资产聚合 API v1版本（需要认证）
         .route(
             "/api/v1/wallets/assets",
@@ -569,6 +584,8 @@ pub fn routes(state: Arc<AppState>) -> Router {
         )
         // 通知系统 API（需要认证）
         .merge(notification_api::create_notification_routes())
+        // 租户出站 Webhook API（端点注册 / 投递日志 / 重投）
+        .merge(tenant_webhook_api::create_tenant_webhook_routes())
         // ✅ 企业级标准：Bridge API v1版本（跨链转移相同代币到不同链）
         .route(
             "/api/v1/bridge/quote",
@@ -588,8 +605,6 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/api/v1/bridge/history",
             axum::routing::get(bridge_enhanced_api::get_bridge_history).options(preflight_ok),
         )
-        // 管理员 API（需要管理员权限）
-        .merge(admin_api::create_admin_routes())
         // ✅ 企业级标准：限价单 API v1版本
         .route(
             "/api/v1/limit-orders",
@@ -603,6 +618,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
                 .delete(limit_order_api::cancel_limit_order)
                 .options(preflight_ok),
         )
+        .route(
+            "/api/v1/limit-orders/:id/submitted",
+            axum::routing::post(limit_order_api::submit_limit_order).options(preflight_ok),
+        )
         // ✅ 企业级标准：法币订单API（需要认证）
         .route(
             "/api/v1/fiat/onramp/orders",
@@ -646,6 +665,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/api/v1/reconciliation/monitoring",
             axum::routing::get(reconciliation_api::get_alerts),
         )
+        .route(
+            "/api/v1/reconciliation/discrepancies",
+            axum::routing::get(reconciliation_api::get_discrepancies),
+        )
         // 审计日志 API（需要认证）
         .route(
             "/api/v1/audit/logs",
@@ -655,6 +678,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/api/v1/audit/compliance/report",
             axum::routing::get(audit_api::generate_compliance_report),
         )
+        .route(
+            "/api/v1/audit/ledger/verify",
+            axum::routing::get(audit_api::verify_ledger_range),
+        )
         // ✅ 企业级标准 V1：提现 API（需要认证）
         .nest("/api/v1/withdrawals", withdrawal_api::routes())
         .nest("/api/v1/withdrawal/review", withdrawal_api::routes())
@@ -681,15 +708,20 @@ pub fn routes(state: Arc<AppState>) -> Router {
         // 应用幂等性检查中间件（在速率限制之前）
         // TODO: 修复幂等性中间件的类型问题
         // .layer(from_fn_with_state(state.clone(), idempotency_middleware))
+        // ✅ 默认拒绝：以上路由不接受 API Key，仅下方合并的 scope 声明路由可用
+        .route_layer(from_fn(reject_api_key))
+        .merge(api_key_routes())
+        // 管理员 API（需要管理员权限，API Key 需 admin:* scope）
+        .merge(admin_api::create_admin_routes())
         // 应用速率限制中间件
         .layer(from_fn_with_state(state.clone(), rate_limit_middleware))
         // 应用CSRF防护中间件（可选，如果需要CSRF保护）
         // 注意：Bearer Token已提供足够保护，CSRF是可选的
         // .layer(from_fn_with_state(state.clone(), csrf_middleware_with_state))
-        // 应用认证中间件
+        // 应用认证中间件（Bearer JWT 或 X-API-Key）
         .layer(from_fn_with_state(
             state.clone(),
-            middleware::jwt_extractor::jwt_extractor_middleware,
+            middleware::auth_middleware,
         ))
         // 安全与观测中间件
         .layer(
@@ -708,7 +740,89 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .with_state(state.clone());
 
     // ✅ 合并所有路由：public_routes（含健康检查）+ protected_routes
-    public_routes.merge(protected_routes).with_state(state)
+    // HTTP 指标层与 Trace Context 挂在合并后的路由上，按路由模板记录（含认证/限流拒绝的请求）
+    public_routes
+        .merge(protected_routes)
+        .layer(from_fn(middleware::trace_context_middleware))
+        .layer(middleware::HttpMetricsLayer)
+        .with_state(state)
+}
+
+/// API Key 可访问的路由（每个方法必须声明 scope）
+///
+/// 其余受保护路由挂了 `reject_api_key`，API Key 调用未在此声明的方法一律 403；
+/// 同一路径下的 JWT 专用方法（如 DELETE /api/v1/wallets/:id）仍注册在主路由表中，合并后按方法分派。
+fn api_key_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/api/v1/transactions",
+            get(simple_list_transactions).route_layer(from_fn(require_scope(scopes::TX_READ))),
+        )
+        .route(
+            "/api/v1/transactions/broadcast",
+            post(broadcast_raw_transaction)
+                .route_layer(from_fn(require_scope(scopes::TX_BROADCAST))),
+        )
+        .route(
+            "/api/v1/transactions/build",
+            post(build_transaction).route_layer(from_fn(require_scope(scopes::TX_BROADCAST))),
+        )
+        .route(
+            "/api/v1/transactions/:hash/status",
+            get(tx_status).route_layer(from_fn(require_scope(scopes::TX_READ))),
+        )
+        .route(
+            "/api/v1/transactions/history",
+            get(get_tx_history).route_layer(from_fn(require_scope(scopes::TX_READ))),
+        )
+        // API Keys API
+        .route(
+            "/api/v1/api-keys",
+            post(create_api_key)
+                .get(list_api_keys)
+                .route_layer(from_fn(require_scope(scopes::ADMIN_ALL))),
+        )
+        .route(
+            "/api/v1/api-keys/:id",
+            get(get_api_key)
+                .delete(delete_api_key)
+                .route_layer(from_fn(require_scope(scopes::ADMIN_ALL))),
+        )
+        .route(
+            "/api/v1/api-keys/:id/status",
+            put(update_api_key_status).route_layer(from_fn(require_scope(scopes::ADMIN_ALL))),
+        )
+        // Tx Broadcasts API
+        .route(
+            "/api/v1/tx-broadcasts",
+            post(create_tx_broadcast)
+                .route_layer(from_fn(require_scope(scopes::TX_BROADCAST)))
+                .get(list_tx_broadcasts.layer(from_fn(require_scope(scopes::TX_READ)))),
+        )
+        // Wallets API
+        .route(
+            "/api/v1/wallets",
+            get(list_wallets).route_layer(from_fn(require_scope(scopes::WALLETS_READ))),
+        )
+        .route(
+            "/api/v1/wallets/:id",
+            get(get_wallet).route_layer(from_fn(require_scope(scopes::WALLETS_READ))),
+        )
+        // Transactions API
+        .route(
+            "/api/v1/tx",
+            post(create_tx)
+                .route_layer(from_fn(require_scope(scopes::TX_BROADCAST)))
+                .get(list_tx.layer(from_fn(require_scope(scopes::TX_READ)))),
+        )
+        .route(
+            "/api/v1/tx/:id",
+            get(get_tx).route_layer(from_fn(require_scope(scopes::TX_READ))),
+        )
+        .route(
+            "/api/v1/balance",
+            get(balance).route_layer(from_fn(require_scope(scopes::WALLETS_READ))),
+        )
 }
 
 async fn preflight_ok(headers: axum::http::HeaderMap) -> Response {
@@ -10,10 +10,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    api::response::{convert_error, success_response},
+    api::{
+        middleware::{auth::AuthInfoExtractor, rbac::require_admin},
+        response::{convert_error, success_response},
+    },
     app_state::AppState,
     error::AppError,
-    service::reconciliation_service::ReconciliationService,
+    service::reconciliation_service::{discrepancies_to_csv, ReconciliationService},
 };
 
 /// POST /api/reconciliation/daily - 执行每日对账
@@ -180,3 +183,61 @@ pub async fn get_alerts(
         alerts: alert_responses,
     })
 }
+
+/// GET /api/v1/reconciliation/discrepancies - 查询/导出对账差异
+#[derive(Debug, Deserialize)]
+pub struct DiscrepanciesQuery {
+    pub date: String, // YYYY-MM-DD format
+    pub provider: Option<String>,
+    /// json（默认）或 csv（财务导出）
+    pub format: Option<String>,
+}
+
+/// 差异覆盖全部租户的服务商订单，仅管理员可查询 / 导出
+pub async fn get_discrepancies(
+    State(state): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Query(query): Query<DiscrepanciesQuery>,
+) -> Result<axum::response::Response, AppError> {
+    use axum::response::IntoResponse;
+
+    require_admin(&auth)?;
+
+    let date = chrono::NaiveDate::parse_from_str(&query.date, "%Y-%m-%d")
+        .map_err(|_| convert_error(StatusCode::BAD_REQUEST, "Invalid date format".to_string()))?;
+
+    let reconciliation_service = ReconciliationService::new(state.pool.clone());
+    let discrepancies = reconciliation_service
+        .list_discrepancies(date, query.provider.as_deref())
+        .await
+        .map_err(|e| convert_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match query.format.as_deref() {
+        Some("csv") => {
+            let filename = format!(
+                "reconciliation-{}-{}.csv",
+                date,
+                query.provider.as_deref().unwrap_or("all")
+            );
+            Ok((
+                [
+                    (
+                        axum::http::header::CONTENT_TYPE,
+                        "text/csv; charset=utf-8".to_string(),
+                    ),
+                    (
+                        axum::http::header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                discrepancies_to_csv(&discrepancies),
+            )
+                .into_response())
+        }
+        None | Some("json") => Ok(success_response(discrepancies)?.into_response()),
+        Some(other) => Err(convert_error(
+            StatusCode::BAD_REQUEST,
+            format!("Unsupported format: {}", other),
+        )),
+    }
+}
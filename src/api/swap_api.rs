@@ -7,6 +7,7 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
+use ethers::types::U256;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 use uuid::Uuid;
@@ -14,9 +15,15 @@ use uuid::Uuid;
 use crate::{
     api::{middleware::jwt_extractor::JwtAuthContext, response::success_response},
     app_state::AppState,
+    domain::token_amount::{parse_decimal_strict, TokenAmount},
     error::AppError,
     repository::SwapTransactionRepository,
-    service::{oneinch_service::OneInchService, token_service::TokenService, wallets},
+    service::{
+        oneinch_service::OneInchService,
+        token_service::TokenService,
+        tx_simulator::{SimulationCall, SimulationResult},
+        wallets,
+    },
 };
 
 /// GET /api/v1/swap/quote - 获取交换报价（简单交换，同链）
@@ -58,16 +65,9 @@ pub async fn get_simple_swap_quote(
         return Err(AppError::bad_request("Token symbols required".to_string()));
     }
 
-    let amount_f64 = query
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
-
-    if amount_f64 <= 0.0 || !amount_f64.is_finite() {
-        return Err(AppError::bad_request(
-            "Amount must be > 0 and finite".to_string(),
-        ));
-    }
+    // ✅ 金额格式校验（严格十进制，拒绝科学计数法/NaN等浮点格式）
+    parse_decimal_strict(&query.amount)
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
 
     // ✅ 使用标准化的链标识符
     let chain_normalized =
@@ -113,8 +113,11 @@ pub async fn get_simple_swap_quote(
             AppError::bad_request(format!("代币 {} 不支持或未启用", query.from))
         })?;
 
-    let amount_wei = (amount_f64 * 10f64.powi(from_decimals as i32)) as u128;
-    let amount_str = amount_wei.to_string();
+    // ✅ U256精确换算为最小单位（超出代币精度的小数位直接拒绝）
+    let amount_units = TokenAmount::parse(&query.amount, from_decimals)
+        .and_then(TokenAmount::ensure_positive)
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
+    let amount_str = amount_units.base_units().to_string();
 
     // 创建1inch服务
     let oneinch_service = OneInchService::new();
@@ -223,16 +226,9 @@ pub async fn execute_simple_swap(
         return Err(AppError::bad_request("Wallet name required".to_string()));
     }
 
-    let amount_f64 = req
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
-
-    if amount_f64 <= 0.0 || !amount_f64.is_finite() {
-        return Err(AppError::bad_request(
-            "Amount must be > 0 and finite".to_string(),
-        ));
-    }
+    // ✅ 金额格式校验（严格十进制，拒绝科学计数法/NaN等浮点格式）
+    parse_decimal_strict(&req.amount)
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
 
     if !req.slippage.is_finite() || req.slippage < 0.0 || req.slippage > 50.0 {
         return Err(AppError::bad_request("Slippage: 0-50%".to_string()));
@@ -305,8 +301,11 @@ pub async fn execute_simple_swap(
         .map_err(|e| AppError::internal(format!("获取代币小数位数失败: {}", e)))?
         .ok_or_else(|| AppError::bad_request(format!("代币 {} 不支持或未启用", req.from_token)))?;
 
-    let amount_wei = (amount_f64 * 10f64.powi(from_decimals as i32)) as u128;
-    let amount_str = amount_wei.to_string();
+    // ✅ U256精确换算为最小单位（超出代币精度的小数位直接拒绝）
+    let amount_units = TokenAmount::parse(&req.amount, from_decimals)
+        .and_then(TokenAmount::ensure_positive)
+        .map_err(|e| AppError::bad_request(format!("Invalid amount: {}", e)))?;
+    let amount_str = amount_units.base_units().to_string();
 
     // ✅ 使用统一的Gas估算服务（预留，当前由1inch API提供）
     let _gas_estimation_service = crate::service::gas_estimation_service::GasEstimationService::new(
@@ -332,6 +331,13 @@ pub async fn execute_simple_swap(
         .await
         .map_err(|e| AppError::rpc_error(format!("无法获取交换交易数据: {}", e)))?;
 
+    // ✅ 签名前模拟：1inch 以 disableEstimate 返回交易，会回滚的交换（授权不足、滑点等）直接拒绝
+    let simulation = simulate_swap_tx(&state, &chain_normalized, &wallet_address, &tx_data).await?;
+    let simulated_gas = simulation
+        .as_ref()
+        .and_then(|s| s.gas_limit)
+        .map(|gas| gas.to_string());
+
     // 创建swap交易记录
     use std::str::FromStr;
 
@@ -340,7 +346,8 @@ pub async fn execute_simple_swap(
     use crate::repository::swap_transaction::SwapTransaction;
 
     let swap_id = format!("swap_{}", Uuid::new_v4().to_string().replace("-", ""));
-    let from_amount_decimal = Decimal::from_str(&req.amount)
+    let from_amount_decimal = amount_units
+        .to_decimal()
         .map_err(|_| AppError::invalid_amount(format!("无效的交换数量: {}", req.amount)))?;
 
     let to_amount_decimal = Decimal::from_str(&quote.to_amount)
@@ -401,7 +408,7 @@ pub async fn execute_simple_swap(
             to: tx_data.to,
             value: tx_data.value,
             data: tx_data.data,
-            gas: tx_data.gas,
+            gas: simulated_gas.or(tx_data.gas),
             gas_price: tx_data.gas_price,
         }),
         needs_approval: None,
@@ -409,6 +416,51 @@ pub async fn execute_simple_swap(
     })
 }
 
+/// 模拟 1inch 交换交易，回滚时返回 TransactionFailed（含解码后的原因）
+async fn simulate_swap_tx(
+    state: &AppState,
+    chain: &str,
+    wallet_address: &str,
+    tx_data: &crate::service::oneinch_service::TransactionData,
+) -> Result<Option<SimulationResult>, AppError> {
+    let parse_value = |value: &str| {
+        let value = value.trim();
+        match value.strip_prefix("0x") {
+            Some(hex_str) => U256::from_str_radix(hex_str, 16).ok(),
+            None => U256::from_dec_str(value).ok(),
+        }
+    };
+
+    let call = SimulationCall {
+        from: wallet_address.parse().map_err(|_| {
+            AppError::invalid_address(format!("无效的钱包地址: {}", wallet_address))
+        })?,
+        to: tx_data.to.parse().map_err(|_| {
+            AppError::rpc_error(format!("1inch 返回无效的目标地址: {}", tx_data.to))
+        })?,
+        value: parse_value(&tx_data.value).ok_or_else(|| {
+            AppError::rpc_error(format!("1inch 返回无效的 value: {}", tx_data.value))
+        })?,
+        data: hex::decode(tx_data.data.trim_start_matches("0x"))
+            .map_err(|e| AppError::rpc_error(format!("1inch 返回无效的调用数据: {}", e)))?
+            .into(),
+    };
+
+    let simulation = state
+        .tx_simulator
+        .preflight(chain, &call)
+        .await
+        .map_err(|e| AppError::service_unavailable(format!("交换交易模拟失败: {}", e)))?;
+    if let Some(reason) = simulation.as_ref().and_then(|s| s.revert_reason.as_ref()) {
+        error!("交换交易模拟回滚: chain={} reason={}", chain, reason);
+        return Err(AppError::transaction_failed(format!(
+            "交换交易模拟回滚: {}",
+            reason
+        )));
+    }
+    Ok(simulation)
+}
+
 /// PUT /api/v1/swap/:swap_id/status - 更新swap交易状态
 #[derive(Debug, Deserialize)]
 pub struct UpdateSwapStatusRequest {
@@ -0,0 +1,193 @@
+//! 租户出站 Webhook API
+//! 端点注册 / 更新 / 删除，投递日志查询与手动重投（需操作员或管理员角色，按租户隔离）
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        middleware::{auth::AuthInfoExtractor, rbac::require_operator_or_admin},
+        response::{success_response, ApiResponse},
+    },
+    app_state::AppState,
+    error::AppError,
+    service::tenant_webhooks::{
+        CreateEndpointInput, TenantWebhookService, UpdateEndpointInput, WebhookDelivery,
+        WebhookDeliveryAttempt, WebhookEndpoint,
+    },
+};
+
+#[derive(Debug, Serialize)]
+pub struct CreatedEndpointResp {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    /// 签名密钥，仅创建时返回
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointListResp {
+    pub items: Vec<WebhookEndpoint>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryListResp {
+    pub items: Vec<WebhookDelivery>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryDetailResp {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    pub attempt_log: Vec<WebhookDeliveryAttempt>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletedResp {
+    pub deleted: bool,
+}
+
+pub async fn create_endpoint(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Json(req): Json<CreateEndpointInput>,
+) -> Result<Json<ApiResponse<CreatedEndpointResp>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let (endpoint, secret) = TenantWebhookService::new(st.pool.clone())
+        .create_endpoint(auth.tenant_id, auth.user_id, req)
+        .await?;
+
+    tracing::info!(
+        endpoint_id = %endpoint.id,
+        tenant_id = %auth.tenant_id,
+        "Webhook endpoint registered"
+    );
+    success_response(CreatedEndpointResp { endpoint, secret })
+}
+
+pub async fn list_endpoints(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+) -> Result<Json<ApiResponse<EndpointListResp>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let items = TenantWebhookService::new(st.pool.clone())
+        .list_endpoints(auth.tenant_id)
+        .await?;
+    success_response(EndpointListResp { items })
+}
+
+pub async fn update_endpoint(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateEndpointInput>,
+) -> Result<Json<ApiResponse<WebhookEndpoint>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let endpoint = TenantWebhookService::new(st.pool.clone())
+        .update_endpoint(auth.tenant_id, id, req)
+        .await?;
+    success_response(endpoint)
+}
+
+pub async fn delete_endpoint(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DeletedResp>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    TenantWebhookService::new(st.pool.clone())
+        .delete_endpoint(auth.tenant_id, id)
+        .await?;
+    success_response(DeletedResp { deleted: true })
+}
+
+pub async fn list_deliveries(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+    Query(q): Query<DeliveryListQuery>,
+) -> Result<Json<ApiResponse<DeliveryListResp>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let items = TenantWebhookService::new(st.pool.clone())
+        .list_deliveries(
+            auth.tenant_id,
+            id,
+            q.status.as_deref(),
+            q.limit.unwrap_or(50).clamp(1, 200),
+            q.offset.unwrap_or(0).max(0),
+        )
+        .await?;
+    success_response(DeliveryListResp { items })
+}
+
+pub async fn get_delivery(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DeliveryDetailResp>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let (delivery, attempt_log) = TenantWebhookService::new(st.pool.clone())
+        .get_delivery(auth.tenant_id, id)
+        .await?;
+    success_response(DeliveryDetailResp {
+        delivery,
+        attempt_log,
+    })
+}
+
+pub async fn redeliver(
+    State(st): State<Arc<AppState>>,
+    AuthInfoExtractor(auth): AuthInfoExtractor,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WebhookDelivery>>, AppError> {
+    require_operator_or_admin(&auth)?;
+
+    let delivery = TenantWebhookService::new(st.pool.clone())
+        .redeliver(auth.tenant_id, id)
+        .await?;
+
+    tracing::info!(
+        delivery_id = %id,
+        operator = %auth.user_id,
+        "Webhook delivery queued for redelivery"
+    );
+    success_response(delivery)
+}
+
+pub fn create_tenant_webhook_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/api/v1/webhooks/endpoints",
+            post(create_endpoint).get(list_endpoints),
+        )
+        .route(
+            "/api/v1/webhooks/endpoints/:id",
+            patch(update_endpoint).delete(delete_endpoint),
+        )
+        .route(
+            "/api/v1/webhooks/endpoints/:id/deliveries",
+            get(list_deliveries),
+        )
+        .route("/api/v1/webhooks/deliveries/:id", get(get_delivery))
+        .route("/api/v1/webhooks/deliveries/:id/redeliver", post(redeliver))
+}
@@ -15,7 +15,12 @@ use crate::{
     api::response::{convert_error, success_response},
     app_state::AppState,
     error::AppError,
-    service::{fiat_service::FiatService, webhook_validator::WebhookValidator},
+    infrastructure::telemetry::TracePropagation,
+    service::{
+        fiat::{alchemypay_client, onramper_client, transfi_client},
+        fiat_service::FiatService,
+        webhook_validator::WebhookValidator,
+    },
 };
 
 /// POST /api/fiat/webhook/:provider - 处理Webhook回调
@@ -42,33 +47,15 @@ pub async fn handle_webhook(
     let body_str = String::from_utf8(body.to_vec())
         .map_err(|e| convert_error(StatusCode::BAD_REQUEST, format!("Invalid UTF-8: {}", e)))?;
 
-    let signature = headers
-        .get("x-webhook-signature")
-        .or_else(|| headers.get("x-signature"))
-        .or_else(|| headers.get("signature"))
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
+    let fiat_service = FiatService::new(
+        state.pool.clone(),
+        state.price_service.clone(),
+        std::env::var("ONRAMPER_API_KEY").ok(),
+        std::env::var("TRANSFI_API_KEY").ok(),
+        std::env::var("TRANSFI_SECRET").ok(),
+    )?;
 
-    if signature.is_empty() {
-        tracing::warn!("Missing webhook signature from provider: {}", provider);
-        return Err(convert_error(
-            StatusCode::UNAUTHORIZED,
-            "Missing signature header".to_string(),
-        ));
-    }
-
-    let validator = WebhookValidator::new();
-    if let Err(e) = validator.verify_signature(&provider, &body_str, signature) {
-        tracing::warn!(
-            "Invalid webhook signature from provider {}: {}",
-            provider,
-            e
-        );
-        return Err(convert_error(
-            StatusCode::UNAUTHORIZED,
-            format!("Invalid signature: {}", e),
-        ));
-    }
+    verify_webhook_request(&fiat_service, &provider, &headers, &body)?;
 
     tracing::info!("✅ Webhook signature verified for provider: {}", provider);
 
@@ -130,13 +117,6 @@ pub async fn handle_webhook(
         }
 
         // 6. 更新订单状态（使用旧版本方法）
-        let fiat_service = FiatService::new(
-            state.pool.clone(),
-            state.price_service.clone(),
-            std::env::var("ONRAMPER_API_KEY").ok(),
-            std::env::var("TRANSFI_API_KEY").ok(),
-            std::env::var("TRANSFI_SECRET").ok(),
-        )?;
         #[allow(deprecated)]
         fiat_service
             .update_order_status_old(
@@ -195,106 +175,70 @@ pub async fn handle_webhook(
     })
 }
 
-/// 验证Webhook签名
-/// 企业级实现：使用HMAC-SHA256验证签名，确保Webhook安全
-#[allow(dead_code)]
-fn verify_webhook_signature(provider: &str, headers: &HeaderMap, body: &[u8]) -> bool {
-    use hmac::{Hmac, Mac};
-    use sha2::Sha256;
-
-    // 从环境变量获取每个服务商的签名密钥
-    let secret_key = match provider {
-        "ramp" => std::env::var("RAMP_WEBHOOK_SECRET").ok(),
-        "moonpay" => std::env::var("MOONPAY_WEBHOOK_SECRET").ok(),
-        "transak" => std::env::var("TRANSAK_WEBHOOK_SECRET").ok(),
-        _ => {
-            tracing::warn!("Unknown provider for signature verification: {}", provider);
-            return false;
-        }
-    };
-
-    // ✅企业级安全：所有环境强制验证
-    let secret_key = match secret_key {
-        Some(key) if !key.is_empty() => key,
-        _ => {
-            tracing::error!(
-                "Webhook secret not configured: {}. Set {}_WEBHOOK_SECRET",
-                provider,
-                provider.to_uppercase()
-            );
-            return false;
-        }
-    };
-
-    // 获取签名头
-    let signature_header = match provider {
-        "ramp" => headers.get("x-ramp-signature"),
-        "moonpay" => headers.get("x-moonpay-signature"),
-        "transak" => headers.get("x-transak-signature"),
-        _ => return false,
-    };
+/// 校验回调签名
+///
+/// 已注册到 `FiatService` 的服务商按各自协议验签（`FiatProvider::verify_webhook`），
+/// 其余服务商（Transak）走 `WebhookValidator`。
+pub(crate) fn verify_webhook_request(
+    fiat_service: &FiatService,
+    provider: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<(), AppError> {
+    let signature = provider_signature_header(provider)
+        .and_then(|header| headers.get(header))
+        .or_else(|| headers.get("x-webhook-signature"))
+        .or_else(|| headers.get("x-signature"))
+        .or_else(|| headers.get("signature"))
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
 
-    let signature = match signature_header {
-        Some(header) => match header.to_str() {
-            Ok(s) => s,
-            Err(_) => {
-                tracing::warn!("Invalid signature header encoding");
-                return false;
-            }
-        },
-        None => {
-            tracing::warn!("Missing signature header for provider: {}", provider);
-            return false;
-        }
-    };
+    if signature.is_empty() {
+        tracing::warn!("Missing webhook signature from provider: {}", provider);
+        return Err(convert_error(
+            StatusCode::UNAUTHORIZED,
+            "Missing signature header".to_string(),
+        ));
+    }
 
-    // 计算HMAC-SHA256
-    type HmacSha256 = Hmac<Sha256>;
-    let mut mac =
-        HmacSha256::new_from_slice(secret_key.as_bytes()).expect("HMAC can take key of any size");
-    mac.update(body);
-    let computed_signature = hex::encode(mac.finalize().into_bytes());
-
-    // 比较签名（使用常量时间比较防止时序攻击）
-    use subtle::ConstantTimeEq;
-    let provided_signature = signature.trim_start_matches("sha256=").trim();
-
-    // 移除可能的0x前缀
-    let provided_signature = provided_signature.trim_start_matches("0x");
-    let computed_signature_hex = computed_signature.trim_start_matches("0x");
-
-    // ✅安全的常量时间比较
-    let provided_bytes = match hex::decode(provided_signature) {
-        Ok(b) => b,
-        Err(_) => {
-            tracing::warn!("Invalid hex in provided signature");
-            return false;
-        }
-    };
-    let computed_bytes = match hex::decode(computed_signature_hex) {
-        Ok(b) => b,
-        Err(_) => {
-            tracing::error!("Failed to decode computed signature");
-            return false;
+    if fiat_service.has_provider(provider) {
+        if !fiat_service.verify_provider_webhook(provider, body, signature) {
+            tracing::warn!("Invalid webhook signature from provider {}", provider);
+            return Err(convert_error(
+                StatusCode::UNAUTHORIZED,
+                "Invalid signature".to_string(),
+            ));
         }
-    };
+        return Ok(());
+    }
 
-    if provided_bytes.len() != computed_bytes.len() {
+    let body_str = std::str::from_utf8(body)
+        .map_err(|e| convert_error(StatusCode::BAD_REQUEST, format!("Invalid UTF-8: {}", e)))?;
+    let validator = WebhookValidator::new();
+    if let Err(e) = validator.verify_signature(provider, body_str, signature) {
         tracing::warn!(
-            "Signature length mismatch: provided={}, computed={}",
-            provided_bytes.len(),
-            computed_bytes.len()
+            "Invalid webhook signature from provider {}: {}",
+            provider,
+            e
         );
-        return false;
+        return Err(convert_error(
+            StatusCode::UNAUTHORIZED,
+            format!("Invalid signature: {}", e),
+        ));
     }
+    Ok(())
+}
 
-    let is_valid = provided_bytes.ct_eq(&computed_bytes).unwrap_u8() == 1;
-
-    if !is_valid {
-        tracing::warn!("Invalid webhook signature for provider: {}", provider);
+/// 各服务商的签名头（未列出的回退到通用签名头）
+fn provider_signature_header(provider: &str) -> Option<&'static str> {
+    match provider {
+        "moonpay" => Some("moonpay-signature-v2"),
+        "ramp" => Some("x-body-signature"),
+        "alchemypay" => Some("x-alchemy-signature"),
+        "onramper" => Some("x-onramper-signature"),
+        "transfi" => Some("x-transfi-signature"),
+        _ => None,
     }
-
-    is_valid
 }
 
 /// 解析不同服务商的Webhook payload
@@ -345,6 +289,45 @@ fn parse_provider_webhook(
                 .map(|a| a.to_string());
             Ok((order_id, status, amount))
         }
+        "alchemypay" => {
+            let order_id = payload["orderNo"]
+                .as_str()
+                .ok_or("Missing orderNo")?
+                .to_string();
+            let status = payload["status"].as_str().ok_or("Missing status")?;
+            let amount = payload["amount"].as_str().map(|s| s.to_string());
+            Ok((
+                order_id,
+                alchemypay_client::normalize_status(status).to_string(),
+                amount,
+            ))
+        }
+        "onramper" => {
+            let order_id = payload["orderId"]
+                .as_str()
+                .ok_or("Missing orderId")?
+                .to_string();
+            let status = payload["status"].as_str().ok_or("Missing status")?;
+            let amount = payload["amount"].as_str().map(|s| s.to_string());
+            Ok((
+                order_id,
+                onramper_client::normalize_status(status).to_string(),
+                amount,
+            ))
+        }
+        "transfi" => {
+            let order_id = payload["orderId"]
+                .as_str()
+                .ok_or("Missing orderId")?
+                .to_string();
+            let status = payload["status"].as_str().ok_or("Missing status")?;
+            let amount = payload["amount"].as_str().map(|s| s.to_string());
+            Ok((
+                order_id,
+                transfi_client::normalize_status(status).to_string(),
+                amount,
+            ))
+        }
         _ => Err(format!("Unknown provider: {}", provider)),
     }
 }
@@ -566,7 +549,7 @@ async fn sync_wallet_balance_after_order(
             .timeout(std::time::Duration::from_secs(5))
             .build()
         {
-            if let Ok(resp) = client.post(&rpc).json(&serde_json::json!({"jsonrpc":"2.0","method":"eth_getBalance","params":[address,"latest"],"id":1})).send().await {
+            if let Ok(resp) = client.post(&rpc).json(&serde_json::json!({"jsonrpc":"2.0","method":"eth_getBalance","params":[address,"latest"],"id":1})).with_trace_context().send().await {
                 if let Ok(json) = resp.json::<serde_json::Value>().await {
                     if let Some(hex) = json.get("result").and_then(|r| r.as_str()) {
                         if let Ok(wei) = u128::from_str_radix(hex.trim_start_matches("0x"), 16) {
@@ -581,3 +564,111 @@ async fn sync_wallet_balance_after_order(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use tower::ServiceExt as _;
+
+    use super::*;
+    use crate::service::{
+        fiat::{AlchemyPayClient, FiatProvider, OnramperClient},
+        price_service::PriceService,
+    };
+
+    fn hmac_hex(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 与 `handle_webhook` 相同的验签阶段，挂在真实回调路径上
+    async fn verify_stage(
+        State(fiat_service): State<Arc<FiatService>>,
+        Path(provider): Path<String>,
+        headers: HeaderMap,
+        body: axum::body::Bytes,
+    ) -> Result<StatusCode, AppError> {
+        verify_webhook_request(&fiat_service, &provider, &headers, &body)?;
+        Ok(StatusCode::OK)
+    }
+
+    fn app() -> Router {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/test").unwrap();
+        let price_service = Arc::new(PriceService::new(pool.clone(), None));
+        let providers: Vec<Arc<dyn FiatProvider>> = vec![
+            Arc::new(
+                AlchemyPayClient::new("app", "api_secret")
+                    .unwrap()
+                    .with_webhook_secret("alchemy_whsec"),
+            ),
+            Arc::new(
+                OnramperClient::new("onramper_key")
+                    .unwrap()
+                    .with_webhook_secret("onramper_whsec"),
+            ),
+        ];
+        let fiat_service = FiatService::with_providers(pool, price_service, providers);
+        Router::new()
+            .route("/api/fiat/webhook/:provider", post(verify_stage))
+            .with_state(Arc::new(fiat_service))
+    }
+
+    async fn post_webhook(
+        provider: &str,
+        header: &str,
+        signature: &str,
+        body: &[u8],
+    ) -> StatusCode {
+        let req = Request::builder()
+            .method("POST")
+            .uri(format!("/api/fiat/webhook/{}", provider))
+            .header(header, signature)
+            .body(Body::from(body.to_vec()))
+            .unwrap();
+        app().oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_alchemypay_webhook_is_verified_by_provider_client() {
+        let body = br#"{"orderNo":"o1","status":"FINISHED","amount":"100"}"#;
+
+        let signed = hmac_hex("alchemy_whsec", body);
+        assert_eq!(
+            post_webhook("alchemypay", "X-Alchemy-Signature", &signed, body).await,
+            StatusCode::OK
+        );
+
+        let forged = hmac_hex("api_secret", body);
+        assert_eq!(
+            post_webhook("alchemypay", "X-Alchemy-Signature", &forged, body).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_onramper_webhook_reaches_provider_client() {
+        let body = br#"{"orderId":"o2","status":"completed"}"#;
+        let signed = hmac_hex("onramper_whsec", body);
+        assert_eq!(
+            post_webhook("onramper", "X-Onramper-Signature", &signed, body).await,
+            StatusCode::OK
+        );
+        // 未配置的服务商不会被当作直连服务商放行
+        assert_eq!(
+            post_webhook("transfi", "X-TransFi-Signature", &signed, body).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn test_parse_alchemypay_payload_normalizes_status() {
+        let payload = serde_json::json!({"orderNo": "o1", "status": "FINISHED", "amount": "100"});
+        let (order_id, status, amount) = parse_provider_webhook("alchemypay", &payload).unwrap();
+        assert_eq!(order_id, "o1");
+        assert_eq!(status, "completed");
+        assert_eq!(amount.as_deref(), Some("100"));
+    }
+}
@@ -1,13 +1,15 @@
 //! 提现API
 //! 企业级实现：三级风控 + 双锁解密 + 审计日志
 
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 
 use axum::{
     extract::State,
+    http::HeaderMap,
     routing::{get, post},
     Json, Router,
 };
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -19,7 +21,12 @@ use crate::{
     },
     app_state::AppState,
     error::AppError,
-    service::withdrawal_risk_control::{WithdrawalRequest, WithdrawalRiskControl},
+    service::{
+        approvals::{park_operation, resource_types, ParkedOperation},
+        risk_rule_engine::ApprovalSpec,
+        token_service::TokenService,
+        withdrawal_risk_control::{WithdrawalRequest, WithdrawalRiskControl},
+    },
 };
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -52,6 +59,8 @@ pub struct CreateWithdrawalResponse {
     pub status: String,
     pub risk_level: String,
     pub requires_manual_review: bool,
+    /// 人工审核时关联的多方审批
+    pub approval_id: Option<String>,
     pub estimated_completion_time: Option<String>,
 }
 
@@ -92,6 +101,7 @@ pub fn routes() -> Router<Arc<AppState>> {
 pub async fn create_withdrawal(
     State(state): State<Arc<AppState>>,
     auth: AuthInfoExtractor,
+    headers: HeaderMap,
     Json(req): Json<CreateWithdrawalRequest>,
 ) -> Result<Json<ApiResponse<CreateWithdrawalResponse>>, AppError> {
     // 1. 验证钱包所有权
@@ -111,17 +121,35 @@ pub async fn create_withdrawal(
         return Err(AppError::forbidden("Wallet not owned by user".to_string()));
     }
 
-    // 2. 解析金额并转换为USD
-    let amount_f64 = req
-        .amount
-        .parse::<f64>()
-        .map_err(|_| AppError::bad_request("Invalid amount".to_string()))?;
+    // 2. 解析金额（按原生代币精度精确换算）并转换为USD
+    let chain = chain_symbol.clone().unwrap_or_else(|| req.chain.clone());
+    let chain_config = crate::utils::chain_normalizer::get_chain_config(&chain)
+        .map_err(|e| AppError::chain_not_supported(e.to_string()))?;
+    let amount = TokenService::new(state.pool.clone())
+        .parse_amount(
+            chain_config.symbol,
+            chain_config.chain_id as u64,
+            &req.amount,
+        )
+        .await
+        .map_err(|e| AppError::bad_request(e.to_string()))?;
 
-    // TODO: 从PriceService获取实时价格转换为USD
-    let amount_usd = amount_f64; // 简化实现
+    // 预言机价格换算 USD：无可信报价时拒绝，不以过期/缺省价格绕过风控
+    let price_usd = state
+        .price_service
+        .get_price_decimal(chain_config.symbol)
+        .await
+        .map_err(|e| AppError::service_unavailable(format!("价格不可用，暂无法评估风控: {}", e)))?;
+    let amount_usd = amount
+        .to_decimal()
+        .ok()
+        .and_then(|a| a.checked_mul(price_usd))
+        .and_then(|usd| usd.to_f64())
+        .ok_or_else(|| AppError::invalid_amount("Amount out of range".to_string()))?;
 
     // 3. 执行风控检查
     let risk_service = WithdrawalRiskControl::new(state.pool.clone());
+    let (client_ip, country) = client_geo(&headers).await;
 
     let risk_request = WithdrawalRequest {
         user_id: auth.0.user_id,
@@ -130,6 +158,8 @@ pub async fn create_withdrawal(
         to_address: req.to_address.clone(),
         amount_usd,
         wallet_id,
+        client_ip: client_ip.clone(),
+        country: country.clone(),
     };
 
     let decision = risk_service
@@ -143,8 +173,8 @@ pub async fn create_withdrawal(
         .await
         .ok();
 
-    // 5. 根据风控决策处理
-    if !decision.allow {
+    // 5. 根据风控决策处理（人工审核的请求挂起等待审批，不直接拒绝）
+    if !decision.allow && !decision.requires_manual_review {
         return Err(AppError::forbidden(decision.suggestion));
     }
 
@@ -156,25 +186,70 @@ pub async fn create_withdrawal(
         "approved"
     };
 
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|e| AppError::database_error(format!("Failed to begin transaction: {}", e)))?;
+
     let _ = sqlx::query(
         "INSERT INTO withdrawal_requests
-         (id, user_id, tenant_id, wallet_id, chain, to_address, amount, amount_usd, status, risk_level)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+         (id, user_id, tenant_id, wallet_id, chain, to_address, amount, amount_usd, status, risk_level,
+          client_ip, country)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"
     )
     .bind(withdrawal_id)
     .bind(auth.0.user_id)
     .bind(auth.0.tenant_id)
     .bind(wallet_id_ret)
-    .bind(chain_symbol.unwrap_or_default())
+    .bind(chain_symbol.clone().unwrap_or_default())
     .bind(&req.to_address)
-    .bind(amount_f64.to_string())
+    .bind(amount.to_human_string())
     .bind(amount_usd)
     .bind(status)
     .bind(format!("{:?}", decision.risk_level))
-    .execute(&state.pool)
+    .bind(&client_ip)
+    .bind(&country)
+    .execute(&mut *tx)
     .await
     .map_err(|e| AppError::database_error(format!("Failed to create withdrawal: {}", e)))?;
 
+    // 6.1 人工审核：同一事务内挂起并创建 M-of-N 审批，审批通过/拒绝/过期后自动流转
+    let approval = if decision.requires_manual_review {
+        let spec = decision
+            .approval
+            .clone()
+            .unwrap_or_else(ApprovalSpec::platform_default);
+        let approval = park_operation(
+            &mut tx,
+            ParkedOperation {
+                tenant_id: auth.0.tenant_id,
+                requester: auth.0.user_id,
+                resource_type: resource_types::WITHDRAWAL,
+                resource_id: withdrawal_id,
+                policy_id: decision.policies.first().map(|p| p.policy_id),
+                payload: serde_json::json!({
+                    "wallet_id": wallet_id_ret,
+                    "chain": chain_symbol.unwrap_or_default(),
+                    "to_address": req.to_address,
+                    "amount": amount.to_human_string(),
+                    "amount_usd": amount_usd,
+                    "risk_level": format!("{:?}", decision.risk_level),
+                    "triggered_rules": decision.triggered_rules,
+                }),
+            },
+            &spec,
+        )
+        .await?;
+        Some((approval, spec))
+    } else {
+        None
+    };
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database_error(format!("Failed to commit withdrawal: {}", e)))?;
+
     // 7. 如果自动通过，立即处理
     if !decision.requires_manual_review {
         // TODO: 异步任务处理提现
@@ -183,8 +258,11 @@ pub async fn create_withdrawal(
         // - 广播到链上
     }
 
-    let estimated_completion = if decision.requires_manual_review {
-        Some("Manual review required, typically within 24 hours".to_string())
+    let estimated_completion = if let Some((_, spec)) = &approval {
+        Some(format!(
+            "Awaiting {} approval(s), auto-rejected if undecided within {} hours",
+            spec.required_approvals, spec.ttl_hours
+        ))
     } else {
         Some("5-30 minutes depending on network".to_string())
     };
@@ -194,10 +272,89 @@ pub async fn create_withdrawal(
         status: status.to_string(),
         risk_level: format!("{:?}", decision.risk_level),
         requires_manual_review: decision.requires_manual_review,
+        approval_id: approval.map(|(a, _)| a.id.to_string()),
         estimated_completion_time: estimated_completion,
     })
 }
 
+/// 服务前的可信代理（TRUSTED_PROXY），决定客户端 IP 与国家的可信来源
+///
+/// 客户端可以伪造任意请求头，只有代理写入（覆盖）的头才可信：
+/// - `fly`：`Fly-Client-IP` 由 fly-proxy 写入，国家按该 IP 做 GeoIP 推断
+/// - `cloudflare`：`CF-Connecting-IP` 与国家头（GEOIP_COUNTRY_HEADER，默认 `CF-IPCountry`）由边缘注入
+/// - 未配置：取 `X-Forwarded-For` 最后一跳（最近一层代理追加），国家按 GeoIP 推断，不读取国家头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrustedProxy {
+    Fly,
+    Cloudflare,
+    None,
+}
+
+impl TrustedProxy {
+    fn from_env() -> Self {
+        match std::env::var("TRUSTED_PROXY")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "fly" => Self::Fly,
+            "cloudflare" => Self::Cloudflare,
+            _ => Self::None,
+        }
+    }
+}
+
+/// 从可信来源提取客户端 IP，以及（仅 Cloudflare 模式下）边缘注入的国家
+fn trusted_client_geo(
+    headers: &HeaderMap,
+    proxy: TrustedProxy,
+) -> (Option<IpAddr>, Option<String>) {
+    let header_str = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let parse_ip = |v: &str| v.trim().parse::<IpAddr>().ok();
+
+    match proxy {
+        TrustedProxy::Fly => (header_str("Fly-Client-IP").and_then(|v| parse_ip(&v)), None),
+        TrustedProxy::Cloudflare => {
+            let country_header = std::env::var("GEOIP_COUNTRY_HEADER")
+                .unwrap_or_else(|_| "CF-IPCountry".to_string());
+            let country = header_str(&country_header)
+                .map(|c| c.to_uppercase())
+                .filter(|c| c.len() == 2 && c != "XX"); // XX = 未知
+            (
+                header_str("CF-Connecting-IP").and_then(|v| parse_ip(&v)),
+                country,
+            )
+        }
+        TrustedProxy::None => (
+            header_str("X-Forwarded-For").and_then(|v| v.rsplit(',').next().and_then(parse_ip)),
+            None,
+        ),
+    }
+}
+
+/// 客户端 IP 与国家：国家头不可信时按可信 IP 做 GeoIP 推断（查询失败则为空，由风控规则按未知国家处理）
+async fn client_geo(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let (client_ip, country) = trusted_client_geo(headers, TrustedProxy::from_env());
+
+    let country = match (country, client_ip) {
+        (Some(country), _) => Some(country),
+        (None, Some(ip)) => crate::utils::geoip::lookup_country(ip)
+            .await
+            .map_err(|e| tracing::debug!(ip = %ip, error = %e, "GeoIP lookup failed"))
+            .ok(),
+        (None, None) => None,
+    };
+
+    (client_ip.map(|ip| ip.to_string()), country)
+}
+
 /// GET /api/withdrawals/status/:id
 ///
 /// 查询提现状态
@@ -280,3 +437,44 @@ pub async fn list_user_withdrawals(
 
     success_response(responses)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spoofed_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        // 客户端自行携带的头
+        headers.insert("CF-IPCountry", "US".parse().unwrap());
+        headers.insert("CF-Connecting-IP", "8.8.8.8".parse().unwrap());
+        headers.insert("X-Forwarded-For", "1.2.3.4, 203.0.113.9".parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_spoofed_geo_headers_are_ignored() {
+        let mut headers = spoofed_headers();
+        headers.insert("Fly-Client-IP", "198.51.100.7".parse().unwrap());
+
+        // fly.io：只信任 fly-proxy 写入的 Fly-Client-IP，国家头忽略（由 GeoIP 推断）
+        let (ip, country) = trusted_client_geo(&headers, TrustedProxy::Fly);
+        assert_eq!(ip, Some("198.51.100.7".parse().unwrap()));
+        assert_eq!(country, None);
+
+        // 未配置可信代理：X-Forwarded-For 取最后一跳，首个（客户端伪造）条目与国家头均忽略
+        let (ip, country) = trusted_client_geo(&headers, TrustedProxy::None);
+        assert_eq!(ip, Some("203.0.113.9".parse().unwrap()));
+        assert_eq!(country, None);
+
+        // 无 Fly-Client-IP 时不回退到客户端头
+        let (ip, _) = trusted_client_geo(&spoofed_headers(), TrustedProxy::Fly);
+        assert_eq!(ip, None);
+    }
+
+    #[test]
+    fn test_edge_country_header_trusted_behind_cloudflare() {
+        let (ip, country) = trusted_client_geo(&spoofed_headers(), TrustedProxy::Cloudflare);
+        assert_eq!(ip, Some("8.8.8.8".parse().unwrap()));
+        assert_eq!(country.as_deref(), Some("US"));
+    }
+}
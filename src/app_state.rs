@@ -24,10 +24,18 @@ pub struct AppState {
     pub cross_chain_config: Arc<crate::config::CrossChainConfig>,
     /// ✅ 企业级优化：Gas 估算器单例（配置只读取一次，避免重复警告）
     pub gas_estimator: Arc<crate::service::gas_estimator::GasEstimator>,
+    /// ✅ 签名前交易模拟（回滚解码 + Gas 余量）
+    pub tx_simulator: Arc<crate::service::tx_simulator::TxSimulator>,
     /// ✅ 生产级：完整配置（包含支付网关配置）
     pub config: Arc<crate::config::Config>,
-    /// ✅ 生产级：实时价格服务（CoinGecko + Redis缓存）
+    /// ✅ 生产级：多源价格预言机服务（中位数聚合 + 时效保护）
     pub price_service: Arc<crate::service::price_service::PriceService>,
+    /// ✅ 跨链桥报价聚合（Wormhole / LayerZero / Axelar 并行询价）
+    pub bridge_aggregator: Arc<crate::service::bridge_aggregator::BridgeAggregator>,
+    /// ✅ EVM nonce 管理（进程内缓存，全局共享一个实例；广播成功后记录占用的 nonce）
+    pub nonce_manager: Arc<crate::service::nonce_manager::NonceManager>,
+    /// ✅ 统一交易构建器（nonce / 费用 / 模拟 / Solana 数据源均已接入）
+    pub tx_builder: Arc<crate::service::transaction_builder::TransactionBuilder>,
 }
 
 impl AppState {
@@ -89,14 +97,46 @@ impl AppState {
         ));
         tracing::info!("✅ Gas estimator initialized with cached configuration");
 
-        // ✅ 生产级：初始化价格服务（CoinGecko + Redis缓存）
-        let price_service = Arc::new(crate::service::price_service::PriceService::new(
+        let tx_simulator = Arc::new(crate::service::tx_simulator::TxSimulator::new(
+            rpc_selector.clone(),
+        ));
+
+        // ✅ 生产级：初始化价格服务（手动覆盖 + CoinGecko + Chainlink + Uniswap TWAP）
+        let price_service = Arc::new(
+            crate::service::price_service::PriceService::new(
+                pool.clone(),
+                Some(
+                    std::env::var("REDIS_URL")
+                        .unwrap_or_else(|_| "redis://localhost:6379".to_string()),
+                ),
+            )
+            .with_onchain_sources(rpc_selector.clone()),
+        );
+        tracing::info!("✅ Price service initialized with multi-source oracle");
+
+        let bridge_aggregator = Arc::new(
+            crate::service::bridge_aggregator::BridgeAggregator::from_env()
+                .with_price_service(price_service.clone()),
+        );
+
+        let nonce_manager = Arc::new(crate::service::nonce_manager::NonceManager::new(
             pool.clone(),
-            Some(
-                std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string()),
-            ),
+            distributed_lock.clone(),
         ));
-        tracing::info!("✅ Price service initialized with CoinGecko API");
+
+        let tx_builder = Arc::new(
+            crate::service::transaction_builder::TransactionBuilder::new()
+                .with_evm_services(blockchain_client.clone(), gas_estimator.clone())
+                .with_solana_source(Arc::new(
+                    crate::service::solana_message_builder::JsonRpcSolanaSource::new(
+                        blockchain_config.solana_rpc_url.clone(),
+                    ),
+                ))
+                .with_token_service(Arc::new(crate::service::token_service::TokenService::new(
+                    pool.clone(),
+                )))
+                .with_simulator(tx_simulator.clone()),
+        );
 
         Ok(Self {
             pool,
@@ -113,8 +153,12 @@ impl AppState {
             blockchain_config,
             cross_chain_config,
             gas_estimator,
+            tx_simulator,
             config,
             price_service,
+            bridge_aggregator,
+            nonce_manager,
+            tx_builder,
         })
     }
 
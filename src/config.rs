@@ -77,6 +77,36 @@ pub struct LoggingConfig {
     pub log_file_path: Option<String>,
     pub max_file_size_mb: u64,
     pub max_files: u32,
+    /// OpenTelemetry 链路追踪导出（默认关闭）
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+}
+
+/// OTLP 追踪导出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtlpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP/HTTP 收集器地址（不含 `/v1/traces`）
+    #[serde(default = "default_otlp_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_otlp_service_name")]
+    pub service_name: String,
+    /// 采样比例（0.0 - 1.0），父 span 已采样时始终跟随
+    #[serde(default = "default_otlp_sample_ratio")]
+    pub sample_ratio: f64,
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4318".into()
+}
+
+fn default_otlp_service_name() -> String {
+    "ironcore".into()
+}
+
+fn default_otlp_sample_ratio() -> f64 {
+    1.0
 }
 
 /// 监控配置
@@ -221,6 +251,26 @@ impl Default for LoggingConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(10),
+            otlp: OtlpConfig::default(),
+        }
+    }
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: std::env::var("OTEL_ENABLED")
+                .ok()
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            endpoint: std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .unwrap_or_else(|_| default_otlp_endpoint()),
+            service_name: std::env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| default_otlp_service_name()),
+            sample_ratio: std::env::var("OTEL_TRACES_SAMPLER_ARG")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_else(default_otlp_sample_ratio),
         }
     }
 }
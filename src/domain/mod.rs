@@ -6,6 +6,7 @@ pub mod chain_config;
 pub mod derivation;
 pub mod derivation_path_validator; // ✅ P1: 派生路径验证器
 pub mod multi_chain_wallet;
+pub mod token_amount; // ✅ U256精确金额
 pub mod transaction_status;
 pub mod wallet_non_custodial; // ✅ 非托管钱包领域模型
 
@@ -15,4 +16,5 @@ pub use chain_config::{AddressFormat, ChainConfig, ChainRegistry, CurveType};
 pub use derivation::{DerivationStrategy, DerivationStrategyFactory, DerivedWallet};
 pub use derivation_path_validator::DerivationPathValidator;
 pub use multi_chain_wallet::{CreateWalletRequest, CreateWalletResponse, MultiChainWalletService};
+pub use token_amount::{AmountError, TokenAmount};
 pub use transaction_status::TransactionStatus;
@@ -0,0 +1,319 @@
+//! 代币金额领域模型
+//!
+//! 企业级实现：基于 U256 的精确金额表示，替代 f64 / u128 换算
+//! - 人类可读十进制字符串 ⇄ 最小单位（wei / lamports / nanoton ...）
+//! - 严格语法：仅接受 `123`、`123.456` 形式，拒绝科学计数法、符号、NaN/inf
+//! - 小数位超过代币精度时报错，不做静默截断
+
+use std::{fmt, str::FromStr};
+
+use ethers::types::U256;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 支持的最大小数位数（U256 可容纳 77 位十进制数字）
+pub const MAX_DECIMALS: u32 = 36;
+
+/// 金额解析/运算错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AmountError {
+    #[error("amount is empty")]
+    Empty,
+    #[error("invalid amount format: {0}")]
+    InvalidFormat(String),
+    #[error("too many decimal places: {got} (token supports {max})")]
+    TooManyDecimals { got: u32, max: u32 },
+    #[error("unsupported token decimals: {0}")]
+    UnsupportedDecimals(u32),
+    #[error("amount overflow")]
+    Overflow,
+    #[error("amount must be greater than zero")]
+    NotPositive,
+}
+
+/// 精确代币金额（最小单位 + 精度）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenAmount {
+    base_units: U256,
+    decimals: u32,
+}
+
+impl TokenAmount {
+    /// 解析人类可读金额（如 "1.5" + 18 位精度 → 1500000000000000000）
+    pub fn parse(value: &str, decimals: u32) -> Result<Self, AmountError> {
+        check_decimals(decimals)?;
+        let (int_part, frac_part) = split_decimal(value)?;
+
+        let frac_len = frac_part.len() as u32;
+        if frac_len > decimals {
+            return Err(AmountError::TooManyDecimals {
+                got: frac_len,
+                max: decimals,
+            });
+        }
+
+        let digits = format!(
+            "{}{}{}",
+            int_part,
+            frac_part,
+            "0".repeat((decimals - frac_len) as usize)
+        );
+        let base_units = U256::from_dec_str(&digits).map_err(|_| AmountError::Overflow)?;
+
+        Ok(Self {
+            base_units,
+            decimals,
+        })
+    }
+
+    /// 解析最小单位整数字符串（如 wei）
+    pub fn parse_base_units(value: &str, decimals: u32) -> Result<Self, AmountError> {
+        check_decimals(decimals)?;
+        let (int_part, frac_part) = split_decimal(value)?;
+        if !frac_part.is_empty() {
+            return Err(AmountError::InvalidFormat(format!(
+                "base units must be an integer: {}",
+                value.trim()
+            )));
+        }
+
+        let base_units = U256::from_dec_str(int_part).map_err(|_| AmountError::Overflow)?;
+        Ok(Self {
+            base_units,
+            decimals,
+        })
+    }
+
+    pub fn from_base_units(base_units: U256, decimals: u32) -> Self {
+        Self {
+            base_units,
+            decimals,
+        }
+    }
+
+    pub fn zero(decimals: u32) -> Self {
+        Self::from_base_units(U256::zero(), decimals)
+    }
+
+    pub fn base_units(&self) -> U256 {
+        self.base_units
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.base_units.is_zero()
+    }
+
+    /// 要求金额大于零
+    pub fn ensure_positive(self) -> Result<Self, AmountError> {
+        if self.is_zero() {
+            return Err(AmountError::NotPositive);
+        }
+        Ok(self)
+    }
+
+    /// 人类可读字符串（去除末尾多余的0）
+    pub fn to_human_string(&self) -> String {
+        let digits = self.base_units.to_string();
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return digits;
+        }
+
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+        let frac_part = frac_part.trim_end_matches('0');
+        if frac_part.is_empty() {
+            int_part.to_string()
+        } else {
+            format!("{}.{}", int_part, frac_part)
+        }
+    }
+
+    /// 转换为 `Decimal`（用于 NUMERIC 列），超出 Decimal 精度时报错
+    pub fn to_decimal(&self) -> Result<Decimal, AmountError> {
+        Decimal::from_str_exact(&self.to_human_string()).map_err(|_| AmountError::Overflow)
+    }
+
+    /// 转换为 f64（仅用于展示/风控估值等可接受精度损失的场景）
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.to_human_string().parse::<f64>().unwrap_or(f64::MAX)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Result<Self, AmountError> {
+        self.ensure_same_decimals(other)?;
+        self.base_units
+            .checked_add(other.base_units)
+            .map(|v| Self::from_base_units(v, self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, AmountError> {
+        self.ensure_same_decimals(other)?;
+        self.base_units
+            .checked_sub(other.base_units)
+            .map(|v| Self::from_base_units(v, self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// 按基点计算比例金额（向下取整），如 100bp = 1%
+    pub fn checked_mul_bp(&self, bp: u32) -> Result<Self, AmountError> {
+        self.base_units
+            .checked_mul(U256::from(bp))
+            .map(|v| Self::from_base_units(v / U256::from(10_000u32), self.decimals))
+            .ok_or(AmountError::Overflow)
+    }
+
+    fn ensure_same_decimals(&self, other: &Self) -> Result<(), AmountError> {
+        if self.decimals != other.decimals {
+            return Err(AmountError::InvalidFormat(format!(
+                "decimals mismatch: {} vs {}",
+                self.decimals, other.decimals
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_human_string())
+    }
+}
+
+impl PartialOrd for TokenAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (self.decimals == other.decimals).then(|| self.base_units.cmp(&other.base_units))
+    }
+}
+
+/// 严格解析十进制数（如价格），拒绝科学计数法和浮点特殊值
+pub fn parse_decimal_strict(value: &str) -> Result<Decimal, AmountError> {
+    split_decimal(value)?;
+    Decimal::from_str(value.trim()).map_err(|_| AmountError::Overflow)
+}
+
+fn check_decimals(decimals: u32) -> Result<(), AmountError> {
+    if decimals > MAX_DECIMALS {
+        return Err(AmountError::UnsupportedDecimals(decimals));
+    }
+    Ok(())
+}
+
+/// 按严格语法拆分整数和小数部分
+fn split_decimal(value: &str) -> Result<(&str, &str), AmountError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(AmountError::Empty);
+    }
+
+    let (int_part, frac_part) = match value.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, frac_part),
+        None => (value, ""),
+    };
+
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if int_part.is_empty()
+        || !all_digits(int_part)
+        || !all_digits(frac_part)
+        || (value.contains('.') && frac_part.is_empty())
+    {
+        return Err(AmountError::InvalidFormat(value.to_string()));
+    }
+
+    Ok((int_part, frac_part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_human_amount() {
+        let amount = TokenAmount::parse("1.5", 18).unwrap();
+        assert_eq!(
+            amount.base_units(),
+            U256::from_dec_str("1500000000000000000").unwrap()
+        );
+        assert_eq!(amount.to_human_string(), "1.5");
+
+        let usdt = TokenAmount::parse("0.000001", 6).unwrap();
+        assert_eq!(usdt.base_units(), U256::from(1u64));
+        assert_eq!(usdt.to_human_string(), "0.000001");
+    }
+
+    #[test]
+    fn test_parse_beyond_u128() {
+        // 10^21 个 18 位精度代币 = 10^39 wei，超出 u128
+        let amount = TokenAmount::parse("1000000000000000000000", 18).unwrap();
+        assert_eq!(amount.base_units(), U256::exp10(39));
+        assert_eq!(amount.to_human_string(), "1000000000000000000000");
+    }
+
+    #[test]
+    fn test_rejects_lossy_formats() {
+        for input in [
+            "1e18", "NaN", "inf", "-1", "+1", "1.", ".5", "1,5", "0x10", "",
+        ] {
+            assert!(TokenAmount::parse(input, 18).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_rejects_excess_precision() {
+        assert_eq!(
+            TokenAmount::parse("0.0000001", 6),
+            Err(AmountError::TooManyDecimals { got: 7, max: 6 })
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let huge = "9".repeat(80);
+        assert_eq!(
+            TokenAmount::parse_base_units(&huge, 18),
+            Err(AmountError::Overflow)
+        );
+        let max = TokenAmount::from_base_units(U256::MAX, 18);
+        assert_eq!(max.checked_mul_bp(100), Err(AmountError::Overflow));
+    }
+
+    #[test]
+    fn test_bp_and_arithmetic() {
+        let amount = TokenAmount::parse("123.456789", 6).unwrap();
+        assert_eq!(
+            amount.checked_mul_bp(30).unwrap().to_human_string(),
+            "0.37037"
+        );
+
+        let fee = TokenAmount::parse("0.456789", 6).unwrap();
+        assert_eq!(amount.checked_sub(&fee).unwrap().to_human_string(), "123");
+        assert!(fee.checked_sub(&amount).is_err());
+        assert!(amount
+            .checked_add(&TokenAmount::parse("1", 18).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        let amount = TokenAmount::parse("0.000000000000000001", 18).unwrap();
+        assert_eq!(
+            amount.to_decimal().unwrap(),
+            Decimal::from_str("0.000000000000000001").unwrap()
+        );
+        assert_eq!(TokenAmount::zero(6).to_human_string(), "0");
+        assert!(TokenAmount::zero(6).ensure_positive().is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal_strict() {
+        assert_eq!(
+            parse_decimal_strict("2500.25").unwrap(),
+            Decimal::from_str("2500.25").unwrap()
+        );
+        assert!(parse_decimal_strict("2.5e3").is_err());
+    }
+}
@@ -4,6 +4,8 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuditEvent {
     pub event: String,
@@ -14,6 +16,31 @@ pub struct AuditEvent {
     pub ts: String,
 }
 
+impl AuditEvent {
+    /// immudb 中的键：`audit:{tenant_id}:{event}:{ts}`
+    pub fn immu_key(&self) -> String {
+        format!("audit:{}:{}:{}", self.tenant_id, self.event, self.ts)
+    }
+}
+
+/// 解析 immudb verifiable/get 响应中的事件（value 可能为原文或 base64）
+fn parse_event_value(json: &serde_json::Value) -> Option<AuditEvent> {
+    use base64::Engine;
+
+    let value = json
+        .get("entry")
+        .and_then(|e| e.get("value"))
+        .or_else(|| json.get("value"))
+        .and_then(|v| v.as_str())?;
+
+    serde_json::from_str(value).ok().or_else(|| {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .ok()?;
+        serde_json::from_slice(&decoded).ok()
+    })
+}
+
 pub struct ImmuCtx {
     pub addr: String,
     pub user: String,
@@ -33,11 +60,29 @@ impl ImmuCtx {
 
     /// 写入审计事件到 immudb（使用 HTTP API）
     /// 注意：这是简化实现，生产环境应使用官方 gRPC 客户端
+    ///
+    /// 最佳努力：immudb 不可用时返回基于内容的哈希，不阻断主流程。
+    /// 需要确认写入成功的场景（如审计账本检查点锚定）使用 `try_write_event`
     pub async fn write_event(&self, ev: &AuditEvent) -> Result<String, anyhow::Error> {
+        match self.try_write_event(ev).await {
+            Ok(proof_hash) => Ok(proof_hash),
+            Err(e) => {
+                tracing::warn!("immudb write failed: {}", e);
+                // 降级：返回基于内容的哈希，不阻断主流程
+                let event_json = serde_json::to_string(ev)?;
+                Ok(format!(
+                    "proof_hash_{}",
+                    faster_hex::hex_string(&Sha256::digest(event_json.as_bytes()))
+                ))
+            }
+        }
+    }
+
+    /// 写入审计事件到 immudb，immudb 不可达或返回非 2xx 时返回错误
+    pub async fn try_write_event(&self, ev: &AuditEvent) -> Result<String, anyhow::Error> {
         // 序列化事件
         let event_json = serde_json::to_string(ev)?;
 
-        // 使用 HTTP API 写入 immudb（最佳努力，不阻断主流程）
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(2))
             .build()
@@ -46,61 +91,81 @@ impl ImmuCtx {
         let url = format!("http://{}/v1/immudb/verifiable/set", self.addr);
 
         // 构建请求体
-        let key = format!("audit:{}:{}:{}", ev.tenant_id, ev.event, ev.ts);
         let body = serde_json::json!({
-            "key": key,
+            "key": ev.immu_key(),
             "value": event_json
         });
 
-        // 发送请求（最佳努力）
-        match client
+        let resp = client
             .post(&url)
             .basic_auth(&self.user, Some(&self.pass))
             .json(&body)
+            .with_trace_context()
             .send()
             .await
-        {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    if let Ok(json) = resp.json::<serde_json::Value>().await {
-                        // 提取 proof hash
-                        let proof_hash = json
-                            .get("proof")
-                            .and_then(|p| p.get("leaf"))
-                            .and_then(|l| l.as_str())
-                            .unwrap_or("unknown")
-                            .to_string();
-                        tracing::debug!("Audit event written to immudb: {}", proof_hash);
-                        Ok(proof_hash)
-                    } else {
-                        tracing::warn!("Failed to parse immudb response");
-                        let mut hasher = Sha256::new();
-                        hasher.update(event_json.as_bytes());
-                        Ok(format!(
-                            "proof_hash_{}",
-                            faster_hex::hex_string(&hasher.finalize())
-                        ))
-                    }
-                } else {
-                    tracing::warn!("immudb write failed: {}", resp.status());
-                    // 降级：返回基于内容的哈希
-                    Ok(format!(
-                        "proof_hash_{}",
-                        faster_hex::hex_string(&sha2::Sha256::digest(event_json.as_bytes()))
-                    ))
-                }
+            .map_err(|e| anyhow::anyhow!("immudb HTTP error: {}", e))?;
+
+        if !resp.status().is_success() {
+            anyhow::bail!("immudb returned {}", resp.status());
+        }
+
+        match resp.json::<serde_json::Value>().await {
+            Ok(json) => {
+                // 提取 proof hash
+                let proof_hash = json
+                    .get("proof")
+                    .and_then(|p| p.get("leaf"))
+                    .and_then(|l| l.as_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                tracing::debug!("Audit event written to immudb: {}", proof_hash);
+                Ok(proof_hash)
             }
-            Err(e) => {
-                tracing::warn!("immudb HTTP error: {}", e);
-                // 降级：返回基于内容的哈希，不阻断主流程
+            Err(_) => {
+                // 已写入但响应无法解析：返回基于内容的哈希
+                tracing::warn!("Failed to parse immudb response");
                 Ok(format!(
                     "proof_hash_{}",
-                    faster_hex::hex_string(&sha2::Sha256::digest(event_json.as_bytes()))
+                    faster_hex::hex_string(&Sha256::digest(event_json.as_bytes()))
                 ))
             }
         }
     }
 
+    /// 按键读取已写入的审计事件，键不存在返回 `None`，immudb 不可达或响应异常返回错误
+    pub async fn try_read_event(&self, key: &str) -> Result<Option<AuditEvent>, anyhow::Error> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(2))
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {}", e))?;
+
+        let url = format!("http://{}/v1/immudb/verifiable/get", self.addr);
+
+        let resp = client
+            .post(&url)
+            .basic_auth(&self.user, Some(&self.pass))
+            .json(&serde_json::json!({ "key": key }))
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("immudb HTTP error: {}", e))?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            anyhow::bail!("immudb returned {}", resp.status());
+        }
+
+        let json = resp
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid immudb response: {}", e))?;
+        parse_event_value(&json)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("immudb entry for {} is not an audit event", key))
+    }
+
     /// 校验证明（简化实现）
     pub async fn verify(&self, proof_hash: &str) -> Result<bool, anyhow::Error> {
         // 如果 proof_hash 以 proof_hash_ 开头，尝试通过 HTTP API 验证
@@ -121,6 +186,7 @@ impl ImmuCtx {
                 .post(&url)
                 .basic_auth(&self.user, Some(&self.pass))
                 .json(&serde_json::json!({ "key": proof_hash }))
+                .with_trace_context()
                 .send()
                 .await
             {
@@ -134,3 +200,33 @@ impl ImmuCtx {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_value() {
+        let event = AuditEvent {
+            event: "audit_ledger.checkpoint".into(),
+            tenant_id: "ledger".into(),
+            actor: "audit_checkpointer".into(),
+            resource: "1-10".into(),
+            payload_hash: "ab".repeat(32),
+            ts: "2026-01-01T00:00:00+00:00".into(),
+        };
+        let raw = serde_json::to_string(&event).unwrap();
+
+        // 原文 value
+        let parsed = parse_event_value(&serde_json::json!({ "entry": { "value": raw } })).unwrap();
+        assert_eq!(parsed.payload_hash, event.payload_hash);
+
+        // gRPC 网关返回的 base64 value
+        use base64::Engine;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&raw);
+        let parsed = parse_event_value(&serde_json::json!({ "value": encoded })).unwrap();
+        assert_eq!(parsed.immu_key(), event.immu_key());
+
+        assert!(parse_event_value(&serde_json::json!({ "entry": {} })).is_none());
+    }
+}
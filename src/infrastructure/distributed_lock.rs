@@ -237,6 +237,58 @@ impl DistributedLock {
         Ok(result == 1)
     }
 
+    /// 以指定持有者标识尝试获取锁（非阻塞，不返回守卫）
+    ///
+    /// # 使用场景
+    /// - 长期租约（如后台任务主节点选举），由调用方自行续约/释放
+    pub async fn try_acquire_with_value(
+        &self,
+        lock_key: &str,
+        lock_value: &str,
+        ttl_secs: u64,
+    ) -> Result<bool> {
+        self.try_acquire_internal(lock_key, lock_value, ttl_secs)
+            .await
+    }
+
+    /// 释放指定持有者的锁（非持有者调用无效果）
+    pub async fn release(&self, lock_key: &str, lock_value: &str) -> Result<()> {
+        self.release_internal(lock_key, lock_value).await
+    }
+
+    /// 获取锁的当前持有者标识
+    pub async fn get_owner(&self, lock_key: &str) -> Result<Option<String>> {
+        let mut conn = self.redis_client.clone();
+        let owner: Option<String> = conn.get(lock_key).await?;
+        Ok(owner)
+    }
+
+    /// 按模式列出锁键（SCAN，不阻塞 Redis）
+    pub async fn scan_keys(&self, pattern: &str) -> Result<Vec<String>> {
+        let mut conn = self.redis_client.clone();
+        let mut cursor: u64 = 0;
+        let mut keys = Vec::new();
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(200)
+                .query_async(&mut conn)
+                .await
+                .context("Failed to scan lock keys")?;
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
+
     /// 检查锁是否存在
     pub async fn is_locked(&self, lock_key: &str) -> Result<bool> {
         let mut conn = self.redis_client.clone();
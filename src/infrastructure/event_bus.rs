@@ -1,11 +1,14 @@
 // Event Bus 框架
 // 异步事件发布/订阅系统，支持事件持久化和重试
+//
+// - InMemoryEventBus：进程内分发，按处理器指数退避重试（不持久化）
+// - OutboxEventBus（event_outbox.rs）：事务性 outbox + 死信队列，重启不丢事件
+
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
-use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use uuid::Uuid;
 
@@ -44,6 +47,38 @@ pub enum DomainEvent {
         target: String,
         timestamp: String,
     },
+    FiatOrderStatusChanged {
+        order_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        provider: String,
+        from_status: String,
+        to_status: String,
+    },
+    BridgeTransferCompleted {
+        transfer_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        source_chain: String,
+        destination_chain: String,
+        source_tx_hash: String,
+        destination_tx_hash: String,
+    },
+    ApprovalDecided {
+        approval_id: Uuid,
+        tenant_id: Uuid,
+        status: String,
+        resource_type: Option<String>,
+        resource_id: Option<Uuid>,
+        decided_by: Option<Uuid>,
+    },
+}
+
+impl DomainEvent {
+    /// 事件类型名（与 EventHandler::event_types 匹配）
+    pub fn event_type(&self) -> &'static str {
+        event_type_str(self)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +95,70 @@ pub struct EventEnvelope {
 pub trait EventHandler: Send + Sync {
     async fn handle(&self, event: &DomainEvent) -> Result<()>;
     fn event_types(&self) -> Vec<&'static str>;
+
+    /// 处理器名称（outbox 按处理器记录投递状态，需在重启、重构间保持稳定）
+    ///
+    /// 必须显式返回常量，不能依赖类型路径：重命名或移动模块会让已投递事件被重复投递
+    fn name(&self) -> &'static str;
+}
+
+// ============ 重试策略 ============
+
+/// 处理器失败重试策略（指数退避）
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 最大尝试次数（含首次），达到后进入死信
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 从环境变量读取（EVENT_BUS_MAX_ATTEMPTS / EVENT_BUS_RETRY_BASE_MS / EVENT_BUS_RETRY_MAX_SECS）
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            max_attempts: std::env::var("EVENT_BUS_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default.max_attempts),
+            base_delay: std::env::var("EVENT_BUS_RETRY_BASE_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+            max_delay: std::env::var("EVENT_BUS_RETRY_MAX_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default.max_delay),
+        }
+    }
+
+    /// 第 attempts 次失败后的等待时间：base * 2^(attempts-1)，不超过 max_delay
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let exp = attempts.saturating_sub(1).min(31);
+        self.base_delay
+            .checked_mul(1u32 << exp)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// 第 attempts 次失败后是否进入死信
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        attempts >= self.max_attempts
+    }
 }
 
 // ============ Event Bus 接口 ============
@@ -76,70 +175,94 @@ pub trait EventBus: Send + Sync {
     async fn get_event_history(&self, limit: i64, offset: i64) -> Result<Vec<EventEnvelope>>;
 }
 
-// ============ 内存 Event Bus 实现（支持持久化） ============
+// ============ 内存 Event Bus 实现 ============
 
+/// 进程内 Event Bus：不持久化，处理器失败按 RetryPolicy 重试，耗尽后记录错误日志
+///
+/// 需要持久化与死信队列时使用 `event_outbox::OutboxEventBus`
 pub struct InMemoryEventBus {
     handlers: Arc<RwLock<Vec<Arc<dyn EventHandler>>>>,
-    pool: Option<PgPool>,
     sender: mpsc::UnboundedSender<EventEnvelope>,
 }
 
 impl InMemoryEventBus {
-    pub fn new(pool: Option<PgPool>) -> Self {
+    pub fn new() -> Self {
+        Self::with_retry_policy(RetryPolicy::from_env())
+    }
+
+    pub fn with_retry_policy(policy: RetryPolicy) -> Self {
         let (sender, mut receiver) = mpsc::unbounded_channel::<EventEnvelope>();
         let handlers: Arc<RwLock<Vec<Arc<dyn EventHandler>>>> = Arc::new(RwLock::new(Vec::new()));
 
         let handlers_clone = handlers.clone();
 
-        // 后台任务：处理事件分发
+        // 后台任务：处理事件分发（每个处理器独立重试，互不阻塞）
         tokio::spawn(async move {
             while let Some(envelope) = receiver.recv().await {
+                let event_type = envelope.event.event_type();
                 let handlers_read = handlers_clone.read().await;
 
                 for handler in handlers_read.iter() {
-                    let event_type = match &envelope.event {
-                        DomainEvent::TransactionConfirmed { .. } => "TransactionConfirmed",
-                        DomainEvent::FeeCollectorRotated { .. } => "FeeCollectorRotated",
-                        DomainEvent::GasSpikeDetected { .. } => "GasSpikeDetected",
-                        DomainEvent::WalletCreated { .. } => "WalletCreated",
-                        DomainEvent::AdminOperationPerformed { .. } => "AdminOperationPerformed",
-                    };
-
-                    if handler.event_types().contains(&event_type) {
-                        if let Err(e) = handler.handle(&envelope.event).await {
-                            tracing::error!("Event handler error: {:?}, event: {:?}", e, envelope);
-                            // 重试逻辑可以在这里实现
-                        }
+                    if !handler.event_types().contains(&event_type) {
+                        continue;
                     }
+
+                    let handler = handler.clone();
+                    let envelope = envelope.clone();
+                    let policy = policy.clone();
+                    tokio::spawn(async move {
+                        deliver_with_retry(handler.as_ref(), &envelope, &policy).await;
+                    });
                 }
             }
         });
 
-        Self {
-            handlers,
-            pool,
-            sender,
-        }
+        Self { handlers, sender }
     }
+}
+
+impl Default for InMemoryEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    /// 持久化事件到数据库
-    async fn persist_event(&self, envelope: &EventEnvelope) -> Result<()> {
-        if let Some(pool) = &self.pool {
-            let event_json = serde_json::to_value(&envelope.event)?;
-
-            sqlx::query(
-                "INSERT INTO events.domain_events (id, event_type, event_data, published_at, retry_count)
-                 VALUES ($1, $2, $3, $4, $5)"
-            )
-            .bind(envelope.event_id)
-            .bind(event_type_str(&envelope.event))
-            .bind(event_json)
-            .bind(envelope.published_at)
-            .bind(envelope.retry_count as i32)
-            .execute(pool)
-            .await?;
+/// 进程内投递：失败后按指数退避重试，返回是否最终成功
+async fn deliver_with_retry(
+    handler: &dyn EventHandler,
+    envelope: &EventEnvelope,
+    policy: &RetryPolicy,
+) -> bool {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match handler.handle(&envelope.event).await {
+            Ok(()) => return true,
+            Err(e) if policy.is_exhausted(attempts) => {
+                crate::metrics::inc_event_dead_lettered();
+                tracing::error!(
+                    event_id = %envelope.event_id,
+                    handler = handler.name(),
+                    attempts,
+                    error = ?e,
+                    "Event handler retries exhausted, dropping in-memory event"
+                );
+                return false;
+            }
+            Err(e) => {
+                crate::metrics::inc_event_handler_retry();
+                let delay = policy.backoff(attempts);
+                tracing::warn!(
+                    event_id = %envelope.event_id,
+                    handler = handler.name(),
+                    attempts,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = ?e,
+                    "Event handler failed, retrying"
+                );
+                tokio::time::sleep(delay).await;
+            }
         }
-        Ok(())
     }
 }
 
@@ -148,14 +271,11 @@ impl EventBus for InMemoryEventBus {
     async fn publish(&self, event: DomainEvent) -> Result<()> {
         let envelope = EventEnvelope {
             event_id: Uuid::new_v4(),
-            event: event.clone(),
+            event,
             published_at: chrono::Utc::now(),
             retry_count: 0,
         };
 
-        // 持久化事件
-        self.persist_event(&envelope).await?;
-
         // 发送到处理队列
         self.sender
             .send(envelope)
@@ -169,43 +289,9 @@ impl EventBus for InMemoryEventBus {
         handlers.push(handler);
     }
 
-    async fn get_event_history(&self, limit: i64, offset: i64) -> Result<Vec<EventEnvelope>> {
-        if let Some(pool) = &self.pool {
-            let rows = sqlx::query_as::<
-                _,
-                (
-                    Uuid,
-                    String,
-                    sqlx::types::JsonValue,
-                    chrono::DateTime<chrono::Utc>,
-                    i32,
-                ),
-            >(
-                "SELECT id, event_type, event_data, published_at, retry_count
-                 FROM events.domain_events
-                 ORDER BY published_at DESC
-                 LIMIT $1 OFFSET $2",
-            )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(pool)
-            .await?;
-
-            let mut envelopes = Vec::new();
-            for (id, _event_type, event_data, published_at, retry_count) in rows {
-                let event: DomainEvent = serde_json::from_value(event_data)?;
-                envelopes.push(EventEnvelope {
-                    event_id: id,
-                    event,
-                    published_at,
-                    retry_count: retry_count as u32,
-                });
-            }
-
-            Ok(envelopes)
-        } else {
-            Ok(vec![])
-        }
+    async fn get_event_history(&self, _limit: i64, _offset: i64) -> Result<Vec<EventEnvelope>> {
+        // 进程内总线不保留历史
+        Ok(vec![])
     }
 }
 
@@ -242,6 +328,10 @@ impl EventHandler for TransactionConfirmedHandler {
     fn event_types(&self) -> Vec<&'static str> {
         vec!["TransactionConfirmed"]
     }
+
+    fn name(&self) -> &'static str {
+        "transaction_confirmed"
+    }
 }
 
 pub struct GasSpikeHandler;
@@ -275,17 +365,24 @@ impl EventHandler for GasSpikeHandler {
     fn event_types(&self) -> Vec<&'static str> {
         vec!["GasSpikeDetected"]
     }
+
+    fn name(&self) -> &'static str {
+        "gas_spike"
+    }
 }
 
 // ============ 辅助函数 ============
 
-fn event_type_str(event: &DomainEvent) -> &'static str {
+pub(crate) fn event_type_str(event: &DomainEvent) -> &'static str {
     match event {
         DomainEvent::TransactionConfirmed { .. } => "TransactionConfirmed",
         DomainEvent::FeeCollectorRotated { .. } => "FeeCollectorRotated",
         DomainEvent::GasSpikeDetected { .. } => "GasSpikeDetected",
         DomainEvent::WalletCreated { .. } => "WalletCreated",
         DomainEvent::AdminOperationPerformed { .. } => "AdminOperationPerformed",
+        DomainEvent::FiatOrderStatusChanged { .. } => "FiatOrderStatusChanged",
+        DomainEvent::BridgeTransferCompleted { .. } => "BridgeTransferCompleted",
+        DomainEvent::ApprovalDecided { .. } => "ApprovalDecided",
     }
 }
 
@@ -295,7 +392,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_event_bus_publish_subscribe() {
-        let bus = InMemoryEventBus::new(None);
+        let bus = InMemoryEventBus::new();
 
         let handler = Arc::new(TransactionConfirmedHandler);
         bus.subscribe(handler).await;
@@ -316,7 +413,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_multiple_handlers() {
-        let bus = InMemoryEventBus::new(None);
+        let bus = InMemoryEventBus::new();
 
         bus.subscribe(Arc::new(TransactionConfirmedHandler)).await;
         bus.subscribe(Arc::new(GasSpikeHandler)).await;
@@ -349,4 +446,94 @@ mod tests {
         let parsed: DomainEvent = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, event);
     }
+
+    struct FlakyHandler {
+        failures_before_success: u32,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait]
+    impl EventHandler for FlakyHandler {
+        async fn handle(&self, _event: &DomainEvent) -> Result<()> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            if call <= self.failures_before_success {
+                anyhow::bail!("transient failure #{}", call);
+            }
+            Ok(())
+        }
+
+        fn event_types(&self) -> Vec<&'static str> {
+            vec!["WalletCreated"]
+        }
+
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    fn wallet_envelope() -> EventEnvelope {
+        EventEnvelope {
+            event_id: Uuid::new_v4(),
+            event: DomainEvent::WalletCreated {
+                wallet_id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                chain_type: "ethereum".to_string(),
+                address: "0xabc".to_string(),
+            },
+            published_at: chrono::Utc::now(),
+            retry_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(30),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(16));
+        assert_eq!(policy.backoff(5), Duration::from_secs(30)); // 封顶
+        assert_eq!(policy.backoff(100), Duration::from_secs(30));
+
+        assert!(!policy.is_exhausted(4));
+        assert!(policy.is_exhausted(5));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_with_retry_recovers() {
+        let handler = FlakyHandler {
+            failures_before_success: 2,
+            calls: Default::default(),
+        };
+        assert!(deliver_with_retry(&handler, &wallet_envelope(), &fast_policy(3)).await);
+        assert_eq!(handler.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_with_retry_exhausts() {
+        let handler = FlakyHandler {
+            failures_before_success: u32::MAX,
+            calls: Default::default(),
+        };
+        assert!(!deliver_with_retry(&handler, &wallet_envelope(), &fast_policy(3)).await);
+        assert_eq!(handler.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_handler_names_are_explicit_constants() {
+        assert_eq!(GasSpikeHandler.name(), "gas_spike");
+        assert_eq!(TransactionConfirmedHandler.name(), "transaction_confirmed");
+        assert_eq!(wallet_envelope().event.event_type(), "WalletCreated");
+    }
 }
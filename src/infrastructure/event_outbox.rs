@@ -0,0 +1,763 @@
+// 事务性 Outbox Event Bus
+//
+// - 事件与业务变更写入同一数据库事务（enqueue_event），提交后才可见
+// - 后台 dispatcher 以租约方式认领待分发事件（FOR UPDATE SKIP LOCKED，支持多实例）
+// - 每个处理器独立记录投递状态，失败按 RetryPolicy 指数退避重试
+// - 重试耗尽后写入 events.dead_letter_events，管理员可查看并重放
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::infrastructure::event_bus::{
+    DomainEvent, EventBus, EventEnvelope, EventHandler, RetryPolicy,
+};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const CLAIM_LEASE_SECS: f64 = 60.0; // 认领租约，实例崩溃后事件自动重新可见
+
+/// 在业务事务中写入 outbox 事件
+///
+/// ```ignore
+/// let mut tx = pool.begin().await?;
+/// // ... 业务写入 ...
+/// enqueue_event(&mut *tx, &DomainEvent::WalletCreated { .. }).await?;
+/// tx.commit().await?;
+/// ```
+pub async fn enqueue_event<'e, E>(executor: E, event: &DomainEvent) -> Result<Uuid>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let event_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO events.domain_events (id, event_type, event_data, published_at, retry_count, processed)
+         VALUES ($1, $2, $3, NOW(), 0, false)",
+    )
+    .bind(event_id)
+    .bind(event.event_type())
+    .bind(serde_json::to_value(event)?)
+    .execute(executor)
+    .await
+    .context("Failed to enqueue outbox event")?;
+    Ok(event_id)
+}
+
+// ============ 投递状态 ============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Succeeded,
+    Retrying,
+    Dead,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Succeeded => "succeeded",
+            DeliveryStatus::Retrying => "retrying",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "succeeded" => DeliveryStatus::Succeeded,
+            "dead" => DeliveryStatus::Dead,
+            _ => DeliveryStatus::Retrying,
+        }
+    }
+
+    fn is_final(&self) -> bool {
+        matches!(self, DeliveryStatus::Succeeded | DeliveryStatus::Dead)
+    }
+}
+
+/// 单个处理器的投递记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryRecord {
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl DeliveryRecord {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == DeliveryStatus::Retrying && self.next_attempt_at.is_none_or(|t| t <= now)
+    }
+}
+
+/// 计算本次投递后的记录
+fn record_after_attempt(
+    previous: Option<&DeliveryRecord>,
+    result: &Result<()>,
+    policy: &RetryPolicy,
+    now: DateTime<Utc>,
+) -> DeliveryRecord {
+    let attempts = previous.map(|r| r.attempts).unwrap_or(0) + 1;
+    match result {
+        Ok(()) => DeliveryRecord {
+            status: DeliveryStatus::Succeeded,
+            attempts,
+            next_attempt_at: None,
+            last_error: None,
+        },
+        Err(e) if policy.is_exhausted(attempts) => DeliveryRecord {
+            status: DeliveryStatus::Dead,
+            attempts,
+            next_attempt_at: None,
+            last_error: Some(format!("{:#}", e)),
+        },
+        Err(e) => DeliveryRecord {
+            status: DeliveryStatus::Retrying,
+            attempts,
+            next_attempt_at: Some(
+                now + chrono::Duration::from_std(policy.backoff(attempts))
+                    .unwrap_or_else(|_| chrono::Duration::seconds(60)),
+            ),
+            last_error: Some(format!("{:#}", e)),
+        },
+    }
+}
+
+/// 对到期的处理器执行投递，返回 (处理器名, 新记录)
+async fn run_due_handlers(
+    handlers: &[Arc<dyn EventHandler>],
+    event: &DomainEvent,
+    records: &HashMap<String, DeliveryRecord>,
+    policy: &RetryPolicy,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, DeliveryRecord)> {
+    let event_type = event.event_type();
+    let mut updates = Vec::new();
+
+    for handler in handlers {
+        if !handler.event_types().contains(&event_type) {
+            continue;
+        }
+        let name = handler.name();
+        let previous = records.get(name);
+        if previous.is_some_and(|r| !r.is_due(now)) {
+            continue;
+        }
+
+        let result = handler.handle(event).await;
+        updates.push((name, record_after_attempt(previous, &result, policy, now)));
+    }
+
+    updates
+}
+
+/// 汇总事件状态：全部处理器终态则完成，否则返回最早的下次重试时间
+fn event_progress(
+    handlers: &[Arc<dyn EventHandler>],
+    event: &DomainEvent,
+    records: &HashMap<String, DeliveryRecord>,
+) -> Option<DateTime<Utc>> {
+    let event_type = event.event_type();
+    handlers
+        .iter()
+        .filter(|h| h.event_types().contains(&event_type))
+        .filter_map(|h| records.get(h.name()))
+        .filter(|r| !r.status.is_final())
+        .filter_map(|r| r.next_attempt_at)
+        .min()
+}
+
+fn all_final(
+    handlers: &[Arc<dyn EventHandler>],
+    event: &DomainEvent,
+    records: &HashMap<String, DeliveryRecord>,
+) -> bool {
+    let event_type = event.event_type();
+    handlers
+        .iter()
+        .filter(|h| h.event_types().contains(&event_type))
+        .all(|h| records.get(h.name()).is_some_and(|r| r.status.is_final()))
+}
+
+// ============ 死信队列 ============
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterEvent {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub handler_name: String,
+    pub event_type: String,
+    pub event_data: serde_json::Value,
+    pub error: Option<String>,
+    pub attempts: i32,
+    pub dead_at: DateTime<Utc>,
+    pub replayed_at: Option<DateTime<Utc>>,
+}
+
+/// 查询死信（默认仅未重放）
+pub async fn list_dead_letters(
+    pool: &PgPool,
+    include_replayed: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<DeadLetterEvent>> {
+    let rows = sqlx::query_as::<
+        _,
+        (
+            Uuid,
+            Uuid,
+            String,
+            String,
+            serde_json::Value,
+            Option<String>,
+            i32,
+            DateTime<Utc>,
+            Option<DateTime<Utc>>,
+        ),
+    >(
+        "SELECT id, event_id, handler_name, event_type, event_data, error, attempts, dead_at, replayed_at
+         FROM events.dead_letter_events
+         WHERE $1 OR replayed_at IS NULL
+         ORDER BY dead_at DESC
+         LIMIT $2 OFFSET $3",
+    )
+    .bind(include_replayed)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .context("Failed to query dead letter events")?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                id,
+                event_id,
+                handler_name,
+                event_type,
+                event_data,
+                error,
+                attempts,
+                dead_at,
+                replayed_at,
+            )| {
+                DeadLetterEvent {
+                    id,
+                    event_id,
+                    handler_name,
+                    event_type,
+                    event_data,
+                    error,
+                    attempts,
+                    dead_at,
+                    replayed_at,
+                }
+            },
+        )
+        .collect())
+}
+
+/// 重放死信：重置该处理器的投递状态并将事件重新放回 outbox
+///
+/// 返回 false 表示死信不存在或已重放
+pub async fn replay_dead_letter(pool: &PgPool, id: Uuid, operator_id: Uuid) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let dead = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT event_id, handler_name FROM events.dead_letter_events
+         WHERE id = $1 AND replayed_at IS NULL
+         FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((event_id, handler_name)) = dead else {
+        tx.rollback().await?;
+        return Ok(false);
+    };
+
+    sqlx::query(
+        "UPDATE events.dead_letter_events SET replayed_at = NOW(), replayed_by = $2 WHERE id = $1",
+    )
+    .bind(id)
+    .bind(operator_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE events.handler_deliveries
+         SET status = 'retrying', attempts = 0, next_attempt_at = NULL, last_error = NULL, updated_at = NOW()
+         WHERE event_id = $1 AND handler_name = $2",
+    )
+    .bind(event_id)
+    .bind(&handler_name)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE events.domain_events
+         SET processed = false, processed_at = NULL, next_attempt_at = NULL
+         WHERE id = $1",
+    )
+    .bind(event_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        dead_letter_id = %id,
+        event_id = %event_id,
+        handler = %handler_name,
+        operator_id = %operator_id,
+        "Dead letter event replayed"
+    );
+    Ok(true)
+}
+
+// ============ Outbox Event Bus ============
+
+pub struct OutboxEventBus {
+    pool: PgPool,
+    handlers: RwLock<Vec<Arc<dyn EventHandler>>>,
+    policy: RetryPolicy,
+    poll_interval: Duration,
+    batch_size: i64,
+}
+
+impl OutboxEventBus {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            handlers: RwLock::new(Vec::new()),
+            policy: RetryPolicy::from_env(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 启动后台分发任务（持续运行）
+    pub async fn start_dispatcher(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.poll_interval);
+
+        tracing::info!(
+            "Outbox dispatcher started, interval={}ms, max_attempts={}",
+            self.poll_interval.as_millis(),
+            self.policy.max_attempts
+        );
+
+        loop {
+            ticker.tick().await;
+
+            match self.dispatch_due_events().await {
+                Ok(dispatched) => {
+                    if dispatched > 0 {
+                        tracing::debug!(count = dispatched, "Dispatched outbox events");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e, "Failed to dispatch outbox events");
+                }
+            }
+        }
+    }
+
+    /// 认领并分发到期事件
+    pub async fn dispatch_due_events(&self) -> Result<usize> {
+        let claimed = sqlx::query_as::<_, (Uuid, sqlx::types::JsonValue, DateTime<Utc>, i32)>(
+            "UPDATE events.domain_events
+             SET next_attempt_at = NOW() + make_interval(secs => $2)
+             WHERE id IN (
+                 SELECT id FROM events.domain_events
+                 WHERE processed = false
+                   AND (next_attempt_at IS NULL OR next_attempt_at <= NOW())
+                 ORDER BY published_at
+                 LIMIT $1
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING id, event_data, published_at, retry_count",
+        )
+        .bind(self.batch_size)
+        .bind(CLAIM_LEASE_SECS)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to claim outbox events")?;
+
+        let handlers = self.handlers.read().await.clone();
+        let mut dispatched = 0;
+
+        for (event_id, event_data, _published_at, _retry_count) in claimed {
+            let event: DomainEvent = match serde_json::from_value(event_data) {
+                Ok(event) => event,
+                Err(e) => {
+                    // 无法反序列化的事件不会自愈，直接标记完成并记录错误
+                    tracing::error!(event_id = %event_id, error = %e, "Undecodable outbox event");
+                    sqlx::query(
+                        "UPDATE events.domain_events
+                         SET processed = true, processed_at = NOW(), last_error = $2
+                         WHERE id = $1",
+                    )
+                    .bind(event_id)
+                    .bind(format!("undecodable event: {}", e))
+                    .execute(&self.pool)
+                    .await?;
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.process_event(&handlers, event_id, &event).await {
+                tracing::error!(event_id = %event_id, error = ?e, "Failed to process outbox event");
+                continue;
+            }
+            dispatched += 1;
+        }
+
+        Ok(dispatched)
+    }
+
+    async fn process_event(
+        &self,
+        handlers: &[Arc<dyn EventHandler>],
+        event_id: Uuid,
+        event: &DomainEvent,
+    ) -> Result<()> {
+        let rows =
+            sqlx::query_as::<_, (String, String, i32, Option<DateTime<Utc>>, Option<String>)>(
+                "SELECT handler_name, status, attempts, next_attempt_at, last_error
+             FROM events.handler_deliveries WHERE event_id = $1",
+            )
+            .bind(event_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut records: HashMap<String, DeliveryRecord> = rows
+            .into_iter()
+            .map(|(name, status, attempts, next_attempt_at, last_error)| {
+                (
+                    name,
+                    DeliveryRecord {
+                        status: DeliveryStatus::parse(&status),
+                        attempts: attempts.max(0) as u32,
+                        next_attempt_at,
+                        last_error,
+                    },
+                )
+            })
+            .collect();
+
+        let updates = run_due_handlers(handlers, event, &records, &self.policy, Utc::now()).await;
+
+        let mut tx = self.pool.begin().await?;
+        for (handler_name, record) in &updates {
+            sqlx::query(
+                "INSERT INTO events.handler_deliveries
+                     (event_id, handler_name, status, attempts, next_attempt_at, last_error, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, NOW())
+                 ON CONFLICT (event_id, handler_name) DO UPDATE
+                 SET status = EXCLUDED.status, attempts = EXCLUDED.attempts,
+                     next_attempt_at = EXCLUDED.next_attempt_at, last_error = EXCLUDED.last_error,
+                     updated_at = NOW()",
+            )
+            .bind(event_id)
+            .bind(handler_name)
+            .bind(record.status.as_str())
+            .bind(record.attempts as i32)
+            .bind(record.next_attempt_at)
+            .bind(&record.last_error)
+            .execute(&mut *tx)
+            .await?;
+
+            match record.status {
+                DeliveryStatus::Dead => {
+                    sqlx::query(
+                        "INSERT INTO events.dead_letter_events
+                             (event_id, handler_name, event_type, event_data, error, attempts)
+                         VALUES ($1, $2, $3, $4, $5, $6)",
+                    )
+                    .bind(event_id)
+                    .bind(handler_name)
+                    .bind(event.event_type())
+                    .bind(serde_json::to_value(event)?)
+                    .bind(&record.last_error)
+                    .bind(record.attempts as i32)
+                    .execute(&mut *tx)
+                    .await?;
+
+                    crate::metrics::inc_event_dead_lettered();
+                    tracing::error!(
+                        event_id = %event_id,
+                        handler = handler_name,
+                        attempts = record.attempts,
+                        error = record.last_error.as_deref().unwrap_or_default(),
+                        "Event handler retries exhausted, moved to dead letter queue"
+                    );
+                }
+                DeliveryStatus::Retrying => {
+                    crate::metrics::inc_event_handler_retry();
+                    tracing::warn!(
+                        event_id = %event_id,
+                        handler = handler_name,
+                        attempts = record.attempts,
+                        error = record.last_error.as_deref().unwrap_or_default(),
+                        "Event handler failed, retry scheduled"
+                    );
+                }
+                DeliveryStatus::Succeeded => {}
+            }
+
+            records.insert(handler_name.to_string(), record.clone());
+        }
+
+        let max_attempts = records.values().map(|r| r.attempts).max().unwrap_or(0) as i32;
+        let last_error = updates.iter().find_map(|(_, r)| r.last_error.clone());
+
+        if all_final(handlers, event, &records) {
+            sqlx::query(
+                "UPDATE events.domain_events
+                 SET processed = true, processed_at = NOW(), next_attempt_at = NULL,
+                     retry_count = $2, last_error = COALESCE($3, last_error)
+                 WHERE id = $1",
+            )
+            .bind(event_id)
+            .bind(max_attempts)
+            .bind(last_error)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query(
+                "UPDATE events.domain_events
+                 SET next_attempt_at = $2, retry_count = $3, last_error = COALESCE($4, last_error)
+                 WHERE id = $1",
+            )
+            .bind(event_id)
+            .bind(event_progress(handlers, event, &records))
+            .bind(max_attempts)
+            .bind(last_error)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventBus for OutboxEventBus {
+    /// 独立发布（不在业务事务中）；需要与业务变更原子提交时使用 `enqueue_event`
+    async fn publish(&self, event: DomainEvent) -> Result<()> {
+        enqueue_event(&self.pool, &event).await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, handler: Arc<dyn EventHandler>) {
+        let mut handlers = self.handlers.write().await;
+        handlers.push(handler);
+    }
+
+    async fn get_event_history(&self, limit: i64, offset: i64) -> Result<Vec<EventEnvelope>> {
+        let rows = sqlx::query_as::<_, (Uuid, sqlx::types::JsonValue, DateTime<Utc>, i32)>(
+            "SELECT id, event_data, published_at, retry_count
+             FROM events.domain_events
+             ORDER BY published_at DESC
+             LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut envelopes = Vec::new();
+        for (id, event_data, published_at, retry_count) in rows {
+            let event: DomainEvent = serde_json::from_value(event_data)?;
+            envelopes.push(EventEnvelope {
+                event_id: id,
+                event,
+                published_at,
+                retry_count: retry_count as u32,
+            });
+        }
+
+        Ok(envelopes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    struct CountingHandler {
+        name: &'static str,
+        fail: bool,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl EventHandler for CountingHandler {
+        async fn handle(&self, _event: &DomainEvent) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                anyhow::bail!("{} unavailable", self.name);
+            }
+            Ok(())
+        }
+
+        fn event_types(&self) -> Vec<&'static str> {
+            vec!["WalletCreated"]
+        }
+
+        fn name(&self) -> &'static str {
+            self.name
+        }
+    }
+
+    fn handler(name: &'static str, fail: bool) -> Arc<CountingHandler> {
+        Arc::new(CountingHandler {
+            name,
+            fail,
+            calls: AtomicU32::new(0),
+        })
+    }
+
+    fn event() -> DomainEvent {
+        DomainEvent::WalletCreated {
+            wallet_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            chain_type: "ethereum".to_string(),
+            address: "0xabc".to_string(),
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_record_after_attempt() {
+        let now = Utc::now();
+        let policy = policy();
+
+        let ok = record_after_attempt(None, &Ok(()), &policy, now);
+        assert_eq!(ok.status, DeliveryStatus::Succeeded);
+        assert_eq!(ok.attempts, 1);
+
+        let first = record_after_attempt(None, &Err(anyhow::anyhow!("boom")), &policy, now);
+        assert_eq!(first.status, DeliveryStatus::Retrying);
+        assert_eq!(
+            first.next_attempt_at,
+            Some(now + chrono::Duration::seconds(10))
+        );
+        assert_eq!(first.last_error.as_deref(), Some("boom"));
+
+        let second =
+            record_after_attempt(Some(&first), &Err(anyhow::anyhow!("boom")), &policy, now);
+        assert_eq!(second.attempts, 2);
+        assert_eq!(
+            second.next_attempt_at,
+            Some(now + chrono::Duration::seconds(20))
+        );
+
+        let third =
+            record_after_attempt(Some(&second), &Err(anyhow::anyhow!("boom")), &policy, now);
+        assert_eq!(third.status, DeliveryStatus::Dead);
+        assert_eq!(third.attempts, 3);
+        assert!(third.next_attempt_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_due_handlers_skips_finished_and_backing_off() {
+        let now = Utc::now();
+        let done = handler("done", false);
+        let waiting = handler("waiting", true);
+        let due = handler("due", true);
+        let fresh = handler("fresh", false);
+        let handlers: Vec<Arc<dyn EventHandler>> =
+            vec![done.clone(), waiting.clone(), due.clone(), fresh.clone()];
+
+        let mut records = HashMap::new();
+        records.insert(
+            "done".to_string(),
+            DeliveryRecord {
+                status: DeliveryStatus::Succeeded,
+                attempts: 1,
+                next_attempt_at: None,
+                last_error: None,
+            },
+        );
+        records.insert(
+            "waiting".to_string(),
+            DeliveryRecord {
+                status: DeliveryStatus::Retrying,
+                attempts: 1,
+                next_attempt_at: Some(now + chrono::Duration::seconds(30)),
+                last_error: Some("boom".into()),
+            },
+        );
+        records.insert(
+            "due".to_string(),
+            DeliveryRecord {
+                status: DeliveryStatus::Retrying,
+                attempts: 2,
+                next_attempt_at: Some(now - chrono::Duration::seconds(1)),
+                last_error: Some("boom".into()),
+            },
+        );
+
+        let updates = run_due_handlers(&handlers, &event(), &records, &policy(), now).await;
+
+        assert_eq!(done.calls.load(Ordering::SeqCst), 0);
+        assert_eq!(waiting.calls.load(Ordering::SeqCst), 0);
+        assert_eq!(due.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fresh.calls.load(Ordering::SeqCst), 1);
+
+        let updates: HashMap<_, _> = updates.into_iter().collect();
+        assert_eq!(updates["due"].status, DeliveryStatus::Dead); // 第 3 次失败
+        assert_eq!(updates["fresh"].status, DeliveryStatus::Succeeded);
+
+        for (name, record) in updates {
+            records.insert(name.to_string(), record);
+        }
+        // waiting 仍在退避，事件未完成，下次分发时间为其 next_attempt_at
+        assert!(!all_final(&handlers, &event(), &records));
+        assert_eq!(
+            event_progress(&handlers, &event(), &records),
+            Some(now + chrono::Duration::seconds(30))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_completes_when_all_handlers_final() {
+        let now = Utc::now();
+        let ok = handler("ok", false);
+        let handlers: Vec<Arc<dyn EventHandler>> = vec![ok];
+
+        let records = HashMap::new();
+        let updates = run_due_handlers(&handlers, &event(), &records, &policy(), now).await;
+        let records: HashMap<String, DeliveryRecord> = updates
+            .into_iter()
+            .map(|(n, r)| (n.to_string(), r))
+            .collect();
+        assert!(all_final(&handlers, &event(), &records));
+
+        // 无订阅处理器的事件直接视为完成
+        assert!(all_final(&[], &event(), &HashMap::new()));
+    }
+}
@@ -1,6 +1,7 @@
 //! 健康检查模块
 //! 提供增强的健康检查功能，包括数据库、Redis、immudb等
 
+use crate::infrastructure::telemetry::TracePropagation;
 use crate::infrastructure::audit::ImmuCtx;
 use crate::infrastructure::cache::RedisCtx;
 use crate::infrastructure::db::PgPool;
@@ -116,6 +117,7 @@ async fn check_immudb(immu: &ImmuCtx) -> ComponentStatus {
                 .post(&url)
                 .basic_auth(&immu.user, Some(&immu.pass))
                 .json(&body)
+                .with_trace_context()
                 .send()
                 .await
             {
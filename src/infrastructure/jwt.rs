@@ -1,10 +1,37 @@
 //! JWT Token 生成和验证模块
+//!
+//! - 非对称签名（RS256 / EdDSA），Header 携带 `kid`，access / refresh 可使用不同密钥
+//! - 密钥环支持多个验证密钥：轮换后旧密钥在宽限期内仍可验证
+//! - 未安装密钥环时回退到 `JWT_SECRET` 的 HS256（兼容存量 Token）
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
 use anyhow::{anyhow, Result};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// 刷新 Token 有效期（30 天）
+pub const REFRESH_TOKEN_EXPIRY_SECS: i64 = 2592000;
+
+/// Ed25519 私钥 PKCS#8 v1 DER 前缀（后接 32 字节 seed）
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+const RSA_KEY_BITS: usize = 2048;
 
 /// JWT Claims
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,34 +102,37 @@ pub fn generate_token_with_expiry(
     role: String,
     expires_in_secs: i64,
 ) -> Result<String> {
-    let secret = get_jwt_secret()?;
     let claims = Claims::new(user_id, tenant_id, role, expires_in_secs);
-
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| anyhow!("Failed to encode token: {}", e))
+    sign_claims(&claims, KeyPurpose::Access)
 }
 
 /// 生成刷新Token（30天过期）
 pub fn generate_refresh_token(user_id: Uuid, tenant_id: Uuid, role: String) -> Result<String> {
-    let secret = get_jwt_secret()?;
     let claims = Claims::new_with_type(
         user_id,
         tenant_id,
         role,
-        2592000, // 30天过期
+        REFRESH_TOKEN_EXPIRY_SECS,
         Some("refresh".to_string()),
     );
+    sign_claims(&claims, KeyPurpose::Refresh)
+}
+
+/// 签名：优先使用密钥环中该用途的当前签名密钥，否则回退 HS256
+fn sign_claims(claims: &Claims, purpose: KeyPurpose) -> Result<String> {
+    if let Some(keyring) = current_keyring() {
+        if let Some(token) = keyring.sign(claims, purpose)? {
+            return Ok(token);
+        }
+    }
 
+    let secret = get_jwt_secret()?;
     encode(
         &Header::default(),
-        &claims,
+        claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
-    .map_err(|e| anyhow!("Failed to encode refresh token: {}", e))
+    .map_err(|e| anyhow!("Failed to encode {} token: {}", purpose.as_str(), e))
 }
 
 /// 验证刷新Token
@@ -119,25 +149,38 @@ pub fn verify_refresh_token(token: &str) -> Result<Claims> {
 
 /// 验证JWT Token✅增强验证 + 调试日志
 pub fn verify_token(token: &str) -> Result<Claims> {
-    let secret = get_jwt_secret()?;
     tracing::debug!("JWT: starting verification, token_len={}", token.len());
 
-    let mut validation = Validation::default();
-    validation.validate_exp = true; // ✅强制验证过期时间
-    validation.leeway = 10; // 允许10秒时钟偏差
-
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &validation,
-    )
-    .map_err(|e| {
-        tracing::warn!("JWT: token verification failed: {}", e);
+    let header = decode_header(token).map_err(|e| {
+        tracing::warn!("JWT: malformed token header: {}", e);
         anyhow!("Token verification failed: {}", e)
     })?;
 
-    // ✅额外验证
-    let claims = token_data.claims;
+    let claims = match header.kid {
+        // ✅ 带 kid：仅用密钥环中的对应密钥及其算法验证（防算法混淆）
+        Some(kid) => {
+            let keyring =
+                current_keyring().ok_or_else(|| anyhow!("Unknown signing key id: {}", kid))?;
+            keyring.verify(token, &kid)?
+        }
+        // 无 kid：存量 HS256 Token
+        None => {
+            if !legacy_hs256_enabled() {
+                return Err(anyhow!("Legacy HS256 tokens are no longer accepted"));
+            }
+            let secret = get_jwt_secret()?;
+            decode::<Claims>(
+                token,
+                &DecodingKey::from_secret(secret.as_bytes()),
+                &validation_for(Algorithm::HS256),
+            )
+            .map_err(|e| {
+                tracing::warn!("JWT: token verification failed: {}", e);
+                anyhow!("Token verification failed: {}", e)
+            })?
+            .claims
+        }
+    };
 
     tracing::debug!(
         "JWT: claims decoded, sub={}, tenant_id={}, role={}",
@@ -165,6 +208,325 @@ fn get_jwt_secret() -> Result<String> {
     }
 }
 
+fn validation_for(algorithm: Algorithm) -> Validation {
+    let mut validation = Validation::new(algorithm);
+    validation.validate_exp = true; // ✅强制验证过期时间
+    validation.leeway = 10; // 允许10秒时钟偏差
+    validation
+}
+
+/// 是否继续接受无 kid 的 HS256 Token
+///
+/// 未加载签名密钥环时 HS256 是唯一的签发方式，始终接受；密钥环就绪后
+/// 只在 JWT_LEGACY_HS256_UNTIL（RFC3339）之前接受存量 Token，未配置则立即停止。
+/// JWT_LEGACY_HS256_ENABLED=false 可随时强制关闭
+fn legacy_hs256_enabled() -> bool {
+    let enabled = std::env::var("JWT_LEGACY_HS256_ENABLED")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true);
+    let until = std::env::var("JWT_LEGACY_HS256_UNTIL")
+        .ok()
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        .map(|t| t.with_timezone(&Utc));
+    legacy_hs256_allowed(current_keyring().is_some(), enabled, until, Utc::now())
+}
+
+fn legacy_hs256_allowed(
+    keyring_installed: bool,
+    enabled: bool,
+    until: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    if !enabled {
+        return false;
+    }
+    if !keyring_installed {
+        return true;
+    }
+    until.is_some_and(|deadline| now < deadline)
+}
+
+// ============ 签名密钥环 ============
+
+/// Token 用途（access / refresh 可使用不同签名密钥）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPurpose {
+    Access,
+    Refresh,
+}
+
+impl KeyPurpose {
+    pub const ALL: [KeyPurpose; 2] = [KeyPurpose::Access, KeyPurpose::Refresh];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyPurpose::Access => "access",
+            KeyPurpose::Refresh => "refresh",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "access" => Some(KeyPurpose::Access),
+            "refresh" => Some(KeyPurpose::Refresh),
+            _ => None,
+        }
+    }
+
+    fn of_claims(claims: &Claims) -> Self {
+        match claims.typ.as_deref() {
+            Some("refresh") => KeyPurpose::Refresh,
+            _ => KeyPurpose::Access,
+        }
+    }
+}
+
+/// 非对称签名算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningAlgorithm {
+    RS256,
+    EdDSA,
+}
+
+impl SigningAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningAlgorithm::RS256 => "RS256",
+            SigningAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "RS256" => Some(SigningAlgorithm::RS256),
+            "EDDSA" | "ED25519" => Some(SigningAlgorithm::EdDSA),
+            _ => None,
+        }
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            SigningAlgorithm::RS256 => Algorithm::RS256,
+            SigningAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+}
+
+/// 新生成的签名密钥材料
+pub struct GeneratedSigningKey {
+    pub algorithm: SigningAlgorithm,
+    /// 私钥 DER（RS256: PKCS#1，EdDSA: PKCS#8），持久化前必须加密
+    pub private_der: Zeroizing<Vec<u8>>,
+    /// 公钥 JWK（不含 kid，由调用方设置）
+    pub public_jwk: Jwk,
+}
+
+/// 生成新的签名密钥
+pub fn generate_signing_key(algorithm: SigningAlgorithm) -> Result<GeneratedSigningKey> {
+    use rand::{rngs::OsRng, RngCore};
+
+    let (private_der, params) = match algorithm {
+        SigningAlgorithm::EdDSA => {
+            let mut seed = Zeroizing::new([0u8; 32]);
+            OsRng.fill_bytes(seed.as_mut());
+            let public = ed25519_dalek::SigningKey::from_bytes(&seed)
+                .verifying_key()
+                .to_bytes();
+
+            let mut der = Zeroizing::new(ED25519_PKCS8_PREFIX.to_vec());
+            der.extend_from_slice(seed.as_ref());
+            (
+                der,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(public),
+                }),
+            )
+        }
+        SigningAlgorithm::RS256 => {
+            use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts};
+
+            let key = rsa::RsaPrivateKey::new(&mut OsRng, RSA_KEY_BITS)
+                .map_err(|e| anyhow!("Failed to generate RSA key: {}", e))?;
+            let der = key
+                .to_pkcs1_der()
+                .map_err(|e| anyhow!("Failed to encode RSA key: {}", e))?;
+            (
+                Zeroizing::new(der.as_bytes().to_vec()),
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            )
+        }
+    };
+
+    Ok(GeneratedSigningKey {
+        algorithm,
+        private_der,
+        public_jwk: Jwk {
+            common: CommonParameters::default(),
+            algorithm: params,
+        },
+    })
+}
+
+/// 密钥环中的单个密钥
+pub struct JwtKey {
+    kid: String,
+    purpose: KeyPurpose,
+    algorithm: SigningAlgorithm,
+    public_jwk: Jwk,
+    decoding: DecodingKey,
+    /// 仅当前签名密钥持有私钥；宽限期内的旧密钥只用于验证
+    encoding: Option<EncodingKey>,
+}
+
+impl JwtKey {
+    pub fn new(
+        kid: String,
+        purpose: KeyPurpose,
+        algorithm: SigningAlgorithm,
+        mut public_jwk: Jwk,
+        private_der: Option<&[u8]>,
+    ) -> Result<Self> {
+        public_jwk.common = CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                SigningAlgorithm::RS256 => KeyAlgorithm::RS256,
+                SigningAlgorithm::EdDSA => KeyAlgorithm::EdDSA,
+            }),
+            key_id: Some(kid.clone()),
+            ..Default::default()
+        };
+
+        let decoding = DecodingKey::from_jwk(&public_jwk)
+            .map_err(|e| anyhow!("Invalid public key for {}: {}", kid, e))?;
+        let encoding = private_der.map(|der| match algorithm {
+            SigningAlgorithm::RS256 => EncodingKey::from_rsa_der(der),
+            SigningAlgorithm::EdDSA => EncodingKey::from_ed_der(der),
+        });
+
+        Ok(Self {
+            kid,
+            purpose,
+            algorithm,
+            public_jwk,
+            decoding,
+            encoding,
+        })
+    }
+}
+
+/// 签名密钥环：每个用途一个签名密钥 + 多个验证密钥
+#[derive(Default)]
+pub struct JwtKeyring {
+    keys: HashMap<String, JwtKey>,
+    signing: HashMap<KeyPurpose, String>,
+}
+
+impl JwtKeyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入密钥；持有私钥的密钥成为该用途的签名密钥
+    pub fn add_key(&mut self, key: JwtKey) {
+        if key.encoding.is_some() {
+            self.signing.insert(key.purpose, key.kid.clone());
+        }
+        self.keys.insert(key.kid.clone(), key);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// 使用该用途的签名密钥签名；无签名密钥时返回 None
+    pub fn sign(&self, claims: &Claims, purpose: KeyPurpose) -> Result<Option<String>> {
+        let Some(key) = self
+            .signing
+            .get(&purpose)
+            .and_then(|kid| self.keys.get(kid))
+        else {
+            return Ok(None);
+        };
+        let Some(encoding) = &key.encoding else {
+            return Ok(None);
+        };
+
+        let mut header = Header::new(key.algorithm.algorithm());
+        header.kid = Some(key.kid.clone());
+
+        encode(&header, claims, encoding)
+            .map(Some)
+            .map_err(|e| anyhow!("Failed to encode {} token: {}", purpose.as_str(), e))
+    }
+
+    /// 按 kid 验证，并校验 Token 类型与密钥用途一致
+    pub fn verify(&self, token: &str, kid: &str) -> Result<Claims> {
+        let key = self
+            .keys
+            .get(kid)
+            .ok_or_else(|| anyhow!("Unknown signing key id: {}", kid))?;
+
+        let claims = decode::<Claims>(
+            token,
+            &key.decoding,
+            &validation_for(key.algorithm.algorithm()),
+        )
+        .map_err(|e| {
+            tracing::warn!(kid = %kid, "JWT: token verification failed: {}", e);
+            anyhow!("Token verification failed: {}", e)
+        })?
+        .claims;
+
+        if KeyPurpose::of_claims(&claims) != key.purpose {
+            return Err(anyhow!(
+                "Token type does not match signing key purpose ({})",
+                key.purpose.as_str()
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    /// 对外公开的验证密钥集合
+    pub fn jwks(&self) -> JwkSet {
+        let mut keys: Vec<Jwk> = self.keys.values().map(|k| k.public_jwk.clone()).collect();
+        keys.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+        JwkSet { keys }
+    }
+}
+
+static KEYRING: RwLock<Option<Arc<JwtKeyring>>> = RwLock::new(None);
+
+/// 安装（替换）全局密钥环
+pub fn install_keyring(keyring: JwtKeyring) {
+    let keyring = (!keyring.is_empty()).then(|| Arc::new(keyring));
+    match KEYRING.write() {
+        Ok(mut guard) => *guard = keyring,
+        Err(poisoned) => *poisoned.into_inner() = keyring,
+    }
+}
+
+fn current_keyring() -> Option<Arc<JwtKeyring>> {
+    match KEYRING.read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// `/.well-known/jwks.json` 内容
+pub fn jwks() -> JwkSet {
+    current_keyring()
+        .map(|k| k.jwks())
+        .unwrap_or(JwkSet { keys: Vec::new() })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +547,155 @@ mod tests {
         assert_eq!(claims.sub, user_id.to_string());
         assert_eq!(claims.role, role);
     }
+
+    fn keyring_with(kid: &str, purpose: KeyPurpose, alg: SigningAlgorithm) -> JwtKeyring {
+        let generated = generate_signing_key(alg).unwrap();
+        let mut keyring = JwtKeyring::new();
+        keyring.add_key(
+            JwtKey::new(
+                kid.to_string(),
+                purpose,
+                alg,
+                generated.public_jwk,
+                Some(&generated.private_der),
+            )
+            .unwrap(),
+        );
+        keyring
+    }
+
+    #[test]
+    fn test_legacy_hs256_requires_deadline_once_keyring_installed() {
+        let now = Utc::now();
+        // 未加载密钥环：HS256 是唯一签发方式
+        assert!(legacy_hs256_allowed(false, true, None, now));
+        // 密钥环就绪：默认关闭，仅在截止时间前接受
+        assert!(!legacy_hs256_allowed(true, true, None, now));
+        assert!(legacy_hs256_allowed(
+            true,
+            true,
+            Some(now + Duration::hours(1)),
+            now
+        ));
+        assert!(!legacy_hs256_allowed(
+            true,
+            true,
+            Some(now - Duration::seconds(1)),
+            now
+        ));
+        // 显式关闭优先
+        assert!(!legacy_hs256_allowed(false, false, None, now));
+        assert!(!legacy_hs256_allowed(
+            true,
+            false,
+            Some(now + Duration::hours(1)),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_keyring_eddsa_sign_and_verify() {
+        let keyring = keyring_with("access-1", KeyPurpose::Access, SigningAlgorithm::EdDSA);
+        let claims = Claims::new(Uuid::new_v4(), Uuid::new_v4(), "viewer".into(), 60);
+
+        let token = keyring.sign(&claims, KeyPurpose::Access).unwrap().unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::EdDSA);
+        assert_eq!(header.kid.as_deref(), Some("access-1"));
+
+        let verified = keyring.verify(&token, "access-1").unwrap();
+        assert_eq!(verified.jti, claims.jti);
+
+        // 无 refresh 签名密钥时交由调用方回退
+        assert!(keyring
+            .sign(&claims, KeyPurpose::Refresh)
+            .unwrap()
+            .is_none());
+        assert!(keyring.verify(&token, "unknown").is_err());
+    }
+
+    #[test]
+    fn test_keyring_rs256_jwks_exposes_public_key() {
+        let keyring = keyring_with("refresh-1", KeyPurpose::Refresh, SigningAlgorithm::RS256);
+        let claims = Claims::new_with_type(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "viewer".into(),
+            60,
+            Some("refresh".into()),
+        );
+
+        let token = keyring.sign(&claims, KeyPurpose::Refresh).unwrap().unwrap();
+        assert!(keyring.verify(&token, "refresh-1").is_ok());
+
+        let jwks = serde_json::to_value(keyring.jwks()).unwrap();
+        let key = &jwks["keys"][0];
+        assert_eq!(key["kty"], "RSA");
+        assert_eq!(key["kid"], "refresh-1");
+        assert_eq!(key["alg"], "RS256");
+        assert!(key.get("d").is_none());
+    }
+
+    #[test]
+    fn test_keyring_rejects_purpose_mismatch() {
+        let keyring = keyring_with("refresh-1", KeyPurpose::Refresh, SigningAlgorithm::EdDSA);
+        // access 类型的 Claims 被 refresh 密钥签名后不可作为 access token 使用
+        let access_claims = Claims::new(Uuid::new_v4(), Uuid::new_v4(), "viewer".into(), 60);
+        let token = keyring
+            .sign(&access_claims, KeyPurpose::Refresh)
+            .unwrap()
+            .unwrap();
+        assert!(keyring.verify(&token, "refresh-1").is_err());
+    }
+
+    #[test]
+    fn test_retired_signing_key_still_verifies_during_grace() {
+        let old = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let new = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let claims = Claims::new(Uuid::new_v4(), Uuid::new_v4(), "viewer".into(), 60);
+
+        let mut before = JwtKeyring::new();
+        before.add_key(
+            JwtKey::new(
+                "old".into(),
+                KeyPurpose::Access,
+                SigningAlgorithm::EdDSA,
+                old.public_jwk.clone(),
+                Some(&old.private_der),
+            )
+            .unwrap(),
+        );
+        let old_token = before.sign(&claims, KeyPurpose::Access).unwrap().unwrap();
+
+        // 轮换后：旧密钥仅保留公钥用于验证，新密钥签名
+        let mut after = JwtKeyring::new();
+        after.add_key(
+            JwtKey::new(
+                "old".into(),
+                KeyPurpose::Access,
+                SigningAlgorithm::EdDSA,
+                old.public_jwk,
+                None,
+            )
+            .unwrap(),
+        );
+        after.add_key(
+            JwtKey::new(
+                "new".into(),
+                KeyPurpose::Access,
+                SigningAlgorithm::EdDSA,
+                new.public_jwk,
+                Some(&new.private_der),
+            )
+            .unwrap(),
+        );
+
+        assert!(after.verify(&old_token, "old").is_ok());
+        let new_token = after.sign(&claims, KeyPurpose::Access).unwrap().unwrap();
+        assert_eq!(
+            decode_header(&new_token).unwrap().kid.as_deref(),
+            Some("new")
+        );
+        assert_eq!(after.jwks().keys.len(), 2);
+    }
 }
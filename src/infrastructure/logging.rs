@@ -140,6 +140,7 @@ mod tests {
             log_file_path: None,
             max_file_size_mb: 100,
             max_files: 10,
+            otlp: Default::default(),
         };
 
         // 测试配置创建
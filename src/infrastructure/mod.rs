@@ -4,14 +4,19 @@ pub mod cache_strategy;
 pub mod db;
 pub mod distributed_lock;
 pub mod encryption;
+pub mod event_bus;
+pub mod event_outbox;
 pub mod jwt;
 pub mod log_redact;
 pub mod log_sanitizer_enhanced; // ✅ P3: 增强型日志脱敏器
+pub mod monitoring;
 pub mod password;
 pub mod rpc_selector;
 pub mod rpc_validator;
+pub mod telemetry;
 pub mod upstream;
 pub mod validation;
 
 // 别名兼容性
 pub use audit as immutable_audit;
+pub mod worker_supervisor;
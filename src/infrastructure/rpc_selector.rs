@@ -9,6 +9,7 @@ use sqlx::{PgPool, Row};
 use tokio::{sync::RwLock, time::interval};
 
 use crate::infrastructure::cache::RedisCtx;
+use crate::infrastructure::telemetry::TracePropagation;
 
 const OPEN_THRESHOLD: i64 = 3;
 const OPEN_TIMEOUT_SECS: u64 = 60;
@@ -93,6 +94,7 @@ impl RpcSelector {
         .await?;
         for r in rows {
             let id: uuid::Uuid = r.try_get("id")?;
+            let chain: String = r.try_get("chain")?;
             let url: String = r.try_get("url")?;
             let circuit: String = r.try_get("circuit_state")?;
             let last_checked: Option<chrono::DateTime<chrono::Utc>> =
@@ -122,9 +124,17 @@ impl RpcSelector {
                 .post(&url)
                 .header("Content-Type", "application/json")
                 .body(body)
+                .with_trace_context()
                 .send()
                 .await;
-            let latency = start.elapsed().as_millis() as i64;
+            let elapsed = start.elapsed();
+            let latency = elapsed.as_millis() as i64;
+            crate::metrics::observe_rpc_request(
+                &chain,
+                &url,
+                elapsed,
+                matches!(&res, Ok(resp) if resp.status().is_success()),
+            );
             match res {
                 Ok(resp) if resp.status().is_success() => {
                     // Success: reset fail_count, update latency
@@ -242,16 +252,33 @@ impl RpcSelector {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn select(&self, chain: &str) -> Option<RpcEndpoint> {
         crate::metrics::inc_rpc_selection();
+        let endpoints = self.load_endpoints().await;
+        self.score_pick(chain, &endpoints)
+    }
+
+    /// 选择 N 个不同的端点（用于多节点共识）
+    ///
+    /// 优先选择不同服务商（主域名）的健康端点，不足时再按不同 URL 补齐；
+    /// 熔断打开的端点不参与，结果可能少于 `count`
+    #[tracing::instrument(skip_all, fields(chain = %chain, count = count))]
+    pub async fn select_distinct(&self, chain: &str, count: usize) -> Vec<RpcEndpoint> {
+        crate::metrics::inc_rpc_selection();
+        let endpoints = self.load_endpoints().await;
+        pick_distinct(chain, &endpoints, count)
+    }
 
+    /// 读取端点列表：L1 内存 → L2 Redis → L3 数据库
+    async fn load_endpoints(&self) -> Vec<RpcEndpoint> {
         // L1: 本地内存缓存
         {
             let cache = self.cache.read().await;
             if let Some(c) = &*cache {
                 if c.fetched_at.elapsed() < self.ttl {
                     tracing::debug!("rpc_cache_hit_l1");
-                    return self.score_pick(chain, &c.endpoints);
+                    return c.endpoints.clone();
                 }
             }
         }
@@ -275,7 +302,7 @@ impl RpcSelector {
                                 endpoints: endpoints.clone(),
                                 fetched_at: Instant::now(),
                             });
-                            return self.score_pick(chain, &endpoints);
+                            return endpoints;
                         }
                     }
                     Ok(None) => tracing::debug!("rpc_cache_miss_l2_redis"),
@@ -292,7 +319,8 @@ impl RpcSelector {
         let cache = self.cache.read().await;
         cache
             .as_ref()
-            .and_then(|c| self.score_pick(chain, &c.endpoints))
+            .map(|c| c.endpoints.clone())
+            .unwrap_or_default()
     }
 
     fn score_pick(&self, chain: &str, list: &[RpcEndpoint]) -> Option<RpcEndpoint> {
@@ -322,6 +350,67 @@ impl RpcSelector {
     }
 }
 
+/// 从候选端点中选出至多 `count` 个不同端点
+///
+/// 候选顺序：健康且熔断关闭 → 半开；同层按 `priority * 100 + avg_latency_ms` 排序。
+/// 第一轮每个服务商只取一个，第二轮按不同 URL 补齐
+pub(crate) fn pick_distinct(chain: &str, list: &[RpcEndpoint], count: usize) -> Vec<RpcEndpoint> {
+    let tier = |e: &RpcEndpoint| {
+        if e.healthy && e.circuit_state == "closed" {
+            Some(0)
+        } else if e.circuit_state == "half_open" {
+            Some(1)
+        } else {
+            None
+        }
+    };
+
+    let mut candidates: Vec<(u8, &RpcEndpoint)> = list
+        .iter()
+        .filter(|e| e.chain == chain)
+        .filter_map(|e| tier(e).map(|t| (t, e)))
+        .collect();
+    candidates.sort_by_key(|(t, e)| (*t, e.priority * 100 + e.avg_latency_ms));
+
+    let mut picked: Vec<RpcEndpoint> = Vec::with_capacity(count);
+    let mut providers: Vec<String> = Vec::new();
+
+    for (_, e) in &candidates {
+        if picked.len() >= count {
+            break;
+        }
+        let provider = provider_key(&e.url);
+        if !providers.contains(&provider) {
+            providers.push(provider);
+            picked.push((*e).clone());
+        }
+    }
+    for (_, e) in &candidates {
+        if picked.len() >= count {
+            break;
+        }
+        if !picked.iter().any(|p| p.url == e.url) {
+            picked.push((*e).clone());
+        }
+    }
+
+    picked
+}
+
+/// 服务商标识：主机名的最后两级域名（IP 地址取完整主机名）
+fn provider_key(url: &str) -> String {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()))
+        .unwrap_or_else(|| url.to_ascii_lowercase());
+
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return host;
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    labels[labels.len().saturating_sub(2)..].join(".")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -679,6 +768,131 @@ mod tests {
         );
     }
 
+    fn endpoint(
+        chain: &str,
+        url: &str,
+        priority: i64,
+        healthy: bool,
+        circuit: &str,
+    ) -> RpcEndpoint {
+        RpcEndpoint {
+            id: uuid::Uuid::new_v4(),
+            chain: chain.to_string(),
+            url: url.to_string(),
+            priority,
+            healthy,
+            fail_count: 0,
+            avg_latency_ms: 100,
+            last_latency_ms: 100,
+            circuit_state: circuit.to_string(),
+            last_checked_at: None,
+        }
+    }
+
+    /// Test 13: 多节点选择优先不同服务商
+    #[test]
+    fn test_pick_distinct_prefers_distinct_providers() {
+        let endpoints = vec![
+            endpoint(
+                "ethereum",
+                "https://eth-mainnet.alchemyapi.io/v2/a",
+                1,
+                true,
+                "closed",
+            ),
+            endpoint(
+                "ethereum",
+                "https://eth-mainnet.g.alchemy.com/v2/b",
+                1,
+                true,
+                "closed",
+            ),
+            endpoint(
+                "ethereum",
+                "https://eth-mainnet.alchemyapi.io/v2/c",
+                1,
+                true,
+                "closed",
+            ),
+            endpoint(
+                "ethereum",
+                "https://mainnet.infura.io/v3/d",
+                2,
+                true,
+                "closed",
+            ),
+            endpoint("ethereum", "https://rpc.ankr.com/eth", 3, true, "closed"),
+        ];
+
+        let picked = pick_distinct("ethereum", &endpoints, 3);
+        let urls: Vec<&str> = picked.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://eth-mainnet.alchemyapi.io/v2/a",
+                "https://eth-mainnet.g.alchemy.com/v2/b",
+                "https://mainnet.infura.io/v3/d",
+            ]
+        );
+    }
+
+    /// Test 14: 服务商不足时按不同 URL 补齐，且不重复
+    #[test]
+    fn test_pick_distinct_fills_with_distinct_urls() {
+        let endpoints = vec![
+            endpoint(
+                "bsc",
+                "https://bsc-dataseed1.binance.org",
+                1,
+                true,
+                "closed",
+            ),
+            endpoint(
+                "bsc",
+                "https://bsc-dataseed2.binance.org",
+                2,
+                true,
+                "closed",
+            ),
+            endpoint(
+                "bsc",
+                "https://bsc-dataseed1.binance.org",
+                3,
+                true,
+                "closed",
+            ),
+        ];
+
+        let picked = pick_distinct("bsc", &endpoints, 3);
+        assert_eq!(picked.len(), 2);
+        assert_ne!(picked[0].url, picked[1].url);
+    }
+
+    /// Test 15: 熔断打开的端点不参与，半开端点排在健康端点之后
+    #[test]
+    fn test_pick_distinct_skips_open_circuits() {
+        let endpoints = vec![
+            endpoint("polygon", "https://a.example.com", 1, false, "open"),
+            endpoint("polygon", "https://b.example.org", 5, false, "half_open"),
+            endpoint("polygon", "https://c.example.net", 9, true, "closed"),
+            endpoint("ethereum", "https://d.example.io", 1, true, "closed"),
+        ];
+
+        let picked = pick_distinct("polygon", &endpoints, 3);
+        let urls: Vec<&str> = picked.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://c.example.net", "https://b.example.org"]);
+    }
+
+    #[test]
+    fn test_provider_key() {
+        assert_eq!(
+            provider_key("https://eth-mainnet.g.alchemy.com/v2/x"),
+            "alchemy.com"
+        );
+        assert_eq!(provider_key("http://127.0.0.1:8545"), "127.0.0.1");
+        assert_eq!(provider_key("https://localhost:8545"), "localhost");
+    }
+
     // ============ 辅助函数 ============
 
     // 提取选择逻辑为独立函数，便于单元测试（不需要数据库）
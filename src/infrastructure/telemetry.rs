@@ -0,0 +1,179 @@
+//! OpenTelemetry 分布式追踪
+//!
+//! - OTLP/HTTP 导出（`LoggingConfig.otlp`，默认关闭）
+//! - W3C `traceparent`：入站请求提取后作为 HTTP span 的父上下文，
+//!   出站 reqwest 请求通过 [`TracePropagation::with_trace_context`] 注入当前 span 上下文
+//!
+//! 未开启导出时不挂载 OpenTelemetry 层，span 上下文为空，注入为空操作
+
+use std::collections::HashMap;
+
+use opentelemetry::{
+    propagation::{Extractor, Injector, TextMapPropagator},
+    trace::TraceContextExt,
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{Sampler, Tracer},
+    Resource,
+};
+use tracing::Subscriber;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::OtlpConfig;
+
+/// 构建 OTLP 导出层；未开启或初始化失败时返回 `None`（不影响日志输出）
+///
+/// 需在 Tokio 运行时内调用（批量导出器依赖后台任务）
+pub fn otlp_layer<S>(config: &OtlpConfig) -> Option<OpenTelemetryLayer<S, Tracer>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    if !config.enabled {
+        return None;
+    }
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        config.sample_ratio.clamp(0.0, 1.0),
+    )));
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(config.endpoint.trim_end_matches('/')),
+        )
+        .with_trace_config(
+            opentelemetry_sdk::trace::config()
+                .with_sampler(sampler)
+                .with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )])),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio);
+
+    match tracer {
+        Ok(tracer) => {
+            opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        Err(e) => {
+            eprintln!("OTLP trace exporter init failed: {}", e);
+            None
+        }
+    }
+}
+
+/// 刷新并关闭导出器（进程退出前调用，避免丢失最后一批 span）
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+struct MapInjector<'a>(&'a mut HashMap<String, String>);
+
+impl Injector for MapInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
+
+/// 从入站请求头提取上游 trace 上下文（无 `traceparent` 时为空上下文）
+pub fn extract_context(headers: &axum::http::HeaderMap) -> opentelemetry::Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
+
+/// 当前 span 的传播头（`traceparent` / `tracestate`），无有效上下文时为空
+pub fn trace_headers() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    if context.span().span_context().is_valid() {
+        TraceContextPropagator::new().inject_context(&context, &mut MapInjector(&mut headers));
+    }
+    headers
+}
+
+/// 当前 span 的 trace id（32 位十六进制），无有效上下文时为 `None`
+pub fn current_trace_id() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let span_context = context.span().span_context().clone();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+/// 出站 HTTP 请求注入 W3C trace 上下文
+pub trait TracePropagation {
+    fn with_trace_context(self) -> Self;
+}
+
+impl TracePropagation for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        trace_headers()
+            .into_iter()
+            .fold(self, |builder, (key, value)| builder.header(key, value))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    pub(crate) const UPSTREAM_TRACEPARENT: &str =
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    /// 本地 span 上下文（不导出），用于验证传播逻辑
+    ///
+    /// SDK Tracer 仅弱引用 provider，需常驻
+    pub(crate) fn test_subscriber() -> impl tracing::Subscriber + Send + Sync {
+        static PROVIDER: std::sync::OnceLock<opentelemetry_sdk::trace::TracerProvider> =
+            std::sync::OnceLock::new();
+        let provider =
+            PROVIDER.get_or_init(|| opentelemetry_sdk::trace::TracerProvider::builder().build());
+        tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+    }
+
+    #[test]
+    fn test_child_span_propagates_upstream_trace_id() {
+        let _guard = tracing::subscriber::set_default(test_subscriber());
+
+        let mut incoming = axum::http::HeaderMap::new();
+        incoming.insert("traceparent", UPSTREAM_TRACEPARENT.parse().unwrap());
+        let span = tracing::info_span!("child");
+        span.set_parent(extract_context(&incoming));
+        let _entered = span.enter();
+
+        let traceparent = trace_headers().remove("traceparent").unwrap();
+        assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert!(!traceparent.contains("00f067aa0ba902b7"));
+        assert_eq!(
+            current_trace_id().as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+    }
+
+    #[test]
+    fn test_no_headers_without_active_trace() {
+        assert!(trace_headers().is_empty());
+        assert!(current_trace_id().is_none());
+    }
+}
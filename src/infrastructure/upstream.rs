@@ -11,6 +11,8 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 #[derive(Clone)]
 pub struct UpstreamClient {
     pub evm_rpc: String,
@@ -80,7 +82,12 @@ impl UpstreamClient {
         let mut attempt = 0usize;
         loop {
             let start = Instant::now();
-            let res = client.post(url).json(body).send().await;
+            let res = client
+                .post(url)
+                .json(body)
+                .with_trace_context()
+                .send()
+                .await;
             match res {
                 Ok(resp) => {
                     let status = resp.status();
@@ -0,0 +1,481 @@
+//! 后台任务主节点选举与分片
+//! 企业级实现：基于 `DistributedLock` 的租约（lease）
+//!
+//! - 每个后台任务（或其按链划分的分片）对应一个租约键，值为持有实例 ID
+//! - 持有者按固定周期续约；续约失败（网络分区/被抢占）立即停止本地任务
+//! - 续约先于心跳与再平衡执行；周期出错或超时时，本周期未确认续约的分片全部中止
+//! - 任务每轮开始前经 `lease_held` 复核租约（fencing），中止信号未送达时也不会双主
+//! - 主节点宕机后租约在 TTL 内过期，其他实例在下个周期自动接管
+//! - 分片任务按存活实例数均摊：每个实例最多持有 ceil(分片数 / 实例数) 个分片，
+//!   新实例加入后超额持有者每周期释放一个分片，逐步再平衡
+//! - 所有状态都在 Redis 中，任意实例都能回答「谁持有什么」
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use tokio::{sync::Mutex, task::JoinHandle};
+
+use crate::infrastructure::distributed_lock::DistributedLock;
+
+const LEASE_PREFIX: &str = "worker_lease:";
+const INSTANCE_PREFIX: &str = "worker_instance:";
+const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(30);
+const DEFAULT_RENEW_INTERVAL: Duration = Duration::from_secs(10);
+/// 未在分片列表中的链统一归入该分片，保证没有链被遗漏
+const REST_SHARD_KEY: &str = "_rest";
+
+/// 按链划分的工作分片
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainShard {
+    /// 不分片（单主）
+    All,
+    /// 指定链（大写，如 ETH）
+    Chain(String),
+    /// 其余链：不在列表中的全部链
+    Rest(Vec<String>),
+}
+
+impl ChainShard {
+    pub fn key(&self) -> String {
+        match self {
+            ChainShard::All => "all".to_string(),
+            ChainShard::Chain(chain) => chain.clone(),
+            ChainShard::Rest(_) => REST_SHARD_KEY.to_string(),
+        }
+    }
+
+    /// SQL 过滤参数：`($n::TEXT[] IS NULL OR (UPPER(chain) = ANY($n)) = $m)`
+    pub fn sql_filter(&self) -> (Option<Vec<String>>, bool) {
+        match self {
+            ChainShard::All => (None, true),
+            ChainShard::Chain(chain) => (Some(vec![chain.clone()]), true),
+            ChainShard::Rest(chains) => (Some(chains.clone()), false),
+        }
+    }
+
+    /// 该分片是否负责指定链
+    pub fn owns(&self, chain: &str) -> bool {
+        let chain = chain.to_uppercase();
+        match self {
+            ChainShard::All => true,
+            ChainShard::Chain(c) => *c == chain,
+            ChainShard::Rest(chains) => !chains.contains(&chain),
+        }
+    }
+
+    /// 由链列表生成分片：每条链一个分片 + 其余链分片
+    pub fn for_chains(chains: &[String]) -> Vec<ChainShard> {
+        let mut normalized: Vec<String> = Vec::new();
+        for chain in chains {
+            let chain = chain.trim().to_uppercase();
+            if !chain.is_empty() && !normalized.contains(&chain) {
+                normalized.push(chain);
+            }
+        }
+        if normalized.is_empty() {
+            return vec![ChainShard::All];
+        }
+
+        let mut shards: Vec<ChainShard> =
+            normalized.iter().cloned().map(ChainShard::Chain).collect();
+        shards.push(ChainShard::Rest(normalized));
+        shards
+    }
+
+    /// 从环境变量 WORKER_CHAIN_SHARDS 读取（逗号分隔，默认 ETH,BSC,POLYGON）
+    pub fn from_env() -> Vec<ChainShard> {
+        let chains = std::env::var("WORKER_CHAIN_SHARDS")
+            .unwrap_or_else(|_| "ETH,BSC,POLYGON".to_string())
+            .split(',')
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        Self::for_chains(&chains)
+    }
+}
+
+/// 任务持有的租约（fencing 令牌）
+#[derive(Clone)]
+struct LeaseFence {
+    lock: Arc<DistributedLock>,
+    key: String,
+    owner: String,
+}
+
+tokio::task_local! {
+    static WORKER_LEASE: LeaseFence;
+}
+
+/// 当前任务是否仍持有其租约
+///
+/// 长时间运行的任务在每轮处理前调用，返回 `false` 时跳过本轮（租约已被接管或 Redis 不可达）。
+/// 不在监督器下运行（单测、单机脚本）时恒为 `true`
+pub async fn lease_held() -> bool {
+    let Ok(fence) = WORKER_LEASE.try_with(|f| f.clone()) else {
+        return true;
+    };
+    match fence.lock.get_owner(&fence.key).await {
+        Ok(owner) => owner.as_deref() == Some(fence.owner.as_str()),
+        Err(e) => {
+            tracing::warn!(lease = %fence.key, error = ?e, "Failed to check worker lease");
+            false
+        }
+    }
+}
+
+/// 任务工厂：每获得一个分片租约就启动一个任务实例
+pub type WorkerFactory = Arc<dyn Fn(ChainShard) -> BoxFuture<'static, ()> + Send + Sync>;
+
+struct WorkerSpec {
+    name: String,
+    shards: Vec<ChainShard>,
+    factory: WorkerFactory,
+}
+
+/// 本实例正在运行的分片
+struct RunningShard {
+    worker: String,
+    handle: JoinHandle<()>,
+    /// 最近一次确认持有租约（获取或续约成功）的时间
+    confirmed_at: Instant,
+}
+
+/// 每个实例的目标分片数：ceil(分片数 / 存活实例数)，至少 1
+pub fn target_shards(total_shards: usize, live_instances: usize) -> usize {
+    if total_shards == 0 {
+        return 0;
+    }
+    total_shards.div_ceil(live_instances.max(1)).max(1)
+}
+
+pub fn lease_key(worker: &str, shard: &ChainShard) -> String {
+    format!("{}{}:{}", LEASE_PREFIX, worker, shard.key())
+}
+
+/// 当前进程的实例 ID（fly.io 分配 ID / 主机名 + 随机后缀，重启后不复用）
+pub fn default_instance_id() -> String {
+    let host = std::env::var("FLY_ALLOC_ID")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "local".to_string());
+    format!(
+        "{}-{}",
+        host,
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    )
+}
+
+/// 租约持有情况
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkerLeaseInfo {
+    pub worker: String,
+    pub shard: String,
+    pub owner: String,
+    pub ttl_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkerOwnershipReport {
+    /// 存活实例
+    pub instances: Vec<String>,
+    pub leases: Vec<WorkerLeaseInfo>,
+}
+
+/// 读取全集群的租约持有情况（任意实例均可调用）
+pub async fn ownership_report(lock: &DistributedLock) -> Result<WorkerOwnershipReport> {
+    let mut instances: Vec<String> = lock
+        .scan_keys(&format!("{}*", INSTANCE_PREFIX))
+        .await?
+        .into_iter()
+        .filter_map(|k| k.strip_prefix(INSTANCE_PREFIX).map(|s| s.to_string()))
+        .collect();
+    instances.sort();
+
+    let mut leases = Vec::new();
+    for key in lock.scan_keys(&format!("{}*", LEASE_PREFIX)).await? {
+        let Some(owner) = lock.get_owner(&key).await? else {
+            continue; // 扫描后已过期
+        };
+        let rest = key.trim_start_matches(LEASE_PREFIX);
+        let (worker, shard) = rest.rsplit_once(':').unwrap_or((rest, "all"));
+        leases.push(WorkerLeaseInfo {
+            worker: worker.to_string(),
+            shard: shard.to_string(),
+            owner,
+            ttl_secs: lock.get_ttl(&key).await?,
+        });
+    }
+    leases.sort_by(|a, b| (&a.worker, &a.shard).cmp(&(&b.worker, &b.shard)));
+
+    Ok(WorkerOwnershipReport { instances, leases })
+}
+
+/// 后台任务监督器
+pub struct WorkerSupervisor {
+    lock: Arc<DistributedLock>,
+    instance_id: String,
+    lease_ttl: Duration,
+    renew_interval: Duration,
+    workers: Vec<WorkerSpec>,
+    running: Mutex<HashMap<String, RunningShard>>,
+}
+
+impl WorkerSupervisor {
+    pub fn new(lock: Arc<DistributedLock>) -> Self {
+        let lease_ttl = std::env::var("WORKER_LEASE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v >= 3)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_LEASE_TTL);
+
+        Self {
+            lock,
+            instance_id: default_instance_id(),
+            lease_ttl,
+            // 一个 TTL 内至少续约三次
+            renew_interval: DEFAULT_RENEW_INTERVAL.min(lease_ttl / 3),
+            workers: Vec::new(),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// 注册单主任务（集群内同一时刻只有一个实例运行）
+    pub fn register<F>(self, name: &str, factory: F) -> Self
+    where
+        F: Fn(ChainShard) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.register_sharded(name, vec![ChainShard::All], factory)
+    }
+
+    /// 注册分片任务（每个分片一个租约，可分布在不同实例）
+    pub fn register_sharded<F>(mut self, name: &str, shards: Vec<ChainShard>, factory: F) -> Self
+    where
+        F: Fn(ChainShard) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        self.workers.push(WorkerSpec {
+            name: name.to_string(),
+            shards,
+            factory: Arc::new(factory),
+        });
+        self
+    }
+
+    /// 启动监督循环（持续运行）
+    pub async fn start(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.renew_interval);
+        tracing::info!(
+            instance_id = %self.instance_id,
+            workers = self.workers.len(),
+            lease_ttl_secs = self.lease_ttl.as_secs(),
+            "Worker supervisor started"
+        );
+
+        loop {
+            ticker.tick().await;
+            let started = Instant::now();
+            // Redis 卡住时不能无限等待：超时按出错处理
+            let result = match tokio::time::timeout(self.renew_interval, self.tick()).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!(
+                    "tick timed out after {}s",
+                    self.renew_interval.as_secs()
+                )),
+            };
+            if let Err(e) = result {
+                tracing::error!(error = ?e, "Worker supervisor tick failed");
+                self.abort_unconfirmed(started).await;
+            }
+        }
+    }
+
+    /// 中止本周期未确认续约的分片（宁可暂停也不双主）
+    async fn abort_unconfirmed(&self, since: Instant) {
+        let mut running = self.running.lock().await;
+        running.retain(|key, shard| {
+            if shard.confirmed_at >= since {
+                return true;
+            }
+            shard.handle.abort();
+            tracing::warn!(
+                lease = %key,
+                worker = %shard.worker,
+                "Worker lease unconfirmed after failed tick, shard aborted"
+            );
+            false
+        });
+    }
+
+    async fn tick(&self) -> Result<()> {
+        let ttl = self.lease_ttl.as_secs();
+        let mut running = self.running.lock().await;
+
+        // 1. 续约已持有的租约（先于心跳，心跳失败不影响续约）；续约失败或任务退出则停止并释放
+        let keys: Vec<String> = running.keys().cloned().collect();
+        for key in keys {
+            // Redis 不可达时按失去租约处理（宁可暂停也不双主）
+            let renewed = match self.lock.renew(&key, &self.instance_id, ttl).await {
+                Ok(renewed) => renewed,
+                Err(e) => {
+                    tracing::warn!(lease = %key, error = ?e, "Failed to renew worker lease");
+                    false
+                }
+            };
+            let finished = running.get(&key).is_some_and(|r| r.handle.is_finished());
+            if renewed && !finished {
+                if let Some(shard) = running.get_mut(&key) {
+                    shard.confirmed_at = Instant::now();
+                }
+                continue;
+            }
+            if let Some(shard) = running.remove(&key) {
+                shard.handle.abort();
+                if finished {
+                    if let Err(e) = self.lock.release(&key, &self.instance_id).await {
+                        tracing::warn!(lease = %key, error = ?e, "Failed to release worker lease");
+                    }
+                }
+                tracing::warn!(
+                    lease = %key,
+                    worker = %shard.worker,
+                    lost = !renewed,
+                    "Worker lease dropped"
+                );
+            }
+        }
+
+        // 2. 实例心跳
+        let instance_key = format!("{}{}", INSTANCE_PREFIX, self.instance_id);
+        if !self
+            .lock
+            .renew(&instance_key, &self.instance_id, ttl)
+            .await?
+        {
+            self.lock
+                .try_acquire_with_value(&instance_key, &self.instance_id, ttl)
+                .await?;
+        }
+        let live_instances = self
+            .lock
+            .scan_keys(&format!("{}*", INSTANCE_PREFIX))
+            .await?
+            .len();
+
+        // 3. 按目标分片数获取/释放
+        for worker in &self.workers {
+            let target = target_shards(worker.shards.len(), live_instances);
+            let owned: Vec<String> = worker
+                .shards
+                .iter()
+                .map(|s| lease_key(&worker.name, s))
+                .filter(|k| running.contains_key(k))
+                .collect();
+
+            if owned.len() > target {
+                // 每周期只释放一个，避免抖动
+                if let Some(key) = owned.last() {
+                    if let Some(shard) = running.remove(key) {
+                        shard.handle.abort();
+                        self.lock.release(key, &self.instance_id).await?;
+                        tracing::info!(lease = %key, "Released worker lease for rebalancing");
+                    }
+                }
+                continue;
+            }
+
+            let mut held = owned.len();
+            for shard in &worker.shards {
+                if held >= target {
+                    break;
+                }
+                let key = lease_key(&worker.name, shard);
+                if running.contains_key(&key) {
+                    continue;
+                }
+                if self
+                    .lock
+                    .try_acquire_with_value(&key, &self.instance_id, ttl)
+                    .await?
+                {
+                    let fence = LeaseFence {
+                        lock: self.lock.clone(),
+                        key: key.clone(),
+                        owner: self.instance_id.clone(),
+                    };
+                    let handle =
+                        tokio::spawn(WORKER_LEASE.scope(fence, (worker.factory)(shard.clone())));
+                    running.insert(
+                        key.clone(),
+                        RunningShard {
+                            worker: worker.name.clone(),
+                            handle,
+                            confirmed_at: Instant::now(),
+                        },
+                    );
+                    held += 1;
+                    tracing::info!(
+                        lease = %key,
+                        instance_id = %self.instance_id,
+                        "Acquired worker lease"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lease_held_outside_supervisor() {
+        // 不在监督器下运行的任务不受 fencing 影响
+        assert!(lease_held().await);
+    }
+
+    #[test]
+    fn test_target_shards_balances_across_instances() {
+        assert_eq!(target_shards(4, 1), 4);
+        assert_eq!(target_shards(4, 2), 2);
+        assert_eq!(target_shards(4, 3), 2);
+        assert_eq!(target_shards(4, 8), 1);
+        assert_eq!(target_shards(1, 0), 1);
+        assert_eq!(target_shards(0, 3), 0);
+    }
+
+    #[test]
+    fn test_chain_shards_cover_every_chain() {
+        let shards = ChainShard::for_chains(&["eth".into(), " BSC".into(), "ETH".into()]);
+        assert_eq!(
+            shards,
+            vec![
+                ChainShard::Chain("ETH".into()),
+                ChainShard::Chain("BSC".into()),
+                ChainShard::Rest(vec!["ETH".into(), "BSC".into()]),
+            ]
+        );
+
+        // 每条链恰好属于一个分片
+        for chain in ["eth", "BSC", "SOL", "ton"] {
+            assert_eq!(shards.iter().filter(|s| s.owns(chain)).count(), 1);
+        }
+
+        assert_eq!(ChainShard::for_chains(&[]), vec![ChainShard::All]);
+        assert_eq!(ChainShard::Rest(vec![]).sql_filter(), (Some(vec![]), false));
+        assert_eq!(
+            lease_key("tx_monitor", &ChainShard::Chain("ETH".into())),
+            "worker_lease:tx_monitor:ETH"
+        );
+    }
+}
@@ -132,23 +132,39 @@ async fn main() -> Result<()> {
         None
     };
 
-    // ✅ 2. 初始化日志（企业级：结构化日志 + 脱敏）
+    // ✅ 2. 初始化日志（企业级：结构化日志 + 脱敏）+ OTLP 链路追踪（默认关闭）
+    let otlp_config = loaded_config
+        .as_ref()
+        .map(|c| c.logging.otlp.clone())
+        .unwrap_or_default();
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "ironcore=debug,tower_http=debug,sqlx=warn".into()),
         )
         .with(tracing_subscriber::fmt::layer())
+        .with(ironcore::infrastructure::telemetry::otlp_layer(
+            &otlp_config,
+        ))
         // TODO: 添加日志脱敏层
         // .with(ironcore::api::middleware::log_sanitizer::SanitizingLayer)
         .init();
 
     tracing::info!("🚀 Starting IronCore Multi-Chain Wallet System");
+    if otlp_config.enabled {
+        tracing::info!(
+            "✅ OTLP tracing enabled: endpoint={}, service={}",
+            otlp_config.endpoint,
+            otlp_config.service_name
+        );
+    }
 
     // ✅ 3. 连接数据库
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let pool = PgPool::connect(&database_url).await?;
     tracing::info!("✅ Database connected");
+    // 连接池仪表（/metrics 抓取时实时读取）
+    ironcore::metrics::register_db_pool(&pool);
 
     // ✅ 4. 运行数据库迁移（可选，用于开发测试）
     // 注意：生产环境建议单独运行迁移
@@ -246,52 +262,210 @@ async fn main() -> Result<()> {
         tracing::info!("✅ Fiat payment providers initialized");
     }
 
-    // ✅ 8. 启动后台服务
-
-    // 8.1 交易监控服务
-    let tx_monitor = Arc::new(
-        ironcore::service::transaction_monitor::TransactionMonitor::new(
-            pool.clone(),
-            state.blockchain_client.clone(),
-        ),
-    );
-    let tx_monitor_clone = tx_monitor.clone();
-    tokio::spawn(async move {
-        tx_monitor_clone.start_background_monitor().await;
-    });
-    tracing::info!("✅ Transaction monitor started");
-
-    // 8.2 交易自动恢复服务（RBF）
-    let nonce_manager = Arc::new(ironcore::service::nonce_manager::NonceManager::new(
+    // ✅ 7.6 JWT 签名密钥环（RS256/EdDSA + kid；未配置时保持 HS256）
+    let jwt_key_manager = Arc::new(ironcore::service::jwt_keys::JwtKeyManager::new(
         pool.clone(),
-        distributed_lock.clone(),
     ));
-
-    let tx_auto_recovery = Arc::new(
-        ironcore::service::transaction_auto_recovery::TransactionAutoRecovery::new(
-            pool.clone(),
-            state.blockchain_client.clone(),
-            nonce_manager.clone(),
-        ),
-    );
-    let tx_auto_recovery_clone = tx_auto_recovery.clone();
+    if let Err(e) = jwt_key_manager.bootstrap().await {
+        tracing::warn!("Failed to load JWT signing keys, using HS256 only: {}", e);
+    }
     tokio::spawn(async move {
-        tx_auto_recovery_clone.start_background_monitor().await;
+        jwt_key_manager.start_background_refresh().await;
     });
-    tracing::info!("✅ Transaction auto-recovery started");
 
-    // 8.3 跨链事件监听服务
-    let cross_chain_listener = Arc::new(
-        ironcore::service::cross_chain_event_listener::CrossChainEventListener::new(
+    // ✅ 8. 启动后台服务
+    //
+    // 轮询型后台任务由 WorkerSupervisor 统一调度：多副本部署时通过 Redis 租约选主，
+    // 按链分片分布到不同实例，主节点宕机后租约过期自动切换（GET /api/v1/admin/workers 查看归属）
+    let nonce_manager = state.nonce_manager.clone();
+    // 入账索引器发现转入后经此通道触发余额同步
+    let (balance_event_tx, mut balance_event_rx) =
+        tokio::sync::mpsc::channel::<ironcore::service::balance_sync_event::BalanceSyncEvent>(1024);
+    {
+        let handler = ironcore::service::balance_sync_event::BalanceSyncEventHandler::new(
             pool.clone(),
             state.blockchain_client.clone(),
-        ),
+        );
+        tokio::spawn(async move {
+            while let Some(event) = balance_event_rx.recv().await {
+                if let Err(e) = handler.handle_event(event).await {
+                    tracing::warn!(error = %e, "Balance sync event failed");
+                }
+            }
+        });
+    }
+
+    let worker_supervisor = {
+        use futures::FutureExt;
+        use ironcore::{
+            infrastructure::worker_supervisor::{ChainShard, WorkerSupervisor},
+            service::{
+                alert_engine::{AlertEvaluator, AlertRulesConfig},
+                approvals::ApprovalExpiryWorker,
+                audit_ledger::AuditCheckpointer,
+                cross_chain_event_listener::CrossChainEventListener,
+                deposit_indexer::{self, DepositIndexer},
+                limit_order_engine::{LimitOrderEngine, OneInchMarket},
+                transaction_auto_recovery::TransactionAutoRecovery,
+                transaction_monitor::TransactionMonitor,
+            },
+        };
+
+        let chain_shards = ChainShard::from_env();
+        let (tx_pool, tx_client) = (pool.clone(), state.blockchain_client.clone());
+        let (rbf_pool, rbf_client) = (pool.clone(), state.blockchain_client.clone());
+        let (bridge_pool, bridge_client) = (pool.clone(), state.blockchain_client.clone());
+        let (order_pool, price_service) = (pool.clone(), state.price_service.clone());
+        let approval_pool = pool.clone();
+        let (audit_pool, audit_immu) = (pool.clone(), state.immu.clone());
+        let alert_evaluator =
+            AlertRulesConfig::load().and_then(|rules| AlertEvaluator::new(pool.clone(), &rules));
+        let (deposit_pool, deposit_rpc) = (pool.clone(), state.rpc_selector.clone());
+        let deposit_shards = deposit_indexer::chains_from_env()
+            .into_iter()
+            .map(|chain| ChainShard::Chain(chain.to_uppercase()))
+            .collect::<Vec<_>>();
+
+        let supervisor = WorkerSupervisor::new(distributed_lock.clone())
+            // 8.1 交易监控服务
+            .register_sharded("tx_monitor", chain_shards.clone(), move |shard| {
+                Arc::new(
+                    TransactionMonitor::new(tx_pool.clone(), tx_client.clone()).with_shard(shard),
+                )
+                .start_background_monitor()
+                .boxed()
+            })
+            // 8.2 交易自动恢复服务（RBF）
+            .register_sharded("tx_auto_recovery", chain_shards.clone(), move |shard| {
+                Arc::new(
+                    TransactionAutoRecovery::new(
+                        rbf_pool.clone(),
+                        rbf_client.clone(),
+                        nonce_manager.clone(),
+                    )
+                    .with_shard(shard),
+                )
+                .start_background_monitor()
+                .boxed()
+            })
+            // 8.3 跨链事件监听服务
+            .register_sharded("cross_chain_listener", chain_shards, move |shard| {
+                Arc::new(
+                    CrossChainEventListener::new(bridge_pool.clone(), bridge_client.clone())
+                        .with_shard(shard),
+                )
+                .start_background_listener()
+                .boxed()
+            })
+            // 8.4 限价单执行引擎（价格触发 → ready_to_sign，到期 → expired）
+            .register("limit_order_engine", move |_| {
+                Arc::new(LimitOrderEngine::new(
+                    order_pool.clone(),
+                    Arc::new(OneInchMarket::new(price_service.clone())),
+                ))
+                .start_background_engine()
+                .boxed()
+            })
+            // 8.6 多方审批过期处理（超时未决 → expired，挂起的提现自动拒绝）
+            .register("approval_expiry", move |_| {
+                Arc::new(ApprovalExpiryWorker::new(approval_pool.clone()))
+                    .start_background_expiry()
+                    .boxed()
+            })
+            // 8.7 链上入账索引器（每条链一个分片：外部转入 → receive 记录 + 余额同步）
+            .register_sharded("deposit_indexer", deposit_shards, move |shard| {
+                let chain = shard.key().to_lowercase();
+                match deposit_indexer::source_for_chain(&chain, deposit_rpc.clone()) {
+                    Ok(source) => Arc::new(
+                        DepositIndexer::new(deposit_pool.clone(), source)
+                            .with_event_sender(balance_event_tx.clone()),
+                    )
+                    .start_background_indexer()
+                    .boxed(),
+                    Err(e) => {
+                        tracing::error!(chain = %chain, error = %e, "Deposit indexer not started");
+                        futures::future::ready(()).boxed()
+                    }
+                }
+            });
+
+        // 8.8 指标告警规则评估（窗口 / 速率 / for 持续时间 → fiat.alerts + 通知）
+        //     每个副本经 Redis 发布采样，持有租约的实例合并全集群采样后评估
+        let supervisor = match alert_evaluator {
+            Ok(evaluator) => {
+                let evaluator = Arc::new(evaluator.with_shared_metrics(
+                    state.redis.clone(),
+                    supervisor.instance_id().to_string(),
+                ));
+                tokio::spawn(evaluator.clone().start_background_publisher());
+                supervisor.register("alert_evaluator", move |_| {
+                    evaluator.clone().start_background_evaluator().boxed()
+                })
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "Invalid alert rules, alert evaluator not started");
+                supervisor
+            }
+        };
+
+        // 8.9 审计账本 Merkle 检查点（校验新增记录的哈希链 → 检查点 → 锚定 immudb）
+        supervisor.register("audit_checkpointer", move |_| {
+            Arc::new(AuditCheckpointer::new(
+                audit_pool.clone(),
+                audit_immu.clone(),
+            ))
+            .start_background_checkpointer()
+            .boxed()
+        })
+    };
+    let worker_supervisor = Arc::new(worker_supervisor);
+    tracing::info!(
+        instance_id = %worker_supervisor.instance_id(),
+        "✅ Worker supervisor started (tx monitor, RBF recovery, cross-chain listener, limit orders, approval expiry, deposit indexer, alert evaluator, audit checkpointer)"
     );
-    let cross_chain_listener_clone = cross_chain_listener.clone();
     tokio::spawn(async move {
-        cross_chain_listener_clone.start_background_listener().await;
+        worker_supervisor.start().await;
     });
-    tracing::info!("✅ Cross-chain event listener started");
+
+    // 8.5 事件 Outbox 分发器（持久化事件 → 处理器重试 → 死信；SKIP LOCKED 认领，各实例并行）
+    {
+        use ironcore::infrastructure::event_bus::{
+            EventBus, GasSpikeHandler, TransactionConfirmedHandler,
+        };
+
+        let event_outbox = Arc::new(ironcore::infrastructure::event_outbox::OutboxEventBus::new(
+            pool.clone(),
+        ));
+        event_outbox
+            .subscribe(Arc::new(TransactionConfirmedHandler))
+            .await;
+        event_outbox.subscribe(Arc::new(GasSpikeHandler)).await;
+        event_outbox
+            .subscribe(Arc::new(
+                ironcore::service::tenant_webhooks::WebhookFanoutHandler::new(pool.clone()),
+            ))
+            .await;
+        tokio::spawn(async move {
+            event_outbox.start_dispatcher().await;
+        });
+    }
+    tracing::info!("✅ Event outbox dispatcher started");
+
+    // 8.10 租户出站 Webhook 投递（SKIP LOCKED 认领，各实例并行）
+    {
+        use ironcore::service::tenant_webhooks::{WebhookDeliveryConfig, WebhookDispatcher};
+
+        match WebhookDispatcher::new(pool.clone(), WebhookDeliveryConfig::from_env()) {
+            Ok(dispatcher) => {
+                let dispatcher = Arc::new(dispatcher);
+                tokio::spawn(async move {
+                    dispatcher.start_background_dispatcher().await;
+                });
+                tracing::info!("✅ Tenant webhook dispatcher started");
+            }
+            Err(e) => tracing::error!(error = ?e, "Failed to start tenant webhook dispatcher"),
+        }
+    }
 
     // ✅ 9. 构建API路由
     // 使用统一的 api::routes() 函数，包含完整的路由配置：
@@ -317,6 +491,9 @@ async fn main() -> Result<()> {
 
     axum::serve(listener, app).await?;
 
+    // 刷新尚未导出的 span
+    ironcore::infrastructure::telemetry::shutdown();
+
     Ok(())
 }
 
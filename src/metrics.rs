@@ -1,289 +1,538 @@
+//! Prometheus 指标注册表
+//!
+//! 所有指标注册在进程级 [`Registry`] 中，支持带标签的计数器、仪表与直方图；
+//! `render_prometheus` 以文本格式导出（由 `monitoring::create_metrics_router` 提供 `/metrics`）。
+//!
+//! - HTTP：`ironcore_http_requests_total{method,route,status}` 与按路由模板的耗时直方图，
+//!   由 `api::middleware::HttpMetricsLayer` 自动记录
+//! - RPC：`ironcore_rpc_request_duration_seconds{chain,endpoint}`，由 `RpcSelector` 探活与
+//!   `BlockchainClient` 调用写入；`endpoint` 只取主机名，避免 URL 中的 API Key 进入标签
+//! - 数据库：`ironcore_db_pool_connections{state}`，抓取时从已注册的连接池实时读取
+
 use std::{
-    collections::HashMap,
-    sync::{Mutex, OnceLock},
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::{
+    proto::MetricFamily, Counter, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
 };
 
-static METRICS: OnceLock<Mutex<MetricsState>> = OnceLock::new();
-
-struct MetricsState {
-    total: u64,
-    errors: u64,
-    per_endpoint: HashMap<&'static str, u64>,
-    per_endpoint_err: HashMap<&'static str, u64>,
-    // 上游成功/失败与时延统计（毫秒）
-    upstream_ok: u64,
-    upstream_err: u64,
-    upstream_latency_sum_ms: u128,
-    // 简易直方图分桶（毫秒）：<50, <100, <250, <500, <1000, >=1000
-    upstream_hist_buckets: [u64; 6],
-    // 新增：平台费相关
-    fee_calculation_total: u64,
-    fee_audit_write_fail: u64,
-    fee_total_amount: f64,
-    // RPC 端点相关
-    rpc_selection_total: u64,
-    rpc_selection_fallback: u64,
-    rpc_circuit_open_total: u64,
-}
-
-fn state() -> &'static Mutex<MetricsState> {
-    METRICS.get_or_init(|| {
-        Mutex::new(MetricsState {
-            total: 0,
-            errors: 0,
-            per_endpoint: HashMap::new(),
-            per_endpoint_err: HashMap::new(),
-            upstream_ok: 0,
-            upstream_err: 0,
-            upstream_latency_sum_ms: 0,
-            upstream_hist_buckets: [0; 6],
-            fee_calculation_total: 0,
-            fee_audit_write_fail: 0,
-            fee_total_amount: 0.0,
-            rpc_selection_total: 0,
-            rpc_selection_fallback: 0,
-            rpc_circuit_open_total: 0,
-        })
-    })
+use crate::infrastructure::db::PgPool;
+
+/// 上游时延分桶（毫秒）
+const UPSTREAM_LATENCY_BUCKETS_MS: &[f64] = &[50.0, 100.0, 250.0, 500.0, 1000.0];
+
+/// HTTP 请求耗时分桶（秒）
+const HTTP_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// RPC 调用耗时分桶（秒）
+const RPC_DURATION_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+static DB_POOL: OnceLock<PgPool> = OnceLock::new();
+
+struct Metrics {
+    registry: Registry,
+    requests_total: IntCounter,
+    errors_total: IntCounter,
+    endpoint_requests: IntCounterVec,
+    endpoint_errors: IntCounterVec,
+    // 上游成功/失败与时延（毫秒）
+    upstream_requests: IntCounterVec,
+    upstream_latency_ms: Histogram,
+    // 平台费
+    fee_calculation_total: IntCounter,
+    fee_audit_write_fail: IntCounter,
+    fee_total_amount: Counter,
+    // RPC 端点选择与共识
+    rpc_selection_total: IntCounter,
+    rpc_selection_fallback: IntCounter,
+    rpc_circuit_open_total: IntCounter,
+    rpc_consensus_disagreement_total: IntCounter,
+    rpc_quorum_failure_total: IntCounter,
+    rpc_requests: IntCounterVec,
+    rpc_request_duration: HistogramVec,
+    blockchain_broadcast: IntCounterVec,
+    // 事件总线
+    event_handler_retry_total: IntCounter,
+    event_dead_letter_total: IntCounter,
+    // 价格源健康
+    price_source_results: IntCounterVec,
+    price_source_last_success: IntGaugeVec,
+    price_unavailable_total: IntCounter,
+    // HTTP
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    http_requests_in_flight: IntGauge,
+    // 数据库连接池
+    db_pool_connections: IntGaugeVec,
+    db_pool_max_connections: IntGauge,
 }
 
-pub fn count_ok(endpoint: &'static str) {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(), // 避免因锁污染导致 panic
+fn register<T>(registry: &Registry, metric: T) -> T
+where
+    T: prometheus::core::Collector + Clone + 'static,
+{
+    // 名称与标签均为编译期常量，注册失败只可能是重复注册（编程错误）
+    if let Err(e) = registry.register(Box::new(metric.clone())) {
+        tracing::error!(error = %e, "metric registration failed");
+    }
+    metric
+}
+
+fn counter(registry: &Registry, name: &str, help: &str) -> IntCounter {
+    register(
+        registry,
+        IntCounter::new(name, help).expect("valid counter descriptor"),
+    )
+}
+
+fn counter_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(
+        registry,
+        IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter descriptor"),
+    )
+}
+
+fn gauge_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    register(
+        registry,
+        IntGaugeVec::new(Opts::new(name, help), labels).expect("valid gauge descriptor"),
+    )
+}
+
+fn histogram_vec(
+    registry: &Registry,
+    name: &str,
+    help: &str,
+    labels: &[&str],
+    buckets: &[f64],
+) -> HistogramVec {
+    register(
+        registry,
+        HistogramVec::new(
+            HistogramOpts::new(name, help).buckets(buckets.to_vec()),
+            labels,
+        )
+        .expect("valid histogram descriptor"),
+    )
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let r = Registry::new();
+        Self {
+            requests_total: counter(&r, "ironcore_requests_total", "Total requests"),
+            errors_total: counter(&r, "ironcore_errors_total", "Total error responses"),
+            endpoint_requests: counter_vec(
+                &r,
+                "ironcore_endpoint_requests_total",
+                "Requests per endpoint",
+                &["endpoint"],
+            ),
+            endpoint_errors: counter_vec(
+                &r,
+                "ironcore_endpoint_errors_total",
+                "Errors per endpoint",
+                &["endpoint"],
+            ),
+            upstream_requests: counter_vec(
+                &r,
+                "ironcore_upstream_requests_total",
+                "Upstream requests",
+                &["result"],
+            ),
+            upstream_latency_ms: register(
+                &r,
+                Histogram::with_opts(
+                    HistogramOpts::new("ironcore_upstream_latency_ms", "Upstream latency in ms")
+                        .buckets(UPSTREAM_LATENCY_BUCKETS_MS.to_vec()),
+                )
+                .expect("valid histogram descriptor"),
+            ),
+            fee_calculation_total: counter(
+                &r,
+                "ironcore_fee_calculation_total",
+                "Total fee calculations",
+            ),
+            fee_audit_write_fail: counter(
+                &r,
+                "ironcore_fee_audit_write_fail_total",
+                "Failed fee audit writes",
+            ),
+            fee_total_amount: register(
+                &r,
+                Counter::new(
+                    "ironcore_fee_total_amount_collected",
+                    "Total platform fees collected",
+                )
+                .expect("valid counter descriptor"),
+            ),
+            rpc_selection_total: counter(
+                &r,
+                "ironcore_rpc_selection_total",
+                "Total RPC endpoint selections",
+            ),
+            rpc_selection_fallback: counter(
+                &r,
+                "ironcore_rpc_selection_fallback_total",
+                "RPC selections using fallback",
+            ),
+            rpc_circuit_open_total: counter(
+                &r,
+                "ironcore_rpc_circuit_open_total",
+                "RPC endpoints in circuit open state",
+            ),
+            rpc_consensus_disagreement_total: counter(
+                &r,
+                "ironcore_rpc_consensus_disagreement_total",
+                "Multi-node queries where RPC providers disagreed",
+            ),
+            rpc_quorum_failure_total: counter(
+                &r,
+                "ironcore_rpc_quorum_failure_total",
+                "Multi-node queries that failed to reach quorum",
+            ),
+            rpc_requests: counter_vec(
+                &r,
+                "ironcore_rpc_requests_total",
+                "RPC calls per chain and endpoint host",
+                &["chain", "endpoint", "result"],
+            ),
+            rpc_request_duration: histogram_vec(
+                &r,
+                "ironcore_rpc_request_duration_seconds",
+                "RPC call latency per chain and endpoint host",
+                &["chain", "endpoint"],
+                RPC_DURATION_BUCKETS,
+            ),
+            blockchain_broadcast: counter_vec(
+                &r,
+                "ironcore_blockchain_broadcast_total",
+                "Transaction broadcast attempts per chain",
+                &["chain", "result"],
+            ),
+            event_handler_retry_total: counter(
+                &r,
+                "ironcore_event_handler_retry_total",
+                "Event handler failures scheduled for retry",
+            ),
+            event_dead_letter_total: counter(
+                &r,
+                "ironcore_event_dead_letter_total",
+                "Event deliveries moved to the dead-letter queue",
+            ),
+            price_source_results: counter_vec(
+                &r,
+                "ironcore_price_source_total",
+                "Price source outcomes (ok/error/stale/outlier)",
+                &["source", "result"],
+            ),
+            price_source_last_success: gauge_vec(
+                &r,
+                "ironcore_price_source_last_success_timestamp_seconds",
+                "Last accepted quote per price source",
+                &["source"],
+            ),
+            price_unavailable_total: counter(
+                &r,
+                "ironcore_price_unavailable_total",
+                "Price lookups rejected for lack of fresh agreeing quotes",
+            ),
+            http_requests: counter_vec(
+                &r,
+                "ironcore_http_requests_total",
+                "HTTP requests by method, route template and status",
+                &["method", "route", "status"],
+            ),
+            http_request_duration: histogram_vec(
+                &r,
+                "ironcore_http_request_duration_seconds",
+                "HTTP request latency by method and route template",
+                &["method", "route"],
+                HTTP_DURATION_BUCKETS,
+            ),
+            http_requests_in_flight: register(
+                &r,
+                IntGauge::new(
+                    "ironcore_http_requests_in_flight",
+                    "HTTP requests currently being served",
+                )
+                .expect("valid gauge descriptor"),
+            ),
+            db_pool_connections: gauge_vec(
+                &r,
+                "ironcore_db_pool_connections",
+                "Database pool connections by state (size/idle/active)",
+                &["state"],
+            ),
+            db_pool_max_connections: register(
+                &r,
+                IntGauge::new(
+                    "ironcore_db_pool_max_connections",
+                    "Configured database pool capacity",
+                )
+                .expect("valid gauge descriptor"),
+            ),
+            registry: r,
+        }
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// 进程级指标注册表（供告警评估等模块读取当前样本）
+pub fn registry() -> &'static Registry {
+    &metrics().registry
+}
+
+/// 注册数据库连接池，抓取时导出连接池仪表（重复注册以首次为准）
+pub fn register_db_pool(pool: &PgPool) {
+    let _ = DB_POOL.set(pool.clone());
+}
+
+fn refresh_db_pool_gauges(m: &Metrics) {
+    let Some(pool) = DB_POOL.get() else {
+        return;
     };
-    s.total += 1;
-    *s.per_endpoint.entry(endpoint).or_insert(0) += 1;
+    let size = i64::from(pool.size());
+    let idle = pool.num_idle() as i64;
+    m.db_pool_connections.with_label_values(&["size"]).set(size);
+    m.db_pool_connections.with_label_values(&["idle"]).set(idle);
+    m.db_pool_connections
+        .with_label_values(&["active"])
+        .set((size - idle).max(0));
+    m.db_pool_max_connections
+        .set(i64::from(pool.options().get_max_connections()));
+}
+
+pub fn count_ok(endpoint: &'static str) {
+    let m = metrics();
+    m.requests_total.inc();
+    m.endpoint_requests.with_label_values(&[endpoint]).inc();
 }
 
 pub fn count_err(endpoint: &'static str) {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.total += 1;
-    s.errors += 1;
-    *s.per_endpoint.entry(endpoint).or_insert(0) += 1;
-    *s.per_endpoint_err.entry(endpoint).or_insert(0) += 1;
+    let m = metrics();
+    m.requests_total.inc();
+    m.errors_total.inc();
+    m.endpoint_requests.with_label_values(&[endpoint]).inc();
+    m.endpoint_errors.with_label_values(&[endpoint]).inc();
+}
+
+/// 采集当前全部指标（先刷新连接池仪表），供 `/metrics` 导出与告警规则评估共用
+pub fn gather() -> Vec<MetricFamily> {
+    let m = metrics();
+    refresh_db_pool_gauges(m);
+    m.registry.gather()
 }
 
 pub fn render_prometheus() -> String {
-    let s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    let mut out = String::new();
-    out.push_str("# HELP ironcore_requests_total Total requests\n");
-    out.push_str("# TYPE ironcore_requests_total counter\n");
-    out.push_str(&format!("ironcore_requests_total {}\n", s.total));
-
-    out.push_str("# HELP ironcore_errors_total Total error responses\n");
-    out.push_str("# TYPE ironcore_errors_total counter\n");
-    out.push_str(&format!("ironcore_errors_total {}\n", s.errors));
-
-    out.push_str("# HELP ironcore_endpoint_requests_total Requests per endpoint\n");
-    out.push_str("# TYPE ironcore_endpoint_requests_total counter\n");
-    for (k, v) in s.per_endpoint.iter() {
-        out.push_str(&format!(
-            "ironcore_endpoint_requests_total{{endpoint=\"{}\"}} {}\n",
-            k, v
-        ));
+    let mut buf = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&gather(), &mut buf) {
+        tracing::error!(error = %e, "failed to encode prometheus metrics");
     }
+    String::from_utf8(buf).unwrap_or_default()
+}
 
-    out.push_str("# HELP ironcore_endpoint_errors_total Errors per endpoint\n");
-    out.push_str("# TYPE ironcore_endpoint_errors_total counter\n");
-    for (k, v) in s.per_endpoint_err.iter() {
-        out.push_str(&format!(
-            "ironcore_endpoint_errors_total{{endpoint=\"{}\"}} {}\n",
-            k, v
-        ));
-    }
+pub fn observe_upstream_latency_ms(latency_ms: u128, ok: bool) {
+    let m = metrics();
+    m.upstream_requests
+        .with_label_values(&[if ok { "ok" } else { "err" }])
+        .inc();
+    m.upstream_latency_ms.observe(latency_ms as f64);
+}
 
-    // 上游统计
-    out.push_str("# HELP ironcore_upstream_requests_total Upstream requests\n");
-    out.push_str("# TYPE ironcore_upstream_requests_total counter\n");
-    out.push_str(&format!(
-        "ironcore_upstream_requests_total{{result=\"ok\"}} {}\n",
-        s.upstream_ok
-    ));
-    out.push_str(&format!(
-        "ironcore_upstream_requests_total{{result=\"err\"}} {}\n",
-        s.upstream_err
-    ));
-
-    out.push_str("# HELP ironcore_upstream_latency_ms_sum Sum of upstream latency in ms\n");
-    out.push_str("# TYPE ironcore_upstream_latency_ms_sum counter\n");
-    out.push_str(&format!(
-        "ironcore_upstream_latency_ms_sum {}\n",
-        s.upstream_latency_sum_ms
-    ));
-
-    out.push_str("# HELP ironcore_upstream_latency_ms_bucket Upstream latency histogram buckets\n");
-    out.push_str("# TYPE ironcore_upstream_latency_ms_bucket histogram\n");
-    let bounds = [50, 100, 250, 500, 1000];
-    for (i, bound) in bounds.iter().enumerate() {
-        out.push_str(&format!(
-            "ironcore_upstream_latency_ms_bucket{{le=\"{}\"}} {}\n",
-            bound, s.upstream_hist_buckets[i]
-        ));
-    }
-    // +Inf 桶
-    out.push_str(&format!(
-        "ironcore_upstream_latency_ms_bucket{{le=\"+Inf\"}} {}\n",
-        s.upstream_hist_buckets.iter().sum::<u64>()
-    ));
-
-    // 费用系统指标
-    out.push_str("# HELP ironcore_fee_calculation_total Total fee calculations\n");
-    out.push_str("# TYPE ironcore_fee_calculation_total counter\n");
-    out.push_str(&format!(
-        "ironcore_fee_calculation_total {}\n",
-        s.fee_calculation_total
-    ));
-
-    out.push_str("# HELP ironcore_fee_audit_write_fail_total Failed fee audit writes\n");
-    out.push_str("# TYPE ironcore_fee_audit_write_fail_total counter\n");
-    out.push_str(&format!(
-        "ironcore_fee_audit_write_fail_total {}\n",
-        s.fee_audit_write_fail
-    ));
-
-    out.push_str("# HELP ironcore_fee_total_amount_collected Total platform fees collected\n");
-    out.push_str("# TYPE ironcore_fee_total_amount_collected counter\n");
-    out.push_str(&format!(
-        "ironcore_fee_total_amount_collected {}\n",
-        s.fee_total_amount
-    ));
-
-    // RPC 选择指标
-    out.push_str("# HELP ironcore_rpc_selection_total Total RPC endpoint selections\n");
-    out.push_str("# TYPE ironcore_rpc_selection_total counter\n");
-    out.push_str(&format!(
-        "ironcore_rpc_selection_total {}\n",
-        s.rpc_selection_total
-    ));
-
-    out.push_str("# HELP ironcore_rpc_selection_fallback_total RPC selections using fallback\n");
-    out.push_str("# TYPE ironcore_rpc_selection_fallback_total counter\n");
-    out.push_str(&format!(
-        "ironcore_rpc_selection_fallback_total {}\n",
-        s.rpc_selection_fallback
-    ));
-
-    out.push_str("# HELP ironcore_rpc_circuit_open_total RPC endpoints in circuit open state\n");
-    out.push_str("# TYPE ironcore_rpc_circuit_open_total counter\n");
-    out.push_str(&format!(
-        "ironcore_rpc_circuit_open_total {}\n",
-        s.rpc_circuit_open_total
-    ));
-
-    out
+/// 记录一次 HTTP 请求（`route` 为路由模板，如 `/api/v1/wallets/:id`）
+pub fn observe_http_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    let m = metrics();
+    m.http_requests
+        .with_label_values(&[method, route, &status.to_string()])
+        .inc();
+    m.http_request_duration
+        .with_label_values(&[method, route])
+        .observe(elapsed.as_secs_f64());
 }
 
-pub fn observe_upstream_latency_ms(latency_ms: u128, ok: bool) {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    if ok {
-        s.upstream_ok += 1;
-    } else {
-        s.upstream_err += 1;
+pub fn inc_http_in_flight() {
+    metrics().http_requests_in_flight.inc();
+}
+
+pub fn dec_http_in_flight() {
+    metrics().http_requests_in_flight.dec();
+}
+
+/// 记录一次 RPC 调用耗时（按链与端点主机名）
+pub fn observe_rpc_request(chain: &str, endpoint_url: &str, elapsed: Duration, ok: bool) {
+    let m = metrics();
+    let endpoint = endpoint_label(endpoint_url);
+    m.rpc_requests
+        .with_label_values(&[chain, &endpoint, if ok { "ok" } else { "err" }])
+        .inc();
+    m.rpc_request_duration
+        .with_label_values(&[chain, &endpoint])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// 端点标签：仅保留主机名（含非默认端口），路径与查询串中常带 API Key
+fn endpoint_label(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(u) => match (u.host_str(), u.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => "unknown".to_string(),
+        },
+        Err(_) => "unknown".to_string(),
     }
-    s.upstream_latency_sum_ms += latency_ms;
-    let b = if latency_ms < 50 {
-        0
-    } else if latency_ms < 100 {
-        1
-    } else if latency_ms < 250 {
-        2
-    } else if latency_ms < 500 {
-        3
-    } else if latency_ms < 1000 {
-        4
-    } else {
-        5
-    };
-    s.upstream_hist_buckets[b] += 1;
 }
 
 pub fn inc_fee_calculation() {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.fee_calculation_total += 1;
+    metrics().fee_calculation_total.inc();
 }
 
 pub fn inc_fee_audit_fail() {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.fee_audit_write_fail += 1;
+    metrics().fee_audit_write_fail.inc();
 }
 
 pub fn add_fee_amount(amount: f64) {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.fee_total_amount += amount;
+    // Counter 只允许非负增量
+    if amount.is_finite() && amount > 0.0 {
+        metrics().fee_total_amount.inc_by(amount);
+    }
 }
 
 pub fn inc_rpc_selection() {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.rpc_selection_total += 1;
+    metrics().rpc_selection_total.inc();
 }
 
 pub fn inc_rpc_fallback() {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.rpc_selection_fallback += 1;
+    metrics().rpc_selection_fallback.inc();
 }
 
 pub fn inc_rpc_circuit_open() {
-    let mut s = match state().lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => poisoned.into_inner(),
-    };
-    s.rpc_circuit_open_total += 1;
+    metrics().rpc_circuit_open_total.inc();
+}
+
+pub fn inc_rpc_consensus_disagreement() {
+    metrics().rpc_consensus_disagreement_total.inc();
+}
+
+pub fn inc_rpc_quorum_failure() {
+    metrics().rpc_quorum_failure_total.inc();
+}
+
+// 区块链广播指标✅多链支持（别名归一，控制标签基数）
+fn broadcast_chain_label(chain: &str) -> &'static str {
+    match chain {
+        "eth" | "ethereum" => "eth",
+        "bsc" | "binance" => "bsc",
+        "polygon" | "matic" => "polygon",
+        "solana" | "sol" => "solana",
+        "bitcoin" | "btc" => "bitcoin",
+        "ton" => "ton",
+        "arbitrum" | "arb" => "arbitrum",
+        "optimism" | "op" => "optimism",
+        "avalanche" | "avax" => "avalanche",
+        _ => "other",
+    }
 }
 
-// 区块链广播指标✅多链支持
 pub fn inc_blockchain_broadcast_success(chain: &str) {
-    let endpoint = match chain {
-        "eth" | "ethereum" => "blockchain_broadcast_eth_success",
-        "bsc" | "binance" => "blockchain_broadcast_bsc_success",
-        "polygon" | "matic" => "blockchain_broadcast_polygon_success",
-        "solana" | "sol" => "blockchain_broadcast_solana_success",
-        "bitcoin" | "btc" => "blockchain_broadcast_bitcoin_success",
-        "ton" => "blockchain_broadcast_ton_success",
-        "arbitrum" | "arb" => "blockchain_broadcast_arbitrum_success",
-        "optimism" | "op" => "blockchain_broadcast_optimism_success",
-        "avalanche" | "avax" => "blockchain_broadcast_avalanche_success",
-        _ => "blockchain_broadcast_other_success",
-    };
-    count_ok(endpoint);
+    metrics()
+        .blockchain_broadcast
+        .with_label_values(&[broadcast_chain_label(chain), "success"])
+        .inc();
 }
 
 pub fn inc_blockchain_broadcast_fail(chain: &str) {
-    let endpoint = match chain {
-        "eth" | "ethereum" => "blockchain_broadcast_eth_fail",
-        "bsc" | "binance" => "blockchain_broadcast_bsc_fail",
-        "polygon" | "matic" => "blockchain_broadcast_polygon_fail",
-        "solana" | "sol" => "blockchain_broadcast_solana_fail",
-        "bitcoin" | "btc" => "blockchain_broadcast_bitcoin_fail",
-        "ton" => "blockchain_broadcast_ton_fail",
-        "arbitrum" | "arb" => "blockchain_broadcast_arbitrum_fail",
-        "optimism" | "op" => "blockchain_broadcast_optimism_fail",
-        "avalanche" | "avax" => "blockchain_broadcast_avalanche_fail",
-        _ => "blockchain_broadcast_other_fail",
-    };
-    count_err(endpoint);
+    metrics()
+        .blockchain_broadcast
+        .with_label_values(&[broadcast_chain_label(chain), "fail"])
+        .inc();
+}
+
+pub fn inc_event_handler_retry() {
+    metrics().event_handler_retry_total.inc();
+}
+
+pub fn inc_event_dead_lettered() {
+    metrics().event_dead_letter_total.inc();
+}
+
+// 价格源健康指标
+pub fn record_price_source(source: &str, result: &'static str) {
+    let m = metrics();
+    m.price_source_results
+        .with_label_values(&[source, result])
+        .inc();
+    if result == "ok" {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        m.price_source_last_success
+            .with_label_values(&[source])
+            .set(now);
+    }
+}
+
+pub fn inc_price_unavailable() {
+    metrics().price_unavailable_total.inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labelled_histogram_renders_cumulative_buckets() {
+        let chain = "metrics-test-chain";
+        observe_rpc_request(
+            chain,
+            "https://rpc.example.com/v3/SECRET_KEY",
+            Duration::from_millis(30),
+            true,
+        );
+        observe_rpc_request(
+            chain,
+            "https://rpc.example.com/v3/SECRET_KEY",
+            Duration::from_millis(700),
+            false,
+        );
+
+        let out = render_prometheus();
+        assert!(!out.contains("SECRET_KEY"));
+        let labels = format!("chain=\"{}\",endpoint=\"rpc.example.com\"", chain);
+        for (le, count) in [("0.025", 0), ("0.05", 1), ("1", 2), ("+Inf", 2)] {
+            let line = format!(
+                "ironcore_rpc_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels, le, count
+            );
+            assert!(out.contains(&line), "missing {}", line);
+        }
+        assert!(out.contains(&format!(
+            "ironcore_rpc_request_duration_seconds_count{{{}}} 2",
+            labels
+        )));
+        assert!(out.contains(&format!(
+            "ironcore_rpc_requests_total{{{},result=\"err\"}} 1",
+            labels
+        )));
+    }
+
+    #[test]
+    fn test_endpoint_label_strips_path_and_keeps_port() {
+        assert_eq!(
+            endpoint_label("https://mainnet.infura.io/v3/abc"),
+            "mainnet.infura.io"
+        );
+        assert_eq!(endpoint_label("http://127.0.0.1:8545"), "127.0.0.1:8545");
+        assert_eq!(endpoint_label("not a url"), "unknown");
+    }
 }
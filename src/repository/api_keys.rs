@@ -12,6 +12,12 @@ pub struct ApiKey {
     pub key_hash: String,
     pub status: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub user_id: Option<Uuid>,
+    pub key_prefix: Option<String>,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub rate_limit_per_minute: Option<i64>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug)]
@@ -20,20 +26,32 @@ pub struct CreateApiKeyInput {
     pub name: String,
     pub key_hash: String,
     pub status: String,
+    pub user_id: Option<Uuid>,
+    pub key_prefix: Option<String>,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub rate_limit_per_minute: Option<i64>,
 }
 
 pub async fn create(pool: &PgPool, input: CreateApiKeyInput) -> Result<ApiKey, sqlx::Error> {
     let rec = sqlx::query_as::<_, ApiKey>(
         r#"
-        INSERT INTO api_keys (tenant_id, name, key_hash, status)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, tenant_id, name, key_hash, status, created_at
+        INSERT INTO api_keys (tenant_id, name, key_hash, status, user_id, key_prefix, scopes,
+                              expires_at, rate_limit_per_minute)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+                  expires_at, rate_limit_per_minute, last_used_at
         "#,
     )
     .bind(input.tenant_id)
     .bind(input.name)
     .bind(input.key_hash)
     .bind(input.status)
+    .bind(input.user_id)
+    .bind(input.key_prefix)
+    .bind(input.scopes)
+    .bind(input.expires_at)
+    .bind(input.rate_limit_per_minute)
     .fetch_one(pool)
     .await?;
     Ok(rec)
@@ -42,7 +60,8 @@ pub async fn create(pool: &PgPool, input: CreateApiKeyInput) -> Result<ApiKey, s
 pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<ApiKey>, sqlx::Error> {
     let rec = sqlx::query_as::<_, ApiKey>(
         r#"
-        SELECT id, tenant_id, name, key_hash, status, created_at
+        SELECT id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+               expires_at, rate_limit_per_minute, last_used_at
         FROM api_keys
         WHERE id = $1
         "#,
@@ -56,7 +75,8 @@ pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<ApiKey>, sqlx::
 pub async fn get_by_hash(pool: &PgPool, key_hash: &str) -> Result<Option<ApiKey>, sqlx::Error> {
     let rec = sqlx::query_as::<_, ApiKey>(
         r#"
-        SELECT id, tenant_id, name, key_hash, status, created_at
+        SELECT id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+               expires_at, rate_limit_per_minute, last_used_at
         FROM api_keys
         WHERE key_hash = $1
         "#,
@@ -67,6 +87,20 @@ pub async fn get_by_hash(pool: &PgPool, key_hash: &str) -> Result<Option<ApiKey>
     Ok(rec)
 }
 
+/// 记录最近使用时间（同一 Key 每分钟最多写一次，避免每个请求都写库）
+pub async fn touch_last_used(pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE api_keys SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '60 seconds')
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn list_by_tenant(
     pool: &PgPool,
     tenant_id: Uuid,
@@ -77,7 +111,8 @@ pub async fn list_by_tenant(
     let recs = if let Some(s) = status {
         sqlx::query_as::<_, ApiKey>(
             r#"
-            SELECT id, tenant_id, name, key_hash, status, created_at
+            SELECT id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+                   expires_at, rate_limit_per_minute, last_used_at
             FROM api_keys
             WHERE tenant_id = $1 AND status = $2
             ORDER BY created_at DESC
@@ -93,7 +128,8 @@ pub async fn list_by_tenant(
     } else {
         sqlx::query_as::<_, ApiKey>(
             r#"
-            SELECT id, tenant_id, name, key_hash, status, created_at
+            SELECT id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+                   expires_at, rate_limit_per_minute, last_used_at
             FROM api_keys
             WHERE tenant_id = $1
             ORDER BY created_at DESC
@@ -119,7 +155,8 @@ pub async fn update_status(
         r#"
         UPDATE api_keys SET status = $3
         WHERE id = $1 AND tenant_id = $2
-        RETURNING id, tenant_id, name, key_hash, status, created_at
+        RETURNING id, tenant_id, name, key_hash, status, created_at, user_id, key_prefix, scopes,
+                  expires_at, rate_limit_per_minute, last_used_at
         "#,
     )
     .bind(id)
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection};
 use uuid::Uuid;
 
 use crate::infrastructure::db::PgPool;
@@ -8,17 +8,26 @@ use crate::infrastructure::db::PgPool;
 pub struct Approval {
     pub id: Uuid,
     pub tenant_id: Uuid,
-    pub policy_id: Uuid,
+    pub policy_id: Option<Uuid>,
     pub requester: Uuid,
     pub status: String,
     pub payload: serde_json::Value,
+    /// 被挂起的操作（如 `withdrawal`），人工创建的审批为空
+    pub resource_type: Option<String>,
+    pub resource_id: Option<Uuid>,
+    /// M-of-N 中的 M
+    pub required_approvals: i32,
+    /// 有权投票的 RBAC 角色
+    pub approver_roles: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub decided_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug)]
 pub struct CreateApprovalInput {
     pub tenant_id: Uuid,
-    pub policy_id: Uuid,
+    pub policy_id: Option<Uuid>,
     pub requester: Uuid,
     pub status: String,
     pub payload: serde_json::Value,
@@ -29,7 +38,8 @@ pub async fn create(pool: &PgPool, input: CreateApprovalInput) -> Result<Approva
         r#"
         INSERT INTO approvals (tenant_id, policy_id, requester, status, payload)
         VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, tenant_id, policy_id, requester, status, payload, created_at
+        RETURNING id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+               required_approvals, approver_roles, expires_at, decided_at, created_at
         "#,
     )
     .bind(input.tenant_id)
@@ -45,7 +55,8 @@ pub async fn create(pool: &PgPool, input: CreateApprovalInput) -> Result<Approva
 pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Approval>, sqlx::Error> {
     let rec = sqlx::query_as::<_, Approval>(
         r#"
-        SELECT id, tenant_id, policy_id, requester, status, payload, created_at
+        SELECT id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+               required_approvals, approver_roles, expires_at, decided_at, created_at
         FROM approvals
         WHERE id = $1
         "#,
@@ -66,7 +77,8 @@ pub async fn list_by_tenant(
     let recs = if let Some(s) = status {
         sqlx::query_as::<_, Approval>(
             r#"
-            SELECT id, tenant_id, policy_id, requester, status, payload, created_at
+            SELECT id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+               required_approvals, approver_roles, expires_at, decided_at, created_at
             FROM approvals
             WHERE tenant_id = $1 AND status = $2
             ORDER BY created_at DESC
@@ -82,7 +94,8 @@ pub async fn list_by_tenant(
     } else {
         sqlx::query_as::<_, Approval>(
             r#"
-            SELECT id, tenant_id, policy_id, requester, status, payload, created_at
+            SELECT id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+               required_approvals, approver_roles, expires_at, decided_at, created_at
             FROM approvals
             WHERE tenant_id = $1
             ORDER BY created_at DESC
@@ -98,32 +111,12 @@ pub async fn list_by_tenant(
     Ok(recs)
 }
 
-pub async fn update_status(
-    pool: &PgPool,
-    id: Uuid,
-    tenant_id: Uuid,
-    status: String,
-) -> Result<Option<Approval>, sqlx::Error> {
-    let rec = sqlx::query_as::<_, Approval>(
-        r#"
-        UPDATE approvals SET status = $3
-        WHERE id = $1 AND tenant_id = $2
-        RETURNING id, tenant_id, policy_id, requester, status, payload, created_at
-        "#,
-    )
-    .bind(id)
-    .bind(tenant_id)
-    .bind(status)
-    .fetch_optional(pool)
-    .await?;
-    Ok(rec)
-}
-
 pub async fn delete(pool: &PgPool, id: Uuid, tenant_id: Uuid) -> Result<bool, sqlx::Error> {
     let rows_affected = sqlx::query(
         r#"
         DELETE FROM approvals
         WHERE id = $1 AND tenant_id = $2
+          AND NOT (status = 'pending' AND resource_id IS NOT NULL)
         "#,
     )
     .bind(id)
@@ -161,3 +154,169 @@ pub async fn count_by_tenant(
     };
     Ok(count.0)
 }
+
+// ========== 多方审批（挂起操作） ==========
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ApprovalVote {
+    pub id: Uuid,
+    pub approval_id: Uuid,
+    pub voter_id: Uuid,
+    pub voter_role: String,
+    pub decision: String,
+    pub comment: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug)]
+pub struct ParkOperationInput {
+    pub tenant_id: Uuid,
+    pub policy_id: Option<Uuid>,
+    pub requester: Uuid,
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    pub payload: serde_json::Value,
+    pub required_approvals: i32,
+    pub approver_roles: Vec<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn create_pending(
+    conn: &mut PgConnection,
+    input: ParkOperationInput,
+) -> Result<Approval, sqlx::Error> {
+    sqlx::query_as::<_, Approval>(
+        r#"
+        INSERT INTO approvals
+            (tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+             required_approvals, approver_roles, expires_at)
+        VALUES ($1, $2, $3, 'pending', $4, $5, $6, $7, $8, $9)
+        RETURNING id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+                  required_approvals, approver_roles, expires_at, decided_at, created_at
+        "#,
+    )
+    .bind(input.tenant_id)
+    .bind(input.policy_id)
+    .bind(input.requester)
+    .bind(input.payload)
+    .bind(input.resource_type)
+    .bind(input.resource_id)
+    .bind(input.required_approvals)
+    .bind(input.approver_roles)
+    .bind(input.expires_at)
+    .fetch_one(conn)
+    .await
+}
+
+/// 加行锁读取（投票与过期处理串行化）
+pub async fn get_for_update(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<Approval>, sqlx::Error> {
+    sqlx::query_as::<_, Approval>(
+        r#"
+        SELECT id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+               required_approvals, approver_roles, expires_at, decided_at, created_at
+        FROM approvals
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn insert_vote(
+    conn: &mut PgConnection,
+    approval_id: Uuid,
+    voter_id: Uuid,
+    voter_role: &str,
+    decision: &str,
+    comment: Option<&str>,
+) -> Result<ApprovalVote, sqlx::Error> {
+    sqlx::query_as::<_, ApprovalVote>(
+        r#"
+        INSERT INTO approval_votes (approval_id, voter_id, voter_role, decision, comment)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, approval_id, voter_id, voter_role, decision, comment, created_at
+        "#,
+    )
+    .bind(approval_id)
+    .bind(voter_id)
+    .bind(voter_role)
+    .bind(decision)
+    .bind(comment)
+    .fetch_one(conn)
+    .await
+}
+
+/// 统计票数：(同意, 拒绝)
+pub async fn tally_votes(
+    conn: &mut PgConnection,
+    approval_id: Uuid,
+) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE decision = 'approve'),
+               COUNT(*) FILTER (WHERE decision = 'reject')
+        FROM approval_votes
+        WHERE approval_id = $1
+        "#,
+    )
+    .bind(approval_id)
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn list_votes(
+    pool: &PgPool,
+    approval_id: Uuid,
+) -> Result<Vec<ApprovalVote>, sqlx::Error> {
+    sqlx::query_as::<_, ApprovalVote>(
+        r#"
+        SELECT id, approval_id, voter_id, voter_role, decision, comment, created_at
+        FROM approval_votes
+        WHERE approval_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(approval_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// 写入终态（approved / rejected / expired）
+pub async fn mark_decided(
+    conn: &mut PgConnection,
+    id: Uuid,
+    status: &str,
+) -> Result<Approval, sqlx::Error> {
+    sqlx::query_as::<_, Approval>(
+        r#"
+        UPDATE approvals SET status = $2, decided_at = NOW()
+        WHERE id = $1
+        RETURNING id, tenant_id, policy_id, requester, status, payload, resource_type, resource_id,
+                  required_approvals, approver_roles, expires_at, decided_at, created_at
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .fetch_one(conn)
+    .await
+}
+
+/// 已过期但仍处于 pending 的审批
+pub async fn list_expired_pending(pool: &PgPool, limit: i64) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id FROM approvals
+        WHERE status = 'pending' AND expires_at IS NOT NULL AND expires_at <= NOW()
+        ORDER BY expires_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
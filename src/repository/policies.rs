@@ -11,6 +11,17 @@ pub struct Policy {
     pub name: String,
     pub rules: serde_json::Value,
     pub version: i32,
+    /// draft / active / inactive（仅 active 策略参与提现风控）
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 策略版本快照
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PolicyVersion {
+    pub policy_id: Uuid,
+    pub version: i32,
+    pub rules: serde_json::Value,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -28,7 +39,7 @@ pub async fn create(pool: &PgPool, input: CreatePolicyInput) -> Result<Policy, s
         r#"
         INSERT INTO policies (tenant_id, name, rules, version)
         VALUES ($1, $2, $3, $4)
-        RETURNING id, tenant_id, name, rules, version, created_at
+        RETURNING id, tenant_id, name, rules, version, status, created_at
         "#,
     )
     .bind(input.tenant_id)
@@ -43,7 +54,7 @@ pub async fn create(pool: &PgPool, input: CreatePolicyInput) -> Result<Policy, s
 pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Policy>, sqlx::Error> {
     let rec = sqlx::query_as::<_, Policy>(
         r#"
-        SELECT id, tenant_id, name, rules, version, created_at
+        SELECT id, tenant_id, name, rules, version, status, created_at
         FROM policies
         WHERE id = $1
         "#,
@@ -62,7 +73,7 @@ pub async fn list_by_tenant(
 ) -> Result<Vec<Policy>, sqlx::Error> {
     let recs = sqlx::query_as::<_, Policy>(
         r#"
-        SELECT id, tenant_id, name, rules, version, created_at
+        SELECT id, tenant_id, name, rules, version, status, created_at
         FROM policies
         WHERE tenant_id = $1
         ORDER BY created_at DESC
@@ -96,7 +107,7 @@ pub async fn update(
             r#"
             UPDATE policies SET name = $3, rules = $4, version = $5
             WHERE id = $1 AND tenant_id = $2
-            RETURNING id, tenant_id, name, rules, version, created_at
+            RETURNING id, tenant_id, name, rules, version, status, created_at
             "#,
         )
         .bind(id)
@@ -112,7 +123,7 @@ pub async fn update(
             r#"
             UPDATE policies SET rules = $3, version = $4
             WHERE id = $1 AND tenant_id = $2
-            RETURNING id, tenant_id, name, rules, version, created_at
+            RETURNING id, tenant_id, name, rules, version, status, created_at
             "#,
         )
         .bind(id)
@@ -127,7 +138,7 @@ pub async fn update(
             r#"
             UPDATE policies SET name = $3, version = $4
             WHERE id = $1 AND tenant_id = $2
-            RETURNING id, tenant_id, name, rules, version, created_at
+            RETURNING id, tenant_id, name, rules, version, status, created_at
             "#,
         )
         .bind(id)
@@ -142,7 +153,7 @@ pub async fn update(
             r#"
             UPDATE policies SET rules = $3
             WHERE id = $1 AND tenant_id = $2
-            RETURNING id, tenant_id, name, rules, version, created_at
+            RETURNING id, tenant_id, name, rules, version, status, created_at
             "#,
         )
         .bind(id)
@@ -156,7 +167,7 @@ pub async fn update(
             r#"
             UPDATE policies SET version = $3
             WHERE id = $1 AND tenant_id = $2
-            RETURNING id, tenant_id, name, rules, version, created_at
+            RETURNING id, tenant_id, name, rules, version, status, created_at
             "#,
         )
         .bind(id)
@@ -197,3 +208,110 @@ pub async fn count_by_tenant(pool: &PgPool, tenant_id: Uuid) -> Result<i64, sqlx
     .await?;
     Ok(count.0)
 }
+
+pub async fn list_active_by_tenant(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<Policy>, sqlx::Error> {
+    let recs = sqlx::query_as::<_, Policy>(
+        r#"
+        SELECT id, tenant_id, name, rules, version, status, created_at
+        FROM policies
+        WHERE tenant_id = $1 AND status = 'active'
+        ORDER BY created_at
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(recs)
+}
+
+/// 更新策略状态；激活时可同时切换到指定版本的规则
+pub async fn set_status(
+    pool: &PgPool,
+    id: Uuid,
+    tenant_id: Uuid,
+    status: &str,
+    rules_and_version: Option<(serde_json::Value, i32)>,
+) -> Result<Option<Policy>, sqlx::Error> {
+    let (rules, version) = rules_and_version.unzip();
+    let rec = sqlx::query_as::<_, Policy>(
+        r#"
+        UPDATE policies
+        SET status = $3,
+            rules = COALESCE($4, rules),
+            version = COALESCE($5, version),
+            activated_at = CASE WHEN $3 = 'active' THEN NOW() ELSE activated_at END
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING id, tenant_id, name, rules, version, status, created_at
+        "#,
+    )
+    .bind(id)
+    .bind(tenant_id)
+    .bind(status)
+    .bind(rules)
+    .bind(version)
+    .fetch_optional(pool)
+    .await?;
+    Ok(rec)
+}
+
+/// 记录版本快照（同一版本号重复写入时覆盖）
+pub async fn upsert_version(
+    pool: &PgPool,
+    policy_id: Uuid,
+    version: i32,
+    rules: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO policy_versions (policy_id, version, rules)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (policy_id, version) DO UPDATE SET rules = EXCLUDED.rules, created_at = NOW()
+        "#,
+    )
+    .bind(policy_id)
+    .bind(version)
+    .bind(rules)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_version(
+    pool: &PgPool,
+    policy_id: Uuid,
+    version: i32,
+) -> Result<Option<PolicyVersion>, sqlx::Error> {
+    let rec = sqlx::query_as::<_, PolicyVersion>(
+        r#"
+        SELECT policy_id, version, rules, created_at
+        FROM policy_versions
+        WHERE policy_id = $1 AND version = $2
+        "#,
+    )
+    .bind(policy_id)
+    .bind(version)
+    .fetch_optional(pool)
+    .await?;
+    Ok(rec)
+}
+
+pub async fn list_versions(
+    pool: &PgPool,
+    policy_id: Uuid,
+) -> Result<Vec<PolicyVersion>, sqlx::Error> {
+    let recs = sqlx::query_as::<_, PolicyVersion>(
+        r#"
+        SELECT policy_id, version, rules, created_at
+        FROM policy_versions
+        WHERE policy_id = $1
+        ORDER BY version DESC
+        "#,
+    )
+    .bind(policy_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(recs)
+}
@@ -1,5 +1,5 @@
 //! Webhook签名验证模块
-//! 支持Onramper, TransFi, Alchemy Pay的签名验证算法
+//! 支持Onramper, TransFi, Alchemy Pay的签名验证算法，以及出站租户 Webhook 的签名
 
 use anyhow::{anyhow, Result};
 use axum::http::HeaderMap;
@@ -40,16 +40,77 @@ pub fn verify_transfi_signature(
     method: &str,
     path: &str,
     secret: &str,
+) -> Result<()> {
+    verify_timestamped_signature(
+        headers,
+        ("X-TransFi-Signature", "X-TransFi-Timestamp"),
+        body,
+        method,
+        path,
+        secret,
+    )
+    .map_err(|e| anyhow!("{} for TransFi", e))
+}
+
+/// 出站 Webhook 签名头（租户端点验签，方案与 TransFi 一致）
+pub const IRONCORE_SIGNATURE_HEADER: &str = "X-IronCore-Signature";
+pub const IRONCORE_TIMESTAMP_HEADER: &str = "X-IronCore-Timestamp";
+
+/// 出站 Webhook 签名
+/// 算法: HMAC-SHA256(secret, timestamp + method + path + body)，十六进制小写
+pub fn sign_timestamped_payload(
+    secret: &str,
+    timestamp: i64,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Result<String> {
+    let payload = format!("{}{}{}{}", timestamp, method.to_uppercase(), path, body);
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| anyhow!("Invalid HMAC key: {}", e))?;
+    mac.update(payload.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// IronCore 出站 Webhook签名验证（供集成方参考实现与测试）
+/// Headers: X-IronCore-Signature, X-IronCore-Timestamp
+pub fn verify_ironcore_signature(
+    headers: &HeaderMap,
+    body: &str,
+    method: &str,
+    path: &str,
+    secret: &str,
+) -> Result<()> {
+    verify_timestamped_signature(
+        headers,
+        (IRONCORE_SIGNATURE_HEADER, IRONCORE_TIMESTAMP_HEADER),
+        body,
+        method,
+        path,
+        secret,
+    )
+}
+
+/// 带时间戳的签名校验（签名头, 时间戳头）
+fn verify_timestamped_signature(
+    headers: &HeaderMap,
+    (signature_header, timestamp_header): (&str, &str),
+    body: &str,
+    method: &str,
+    path: &str,
+    secret: &str,
 ) -> Result<()> {
     let signature = headers
-        .get("X-TransFi-Signature")
-        .ok_or_else(|| anyhow!("Missing X-TransFi-Signature header"))?
+        .get(signature_header)
+        .ok_or_else(|| anyhow!("Missing {} header", signature_header))?
         .to_str()
         .map_err(|_| anyhow!("Invalid signature format"))?;
 
     let timestamp = headers
-        .get("X-TransFi-Timestamp")
-        .ok_or_else(|| anyhow!("Missing X-TransFi-Timestamp header"))?
+        .get(timestamp_header)
+        .ok_or_else(|| anyhow!("Missing {} header", timestamp_header))?
         .to_str()
         .map_err(|_| anyhow!("Invalid timestamp format"))?;
 
@@ -63,17 +124,10 @@ pub fn verify_transfi_signature(
         return Err(anyhow!("Webhook timestamp expired (>5 minutes)"));
     }
 
-    // 构造签名payload
-    let payload = format!("{}{}{}{}", timestamp, method.to_uppercase(), path, body);
+    let expected_signature = sign_timestamped_payload(secret, webhook_time, method, path, body)?;
 
-    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-        .map_err(|e| anyhow!("Invalid HMAC key: {}", e))?;
-    mac.update(payload.as_bytes());
-
-    let expected_signature = hex::encode(mac.finalize().into_bytes());
-
-    if signature.to_lowercase() != expected_signature.to_lowercase() {
-        return Err(anyhow!("Signature verification failed for TransFi"));
+    if signature.to_lowercase() != expected_signature {
+        return Err(anyhow!("Signature verification failed"));
     }
 
     Ok(())
@@ -215,4 +269,30 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("expired"));
     }
+
+    #[test]
+    fn test_ironcore_outbound_signature_roundtrip() {
+        let body = r#"{"type":"transaction.confirmed"}"#;
+        let secret = "whsec_test";
+        let timestamp = chrono::Utc::now().timestamp();
+
+        // 与 TransFi 方案一致：方法大小写不敏感
+        let signature =
+            sign_timestamped_payload(secret, timestamp, "post", "/hooks/ironcore", body).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IRONCORE_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers.insert(
+            IRONCORE_TIMESTAMP_HEADER,
+            timestamp.to_string().parse().unwrap(),
+        );
+
+        assert!(
+            verify_ironcore_signature(&headers, body, "POST", "/hooks/ironcore", secret).is_ok()
+        );
+        assert!(verify_ironcore_signature(&headers, body, "POST", "/hooks/other", secret).is_err());
+        assert!(
+            verify_ironcore_signature(&headers, "{}", "POST", "/hooks/ironcore", secret).is_err()
+        );
+    }
 }
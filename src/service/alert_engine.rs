@@ -0,0 +1,1540 @@
+//! 指标告警规则引擎
+//!
+//! 企业级实现：周期采样 Prometheus 注册表（`metrics::gather`），按规则评估
+//! - 函数：`value` 最新值 / `increase`、`rate` 窗口内增量与速率（处理计数器重置）/
+//!   `quantile` 直方图窗口内分位数；可选 `denominator` 构成比率
+//! - 阈值比较 + `for` 持续时间：inactive → pending → firing → resolved
+//! - 状态持久化到 `admin.alert_rule_states`，重启 / 换主后恢复 pending 计时与未恢复的告警
+//! - 触发时经 `ReconciliationService::create_alert` 写入 `fiat.alerts` 并通知管理员与运维，
+//!   恢复时将该告警置为 `resolved`
+//!
+//! 规则从 `config.toml` 同目录的 `alert_rules.toml` 加载（`ALERT_RULES_PATH` 可覆盖），
+//! 缺省使用内置的 `alert_rules.example.toml`。
+//!
+//! 多副本：每个副本按评估间隔把规则引用的序列发布到 Redis（`alert:metrics:{instance}`，
+//! 过期时间为三个间隔），评估器以单实例 worker 运行，合并全部存活副本的采样后评估；
+//! 合并时每条序列附加 `instance` 标签，计数器按副本各自处理重置，规则默认对所有副本求和，
+//! 需要针对单个副本时在 `labels` 中匹配 `instance`。未接入 Redis 时只评估本进程注册表。
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use prometheus::proto::{MetricFamily, MetricType};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{PgPool, Row};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    infrastructure::cache::RedisCtx,
+    service::{
+        notification_service::{NotificationService, PublishNotificationInput},
+        reconciliation_service::{AlertSeverity, ReconciliationService},
+    },
+};
+
+/// 内置默认规则（未提供 alert_rules.toml 时使用）
+const BUILTIN_RULES: &str = include_str!("../../alert_rules.example.toml");
+const RULES_FILE_NAME: &str = "alert_rules.toml";
+const DEFAULT_EVALUATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 副本采样在 Redis 中的键前缀
+const REPLICA_SNAPSHOT_PREFIX: &str = "alert:metrics:";
+/// 副本采样保留的评估间隔数，副本下线后其序列随键过期退出评估
+const REPLICA_SNAPSHOT_TTL_INTERVALS: u32 = 3;
+/// 合并副本采样时附加的标签
+const INSTANCE_LABEL: &str = "instance";
+
+// ============ 规则配置 ============
+
+/// 告警规则文件
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRulesConfig {
+    /// 采样与评估间隔
+    #[serde(
+        default = "default_evaluation_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub evaluation_interval: Duration,
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+fn default_evaluation_interval() -> Duration {
+    DEFAULT_EVALUATION_INTERVAL
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 指标选择器：指标名 + 标签匹配（标签值按正则整体匹配，缺失的标签按空串匹配）
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricSelector {
+    pub metric: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// 规则函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleFunction {
+    /// 最新采样值（仪表）
+    #[default]
+    Value,
+    /// 窗口内增量（计数器）
+    Increase,
+    /// 窗口内每秒速率（计数器）
+    Rate,
+    /// 窗口内直方图分位数
+    Quantile,
+}
+
+/// 阈值比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+}
+
+impl Comparison {
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Gt => value > threshold,
+            Comparison::Ge => value >= threshold,
+            Comparison::Lt => value < threshold,
+            Comparison::Le => value <= threshold,
+            Comparison::Eq => value == threshold,
+            Comparison::Ne => value != threshold,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+        }
+    }
+}
+
+/// 告警规则
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(default)]
+    pub summary: String,
+    pub metric: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub function: RuleFunction,
+    /// 比率分母（与分子使用同一函数与窗口）
+    #[serde(default)]
+    pub denominator: Option<MetricSelector>,
+    /// `quantile` 函数的分位数（0~1）
+    #[serde(default)]
+    pub quantile: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub window: Duration,
+    pub op: Comparison,
+    pub threshold: f64,
+    /// 条件需持续满足的时长，0 表示立即触发
+    #[serde(default, rename = "for", deserialize_with = "deserialize_duration")]
+    pub for_duration: Duration,
+    pub severity: AlertSeverity,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl AlertRule {
+    fn selector(&self) -> MetricSelector {
+        MetricSelector {
+            metric: self.metric.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    /// 规则表达式（PromQL 风格，用于告警消息与日志）
+    pub fn expression(&self) -> String {
+        let term = |selector: &MetricSelector| {
+            let labels = selector
+                .labels
+                .iter()
+                .map(|(k, v)| format!("{}=~\"{}\"", k, v))
+                .collect::<Vec<_>>()
+                .join(",");
+            let series = if labels.is_empty() {
+                selector.metric.clone()
+            } else {
+                format!("{}{{{}}}", selector.metric, labels)
+            };
+            let window = format_duration(self.window);
+            match self.function {
+                RuleFunction::Value => series,
+                RuleFunction::Increase => format!("increase({}[{}])", series, window),
+                RuleFunction::Rate => format!("rate({}[{}])", series, window),
+                RuleFunction::Quantile => format!(
+                    "histogram_quantile({}, {}[{}])",
+                    self.quantile.unwrap_or_default(),
+                    series,
+                    window
+                ),
+            }
+        };
+        let numerator = term(&self.selector());
+        match &self.denominator {
+            Some(denominator) => format!("{} / {}", numerator, term(denominator)),
+            None => numerator,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("alert rule name must not be empty");
+        }
+        if self.metric.trim().is_empty() {
+            bail!("alert rule {}: metric must not be empty", self.name);
+        }
+        if !self.threshold.is_finite() {
+            bail!("alert rule {}: threshold must be finite", self.name);
+        }
+        if self.function != RuleFunction::Value && self.window.is_zero() {
+            bail!(
+                "alert rule {}: window is required for {:?}",
+                self.name,
+                self.function
+            );
+        }
+        if self.function == RuleFunction::Quantile {
+            match self.quantile {
+                Some(q) if q > 0.0 && q <= 1.0 => {}
+                _ => bail!("alert rule {}: quantile must be in (0, 1]", self.name),
+            }
+            if self.denominator.is_some() {
+                bail!(
+                    "alert rule {}: quantile does not support denominator",
+                    self.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AlertRulesConfig {
+    /// 内置默认规则
+    pub fn builtin() -> Result<Self> {
+        Self::from_toml_str(BUILTIN_RULES)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content).context("Failed to parse alert rules")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .with_context(|| format!("Failed to read alert rules: {}", path.as_ref().display()))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// 规则文件路径：`ALERT_RULES_PATH` > `CONFIG_PATH` 同目录 > 当前目录
+    pub fn rules_path() -> PathBuf {
+        if let Ok(path) = std::env::var("ALERT_RULES_PATH") {
+            return PathBuf::from(path);
+        }
+        match std::env::var("CONFIG_PATH") {
+            Ok(config_path) => Path::new(&config_path).with_file_name(RULES_FILE_NAME),
+            Err(_) => PathBuf::from(RULES_FILE_NAME),
+        }
+    }
+
+    /// 加载规则文件，不存在时使用内置规则（文件存在但无效时报错）
+    pub fn load() -> Result<Self> {
+        let path = Self::rules_path();
+        if path.exists() {
+            tracing::info!(path = %path.display(), "Loading alert rules");
+            Self::from_file(&path)
+        } else {
+            tracing::info!(path = %path.display(), "Alert rules file not found, using built-in rules");
+            Self::builtin()
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.evaluation_interval.is_zero() {
+            bail!("evaluation_interval must be positive");
+        }
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            rule.validate()?;
+            if !names.insert(rule.name.as_str()) {
+                bail!("duplicate alert rule name: {}", rule.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 解析时长：`30s` / `5m` / `1h` / `1d`，纯数字按秒
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("invalid duration: {:?}", value))?;
+    let secs = match unit {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        "d" => number * 86400,
+        _ => bail!("invalid duration unit: {:?}", value),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        s if s > 0 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s > 0 && s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Secs(u64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Secs(secs) => Ok(Duration::from_secs(secs)),
+        Raw::Text(text) => parse_duration(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+// ============ 采样与求值 ============
+
+type Labels = Vec<(String, String)>;
+type SeriesKey = (String, Labels);
+
+/// 一次注册表采样（仅保留规则引用的指标）
+struct Snapshot {
+    at: DateTime<Utc>,
+    series: HashMap<SeriesKey, f64>,
+}
+
+/// 单个副本发布到共享存储的采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplicaSnapshot {
+    instance: String,
+    series: Vec<(String, Labels, f64)>,
+}
+
+struct CompiledSelector {
+    metric: String,
+    matchers: Vec<(String, Regex)>,
+}
+
+impl CompiledSelector {
+    fn compile(selector: &MetricSelector) -> Result<Self> {
+        let matchers = selector
+            .labels
+            .iter()
+            .map(|(name, pattern)| {
+                Regex::new(&format!("^(?:{})$", pattern))
+                    .map(|re| (name.clone(), re))
+                    .with_context(|| format!("invalid label matcher {}=~{:?}", name, pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            metric: selector.metric.clone(),
+            matchers,
+        })
+    }
+
+    fn matches(&self, labels: &Labels) -> bool {
+        self.matchers.iter().all(|(name, re)| {
+            let value = labels
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+                .unwrap_or("");
+            re.is_match(value)
+        })
+    }
+}
+
+struct CompiledRule {
+    rule: AlertRule,
+    numerator: CompiledSelector,
+    denominator: Option<CompiledSelector>,
+}
+
+/// 规则状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlertState {
+    #[default]
+    Inactive,
+    Pending,
+    Firing,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertState::Inactive => "inactive",
+            AlertState::Pending => "pending",
+            AlertState::Firing => "firing",
+            AlertState::Resolved => "resolved",
+        }
+    }
+
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "inactive" => Some(AlertState::Inactive),
+            "pending" => Some(AlertState::Pending),
+            "firing" => Some(AlertState::Firing),
+            "resolved" => Some(AlertState::Resolved),
+            _ => None,
+        }
+    }
+}
+
+/// 单条规则的运行状态
+#[derive(Debug, Clone, Default)]
+pub struct RuleStatus {
+    pub state: AlertState,
+    /// 进入当前状态的时间（pending 计时起点 / 触发时间 / 恢复时间）
+    pub since: Option<DateTime<Utc>>,
+    pub value: Option<f64>,
+    /// 最近一次触发写入的 `fiat.alerts` 记录
+    pub alert_id: Option<Uuid>,
+}
+
+/// 状态变化
+#[derive(Debug, Clone)]
+pub struct AlertTransition {
+    pub rule: String,
+    /// 变化前的状态（处理失败时据此回滚，下一轮重试）
+    pub previous: RuleStatus,
+    pub to: AlertState,
+    pub value: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// 告警规则引擎（纯内存：采样窗口 + 状态机）
+pub struct AlertEngine {
+    rules: Vec<CompiledRule>,
+    tracked: HashSet<String>,
+    retention: chrono::Duration,
+    history: VecDeque<Snapshot>,
+    statuses: HashMap<String, RuleStatus>,
+}
+
+impl AlertEngine {
+    pub fn new(config: &AlertRulesConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        let mut tracked = HashSet::new();
+        for rule in config.rules.iter().filter(|r| r.enabled) {
+            let numerator = CompiledSelector::compile(&rule.selector())?;
+            let denominator = rule
+                .denominator
+                .as_ref()
+                .map(CompiledSelector::compile)
+                .transpose()?;
+            tracked.insert(series_name(rule.function, &numerator.metric));
+            if let Some(d) = &denominator {
+                tracked.insert(series_name(rule.function, &d.metric));
+            }
+            rules.push(CompiledRule {
+                rule: rule.clone(),
+                numerator,
+                denominator,
+            });
+        }
+
+        let longest_window = rules
+            .iter()
+            .map(|r| r.rule.window)
+            .max()
+            .unwrap_or_default();
+        let retention = chrono::Duration::from_std(longest_window + config.evaluation_interval * 2)
+            .context("alert rule window too large")?;
+
+        Ok(Self {
+            rules,
+            tracked,
+            retention,
+            history: VecDeque::new(),
+            statuses: HashMap::new(),
+        })
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&AlertRule> {
+        self.rules.iter().map(|r| &r.rule).find(|r| r.name == name)
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn status(&self, rule: &str) -> Option<&RuleStatus> {
+        self.statuses.get(rule)
+    }
+
+    /// 恢复持久化的状态（未配置的规则忽略）
+    pub fn restore(&mut self, rule: &str, status: RuleStatus) {
+        if self.rule(rule).is_some() {
+            self.statuses.insert(rule.to_string(), status);
+        }
+    }
+
+    pub fn clear_statuses(&mut self) {
+        self.statuses.clear();
+    }
+
+    pub fn set_alert_id(&mut self, rule: &str, alert_id: Option<Uuid>) {
+        if let Some(status) = self.statuses.get_mut(rule) {
+            status.alert_id = alert_id;
+        }
+    }
+
+    /// 记录一次本进程采样并淘汰超出最长窗口的历史
+    pub fn record(&mut self, at: DateTime<Utc>, families: &[MetricFamily]) {
+        let series = flatten_families(families, &self.tracked);
+        self.push_snapshot(at, series);
+    }
+
+    /// 本进程注册表中规则引用的序列（供发布到共享存储）
+    fn replica_snapshot(&self, instance: &str, families: &[MetricFamily]) -> ReplicaSnapshot {
+        ReplicaSnapshot {
+            instance: instance.to_string(),
+            series: flatten_families(families, &self.tracked)
+                .into_iter()
+                .map(|((name, labels), value)| (name, labels, value))
+                .collect(),
+        }
+    }
+
+    /// 记录一次全集群采样：各副本的序列附加 `instance` 标签后合并
+    fn record_replicas(&mut self, at: DateTime<Utc>, replicas: &[ReplicaSnapshot]) {
+        let mut series = HashMap::new();
+        for replica in replicas {
+            for (name, labels, value) in &replica.series {
+                if !self.tracked.contains(name) {
+                    continue;
+                }
+                let mut labels: Labels = labels
+                    .iter()
+                    .filter(|(k, _)| k != INSTANCE_LABEL)
+                    .cloned()
+                    .collect();
+                labels.push((INSTANCE_LABEL.to_string(), replica.instance.clone()));
+                labels.sort();
+                series.insert((name.clone(), labels), *value);
+            }
+        }
+        self.push_snapshot(at, series);
+    }
+
+    fn push_snapshot(&mut self, at: DateTime<Utc>, series: HashMap<SeriesKey, f64>) {
+        self.history.push_back(Snapshot { at, series });
+        let cutoff = at - self.retention;
+        while self.history.front().is_some_and(|s| s.at < cutoff) {
+            self.history.pop_front();
+        }
+    }
+
+    /// 评估全部规则，返回状态变化
+    ///
+    /// 数据不足（如重启后窗口内采样少于两次）时保持当前状态，避免误恢复
+    pub fn evaluate(&mut self, now: DateTime<Utc>) -> Vec<AlertTransition> {
+        let mut transitions = Vec::new();
+        for compiled in &self.rules {
+            let rule = &compiled.rule;
+            let Some(value) = rule_value(&self.history, compiled, now) else {
+                continue;
+            };
+
+            let status = self.statuses.entry(rule.name.clone()).or_default();
+            status.value = Some(value);
+            let breached = rule.op.holds(value, rule.threshold);
+            let pending_for = status
+                .since
+                .map(|since| now - since)
+                .and_then(|elapsed| elapsed.to_std().ok())
+                .unwrap_or_default();
+
+            let next = match (status.state, breached) {
+                (AlertState::Inactive | AlertState::Resolved, true) => {
+                    if rule.for_duration.is_zero() {
+                        AlertState::Firing
+                    } else {
+                        AlertState::Pending
+                    }
+                }
+                (AlertState::Pending, true) if pending_for >= rule.for_duration => {
+                    AlertState::Firing
+                }
+                (AlertState::Pending, false) => AlertState::Inactive,
+                (AlertState::Firing, false) => AlertState::Resolved,
+                (state, _) => state,
+            };
+
+            if next != status.state {
+                let previous = status.clone();
+                status.state = next;
+                status.since = Some(now);
+                transitions.push(AlertTransition {
+                    rule: rule.name.clone(),
+                    previous,
+                    to: next,
+                    value,
+                    at: now,
+                });
+            }
+        }
+        transitions
+    }
+}
+
+/// 规则实际读取的序列名（直方图分位数读取 `_bucket`）
+fn series_name(function: RuleFunction, metric: &str) -> String {
+    match function {
+        RuleFunction::Quantile => format!("{}_bucket", metric),
+        _ => metric.to_string(),
+    }
+}
+
+fn flatten_families(
+    families: &[MetricFamily],
+    tracked: &HashSet<String>,
+) -> HashMap<SeriesKey, f64> {
+    let mut series = HashMap::new();
+    let mut insert = |name: String, labels: &Labels, value: f64| {
+        if tracked.contains(&name) {
+            series.insert((name, labels.clone()), value);
+        }
+    };
+
+    for family in families {
+        let name = family.get_name();
+        for metric in family.get_metric() {
+            let mut labels: Labels = metric
+                .get_label()
+                .iter()
+                .map(|l| (l.get_name().to_string(), l.get_value().to_string()))
+                .collect();
+            labels.sort();
+
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    insert(name.to_string(), &labels, metric.get_counter().get_value())
+                }
+                MetricType::GAUGE => {
+                    insert(name.to_string(), &labels, metric.get_gauge().get_value())
+                }
+                // 本注册表不产生 untyped 指标
+                MetricType::UNTYPED => {}
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let bucket_name = format!("{}_bucket", name);
+                    let buckets = histogram
+                        .get_bucket()
+                        .iter()
+                        .map(|b| (b.get_upper_bound().to_string(), b.get_cumulative_count()))
+                        .chain(std::iter::once((
+                            "+Inf".to_string(),
+                            histogram.get_sample_count(),
+                        )));
+                    for (le, count) in buckets {
+                        let mut bucket_labels = labels.clone();
+                        bucket_labels.push(("le".to_string(), le));
+                        bucket_labels.sort();
+                        insert(bucket_name.clone(), &bucket_labels, count as f64);
+                    }
+                    insert(
+                        format!("{}_count", name),
+                        &labels,
+                        histogram.get_sample_count() as f64,
+                    );
+                    insert(format!("{}_sum", name), &labels, histogram.get_sample_sum());
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    insert(
+                        format!("{}_count", name),
+                        &labels,
+                        summary.get_sample_count() as f64,
+                    );
+                    insert(format!("{}_sum", name), &labels, summary.get_sample_sum());
+                }
+            }
+        }
+    }
+    series
+}
+
+/// 窗口内的采样（至少两次才能计算增量）
+fn window_snapshots(
+    history: &VecDeque<Snapshot>,
+    now: DateTime<Utc>,
+    window: Duration,
+) -> Option<Vec<&Snapshot>> {
+    let start = now - chrono::Duration::from_std(window).ok()?;
+    let snapshots: Vec<&Snapshot> = history
+        .iter()
+        .filter(|s| s.at >= start && s.at <= now)
+        .collect();
+    (snapshots.len() >= 2).then_some(snapshots)
+}
+
+/// 窗口内匹配序列的增量，按 `group_label` 的取值分组求和
+///
+/// 计数器下降视为重置（从 0 重新累计）；窗口首个采样中不存在的序列以 0 为基线
+/// （进程内注册表的带标签序列在首次递增时才出现）
+fn increase_by(
+    snapshots: &[&Snapshot],
+    name: &str,
+    selector: &CompiledSelector,
+    group_label: Option<&str>,
+) -> HashMap<String, f64> {
+    let keys: HashSet<&SeriesKey> = snapshots
+        .iter()
+        .flat_map(|s| s.series.keys())
+        .filter(|(n, labels)| n == name && selector.matches(labels))
+        .collect();
+
+    let mut increases = HashMap::new();
+    for key in keys {
+        let mut previous = snapshots[0].series.get(key).copied().unwrap_or(0.0);
+        let mut increase = 0.0;
+        for snapshot in &snapshots[1..] {
+            if let Some(&value) = snapshot.series.get(key) {
+                increase += if value >= previous {
+                    value - previous
+                } else {
+                    value
+                };
+                previous = value;
+            }
+        }
+        let group = group_label
+            .and_then(|label| key.1.iter().find(|(k, _)| k == label))
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default();
+        *increases.entry(group).or_insert(0.0) += increase;
+    }
+    increases
+}
+
+fn selector_value(
+    history: &VecDeque<Snapshot>,
+    function: RuleFunction,
+    window: Duration,
+    quantile: Option<f64>,
+    selector: &CompiledSelector,
+    now: DateTime<Utc>,
+) -> Option<f64> {
+    match function {
+        RuleFunction::Value => {
+            let latest = history.back()?;
+            let values: Vec<f64> = latest
+                .series
+                .iter()
+                .filter(|((n, labels), _)| *n == selector.metric && selector.matches(labels))
+                .map(|(_, v)| *v)
+                .collect();
+            (!values.is_empty()).then(|| values.iter().sum())
+        }
+        RuleFunction::Increase => {
+            let snapshots = window_snapshots(history, now, window)?;
+            Some(
+                increase_by(&snapshots, &selector.metric, selector, None)
+                    .values()
+                    .sum(),
+            )
+        }
+        RuleFunction::Rate => {
+            let snapshots = window_snapshots(history, now, window)?;
+            let elapsed = (snapshots[snapshots.len() - 1].at - snapshots[0].at).num_milliseconds()
+                as f64
+                / 1000.0;
+            if elapsed <= 0.0 {
+                return None;
+            }
+            let increase: f64 = increase_by(&snapshots, &selector.metric, selector, None)
+                .values()
+                .sum();
+            Some(increase / elapsed)
+        }
+        RuleFunction::Quantile => {
+            let snapshots = window_snapshots(history, now, window)?;
+            let name = series_name(function, &selector.metric);
+            let buckets = increase_by(&snapshots, &name, selector, Some("le"));
+            Some(bucket_quantile(quantile?, &buckets))
+        }
+    }
+}
+
+fn rule_value(
+    history: &VecDeque<Snapshot>,
+    compiled: &CompiledRule,
+    now: DateTime<Utc>,
+) -> Option<f64> {
+    let rule = &compiled.rule;
+    let numerator = selector_value(
+        history,
+        rule.function,
+        rule.window,
+        rule.quantile,
+        &compiled.numerator,
+        now,
+    )?;
+    let Some(denominator) = &compiled.denominator else {
+        return Some(numerator);
+    };
+    let denominator = selector_value(
+        history,
+        rule.function,
+        rule.window,
+        rule.quantile,
+        denominator,
+        now,
+    )?;
+    if denominator == 0.0 {
+        // 窗口内无流量视为比率 0；仪表分母为 0 视为无数据
+        return (rule.function != RuleFunction::Value).then_some(0.0);
+    }
+    Some(numerator / denominator)
+}
+
+/// 由窗口内各桶（累计）增量线性插值求分位数；无观测时为 0，落在 +Inf 桶时取最大有限上界
+fn bucket_quantile(quantile: f64, buckets: &HashMap<String, f64>) -> f64 {
+    let mut buckets: Vec<(f64, f64)> = buckets
+        .iter()
+        .filter_map(|(le, count)| {
+            let bound = if le == "+Inf" {
+                f64::INFINITY
+            } else {
+                le.parse().ok()?
+            };
+            Some((bound, *count))
+        })
+        .collect();
+    buckets.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let total = buckets.last().map(|(_, count)| *count).unwrap_or(0.0);
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let target = total * quantile;
+
+    let mut previous_bound = 0.0;
+    let mut previous_count = 0.0;
+    for (bound, count) in &buckets {
+        if *count >= target {
+            if bound.is_infinite() {
+                return previous_bound;
+            }
+            if *count == previous_count {
+                return *bound;
+            }
+            let ratio = (target - previous_count) / (count - previous_count);
+            return previous_bound + ratio * (bound - previous_bound);
+        }
+        previous_bound = *bound;
+        previous_count = *count;
+    }
+    previous_bound
+}
+
+impl fmt::Display for AlertTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} (value={})",
+            self.rule,
+            self.previous.state.as_str(),
+            self.to.as_str(),
+            self.value
+        )
+    }
+}
+
+// ============ 副本采样共享 ============
+
+async fn publish_replica_snapshot(
+    redis: &RedisCtx,
+    key: &str,
+    snapshot: &ReplicaSnapshot,
+    ttl_secs: u64,
+) -> Result<()> {
+    let payload = serde_json::to_string(snapshot)?;
+    let mut conn = redis.client.get_multiplexed_async_connection().await?;
+    redis::cmd("SET")
+        .arg(key)
+        .arg(payload)
+        .arg("EX")
+        .arg(ttl_secs)
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// 读取全部存活副本的采样（键过期即视为副本下线）
+async fn load_replica_snapshots(redis: &RedisCtx) -> Result<Vec<ReplicaSnapshot>> {
+    let mut conn = redis.client.get_multiplexed_async_connection().await?;
+    let pattern = format!("{}*", REPLICA_SNAPSHOT_PREFIX);
+    let mut keys = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(100)
+            .query_async(&mut conn)
+            .await?;
+        keys.extend(batch);
+        cursor = next;
+        if cursor == 0 {
+            break;
+        }
+    }
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let payloads: Vec<Option<String>> =
+        redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+    Ok(payloads
+        .into_iter()
+        .flatten()
+        .filter_map(|payload| match serde_json::from_str(&payload) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!(error = %e, "Skipping malformed alert metrics snapshot");
+                None
+            }
+        })
+        .collect())
+}
+
+// ============ 后台评估器 ============
+
+/// 告警评估 worker：采样 → 评估 → 写告警 / 通知 → 持久化状态
+pub struct AlertEvaluator {
+    pool: PgPool,
+    alerts: ReconciliationService,
+    notifications: NotificationService,
+    engine: Mutex<AlertEngine>,
+    interval: Duration,
+    /// 多副本共享采样（Redis + 本副本实例 ID）
+    shared: Option<(Arc<RedisCtx>, String)>,
+}
+
+impl AlertEvaluator {
+    pub fn new(pool: PgPool, config: &AlertRulesConfig) -> Result<Self> {
+        Ok(Self {
+            alerts: ReconciliationService::new(pool.clone()),
+            notifications: NotificationService::new(pool.clone()),
+            engine: Mutex::new(AlertEngine::new(config)?),
+            interval: config.evaluation_interval,
+            pool,
+            shared: None,
+        })
+    }
+
+    /// 经 Redis 共享各副本采样：每个副本运行 `start_background_publisher`，评估器合并后评估
+    pub fn with_shared_metrics(mut self, redis: Arc<RedisCtx>, instance_id: String) -> Self {
+        self.shared = Some((redis, instance_id));
+        self
+    }
+
+    /// 周期发布本副本采样（每个副本都运行，不受 worker 租约限制）
+    pub async fn start_background_publisher(self: Arc<Self>) {
+        let Some((redis, instance_id)) = self.shared.clone() else {
+            return;
+        };
+        let key = format!("{}{}", REPLICA_SNAPSHOT_PREFIX, instance_id);
+        let ttl = (self.interval * REPLICA_SNAPSHOT_TTL_INTERVALS)
+            .as_secs()
+            .max(1);
+        let mut ticker = tokio::time::interval(self.interval);
+
+        loop {
+            ticker.tick().await;
+            let snapshot = self
+                .engine
+                .lock()
+                .await
+                .replica_snapshot(&instance_id, &crate::metrics::gather());
+            if let Err(e) = publish_replica_snapshot(&redis, &key, &snapshot, ttl).await {
+                tracing::warn!(error = ?e, "Failed to publish alert metrics snapshot");
+            }
+        }
+    }
+
+    /// 启动后台评估（持续运行）；每次获得租约时先从数据库恢复状态
+    pub async fn start_background_evaluator(self: Arc<Self>) {
+        if let Err(e) = self.restore_states().await {
+            tracing::error!(error = ?e, "Failed to restore alert rule states");
+        }
+
+        let rule_count = self.engine.lock().await.rule_count();
+        let mut ticker = tokio::time::interval(self.interval);
+        tracing::info!(
+            rules = rule_count,
+            "Alert evaluator started, interval={}s",
+            self.interval.as_secs()
+        );
+
+        loop {
+            ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
+            if let Err(e) = self.evaluate_once().await {
+                tracing::error!(error = ?e, "Alert evaluation failed");
+            }
+        }
+    }
+
+    /// 采样并评估一轮，返回状态变化数
+    pub async fn evaluate_once(&self) -> Result<usize> {
+        let now = Utc::now();
+        let replicas = match &self.shared {
+            Some((redis, _)) => {
+                let replicas = load_replica_snapshots(redis).await?;
+                if replicas.is_empty() {
+                    // 副本尚未发布：本轮不记录，避免空采样被当作无流量
+                    tracing::debug!("No alert metrics snapshots published yet");
+                    return Ok(0);
+                }
+                Some(replicas)
+            }
+            None => None,
+        };
+
+        let mut engine = self.engine.lock().await;
+        match replicas {
+            Some(replicas) => engine.record_replicas(now, &replicas),
+            None => engine.record(now, &crate::metrics::gather()),
+        }
+        let transitions = engine.evaluate(now);
+
+        for transition in &transitions {
+            tracing::info!(transition = %transition, "Alert rule state changed");
+            if let Err(e) = self.apply(&mut engine, transition).await {
+                tracing::error!(rule = %transition.rule, error = ?e, "Failed to apply alert transition");
+                // 回滚到变化前状态，下一轮重试
+                engine.restore(&transition.rule, transition.previous.clone());
+            }
+        }
+        Ok(transitions.len())
+    }
+
+    async fn restore_states(&self) -> Result<()> {
+        let rows = sqlx::query(
+            "SELECT rule_name, state, value, active_since, alert_id FROM admin.alert_rule_states",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load alert rule states")?;
+
+        let mut engine = self.engine.lock().await;
+        engine.clear_statuses();
+        for row in rows {
+            let rule: String = row.try_get("rule_name")?;
+            let state: String = row.try_get("state")?;
+            let Some(state) = AlertState::parse(&state) else {
+                continue;
+            };
+            engine.restore(
+                &rule,
+                RuleStatus {
+                    state,
+                    since: row.try_get("active_since")?,
+                    value: row.try_get("value")?,
+                    alert_id: row.try_get("alert_id")?,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn apply(&self, engine: &mut AlertEngine, transition: &AlertTransition) -> Result<()> {
+        let Some(rule) = engine.rule(&transition.rule).cloned() else {
+            return Ok(());
+        };
+        let summary = if rule.summary.is_empty() {
+            rule.name.as_str()
+        } else {
+            rule.summary.as_str()
+        };
+        let detail = format!(
+            "{} = {:.4} ({} {})",
+            rule.expression(),
+            transition.value,
+            rule.op.as_str(),
+            rule.threshold
+        );
+
+        match transition.to {
+            AlertState::Firing => {
+                let message = format!("{}: {}", summary, detail);
+                let alert_id = self
+                    .alerts
+                    .create_alert(
+                        None,
+                        &format!("metric_{}", rule.name),
+                        rule.severity.as_str(),
+                        &message,
+                        None,
+                        None,
+                    )
+                    .await?;
+                engine.set_alert_id(&rule.name, Some(alert_id));
+                self.notify(format!("[FIRING] {}", rule.name), message, rule.severity)
+                    .await;
+            }
+            AlertState::Resolved => {
+                if let Some(alert_id) = transition.previous.alert_id {
+                    self.alerts.resolve_alert(alert_id).await?;
+                }
+                self.notify(
+                    format!("[RESOLVED] {}", rule.name),
+                    format!("{} recovered: {}", summary, detail),
+                    rule.severity,
+                )
+                .await;
+            }
+            AlertState::Inactive | AlertState::Pending => {}
+        }
+
+        let status = engine.status(&rule.name).cloned().unwrap_or_default();
+        self.persist_state(&rule.name, &status, transition).await
+    }
+
+    async fn persist_state(
+        &self,
+        rule: &str,
+        status: &RuleStatus,
+        transition: &AlertTransition,
+    ) -> Result<()> {
+        let fired_at = (transition.to == AlertState::Firing).then_some(transition.at);
+        let resolved_at = (transition.to == AlertState::Resolved).then_some(transition.at);
+
+        sqlx::query(
+            r#"
+            INSERT INTO admin.alert_rule_states (
+                rule_name, state, value, active_since, alert_id,
+                last_fired_at, last_resolved_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, CURRENT_TIMESTAMP)
+            ON CONFLICT (rule_name) DO UPDATE SET
+                state = EXCLUDED.state,
+                value = EXCLUDED.value,
+                active_since = EXCLUDED.active_since,
+                alert_id = EXCLUDED.alert_id,
+                last_fired_at = COALESCE(EXCLUDED.last_fired_at, admin.alert_rule_states.last_fired_at),
+                last_resolved_at = COALESCE(EXCLUDED.last_resolved_at, admin.alert_rule_states.last_resolved_at),
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(rule)
+        .bind(status.state.as_str())
+        .bind(status.value)
+        .bind(status.since)
+        .bind(status.alert_id)
+        .bind(fired_at)
+        .bind(resolved_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to persist alert rule state")?;
+        Ok(())
+    }
+
+    /// 通知在职管理员与运维（失败仅记录，不影响告警状态）
+    async fn notify(&self, title: String, body: String, severity: AlertSeverity) {
+        let user_ids: Vec<Uuid> = match sqlx::query_scalar(
+            "SELECT id FROM users WHERE role IN ('admin', 'operator') AND status = 'active' LIMIT 500",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to load alert notification recipients");
+                return;
+            }
+        };
+        if user_ids.is_empty() {
+            return;
+        }
+
+        let result = self
+            .notifications
+            .publish(PublishNotificationInput {
+                title,
+                body,
+                category: "alert".to_string(),
+                severity: Some(severity.as_str().to_string()),
+                scope: "user".to_string(),
+                creator_role: "system".to_string(),
+                user_ids: Some(user_ids),
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to publish alert notification");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{
+        HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    };
+
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn engine(rules: &str) -> AlertEngine {
+        AlertEngine::new(&AlertRulesConfig::from_toml_str(rules).unwrap()).unwrap()
+    }
+
+    /// 每次返回新的注册表，计数器值按参数设置（模拟某一时刻的采样）
+    fn http_families(ok: u64, server_errors: u64) -> Vec<MetricFamily> {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new("ironcore_http_requests_total", "requests"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        registry.register(Box::new(requests.clone())).unwrap();
+        requests.with_label_values(&["GET", "/a", "200"]).inc_by(ok);
+        if server_errors > 0 {
+            requests
+                .with_label_values(&["GET", "/a", "502"])
+                .inc_by(server_errors);
+        }
+        registry.gather()
+    }
+
+    const ERROR_RATE_RULE: &str = r#"
+        evaluation_interval = "10s"
+
+        [[rules]]
+        name = "high_http_error_rate"
+        metric = "ironcore_http_requests_total"
+        labels = { status = "5.." }
+        function = "rate"
+        denominator = { metric = "ironcore_http_requests_total" }
+        window = "1m"
+        op = ">"
+        threshold = 0.1
+        for = "30s"
+        severity = "critical"
+    "#;
+
+    #[test]
+    fn test_rules_config_parse_and_validate() {
+        let builtin = AlertRulesConfig::builtin().unwrap();
+        assert_eq!(builtin.evaluation_interval, Duration::from_secs(30));
+        let consensus = builtin
+            .rules
+            .iter()
+            .find(|r| r.name == "rpc_consensus_disagreement")
+            .unwrap();
+        assert_eq!(consensus.function, RuleFunction::Increase);
+        assert_eq!(consensus.window, Duration::from_secs(600));
+        assert!(consensus.for_duration.is_zero());
+        assert_eq!(consensus.severity, AlertSeverity::Critical);
+        assert_eq!(
+            consensus.expression(),
+            "increase(ironcore_rpc_consensus_disagreement_total[10m])"
+        );
+
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_duration("5w").is_err());
+
+        let missing_window = r#"
+            [[rules]]
+            name = "p99"
+            metric = "ironcore_http_request_duration_seconds"
+            function = "quantile"
+            quantile = 0.99
+            op = ">"
+            threshold = 1
+            severity = "high"
+        "#;
+        assert!(AlertRulesConfig::from_toml_str(missing_window).is_err());
+
+        let bad_severity = ERROR_RATE_RULE.replace("\"critical\"", "\"warning\"");
+        assert!(AlertRulesConfig::from_toml_str(&bad_severity).is_err());
+
+        let duplicate = format!(
+            "{}\n{}",
+            ERROR_RATE_RULE,
+            &ERROR_RATE_RULE[ERROR_RATE_RULE.find("[[rules]]").unwrap()..]
+        );
+        assert!(AlertRulesConfig::from_toml_str(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_error_ratio_pending_firing_resolved() {
+        let mut engine = engine(ERROR_RATE_RULE);
+
+        // 单次采样不足以计算速率
+        engine.record(at(0), &http_families(100, 0));
+        assert!(engine.evaluate(at(0)).is_empty());
+
+        // 5xx 占比 20/(80+20) → pending
+        engine.record(at(10), &http_families(180, 20));
+        let transitions = engine.evaluate(at(10));
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to, AlertState::Pending);
+        assert!((transitions[0].value - 0.2).abs() < 1e-9);
+
+        // 未满 for 时长，保持 pending
+        engine.record(at(20), &http_families(260, 40));
+        assert!(engine.evaluate(at(20)).is_empty());
+
+        // 持续 30s → firing
+        engine.record(at(40), &http_families(420, 80));
+        let transitions = engine.evaluate(at(40));
+        assert_eq!(transitions[0].to, AlertState::Firing);
+        assert_eq!(transitions[0].previous.state, AlertState::Pending);
+
+        // 错误停止且旧采样滑出 1m 窗口 → resolved
+        engine.record(at(100), &http_families(1000, 80));
+        engine.record(at(110), &http_families(1100, 80));
+        let transitions = engine.evaluate(at(110));
+        assert_eq!(transitions[0].to, AlertState::Resolved);
+        assert_eq!(
+            engine.status("high_http_error_rate").unwrap().state,
+            AlertState::Resolved
+        );
+    }
+
+    #[test]
+    fn test_replica_snapshots_are_merged_per_instance() {
+        let mut engine = engine(ERROR_RATE_RULE);
+        let replicas = |a: (u64, u64), b: (u64, u64)| {
+            let snapshots = [
+                engine_snapshot("replica-a", &http_families(a.0, a.1)),
+                engine_snapshot("replica-b", &http_families(b.0, b.1)),
+            ];
+            // 经 JSON 往返，与 Redis 中的格式一致
+            snapshots
+                .iter()
+                .map(|s| serde_json::from_str(&serde_json::to_string(s).unwrap()).unwrap())
+                .collect::<Vec<ReplicaSnapshot>>()
+        };
+
+        engine.record_replicas(at(0), &replicas((100, 0), (100, 0)));
+        // 只有 replica-b 出错：40 / (90 + 90 + 40)
+        engine.record_replicas(at(10), &replicas((190, 0), (190, 40)));
+        let transitions = engine.evaluate(at(10));
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to, AlertState::Pending);
+        assert!((transitions[0].value - 40.0 / 220.0).abs() < 1e-9);
+
+        // replica-a 重启（计数器归零）不影响 replica-b 的序列
+        engine.record_replicas(at(20), &replicas((5, 0), (290, 80)));
+        assert!(engine.evaluate(at(20)).is_empty());
+        // 窗口增量：a = 90 + 5，b = 190 + 80
+        let value = engine
+            .status("high_http_error_rate")
+            .unwrap()
+            .value
+            .unwrap();
+        assert!((value - 80.0 / 365.0).abs() < 1e-9);
+    }
+
+    fn engine_snapshot(instance: &str, families: &[MetricFamily]) -> ReplicaSnapshot {
+        engine(ERROR_RATE_RULE).replica_snapshot(instance, families)
+    }
+
+    #[test]
+    fn test_restored_firing_state_held_until_window_has_data() {
+        let mut engine = engine(ERROR_RATE_RULE);
+        let alert_id = Uuid::new_v4();
+        engine.restore(
+            "high_http_error_rate",
+            RuleStatus {
+                state: AlertState::Firing,
+                since: Some(at(-300)),
+                value: Some(0.5),
+                alert_id: Some(alert_id),
+            },
+        );
+        engine.restore("removed_rule", RuleStatus::default());
+        assert!(engine.status("removed_rule").is_none());
+
+        // 重启后首次采样：数据不足，不误判恢复
+        engine.record(at(0), &http_families(10, 0));
+        assert!(engine.evaluate(at(0)).is_empty());
+
+        engine.record(at(10), &http_families(20, 0));
+        let transitions = engine.evaluate(at(10));
+        assert_eq!(transitions[0].to, AlertState::Resolved);
+        assert_eq!(transitions[0].previous.alert_id, Some(alert_id));
+    }
+
+    #[test]
+    fn test_increase_handles_counter_reset_and_new_series() {
+        let mut engine = engine(
+            r#"
+            [[rules]]
+            name = "rpc_consensus_disagreement"
+            metric = "ironcore_rpc_consensus_disagreement_total"
+            function = "increase"
+            window = "10m"
+            op = ">"
+            threshold = 0
+            severity = "critical"
+        "#,
+        );
+        let families = |value: Option<u64>| {
+            let registry = Registry::new();
+            if let Some(value) = value {
+                let counter =
+                    IntCounter::new("ironcore_rpc_consensus_disagreement_total", "disagreements")
+                        .unwrap();
+                registry.register(Box::new(counter.clone())).unwrap();
+                counter.inc_by(value);
+            }
+            registry.gather()
+        };
+
+        engine.record(at(0), &families(None));
+        engine.record(at(30), &families(None));
+        assert!(engine.evaluate(at(30)).is_empty());
+        assert_eq!(
+            engine.status("rpc_consensus_disagreement").unwrap().value,
+            Some(0.0)
+        );
+
+        // 序列首次出现即为 2，以 0 为基线立即触发（for = 0）
+        engine.record(at(60), &families(Some(2)));
+        let transitions = engine.evaluate(at(60));
+        assert_eq!(transitions[0].to, AlertState::Firing);
+        assert_eq!(transitions[0].value, 2.0);
+
+        // 进程重启导致计数器归零后再 +1：累计增量 2 + 1
+        engine.record(at(90), &families(Some(1)));
+        engine.evaluate(at(90));
+        assert_eq!(
+            engine.status("rpc_consensus_disagreement").unwrap().value,
+            Some(3.0)
+        );
+    }
+
+    #[test]
+    fn test_histogram_quantile_and_gauge_ratio() {
+        let mut engine = engine(
+            r#"
+            [[rules]]
+            name = "high_http_latency_p99"
+            metric = "ironcore_http_request_duration_seconds"
+            labels = { route = "/slow" }
+            function = "quantile"
+            quantile = 0.5
+            window = "5m"
+            op = ">"
+            threshold = 1.0
+            severity = "medium"
+
+            [[rules]]
+            name = "db_pool_saturated"
+            metric = "ironcore_db_pool_connections"
+            labels = { state = "active" }
+            denominator = { metric = "ironcore_db_pool_max_connections" }
+            op = ">="
+            threshold = 0.9
+            severity = "high"
+        "#,
+        );
+
+        let registry = Registry::new();
+        let duration = HistogramVec::new(
+            HistogramOpts::new("ironcore_http_request_duration_seconds", "duration")
+                .buckets(vec![0.5, 1.0, 2.0, 4.0]),
+            &["route"],
+        )
+        .unwrap();
+        let pool = prometheus::IntGaugeVec::new(
+            Opts::new("ironcore_db_pool_connections", "pool"),
+            &["state"],
+        )
+        .unwrap();
+        let max = IntGauge::new("ironcore_db_pool_max_connections", "max").unwrap();
+        registry.register(Box::new(duration.clone())).unwrap();
+        registry.register(Box::new(pool.clone())).unwrap();
+        registry.register(Box::new(max.clone())).unwrap();
+        max.set(10);
+        pool.with_label_values(&["active"]).set(9);
+        pool.with_label_values(&["idle"]).set(1);
+
+        // 窗口前的快速请求不计入
+        for _ in 0..100 {
+            duration.with_label_values(&["/slow"]).observe(0.1);
+        }
+        engine.record(at(0), &registry.gather());
+        for _ in 0..4 {
+            duration.with_label_values(&["/slow"]).observe(3.0);
+        }
+        duration.with_label_values(&["/fast"]).observe(0.1);
+        engine.record(at(30), &registry.gather());
+
+        let transitions = engine.evaluate(at(30));
+        let firing: HashMap<_, _> = transitions.iter().map(|t| (t.rule.as_str(), t)).collect();
+        // 窗口内 4 个观测均在 (2, 4] 桶：中位数插值为 3
+        assert!((firing["high_http_latency_p99"].value - 3.0).abs() < 1e-9);
+        assert!((firing["db_pool_saturated"].value - 0.9).abs() < 1e-9);
+        assert!(firing.values().all(|t| t.to == AlertState::Firing));
+    }
+}
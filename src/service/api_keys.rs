@@ -1,3 +1,10 @@
+//! API Key 服务
+//!
+//! 企业级实现：服务端生成 Key，仅存储 SHA-256 哈希；scope 控制可访问的路由
+
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::{
@@ -5,21 +12,139 @@ use crate::{
     repository::api_keys::{self, ApiKey, CreateApiKeyInput},
 };
 
+/// 原始 Key 前缀（便于日志/密钥扫描识别）
+pub const API_KEY_PREFIX: &str = "ik_";
+
+/// 内置 scope
+pub mod scopes {
+    pub const WALLETS_READ: &str = "wallets:read";
+    pub const TX_READ: &str = "tx:read";
+    pub const TX_BROADCAST: &str = "tx:broadcast";
+    pub const ADMIN_ALL: &str = "admin:*";
+}
+
+/// API Key 认证失败原因
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyAuthError {
+    #[error("Invalid API key")]
+    Invalid,
+    #[error("API key is not active")]
+    Inactive,
+    #[error("API key has expired")]
+    Expired,
+    #[error("API key has no owner")]
+    NoOwner,
+    #[error("Failed to verify API key: {0}")]
+    Storage(String),
+}
+
+/// 计算原始 Key 的哈希（SHA-256 hex）
+pub fn hash_api_key(raw_key: &str) -> String {
+    faster_hex::hex_string(&Sha256::digest(raw_key.as_bytes()))
+}
+
+/// 生成原始 Key：`ik_` + 32 字节随机数 hex
+pub fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, hex::encode(bytes))
+}
+
+/// 校验 scope 格式：`resource:action`，action 可为 `*`
+pub fn validate_scopes(scopes: &[String]) -> Result<(), anyhow::Error> {
+    if scopes.is_empty() {
+        anyhow::bail!("At least one scope is required");
+    }
+    for scope in scopes {
+        let valid = match scope.split_once(':') {
+            Some((resource, action)) => {
+                let ident = |s: &str| {
+                    !s.is_empty()
+                        && s.chars()
+                            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+                };
+                ident(resource) && (action == "*" || ident(action))
+            }
+            None => false,
+        };
+        if !valid {
+            anyhow::bail!("Invalid scope: {}", scope);
+        }
+    }
+    Ok(())
+}
+
+/// 判断已授予的 scope 是否覆盖路由声明的 scope（`resource:*` 覆盖该资源全部操作）
+pub fn scope_grants(granted: &[String], required: &str) -> bool {
+    let required_resource = required.split_once(':').map(|(r, _)| r);
+    granted.iter().any(|g| {
+        g == required
+            || matches!(
+                (g.split_once(':'), required_resource),
+                (Some((resource, "*")), Some(req)) if resource == req
+            )
+    })
+}
+
+/// 检查 Key 是否可用（状态、过期、归属）
+pub fn check_api_key_usable(key: &ApiKey, now: DateTime<Utc>) -> Result<Uuid, ApiKeyAuthError> {
+    if key.status != "active" {
+        return Err(ApiKeyAuthError::Inactive);
+    }
+    if key.expires_at.is_some_and(|exp| exp <= now) {
+        return Err(ApiKeyAuthError::Expired);
+    }
+    key.user_id.ok_or(ApiKeyAuthError::NoOwner)
+}
+
+/// 验证原始 Key，返回 Key 记录与归属用户
+pub async fn verify_api_key(
+    pool: &PgPool,
+    raw_key: &str,
+) -> Result<(ApiKey, Uuid), ApiKeyAuthError> {
+    let key = api_keys::get_by_hash(pool, &hash_api_key(raw_key))
+        .await
+        .map_err(|e| ApiKeyAuthError::Storage(e.to_string()))?
+        .ok_or(ApiKeyAuthError::Invalid)?;
+    let user_id = check_api_key_usable(&key, Utc::now())?;
+    Ok((key, user_id))
+}
+
+/// 创建 API Key，返回记录与原始 Key（原始 Key 仅此一次可见）
 pub async fn create_api_key(
     pool: &PgPool,
     tenant_id: Uuid,
+    user_id: Uuid,
     name: String,
-    key_hash: String,
-    status: String,
-) -> Result<ApiKey, anyhow::Error> {
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+    rate_limit_per_minute: Option<i64>,
+) -> Result<(ApiKey, String), anyhow::Error> {
+    validate_scopes(&scopes)?;
+    if rate_limit_per_minute.is_some_and(|limit| limit <= 0) {
+        anyhow::bail!("rate_limit_per_minute must be > 0");
+    }
+
+    let raw_key = generate_api_key();
     let input = CreateApiKeyInput {
         tenant_id,
         name,
-        key_hash,
-        status,
+        key_hash: hash_api_key(&raw_key),
+        status: "active".to_string(),
+        user_id: Some(user_id),
+        key_prefix: Some(raw_key[..API_KEY_PREFIX.len() + 8].to_string()),
+        scopes,
+        expires_at,
+        rate_limit_per_minute,
     };
     let k = api_keys::create(pool, input).await?;
-    Ok(k)
+    Ok((k, raw_key))
+}
+
+/// 记录最近使用时间
+pub async fn touch_api_key_last_used(pool: &PgPool, id: Uuid) -> Result<(), anyhow::Error> {
+    api_keys::touch_last_used(pool, id).await?;
+    Ok(())
 }
 
 pub async fn get_api_key_by_id(pool: &PgPool, id: Uuid) -> Result<Option<ApiKey>, anyhow::Error> {
@@ -73,3 +198,92 @@ pub async fn count_api_keys_by_tenant(
     let count = api_keys::count_by_tenant(pool, tenant_id, status).await?;
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(status: &str, user_id: Option<Uuid>, expires_at: Option<DateTime<Utc>>) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "server".to_string(),
+            key_hash: hash_api_key("ik_test"),
+            status: status.to_string(),
+            created_at: Utc::now(),
+            user_id,
+            key_prefix: None,
+            scopes: vec![scopes::WALLETS_READ.to_string()],
+            expires_at,
+            rate_limit_per_minute: None,
+            last_used_at: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_and_hash() {
+        let raw = generate_api_key();
+        assert!(raw.starts_with(API_KEY_PREFIX));
+        assert_eq!(raw.len(), API_KEY_PREFIX.len() + 64);
+        assert_ne!(raw, generate_api_key());
+
+        // SHA-256("abc")
+        assert_eq!(
+            hash_api_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_validate_scopes() {
+        assert!(validate_scopes(&["wallets:read".into(), "admin:*".into()]).is_ok());
+        assert!(validate_scopes(&[]).is_err());
+        assert!(validate_scopes(&["wallets".into()]).is_err());
+        assert!(validate_scopes(&["*:read".into()]).is_err());
+        assert!(validate_scopes(&["Wallets:read".into()]).is_err());
+    }
+
+    #[test]
+    fn test_scope_grants() {
+        let granted = vec!["wallets:read".to_string(), "admin:*".to_string()];
+        assert!(scope_grants(&granted, scopes::WALLETS_READ));
+        assert!(scope_grants(&granted, scopes::ADMIN_ALL));
+        assert!(scope_grants(&granted, "admin:fee_rules"));
+        assert!(!scope_grants(&granted, scopes::TX_BROADCAST));
+
+        // 具体操作不覆盖通配
+        let narrow = vec!["admin:fee_rules".to_string()];
+        assert!(!scope_grants(&narrow, scopes::ADMIN_ALL));
+        assert!(!scope_grants(&[], scopes::TX_READ));
+    }
+
+    #[test]
+    fn test_check_api_key_usable() {
+        let owner = Uuid::new_v4();
+        let now = Utc::now();
+
+        assert_eq!(
+            check_api_key_usable(&key("active", Some(owner), None), now).unwrap(),
+            owner
+        );
+        assert!(matches!(
+            check_api_key_usable(&key("revoked", Some(owner), None), now),
+            Err(ApiKeyAuthError::Inactive)
+        ));
+        assert!(matches!(
+            check_api_key_usable(
+                &key(
+                    "active",
+                    Some(owner),
+                    Some(now - chrono::Duration::seconds(1))
+                ),
+                now
+            ),
+            Err(ApiKeyAuthError::Expired)
+        ));
+        assert!(matches!(
+            check_api_key_usable(&key("active", None, None), now),
+            Err(ApiKeyAuthError::NoOwner)
+        ));
+    }
+}
@@ -1,10 +1,409 @@
+//! 审批服务
+//! 企业级实现：M-of-N 多方审批
+//!
+//! - 风控要求人工审核的操作被挂起（如提现 `pending_review`），同时创建 pending 审批
+//! - 审批人按 RBAC 角色逐一投票，申请人不能为自己的操作投票，每人一票
+//! - 同意票达到 M 即通过并恢复操作；任一拒绝票即否决；超时自动过期并拒绝操作
+//! - 每一票与最终结果都写入 audit_logs（与投票在同一事务），终态同时写入 outbox 事件 `ApprovalDecided`
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::{
-    infrastructure::db::PgPool,
-    repository::approvals::{self, Approval, CreateApprovalInput},
+    error::AppError,
+    infrastructure::{db::PgPool, event_bus::DomainEvent, event_outbox::enqueue_event},
+    repository::approvals::{
+        self, Approval, ApprovalVote, CreateApprovalInput, ParkOperationInput,
+    },
+    service::risk_rule_engine::ApprovalSpec,
 };
 
+/// 可被审批挂起的操作类型
+pub mod resource_types {
+    pub const WITHDRAWAL: &str = "withdrawal";
+}
+
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+const EXPIRY_BATCH: i64 = 100;
+
+/// 审批流程错误
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Approval not found")]
+    NotFound,
+    #[error("Approval is already {0}")]
+    AlreadyDecided(String),
+    #[error("Approval has expired")]
+    Expired,
+    #[error("Requester cannot vote on their own operation")]
+    SelfApproval,
+    #[error("Role {0} is not allowed to vote on this approval")]
+    RoleNotAllowed(String),
+    #[error("Already voted on this approval")]
+    DuplicateVote,
+    #[error("Invalid vote decision: {0}")]
+    InvalidDecision(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Failed to enqueue approval event: {0:#}")]
+    Event(anyhow::Error),
+}
+
+impl From<ApprovalError> for AppError {
+    fn from(e: ApprovalError) -> Self {
+        match e {
+            ApprovalError::NotFound => AppError::not_found(e.to_string()),
+            ApprovalError::AlreadyDecided(_)
+            | ApprovalError::Expired
+            | ApprovalError::DuplicateVote => AppError::conflict(e.to_string()),
+            ApprovalError::SelfApproval | ApprovalError::RoleNotAllowed(_) => {
+                AppError::forbidden(e.to_string())
+            }
+            ApprovalError::InvalidDecision(_) => AppError::bad_request(e.to_string()),
+            ApprovalError::Database(e) => AppError::from(e),
+            ApprovalError::Event(e) => AppError::internal(format!("{:#}", e)),
+        }
+    }
+}
+
+/// 投票决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteDecision {
+    Approve,
+    Reject,
+}
+
+impl VoteDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoteDecision::Approve => "approve",
+            VoteDecision::Reject => "reject",
+        }
+    }
+
+    /// 兼容旧接口的状态写法（approved / rejected）
+    pub fn parse(s: &str) -> Result<Self, ApprovalError> {
+        match s.to_ascii_lowercase().as_str() {
+            "approve" | "approved" => Ok(VoteDecision::Approve),
+            "reject" | "rejected" => Ok(VoteDecision::Reject),
+            _ => Err(ApprovalError::InvalidDecision(s.to_string())),
+        }
+    }
+}
+
+/// 投票人（来自认证上下文）
+#[derive(Debug, Clone)]
+pub struct Voter {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub role: String,
+}
+
+/// 待挂起的操作
+#[derive(Debug)]
+pub struct ParkedOperation {
+    pub tenant_id: Uuid,
+    pub requester: Uuid,
+    pub resource_type: &'static str,
+    pub resource_id: Uuid,
+    pub policy_id: Option<Uuid>,
+    pub payload: serde_json::Value,
+}
+
+/// 根据票数决定审批结果：任一拒绝即否决，同意票达到 M 即通过，否则继续等待
+pub fn tally_status(approvals: i64, rejections: i64, required_approvals: i32) -> &'static str {
+    if rejections > 0 {
+        "rejected"
+    } else if approvals >= i64::from(required_approvals) {
+        "approved"
+    } else {
+        "pending"
+    }
+}
+
+/// 检查投票资格（不含重复投票，由唯一约束保证）
+pub fn check_eligibility(approval: &Approval, voter: &Voter) -> Result<(), ApprovalError> {
+    if approval.tenant_id != voter.tenant_id {
+        return Err(ApprovalError::NotFound);
+    }
+    if approval.status != "pending" {
+        return Err(ApprovalError::AlreadyDecided(approval.status.clone()));
+    }
+    if approval.requester == voter.user_id {
+        return Err(ApprovalError::SelfApproval);
+    }
+    if !approval.approver_roles.iter().any(|r| r == &voter.role) {
+        return Err(ApprovalError::RoleNotAllowed(voter.role.clone()));
+    }
+    Ok(())
+}
+
+/// 挂起操作并创建审批（调用方在创建操作的同一事务内调用）
+pub async fn park_operation(
+    conn: &mut PgConnection,
+    op: ParkedOperation,
+    spec: &ApprovalSpec,
+) -> Result<Approval, ApprovalError> {
+    let approval = approvals::create_pending(
+        &mut *conn,
+        ParkOperationInput {
+            tenant_id: op.tenant_id,
+            policy_id: op.policy_id,
+            requester: op.requester,
+            resource_type: op.resource_type.to_string(),
+            resource_id: op.resource_id,
+            payload: op.payload,
+            required_approvals: spec.required_approvals as i32,
+            approver_roles: spec.approver_roles.clone(),
+            expires_at: Utc::now() + chrono::Duration::hours(i64::from(spec.ttl_hours)),
+        },
+    )
+    .await?;
+
+    record_audit(
+        &mut *conn,
+        "APPROVAL_REQUESTED",
+        &approval,
+        Some(op.requester),
+        serde_json::json!({
+            "resource_type": approval.resource_type,
+            "resource_id": approval.resource_id,
+            "required_approvals": approval.required_approvals,
+            "approver_roles": approval.approver_roles,
+            "expires_at": approval.expires_at,
+        }),
+    )
+    .await?;
+
+    Ok(approval)
+}
+
+/// 投票；达到结果时在同一事务内恢复或拒绝被挂起的操作
+pub async fn cast_vote(
+    pool: &PgPool,
+    approval_id: Uuid,
+    voter: &Voter,
+    decision: VoteDecision,
+    comment: Option<String>,
+) -> Result<(Approval, ApprovalVote), ApprovalError> {
+    let mut tx = pool.begin().await?;
+
+    let approval = approvals::get_for_update(&mut tx, approval_id)
+        .await?
+        .ok_or(ApprovalError::NotFound)?;
+    check_eligibility(&approval, voter)?;
+
+    if approval.expires_at.is_some_and(|at| at <= Utc::now()) {
+        finalize(&mut tx, &approval, "expired", None).await?;
+        tx.commit().await?;
+        return Err(ApprovalError::Expired);
+    }
+
+    let vote = approvals::insert_vote(
+        &mut tx,
+        approval.id,
+        voter.user_id,
+        &voter.role,
+        decision.as_str(),
+        comment.as_deref(),
+    )
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => {
+            ApprovalError::DuplicateVote
+        }
+        _ => ApprovalError::Database(e),
+    })?;
+
+    record_audit(
+        &mut tx,
+        "APPROVAL_VOTE",
+        &approval,
+        Some(voter.user_id),
+        serde_json::json!({
+            "vote_id": vote.id,
+            "decision": vote.decision,
+            "voter_role": vote.voter_role,
+            "comment": vote.comment,
+        }),
+    )
+    .await?;
+
+    let (approves, rejects) = approvals::tally_votes(&mut tx, approval.id).await?;
+    let status = tally_status(approves, rejects, approval.required_approvals);
+    let approval = if status == "pending" {
+        approval
+    } else {
+        finalize(&mut tx, &approval, status, Some(voter.user_id)).await?
+    };
+
+    tx.commit().await?;
+
+    tracing::info!(
+        approval_id = %approval.id,
+        voter = %voter.user_id,
+        decision = decision.as_str(),
+        approves,
+        rejects,
+        status = %approval.status,
+        "Approval vote recorded"
+    );
+
+    Ok((approval, vote))
+}
+
+/// 过期处理：pending 且已超时的审批置为 expired 并拒绝挂起的操作
+pub async fn expire_due_approvals(pool: &PgPool) -> Result<usize, ApprovalError> {
+    let ids = approvals::list_expired_pending(pool, EXPIRY_BATCH).await?;
+    let mut expired = 0;
+
+    for id in ids {
+        let mut tx = pool.begin().await?;
+        let Some(approval) = approvals::get_for_update(&mut tx, id).await? else {
+            continue;
+        };
+        // 加锁后复核（可能已被并发投票决出结果）
+        if approval.status != "pending" || approval.expires_at.is_none_or(|at| at > Utc::now()) {
+            continue;
+        }
+        finalize(&mut tx, &approval, "expired", None).await?;
+        tx.commit().await?;
+        expired += 1;
+    }
+
+    Ok(expired)
+}
+
+/// 写入终态，恢复/拒绝被挂起的操作，并记审计
+async fn finalize(
+    conn: &mut PgConnection,
+    approval: &Approval,
+    status: &str,
+    decided_by: Option<Uuid>,
+) -> Result<Approval, ApprovalError> {
+    let decided = approvals::mark_decided(&mut *conn, approval.id, status).await?;
+
+    let resumed = match (decided.resource_type.as_deref(), decided.resource_id) {
+        (Some(resource_types::WITHDRAWAL), Some(withdrawal_id)) => {
+            let next_status = if status == "approved" {
+                "approved"
+            } else {
+                "rejected"
+            };
+            sqlx::query(
+                "UPDATE withdrawal_requests
+                 SET status = $2, reviewed_by = $3, reviewed_at = NOW(), updated_at = NOW()
+                 WHERE id = $1 AND status = 'pending_review'",
+            )
+            .bind(withdrawal_id)
+            .bind(next_status)
+            .bind(decided_by)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected()
+                > 0
+        }
+        (Some(other), _) => {
+            tracing::warn!(
+                approval_id = %decided.id,
+                resource_type = other,
+                "No resume handler for approval resource type"
+            );
+            false
+        }
+        (None, _) => false,
+    };
+
+    let event_type = match status {
+        "approved" => "APPROVAL_APPROVED",
+        "expired" => "APPROVAL_EXPIRED",
+        _ => "APPROVAL_REJECTED",
+    };
+    record_audit(
+        &mut *conn,
+        event_type,
+        &decided,
+        decided_by,
+        serde_json::json!({
+            "resource_type": decided.resource_type,
+            "resource_id": decided.resource_id,
+            "resumed": resumed,
+        }),
+    )
+    .await?;
+
+    // 与终态同一事务写入 outbox（租户 Webhook 等订阅方）
+    enqueue_event(
+        &mut *conn,
+        &DomainEvent::ApprovalDecided {
+            approval_id: decided.id,
+            tenant_id: decided.tenant_id,
+            status: decided.status.clone(),
+            resource_type: decided.resource_type.clone(),
+            resource_id: decided.resource_id,
+            decided_by,
+        },
+    )
+    .await
+    .map_err(ApprovalError::Event)?;
+
+    Ok(decided)
+}
+
+async fn record_audit(
+    conn: &mut PgConnection,
+    event_type: &str,
+    approval: &Approval,
+    user_id: Option<Uuid>,
+    metadata: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_logs (event_type, resource_type, resource_id, user_id, tenant_id, metadata, created_at)
+         VALUES ($1, 'approval', $2, $3, $4, $5, CURRENT_TIMESTAMP)",
+    )
+    .bind(event_type)
+    .bind(approval.id)
+    .bind(user_id)
+    .bind(approval.tenant_id)
+    .bind(metadata)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 后台过期处理
+pub struct ApprovalExpiryWorker {
+    pool: PgPool,
+    interval: Duration,
+}
+
+impl ApprovalExpiryWorker {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            interval: EXPIRY_INTERVAL,
+        }
+    }
+
+    pub async fn start_background_expiry(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
+            match expire_due_approvals(&self.pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(expired = n, "Expired pending approvals"),
+                Err(e) => tracing::error!(error = ?e, "Failed to expire approvals"),
+            }
+        }
+    }
+}
+
 pub async fn create_approval(
     pool: &PgPool,
     tenant_id: Uuid,
@@ -15,7 +414,7 @@ pub async fn create_approval(
 ) -> Result<Approval, anyhow::Error> {
     let input = CreateApprovalInput {
         tenant_id,
-        policy_id,
+        policy_id: Some(policy_id),
         requester,
         status,
         payload,
@@ -43,14 +442,12 @@ pub async fn list_approvals_by_tenant(
     Ok(a)
 }
 
-pub async fn update_approval_status(
+pub async fn list_approval_votes(
     pool: &PgPool,
-    id: Uuid,
-    tenant_id: Uuid,
-    status: String,
-) -> Result<Option<Approval>, anyhow::Error> {
-    let a = approvals::update_status(pool, id, tenant_id, status).await?;
-    Ok(a)
+    approval_id: Uuid,
+) -> Result<Vec<ApprovalVote>, anyhow::Error> {
+    let votes = approvals::list_votes(pool, approval_id).await?;
+    Ok(votes)
 }
 
 pub async fn delete_approval(
@@ -70,3 +467,82 @@ pub async fn count_approvals_by_tenant(
     let count = approvals::count_by_tenant(pool, tenant_id, status).await?;
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending_approval(requester: Uuid, tenant_id: Uuid) -> Approval {
+        Approval {
+            id: Uuid::new_v4(),
+            tenant_id,
+            policy_id: None,
+            requester,
+            status: "pending".into(),
+            payload: serde_json::json!({}),
+            resource_type: Some(resource_types::WITHDRAWAL.into()),
+            resource_id: Some(Uuid::new_v4()),
+            required_approvals: 2,
+            approver_roles: vec!["admin".into(), "operator".into()],
+            expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
+            decided_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_m_of_n_tally() {
+        assert_eq!(tally_status(0, 0, 2), "pending");
+        assert_eq!(tally_status(1, 0, 2), "pending");
+        assert_eq!(tally_status(2, 0, 2), "approved");
+        // 任一拒绝即否决
+        assert_eq!(tally_status(3, 1, 2), "rejected");
+    }
+
+    #[test]
+    fn test_vote_eligibility() {
+        let tenant_id = Uuid::new_v4();
+        let requester = Uuid::new_v4();
+        let approval = pending_approval(requester, tenant_id);
+        let voter = |user_id, role: &str| Voter {
+            user_id,
+            tenant_id,
+            role: role.into(),
+        };
+
+        assert!(check_eligibility(&approval, &voter(Uuid::new_v4(), "operator")).is_ok());
+        assert!(matches!(
+            check_eligibility(&approval, &voter(requester, "admin")),
+            Err(ApprovalError::SelfApproval)
+        ));
+        assert!(matches!(
+            check_eligibility(&approval, &voter(Uuid::new_v4(), "viewer")),
+            Err(ApprovalError::RoleNotAllowed(_))
+        ));
+
+        let other_tenant = Voter {
+            user_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            role: "admin".into(),
+        };
+        assert!(matches!(
+            check_eligibility(&approval, &other_tenant),
+            Err(ApprovalError::NotFound)
+        ));
+
+        let decided = Approval {
+            status: "approved".into(),
+            ..pending_approval(requester, tenant_id)
+        };
+        assert!(matches!(
+            check_eligibility(&decided, &voter(Uuid::new_v4(), "admin")),
+            Err(ApprovalError::AlreadyDecided(_))
+        ));
+
+        assert_eq!(
+            VoteDecision::parse("approved").unwrap(),
+            VoteDecision::Approve
+        );
+        assert!(VoteDecision::parse("pending").is_err());
+    }
+}
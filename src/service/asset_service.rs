@@ -7,6 +7,8 @@ use uuid::Uuid;
 
 use super::price_service::PriceService;
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 /// 资产响应（统一 USDT 展示）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetResponse {
@@ -313,6 +315,7 @@ impl AssetService {
             .post(rpc_url)
             .json(&request_body)
             .timeout(std::time::Duration::from_secs(10))
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send Solana RPC request")?;
@@ -362,6 +365,7 @@ impl AssetService {
         let response = client
             .get(&url)
             .timeout(std::time::Duration::from_secs(10))
+            .with_trace_context()
             .send()
             .await
             .context("Failed to query Bitcoin balance")?;
@@ -0,0 +1,931 @@
+//! 防篡改审计账本
+//!
+//! 企业级实现：审计事件先写入本地只追加账本，不依赖 immudb 可用性
+//! - 哈希链：每条记录的 entry_hash 承诺前一条记录的哈希，修改 / 删除任一条都会使后续校验失败
+//! - Merkle 检查点：后台任务定期对新增记录计算 Merkle 根，immudb 可用时锚定（外部不可篡改证明）
+//! - 区间校验：重算哈希链并报告第一条被篡改的记录；已锚定的检查点再与 immudb 中的根比对
+//!
+//! 追加以链头行 `FOR UPDATE` 串行化（CockroachDB 兼容，无咨询锁），序号冲突 / 串行化失败自动重试
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::infrastructure::audit::{AuditEvent, ImmuCtx};
+
+/// 创世记录的 prev_hash
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const MAX_APPEND_RETRIES: u32 = 5;
+const VERIFY_PAGE_SIZE: i64 = 1000;
+/// 单个检查点最多覆盖的记录数
+const MAX_CHECKPOINT_ENTRIES: i64 = 10_000;
+const ANCHOR_BATCH: i64 = 10;
+const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(600);
+
+/// 账本记录
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LedgerEntry {
+    pub seq: i64,
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub event: String,
+    pub actor: String,
+    pub resource: String,
+    pub payload: serde_json::Value,
+    pub payload_hash: String,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 待追加的记录
+#[derive(Debug, Clone)]
+pub struct NewLedgerEntry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub event: String,
+    pub actor: String,
+    pub resource: String,
+    pub payload: serde_json::Value,
+}
+
+/// Merkle 检查点
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LedgerCheckpoint {
+    pub id: Uuid,
+    pub from_seq: i64,
+    pub to_seq: i64,
+    pub entry_count: i64,
+    pub merkle_root: String,
+    pub head_hash: String,
+    pub anchored: bool,
+    pub anchor_proof: Option<String>,
+    pub anchored_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 篡改类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperReason {
+    /// 序号缺失（记录被删除）
+    Missing,
+    /// payload 与 payload_hash 不一致（内容被修改）
+    PayloadHashMismatch,
+    /// prev_hash 与前一条记录不一致（链被截断或重排）
+    PrevHashMismatch,
+    /// 重算的 entry_hash 与存储值不一致
+    EntryHashMismatch,
+}
+
+/// 第一条被篡改的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TamperedEntry {
+    pub seq: i64,
+    pub id: Option<Uuid>,
+    pub reason: TamperReason,
+}
+
+/// 检查点不一致类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointMismatchReason {
+    /// 数据库中的 merkle_root 与重算结果不一致
+    RootMismatch,
+    /// immudb 锚定的根与重算结果不一致（记录与检查点被同步改写）
+    AnchorMismatch,
+    /// 检查点标记为已锚定，但 immudb 中没有对应记录
+    AnchorMissing,
+}
+
+/// 检查点根哈希与重算结果不一致
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointMismatch {
+    pub checkpoint_id: Uuid,
+    pub from_seq: i64,
+    pub to_seq: i64,
+    pub reason: CheckpointMismatchReason,
+    pub expected_root: String,
+    pub actual_root: String,
+    /// immudb 中锚定的根（仅 AnchorMismatch）
+    pub anchored_root: Option<String>,
+}
+
+/// 已锚定检查点无法与 immudb 比对（immudb 不可达或响应异常）
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorUnreachable {
+    pub checkpoint_id: Uuid,
+    pub error: String,
+}
+
+/// 区间校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerVerification {
+    pub from_seq: i64,
+    pub to_seq: i64,
+    /// 当前链头序号（空账本为 0）
+    pub head_seq: i64,
+    pub entries_checked: i64,
+    pub valid: bool,
+    pub first_tampered: Option<TamperedEntry>,
+    pub checkpoints_checked: i64,
+    pub checkpoint_mismatch: Option<CheckpointMismatch>,
+    /// 已与 immudb 比对的锚定检查点数
+    pub anchors_checked: i64,
+    /// 锚点无法确认时区间不视为有效
+    pub anchor_unreachable: Option<AnchorUnreachable>,
+}
+
+// ============ 哈希计算 ============
+
+/// 规范化 JSON：对象键按字典序递归排序，保证 JSONB 往返后哈希不变
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    fn write(value: &serde_json::Value, out: &mut String) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                out.push('{');
+                for (i, key) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                    out.push(':');
+                    write(&map[key], out);
+                }
+                out.push('}');
+            }
+            serde_json::Value::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write(item, out);
+                }
+                out.push(']');
+            }
+            other => out.push_str(&other.to_string()),
+        }
+    }
+
+    let mut out = String::new();
+    write(value, &mut out);
+    out
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    faster_hex::hex_string(&Sha256::digest(data))
+}
+
+pub fn payload_hash(payload: &serde_json::Value) -> String {
+    sha256_hex(canonical_json(payload).as_bytes())
+}
+
+/// entry_hash = SHA-256(规范化 {seq, id, tenant_id, event, actor, resource, payload_hash, created_at, prev_hash})
+#[allow(clippy::too_many_arguments)]
+pub fn compute_entry_hash(
+    seq: i64,
+    id: Uuid,
+    tenant_id: Uuid,
+    event: &str,
+    actor: &str,
+    resource: &str,
+    payload_hash: &str,
+    created_at: DateTime<Utc>,
+    prev_hash: &str,
+) -> String {
+    let record = serde_json::json!({
+        "seq": seq,
+        "id": id,
+        "tenant_id": tenant_id,
+        "event": event,
+        "actor": actor,
+        "resource": resource,
+        "payload_hash": payload_hash,
+        // 数据库保存到微秒，按微秒格式化保证往返一致
+        "created_at": created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        "prev_hash": prev_hash,
+    });
+    sha256_hex(canonical_json(&record).as_bytes())
+}
+
+impl LedgerEntry {
+    fn recompute_hash(&self) -> String {
+        compute_entry_hash(
+            self.seq,
+            self.id,
+            self.tenant_id,
+            &self.event,
+            &self.actor,
+            &self.resource,
+            &self.payload_hash,
+            self.created_at,
+            &self.prev_hash,
+        )
+    }
+}
+
+/// Merkle 根：叶子 SHA-256(0x00 || entry_hash)，内部节点 SHA-256(0x01 || left || right)，奇数层复制末尾节点
+pub fn merkle_root(entry_hashes: &[String]) -> Result<String> {
+    if entry_hashes.is_empty() {
+        anyhow::bail!("Cannot compute Merkle root of an empty range");
+    }
+
+    let mut level = entry_hashes
+        .iter()
+        .map(|h| {
+            let bytes = hex::decode(h).with_context(|| format!("Invalid entry hash: {}", h))?;
+            let mut hasher = Sha256::new();
+            hasher.update([0x00]);
+            hasher.update(&bytes);
+            Ok(hasher.finalize().to_vec())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let right = pair.get(1).unwrap_or(&pair[0]);
+                let mut hasher = Sha256::new();
+                hasher.update([0x01]);
+                hasher.update(&pair[0]);
+                hasher.update(right);
+                hasher.finalize().to_vec()
+            })
+            .collect();
+    }
+
+    Ok(faster_hex::hex_string(&level[0]))
+}
+
+/// 校验一段连续记录：从 expected_seq 开始、前驱哈希为 prev_hash
+///
+/// 成功返回最后一条记录的 entry_hash，失败返回第一条被篡改的记录
+pub fn verify_chain(
+    prev_hash: &str,
+    expected_seq: i64,
+    entries: &[LedgerEntry],
+) -> std::result::Result<String, TamperedEntry> {
+    let mut prev_hash = prev_hash.to_string();
+    let mut expected_seq = expected_seq;
+
+    for entry in entries {
+        if entry.seq != expected_seq {
+            return Err(TamperedEntry {
+                seq: expected_seq,
+                id: None,
+                reason: TamperReason::Missing,
+            });
+        }
+
+        let tampered = |reason| TamperedEntry {
+            seq: entry.seq,
+            id: Some(entry.id),
+            reason,
+        };
+        if payload_hash(&entry.payload) != entry.payload_hash {
+            return Err(tampered(TamperReason::PayloadHashMismatch));
+        }
+        if entry.prev_hash != prev_hash {
+            return Err(tampered(TamperReason::PrevHashMismatch));
+        }
+        if entry.recompute_hash() != entry.entry_hash {
+            return Err(tampered(TamperReason::EntryHashMismatch));
+        }
+
+        prev_hash = entry.entry_hash.clone();
+        expected_seq += 1;
+    }
+
+    Ok(prev_hash)
+}
+
+/// 检查点锚定到 immudb 的事件（锚定与校验共用，键与内容需完全一致）
+fn checkpoint_anchor_event(checkpoint: &LedgerCheckpoint) -> AuditEvent {
+    AuditEvent {
+        event: "audit_ledger.checkpoint".to_string(),
+        tenant_id: "ledger".to_string(),
+        actor: "audit_checkpointer".to_string(),
+        resource: format!("{}-{}", checkpoint.from_seq, checkpoint.to_seq),
+        payload_hash: checkpoint.merkle_root.clone(),
+        ts: checkpoint.created_at.to_rfc3339(),
+    }
+}
+
+const ENTRY_COLUMNS: &str = "seq, id, tenant_id, event, actor, resource, payload, payload_hash, \
+     prev_hash, entry_hash, created_at";
+
+const CHECKPOINT_COLUMNS: &str = "id, from_seq, to_seq, entry_count, merkle_root, head_hash, \
+     anchored, anchor_proof, anchored_at, created_at";
+
+fn is_retryable(e: &sqlx::Error) -> bool {
+    // 23505: 并发追加抢到同一序号；40001: CockroachDB 串行化冲突
+    matches!(e, sqlx::Error::Database(db) if matches!(db.code().as_deref(), Some("23505" | "40001")))
+}
+
+/// 以链头冲突重试执行一次追加（`op` 每次重试需开启新事务）
+pub async fn retry_append<T, F, Fut>(mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if is_retryable(&e) && attempt < MAX_APPEND_RETRIES => {
+                tracing::debug!(attempt, error = %e, "Audit ledger append conflict, retrying");
+                tokio::time::sleep(Duration::from_millis(10 * attempt as u64)).await;
+            }
+            Err(e) => return Err(e).context("Failed to append audit ledger entry"),
+        }
+    }
+}
+
+// ============ 账本 ============
+
+pub struct AuditLedger {
+    pool: PgPool,
+}
+
+impl AuditLedger {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 追加记录（链头冲突时重试）
+    pub async fn append(&self, entry: &NewLedgerEntry) -> Result<LedgerEntry> {
+        retry_append(|| async move {
+            let mut tx = self.pool.begin().await?;
+            let appended = Self::append_in_tx(&mut tx, entry).await?;
+            tx.commit().await?;
+            Ok(appended)
+        })
+        .await
+    }
+
+    /// 在调用方事务内追加记录，与业务写入同时提交或回滚
+    ///
+    /// 链头冲突返回可重试错误，调用方以 `retry_append` 重跑整个事务
+    pub async fn append_in_tx(
+        tx: &mut Transaction<'_, Postgres>,
+        entry: &NewLedgerEntry,
+    ) -> Result<LedgerEntry, sqlx::Error> {
+        let head = sqlx::query_as::<_, (i64, String)>(
+            "SELECT seq, entry_hash FROM admin.audit_ledger
+             ORDER BY seq DESC
+             LIMIT 1
+             FOR UPDATE",
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let (seq, prev_hash) = match head {
+            Some((seq, hash)) => (seq + 1, hash),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+        let created_at = Utc::now().trunc_subsecs(6);
+        let payload_hash = payload_hash(&entry.payload);
+        let entry_hash = compute_entry_hash(
+            seq,
+            entry.id,
+            entry.tenant_id,
+            &entry.event,
+            &entry.actor,
+            &entry.resource,
+            &payload_hash,
+            created_at,
+            &prev_hash,
+        );
+
+        let appended = sqlx::query_as::<_, LedgerEntry>(&format!(
+            "INSERT INTO admin.audit_ledger
+                 (seq, id, tenant_id, event, actor, resource, payload,
+                  payload_hash, prev_hash, entry_hash, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             RETURNING {ENTRY_COLUMNS}"
+        ))
+        .bind(seq)
+        .bind(entry.id)
+        .bind(entry.tenant_id)
+        .bind(&entry.event)
+        .bind(&entry.actor)
+        .bind(&entry.resource)
+        .bind(&entry.payload)
+        .bind(&payload_hash)
+        .bind(&prev_hash)
+        .bind(&entry_hash)
+        .bind(created_at)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(appended)
+    }
+
+    async fn head_seq(&self) -> Result<i64> {
+        Ok(
+            sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(seq) FROM admin.audit_ledger")
+                .fetch_one(&self.pool)
+                .await?
+                .unwrap_or(0),
+        )
+    }
+
+    async fn fetch_entries(
+        &self,
+        after_seq: i64,
+        to_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<LedgerEntry>> {
+        Ok(sqlx::query_as::<_, LedgerEntry>(&format!(
+            "SELECT {ENTRY_COLUMNS} FROM admin.audit_ledger
+             WHERE seq > $1 AND seq <= $2
+             ORDER BY seq
+             LIMIT $3"
+        ))
+        .bind(after_seq)
+        .bind(to_seq)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// 重算 [from_seq, to_seq] 的哈希链并校验区间内的检查点，to_seq 为空时校验到链头
+    ///
+    /// 已锚定的检查点同时与 immudb 中的根比对；immudb 不可达时结果为无效并报告 `anchor_unreachable`
+    pub async fn verify_range(
+        &self,
+        from_seq: i64,
+        to_seq: Option<i64>,
+        immu: &ImmuCtx,
+    ) -> Result<LedgerVerification> {
+        let from_seq = from_seq.max(1);
+        let head_seq = self.head_seq().await?;
+        let to_seq = to_seq.map_or(head_seq, |to| to.min(head_seq));
+
+        let mut result = LedgerVerification {
+            from_seq,
+            to_seq,
+            head_seq,
+            entries_checked: 0,
+            valid: true,
+            first_tampered: None,
+            checkpoints_checked: 0,
+            checkpoint_mismatch: None,
+            anchors_checked: 0,
+            anchor_unreachable: None,
+        };
+
+        // 尾部被删除：已有检查点覆盖到链头之后
+        let checkpointed_to: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(to_seq) FROM admin.audit_ledger_checkpoints")
+                .fetch_one(&self.pool)
+                .await?;
+        let truncated = checkpointed_to.filter(|to| *to > head_seq);
+
+        if from_seq <= to_seq {
+            // 区间起点的前驱哈希
+            let mut prev_hash = if from_seq == 1 {
+                Some(GENESIS_HASH.to_string())
+            } else {
+                sqlx::query_scalar("SELECT entry_hash FROM admin.audit_ledger WHERE seq = $1")
+                    .bind(from_seq - 1)
+                    .fetch_optional(&self.pool)
+                    .await?
+            };
+            if prev_hash.is_none() {
+                result.first_tampered = Some(TamperedEntry {
+                    seq: from_seq - 1,
+                    id: None,
+                    reason: TamperReason::Missing,
+                });
+            }
+
+            let mut cursor = from_seq - 1;
+            while let Some(prev) = prev_hash.take() {
+                let page = self.fetch_entries(cursor, to_seq, VERIFY_PAGE_SIZE).await?;
+                if page.is_empty() {
+                    if cursor < to_seq {
+                        // 区间内末尾记录缺失
+                        result.first_tampered = Some(TamperedEntry {
+                            seq: cursor + 1,
+                            id: None,
+                            reason: TamperReason::Missing,
+                        });
+                    }
+                    break;
+                }
+
+                match verify_chain(&prev, cursor + 1, &page) {
+                    Ok(last_hash) => {
+                        result.entries_checked += page.len() as i64;
+                        cursor = page.last().map_or(cursor, |e| e.seq);
+                        prev_hash = Some(last_hash);
+                    }
+                    Err(tampered) => {
+                        result.entries_checked += tampered.seq - cursor - 1;
+                        result.first_tampered = Some(tampered);
+                    }
+                }
+            }
+        }
+
+        if result.first_tampered.is_none() {
+            if let Some(checkpoint_to) = truncated {
+                result.first_tampered = Some(TamperedEntry {
+                    seq: head_seq + 1,
+                    id: None,
+                    reason: TamperReason::Missing,
+                });
+                tracing::error!(head_seq, checkpoint_to, "Audit ledger tail truncated");
+            } else {
+                self.verify_checkpoints(from_seq, to_seq, immu, &mut result)
+                    .await?;
+            }
+        }
+
+        result.valid = result.first_tampered.is_none()
+            && result.checkpoint_mismatch.is_none()
+            && result.anchor_unreachable.is_none();
+        if !result.valid {
+            tracing::error!(
+                first_tampered = ?result.first_tampered,
+                checkpoint_mismatch = ?result.checkpoint_mismatch,
+                anchor_unreachable = ?result.anchor_unreachable,
+                "Audit ledger verification failed"
+            );
+        }
+        Ok(result)
+    }
+
+    /// 链校验通过后，重算区间内检查点的 Merkle 根，并与数据库 / immudb 锚点比对
+    async fn verify_checkpoints(
+        &self,
+        from_seq: i64,
+        to_seq: i64,
+        immu: &ImmuCtx,
+        result: &mut LedgerVerification,
+    ) -> Result<()> {
+        let checkpoints = sqlx::query_as::<_, LedgerCheckpoint>(&format!(
+            "SELECT {CHECKPOINT_COLUMNS} FROM admin.audit_ledger_checkpoints
+             WHERE from_seq >= $1 AND to_seq <= $2
+             ORDER BY to_seq"
+        ))
+        .bind(from_seq)
+        .bind(to_seq)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for checkpoint in checkpoints {
+            let hashes: Vec<String> = sqlx::query_scalar(
+                "SELECT entry_hash FROM admin.audit_ledger
+                 WHERE seq BETWEEN $1 AND $2
+                 ORDER BY seq",
+            )
+            .bind(checkpoint.from_seq)
+            .bind(checkpoint.to_seq)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let actual_root = merkle_root(&hashes)?;
+            result.checkpoints_checked += 1;
+            let mismatch = |reason, anchored_root| CheckpointMismatch {
+                checkpoint_id: checkpoint.id,
+                from_seq: checkpoint.from_seq,
+                to_seq: checkpoint.to_seq,
+                reason,
+                expected_root: checkpoint.merkle_root.clone(),
+                actual_root: actual_root.clone(),
+                anchored_root,
+            };
+            if actual_root != checkpoint.merkle_root {
+                result.checkpoint_mismatch =
+                    Some(mismatch(CheckpointMismatchReason::RootMismatch, None));
+                break;
+            }
+
+            // 未锚定或 immudb 已不可达：仅数据库比对
+            if !checkpoint.anchored || result.anchor_unreachable.is_some() {
+                continue;
+            }
+
+            let anchor = checkpoint_anchor_event(&checkpoint);
+            match immu.try_read_event(&anchor.immu_key()).await {
+                Ok(Some(anchored)) => {
+                    result.anchors_checked += 1;
+                    if anchored.payload_hash != actual_root {
+                        result.checkpoint_mismatch = Some(mismatch(
+                            CheckpointMismatchReason::AnchorMismatch,
+                            Some(anchored.payload_hash),
+                        ));
+                        break;
+                    }
+                }
+                Ok(None) => {
+                    result.checkpoint_mismatch =
+                        Some(mismatch(CheckpointMismatchReason::AnchorMissing, None));
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        checkpoint_to = checkpoint.to_seq,
+                        error = %e,
+                        "immudb unavailable, audit checkpoint anchor not verified"
+                    );
+                    result.anchor_unreachable = Some(AnchorUnreachable {
+                        checkpoint_id: checkpoint.id,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 为上一个检查点之后的新记录创建检查点（先校验该段哈希链）
+    pub async fn create_checkpoint(&self) -> Result<Option<LedgerCheckpoint>> {
+        let (last_to, last_head): (i64, String) = sqlx::query_as::<_, (i64, String)>(
+            "SELECT to_seq, head_hash FROM admin.audit_ledger_checkpoints
+             ORDER BY to_seq DESC
+             LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or((0, GENESIS_HASH.to_string()));
+
+        let entries = self
+            .fetch_entries(last_to, i64::MAX, MAX_CHECKPOINT_ENTRIES)
+            .await?;
+        let Some(last) = entries.last() else {
+            return Ok(None);
+        };
+
+        if let Err(tampered) = verify_chain(&last_head, last_to + 1, &entries) {
+            anyhow::bail!(
+                "Audit ledger chain broken at seq {} ({:?}), checkpoint skipped",
+                tampered.seq,
+                tampered.reason
+            );
+        }
+
+        let hashes: Vec<String> = entries.iter().map(|e| e.entry_hash.clone()).collect();
+        let root = merkle_root(&hashes)?;
+
+        let checkpoint = sqlx::query_as::<_, LedgerCheckpoint>(&format!(
+            "INSERT INTO admin.audit_ledger_checkpoints
+                 (from_seq, to_seq, entry_count, merkle_root, head_hash)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (to_seq) DO NOTHING
+             RETURNING {CHECKPOINT_COLUMNS}"
+        ))
+        .bind(last_to + 1)
+        .bind(last.seq)
+        .bind(entries.len() as i64)
+        .bind(&root)
+        .bind(&last.entry_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(checkpoint)
+    }
+
+    /// 将未锚定的检查点写入 immudb；immudb 不可用时保留待下次重试
+    pub async fn anchor_pending_checkpoints(&self, immu: &ImmuCtx) -> Result<usize> {
+        let pending = sqlx::query_as::<_, LedgerCheckpoint>(&format!(
+            "SELECT {CHECKPOINT_COLUMNS} FROM admin.audit_ledger_checkpoints
+             WHERE NOT anchored
+             ORDER BY to_seq
+             LIMIT $1"
+        ))
+        .bind(ANCHOR_BATCH)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut anchored = 0;
+        for checkpoint in pending {
+            let event = checkpoint_anchor_event(&checkpoint);
+
+            match immu.try_write_event(&event).await {
+                Ok(proof) => {
+                    sqlx::query(
+                        "UPDATE admin.audit_ledger_checkpoints
+                         SET anchored = true, anchor_proof = $2, anchored_at = NOW(),
+                             anchor_attempts = anchor_attempts + 1
+                         WHERE id = $1",
+                    )
+                    .bind(checkpoint.id)
+                    .bind(&proof)
+                    .execute(&self.pool)
+                    .await?;
+                    anchored += 1;
+                }
+                Err(e) => {
+                    sqlx::query(
+                        "UPDATE admin.audit_ledger_checkpoints
+                         SET anchor_attempts = anchor_attempts + 1
+                         WHERE id = $1",
+                    )
+                    .bind(checkpoint.id)
+                    .execute(&self.pool)
+                    .await?;
+                    tracing::warn!(
+                        checkpoint_to = checkpoint.to_seq,
+                        error = %e,
+                        "immudb unavailable, audit checkpoint anchoring deferred"
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(anchored)
+    }
+}
+
+// ============ 检查点任务 ============
+
+/// 后台检查点任务（单实例运行，由 WorkerSupervisor 选主）
+pub struct AuditCheckpointer {
+    ledger: AuditLedger,
+    immu: Arc<ImmuCtx>,
+    interval: Duration,
+}
+
+impl AuditCheckpointer {
+    pub fn new(pool: PgPool, immu: Arc<ImmuCtx>) -> Self {
+        Self {
+            ledger: AuditLedger::new(pool),
+            immu,
+            // AUDIT_CHECKPOINT_INTERVAL_SECS 覆盖默认 10 分钟
+            interval: std::env::var("AUDIT_CHECKPOINT_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+        }
+    }
+
+    /// 启动后台检查点任务（持续运行）
+    pub async fn start_background_checkpointer(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.interval);
+
+        tracing::info!(
+            "Audit ledger checkpointer started, interval={}s",
+            self.interval.as_secs()
+        );
+
+        loop {
+            ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
+
+            match self.ledger.create_checkpoint().await {
+                Ok(Some(checkpoint)) => tracing::info!(
+                    from_seq = checkpoint.from_seq,
+                    to_seq = checkpoint.to_seq,
+                    root = %checkpoint.merkle_root,
+                    "Audit ledger checkpoint created"
+                ),
+                Ok(None) => {}
+                Err(e) => tracing::error!(error = ?e, "Failed to create audit ledger checkpoint"),
+            }
+
+            match self.ledger.anchor_pending_checkpoints(&self.immu).await {
+                Ok(anchored) if anchored > 0 => {
+                    tracing::info!(
+                        count = anchored,
+                        "Audit ledger checkpoints anchored to immudb"
+                    )
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!(error = ?e, "Failed to anchor audit ledger checkpoints"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_chain(len: usize) -> Vec<LedgerEntry> {
+        let tenant_id = Uuid::new_v4();
+        let mut prev_hash = GENESIS_HASH.to_string();
+        (1..=len as i64)
+            .map(|seq| {
+                let payload = serde_json::json!({ "amount": "10.5", "seq": seq, "meta": { "b": 1, "a": [true, null] } });
+                let id = Uuid::new_v4();
+                let created_at = Utc::now().trunc_subsecs(6);
+                let payload_hash = payload_hash(&payload);
+                let entry_hash = compute_entry_hash(
+                    seq,
+                    id,
+                    tenant_id,
+                    "withdrawal.approved",
+                    "actor",
+                    "resource",
+                    &payload_hash,
+                    created_at,
+                    &prev_hash,
+                );
+                let entry = LedgerEntry {
+                    seq,
+                    id,
+                    tenant_id,
+                    event: "withdrawal.approved".into(),
+                    actor: "actor".into(),
+                    resource: "resource".into(),
+                    payload,
+                    payload_hash,
+                    prev_hash: prev_hash.clone(),
+                    entry_hash,
+                    created_at,
+                };
+                prev_hash = entry.entry_hash.clone();
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_canonical_json_is_key_order_independent() {
+        let a: serde_json::Value =
+            serde_json::from_str(r#"{"b":1,"a":{"y":[1,2],"x":"s"}}"#).unwrap();
+        let b: serde_json::Value =
+            serde_json::from_str(r#"{"a":{"x":"s","y":[1,2]},"b":1}"#).unwrap();
+        assert_eq!(canonical_json(&a), r#"{"a":{"x":"s","y":[1,2]},"b":1}"#);
+        assert_eq!(payload_hash(&a), payload_hash(&b));
+    }
+
+    #[test]
+    fn test_verify_chain_reports_first_tampered_entry() {
+        let chain = build_chain(5);
+        let head = verify_chain(GENESIS_HASH, 1, &chain).unwrap();
+        assert_eq!(head, chain[4].entry_hash);
+
+        // 中间区间以前一条的哈希为起点
+        assert!(verify_chain(&chain[1].entry_hash, 3, &chain[2..]).is_ok());
+
+        // 修改内容
+        let mut edited = chain.clone();
+        edited[2].payload["amount"] = serde_json::json!("1000");
+        let err = verify_chain(GENESIS_HASH, 1, &edited).unwrap_err();
+        assert_eq!(
+            (err.seq, err.reason),
+            (3, TamperReason::PayloadHashMismatch)
+        );
+
+        // 修改内容并同步 payload_hash：entry_hash 不再匹配
+        edited[2].payload_hash = payload_hash(&edited[2].payload);
+        let err = verify_chain(GENESIS_HASH, 1, &edited).unwrap_err();
+        assert_eq!((err.seq, err.reason), (3, TamperReason::EntryHashMismatch));
+
+        // 删除一条
+        let mut deleted = chain.clone();
+        deleted.remove(1);
+        let err = verify_chain(GENESIS_HASH, 1, &deleted).unwrap_err();
+        assert_eq!(
+            (err.seq, err.reason, err.id),
+            (2, TamperReason::Missing, None)
+        );
+
+        // 改写 prev_hash（链被截断后重接）
+        let mut relinked = chain.clone();
+        relinked[3].prev_hash = GENESIS_HASH.to_string();
+        let err = verify_chain(GENESIS_HASH, 1, &relinked).unwrap_err();
+        assert_eq!((err.seq, err.reason), (4, TamperReason::PrevHashMismatch));
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let chain = build_chain(5);
+        let hashes: Vec<String> = chain.iter().map(|e| e.entry_hash.clone()).collect();
+
+        let root = merkle_root(&hashes).unwrap();
+        assert_eq!(root.len(), 64);
+        assert_eq!(root, merkle_root(&hashes).unwrap());
+
+        // 单叶子根不等于叶子本身（叶子前缀隔离）
+        assert_ne!(merkle_root(&hashes[..1]).unwrap(), hashes[0]);
+
+        // 顺序敏感
+        let mut swapped = hashes.clone();
+        swapped.swap(0, 1);
+        assert_ne!(merkle_root(&swapped).unwrap(), root);
+
+        assert!(merkle_root(&[]).is_err());
+        assert!(merkle_root(&["zz".to_string()]).is_err());
+    }
+}
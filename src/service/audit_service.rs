@@ -1,5 +1,6 @@
 //! 审计日志服务
-//! 企业级实现，使用Immudb进行不可篡改审计日志存储
+//! 企业级实现：本地哈希链账本（`audit_ledger`）与 fiat.audit_logs 在同一事务内写入，
+//! Immudb 双写为最佳努力（账本检查点另行锚定到 Immudb）
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -8,7 +9,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-use crate::infrastructure::audit::{AuditEvent, ImmuCtx};
+use crate::{
+    infrastructure::audit::{AuditEvent, ImmuCtx},
+    service::audit_ledger::{retry_append, AuditLedger, LedgerVerification, NewLedgerEntry},
+};
 
 /// 审计日志
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,11 +52,16 @@ pub struct ComplianceReport {
 pub struct AuditService {
     pool: PgPool,
     immu: Arc<ImmuCtx>,
+    ledger: AuditLedger,
 }
 
 impl AuditService {
     pub fn new(pool: PgPool, immu: Arc<ImmuCtx>) -> Self {
-        Self { pool, immu }
+        Self {
+            ledger: AuditLedger::new(pool.clone()),
+            pool,
+            immu,
+        }
     }
 
     /// 记录审计事件
@@ -73,40 +82,68 @@ impl AuditService {
         let log_id = Uuid::new_v4();
         let now = Utc::now();
 
-        // 1. 写入数据库
-        let _proof_hash = sqlx::query_scalar::<_, Option<String>>(
-            r#"
-            INSERT INTO fiat.audit_logs (
-                id, tenant_id, user_id, order_id, action,
-                amount, status, provider, ip_address, user_agent,
-                metadata, created_at
+        // 1. 哈希链账本（权威记录，不依赖 Immudb）与 fiat.audit_logs 同一事务写入，
+        //    任一失败整体回滚，链头冲突时重跑整个事务
+        let new_entry = NewLedgerEntry {
+            id: log_id,
+            tenant_id,
+            event: action.to_string(),
+            actor: user_id.map(|u| u.to_string()).unwrap_or_default(),
+            resource: order_id.map(|o| o.to_string()).unwrap_or_default(),
+            payload: serde_json::json!({
+                "user_id": user_id,
+                "order_id": order_id,
+                "amount": amount.map(|a| a.to_string()),
+                "status": status,
+                "provider": provider,
+                "ip_address": ip_address,
+                "user_agent": user_agent,
+                "metadata": metadata,
+            }),
+        };
+        let (pool, new_entry, metadata) = (&self.pool, &new_entry, &metadata);
+        let entry = retry_append(|| async move {
+            let mut tx = pool.begin().await?;
+            let entry = AuditLedger::append_in_tx(&mut tx, new_entry).await?;
+
+            // 2. 写入数据库
+            sqlx::query(
+                r#"
+                INSERT INTO fiat.audit_logs (
+                    id, tenant_id, user_id, order_id, action,
+                    amount, status, provider, ip_address, user_agent,
+                    metadata, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                "#,
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-            RETURNING immudb_proof_hash
-            "#,
-        )
-        .bind(log_id)
-        .bind(tenant_id)
-        .bind(user_id)
-        .bind(order_id)
-        .bind(action)
-        .bind(amount)
-        .bind(status)
-        .bind(provider)
-        .bind(ip_address)
-        .bind(user_agent)
-        .bind(&metadata)
-        .bind(now)
-        .fetch_one(&self.pool)
+            .bind(log_id)
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(order_id)
+            .bind(action)
+            .bind(amount)
+            .bind(status)
+            .bind(provider)
+            .bind(ip_address)
+            .bind(user_agent)
+            .bind(metadata)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(entry)
+        })
         .await?;
 
-        // 2. 写入Immudb（不可篡改审计日志）
+        // 3. 写入Immudb（最佳努力；账本检查点保证 Immudb 不可用期间的记录可事后证明）
         let event = AuditEvent {
-            event: action.to_string(),
+            event: entry.event,
             tenant_id: tenant_id.to_string(),
-            actor: user_id.map(|u| u.to_string()).unwrap_or_default(),
-            resource: order_id.map(|o| o.to_string()).unwrap_or_default(),
-            payload_hash: format!("hash_{}", log_id),
+            actor: entry.actor,
+            resource: entry.resource,
+            payload_hash: entry.entry_hash,
             ts: now.to_rfc3339(),
         };
 
@@ -123,6 +160,15 @@ impl AuditService {
         Ok(log_id)
     }
 
+    /// 校验账本区间 [from_seq, to_seq] 的哈希链与检查点
+    pub async fn verify_ledger_range(
+        &self,
+        from_seq: i64,
+        to_seq: Option<i64>,
+    ) -> Result<LedgerVerification> {
+        self.ledger.verify_range(from_seq, to_seq, &self.immu).await
+    }
+
     /// 查询审计日志
     #[allow(clippy::too_many_arguments)]
     pub async fn get_audit_logs(
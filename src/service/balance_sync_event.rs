@@ -23,6 +23,13 @@ pub enum BalanceSyncEvent {
     },
     /// 手动触发同步
     ManualSync { chain: String, address: String },
+    /// 入账索引器发现外部转入（或重组回滚了已入账记录）
+    DepositDetected {
+        chain: String,
+        wallet_id: uuid::Uuid,
+        address: String,
+        tx_hash: String,
+    },
 }
 
 /// 余额同步事件处理器
@@ -71,7 +78,7 @@ impl BalanceSyncEventHandler {
                 if let Some(wallet_id) = wallet_id_opt {
                     // 同步余额（需要3个参数：wallet_id, address, chain）
                     self.balance_sync_service
-                        .sync_wallet_balance(wallet_id, &address, &chain)
+                        .sync_wallet_balance(wallet_id, &chain, &address)
                         .await
                         .ok();
                 } else {
@@ -105,7 +112,7 @@ impl BalanceSyncEventHandler {
 
                 if let Some(wallet_id) = wallet_id_opt {
                     self.balance_sync_service
-                        .sync_wallet_balance(wallet_id, &address, &chain)
+                        .sync_wallet_balance(wallet_id, &chain, &address)
                         .await
                         .ok();
                     Ok(())
@@ -113,6 +120,24 @@ impl BalanceSyncEventHandler {
                     anyhow::bail!("Wallet not found for address: {}", address)
                 }
             }
+            BalanceSyncEvent::DepositDetected {
+                chain,
+                wallet_id,
+                address,
+                tx_hash,
+            } => {
+                tracing::info!(
+                    "Incoming transfer indexed, syncing balance: chain={}, address={}, tx_hash={}",
+                    chain,
+                    address,
+                    tx_hash
+                );
+
+                self.balance_sync_service
+                    .sync_wallet_balance(wallet_id, &chain, &address)
+                    .await?;
+                Ok(())
+            }
         }
     }
 
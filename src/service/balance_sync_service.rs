@@ -10,6 +10,8 @@ use sqlx::PgPool;
 use tokio::time::interval;
 use uuid::Uuid;
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 /// 余额同步服务
 pub struct BalanceSyncService {
     pool: PgPool,
@@ -132,6 +134,7 @@ impl BalanceSyncService {
         let response = client
             .post(&rpc_url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call Solana RPC")?;
@@ -168,6 +171,7 @@ impl BalanceSyncService {
 
         let utxos: Vec<Utxo> = client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call Bitcoin API")?
@@ -204,6 +208,7 @@ impl BalanceSyncService {
 
         let response: TonResponse = client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call TON API")?
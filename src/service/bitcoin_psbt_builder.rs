@@ -0,0 +1,1109 @@
+//! Bitcoin PSBT 交易构建
+//!
+//! 企业级实现：基于UTXO模型构建未签名的PSBT（BIP-174），由客户端签名
+//! - UTXO查询：Esplora兼容API（Blockstream / mempool.space）
+//! - 选币：Branch-and-Bound（无找零精确匹配），失败时回退到大额优先累加
+//! - 费用：按 sat/vB 费率 × 估算虚拟大小计算
+//! - 地址类型：P2PKH / P2SH-P2WPKH / P2WPKH / P2TR
+//! - 签名元数据：P2SH-P2WPKH 填 `redeem_script`，P2TR 填 `tap_internal_key` / `tap_key_origins`
+//!   （需要客户端提供公钥与 BIP32 来源），其余类型提供时填 `bip32_derivation`
+//! - 网络：`BITCOIN_NETWORK`（bitcoin / testnet / signet / regtest），缺省主网
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use bitcoin::{
+    absolute::LockTime,
+    address::AddressType,
+    bip32::{DerivationPath, Fingerprint, KeySource},
+    key::{PublicKey, Secp256k1, XOnlyPublicKey},
+    psbt::{Input as PsbtInput, Psbt},
+    transaction::Version,
+    Address, Amount, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+    Witness,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::chain_config::AddressFormat;
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// Branch-and-Bound 最大搜索次数（与 Bitcoin Core 保持一致）
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// 交易固定开销（version + locktime + 输入输出计数 + segwit marker/flag）
+const TX_OVERHEAD_VBYTES: u64 = 11;
+
+/// 最低中继费率（sat/vB）
+pub const MIN_RELAY_FEE_RATE: u64 = 1;
+
+/// 从 `BITCOIN_NETWORK` 读取网络，缺省（或无法识别时）为主网
+pub fn network_from_env() -> Network {
+    match std::env::var("BITCOIN_NETWORK") {
+        Ok(value) => parse_network(&value).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Invalid BITCOIN_NETWORK, falling back to mainnet");
+            Network::Bitcoin
+        }),
+        Err(_) => Network::Bitcoin,
+    }
+}
+
+/// 解析网络名称（兼容 `mainnet` / `main` / `test` 别名）
+pub fn parse_network(value: &str) -> Result<Network> {
+    match value.trim().to_lowercase().as_str() {
+        "mainnet" | "main" => Ok(Network::Bitcoin),
+        "test" => Ok(Network::Testnet),
+        other => Network::from_str(other)
+            .map_err(|_| anyhow::anyhow!("Unknown Bitcoin network: {}", value)),
+    }
+}
+
+/// 可花费的UTXO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    /// 金额（sat）
+    pub value: u64,
+    /// 是否已确认
+    #[serde(default)]
+    pub confirmed: bool,
+}
+
+/// UTXO数据源
+#[async_trait]
+pub trait UtxoSource: Send + Sync {
+    /// 查询地址的全部UTXO
+    async fn list_utxos(&self, address: &str) -> Result<Vec<Utxo>>;
+
+    /// 查询目标确认区块数对应的费率（sat/vB）
+    async fn fee_rate(&self, target_blocks: u16) -> Result<u64>;
+
+    /// 查询原始交易（P2PKH输入需要完整的前序交易）
+    async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>>;
+}
+
+/// Esplora 兼容 API 的 UTXO 数据源
+pub struct EsploraUtxoSource {
+    http_client: reqwest::Client,
+    api_url: String,
+}
+
+impl EsploraUtxoSource {
+    pub fn new(api_url: String) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            http_client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let api_url = std::env::var("BITCOIN_API_URL")
+            .unwrap_or_else(|_| "https://blockstream.info/api".to_string());
+        Self::new(api_url)
+    }
+}
+
+#[async_trait]
+impl UtxoSource for EsploraUtxoSource {
+    async fn list_utxos(&self, address: &str) -> Result<Vec<Utxo>> {
+        #[derive(Deserialize)]
+        struct EsploraStatus {
+            confirmed: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct EsploraUtxo {
+            txid: String,
+            vout: u32,
+            value: u64,
+            status: EsploraStatus,
+        }
+
+        let url = format!("{}/address/{}/utxo", self.api_url, address);
+        let utxos: Vec<EsploraUtxo> = self
+            .http_client
+            .get(&url)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call Bitcoin API")?
+            .error_for_status()
+            .context("Bitcoin API returned error status")?
+            .json()
+            .await
+            .context("Failed to parse Bitcoin UTXO response")?;
+
+        Ok(utxos
+            .into_iter()
+            .map(|u| Utxo {
+                txid: u.txid,
+                vout: u.vout,
+                value: u.value,
+                confirmed: u.status.confirmed,
+            })
+            .collect())
+    }
+
+    async fn fee_rate(&self, target_blocks: u16) -> Result<u64> {
+        let url = format!("{}/fee-estimates", self.api_url);
+        let estimates: std::collections::HashMap<String, f64> = self
+            .http_client
+            .get(&url)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call Bitcoin fee API")?
+            .error_for_status()
+            .context("Bitcoin fee API returned error status")?
+            .json()
+            .await
+            .context("Failed to parse Bitcoin fee estimates")?;
+
+        // 选择不超过目标区块数的最近一档估算
+        let rate = estimates
+            .iter()
+            .filter_map(|(k, v)| k.parse::<u16>().ok().map(|blocks| (blocks, *v)))
+            .filter(|(blocks, _)| *blocks <= target_blocks)
+            .max_by_key(|(blocks, _)| *blocks)
+            .map(|(_, v)| v)
+            .context("No fee estimate available for target")?;
+
+        Ok((rate.ceil() as u64).max(MIN_RELAY_FEE_RATE))
+    }
+
+    async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/tx/{}/hex", self.api_url, txid);
+        let tx_hex = self
+            .http_client
+            .get(&url)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call Bitcoin API")?
+            .error_for_status()
+            .context("Bitcoin API returned error status")?
+            .text()
+            .await
+            .context("Failed to read raw transaction")?;
+
+        hex::decode(tx_hex.trim()).context("Invalid raw transaction hex")
+    }
+}
+
+/// 支持的脚本类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptKind {
+    /// Legacy (1...)
+    P2pkh,
+    /// 嵌套隔离见证 BIP49 (3...)
+    P2shP2wpkh,
+    /// 原生隔离见证 BIP84 (bc1q...)
+    P2wpkh,
+    /// Taproot BIP86 (bc1p...)
+    P2tr,
+}
+
+impl ScriptKind {
+    /// 从地址类型推断脚本类型
+    ///
+    /// 注意：P2SH 仅按 BIP49 钱包处理（P2SH-P2WPKH）
+    pub fn from_address(address: &Address) -> Result<Self> {
+        match address.address_type() {
+            Some(AddressType::P2pkh) => Ok(Self::P2pkh),
+            Some(AddressType::P2sh) => Ok(Self::P2shP2wpkh),
+            Some(AddressType::P2wpkh) => Ok(Self::P2wpkh),
+            Some(AddressType::P2tr) => Ok(Self::P2tr),
+            other => anyhow::bail!("Unsupported Bitcoin address type: {:?}", other),
+        }
+    }
+
+    /// 对应的地址编码格式
+    pub fn address_format(&self) -> AddressFormat {
+        match self {
+            Self::P2pkh | Self::P2shP2wpkh => AddressFormat::Base58,
+            Self::P2wpkh => AddressFormat::Bech32,
+            Self::P2tr => AddressFormat::Bech32m,
+        }
+    }
+
+    /// 花费该类型输出时输入的虚拟大小（vB）
+    pub fn input_vbytes(&self) -> u64 {
+        match self {
+            Self::P2pkh => 148,
+            Self::P2shP2wpkh => 91,
+            Self::P2wpkh => 68,
+            Self::P2tr => 58,
+        }
+    }
+
+    /// 该类型输出的虚拟大小（vB）
+    pub fn output_vbytes(&self) -> u64 {
+        match self {
+            Self::P2pkh => 34,
+            Self::P2shP2wpkh => 32,
+            Self::P2wpkh => 31,
+            Self::P2tr => 43,
+        }
+    }
+
+    /// 粉尘阈值（sat），低于该值的输出不会被中继
+    pub fn dust_limit(&self) -> u64 {
+        match self {
+            Self::P2pkh => 546,
+            Self::P2shP2wpkh => 540,
+            Self::P2wpkh => 294,
+            Self::P2tr => 330,
+        }
+    }
+
+    pub fn is_segwit(&self) -> bool {
+        !matches!(self, Self::P2pkh)
+    }
+}
+
+/// 选币结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinSelection {
+    pub selected: Vec<Utxo>,
+    /// 手续费（sat）
+    pub fee: u64,
+    /// 找零金额（sat），0表示无找零输出
+    pub change: u64,
+    /// 估算虚拟大小（vB）
+    pub vsize: u64,
+}
+
+/// 选币参数
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionParams {
+    /// 转账金额（sat）
+    pub target: u64,
+    /// 费率（sat/vB）
+    pub fee_rate: u64,
+    pub input_kind: ScriptKind,
+    pub recipient_kind: ScriptKind,
+    pub change_kind: ScriptKind,
+}
+
+impl SelectionParams {
+    /// 不含输入、不含找零时的交易虚拟大小
+    fn base_vbytes(&self) -> u64 {
+        TX_OVERHEAD_VBYTES + self.recipient_kind.output_vbytes()
+    }
+
+    /// 单个输入的费用
+    fn input_fee(&self) -> u64 {
+        self.input_kind.input_vbytes() * self.fee_rate
+    }
+
+    /// 增加找零输出并在未来花费它的成本
+    fn cost_of_change(&self) -> u64 {
+        (self.change_kind.output_vbytes() + self.change_kind.input_vbytes()) * self.fee_rate
+    }
+}
+
+/// 选币：优先 Branch-and-Bound（无找零），失败时回退到大额优先
+pub fn select_coins(utxos: &[Utxo], params: &SelectionParams) -> Result<CoinSelection> {
+    if params.target == 0 {
+        anyhow::bail!("Amount must be greater than zero");
+    }
+    if params.target < params.recipient_kind.dust_limit() {
+        anyhow::bail!(
+            "Amount {} sat is below dust limit {} sat",
+            params.target,
+            params.recipient_kind.dust_limit()
+        );
+    }
+
+    // 过滤掉花费成本高于自身价值的UTXO
+    let input_fee = params.input_fee();
+    let mut candidates: Vec<&Utxo> = utxos.iter().filter(|u| u.value > input_fee).collect();
+    candidates.sort_by(|a, b| b.value.cmp(&a.value));
+
+    if let Some(selection) = branch_and_bound(&candidates, params) {
+        return Ok(selection);
+    }
+
+    largest_first(&candidates, params)
+}
+
+/// Branch-and-Bound 选币（参考 Bitcoin Core `SelectCoinsBnB`）
+///
+/// 在 `[target, target + cost_of_change]` 区间内寻找有效值之和的组合，
+/// 选出超额最小（即浪费最少）的方案，该方案不产生找零输出
+fn branch_and_bound(candidates: &[&Utxo], params: &SelectionParams) -> Option<CoinSelection> {
+    let input_fee = params.input_fee();
+    let effective: Vec<u64> = candidates.iter().map(|u| u.value - input_fee).collect();
+    let target = params.target + params.base_vbytes() * params.fee_rate;
+    let upper = target + params.cost_of_change();
+
+    let total: u64 = effective.iter().sum();
+    if total < target {
+        return None;
+    }
+
+    let mut remaining = total;
+    let mut current_value = 0u64;
+    let mut selection: Vec<bool> = Vec::with_capacity(effective.len());
+    let mut best: Option<(Vec<bool>, u64)> = None;
+
+    for _ in 0..BNB_MAX_TRIES {
+        let mut backtrack = false;
+
+        if current_value + remaining < target || current_value > upper {
+            backtrack = true;
+        } else if current_value >= target {
+            let excess = current_value - target;
+            if best.as_ref().map(|(_, e)| excess < *e).unwrap_or(true) {
+                best = Some((selection.clone(), excess));
+                if excess == 0 {
+                    break;
+                }
+            }
+            backtrack = true;
+        } else if selection.len() == effective.len() {
+            backtrack = true;
+        }
+
+        if backtrack {
+            // 回溯到最近一个被选中的节点，改为不选
+            while let Some(last) = selection.last().copied() {
+                if last {
+                    break;
+                }
+                selection.pop();
+                remaining += effective[selection.len()];
+            }
+            match selection.last_mut() {
+                Some(last) => {
+                    *last = false;
+                    current_value -= effective[selection.len() - 1];
+                }
+                None => break,
+            }
+        } else {
+            // 继续向下：选中当前节点
+            let idx = selection.len();
+            remaining -= effective[idx];
+            current_value += effective[idx];
+            selection.push(true);
+        }
+    }
+
+    let (picked, excess) = best?;
+    let selected: Vec<Utxo> = picked
+        .iter()
+        .enumerate()
+        .filter(|(_, chosen)| **chosen)
+        .map(|(i, _)| candidates[i].clone())
+        .collect();
+    let vsize = params.base_vbytes() + selected.len() as u64 * params.input_kind.input_vbytes();
+    let fee = vsize * params.fee_rate + excess;
+
+    Some(CoinSelection {
+        selected,
+        fee,
+        change: 0,
+        vsize,
+    })
+}
+
+/// 大额优先累加选币（带找零）
+fn largest_first(candidates: &[&Utxo], params: &SelectionParams) -> Result<CoinSelection> {
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+
+    for utxo in candidates {
+        selected.push((*utxo).clone());
+        total += utxo.value;
+
+        let inputs_vbytes = selected.len() as u64 * params.input_kind.input_vbytes();
+        let vsize_no_change = params.base_vbytes() + inputs_vbytes;
+        let fee_no_change = vsize_no_change * params.fee_rate;
+
+        if total < params.target + fee_no_change {
+            continue;
+        }
+
+        let vsize_with_change = vsize_no_change + params.change_kind.output_vbytes();
+        let fee_with_change = vsize_with_change * params.fee_rate;
+        let change = total.saturating_sub(params.target + fee_with_change);
+
+        if total >= params.target + fee_with_change && change >= params.change_kind.dust_limit() {
+            return Ok(CoinSelection {
+                selected,
+                fee: fee_with_change,
+                change,
+                vsize: vsize_with_change,
+            });
+        }
+
+        // 找零低于粉尘阈值，并入手续费
+        return Ok(CoinSelection {
+            fee: total - params.target,
+            selected,
+            change: 0,
+            vsize: vsize_no_change,
+        });
+    }
+
+    anyhow::bail!(
+        "Insufficient funds: available {} sat, required {} sat plus fees",
+        candidates.iter().map(|u| u.value).sum::<u64>(),
+        params.target
+    )
+}
+
+/// PSBT构建参数
+#[derive(Debug, Clone, Default)]
+pub struct PsbtRequest {
+    pub from: String,
+    pub to: String,
+    /// 找零地址（客户端按BIP44 change路径派生）；产生找零时必填，不会找零回发送地址
+    pub change_address: Option<String>,
+    /// 发送地址公钥（十六进制，33字节压缩公钥；P2TR 也可为32字节 x-only 内部公钥）
+    ///
+    /// P2SH-P2WPKH / P2TR 必填，用于生成 `redeem_script` / `tap_internal_key`
+    pub public_key: Option<String>,
+    /// 公钥的BIP32来源，`指纹/路径`，如 `d34db33f/86'/0'/0'/0/0`（P2TR 必填）
+    pub key_origin: Option<String>,
+    /// 转账金额（sat）
+    pub amount: u64,
+    /// 费率（sat/vB），缺省时按 `target_blocks` 从数据源获取
+    pub fee_rate: Option<u64>,
+    pub target_blocks: u16,
+}
+
+/// 未签名PSBT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedPsbt {
+    /// Base64编码的PSBT
+    pub psbt_base64: String,
+    pub inputs: Vec<Utxo>,
+    /// 手续费（sat）
+    pub fee: u64,
+    /// 费率（sat/vB）
+    pub fee_rate: u64,
+    /// 估算虚拟大小（vB）
+    pub vsize: u64,
+    /// 找零金额（sat）
+    pub change: u64,
+    pub change_address: Option<String>,
+}
+
+/// Bitcoin PSBT 构建器
+pub struct BitcoinPsbtBuilder {
+    utxo_source: Arc<dyn UtxoSource>,
+    network: Network,
+}
+
+impl BitcoinPsbtBuilder {
+    pub fn new(utxo_source: Arc<dyn UtxoSource>, network: Network) -> Self {
+        Self {
+            utxo_source,
+            network,
+        }
+    }
+
+    /// 替换网络（地址按该网络校验）
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    fn parse_address(&self, address: &str) -> Result<Address> {
+        Address::from_str(address)
+            .map_err(|e| anyhow::anyhow!("Invalid Bitcoin address {}: {}", address, e))?
+            .require_network(self.network)
+            .map_err(|e| anyhow::anyhow!("Address {} not valid for network: {}", address, e))
+    }
+
+    /// 构建未签名的PSBT
+    pub async fn build(&self, request: &PsbtRequest) -> Result<UnsignedPsbt> {
+        let from = self.parse_address(&request.from)?;
+        let to = self.parse_address(&request.to)?;
+        let change_address = request
+            .change_address
+            .as_deref()
+            .map(|addr| self.parse_address(addr))
+            .transpose()?;
+
+        let fee_rate = match request.fee_rate {
+            Some(rate) => rate,
+            None => self.utxo_source.fee_rate(request.target_blocks).await?,
+        }
+        .max(MIN_RELAY_FEE_RATE);
+
+        let input_kind = ScriptKind::from_address(&from)?;
+        let signer = self.signer_metadata(request, &from, input_kind)?;
+        let params = SelectionParams {
+            target: request.amount,
+            fee_rate,
+            input_kind,
+            recipient_kind: ScriptKind::from_address(&to)?,
+            // 未提供找零地址时按输入类型估算（仅用于判断是否值得找零）
+            change_kind: match &change_address {
+                Some(addr) => ScriptKind::from_address(addr)?,
+                None => input_kind,
+            },
+        };
+
+        // 只使用已确认的UTXO，避免依赖未确认链
+        let utxos: Vec<Utxo> = self
+            .utxo_source
+            .list_utxos(&request.from)
+            .await?
+            .into_iter()
+            .filter(|u| u.confirmed)
+            .collect();
+
+        let selection = select_coins(&utxos, &params)?;
+
+        let mut outputs = vec![TxOut {
+            value: Amount::from_sat(request.amount),
+            script_pubkey: to.script_pubkey(),
+        }];
+        let change_address = match (selection.change, change_address) {
+            (0, _) => None,
+            (change, Some(addr)) => {
+                outputs.push(TxOut {
+                    value: Amount::from_sat(change),
+                    script_pubkey: addr.script_pubkey(),
+                });
+                Some(addr)
+            }
+            // 找零回发送地址会复用地址并暴露资金归属，要求客户端显式派生找零地址
+            (change, None) => anyhow::bail!(
+                "change_address is required: selected inputs leave {} sat change",
+                change
+            ),
+        };
+
+        let inputs = selection
+            .selected
+            .iter()
+            .map(|u| {
+                Ok(TxIn {
+                    previous_output: OutPoint::new(
+                        Txid::from_str(&u.txid).context("Invalid UTXO txid")?,
+                        u.vout,
+                    ),
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: Witness::default(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let unsigned_tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs,
+            output: outputs,
+        };
+
+        let mut psbt = Psbt::from_unsigned_tx(unsigned_tx)
+            .map_err(|e| anyhow::anyhow!("Failed to create PSBT: {}", e))?;
+
+        let from_script = from.script_pubkey();
+        for (psbt_input, utxo) in psbt.inputs.iter_mut().zip(selection.selected.iter()) {
+            if input_kind.is_segwit() {
+                psbt_input.witness_utxo = Some(TxOut {
+                    value: Amount::from_sat(utxo.value),
+                    script_pubkey: from_script.clone(),
+                });
+            } else {
+                // Legacy输入签名需要完整的前序交易
+                let raw = self.utxo_source.raw_transaction(&utxo.txid).await?;
+                let prev_tx: Transaction = bitcoin::consensus::deserialize(&raw)
+                    .context("Failed to decode previous transaction")?;
+                psbt_input.non_witness_utxo = Some(prev_tx);
+            }
+            signer.apply(psbt_input);
+        }
+
+        Ok(UnsignedPsbt {
+            psbt_base64: base64::engine::general_purpose::STANDARD.encode(psbt.serialize()),
+            inputs: selection.selected,
+            fee: selection.fee,
+            fee_rate,
+            vsize: selection.vsize,
+            change: selection.change,
+            change_address: change_address.map(|addr| addr.to_string()),
+        })
+    }
+
+    /// 校验客户端提供的公钥与发送地址一致，生成各输入共用的签名元数据
+    fn signer_metadata(
+        &self,
+        request: &PsbtRequest,
+        from: &Address,
+        kind: ScriptKind,
+    ) -> Result<SignerMetadata> {
+        let origin = request
+            .key_origin
+            .as_deref()
+            .map(parse_key_origin)
+            .transpose()?;
+        let key_bytes = request
+            .public_key
+            .as_deref()
+            .map(|key| {
+                hex::decode(key.trim().trim_start_matches("0x"))
+                    .map_err(|e| anyhow::anyhow!("Invalid public_key hex: {}", e))
+            })
+            .transpose()?;
+
+        let Some(key_bytes) = key_bytes else {
+            return match kind {
+                ScriptKind::P2shP2wpkh => {
+                    anyhow::bail!("public_key is required to build redeem_script for P2SH-P2WPKH")
+                }
+                ScriptKind::P2tr => anyhow::bail!("public_key is required for Taproot inputs"),
+                ScriptKind::P2pkh | ScriptKind::P2wpkh => Ok(SignerMetadata::default()),
+            };
+        };
+
+        if kind == ScriptKind::P2tr {
+            let internal_key = match key_bytes.len() {
+                32 => XOnlyPublicKey::from_slice(&key_bytes)
+                    .map_err(|e| anyhow::anyhow!("Invalid x-only public key: {}", e))?,
+                _ => {
+                    parse_compressed_key(&key_bytes)?
+                        .inner
+                        .x_only_public_key()
+                        .0
+                }
+            };
+            // BIP86：仅 key-path，无脚本树
+            let expected = Address::p2tr(
+                &Secp256k1::verification_only(),
+                internal_key,
+                None,
+                self.network,
+            );
+            if expected != *from {
+                anyhow::bail!("public_key does not match Taproot address {}", from);
+            }
+            let origin = origin.context("key_origin is required for Taproot inputs")?;
+            return Ok(SignerMetadata {
+                tap_internal_key: Some(internal_key),
+                tap_key_origin: Some((internal_key, origin)),
+                ..Default::default()
+            });
+        }
+
+        let public_key = parse_compressed_key(&key_bytes)?;
+        let expected = match kind {
+            ScriptKind::P2pkh => Address::p2pkh(&public_key, self.network),
+            ScriptKind::P2shP2wpkh => Address::p2shwpkh(&public_key, self.network)?,
+            ScriptKind::P2wpkh => Address::p2wpkh(&public_key, self.network)?,
+            ScriptKind::P2tr => unreachable!("handled above"),
+        };
+        if expected != *from {
+            anyhow::bail!("public_key does not match address {}", from);
+        }
+
+        let redeem_script = match kind {
+            ScriptKind::P2shP2wpkh => Some(ScriptBuf::new_p2wpkh(
+                &public_key
+                    .wpubkey_hash()
+                    .context("P2SH-P2WPKH requires a compressed public key")?,
+            )),
+            _ => None,
+        };
+        Ok(SignerMetadata {
+            redeem_script,
+            bip32_derivation: origin.map(|origin| (public_key.inner, origin)),
+            ..Default::default()
+        })
+    }
+}
+
+/// 同一发送地址的全部输入共用的签名元数据
+#[derive(Debug, Default)]
+struct SignerMetadata {
+    redeem_script: Option<ScriptBuf>,
+    bip32_derivation: Option<(bitcoin::secp256k1::PublicKey, KeySource)>,
+    tap_internal_key: Option<XOnlyPublicKey>,
+    tap_key_origin: Option<(XOnlyPublicKey, KeySource)>,
+}
+
+impl SignerMetadata {
+    fn apply(&self, input: &mut PsbtInput) {
+        input.redeem_script = self.redeem_script.clone();
+        input.tap_internal_key = self.tap_internal_key;
+        if let Some((key, origin)) = &self.bip32_derivation {
+            input.bip32_derivation.insert(*key, origin.clone());
+        }
+        if let Some((key, origin)) = &self.tap_key_origin {
+            input
+                .tap_key_origins
+                .insert(*key, (Vec::new(), origin.clone()));
+        }
+    }
+}
+
+fn parse_compressed_key(bytes: &[u8]) -> Result<PublicKey> {
+    let key =
+        PublicKey::from_slice(bytes).map_err(|e| anyhow::anyhow!("Invalid public_key: {}", e))?;
+    if !key.compressed {
+        anyhow::bail!("public_key must be compressed (33 bytes)");
+    }
+    Ok(key)
+}
+
+/// 解析 BIP32 来源：`d34db33f/84'/0'/0'/0/0`，兼容描述符写法 `[d34db33f/84h/0h/0h/0/0]`
+pub fn parse_key_origin(origin: &str) -> Result<KeySource> {
+    let origin = origin.trim().trim_start_matches('[').trim_end_matches(']');
+    let (fingerprint, path) = origin.split_once('/').unwrap_or((origin, ""));
+    let fingerprint = Fingerprint::from_str(fingerprint)
+        .map_err(|e| anyhow::anyhow!("Invalid key_origin fingerprint {}: {}", fingerprint, e))?;
+    let path = DerivationPath::from_str(format!("m/{}", path).trim_end_matches('/'))
+        .map_err(|e| anyhow::anyhow!("Invalid key_origin path {}: {}", path, e))?;
+    Ok((fingerprint, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utxo(value: u64) -> Utxo {
+        Utxo {
+            txid: format!("{:064x}", value),
+            vout: 0,
+            value,
+            confirmed: true,
+        }
+    }
+
+    fn params(target: u64, fee_rate: u64) -> SelectionParams {
+        SelectionParams {
+            target,
+            fee_rate,
+            input_kind: ScriptKind::P2wpkh,
+            recipient_kind: ScriptKind::P2wpkh,
+            change_kind: ScriptKind::P2wpkh,
+        }
+    }
+
+    #[test]
+    fn test_bnb_finds_changeless_match() {
+        // 1 输入 + 1 输出 = 11 + 31 + 68 = 110 vB，费率 1 → 110 sat
+        let utxos = vec![utxo(500_000), utxo(100_110), utxo(30_000)];
+        let selection = select_coins(&utxos, &params(100_000, 1)).unwrap();
+
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.selected.len(), 1);
+        assert_eq!(selection.selected[0].value, 100_110);
+        assert_eq!(selection.fee, 110);
+    }
+
+    #[test]
+    fn test_fallback_produces_change() {
+        let utxos = vec![utxo(1_000_000), utxo(20_000)];
+        let selection = select_coins(&utxos, &params(100_000, 2)).unwrap();
+
+        assert_eq!(selection.selected.len(), 1);
+        assert!(selection.change > 0);
+        assert_eq!(
+            selection.selected[0].value,
+            100_000 + selection.fee + selection.change
+        );
+        assert_eq!(selection.fee, selection.vsize * 2);
+    }
+
+    #[test]
+    fn test_dust_change_goes_to_fee() {
+        // 11 + 31 + 68 = 110 vB；剩余 200 sat < 粉尘阈值
+        let utxos = vec![utxo(100_310)];
+        let selection = select_coins(&utxos, &params(100_000, 1)).unwrap();
+
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, 310);
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let utxos = vec![utxo(10_000), utxo(20_000)];
+        assert!(select_coins(&utxos, &params(100_000, 1)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_dust_amount() {
+        let utxos = vec![utxo(10_000)];
+        assert!(select_coins(&utxos, &params(100, 1)).is_err());
+    }
+
+    #[test]
+    fn test_script_kind_from_address() {
+        let cases = [
+            ("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", ScriptKind::P2pkh),
+            ("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", ScriptKind::P2shP2wpkh),
+            (
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                ScriptKind::P2wpkh,
+            ),
+            (
+                "bc1p5d7rjq7g6rdk2yhzks9smlaqtedr4dekq08ge8ztwac72sfr9rusxg3297",
+                ScriptKind::P2tr,
+            ),
+        ];
+
+        for (addr, expected) in cases {
+            let address = Address::from_str(addr)
+                .unwrap()
+                .require_network(Network::Bitcoin)
+                .unwrap();
+            assert_eq!(ScriptKind::from_address(&address).unwrap(), expected);
+        }
+
+        assert_eq!(ScriptKind::P2tr.address_format(), AddressFormat::Bech32m);
+        assert_eq!(ScriptKind::P2wpkh.address_format(), AddressFormat::Bech32);
+    }
+
+    /// 单个UTXO的数据源：前序交易向 `script` 支付 `value`
+    struct SingleUtxoSource {
+        prev_tx: Transaction,
+    }
+
+    impl SingleUtxoSource {
+        fn paying(script: ScriptBuf, value: u64) -> Self {
+            Self {
+                prev_tx: Transaction {
+                    version: Version::TWO,
+                    lock_time: LockTime::ZERO,
+                    input: vec![TxIn::default()],
+                    output: vec![TxOut {
+                        value: Amount::from_sat(value),
+                        script_pubkey: script,
+                    }],
+                },
+            }
+        }
+    }
+
+    #[async_trait]
+    impl UtxoSource for SingleUtxoSource {
+        async fn list_utxos(&self, _address: &str) -> Result<Vec<Utxo>> {
+            Ok(vec![Utxo {
+                txid: self.prev_tx.txid().to_string(),
+                vout: 0,
+                value: self.prev_tx.output[0].value.to_sat(),
+                confirmed: true,
+            }])
+        }
+
+        async fn fee_rate(&self, _target_blocks: u16) -> Result<u64> {
+            Ok(2)
+        }
+
+        async fn raw_transaction(&self, txid: &str) -> Result<Vec<u8>> {
+            assert_eq!(txid, self.prev_tx.txid().to_string());
+            Ok(bitcoin::consensus::serialize(&self.prev_tx))
+        }
+    }
+
+    const TESTNET: Network = Network::Testnet;
+    const ORIGIN: &str = "d34db33f/84'/1'/0'/0/0";
+
+    fn test_key() -> PublicKey {
+        let secp = Secp256k1::new();
+        let secret = bitcoin::secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+        PublicKey::new(bitcoin::secp256k1::PublicKey::from_secret_key(
+            &secp, &secret,
+        ))
+    }
+
+    fn change_address() -> String {
+        let secp = Secp256k1::new();
+        let secret = bitcoin::secp256k1::SecretKey::from_slice(&[9u8; 32]).unwrap();
+        let key = PublicKey::new(bitcoin::secp256k1::PublicKey::from_secret_key(
+            &secp, &secret,
+        ));
+        Address::p2wpkh(&key, TESTNET).unwrap().to_string()
+    }
+
+    async fn build_psbt(from: &Address, request: PsbtRequest) -> Result<(UnsignedPsbt, Psbt)> {
+        let source = SingleUtxoSource::paying(from.script_pubkey(), 1_000_000);
+        let builder = BitcoinPsbtBuilder::new(Arc::new(source), TESTNET);
+        let unsigned = builder
+            .build(&PsbtRequest {
+                from: from.to_string(),
+                to: "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string(),
+                amount: 100_000,
+                target_blocks: 6,
+                ..request
+            })
+            .await?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&unsigned.psbt_base64)
+            .unwrap();
+        let psbt = Psbt::deserialize(&bytes).unwrap();
+        Ok((unsigned, psbt))
+    }
+
+    #[test]
+    fn test_parse_network_and_key_origin() {
+        assert_eq!(parse_network("mainnet").unwrap(), Network::Bitcoin);
+        assert_eq!(parse_network("testnet").unwrap(), Network::Testnet);
+        assert_eq!(parse_network("Signet").unwrap(), Network::Signet);
+        assert!(parse_network("litecoin").is_err());
+
+        let (fingerprint, path) = parse_key_origin("[d34db33f/86h/0h/0h/0/0]").unwrap();
+        assert_eq!(fingerprint.to_string(), "d34db33f");
+        assert_eq!(path.to_string(), "m/86'/0'/0'/0/0");
+        assert!(parse_key_origin("nothex/0").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_legacy_input_uses_full_previous_tx() {
+        let key = test_key();
+        let from = Address::p2pkh(&key, TESTNET);
+
+        let (unsigned, psbt) = build_psbt(
+            &from,
+            PsbtRequest {
+                change_address: Some(change_address()),
+                public_key: Some(key.to_string()),
+                key_origin: Some(ORIGIN.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let input = &psbt.inputs[0];
+        assert!(input.witness_utxo.is_none());
+        let prev_tx = input.non_witness_utxo.as_ref().unwrap();
+        assert_eq!(
+            prev_tx.txid(),
+            psbt.unsigned_tx.input[0].previous_output.txid
+        );
+        assert_eq!(
+            input
+                .bip32_derivation
+                .get(&key.inner)
+                .unwrap()
+                .1
+                .to_string(),
+            "m/84'/1'/0'/0/0"
+        );
+        assert_eq!(unsigned.change_address, Some(change_address()));
+
+        // 主网地址不属于配置的测试网
+        let mainnet = Address::p2pkh(&key, Network::Bitcoin);
+        let source = SingleUtxoSource::paying(mainnet.script_pubkey(), 1_000_000);
+        let builder = BitcoinPsbtBuilder::new(Arc::new(source), TESTNET);
+        assert!(builder
+            .build(&PsbtRequest {
+                from: mainnet.to_string(),
+                to: from.to_string(),
+                change_address: Some(change_address()),
+                amount: 100_000,
+                target_blocks: 6,
+                ..Default::default()
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_p2sh_p2wpkh_input_has_redeem_script() {
+        let key = test_key();
+        let from = Address::p2shwpkh(&key, TESTNET).unwrap();
+
+        let (_, psbt) = build_psbt(
+            &from,
+            PsbtRequest {
+                change_address: Some(change_address()),
+                public_key: Some(key.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let input = &psbt.inputs[0];
+        assert_eq!(
+            input.redeem_script,
+            Some(ScriptBuf::new_p2wpkh(&key.wpubkey_hash().unwrap()))
+        );
+        assert_eq!(
+            input.witness_utxo.as_ref().unwrap().script_pubkey,
+            from.script_pubkey()
+        );
+        assert!(input.non_witness_utxo.is_none());
+
+        // 缺少公钥无法生成 redeem_script；公钥与地址不符时拒绝
+        let missing = PsbtRequest {
+            change_address: Some(change_address()),
+            ..Default::default()
+        };
+        assert!(build_psbt(&from, missing).await.is_err());
+        let other_key = PsbtRequest {
+            change_address: Some(change_address()),
+            public_key: Some(
+                "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".to_string(),
+            ),
+            ..Default::default()
+        };
+        assert!(build_psbt(&from, other_key).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_p2tr_input_has_internal_key_and_origin() {
+        let key = test_key();
+        let internal_key = key.inner.x_only_public_key().0;
+        let from = Address::p2tr(&Secp256k1::verification_only(), internal_key, None, TESTNET);
+
+        let (_, psbt) = build_psbt(
+            &from,
+            PsbtRequest {
+                change_address: Some(change_address()),
+                public_key: Some(internal_key.to_string()),
+                key_origin: Some("d34db33f/86'/1'/0'/0/0".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let input = &psbt.inputs[0];
+        assert_eq!(input.tap_internal_key, Some(internal_key));
+        let (leaves, (fingerprint, path)) = input.tap_key_origins.get(&internal_key).unwrap();
+        assert!(leaves.is_empty());
+        assert_eq!(fingerprint.to_string(), "d34db33f");
+        assert_eq!(path.to_string(), "m/86'/1'/0'/0/0");
+        assert!(input.redeem_script.is_none());
+
+        // Taproot 输入必须提供 BIP32 来源
+        let no_origin = PsbtRequest {
+            change_address: Some(change_address()),
+            public_key: Some(key.to_string()),
+            ..Default::default()
+        };
+        assert!(build_psbt(&from, no_origin).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_change_requires_explicit_change_address() {
+        let key = test_key();
+        let from = Address::p2wpkh(&key, TESTNET).unwrap();
+
+        let err = build_psbt(&from, PsbtRequest::default()).await.unwrap_err();
+        assert!(err.to_string().contains("change_address is required"));
+    }
+}
@@ -2,13 +2,15 @@
 // 支持真实RPC广播、故障转移、重试机制
 // 企业级实现：支持EVM链和非EVM链（Solana、Bitcoin、TON）
 
-use std::{sync::Arc, time::Duration};
+use std::{future::Future, sync::Arc, time::Duration};
 
 use anyhow::{Context, Result};
 use base64::Engine;
 use hex;
 use serde::{Deserialize, Serialize};
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY_MS: u64 = 1000;
 
@@ -46,6 +48,18 @@ fn is_evm_chain(chain: &str) -> bool {
     crate::utils::chain_normalizer::is_evm_chain(chain)
 }
 
+/// 执行一次RPC调用，按链/端点记录耗时直方图与成功率
+async fn observe_rpc<T>(
+    chain: &str,
+    endpoint_url: &str,
+    call: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let started = std::time::Instant::now();
+    let result = call.await;
+    crate::metrics::observe_rpc_request(chain, endpoint_url, started.elapsed(), result.is_ok());
+    result
+}
+
 impl BlockchainClient {
     pub fn new(rpc_selector: Arc<crate::infrastructure::rpc_selector::RpcSelector>) -> Self {
         let client = reqwest::Client::builder()
@@ -64,6 +78,7 @@ impl BlockchainClient {
 
     /// 广播已签名的交易到区块链网络（带重试和故障转移）
     /// 企业级实现：支持EVM链和非EVM链
+    #[tracing::instrument(skip_all, fields(chain = %req.chain))]
     pub async fn broadcast_transaction(
         &self,
         req: BroadcastTransactionRequest,
@@ -115,9 +130,12 @@ impl BlockchainClient {
                         "Attempting to broadcast EVM transaction"
                     );
 
-                    match self
-                        .send_raw_transaction(&endpoint.url, &req.signed_raw_tx)
-                        .await
+                    match observe_rpc(
+                        &chain_lower,
+                        &endpoint.url,
+                        self.send_raw_transaction(&endpoint.url, &req.signed_raw_tx),
+                    )
+                    .await
                     {
                         Ok(tx_hash) => {
                             crate::metrics::inc_blockchain_broadcast_success(&chain_lower);
@@ -205,22 +223,27 @@ impl BlockchainClient {
                     ]
                 });
 
-                let response = self
-                    .http_client
-                    .post(&endpoint.url)
-                    .json(&payload)
-                    .send()
-                    .await
-                    .context("Failed to send Solana RPC request")?;
+                let json = observe_rpc(chain_lower, &endpoint.url, async {
+                    let response = self
+                        .http_client
+                        .post(&endpoint.url)
+                        .json(&payload)
+                        .with_trace_context()
+                        .send()
+                        .await
+                        .context("Failed to send Solana RPC request")?;
 
-                let json: serde_json::Value = response
-                    .json()
-                    .await
-                    .context("Failed to parse Solana RPC response")?;
+                    let json: serde_json::Value = response
+                        .json()
+                        .await
+                        .context("Failed to parse Solana RPC response")?;
 
-                if let Some(error) = json.get("error") {
-                    anyhow::bail!("Solana RPC error: {:?}", error);
-                }
+                    if let Some(error) = json.get("error") {
+                        anyhow::bail!("Solana RPC error: {:?}", error);
+                    }
+                    Ok(json)
+                })
+                .await?;
 
                 let signature = json
                     .get("result")
@@ -261,22 +284,27 @@ impl BlockchainClient {
                     "params": [tx_hex]
                 });
 
-                let response = self
-                    .http_client
-                    .post(&endpoint.url)
-                    .json(&payload)
-                    .send()
-                    .await
-                    .context("Failed to send Bitcoin RPC request")?;
+                let json = observe_rpc(chain_lower, &endpoint.url, async {
+                    let response = self
+                        .http_client
+                        .post(&endpoint.url)
+                        .json(&payload)
+                        .with_trace_context()
+                        .send()
+                        .await
+                        .context("Failed to send Bitcoin RPC request")?;
 
-                let json: serde_json::Value = response
-                    .json()
-                    .await
-                    .context("Failed to parse Bitcoin RPC response")?;
+                    let json: serde_json::Value = response
+                        .json()
+                        .await
+                        .context("Failed to parse Bitcoin RPC response")?;
 
-                if let Some(error) = json.get("error") {
-                    anyhow::bail!("Bitcoin RPC error: {:?}", error);
-                }
+                    if let Some(error) = json.get("error") {
+                        anyhow::bail!("Bitcoin RPC error: {:?}", error);
+                    }
+                    Ok(json)
+                })
+                .await?;
 
                 let txid = json
                     .get("result")
@@ -323,22 +351,27 @@ impl BlockchainClient {
                     }
                 });
 
-                let response = self
-                    .http_client
-                    .post(&endpoint.url)
-                    .json(&payload)
-                    .send()
-                    .await
-                    .context("Failed to send TON RPC request")?;
+                let json = observe_rpc(chain_lower, &endpoint.url, async {
+                    let response = self
+                        .http_client
+                        .post(&endpoint.url)
+                        .json(&payload)
+                        .with_trace_context()
+                        .send()
+                        .await
+                        .context("Failed to send TON RPC request")?;
 
-                let json: serde_json::Value = response
-                    .json()
-                    .await
-                    .context("Failed to parse TON RPC response")?;
+                    let json: serde_json::Value = response
+                        .json()
+                        .await
+                        .context("Failed to parse TON RPC response")?;
 
-                if let Some(error) = json.get("error") {
-                    anyhow::bail!("TON RPC error: {:?}", error);
-                }
+                    if let Some(error) = json.get("error") {
+                        anyhow::bail!("TON RPC error: {:?}", error);
+                    }
+                    Ok(json)
+                })
+                .await?;
 
                 // TON返回的可能是不同的格式，需要根据实际API调整
                 let tx_hash = json
@@ -362,6 +395,7 @@ impl BlockchainClient {
     }
 
     /// 查询交易回执（用于回填fee_audit）
+    #[tracing::instrument(skip_all, fields(chain = %chain, tx_hash = %tx_hash))]
     pub async fn get_transaction_receipt(
         &self,
         chain: &str,
@@ -375,9 +409,12 @@ impl BlockchainClient {
             .await
             .context("No healthy RPC endpoint available")?;
 
-        let receipt = self
-            .fetch_transaction_receipt(&endpoint.url, tx_hash, &chain_lower)
-            .await?;
+        let receipt = observe_rpc(
+            &chain_lower,
+            &endpoint.url,
+            self.fetch_transaction_receipt(&endpoint.url, tx_hash, &chain_lower),
+        )
+        .await?;
 
         Ok(receipt)
     }
@@ -396,6 +433,7 @@ impl BlockchainClient {
             .post(rpc_url)
             .header("Content-Type", "application/json")
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send RPC request")?;
@@ -441,6 +479,7 @@ impl BlockchainClient {
     }
 
     /// 获取ERC20代币余额（企业级实现）
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_erc20_balance(
         &self,
         chain: &str,
@@ -478,23 +517,27 @@ impl BlockchainClient {
             ]
         });
 
-        let response = self
-            .http_client
-            .post(&endpoint.url)
-            .json(&request_body)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await
-            .context("Failed to call RPC endpoint")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("RPC call failed with status: {}", response.status());
-        }
+        let json: serde_json::Value = observe_rpc(&chain_lower, &endpoint.url, async {
+            let response = self
+                .http_client
+                .post(&endpoint.url)
+                .json(&request_body)
+                .timeout(Duration::from_secs(10))
+                .with_trace_context()
+                .send()
+                .await
+                .context("Failed to call RPC endpoint")?;
+
+            if !response.status().is_success() {
+                anyhow::bail!("RPC call failed with status: {}", response.status());
+            }
 
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse RPC response")?;
+            response
+                .json()
+                .await
+                .context("Failed to parse RPC response")
+        })
+        .await?;
 
         // 验证RPC响应格式
         crate::infrastructure::rpc_validator::validate_rpc_response(&json)
@@ -513,6 +556,7 @@ impl BlockchainClient {
     }
 
     /// 获取原生代币余额（ETH/BNB/MATIC等）
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_native_balance(&self, chain: &str, wallet_address: &str) -> Result<u128> {
         let chain_lower = chain.to_lowercase();
 
@@ -531,23 +575,27 @@ impl BlockchainClient {
             "params": [wallet_address, "latest"]
         });
 
-        let response = self
-            .http_client
-            .post(&endpoint.url)
-            .json(&request_body)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await
-            .context("Failed to call RPC endpoint")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("RPC call failed with status: {}", response.status());
-        }
+        let json: serde_json::Value = observe_rpc(&chain_lower, &endpoint.url, async {
+            let response = self
+                .http_client
+                .post(&endpoint.url)
+                .json(&request_body)
+                .timeout(Duration::from_secs(10))
+                .with_trace_context()
+                .send()
+                .await
+                .context("Failed to call RPC endpoint")?;
+
+            if !response.status().is_success() {
+                anyhow::bail!("RPC call failed with status: {}", response.status());
+            }
 
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse RPC response")?;
+            response
+                .json()
+                .await
+                .context("Failed to parse RPC response")
+        })
+        .await?;
 
         // 验证RPC响应格式
         crate::infrastructure::rpc_validator::validate_rpc_response(&json)
@@ -566,7 +614,23 @@ impl BlockchainClient {
     }
 
     /// 获取交易计数（用于nonce管理）
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_transaction_count(&self, chain: &str, address: &str) -> Result<u64> {
+        self.transaction_count_at(chain, address, "latest").await
+    }
+
+    /// 获取包含内存池交易的交易计数（只读，用于构建待签名交易）
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
+    pub async fn get_pending_transaction_count(&self, chain: &str, address: &str) -> Result<u64> {
+        self.transaction_count_at(chain, address, "pending").await
+    }
+
+    async fn transaction_count_at(
+        &self,
+        chain: &str,
+        address: &str,
+        block_tag: &str,
+    ) -> Result<u64> {
         let chain_lower = chain.to_lowercase();
 
         // 选择健康的RPC端点
@@ -581,26 +645,30 @@ impl BlockchainClient {
             "jsonrpc": "2.0",
             "id": 1,
             "method": "eth_getTransactionCount",
-            "params": [address, "latest"]
+            "params": [address, block_tag]
         });
 
-        let response = self
-            .http_client
-            .post(&endpoint.url)
-            .json(&request_body)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await
-            .context("Failed to call RPC endpoint")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("RPC call failed with status: {}", response.status());
-        }
+        let json: serde_json::Value = observe_rpc(&chain_lower, &endpoint.url, async {
+            let response = self
+                .http_client
+                .post(&endpoint.url)
+                .json(&request_body)
+                .timeout(Duration::from_secs(10))
+                .with_trace_context()
+                .send()
+                .await
+                .context("Failed to call RPC endpoint")?;
+
+            if !response.status().is_success() {
+                anyhow::bail!("RPC call failed with status: {}", response.status());
+            }
 
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse RPC response")?;
+            response
+                .json()
+                .await
+                .context("Failed to parse RPC response")
+        })
+        .await?;
 
         // 验证RPC响应格式
         crate::infrastructure::rpc_validator::validate_rpc_response(&json)
@@ -620,6 +688,7 @@ impl BlockchainClient {
 
     /// 获取链高（区块高度）
     /// 企业级实现：支持EVM链和非EVM链
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_block_number(&self, chain: &str) -> Result<u64> {
         let chain_lower = chain.to_lowercase();
 
@@ -652,28 +721,33 @@ impl BlockchainClient {
             "id": 1
         });
 
-        let response = self
-            .http_client
-            .post(&endpoint.url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await
-            .context("Failed to send RPC request")?;
-
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse JSON response")?;
-
-        // 检查错误
-        if let Some(error) = json.get("error") {
-            let error_msg = error
-                .get("message")
-                .and_then(|m| m.as_str())
-                .unwrap_or("Unknown RPC error");
-            anyhow::bail!("RPC error: {}", error_msg);
-        }
+        let json: serde_json::Value = observe_rpc(chain, &endpoint.url, async {
+            let response = self
+                .http_client
+                .post(&endpoint.url)
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .with_trace_context()
+                .send()
+                .await
+                .context("Failed to send RPC request")?;
+
+            let json: serde_json::Value = response
+                .json()
+                .await
+                .context("Failed to parse JSON response")?;
+
+            // 检查错误
+            if let Some(error) = json.get("error") {
+                let error_msg = error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown RPC error");
+                anyhow::bail!("RPC error: {}", error_msg);
+            }
+            Ok(json)
+        })
+        .await?;
 
         // 解析区块高度（十六进制字符串）
         let hex_str = json
@@ -702,6 +776,7 @@ impl BlockchainClient {
             .http_client
             .post(&rpc_url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call Solana RPC")?;
@@ -733,6 +808,7 @@ impl BlockchainClient {
         let response = self
             .http_client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call Bitcoin API")?;
@@ -773,6 +849,7 @@ impl BlockchainClient {
         let response: TonResponse = self
             .http_client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to call TON API")?
@@ -806,6 +883,7 @@ impl BlockchainClient {
             .post(rpc_url)
             .header("Content-Type", "application/json")
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send RPC request")?;
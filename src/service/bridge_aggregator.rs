@@ -0,0 +1,505 @@
+//! 跨链桥报价聚合器
+//!
+//! 企业级实现：并行向所有支持该链对的桥询价，按净到账、预计时间与桥风险评分排序，
+//! 全部候选返回给客户端，由用户或服务端选择最终执行的桥。
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    bridge_sdk::{
+        AxelarBridge, BridgeQuote, BridgeQuoteRequest, BridgeSDK, BridgeType, LayerZeroBridge,
+        WormholeBridge,
+    },
+    price_service::PriceService,
+};
+use crate::{domain::token_amount::parse_decimal_strict, utils::chain_normalizer};
+
+/// 排序权重（单位：基点）
+///
+/// 得分 = 净到账 / 输入 × 10000 − 预计分钟 × `eta_bps_per_minute` − 风险分 × `risk_bps_per_point`
+#[derive(Debug, Clone)]
+pub struct RankingWeights {
+    pub eta_bps_per_minute: Decimal,
+    pub risk_bps_per_point: Decimal,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            eta_bps_per_minute: Decimal::ONE,
+            risk_bps_per_point: Decimal::ONE,
+        }
+    }
+}
+
+impl RankingWeights {
+    /// `BRIDGE_RANK_ETA_BPS_PER_MIN` / `BRIDGE_RANK_RISK_BPS_PER_POINT`
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: Decimal| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<Decimal>().ok())
+                .filter(|v| !v.is_sign_negative())
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            eta_bps_per_minute: read("BRIDGE_RANK_ETA_BPS_PER_MIN", defaults.eta_bps_per_minute),
+            risk_bps_per_point: read(
+                "BRIDGE_RANK_RISK_BPS_PER_POINT",
+                defaults.risk_bps_per_point,
+            ),
+        }
+    }
+}
+
+/// 单个桥的候选方案（已排序）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BridgeOption {
+    /// 排名（从 1 开始）
+    pub rank: u32,
+    /// 桥协议：wormhole / layerzero / axelar
+    pub protocol: String,
+    #[schema(value_type = String)]
+    pub amount_in: Decimal,
+    /// 目标链到账数量（已扣除以代币计价的协议费）
+    #[schema(value_type = String)]
+    pub amount_out: Decimal,
+    #[schema(value_type = String)]
+    pub protocol_fee: Decimal,
+    /// 源链原生币计价的消息费
+    #[schema(value_type = Option<String>)]
+    pub native_fee: Option<Decimal>,
+    pub native_fee_symbol: Option<String>,
+    /// 净到账 = 到账数量 − 原生币费用折算为代币
+    #[schema(value_type = String)]
+    pub net_received: Decimal,
+    /// 原生币费用是否已按价格折算（未折算的方案排在已折算之后）
+    pub fees_priced: bool,
+    pub eta_secs: u64,
+    /// 桥风险评分（0-100，越高风险越大）
+    pub risk_score: u8,
+    /// 综合得分（基点）
+    #[schema(value_type = String)]
+    pub score: Decimal,
+}
+
+/// 跨链桥报价聚合器
+pub struct BridgeAggregator {
+    bridges: Vec<Arc<dyn BridgeSDK>>,
+    price_service: Option<Arc<PriceService>>,
+    weights: RankingWeights,
+    timeout: Duration,
+}
+
+impl BridgeAggregator {
+    pub fn new(bridges: Vec<Arc<dyn BridgeSDK>>) -> Self {
+        Self {
+            bridges,
+            price_service: None,
+            weights: RankingWeights::default(),
+            timeout: Duration::from_secs(8),
+        }
+    }
+
+    /// 从环境变量构建：未配置凭证的桥被跳过
+    pub fn from_env() -> Self {
+        let mut bridges: Vec<Arc<dyn BridgeSDK>> = Vec::new();
+        match WormholeBridge::from_env() {
+            Ok(b) => bridges.push(Arc::new(b)),
+            Err(e) => tracing::warn!("Wormhole quotes disabled: {}", e),
+        }
+        match LayerZeroBridge::from_env() {
+            Ok(b) => bridges.push(Arc::new(b)),
+            Err(e) => tracing::warn!("LayerZero quotes disabled: {}", e),
+        }
+        match AxelarBridge::from_env() {
+            Ok(b) => bridges.push(Arc::new(b)),
+            Err(e) => tracing::warn!("Axelar quotes disabled: {}", e),
+        }
+
+        let timeout_ms = std::env::var("BRIDGE_QUOTE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(8_000);
+
+        let mut aggregator = Self::new(bridges);
+        aggregator.weights = RankingWeights::from_env();
+        aggregator.timeout = Duration::from_millis(timeout_ms);
+        aggregator
+    }
+
+    /// 使用价格服务折算原生币消息费
+    pub fn with_price_service(mut self, price_service: Arc<PriceService>) -> Self {
+        self.price_service = Some(price_service);
+        self
+    }
+
+    pub fn with_weights(mut self, weights: RankingWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 并行询价并排序；没有任何可用报价时返回错误
+    #[tracing::instrument(skip_all)]
+    pub async fn quote_all(&self, request: &BridgeQuoteRequest) -> Result<Vec<BridgeOption>> {
+        if request.amount <= Decimal::ZERO {
+            bail!("Bridge amount must be greater than 0");
+        }
+        let request = BridgeQuoteRequest {
+            source_chain: chain_normalizer::normalize_chain_identifier(&request.source_chain)?,
+            target_chain: chain_normalizer::normalize_chain_identifier(&request.target_chain)?,
+            token: request.token.trim().to_uppercase(),
+            amount: request.amount,
+        };
+        if request.source_chain == request.target_chain {
+            bail!("Source and target chains must be different for cross-chain swap");
+        }
+
+        let candidates: Vec<&Arc<dyn BridgeSDK>> = self
+            .bridges
+            .iter()
+            .filter(|b| b.supports(&request.source_chain, &request.target_chain))
+            .collect();
+        if candidates.is_empty() {
+            bail!(
+                "No bridge supports {} -> {}",
+                request.source_chain,
+                request.target_chain
+            );
+        }
+
+        let request_ref = &request;
+        let results = futures::future::join_all(candidates.into_iter().map(|bridge| async move {
+            let outcome = tokio::time::timeout(self.timeout, bridge.quote(request_ref)).await;
+            (bridge.bridge_type(), outcome)
+        }))
+        .await;
+
+        let mut quotes = Vec::new();
+        let mut failures = Vec::new();
+        for (bridge, outcome) in results {
+            match outcome {
+                Ok(Ok(quote)) => quotes.push(quote),
+                Ok(Err(e)) => {
+                    tracing::warn!(bridge = %bridge, error = %e, "Bridge quote failed");
+                    failures.push(format!("{}: {}", bridge, e));
+                }
+                Err(_) => {
+                    tracing::warn!(bridge = %bridge, "Bridge quote timed out");
+                    failures.push(format!("{}: timed out", bridge));
+                }
+            }
+        }
+        if quotes.is_empty() {
+            bail!("All bridge quotes failed ({})", failures.join("; "));
+        }
+
+        let mut options = Vec::with_capacity(quotes.len());
+        for quote in quotes {
+            let native_in_token = match quote.native_fee {
+                Some(fee) if fee > Decimal::ZERO => self.native_fee_in_token(&request, fee).await,
+                _ => Some(Decimal::ZERO),
+            };
+            options.push(build_option(
+                &request,
+                quote,
+                native_in_token,
+                &self.weights,
+            ));
+        }
+        Ok(rank_options(options))
+    }
+
+    /// 源链原生币费用折算为转账代币数量
+    async fn native_fee_in_token(
+        &self,
+        request: &BridgeQuoteRequest,
+        native_fee: Decimal,
+    ) -> Option<Decimal> {
+        let native_symbol = chain_normalizer::get_chain_symbol(&request.source_chain).ok()?;
+        if native_symbol.eq_ignore_ascii_case(&request.token) {
+            return Some(native_fee);
+        }
+        let price_service = self.price_service.as_ref()?;
+        let prices = futures::future::try_join(
+            price_service.get_price_decimal(native_symbol),
+            price_service.get_price_decimal(&request.token),
+        )
+        .await;
+        match prices {
+            Ok((native_price, token_price)) if token_price > Decimal::ZERO => {
+                Some(native_fee * native_price / token_price)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(
+                    native = native_symbol,
+                    token = %request.token,
+                    error = %e,
+                    "Cannot price bridge native fee; ranking option after priced ones"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// 桥风险评分：`BRIDGE_RISK_SCORE_<PROTOCOL>` 覆盖默认值
+pub fn risk_score(bridge: &BridgeType) -> u8 {
+    std::env::var(format!(
+        "BRIDGE_RISK_SCORE_{}",
+        bridge.as_str().to_uppercase()
+    ))
+    .ok()
+    .and_then(|v| v.parse::<u8>().ok())
+    .filter(|v| *v <= 100)
+    .unwrap_or_else(|| bridge.default_risk_score())
+}
+
+fn build_option(
+    request: &BridgeQuoteRequest,
+    quote: BridgeQuote,
+    native_fee_in_token: Option<Decimal>,
+    weights: &RankingWeights,
+) -> BridgeOption {
+    let fees_priced = native_fee_in_token.is_some();
+    let net_received =
+        (quote.amount_out - native_fee_in_token.unwrap_or(Decimal::ZERO)).max(Decimal::ZERO);
+    let risk = risk_score(&quote.bridge);
+    let eta_minutes = Decimal::from(quote.eta_secs) / Decimal::from(60);
+    let score = net_received / request.amount * Decimal::from(10_000)
+        - eta_minutes * weights.eta_bps_per_minute
+        - Decimal::from(risk) * weights.risk_bps_per_point;
+
+    BridgeOption {
+        rank: 0,
+        protocol: quote.bridge.to_string(),
+        amount_in: request.amount,
+        amount_out: quote.amount_out,
+        protocol_fee: quote.protocol_fee,
+        native_fee: quote.native_fee,
+        native_fee_symbol: quote.native_fee.and_then(|_| {
+            chain_normalizer::get_chain_symbol(&request.source_chain)
+                .ok()
+                .map(str::to_string)
+        }),
+        net_received,
+        fees_priced,
+        eta_secs: quote.eta_secs,
+        risk_score: risk,
+        score: score.round_dp(4),
+    }
+}
+
+/// 排序：已折算费用的方案优先，其次按得分降序
+pub fn rank_options(mut options: Vec<BridgeOption>) -> Vec<BridgeOption> {
+    options.sort_by(|a, b| {
+        b.fees_priced
+            .cmp(&a.fees_priced)
+            .then_with(|| b.score.cmp(&a.score))
+            .then_with(|| a.eta_secs.cmp(&b.eta_secs))
+    });
+    for (i, option) in options.iter_mut().enumerate() {
+        option.rank = i as u32 + 1;
+    }
+    options
+}
+
+/// 解析报价请求金额（严格十进制字符串，拒绝科学计数法与浮点特殊值）
+pub fn decimal_amount(amount: &str) -> Result<Decimal> {
+    let amount = parse_decimal_strict(amount)
+        .map_err(|e| anyhow::anyhow!("Invalid amount {:?}: {}", amount, e))?;
+    if amount <= Decimal::ZERO {
+        bail!("Invalid amount: must be greater than 0");
+    }
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::bridge_sdk::{BridgeRequest, BridgeStatus};
+
+    struct FixedBridge {
+        bridge: BridgeType,
+        amount_out: &'static str,
+        native_fee: Option<&'static str>,
+        eta_secs: u64,
+        fail: bool,
+    }
+
+    #[axum::async_trait]
+    impl BridgeSDK for FixedBridge {
+        fn bridge_type(&self) -> BridgeType {
+            self.bridge.clone()
+        }
+
+        fn supports(&self, source_chain: &str, target_chain: &str) -> bool {
+            source_chain != "bitcoin" && target_chain != "bitcoin"
+        }
+
+        async fn quote(&self, request: &BridgeQuoteRequest) -> Result<BridgeQuote> {
+            if self.fail {
+                bail!("upstream 502");
+            }
+            let amount_out: Decimal = self.amount_out.parse()?;
+            Ok(BridgeQuote {
+                bridge: self.bridge.clone(),
+                amount_out,
+                protocol_fee: request.amount - amount_out,
+                native_fee: self.native_fee.map(|f| f.parse().unwrap()),
+                eta_secs: self.eta_secs,
+            })
+        }
+
+        async fn lock_asset(&self, _request: &BridgeRequest) -> Result<String> {
+            bail!("not used by quote tests")
+        }
+
+        async fn generate_proof(&self, _tx_hash: &str) -> Result<String> {
+            bail!("not used by quote tests")
+        }
+
+        async fn mint_on_target(&self, _proof: &str, _request: &BridgeRequest) -> Result<String> {
+            bail!("not used by quote tests")
+        }
+
+        async fn query_status(&self, _tx_hash: &str) -> Result<BridgeStatus> {
+            bail!("not used by quote tests")
+        }
+    }
+
+    fn fixed(
+        bridge: BridgeType,
+        amount_out: &'static str,
+        native_fee: Option<&'static str>,
+        eta_secs: u64,
+    ) -> Arc<dyn BridgeSDK> {
+        Arc::new(FixedBridge {
+            bridge,
+            amount_out,
+            native_fee,
+            eta_secs,
+            fail: false,
+        })
+    }
+
+    fn usdc_request(amount: &str) -> BridgeQuoteRequest {
+        BridgeQuoteRequest {
+            source_chain: "eth".into(),
+            target_chain: "Polygon".into(),
+            token: "usdc".into(),
+            amount: amount.parse().unwrap(),
+        }
+    }
+
+    /// Test 1: 净到账相近时，更快、风险更低的桥排名靠前；失败的桥被忽略
+    #[tokio::test]
+    async fn test_quote_all_ranks_by_net_eta_and_risk() {
+        let aggregator = BridgeAggregator::new(vec![
+            fixed(BridgeType::Wormhole, "999.25", None, 1_080),
+            fixed(BridgeType::Axelar, "998.5", None, 960),
+            fixed(BridgeType::LayerZero, "999.40", Some("0.0021"), 240),
+            Arc::new(FixedBridge {
+                bridge: BridgeType::Axelar,
+                amount_out: "0",
+                native_fee: None,
+                eta_secs: 0,
+                fail: true,
+            }),
+        ]);
+
+        let options = aggregator.quote_all(&usdc_request("1000")).await.unwrap();
+        assert_eq!(options.len(), 3);
+
+        // LayerZero 的 ETH 消息费无价格服务可折算 → 排在最后
+        assert_eq!(options[2].protocol, "layerzero");
+        assert!(!options[2].fees_priced);
+        assert_eq!(options[2].native_fee_symbol.as_deref(), Some("ETH"));
+
+        // Wormhole: 9992.5 − 18 − 35 = 9939.5；Axelar: 9985 − 16 − 25 = 9944
+        assert_eq!(options[0].protocol, "axelar");
+        assert_eq!(options[0].rank, 1);
+        assert_eq!(options[0].score, Decimal::from(9_944));
+        assert_eq!(options[1].protocol, "wormhole");
+        assert_eq!(options[1].score, "9939.5".parse().unwrap());
+        assert_eq!(options[1].net_received, "999.25".parse().unwrap());
+    }
+
+    /// Test 2: 原生币即转账代币时直接扣除消息费
+    #[tokio::test]
+    async fn test_native_fee_in_same_token() {
+        let aggregator = BridgeAggregator::new(vec![fixed(
+            BridgeType::LayerZero,
+            "1.0",
+            Some("0.002"),
+            300,
+        )])
+        .with_weights(RankingWeights {
+            eta_bps_per_minute: Decimal::ZERO,
+            risk_bps_per_point: Decimal::ZERO,
+        });
+
+        let mut request = usdc_request("1.0");
+        request.token = "ETH".into();
+        let options = aggregator.quote_all(&request).await.unwrap();
+        assert!(options[0].fees_priced);
+        assert_eq!(options[0].net_received, "0.998".parse().unwrap());
+        assert_eq!(options[0].score, Decimal::from(9_980));
+    }
+
+    /// Test 3: 链对不受支持或全部失败时返回错误
+    #[tokio::test]
+    async fn test_quote_all_errors() {
+        let aggregator = BridgeAggregator::new(vec![fixed(BridgeType::Wormhole, "1", None, 60)]);
+        let mut request = usdc_request("10");
+        request.target_chain = "bitcoin".into();
+        let err = aggregator.quote_all(&request).await.unwrap_err();
+        assert!(err.to_string().contains("No bridge supports"), "{}", err);
+
+        let failing = BridgeAggregator::new(vec![Arc::new(FixedBridge {
+            bridge: BridgeType::Wormhole,
+            amount_out: "0",
+            native_fee: None,
+            eta_secs: 0,
+            fail: true,
+        })]);
+        let err = failing.quote_all(&usdc_request("10")).await.unwrap_err();
+        assert!(
+            err.to_string().contains("wormhole: upstream 502"),
+            "{}",
+            err
+        );
+
+        assert!(aggregator.quote_all(&usdc_request("0")).await.is_err());
+    }
+
+    #[test]
+    fn test_decimal_amount_is_strict() {
+        assert_eq!(
+            decimal_amount("0.1").unwrap(),
+            Decimal::from_str_exact("0.1").unwrap()
+        );
+        assert_eq!(
+            decimal_amount("123456789.123456789").unwrap().to_string(),
+            "123456789.123456789"
+        );
+        assert!(decimal_amount("0").is_err());
+        assert!(decimal_amount("-1").is_err());
+        assert!(decimal_amount("1e3").is_err());
+        assert!(decimal_amount("NaN").is_err());
+        assert!(decimal_amount("").is_err());
+    }
+}
@@ -9,6 +9,8 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 /// 广播重试策略
 #[derive(Debug, Clone)]
 pub struct RetryStrategy {
@@ -169,6 +171,7 @@ impl BroadcastReliabilityEnhancer {
             .post(rpc_url)
             .json(&payload)
             .timeout(Duration::from_secs(30))
+            .with_trace_context()
             .send()
             .await?;
 
@@ -230,6 +233,7 @@ impl BroadcastReliabilityEnhancer {
             .post(&endpoint.url)
             .json(&payload)
             .timeout(Duration::from_secs(10))
+            .with_trace_context()
             .send()
             .await?;
 
@@ -2,26 +2,36 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use super::{fee_service::FeeService, price_service::PriceService};
-use crate::repository::wallet_repository::WalletRepository;
+use super::{
+    bridge_aggregator::{BridgeAggregator, BridgeOption},
+    bridge_sdk::BridgeQuoteRequest,
+    fee_service::FeeService,
+    price_service::PriceService,
+    token_service::TokenService,
+};
+use crate::{repository::wallet_repository::WalletRepository, utils::chain_normalizer};
 
 /// 跨链兑换请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossChainSwapRequest {
     #[serde(skip_deserializing)]
     pub user_id: Uuid,
-    pub source_chain: String, // eth, bsc, polygon
-    pub source_token: String, // ETH, BNB, MATIC
-    pub source_amount: f64,   // 用户输入的数量
+    pub source_chain: String,   // eth, bsc, polygon
+    pub source_token: String,   // ETH, BNB, MATIC
+    pub source_amount: Decimal, // 用户输入的数量
     #[serde(default)]
     pub source_wallet_id: Uuid, // 自动从用户钱包列表中选择
-    pub target_chain: String, // sol, avax
-    pub target_token: String, // SOL, AVAX
+    pub target_chain: String,   // sol, avax
+    pub target_token: String,   // SOL, AVAX
     pub target_wallet_id: Option<Uuid>, // 可选：自动创建
+    /// 可选：指定报价候选中的桥协议（默认取排名第一）
+    #[serde(default)]
+    pub bridge_protocol: Option<String>,
 }
 
 /// 跨链兑换响应
@@ -52,21 +62,23 @@ pub struct SwapQuote {
     pub total_fee_percentage: f64, // 总手续费率
     pub estimated_time_minutes: u32,
     pub recommended_protocol: String, // 推荐桥协议
+    /// 所有可用桥的候选方案（按综合得分排序）
+    pub options: Vec<BridgeOption>,
 }
 
 /// 跨链桥服务（企业级实现）
 ///
 /// 业务逻辑：
 /// - 跨链桥收取：跨链费用（bridge_fee）+ 平台服务费（platform_fee）
-/// - 跨链费用：跨链桥协议收取的费用
+/// - 跨链费用：跨链桥协议收取的费用（来自各桥实时报价）
 /// - 平台服务费：钱包服务商收取的服务费用（通过FeeService计算）
 ///
 /// 注意：这两个费用是完全独立的，不能混淆！
 pub struct CrossChainBridgeService {
     pool: PgPool,
     price_service: Arc<PriceService>,
-    config: Arc<crate::config::CrossChainConfig>,
-    fee_service: Arc<FeeService>, // 企业级实现：用于计算平台服务费
+    aggregator: Arc<BridgeAggregator>, // 企业级实现：多桥实时报价聚合
+    fee_service: Arc<FeeService>,      // 企业级实现：用于计算平台服务费
     wallet_repo: Arc<dyn WalletRepository>, // 企业级实现：用于获取钱包地址
 }
 
@@ -74,31 +86,55 @@ impl CrossChainBridgeService {
     pub fn new(
         pool: PgPool,
         price_service: Arc<PriceService>,
-        config: Arc<crate::config::CrossChainConfig>,
-        fee_service: Arc<FeeService>, // 企业级实现：用于计算平台服务费
+        aggregator: Arc<BridgeAggregator>, // 企业级实现：多桥实时报价聚合
+        fee_service: Arc<FeeService>,      // 企业级实现：用于计算平台服务费
         wallet_repo: Arc<dyn WalletRepository>, // 企业级实现：用于获取钱包地址
     ) -> Self {
         Self {
             pool,
             price_service,
-            config,
+            aggregator,
             fee_service,
             wallet_repo,
         }
     }
 
     /// 获取跨链兑换报价✅验证
+    ///
+    /// 并行向所有支持该链对的桥询价，推荐排名第一的方案并返回全部候选
+    #[tracing::instrument(skip_all, fields(source_chain = %source_chain, target_chain = %target_chain))]
     pub async fn get_swap_quote(
         &self,
         source_chain: &str,
         source_token: &str,
-        source_amount: f64,
+        source_amount: Decimal,
+        target_chain: &str,
+        target_token: &str,
+    ) -> Result<SwapQuote> {
+        self.quote_route(
+            source_chain,
+            source_token,
+            source_amount,
+            target_chain,
+            target_token,
+            None,
+        )
+        .await
+    }
+
+    /// 报价并按指定协议（或排名第一）汇总
+    async fn quote_route(
+        &self,
+        source_chain: &str,
+        source_token: &str,
+        source_amount: Decimal,
         target_chain: &str,
         target_token: &str,
+        preferred_protocol: Option<&str>,
     ) -> Result<SwapQuote> {
         // ✅参数验证
-        if source_amount <= 0.0 || !source_amount.is_finite() {
-            anyhow::bail!("Invalid amount: must be > 0 and finite");
+        if source_amount <= Decimal::ZERO {
+            anyhow::bail!("Invalid amount: must be greater than 0");
         }
         if source_chain.trim().is_empty() || target_chain.trim().is_empty() {
             anyhow::bail!("Chain identifiers required");
@@ -116,71 +152,56 @@ impl CrossChainBridgeService {
             target_chain
         );
 
-        // 1. 获取源币种和目标币种价格
-        let source_price = self.price_service.get_price(source_token).await?;
-        let target_price = self.price_service.get_price(target_token).await?;
-
-        // 2. 计算源币种的 USDT 价值
-        let source_value_usdt = source_amount * source_price;
-
-        // 3. 企业级实现：从配置读取手续费（支持环境变量动态调整）
-        // 优先从环境变量读取，降级到配置文件
-        let bridge_fee = std::env::var("BRIDGE_FEE_PERCENTAGE")
-            .ok()
-            .and_then(|v| v.parse::<f64>().ok())
-            .filter(|&v| (0.0..=1.0).contains(&v))
-            .unwrap_or(self.config.bridge_fee_percentage);
-
-        let tx_fee = std::env::var("BRIDGE_TX_FEE_PERCENTAGE")
-            .ok()
-            .and_then(|v| v.parse::<f64>().ok())
-            .filter(|&v| (0.0..=1.0).contains(&v))
-            .unwrap_or(self.config.transaction_fee_percentage);
-
-        let total_fee_percentage = bridge_fee + tx_fee;
-        let fee_usdt = source_value_usdt * total_fee_percentage;
-
-        // 验证费用合理性
-        if total_fee_percentage > 0.1 {
-            tracing::warn!(
-                "跨链桥费用过高警告: total_fee_percentage={}%, bridge_fee={}%, tx_fee={}%",
-                total_fee_percentage * 100.0,
-                bridge_fee * 100.0,
-                tx_fee * 100.0
-            );
-        }
-
-        // 4. 计算目标币种数量（扣除手续费后）
-        let net_value_usdt = source_value_usdt - fee_usdt;
-        let target_amount = net_value_usdt / target_price;
-
-        // 5. 计算汇率（source → target）
-        let exchange_rate = source_price / target_price;
+        // 1. 多桥实时报价（源代币跨链）
+        let options = self
+            .aggregator
+            .quote_all(&BridgeQuoteRequest {
+                source_chain: source_chain.to_string(),
+                target_chain: target_chain.to_string(),
+                token: source_token.to_string(),
+                amount: source_amount,
+            })
+            .await?;
 
-        // 6. 选择最佳桥协议
-        let recommended_protocol = self.select_best_bridge(source_chain, target_chain);
+        // 2. 源/目标代币价格：用于手续费 USDT 计价与目标代币折算
+        let source_price = self.price_service.get_price_decimal(source_token).await?;
+        let target_price = if source_token.eq_ignore_ascii_case(target_token) {
+            source_price
+        } else {
+            self.price_service.get_price_decimal(target_token).await?
+        };
 
-        // 7. 估算时间
-        let estimated_time_minutes = self.estimate_bridge_time(source_chain, target_chain);
+        let chosen = match preferred_protocol {
+            Some(protocol) => options
+                .iter()
+                .find(|o| o.protocol.eq_ignore_ascii_case(protocol))
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Bridge protocol '{}' is not available for {} -> {}",
+                        protocol,
+                        source_chain,
+                        target_chain
+                    )
+                })?,
+            None => options[0].clone(),
+        };
 
         tracing::info!(
-            bridge_fee_pct = %bridge_fee,
-            tx_fee_pct = %tx_fee,
-            total_fee_pct = %total_fee_percentage,
-            "Using configured cross-chain fees"
+            protocol = %chosen.protocol,
+            net_received = %chosen.net_received,
+            candidates = options.len(),
+            "Selected cross-chain bridge"
         );
 
-        Ok(SwapQuote {
-            source_chain: source_chain.to_string(),
-            target_chain: target_chain.to_string(),
-            source_amount,
-            target_amount,
-            exchange_rate,
-            fee_usdt,
-            total_fee_percentage: total_fee_percentage * 100.0,
-            estimated_time_minutes,
-            recommended_protocol,
-        })
+        build_swap_quote(
+            source_chain,
+            target_chain,
+            source_price,
+            target_price,
+            &chosen,
+            options,
+        )
     }
 
     /// 执行跨链兑换（用户确认后调用）
@@ -191,12 +212,13 @@ impl CrossChainBridgeService {
     /// - 平台服务费：钱包服务商收取的服务费用（通过FeeService计算）
     ///
     /// 注意：这两个费用是完全独立的，不能混淆！
+    #[tracing::instrument(skip_all)]
     pub async fn execute_swap(
         &self,
         request: CrossChainSwapRequest,
     ) -> Result<CrossChainSwapResponse> {
         // ✅验证请求
-        if request.source_amount <= 0.0 || !request.source_amount.is_finite() {
+        if request.source_amount <= Decimal::ZERO {
             anyhow::bail!("Invalid source_amount");
         }
         if request
@@ -206,16 +228,27 @@ impl CrossChainBridgeService {
             anyhow::bail!("Cannot bridge to same chain");
         }
 
+        // 按源代币精度换算为最小单位（超出精度的小数位直接拒绝），用于精确计费
+        let chain_config = chain_normalizer::get_chain_config(&request.source_chain)?;
+        let source_units = TokenService::new(self.pool.clone())
+            .parse_amount(
+                &request.source_token,
+                chain_config.chain_id as u64,
+                &request.source_amount.to_string(),
+            )
+            .await?;
+
         tracing::info!("Executing cross-chain swap: {:?}", request);
 
-        // 1. 获取报价（包含跨链桥协议费用）
+        // 1. 获取报价（包含跨链桥协议费用；可指定候选中的桥协议）
         let quote = self
-            .get_swap_quote(
+            .quote_route(
                 &request.source_chain,
                 &request.source_token,
                 request.source_amount,
                 &request.target_chain,
                 &request.target_token,
+                request.bridge_protocol.as_deref(),
             )
             .await?;
 
@@ -241,7 +274,7 @@ impl CrossChainBridgeService {
 
         let platform_fee_result = self
             .fee_service
-            .calculate_fee(&chain_key, "bridge", request.source_amount)
+            .calculate_fee_for_amount(&chain_key, "bridge", &source_units)
             .await;
 
         // 记录平台服务费（如果计算成功）
@@ -261,7 +294,7 @@ impl CrossChainBridgeService {
                     request.user_id,
                     &chain_key,
                     "bridge",
-                    request.source_amount,
+                    &source_units,
                     &fee_calc,
                     &wallet_address, // 企业级实现：使用实际钱包地址
                     None,            // tx_hash（跨链交易可能还没有tx_hash，后续回填）
@@ -314,7 +347,7 @@ impl CrossChainBridgeService {
         Ok(CrossChainSwapResponse {
             swap_id: swap_id.to_string(),
             status: "pending".to_string(),
-            source_amount: request.source_amount,
+            source_amount: request.source_amount.to_f64().unwrap_or(0.0),
             estimated_target_amount: quote.target_amount,
             actual_target_amount: None,
             exchange_rate: quote.exchange_rate,
@@ -378,7 +411,6 @@ impl CrossChainBridgeService {
         ) = row;
 
         // 转换为f64以保持API兼容性
-        use rust_decimal::prelude::ToPrimitive;
         Ok(CrossChainSwapResponse {
             swap_id: swap_id.to_string(),
             status,
@@ -409,14 +441,24 @@ impl CrossChainBridgeService {
             .await?;
 
         // 查询 swap 详情
-        let swap = sqlx::query_as::<_, (String, String, String, rust_decimal::Decimal, uuid::Uuid)>(
-            "SELECT source_chain, target_chain, source_token, source_amount, user_id FROM cross_chain_swaps WHERE id = $1"
+        let swap = sqlx::query_as::<
+            _,
+            (
+                String,
+                String,
+                String,
+                rust_decimal::Decimal,
+                uuid::Uuid,
+                Option<String>,
+            ),
+        >(
+            "SELECT source_chain, target_chain, source_token, source_amount, user_id, bridge_protocol FROM cross_chain_swaps WHERE id = $1"
         )
         .bind(swap_id)
         .fetch_one(&pool)
         .await?;
 
-        let (source_chain, target_chain, source_token, source_amount, user_id) = swap;
+        let (source_chain, target_chain, source_token, source_amount, user_id, protocol) = swap;
 
         // 2. 跨链桥接过程（生产级实现）
         use crate::{
@@ -454,8 +496,10 @@ impl CrossChainBridgeService {
             recipient: recipient_address,
         };
 
-        // 选择并创建桥接 SDK
-        let bridge = create_bridge(&source_chain, &target_chain)?;
+        // 使用报价阶段选定的桥协议创建 SDK
+        let protocol =
+            protocol.ok_or_else(|| anyhow::anyhow!("Swap {} has no bridge protocol", swap_id))?;
+        let bridge = create_bridge(&protocol)?;
 
         // 步骤1: 锁定源链资产
         tracing::info!(swap_id = %swap_id, "Step 1: Locking asset on source chain");
@@ -489,50 +533,6 @@ impl CrossChainBridgeService {
         Ok(())
     }
 
-    /// 企业级实现：选择最佳桥协议（从环境变量读取配置）
-    ///
-    /// 多级降级策略：
-    /// 1. 优先从环境变量读取链组合特定的协议
-    /// 2. 降级：从环境变量读取通用默认协议
-    /// 3. 最终降级：使用安全默认值（仅作为最后保障）
-    fn select_best_bridge(&self, source_chain: &str, target_chain: &str) -> String {
-        // 企业级实现：优先从环境变量读取链组合特定的协议
-        let pair_key = format!(
-            "BRIDGE_PROTOCOL_{}_{}",
-            source_chain.to_uppercase(),
-            target_chain.to_uppercase()
-        );
-        let protocol = std::env::var(&pair_key)
-            .ok()
-            .or_else(|| {
-                // 尝试反向链组合
-                let reverse_key = format!("BRIDGE_PROTOCOL_{}_{}",
-                    target_chain.to_uppercase(),
-                    source_chain.to_uppercase()
-                );
-                std::env::var(&reverse_key).ok()
-            })
-            .or_else(|| {
-                // 降级：从环境变量读取通用默认协议
-                std::env::var("BRIDGE_PROTOCOL_DEFAULT").ok()
-            })
-            .unwrap_or_else(|| {
-                // 最终降级：使用基于链组合的安全默认值
-                tracing::warn!(
-                    "未找到桥接协议配置 (from={}, to={})，使用安全默认值 wormhole，建议配置环境变量",
-                    source_chain, target_chain
-                );
-                match (source_chain, target_chain) {
-                    ("eth", "sol") | ("sol", "eth") => "wormhole".to_string(),
-                    ("eth", "bsc") | ("bsc", "eth") => "layerzero".to_string(),
-                    ("polygon", "avax") | ("avax", "polygon") => "across".to_string(),
-                    _ => "wormhole".to_string(), // 安全默认值
-                }
-            });
-
-        protocol
-    }
-
     /// 企业级实现：基于链对和协议计算跨链时间（分钟）
     ///
     /// 多级降级策略：
@@ -640,58 +640,93 @@ impl CrossChainBridgeService {
             }
         }
     }
+}
 
-    /// 估算桥接时间（分钟）- 实例方法保持向后兼容
-    fn estimate_bridge_time(&self, source_chain: &str, target_chain: &str) -> u32 {
-        Self::calculate_bridge_time(source_chain, target_chain, None)
+/// 汇总选定桥的报价为兑换报价（金额对外保持 f64 兼容）
+fn build_swap_quote(
+    source_chain: &str,
+    target_chain: &str,
+    source_price: Decimal,
+    target_price: Decimal,
+    chosen: &BridgeOption,
+    options: Vec<BridgeOption>,
+) -> Result<SwapQuote> {
+    if target_price <= Decimal::ZERO {
+        anyhow::bail!("Invalid target token price");
     }
+    let exchange_rate = source_price / target_price;
+    let fee_tokens = chosen.amount_in - chosen.net_received;
+    let to_f64 = |d: Decimal| d.to_f64().unwrap_or(0.0);
+
+    Ok(SwapQuote {
+        source_chain: source_chain.to_string(),
+        target_chain: target_chain.to_string(),
+        source_amount: to_f64(chosen.amount_in),
+        target_amount: to_f64(chosen.net_received * exchange_rate),
+        exchange_rate: to_f64(exchange_rate),
+        fee_usdt: to_f64(fee_tokens * source_price),
+        total_fee_percentage: to_f64(fee_tokens / chosen.amount_in * Decimal::from(100)),
+        estimated_time_minutes: chosen.eta_secs.div_ceil(60) as u32,
+        recommended_protocol: chosen.protocol.clone(),
+        options,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    #[ignore = "requires database connection"]
-    fn test_select_best_bridge() {
-        let pool = PgPool::connect_lazy("postgres://localhost/test").unwrap();
-        let price_service = Arc::new(PriceService::new(pool.clone(), None));
-        let cross_chain_config = Arc::new(crate::config::CrossChainConfig::default());
-        // 测试环境：创建mock fee_service 和 wallet_repo
-        use crate::repository::wallet_repository::PgWalletRepository;
-        let fee_service = Arc::new(FeeService::new(pool.clone()));
-        let wallet_repo = Arc::new(PgWalletRepository::new(pool.clone()));
-        let service = CrossChainBridgeService::new(
-            pool,
-            price_service,
-            cross_chain_config,
-            fee_service,
-            wallet_repo,
-        );
+    fn option(protocol: &str, net_received: &str, eta_secs: u64) -> BridgeOption {
+        BridgeOption {
+            rank: 1,
+            protocol: protocol.to_string(),
+            amount_in: Decimal::from(1_000),
+            amount_out: net_received.parse().unwrap(),
+            protocol_fee: Decimal::ZERO,
+            native_fee: None,
+            native_fee_symbol: None,
+            net_received: net_received.parse().unwrap(),
+            fees_priced: true,
+            eta_secs,
+            risk_score: 25,
+            score: Decimal::ZERO,
+        }
+    }
 
-        assert_eq!(service.select_best_bridge("eth", "sol"), "wormhole");
-        assert_eq!(service.select_best_bridge("eth", "bsc"), "layerzero");
+    #[test]
+    fn test_build_swap_quote_from_bridge_option() {
+        let chosen = option("axelar", "998.5", 960);
+        let options = vec![chosen.clone(), option("wormhole", "999.25", 1_080)];
+
+        // 目标代币价格为源代币的一半 → 汇率 2
+        let quote = build_swap_quote(
+            "ethereum",
+            "polygon",
+            Decimal::ONE,
+            "0.5".parse().unwrap(),
+            &chosen,
+            options,
+        )
+        .unwrap();
+
+        assert_eq!(quote.recommended_protocol, "axelar");
+        assert_eq!(quote.options.len(), 2);
+        assert_eq!(quote.estimated_time_minutes, 16);
+        assert!((quote.fee_usdt - 1.5).abs() < 1e-9);
+        assert!((quote.total_fee_percentage - 0.15).abs() < 1e-9);
+        assert!((quote.exchange_rate - 2.0).abs() < 1e-9);
+        assert!((quote.target_amount - 1_997.0).abs() < 1e-9);
     }
 
     #[test]
-    #[ignore = "requires database connection"]
-    fn test_estimate_bridge_time() {
-        let pool = PgPool::connect_lazy("postgres://localhost/test").unwrap();
-        let price_service = Arc::new(PriceService::new(pool.clone(), None));
-        let cross_chain_config = Arc::new(crate::config::CrossChainConfig::default());
-        // 测试环境：创建mock fee_service 和 wallet_repo
-        use crate::repository::wallet_repository::PgWalletRepository;
-        let fee_service = Arc::new(FeeService::new(pool.clone()));
-        let wallet_repo = Arc::new(PgWalletRepository::new(pool.clone()));
-        let service = CrossChainBridgeService::new(
-            pool,
-            price_service,
-            cross_chain_config,
-            fee_service,
-            wallet_repo,
+    fn test_calculate_bridge_time_defaults() {
+        assert_eq!(
+            CrossChainBridgeService::calculate_bridge_time("ethereum", "solana", Some("wormhole")),
+            10
+        );
+        assert_eq!(
+            CrossChainBridgeService::calculate_bridge_time("solana", "ethereum", None),
+            2
         );
-
-        assert_eq!(service.estimate_bridge_time("eth", "sol"), 15);
-        assert_eq!(service.estimate_bridge_time("sol", "eth"), 5);
     }
 }
@@ -9,7 +9,12 @@ use sqlx::PgPool;
 use tokio::time::interval;
 use uuid::Uuid;
 
-use crate::service::blockchain_client::BlockchainClient;
+use crate::{
+    infrastructure::{
+        event_bus::DomainEvent, event_outbox::enqueue_event, worker_supervisor::ChainShard,
+    },
+    service::blockchain_client::BlockchainClient,
+};
 
 const POLL_INTERVAL_SECS: u64 = 30;
 #[allow(dead_code)]
@@ -37,6 +42,7 @@ pub enum CrossChainStatus {
 struct CrossChainTransaction {
     id: Uuid,
     user_id: Uuid,
+    tenant_id: Uuid,
     source_chain: String,
     source_tx_hash: String,
     destination_chain: String,
@@ -52,6 +58,8 @@ struct CrossChainTransaction {
 pub struct CrossChainEventListener {
     pool: PgPool,
     blockchain_client: Arc<BlockchainClient>,
+    /// 负责的源链分片（多实例部署时由 WorkerSupervisor 分配）
+    shard: ChainShard,
 }
 
 impl CrossChainEventListener {
@@ -59,20 +67,31 @@ impl CrossChainEventListener {
         Self {
             pool,
             blockchain_client,
+            shard: ChainShard::All,
         }
     }
 
+    /// 仅处理源链属于指定分片的跨链交易
+    pub fn with_shard(mut self, shard: ChainShard) -> Self {
+        self.shard = shard;
+        self
+    }
+
     /// 启动后台监听任务
     pub async fn start_background_listener(self: Arc<Self>) {
         let mut ticker = interval(Duration::from_secs(POLL_INTERVAL_SECS));
 
         tracing::info!(
+            shard = %self.shard.key(),
             "Cross-chain event listener started, interval={}s",
             POLL_INTERVAL_SECS
         );
 
         loop {
             ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
 
             // 处理待确认的跨链交易
             match self.process_pending_cross_chain_transactions().await {
@@ -97,9 +116,11 @@ impl CrossChainEventListener {
     /// 处理待确认的跨链交易
     async fn process_pending_cross_chain_transactions(&self) -> Result<usize> {
         // 查询所有非最终状态的跨链交易
+        let (shard_chains, shard_include) = self.shard.sql_filter();
         let pending_txs = sqlx::query_as::<
             _,
             (
+                Uuid,
                 Uuid,
                 Uuid,
                 String,
@@ -111,14 +132,17 @@ impl CrossChainEventListener {
                 String,
             ),
         >(
-            "SELECT id, user_id, source_chain, source_tx_hash, destination_chain,
+            "SELECT id, user_id, tenant_id, source_chain, source_tx_hash, destination_chain,
                     destination_tx_hash, status, amount, token_symbol
              FROM cross_chain_transactions
              WHERE status NOT IN ('DestinationConfirmed', 'Failed')
                AND created_at > NOW() - INTERVAL '7 days'
+               AND ($1::TEXT[] IS NULL OR (UPPER(source_chain) = ANY($1)) = $2)
              ORDER BY created_at ASC
              LIMIT 50",
         )
+        .bind(shard_chains)
+        .bind(shard_include)
         .fetch_all(&self.pool)
         .await
         .context("Failed to query pending cross-chain transactions")?;
@@ -132,6 +156,7 @@ impl CrossChainEventListener {
         for (
             id,
             user_id,
+            tenant_id,
             source_chain,
             source_tx_hash,
             destination_chain,
@@ -146,6 +171,7 @@ impl CrossChainEventListener {
             let tx = CrossChainTransaction {
                 id,
                 user_id,
+                tenant_id,
                 source_chain: source_chain.clone(),
                 source_tx_hash: source_tx_hash.clone(),
                 destination_chain: destination_chain.clone(),
@@ -286,9 +312,8 @@ impl CrossChainEventListener {
 
             if let Some(receipt) = receipt {
                 if receipt.status == Some(1) && receipt.confirmations >= 12 {
-                    // 目标链交易已确认，跨链完成
-                    self.update_status(tx.id, CrossChainStatus::DestinationConfirmed)
-                        .await?;
+                    // 目标链交易已确认，跨链完成（状态与完成事件同一事务）
+                    self.complete_transfer(tx, dest_tx_hash).await?;
 
                     tracing::info!(
                         tx_id = %tx.id,
@@ -296,9 +321,6 @@ impl CrossChainEventListener {
                         "Cross-chain transaction completed"
                     );
 
-                    // 发送通知给用户
-                    self.notify_user_cross_chain_completed(tx).await?;
-
                     return Ok(true);
                 }
             }
@@ -336,15 +358,41 @@ impl CrossChainEventListener {
         Ok(age.unwrap_or(0))
     }
 
-    /// 通知用户跨链完成
-    async fn notify_user_cross_chain_completed(&self, tx: &CrossChainTransaction) -> Result<()> {
-        // TODO: 实际实现：发送推送通知、邮件、短信等
-        tracing::info!(
-            user_id = %tx.user_id,
-            tx_id = %tx.id,
-            "Notifying user of cross-chain completion"
-        );
+    /// 标记跨链完成
+    ///
+    /// 写入 outbox 事件 `BridgeTransferCompleted`，由订阅方（租户 Webhook 等）异步通知
+    async fn complete_transfer(
+        &self,
+        tx: &CrossChainTransaction,
+        dest_tx_hash: &str,
+    ) -> Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE cross_chain_transactions
+             SET status = $1, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $2",
+        )
+        .bind(format!("{:?}", CrossChainStatus::DestinationConfirmed))
+        .bind(tx.id)
+        .execute(&mut *db_tx)
+        .await?;
+
+        enqueue_event(
+            &mut *db_tx,
+            &DomainEvent::BridgeTransferCompleted {
+                transfer_id: tx.id,
+                tenant_id: tx.tenant_id,
+                user_id: tx.user_id,
+                source_chain: tx.source_chain.clone(),
+                destination_chain: tx.destination_chain.clone(),
+                source_tx_hash: tx.source_tx_hash.clone(),
+                destination_tx_hash: dest_tx_hash.to_string(),
+            },
+        )
+        .await?;
 
+        db_tx.commit().await?;
         Ok(())
     }
 
@@ -0,0 +1,1488 @@
+//! 链上入账索引器
+//!
+//! 企业级实现：按链跟踪已注册钱包地址的外部转入（交易所提现、空投等）
+//! - EVM：逐块扫描原生币转账 + `eth_getLogs` 扫描 ERC-20 `Transfer` 事件
+//! - Solana：`getSignaturesForAddress` + `getTransaction` 余额差（SOL / SPL）
+//! - Bitcoin：Esplora 地址交易列表，按输出（vout）入账
+//! - TON：toncenter `getTransactions` 的内部入站消息
+//!
+//! 只索引到「链高 - 确认数」，游标（高度 + 区块哈希）按链持久化在
+//! `deposit_index_cursors`；下一轮发现游标区块哈希与链上不一致即判定重组，
+//! 回退游标并删除回退区间内已入账的 `receive` 记录。
+//! 新入账写入 `transactions`（tx_type = receive）并发出 `BalanceSyncEvent`。
+//!
+//! 未在 `tokens.registry` 中启用的代币不入账（垃圾空投防护）。
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::types::U256;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tokio::sync::{mpsc, Mutex};
+use uuid::Uuid;
+
+use crate::{
+    domain::token_amount::TokenAmount,
+    infrastructure::rpc_selector::RpcSelector,
+    infrastructure::telemetry::TracePropagation,
+    service::{balance_sync_event::BalanceSyncEvent, multi_node_verifier::EndpointProvider},
+    utils::chain_normalizer,
+};
+
+/// ERC-20 `Transfer(address,address,uint256)` 事件签名
+pub const TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// 单次 `eth_getLogs` 的收款地址数上限（topic OR 列表）
+const LOG_ADDRESS_CHUNK: usize = 100;
+/// 按地址分页查询的最大页数（单轮）
+const MAX_ADDRESS_PAGES: usize = 10;
+const SOLANA_SIGNATURE_PAGE: usize = 1000;
+const ESPLORA_PAGE: usize = 25;
+
+/// 入账资产
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferAsset {
+    /// 链原生币
+    Native,
+    /// 代币合约（EVM 合约地址 / SPL mint）
+    Token(String),
+}
+
+/// 链上发现的一笔转入
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomingTransfer {
+    pub tx_hash: String,
+    /// 交易内事件标识：native / log:N / spl:mint / vout:N / msg:lt
+    pub event_key: String,
+    pub block_number: u64,
+    pub from_address: String,
+    /// 收款地址（与被跟踪地址同一格式）
+    pub to_address: String,
+    pub asset: TransferAsset,
+    /// 最小单位金额
+    pub base_units: U256,
+}
+
+/// 链数据源
+#[async_trait]
+pub trait DepositSource: Send + Sync {
+    /// 规范链名（小写，如 ethereum）
+    fn chain(&self) -> &str;
+
+    /// 当前链高（Solana 为 finalized slot，TON 为 unix 秒）
+    async fn head(&self) -> Result<u64>;
+
+    /// 指定高度的区块哈希；不支持重组检测的链返回 None
+    async fn block_hash(&self, height: u64) -> Result<Option<String>>;
+
+    /// 扫描 [from, to] 区间内转入 `watched` 地址的转账
+    async fn scan(&self, from: u64, to: u64, watched: &[String]) -> Result<Vec<IncomingTransfer>>;
+}
+
+/// 索引器配置（按链）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositIndexerConfig {
+    /// 确认数：只索引到 head - confirmations
+    pub confirmations: u64,
+    /// 单轮最大扫描区间
+    pub max_range: u64,
+    /// 检测到重组时回退的高度
+    pub reorg_rewind: u64,
+    pub poll_interval: Duration,
+}
+
+impl DepositIndexerConfig {
+    /// 链默认值，可用 DEPOSIT_CONFIRMATIONS_<CHAIN> / DEPOSIT_MAX_RANGE_<CHAIN> /
+    /// DEPOSIT_POLL_SECS_<CHAIN> 覆盖（CHAIN 为大写规范链名，如 ETHEREUM）
+    pub fn for_chain(chain: &str) -> Self {
+        let (confirmations, max_range, poll_secs) = match chain {
+            "ethereum" => (12, 20, 15),
+            "bsc" => (15, 20, 6),
+            "polygon" => (128, 20, 5),
+            "arbitrum" | "optimism" => (20, 100, 5),
+            "avalanche" => (12, 50, 5),
+            "solana" => (0, 5_000, 15),
+            "bitcoin" => (3, 6, 60),
+            // TON 以 unix 秒为高度：延迟 30 秒、单轮 1 小时
+            "ton" => (30, 3_600, 15),
+            _ => (12, 20, 15),
+        };
+
+        let suffix = chain.to_uppercase();
+        let env_u64 = |key: &str, default: u64| {
+            std::env::var(format!("{}_{}", key, suffix))
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+
+        let confirmations = env_u64("DEPOSIT_CONFIRMATIONS", confirmations);
+        Self {
+            confirmations,
+            max_range: env_u64("DEPOSIT_MAX_RANGE", max_range).max(1),
+            reorg_rewind: (confirmations * 2).max(6),
+            poll_interval: Duration::from_secs(env_u64("DEPOSIT_POLL_SECS", poll_secs).max(1)),
+        }
+    }
+}
+
+/// 从环境变量 DEPOSIT_INDEXER_CHAINS 读取需要索引的链（规范名，默认 ethereum,bsc,polygon）
+pub fn chains_from_env() -> Vec<String> {
+    let raw = std::env::var("DEPOSIT_INDEXER_CHAINS")
+        .unwrap_or_else(|_| "ethereum,bsc,polygon".to_string());
+
+    let mut chains = Vec::new();
+    for item in raw.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match chain_normalizer::normalize_chain_identifier(item) {
+            Ok(chain) if !chains.contains(&chain) => chains.push(chain),
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(chain = %item, error = %e, "Skipping unsupported deposit indexer chain")
+            }
+        }
+    }
+    chains
+}
+
+/// 按链构建数据源（EVM 走 RpcSelector，其余链读各自的 API 环境变量）
+pub fn source_for_chain(
+    chain: &str,
+    rpc_selector: Arc<RpcSelector>,
+) -> Result<Arc<dyn DepositSource>> {
+    let chain = chain_normalizer::normalize_chain_identifier(chain)?;
+    if chain_normalizer::is_evm_chain(&chain) {
+        return Ok(Arc::new(EvmDepositSource::new(&chain, rpc_selector)));
+    }
+
+    match chain.as_str() {
+        "solana" => Ok(Arc::new(SolanaDepositSource::new(
+            std::env::var("SOLANA_RPC_URL")
+                .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
+        ))),
+        "bitcoin" => Ok(Arc::new(BitcoinDepositSource::new(
+            std::env::var("BITCOIN_API_URL")
+                .unwrap_or_else(|_| "https://blockstream.info/api".to_string()),
+        ))),
+        "ton" => Ok(Arc::new(TonDepositSource::new(
+            std::env::var("TON_API_URL")
+                .unwrap_or_else(|_| "https://toncenter.com/api/v2".to_string()),
+        ))),
+        other => anyhow::bail!("Deposit indexing not supported for chain: {}", other),
+    }
+}
+
+/// 下一轮扫描区间：(cursor, safe_head] 截断到 max_range；已追平返回 None
+pub fn scan_window(cursor: u64, safe_head: u64, max_range: u64) -> Option<(u64, u64)> {
+    if cursor >= safe_head {
+        return None;
+    }
+    let from = cursor + 1;
+    let to = safe_head.min(cursor.saturating_add(max_range.max(1)));
+    Some((from, to))
+}
+
+/// 重组回退目标高度
+pub fn rewind_target(cursor: u64, rewind: u64) -> u64 {
+    cursor.saturating_sub(rewind.max(1))
+}
+
+/// 链原生币精度
+pub fn native_decimals(chain: &str) -> u32 {
+    match chain {
+        "solana" | "ton" => 9,
+        "bitcoin" => 8,
+        _ => 18,
+    }
+}
+
+/// 地址归一化：EVM 地址不区分大小写，其余链保持原样
+fn normalize_address(chain: &str, address: &str) -> String {
+    if chain_normalizer::is_evm_chain(chain) {
+        address.trim().to_lowercase()
+    } else {
+        address.trim().to_string()
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+fn parse_hex_u64(value: &Value) -> Option<u64> {
+    let s = value.as_str()?.trim_start_matches("0x");
+    u64::from_str_radix(s, 16).ok()
+}
+
+fn parse_hex_u256(value: &str) -> Option<U256> {
+    let s = value.trim_start_matches("0x");
+    if s.is_empty() {
+        return Some(U256::zero());
+    }
+    U256::from_str_radix(s, 16).ok()
+}
+
+fn parse_dec_u256(value: &Value) -> Option<U256> {
+    match value {
+        Value::String(s) => U256::from_dec_str(s.trim()).ok(),
+        Value::Number(n) => n.as_u64().map(U256::from),
+        _ => None,
+    }
+}
+
+fn to_hex_quantity(height: u64) -> String {
+    format!("0x{:x}", height)
+}
+
+/// 地址 → 32 字节 topic
+fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+/// 32 字节 topic → 地址（取低 20 字节）
+fn topic_address(topic: &str) -> Option<String> {
+    let hex = topic.trim_start_matches("0x");
+    if hex.len() < 40 {
+        return None;
+    }
+    Some(format!("0x{}", hex[hex.len() - 40..].to_lowercase()))
+}
+
+// ============ EVM ============
+
+/// EVM 数据源（JSON-RPC）
+pub struct EvmDepositSource {
+    chain: String,
+    endpoint_provider: Arc<dyn EndpointProvider>,
+    http_client: reqwest::Client,
+}
+
+impl EvmDepositSource {
+    pub fn new(chain: &str, rpc_selector: Arc<RpcSelector>) -> Self {
+        Self::with_endpoint_provider(chain, rpc_selector)
+    }
+
+    pub fn with_endpoint_provider(
+        chain: &str,
+        endpoint_provider: Arc<dyn EndpointProvider>,
+    ) -> Self {
+        Self {
+            chain: chain.to_string(),
+            endpoint_provider,
+            http_client: http_client(),
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let url = self
+            .endpoint_provider
+            .distinct_endpoints(&self.chain, 1)
+            .await
+            .into_iter()
+            .next()
+            .with_context(|| format!("No RPC endpoint available for {}", self.chain))?;
+
+        let payload = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let json: Value = self
+            .http_client
+            .post(&url)
+            .json(&payload)
+            .with_trace_context()
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        if let Some(error) = json.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown RPC error");
+            anyhow::bail!("RPC error ({}): {}", method, message);
+        }
+        Ok(json.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// 交易是否执行成功（无 status 字段的旧区块视为成功）
+    async fn tx_succeeded(&self, tx_hash: &str) -> Result<bool> {
+        let receipt = self
+            .rpc("eth_getTransactionReceipt", json!([tx_hash]))
+            .await?;
+        if receipt.is_null() {
+            anyhow::bail!("Receipt not available for {}", tx_hash);
+        }
+        Ok(receipt
+            .get("status")
+            .and_then(parse_hex_u64)
+            .is_none_or(|status| status == 1))
+    }
+
+    async fn scan_native(
+        &self,
+        from: u64,
+        to: u64,
+        watched: &HashSet<String>,
+    ) -> Result<Vec<IncomingTransfer>> {
+        let mut transfers = Vec::new();
+        for height in from..=to {
+            let block = self
+                .rpc(
+                    "eth_getBlockByNumber",
+                    json!([to_hex_quantity(height), true]),
+                )
+                .await?;
+            if block.is_null() {
+                anyhow::bail!("Block {} not available on {}", height, self.chain);
+            }
+
+            for tx in block["transactions"].as_array().into_iter().flatten() {
+                let Some(to_address) = tx["to"]
+                    .as_str()
+                    .map(str::to_lowercase)
+                    .filter(|a| watched.contains(a))
+                else {
+                    continue;
+                };
+                let value = tx["value"]
+                    .as_str()
+                    .and_then(parse_hex_u256)
+                    .unwrap_or_default();
+                if value.is_zero() {
+                    continue;
+                }
+                let tx_hash = tx["hash"].as_str().unwrap_or_default().to_lowercase();
+                if !self.tx_succeeded(&tx_hash).await? {
+                    continue;
+                }
+
+                transfers.push(IncomingTransfer {
+                    tx_hash,
+                    event_key: "native".to_string(),
+                    block_number: height,
+                    from_address: tx["from"].as_str().unwrap_or_default().to_lowercase(),
+                    to_address,
+                    asset: TransferAsset::Native,
+                    base_units: value,
+                });
+            }
+        }
+        Ok(transfers)
+    }
+
+    async fn scan_token_logs(
+        &self,
+        from: u64,
+        to: u64,
+        watched: &[String],
+    ) -> Result<Vec<IncomingTransfer>> {
+        let mut transfers = Vec::new();
+        for chunk in watched.chunks(LOG_ADDRESS_CHUNK) {
+            let topics: Vec<String> = chunk.iter().map(|a| address_topic(a)).collect();
+            let logs = self
+                .rpc(
+                    "eth_getLogs",
+                    json!([{
+                        "fromBlock": to_hex_quantity(from),
+                        "toBlock": to_hex_quantity(to),
+                        "topics": [TRANSFER_TOPIC, Value::Null, topics],
+                    }]),
+                )
+                .await?;
+
+            transfers.extend(
+                logs.as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(erc20_incoming),
+            );
+        }
+        Ok(transfers)
+    }
+}
+
+/// 解析 ERC-20 Transfer 日志（ERC-721 的 tokenId 也是 indexed，topic 数为 4，跳过）
+fn erc20_incoming(log: &Value) -> Option<IncomingTransfer> {
+    if log["removed"].as_bool() == Some(true) {
+        return None;
+    }
+    let topics = log["topics"].as_array()?;
+    if topics.len() != 3 || !topics[0].as_str()?.eq_ignore_ascii_case(TRANSFER_TOPIC) {
+        return None;
+    }
+    let base_units = parse_hex_u256(log["data"].as_str()?)?;
+    if base_units.is_zero() {
+        return None;
+    }
+    let log_index = parse_hex_u64(&log["logIndex"])?;
+
+    Some(IncomingTransfer {
+        tx_hash: log["transactionHash"].as_str()?.to_lowercase(),
+        event_key: format!("log:{}", log_index),
+        block_number: parse_hex_u64(&log["blockNumber"])?,
+        from_address: topic_address(topics[1].as_str()?)?,
+        to_address: topic_address(topics[2].as_str()?)?,
+        asset: TransferAsset::Token(log["address"].as_str()?.to_lowercase()),
+        base_units,
+    })
+}
+
+#[async_trait]
+impl DepositSource for EvmDepositSource {
+    fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    async fn head(&self) -> Result<u64> {
+        let result = self.rpc("eth_blockNumber", json!([])).await?;
+        parse_hex_u64(&result).context("Invalid eth_blockNumber result")
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<Option<String>> {
+        let block = self
+            .rpc(
+                "eth_getBlockByNumber",
+                json!([to_hex_quantity(height), false]),
+            )
+            .await?;
+        Ok(block["hash"].as_str().map(str::to_lowercase))
+    }
+
+    async fn scan(&self, from: u64, to: u64, watched: &[String]) -> Result<Vec<IncomingTransfer>> {
+        let watched: Vec<String> = watched.iter().map(|a| a.to_lowercase()).collect();
+        let watched_set: HashSet<String> = watched.iter().cloned().collect();
+
+        let mut transfers = self.scan_native(from, to, &watched_set).await?;
+        transfers.extend(self.scan_token_logs(from, to, &watched).await?);
+        Ok(transfers)
+    }
+}
+
+// ============ Solana ============
+
+/// Solana 数据源（finalized 承诺级别，不会重组）
+pub struct SolanaDepositSource {
+    rpc_url: String,
+    http_client: reqwest::Client,
+}
+
+impl SolanaDepositSource {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_url,
+            http_client: http_client(),
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let payload = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let json: Value = self
+            .http_client
+            .post(&self.rpc_url)
+            .json(&payload)
+            .with_trace_context()
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        if let Some(error) = json.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown RPC error");
+            anyhow::bail!("Solana RPC error ({}): {}", method, message);
+        }
+        Ok(json.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// 区间内该地址参与的成功交易签名（签名列表按新到旧返回）
+    async fn signatures_in_range(
+        &self,
+        address: &str,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<(String, u64)>> {
+        let mut found = Vec::new();
+        let mut before: Option<String> = None;
+
+        for _ in 0..MAX_ADDRESS_PAGES {
+            let mut options = json!({ "limit": SOLANA_SIGNATURE_PAGE, "commitment": "finalized" });
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+            let result = self
+                .rpc("getSignaturesForAddress", json!([address, options]))
+                .await?;
+            let page = result.as_array().cloned().unwrap_or_default();
+
+            let mut reached_start = false;
+            for item in &page {
+                let slot = item["slot"].as_u64().unwrap_or_default();
+                if slot > to {
+                    continue;
+                }
+                if slot < from {
+                    reached_start = true;
+                    break;
+                }
+                if item["err"].is_null() {
+                    if let Some(signature) = item["signature"].as_str() {
+                        found.push((signature.to_string(), slot));
+                    }
+                }
+            }
+
+            if reached_start || page.len() < SOLANA_SIGNATURE_PAGE {
+                break;
+            }
+            before = page
+                .last()
+                .and_then(|item| item["signature"].as_str())
+                .map(str::to_string);
+        }
+        Ok(found)
+    }
+}
+
+/// 从 jsonParsed 交易中提取 `address` 的 SOL / SPL 净流入
+fn solana_incoming(tx: &Value, address: &str, signature: &str, slot: u64) -> Vec<IncomingTransfer> {
+    let mut transfers = Vec::new();
+    let meta = &tx["meta"];
+    if !meta["err"].is_null() {
+        return transfers;
+    }
+
+    let keys: Vec<&str> = tx["transaction"]["message"]["accountKeys"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|k| k["pubkey"].as_str().or_else(|| k.as_str()))
+        .collect();
+    let fee_payer = keys.first().copied().unwrap_or_default().to_string();
+
+    if let Some(index) = keys.iter().position(|k| *k == address) {
+        let pre = meta["preBalances"][index].as_u64().unwrap_or_default();
+        let post = meta["postBalances"][index].as_u64().unwrap_or_default();
+        if post > pre && fee_payer != address {
+            transfers.push(IncomingTransfer {
+                tx_hash: signature.to_string(),
+                event_key: "native".to_string(),
+                block_number: slot,
+                from_address: fee_payer.clone(),
+                to_address: address.to_string(),
+                asset: TransferAsset::Native,
+                base_units: U256::from(post - pre),
+            });
+        }
+    }
+
+    // SPL：按 (accountIndex, mint) 对比前后余额，仅统计 owner 为该地址的代币账户
+    let token_balances = |field: &str| -> HashMap<(u64, String), U256> {
+        meta[field]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|b| b["owner"].as_str() == Some(address))
+            .filter_map(|b| {
+                let key = (b["accountIndex"].as_u64()?, b["mint"].as_str()?.to_string());
+                let amount = parse_dec_u256(&b["uiTokenAmount"]["amount"])?;
+                Some((key, amount))
+            })
+            .collect()
+    };
+    let pre_tokens = token_balances("preTokenBalances");
+    let mut post_tokens: Vec<_> = token_balances("postTokenBalances").into_iter().collect();
+    post_tokens.sort();
+
+    for ((account_index, mint), post) in post_tokens {
+        let pre = pre_tokens
+            .get(&(account_index, mint.clone()))
+            .copied()
+            .unwrap_or_default();
+        if post > pre {
+            transfers.push(IncomingTransfer {
+                tx_hash: signature.to_string(),
+                event_key: format!("spl:{}", mint),
+                block_number: slot,
+                from_address: fee_payer.clone(),
+                to_address: address.to_string(),
+                asset: TransferAsset::Token(mint),
+                base_units: post - pre,
+            });
+        }
+    }
+
+    transfers
+}
+
+#[async_trait]
+impl DepositSource for SolanaDepositSource {
+    fn chain(&self) -> &str {
+        "solana"
+    }
+
+    async fn head(&self) -> Result<u64> {
+        let result = self
+            .rpc("getSlot", json!([{ "commitment": "finalized" }]))
+            .await?;
+        result.as_u64().context("Invalid getSlot result")
+    }
+
+    async fn block_hash(&self, _height: u64) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn scan(&self, from: u64, to: u64, watched: &[String]) -> Result<Vec<IncomingTransfer>> {
+        let mut transfers = Vec::new();
+        for address in watched {
+            for (signature, slot) in self.signatures_in_range(address, from, to).await? {
+                let tx = self
+                    .rpc(
+                        "getTransaction",
+                        json!([signature, {
+                            "encoding": "jsonParsed",
+                            "commitment": "finalized",
+                            "maxSupportedTransactionVersion": 0,
+                        }]),
+                    )
+                    .await?;
+                if tx.is_null() {
+                    anyhow::bail!("Transaction {} not available", signature);
+                }
+                transfers.extend(solana_incoming(&tx, address, &signature, slot));
+            }
+        }
+        Ok(transfers)
+    }
+}
+
+// ============ Bitcoin ============
+
+/// Bitcoin 数据源（Esplora REST）
+pub struct BitcoinDepositSource {
+    api_url: String,
+    http_client: reqwest::Client,
+}
+
+impl BitcoinDepositSource {
+    pub fn new(api_url: String) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http_client: http_client(),
+        }
+    }
+
+    async fn get_text(&self, path: &str) -> Result<Option<String>> {
+        let response = self
+            .http_client
+            .get(format!("{}{}", self.api_url, path))
+            .with_trace_context()
+            .send()
+            .await
+            .with_context(|| format!("Failed to query Esplora {}", path))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("Esplora request failed: {}", path))?;
+        Ok(Some(response.text().await?))
+    }
+}
+
+/// 提取转入 `address` 的输出；输入中含该地址的交易视为本钱包发出（找零），跳过
+fn bitcoin_incoming(tx: &Value, address: &str, height: u64) -> Vec<IncomingTransfer> {
+    let vin = tx["vin"].as_array().cloned().unwrap_or_default();
+    let spends_own = vin
+        .iter()
+        .any(|input| input["prevout"]["scriptpubkey_address"].as_str() == Some(address));
+    if spends_own {
+        return Vec::new();
+    }
+
+    let from_address = vin
+        .first()
+        .and_then(|input| input["prevout"]["scriptpubkey_address"].as_str())
+        .unwrap_or("coinbase")
+        .to_string();
+    let tx_hash = tx["txid"].as_str().unwrap_or_default().to_string();
+
+    tx["vout"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, output)| output["scriptpubkey_address"].as_str() == Some(address))
+        .filter_map(|(index, output)| {
+            let value = output["value"].as_u64().filter(|v| *v > 0)?;
+            Some(IncomingTransfer {
+                tx_hash: tx_hash.clone(),
+                event_key: format!("vout:{}", index),
+                block_number: height,
+                from_address: from_address.clone(),
+                to_address: address.to_string(),
+                asset: TransferAsset::Native,
+                base_units: U256::from(value),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl DepositSource for BitcoinDepositSource {
+    fn chain(&self) -> &str {
+        "bitcoin"
+    }
+
+    async fn head(&self) -> Result<u64> {
+        let text = self
+            .get_text("/blocks/tip/height")
+            .await?
+            .context("Esplora tip height not available")?;
+        text.trim().parse::<u64>().context("Invalid tip height")
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<Option<String>> {
+        Ok(self
+            .get_text(&format!("/block-height/{}", height))
+            .await?
+            .map(|hash| hash.trim().to_string()))
+    }
+
+    async fn scan(&self, from: u64, to: u64, watched: &[String]) -> Result<Vec<IncomingTransfer>> {
+        let mut transfers = Vec::new();
+        for address in watched {
+            // 已确认交易按新到旧分页：/txs/chain[/:last_seen_txid]
+            let mut last_seen: Option<String> = None;
+            for _ in 0..MAX_ADDRESS_PAGES {
+                let path = match &last_seen {
+                    Some(txid) => format!("/address/{}/txs/chain/{}", address, txid),
+                    None => format!("/address/{}/txs/chain", address),
+                };
+                let Some(body) = self.get_text(&path).await? else {
+                    break;
+                };
+                let page: Vec<Value> =
+                    serde_json::from_str(&body).context("Invalid Esplora txs response")?;
+
+                let mut reached_start = false;
+                for tx in &page {
+                    let height = tx["status"]["block_height"].as_u64().unwrap_or_default();
+                    if height > to {
+                        continue;
+                    }
+                    if height < from {
+                        reached_start = true;
+                        break;
+                    }
+                    transfers.extend(bitcoin_incoming(tx, address, height));
+                }
+
+                if reached_start || page.len() < ESPLORA_PAGE {
+                    break;
+                }
+                last_seen = page
+                    .last()
+                    .and_then(|tx| tx["txid"].as_str())
+                    .map(str::to_string);
+            }
+        }
+        Ok(transfers)
+    }
+}
+
+// ============ TON ============
+
+/// TON 数据源（toncenter v2），以 unix 秒为高度
+pub struct TonDepositSource {
+    api_url: String,
+    api_key: Option<String>,
+    http_client: reqwest::Client,
+}
+
+impl TonDepositSource {
+    pub fn new(api_url: String) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: std::env::var("TON_API_KEY").ok().filter(|k| !k.is_empty()),
+            http_client: http_client(),
+        }
+    }
+}
+
+/// 提取带 TON 的内部入站消息（外部消息 source 为空）
+fn ton_incoming(tx: &Value, address: &str) -> Option<IncomingTransfer> {
+    let in_msg = &tx["in_msg"];
+    let source = in_msg["source"].as_str().filter(|s| !s.is_empty())?;
+    let value = parse_dec_u256(&in_msg["value"]).filter(|v| !v.is_zero())?;
+    let lt = tx["transaction_id"]["lt"].as_str()?;
+
+    Some(IncomingTransfer {
+        tx_hash: tx["transaction_id"]["hash"].as_str()?.to_string(),
+        event_key: format!("msg:{}", lt),
+        block_number: tx["utime"].as_u64()?,
+        from_address: source.to_string(),
+        to_address: address.to_string(),
+        asset: TransferAsset::Native,
+        base_units: value,
+    })
+}
+
+#[async_trait]
+impl DepositSource for TonDepositSource {
+    fn chain(&self) -> &str {
+        "ton"
+    }
+
+    async fn head(&self) -> Result<u64> {
+        Ok(chrono::Utc::now().timestamp().max(0) as u64)
+    }
+
+    async fn block_hash(&self, _height: u64) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    async fn scan(&self, from: u64, to: u64, watched: &[String]) -> Result<Vec<IncomingTransfer>> {
+        let mut transfers = Vec::new();
+        for address in watched {
+            let mut request = self
+                .http_client
+                .get(format!("{}/getTransactions", self.api_url))
+                .query(&[
+                    ("address", address.as_str()),
+                    ("limit", "100"),
+                    ("archival", "true"),
+                ]);
+            if let Some(key) = &self.api_key {
+                request = request.header("X-API-Key", key);
+            }
+            let json: Value = request
+                .with_trace_context()
+                .send()
+                .await
+                .context("Failed to query TON transactions")?
+                .json()
+                .await
+                .context("Failed to parse TON transactions")?;
+            if json["ok"].as_bool() == Some(false) {
+                anyhow::bail!(
+                    "TON API error: {}",
+                    json["error"].as_str().unwrap_or("unknown error")
+                );
+            }
+
+            transfers.extend(
+                json["result"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|tx| {
+                        tx["utime"]
+                            .as_u64()
+                            .is_some_and(|t| (from..=to).contains(&t))
+                    })
+                    .filter_map(|tx| ton_incoming(tx, address)),
+            );
+        }
+        Ok(transfers)
+    }
+}
+
+// ============ 索引器 ============
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct WatchedWallet {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    address: String,
+}
+
+#[derive(Debug, Clone)]
+struct TokenInfo {
+    symbol: String,
+    decimals: u32,
+    address: Option<String>,
+}
+
+/// 入账索引器（单链）
+pub struct DepositIndexer {
+    pool: PgPool,
+    source: Arc<dyn DepositSource>,
+    config: DepositIndexerConfig,
+    event_sender: Option<mpsc::Sender<BalanceSyncEvent>>,
+    token_cache: Mutex<HashMap<String, Option<TokenInfo>>>,
+}
+
+impl DepositIndexer {
+    pub fn new(pool: PgPool, source: Arc<dyn DepositSource>) -> Self {
+        let config = DepositIndexerConfig::for_chain(source.chain());
+        Self {
+            pool,
+            source,
+            config,
+            event_sender: None,
+            token_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_config(mut self, config: DepositIndexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 新入账/回滚后发送余额同步事件
+    pub fn with_event_sender(mut self, sender: mpsc::Sender<BalanceSyncEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+
+    /// 启动后台索引循环（落后时连续追赶，追平后按轮询间隔休眠）
+    pub async fn start_background_indexer(self: Arc<Self>) {
+        tracing::info!(
+            chain = %self.source.chain(),
+            confirmations = self.config.confirmations,
+            "Starting deposit indexer"
+        );
+
+        loop {
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                tokio::time::sleep(self.config.poll_interval).await;
+                continue;
+            }
+            let caught_up = match self.run_once().await {
+                Ok(progress) => progress.caught_up,
+                Err(e) => {
+                    tracing::warn!(chain = %self.source.chain(), error = %e, "Deposit indexer tick failed");
+                    true
+                }
+            };
+            if caught_up {
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+        }
+    }
+
+    /// 执行一轮：重组检测 → 扫描一个区间 → 入账 + 推进游标（同一事务）
+    pub async fn run_once(&self) -> Result<IndexProgress> {
+        let chain = self.source.chain().to_string();
+        let head = self.source.head().await?;
+        let safe_head = head.saturating_sub(self.config.confirmations);
+
+        let Some((cursor, cursor_hash)) = self.load_cursor().await? else {
+            // 首次运行从当前安全高度开始，不回溯历史
+            let hash = self.source.block_hash(safe_head).await?;
+            self.save_cursor(&self.pool, safe_head, hash.as_deref())
+                .await?;
+            tracing::info!(chain = %chain, height = safe_head, "Deposit indexer cursor initialized");
+            return Ok(IndexProgress::idle(safe_head));
+        };
+
+        if let Some(stored) = cursor_hash {
+            let current = self.source.block_hash(cursor).await?;
+            if current.is_some_and(|h| !h.eq_ignore_ascii_case(&stored)) {
+                let height = self.rewind(cursor).await?;
+                return Ok(IndexProgress {
+                    cursor: height,
+                    inserted: 0,
+                    caught_up: false,
+                });
+            }
+        }
+
+        let Some((from, to)) = scan_window(cursor, safe_head, self.config.max_range) else {
+            return Ok(IndexProgress::idle(cursor));
+        };
+
+        let wallets = self.load_watched_wallets().await?;
+        // 先取区间末块哈希：扫描期间若发生重组，下一轮哈希校验即可发现
+        let to_hash = self.source.block_hash(to).await?;
+        let transfers = if wallets.is_empty() {
+            Vec::new()
+        } else {
+            let addresses: Vec<String> = wallets.keys().cloned().collect();
+            self.source.scan(from, to, &addresses).await?
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let mut detected = Vec::new();
+        for transfer in &transfers {
+            let key = normalize_address(&chain, &transfer.to_address);
+            let Some(wallet) = wallets.get(&key) else {
+                continue;
+            };
+            let Some(token) = self.resolve_asset(&transfer.asset).await? else {
+                tracing::debug!(chain = %chain, tx_hash = %transfer.tx_hash, asset = ?transfer.asset, "Skipping transfer of unregistered token");
+                continue;
+            };
+            let amount = match TokenAmount::from_base_units(transfer.base_units, token.decimals)
+                .to_decimal()
+            {
+                Ok(amount) => amount,
+                Err(e) => {
+                    tracing::warn!(chain = %chain, tx_hash = %transfer.tx_hash, error = %e, "Skipping transfer with unrepresentable amount");
+                    continue;
+                }
+            };
+
+            let metadata = json!({
+                "source": "deposit_indexer",
+                "event_key": transfer.event_key,
+                "token_address": token.address,
+                "base_units": transfer.base_units.to_string(),
+            });
+            let result = sqlx::query(
+                r#"INSERT INTO transactions
+                   (id, tenant_id, user_id, wallet_id, chain, tx_hash, tx_type, status,
+                    from_address, to_address, amount, token_symbol, gas_fee, metadata,
+                    block_number, event_key, created_at, updated_at, confirmed_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, 'receive', 'confirmed',
+                           $7, $8, $9, $10, '0', $11,
+                           $12, $13, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                   ON CONFLICT DO NOTHING"#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(wallet.tenant_id)
+            .bind(wallet.user_id)
+            .bind(wallet.id)
+            .bind(&chain)
+            .bind(&transfer.tx_hash)
+            .bind(&transfer.from_address)
+            .bind(&key)
+            .bind(amount)
+            .bind(&token.symbol)
+            .bind(metadata)
+            .bind(transfer.block_number as i64)
+            .bind(&transfer.event_key)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                detected.push((wallet.id, wallet.address.clone(), transfer.tx_hash.clone()));
+            }
+        }
+        self.save_cursor(&mut *tx, to, to_hash.as_deref()).await?;
+        tx.commit().await?;
+
+        if !detected.is_empty() {
+            tracing::info!(chain = %chain, from, to, deposits = detected.len(), "Indexed incoming transfers");
+        }
+        let inserted = detected.len();
+        self.emit(detected);
+
+        Ok(IndexProgress {
+            cursor: to,
+            inserted,
+            caught_up: to >= safe_head,
+        })
+    }
+
+    /// 重组回退：游标退回 `rewind_target`，删除其后由索引器写入的入账记录
+    async fn rewind(&self, cursor: u64) -> Result<u64> {
+        let chain = self.source.chain();
+        let target = rewind_target(cursor, self.config.reorg_rewind);
+        let target_hash = self.source.block_hash(target).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let removed: Vec<(Option<Uuid>, String, Option<String>)> = sqlx::query_as(
+            r#"DELETE FROM transactions
+               WHERE chain = $1 AND tx_type = 'receive'
+                 AND event_key IS NOT NULL AND block_number > $2
+               RETURNING wallet_id, to_address, tx_hash"#,
+        )
+        .bind(chain)
+        .bind(target as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+        self.save_cursor(&mut *tx, target, target_hash.as_deref())
+            .await?;
+        tx.commit().await?;
+
+        tracing::warn!(
+            chain = %chain,
+            from = cursor,
+            to = target,
+            removed = removed.len(),
+            "Chain reorganization detected, deposit cursor rewound"
+        );
+
+        self.emit(
+            removed
+                .into_iter()
+                .filter_map(|(wallet_id, address, tx_hash)| {
+                    Some((wallet_id?, address, tx_hash.unwrap_or_default()))
+                })
+                .collect(),
+        );
+        Ok(target)
+    }
+
+    /// 每个钱包只发一次事件（余额同步按钱包维度）
+    fn emit(&self, items: Vec<(Uuid, String, String)>) {
+        let Some(sender) = &self.event_sender else {
+            return;
+        };
+        let mut seen = HashSet::new();
+        for (wallet_id, address, tx_hash) in items {
+            if !seen.insert(wallet_id) {
+                continue;
+            }
+            let event = BalanceSyncEvent::DepositDetected {
+                chain: self.source.chain().to_string(),
+                wallet_id,
+                address,
+                tx_hash,
+            };
+            if let Err(e) = sender.try_send(event) {
+                tracing::warn!(wallet_id = %wallet_id, error = %e, "Balance sync event dropped");
+            }
+        }
+    }
+
+    async fn load_cursor(&self) -> Result<Option<(u64, Option<String>)>> {
+        let row: Option<(i64, Option<String>)> =
+            sqlx::query_as("SELECT height, block_hash FROM deposit_index_cursors WHERE chain = $1")
+                .bind(self.source.chain())
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.map(|(height, hash)| (height.max(0) as u64, hash)))
+    }
+
+    async fn save_cursor<'e, E>(
+        &self,
+        executor: E,
+        height: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"INSERT INTO deposit_index_cursors (chain, height, block_hash, updated_at)
+               VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+               ON CONFLICT (chain) DO UPDATE
+               SET height = EXCLUDED.height,
+                   block_hash = EXCLUDED.block_hash,
+                   updated_at = CURRENT_TIMESTAMP"#,
+        )
+        .bind(self.source.chain())
+        .bind(height as i64)
+        .bind(block_hash)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// 该链所有已注册钱包（按归一化地址索引）
+    async fn load_watched_wallets(&self) -> Result<HashMap<String, WatchedWallet>> {
+        let chain = self.source.chain();
+        let chain_id = chain_normalizer::get_chain_id(chain)?;
+        let wallets: Vec<WatchedWallet> = sqlx::query_as(
+            "SELECT id, tenant_id, user_id, address FROM wallets WHERE chain_id = $1",
+        )
+        .bind(chain_id as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut by_address = HashMap::new();
+        for wallet in wallets {
+            by_address
+                .entry(normalize_address(chain, &wallet.address))
+                .or_insert(wallet);
+        }
+        Ok(by_address)
+    }
+
+    /// 资产 → 符号与精度；代币需在 tokens.registry 中启用
+    async fn resolve_asset(&self, asset: &TransferAsset) -> Result<Option<TokenInfo>> {
+        let chain = self.source.chain();
+        let contract = match asset {
+            TransferAsset::Native => {
+                return Ok(Some(TokenInfo {
+                    symbol: chain_normalizer::get_chain_symbol(chain)?.to_string(),
+                    decimals: native_decimals(chain),
+                    address: None,
+                }))
+            }
+            TransferAsset::Token(contract) => contract,
+        };
+
+        if let Some(cached) = self.token_cache.lock().await.get(contract) {
+            return Ok(cached.clone());
+        }
+
+        let row: Option<(String, i64)> = sqlx::query_as(
+            r#"SELECT symbol, decimals FROM tokens.registry
+               WHERE chain_id = $1 AND LOWER(address) = LOWER($2) AND is_enabled = true
+               ORDER BY priority DESC LIMIT 1"#,
+        )
+        .bind(chain_normalizer::get_chain_id(chain)?)
+        .bind(contract)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let info = row.map(|(symbol, decimals)| TokenInfo {
+            symbol,
+            decimals: decimals.clamp(0, 36) as u32,
+            address: Some(contract.clone()),
+        });
+        self.token_cache
+            .lock()
+            .await
+            .insert(contract.clone(), info.clone());
+        Ok(info)
+    }
+}
+
+/// 单轮索引结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexProgress {
+    /// 本轮结束后的游标高度
+    pub cursor: u64,
+    /// 新入账记录数
+    pub inserted: usize,
+    /// 是否已追平安全高度
+    pub caught_up: bool,
+}
+
+impl IndexProgress {
+    fn idle(cursor: u64) -> Self {
+        Self {
+            cursor,
+            inserted: 0,
+            caught_up: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+
+    use super::*;
+
+    const WATCHED: &str = "0x00000000000000000000000000000000000000aa";
+    const SENDER: &str = "0x00000000000000000000000000000000000000bb";
+    const TOKEN: &str = "0x00000000000000000000000000000000000000cc";
+
+    struct StaticEndpoint(String);
+
+    #[async_trait]
+    impl EndpointProvider for StaticEndpoint {
+        async fn distinct_endpoints(&self, _chain: &str, _count: usize) -> Vec<String> {
+            vec![self.0.clone()]
+        }
+    }
+
+    /// 本地 JSON-RPC 桩：链高 0x20，区块 0x10 含一笔转入 + 一笔失败转入 + 一笔无关转账
+    async fn mock_evm_rpc() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|Json(req): Json<Value>| async move {
+                let params = &req["params"];
+                let result = match req["method"].as_str().unwrap_or_default() {
+                    "eth_blockNumber" => json!("0x20"),
+                    "eth_getBlockByNumber" => {
+                        let number = params[0].as_str().unwrap_or_default().to_string();
+                        let transactions = if number == "0x10" && params[1] == json!(true) {
+                            json!([
+                                { "hash": "0xAAA1", "from": SENDER, "to": WATCHED.to_uppercase().replace("0X", "0x"), "value": "0xde0b6b3a7640000" },
+                                { "hash": "0xaaa2", "from": SENDER, "to": WATCHED, "value": "0x1" },
+                                { "hash": "0xaaa3", "from": SENDER, "to": SENDER, "value": "0x5" },
+                            ])
+                        } else {
+                            json!([])
+                        };
+                        json!({ "number": number, "hash": format!("0xhash{}", number), "transactions": transactions })
+                    }
+                    "eth_getTransactionReceipt" => {
+                        let status = if params[0] == json!("0xaaa2") { "0x0" } else { "0x1" };
+                        json!({ "status": status })
+                    }
+                    "eth_getLogs" => {
+                        let filter = &params[0];
+                        assert_eq!(filter["topics"][0], json!(TRANSFER_TOPIC));
+                        assert_eq!(filter["topics"][2][0], json!(address_topic(WATCHED)));
+                        json!([
+                            {
+                                "address": TOKEN,
+                                "topics": [TRANSFER_TOPIC, address_topic(SENDER), address_topic(WATCHED)],
+                                "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+                                "blockNumber": "0x11",
+                                "transactionHash": "0xbbb1",
+                                "logIndex": "0x3",
+                            },
+                            {
+                                "address": TOKEN,
+                                "topics": [TRANSFER_TOPIC, address_topic(SENDER), address_topic(WATCHED)],
+                                "data": "0x01",
+                                "blockNumber": "0x11",
+                                "transactionHash": "0xbbb2",
+                                "logIndex": "0x4",
+                                "removed": true,
+                            },
+                        ])
+                    }
+                    _ => Value::Null,
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_evm_source_scans_native_and_erc20_transfers() {
+        let url = mock_evm_rpc().await;
+        let source =
+            EvmDepositSource::with_endpoint_provider("ethereum", Arc::new(StaticEndpoint(url)));
+
+        assert_eq!(source.head().await.unwrap(), 0x20);
+        assert_eq!(
+            source.block_hash(0x10).await.unwrap().as_deref(),
+            Some("0xhash0x10")
+        );
+
+        let transfers = source
+            .scan(0x10, 0x11, &[WATCHED.to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            transfers,
+            vec![
+                IncomingTransfer {
+                    tx_hash: "0xaaa1".to_string(),
+                    event_key: "native".to_string(),
+                    block_number: 0x10,
+                    from_address: SENDER.to_string(),
+                    to_address: WATCHED.to_string(),
+                    asset: TransferAsset::Native,
+                    base_units: U256::exp10(18),
+                },
+                IncomingTransfer {
+                    tx_hash: "0xbbb1".to_string(),
+                    event_key: "log:3".to_string(),
+                    block_number: 0x11,
+                    from_address: SENDER.to_string(),
+                    to_address: WATCHED.to_string(),
+                    asset: TransferAsset::Token(TOKEN.to_string()),
+                    base_units: U256::from(1_000_000u64),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_window_respects_confirmations_and_range() {
+        assert_eq!(scan_window(100, 100, 20), None);
+        assert_eq!(scan_window(101, 100, 20), None);
+        assert_eq!(scan_window(100, 105, 20), Some((101, 105)));
+        assert_eq!(scan_window(100, 500, 20), Some((101, 120)));
+        assert_eq!(rewind_target(100, 24), 76);
+        assert_eq!(rewind_target(3, 24), 0);
+    }
+
+    #[test]
+    fn test_solana_incoming_native_and_spl() {
+        let owner = "Recv1111111111111111111111111111111111111";
+        let tx = json!({
+            "meta": {
+                "err": null,
+                "preBalances": [5_000_000_000u64, 1_000_000_000u64],
+                "postBalances": [3_999_995_000u64, 2_000_000_000u64],
+                "preTokenBalances": [
+                    { "accountIndex": 2, "mint": "MintA", "owner": owner, "uiTokenAmount": { "amount": "100" } }
+                ],
+                "postTokenBalances": [
+                    { "accountIndex": 2, "mint": "MintA", "owner": owner, "uiTokenAmount": { "amount": "350" } },
+                    { "accountIndex": 3, "mint": "MintA", "owner": "Other", "uiTokenAmount": { "amount": "999" } }
+                ]
+            },
+            "transaction": { "message": { "accountKeys": [
+                { "pubkey": "Payer111" }, { "pubkey": owner }
+            ] } }
+        });
+
+        let transfers = solana_incoming(&tx, owner, "sig1", 42);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].asset, TransferAsset::Native);
+        assert_eq!(transfers[0].base_units, U256::from(1_000_000_000u64));
+        assert_eq!(transfers[0].from_address, "Payer111");
+        assert_eq!(
+            transfers[1].asset,
+            TransferAsset::Token("MintA".to_string())
+        );
+        assert_eq!(transfers[1].event_key, "spl:MintA");
+        assert_eq!(transfers[1].base_units, U256::from(250u64));
+    }
+
+    #[test]
+    fn test_bitcoin_incoming_skips_own_change() {
+        let address = "bc1qwatched";
+        let deposit = json!({
+            "txid": "t1",
+            "vin": [{ "prevout": { "scriptpubkey_address": "bc1qsender" } }],
+            "vout": [
+                { "scriptpubkey_address": "bc1qsender", "value": 500 },
+                { "scriptpubkey_address": address, "value": 12_000 }
+            ]
+        });
+        let transfers = bitcoin_incoming(&deposit, address, 800_000);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].event_key, "vout:1");
+        assert_eq!(transfers[0].base_units, U256::from(12_000u64));
+
+        let change = json!({
+            "txid": "t2",
+            "vin": [{ "prevout": { "scriptpubkey_address": address } }],
+            "vout": [{ "scriptpubkey_address": address, "value": 700 }]
+        });
+        assert!(bitcoin_incoming(&change, address, 800_001).is_empty());
+    }
+
+    #[test]
+    fn test_ton_incoming_ignores_external_messages() {
+        let internal = json!({
+            "utime": 1_700_000_000u64,
+            "transaction_id": { "lt": "123", "hash": "h1" },
+            "in_msg": { "source": "EQsender", "value": "1500000000" }
+        });
+        let transfer = ton_incoming(&internal, "EQwatched").unwrap();
+        assert_eq!(transfer.event_key, "msg:123");
+        assert_eq!(transfer.base_units, U256::from(1_500_000_000u64));
+
+        let external = json!({
+            "utime": 1_700_000_001u64,
+            "transaction_id": { "lt": "124", "hash": "h2" },
+            "in_msg": { "source": "", "value": "0" }
+        });
+        assert!(ton_incoming(&external, "EQwatched").is_none());
+    }
+
+    #[test]
+    fn test_topic_address_roundtrip() {
+        let topic = address_topic("0x00000000000000000000000000000000000000AA");
+        assert_eq!(topic.len(), 66);
+        assert_eq!(topic_address(&topic).unwrap(), WATCHED);
+    }
+}
@@ -11,6 +11,8 @@ use std::{
 use anyhow::Result;
 use tokio::sync::RwLock;
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 /// 费用缓存项
 struct FeeCacheItem {
     fee: f64,
@@ -94,6 +96,7 @@ impl DynamicFeeService {
         match client
             .post(&self.solana_rpc_url)
             .json(&request_body)
+            .with_trace_context()
             .send()
             .await
         {
@@ -171,7 +174,7 @@ impl DynamicFeeService {
         // 使用Blockstream API获取费用估算
         let url = format!("{}/fee-estimates", self.bitcoin_api_url);
         let client = reqwest::Client::new();
-        let response = client.get(&url).send().await?;
+        let response = client.get(&url).with_trace_context().send().await?;
 
         if response.status().is_success() {
             let estimates: HashMap<String, f64> = response.json().await?;
@@ -236,6 +239,7 @@ impl DynamicFeeService {
             // 简化实现：使用固定基础费用 + 动态调整
             match client
                 .get(format!("{}/getAddressInformation", self.ton_api_url))
+                .with_trace_context()
                 .send()
                 .await
             {
@@ -5,11 +5,12 @@ use std::{
 };
 
 use anyhow::Result;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use tokio::sync::RwLock;
 
-use crate::infrastructure::cache::RedisCtx;
+use crate::{domain::token_amount::TokenAmount, infrastructure::cache::RedisCtx};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FeeRule {
@@ -28,6 +29,8 @@ pub struct FeeRule {
 #[derive(Clone, Debug)]
 pub struct FeeCalcResult {
     pub platform_fee: f64,
+    /// 精确费用（最小单位），仅 `calculate_fee_for_amount` 填充
+    pub platform_fee_exact: Option<TokenAmount>,
     pub collector_address: String,
     pub applied_rule_id: uuid::Uuid,
     pub rule_version: i32,
@@ -333,6 +336,48 @@ impl FeeService {
         // 这两个费用是完全独立的，不能混淆！
         Ok(Some(FeeCalcResult {
             platform_fee: fee, // 平台服务费：钱包服务商收取的服务费用
+            platform_fee_exact: None,
+            collector_address: collector,
+            applied_rule_id: rule.id,
+            rule_version: rule.rule_version,
+        }))
+    }
+
+    /// 按精确金额计算平台服务费（企业级实现）
+    ///
+    /// 与 `calculate_fee` 相同的规则语义，但在最小单位上使用 U256 整数运算，
+    /// 不会因 18 位精度代币或大额金额产生浮点误差
+    pub async fn calculate_fee_for_amount(
+        &self,
+        chain: &str,
+        operation: &str,
+        amount: &TokenAmount,
+    ) -> Result<Option<FeeCalcResult>> {
+        if chain.trim().is_empty() || operation.trim().is_empty() || amount.is_zero() {
+            tracing::warn!(
+                "费用计算参数无效: chain={}, operation={}, amount={}",
+                chain,
+                operation,
+                amount
+            );
+            return Ok(None);
+        }
+
+        crate::metrics::inc_fee_calculation();
+        let Some(rule) = self.get_active_rule(chain, operation).await? else {
+            return Ok(None);
+        };
+        let Some(collector) = self.get_collector_address(chain).await? else {
+            return Ok(None);
+        };
+
+        let fee = compute_exact_fee(&rule, amount)?;
+        let fee_f64 = fee.to_f64_lossy();
+        crate::metrics::add_fee_amount(fee_f64);
+
+        Ok(Some(FeeCalcResult {
+            platform_fee: fee_f64,
+            platform_fee_exact: Some(fee),
             collector_address: collector,
             applied_rule_id: rule.id,
             rule_version: rule.rule_version,
@@ -355,7 +400,7 @@ impl FeeService {
         user_id: uuid::Uuid,
         chain: &str,
         operation: &str,
-        original_amount: f64,
+        original_amount: &TokenAmount,
         calc: &FeeCalcResult,
         wallet_address: &str,
         tx_hash: Option<&str>, // 交易哈希（用于后续transaction_monitor回填gas_fee_native）
@@ -381,6 +426,14 @@ impl FeeService {
             }
         }
 
+        // 有精确费用时按十进制写入，避免 f64 舍入误差
+        let original_amount = original_amount.to_decimal()?;
+        let platform_fee = match &calc.platform_fee_exact {
+            Some(fee) => fee.to_decimal()?,
+            None => Decimal::from_f64(calc.platform_fee)
+                .ok_or_else(|| anyhow::anyhow!("Invalid platform fee: {}", calc.platform_fee))?,
+        };
+
         // 企业级实现：只记录平台服务费（platform_fee）
         // Gas费用（gas_fee_native）由transaction_monitor服务在交易确认后回填
         let res = sqlx::query(
@@ -391,7 +444,7 @@ impl FeeService {
         .bind(chain)
         .bind(operation)
         .bind(original_amount)
-        .bind(calc.platform_fee)  // 平台服务费：钱包服务商收取的服务费用
+        .bind(platform_fee)  // 平台服务费：钱包服务商收取的服务费用
         .bind("computed")
         .bind(calc.applied_rule_id)
         .bind(&calc.collector_address)
@@ -408,6 +461,75 @@ impl FeeService {
     }
 }
 
+/// 精确计算平台服务费（最小单位）
+///
+/// 规则中的 flat_amount / min_fee / max_fee 为代币单位的配置值，
+/// 按金额精度换算后参与整数运算；百分比部分向下取整
+pub fn compute_exact_fee(rule: &FeeRule, amount: &TokenAmount) -> Result<TokenAmount> {
+    let decimals = amount.decimals();
+    let percent_bp = u32::try_from(rule.percent_bp)
+        .map_err(|_| anyhow::anyhow!("Invalid percent_bp: {}", rule.percent_bp))?;
+
+    let percent_part = || -> Result<TokenAmount> {
+        let raw = amount.checked_mul_bp(percent_bp)?;
+        let min_fee = config_amount(rule.min_fee, decimals)?;
+        Ok(max_amount(raw, min_fee))
+    };
+
+    let fee = match rule.fee_type.as_str() {
+        "flat" => config_amount(rule.flat_amount, decimals)?,
+        "percent" => percent_part()?,
+        "mixed" => config_amount(rule.flat_amount, decimals)?.checked_add(&percent_part()?)?,
+        _ => TokenAmount::zero(decimals),
+    };
+
+    // 固定费用不受 max_fee 约束，与 calculate_fee 保持一致
+    let fee = match (rule.fee_type.as_str(), rule.max_fee) {
+        ("percent" | "mixed", Some(max)) => min_amount(fee, config_amount(max, decimals)?),
+        _ => fee,
+    };
+
+    Ok(fee)
+}
+
+/// 将费率配置值（f64列）换算为最小单位，超出代币精度的部分截断
+fn config_amount(value: f64, decimals: u32) -> Result<TokenAmount> {
+    if !value.is_finite() || value < 0.0 {
+        anyhow::bail!("Invalid fee rule value: {}", value);
+    }
+
+    // f64 的 Display 输出最短往返十进制表示（无科学计数法）
+    let repr = value.to_string();
+    let truncated = match repr.split_once('.') {
+        Some((int_part, frac_part)) if frac_part.len() > decimals as usize => {
+            if decimals == 0 {
+                int_part.to_string()
+            } else {
+                format!("{}.{}", int_part, &frac_part[..decimals as usize])
+            }
+        }
+        _ => repr,
+    };
+
+    Ok(TokenAmount::parse(&truncated, decimals)?)
+}
+
+fn max_amount(a: TokenAmount, b: TokenAmount) -> TokenAmount {
+    if a.base_units() >= b.base_units() {
+        a
+    } else {
+        b
+    }
+}
+
+fn min_amount(a: TokenAmount, b: TokenAmount) -> TokenAmount {
+    if a.base_units() <= b.base_units() {
+        a
+    } else {
+        b
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
@@ -648,6 +770,57 @@ mod tests {
         assert_eq!(final_fee, 1.5, "Final fee should be within all constraints");
     }
 
+    fn rule(fee_type: &str, flat: f64, bp: i32, min: f64, max: Option<f64>) -> FeeRule {
+        FeeRule {
+            id: uuid::Uuid::nil(),
+            chain: "ethereum".to_string(),
+            operation: "transfer".to_string(),
+            fee_type: fee_type.to_string(),
+            flat_amount: flat,
+            percent_bp: bp,
+            min_fee: min,
+            max_fee: max,
+            priority: 0,
+            rule_version: 1,
+        }
+    }
+
+    /// Test: 精确费用计算（18位精度大额金额无浮点误差）
+    #[test]
+    fn test_exact_percent_fee_large_amount() {
+        // 123456789.123456789123456789 * 0.3%
+        let amount = TokenAmount::parse("123456789.123456789123456789", 18).unwrap();
+        let fee = compute_exact_fee(&rule("percent", 0.0, 30, 0.0, None), &amount).unwrap();
+        assert_eq!(fee.to_human_string(), "370370.36737037036737037");
+    }
+
+    /// Test: 精确费用计算的最小/最大费用约束
+    #[test]
+    fn test_exact_fee_bounds() {
+        let amount = TokenAmount::parse("10", 6).unwrap();
+
+        let min_applied = compute_exact_fee(&rule("percent", 0.0, 10, 0.5, None), &amount).unwrap();
+        assert_eq!(min_applied.to_human_string(), "0.5");
+
+        let max_applied =
+            compute_exact_fee(&rule("percent", 0.0, 5_000, 0.0, Some(1.25)), &amount).unwrap();
+        assert_eq!(max_applied.to_human_string(), "1.25");
+
+        let mixed = compute_exact_fee(&rule("mixed", 0.1, 100, 0.0, None), &amount).unwrap();
+        assert_eq!(mixed.to_human_string(), "0.2");
+
+        let flat = compute_exact_fee(&rule("flat", 0.1234567, 0, 0.0, None), &amount).unwrap();
+        assert_eq!(flat.to_human_string(), "0.123456");
+    }
+
+    /// Test: 非法规则配置
+    #[test]
+    fn test_exact_fee_invalid_rule() {
+        let amount = TokenAmount::parse("1", 18).unwrap();
+        assert!(compute_exact_fee(&rule("percent", 0.0, -1, 0.0, None), &amount).is_err());
+        assert!(compute_exact_fee(&rule("flat", f64::NAN, 0, 0.0, None), &amount).is_err());
+    }
+
     // ============ 集成测试（需要数据库）============
 
     // 注意：以下测试需要真实数据库连接，在 CI/CD 中使用 testcontainers
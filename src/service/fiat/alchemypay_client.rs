@@ -0,0 +1,380 @@
+//! AlchemyPay API客户端
+//!
+//! AlchemyPay专注亚洲市场与Web3场景
+//! 优势：
+//! - 支持支付宝/微信及本地钱包
+//! - Binance/OKX 合作通道
+//! - 费率2%-4%
+//!
+//! API文档: https://alchemypay.readme.io/
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+
+use super::{
+    provider::{
+        env_non_empty, verify_hmac_sha256_hex, FiatOrderRequest, FiatProvider, FiatProviderOrder,
+        FiatProviderQuote, FiatQuoteRequest,
+    },
+    reconciliation_source::parse_amount,
+};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 报价有效期（秒）
+const QUOTE_TTL_SECS: i64 = 60;
+
+/// 接口成功返回码
+const SUCCESS_CODE: &str = "0000";
+
+/// AlchemyPay客户端配置
+pub struct AlchemyPayClient {
+    app_id: String,
+    secret: String,
+    /// 回调签名密钥（ALCHEMYPAY_WEBHOOK_SECRET，与 API 签名 secret 不同）
+    webhook_secret: Option<String>,
+    /// 加密货币所在网络（如 ETH, BSC, TRX）
+    network: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl AlchemyPayClient {
+    /// 创建新的AlchemyPay客户端
+    pub fn new(app_id: &str, secret: &str) -> Result<Self> {
+        Ok(Self {
+            app_id: app_id.to_string(),
+            secret: secret.to_string(),
+            webhook_secret: None,
+            network: "ETH".to_string(),
+            base_url: "https://openapi.alchemypay.org".to_string(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .context("Failed to create HTTP client")?,
+        })
+    }
+
+    /// 覆盖 API 地址（沙箱 / 测试桩）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 设置 Webhook 签名密钥
+    pub fn with_webhook_secret(mut self, secret: &str) -> Self {
+        self.webhook_secret = Some(secret.to_string());
+        self
+    }
+
+    /// 设置加密货币网络（默认 ETH）
+    pub fn with_network(mut self, network: &str) -> Self {
+        self.network = network.to_uppercase();
+        self
+    }
+
+    /// 生成API签名：Base64(HMAC-SHA256(secret, timestamp + method + path + body))
+    fn generate_signature(
+        &self,
+        timestamp: i64,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> Result<String> {
+        use base64::Engine;
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let message = format!("{}{}{}{}", timestamp, method, path, body);
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).context("Invalid secret key")?;
+        mac.update(message.as_bytes());
+
+        Ok(base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+    }
+
+    /// 发送签名请求并解包 `{success, returnCode, returnMsg, data}`
+    async fn signed_request<T: Serialize>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&T>,
+    ) -> Result<serde_json::Value> {
+        let body_str = match body {
+            Some(body) => serde_json::to_string(body).context("Failed to serialize payload")?,
+            None => String::new(),
+        };
+        let timestamp = Utc::now().timestamp_millis();
+        let signature = self.generate_signature(timestamp, method.as_str(), path, &body_str)?;
+
+        let mut builder = self
+            .client
+            .request(method, format!("{}{}", self.base_url, path))
+            .header("appId", &self.app_id)
+            .header("timestamp", timestamp.to_string())
+            .header("sign", signature);
+        if body.is_some() {
+            builder = builder
+                .header("Content-Type", "application/json")
+                .body(body_str);
+        }
+
+        let response = builder
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to send request to AlchemyPay")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ AlchemyPay API错误 ({}): {}", status, error_text);
+            return Err(anyhow!("AlchemyPay API返回错误: {}", status));
+        }
+
+        let envelope: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse AlchemyPay response")?;
+        if envelope["returnCode"].as_str() != Some(SUCCESS_CODE) {
+            return Err(anyhow!(
+                "AlchemyPay业务错误 {}: {}",
+                envelope["returnCode"].as_str().unwrap_or("unknown"),
+                envelope["returnMsg"].as_str().unwrap_or_default()
+            ));
+        }
+
+        Ok(envelope["data"].clone())
+    }
+}
+
+/// 本地支付方式 → AlchemyPay 通道编码
+///
+/// 通道编码以商户后台开通的为准，可通过 `ALCHEMYPAY_PAYWAY_<METHOD>` 覆盖。
+fn pay_way_code(payment_method: &str) -> String {
+    let key = format!("ALCHEMYPAY_PAYWAY_{}", payment_method.to_uppercase());
+    if let Some(code) = env_non_empty(&key) {
+        return code;
+    }
+    match payment_method {
+        "credit_card" | "debit_card" => "10001",
+        "apple_pay" => "501",
+        "google_pay" => "701",
+        "alipay" => "52002",
+        "wechat_pay" => "52003",
+        "bank_transfer" => "60001",
+        other => other,
+    }
+    .to_string()
+}
+
+/// AlchemyPay 订单状态 → 本地订单状态
+pub(crate) fn normalize_status(status: &str) -> &'static str {
+    match status.to_uppercase().as_str() {
+        "NEW" | "PENDING" => "pending",
+        "PAY_SUCCESS" | "PROCESSING" => "processing",
+        "FINISHED" | "SUCCESS" => "completed",
+        "CANCEL" | "CANCELLED" => "cancelled",
+        "REFUND" | "REFUNDED" => "refunded",
+        "EXPIRED" | "TIMEOUT" => "expired",
+        _ => "failed",
+    }
+}
+
+#[async_trait::async_trait]
+impl FiatProvider for AlchemyPayClient {
+    fn name(&self) -> &str {
+        "alchemypay"
+    }
+
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+        tracing::info!(
+            "🌐 调用AlchemyPay API获取报价: {} {} → {}",
+            request.fiat_amount,
+            request.fiat_currency,
+            request.crypto_currency
+        );
+
+        let data = self
+            .signed_request(
+                reqwest::Method::POST,
+                "/open/api/v4/merchant/order/quote",
+                Some(&serde_json::json!({
+                    "side": "BUY",
+                    "fiat": request.fiat_currency.to_uppercase(),
+                    "crypto": request.crypto_currency.to_uppercase(),
+                    "network": self.network,
+                    "amount": request.fiat_amount.to_string(),
+                    "payWayCode": pay_way_code(&request.payment_method),
+                })),
+            )
+            .await?;
+
+        let fee_amount = parse_amount(&data["rampFee"]).unwrap_or_default()
+            + parse_amount(&data["networkFee"]).unwrap_or_default();
+
+        Ok(FiatProviderQuote {
+            provider: self.name().to_string(),
+            quote_id: format!("alchemypay:{}", Utc::now().timestamp_millis()),
+            fiat_amount: parse_amount(&data["fiatQuantity"]).unwrap_or(request.fiat_amount),
+            crypto_amount: parse_amount(&data["cryptoQuantity"])
+                .context("AlchemyPay quote missing cryptoQuantity")?,
+            fee_amount,
+            estimated_arrival_minutes: None,
+            expires_at: Utc::now() + chrono::Duration::seconds(QUOTE_TTL_SECS),
+        })
+    }
+
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+        tracing::info!(
+            "🌐 调用AlchemyPay API创建订单: merchant_order={}, wallet={}",
+            request.order_id,
+            request.wallet_address
+        );
+
+        let data = self
+            .signed_request(
+                reqwest::Method::POST,
+                "/open/api/v4/merchant/trade/create",
+                Some(&serde_json::json!({
+                    "side": "BUY",
+                    "merchantOrderNo": request.order_id,
+                    "amount": request.fiat_amount.to_string(),
+                    "fiatCurrency": request.fiat_currency.to_uppercase(),
+                    "cryptoCurrency": request.crypto_currency.to_uppercase(),
+                    "network": self.network,
+                    "address": request.wallet_address,
+                    "payWayCode": pay_way_code(&request.payment_method),
+                    "userId": request.user_id,
+                    "email": request.email,
+                    "redirectUrl": request.return_url,
+                    "callbackUrl": request.webhook_url,
+                })),
+            )
+            .await?;
+
+        let provider_order_id = data["orderNo"]
+            .as_str()
+            .context("AlchemyPay order missing orderNo")?;
+
+        Ok(FiatProviderOrder {
+            provider_order_id: provider_order_id.to_string(),
+            payment_url: data["payUrl"].as_str().map(str::to_string),
+            status: "pending".to_string(),
+            raw_status: "NEW".to_string(),
+        })
+    }
+
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder> {
+        let path = format!(
+            "/open/api/v4/merchant/query/trade?orderNo={}",
+            provider_order_id
+        );
+        let data = self
+            .signed_request::<serde_json::Value>(reqwest::Method::GET, &path, None)
+            .await?;
+        let raw_status = data["status"].as_str().unwrap_or_default();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: provider_order_id.to_string(),
+            payment_url: data["payUrl"].as_str().map(str::to_string),
+            status: normalize_status(raw_status).to_string(),
+            raw_status: raw_status.to_string(),
+        })
+    }
+
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()> {
+        Err(anyhow!(
+            "AlchemyPay不支持通过API取消订单: {}",
+            provider_order_id
+        ))
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool {
+        // X-Alchemy-Signature：HMAC-SHA256(webhook secret, body) 十六进制
+        match &self.webhook_secret {
+            Some(secret) => verify_hmac_sha256_hex(secret, payload, signature),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    /// 录制的 AlchemyPay 报价：请求需携带 appId/timestamp/sign 头，费用 = rampFee + networkFee
+    #[tokio::test]
+    async fn test_alchemypay_quote_from_fixture() {
+        use axum::{http::HeaderMap, routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/open/api/v4/merchant/order/quote",
+            post(
+                |headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                    assert_eq!(headers["appId"], "app");
+                    assert!(headers.contains_key("sign"));
+                    assert_eq!(body["side"], "BUY");
+                    assert_eq!(body["payWayCode"], "52002");
+                    Json(
+                        serde_json::from_str::<serde_json::Value>(include_str!(
+                            "../../../tests/fixtures/fiat/alchemypay_quote.json"
+                        ))
+                        .unwrap(),
+                    )
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = AlchemyPayClient::new("app", "secret")
+            .unwrap()
+            .with_base_url(&format!("http://{}", addr));
+        let quote = client
+            .quote(&FiatQuoteRequest {
+                fiat_currency: "CNY".to_string(),
+                crypto_currency: "USDT".to_string(),
+                fiat_amount: Decimal::from(1000),
+                payment_method: "alipay".to_string(),
+                country: "CN".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(quote.provider, "alchemypay");
+        assert_eq!(quote.crypto_amount, Decimal::from_str("134.8577").unwrap());
+        assert_eq!(quote.fee_amount, Decimal::from(27));
+    }
+
+    #[test]
+    fn test_alchemypay_webhook_uses_webhook_secret() {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let body = br#"{"orderNo":"o1","status":"COMPLETED"}"#;
+        let sign = |secret: &[u8]| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+            mac.update(body);
+            hex::encode(mac.finalize().into_bytes())
+        };
+
+        let client = AlchemyPayClient::new("app", "api_secret")
+            .unwrap()
+            .with_webhook_secret("whsec");
+        assert!(client.verify_webhook(body, &sign(b"whsec")));
+        // API 签名 secret 不能用于伪造回调
+        assert!(!client.verify_webhook(body, &sign(b"api_secret")));
+        assert!(!AlchemyPayClient::new("app", "api_secret")
+            .unwrap()
+            .verify_webhook(body, &sign(b"api_secret")));
+    }
+}
@@ -2,15 +2,88 @@
 //!
 //! 生产级实现，集成真实的第三方支付服务商API
 //!
-//! 支持的服务商：
+//! 支持的服务商（均实现 [`FiatProvider`]）：
 //! - Onramper（聚合器，推荐优先使用）
 //! - TransFi（中国市场）
 //! - AlchemyPay（亚洲市场）
 //! - Ramp Network（欧洲市场）
 //! - MoonPay（美国市场）
 
+use std::sync::Arc;
+
+pub mod alchemypay_client;
+pub mod moonpay_client;
 pub mod onramper_client;
+pub mod provider;
+pub mod ramp_client;
+pub mod reconciliation_source;
 pub mod transfi_client;
 
+pub use alchemypay_client::AlchemyPayClient;
+pub use moonpay_client::MoonPayClient;
 pub use onramper_client::OnramperClient;
+pub use provider::{
+    FiatOrderRequest, FiatProvider, FiatProviderOrder, FiatProviderQuote, FiatQuoteRequest,
+};
+pub use ramp_client::RampClient;
+pub use reconciliation_source::{ProviderOrder, ProviderOrderPage, ProviderReconciliationSource};
 pub use transfi_client::TransFiClient;
+
+use provider::env_non_empty;
+
+/// 从环境变量构建直连服务商客户端（MoonPay / Ramp / AlchemyPay），未配置的跳过
+pub fn direct_providers_from_env() -> Vec<Arc<dyn FiatProvider>> {
+    let mut providers: Vec<Arc<dyn FiatProvider>> = Vec::new();
+
+    if let (Some(api_key), Some(secret_key)) = (
+        env_non_empty("MOONPAY_API_KEY"),
+        env_non_empty("MOONPAY_SECRET_KEY"),
+    ) {
+        match MoonPayClient::new(&api_key, &secret_key) {
+            Ok(mut client) => {
+                if let Some(secret) = env_non_empty("MOONPAY_WEBHOOK_SECRET") {
+                    client = client.with_webhook_secret(&secret);
+                }
+                providers.push(Arc::new(client));
+            }
+            Err(e) => tracing::warn!("⚠️ MoonPay客户端初始化失败: {}", e),
+        }
+    }
+
+    if let Some(api_key) = env_non_empty("RAMP_API_KEY") {
+        let client = RampClient::new(&api_key).and_then(|client| {
+            let client = match env_non_empty("RAMP_WEBHOOK_PUBLIC_KEY") {
+                Some(key) => client.with_webhook_public_key(&key)?,
+                None => client,
+            };
+            Ok(match env_non_empty("RAMP_ASSET_CHAIN") {
+                Some(chain) => client.with_asset_chain(&chain),
+                None => client,
+            })
+        });
+        match client {
+            Ok(client) => providers.push(Arc::new(client)),
+            Err(e) => tracing::warn!("⚠️ Ramp客户端初始化失败: {}", e),
+        }
+    }
+
+    if let (Some(app_id), Some(secret)) = (
+        env_non_empty("ALCHEMYPAY_APP_ID"),
+        env_non_empty("ALCHEMYPAY_SECRET"),
+    ) {
+        match AlchemyPayClient::new(&app_id, &secret) {
+            Ok(mut client) => {
+                if let Some(network) = env_non_empty("ALCHEMYPAY_NETWORK") {
+                    client = client.with_network(&network);
+                }
+                if let Some(secret) = env_non_empty("ALCHEMYPAY_WEBHOOK_SECRET") {
+                    client = client.with_webhook_secret(&secret);
+                }
+                providers.push(Arc::new(client));
+            }
+            Err(e) => tracing::warn!("⚠️ AlchemyPay客户端初始化失败: {}", e),
+        }
+    }
+
+    providers
+}
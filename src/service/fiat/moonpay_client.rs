@@ -0,0 +1,385 @@
+//! MoonPay API客户端
+//!
+//! MoonPay是全球品牌服务商（兜底通道）
+//! 优势：
+//! - 覆盖欧美及亚太主流市场
+//! - 信用卡/Apple Pay/Google Pay
+//! - 品牌信任度高
+//!
+//! API文档: https://dev.moonpay.com/
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::Deserialize;
+
+use super::{
+    provider::{
+        verify_hmac_sha256_hex, FiatOrderRequest, FiatProvider, FiatProviderOrder,
+        FiatProviderQuote, FiatQuoteRequest,
+    },
+    reconciliation_source::parse_amount,
+};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 报价有效期（秒）
+const QUOTE_TTL_SECS: i64 = 60;
+
+/// Webhook 时间戳允许的偏差（秒），超出视为重放
+const WEBHOOK_TOLERANCE_SECS: i64 = 300;
+
+/// MoonPay客户端配置
+pub struct MoonPayClient {
+    /// 可公开的 publishable key（pk_live_...）
+    api_key: String,
+    /// 服务端 secret key（sk_live_...），用于签名支付链接与查单
+    secret_key: String,
+    webhook_secret: Option<String>,
+    base_url: String,
+    widget_url: String,
+    client: reqwest::Client,
+}
+
+/// MoonPay 买入报价响应
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MoonPayBuyQuote {
+    base_currency_amount: serde_json::Value,
+    quote_currency_amount: serde_json::Value,
+    fee_amount: serde_json::Value,
+    #[serde(default)]
+    extra_fee_amount: serde_json::Value,
+    #[serde(default)]
+    network_fee_amount: serde_json::Value,
+}
+
+impl MoonPayClient {
+    /// 创建新的MoonPay客户端
+    pub fn new(api_key: &str, secret_key: &str) -> Result<Self> {
+        Ok(Self {
+            api_key: api_key.to_string(),
+            secret_key: secret_key.to_string(),
+            webhook_secret: None,
+            base_url: "https://api.moonpay.com".to_string(),
+            widget_url: "https://buy.moonpay.com".to_string(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .context("Failed to create HTTP client")?,
+        })
+    }
+
+    /// 覆盖 API 地址（沙箱 / 测试桩）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 设置 Webhook 签名密钥
+    pub fn with_webhook_secret(mut self, secret: &str) -> Self {
+        self.webhook_secret = Some(secret.to_string());
+        self
+    }
+
+    /// 生成带签名的支付链接
+    ///
+    /// MoonPay 要求对以 `?` 开头的完整查询串做 HMAC-SHA256，Base64 后作为 `signature` 追加。
+    fn signed_widget_url(&self, params: &[(&str, String)]) -> Result<String> {
+        use base64::Engine;
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let mut url = reqwest::Url::parse_with_params(&self.widget_url, params)
+            .context("Invalid MoonPay widget URL")?;
+        let query = format!("?{}", url.query().unwrap_or_default());
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
+            .context("Invalid MoonPay secret key")?;
+        mac.update(query.as_bytes());
+        let signature =
+            base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        url.query_pairs_mut().append_pair("signature", &signature);
+
+        Ok(url.to_string())
+    }
+
+    /// 校验 Moonpay-Signature-V2：`t=<timestamp>,s=<hex>`，签名内容为 `<timestamp>.<body>`
+    fn verify_webhook_at(&self, payload: &[u8], signature: &str, now: i64) -> bool {
+        let Some(secret) = &self.webhook_secret else {
+            return false;
+        };
+        let mut timestamp = None;
+        let mut sig = None;
+        for part in signature.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", v)) => timestamp = Some(v),
+                Some(("s", v)) => sig = Some(v),
+                _ => {}
+            }
+        }
+        let (Some(timestamp), Some(sig)) = (timestamp, sig) else {
+            return false;
+        };
+        // 时间戳为秒；超出容忍窗口的旧请求即使签名正确也拒绝
+        match timestamp.parse::<i64>() {
+            Ok(ts) if (now - ts).abs() <= WEBHOOK_TOLERANCE_SECS => {}
+            _ => return false,
+        }
+
+        let mut signed = Vec::with_capacity(timestamp.len() + 1 + payload.len());
+        signed.extend_from_slice(timestamp.as_bytes());
+        signed.push(b'.');
+        signed.extend_from_slice(payload);
+        verify_hmac_sha256_hex(secret, &signed, sig)
+    }
+}
+
+/// MoonPay 交易状态 → 本地订单状态
+fn normalize_status(status: &str) -> &'static str {
+    match status.to_lowercase().as_str() {
+        "waitingpayment" | "pending" => "pending",
+        "waitingauthorization" | "processing" => "processing",
+        "completed" => "completed",
+        "cancelled" | "canceled" => "cancelled",
+        "refunded" => "refunded",
+        "expired" => "expired",
+        _ => "failed",
+    }
+}
+
+#[async_trait::async_trait]
+impl FiatProvider for MoonPayClient {
+    fn name(&self) -> &str {
+        "moonpay"
+    }
+
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+        let crypto = request.crypto_currency.to_lowercase();
+        let url = format!("{}/v3/currencies/{}/buy_quote", self.base_url, crypto);
+
+        tracing::info!(
+            "🌐 调用MoonPay API获取报价: {} {} → {}",
+            request.fiat_amount,
+            request.fiat_currency,
+            request.crypto_currency
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[
+                ("apiKey", self.api_key.clone()),
+                ("baseCurrencyCode", request.fiat_currency.to_lowercase()),
+                ("baseCurrencyAmount", request.fiat_amount.to_string()),
+                ("paymentMethod", request.payment_method.clone()),
+                ("areFeesIncluded", "true".to_string()),
+            ])
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to send request to MoonPay")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ MoonPay API错误 ({}): {}", status, error_text);
+            return Err(anyhow!("MoonPay API返回错误: {}", status));
+        }
+
+        let quote = response
+            .json::<MoonPayBuyQuote>()
+            .await
+            .context("Failed to parse MoonPay response")?;
+
+        let fee_amount = [
+            &quote.fee_amount,
+            &quote.extra_fee_amount,
+            &quote.network_fee_amount,
+        ]
+        .into_iter()
+        .filter_map(parse_amount)
+        .sum();
+
+        Ok(FiatProviderQuote {
+            provider: self.name().to_string(),
+            // MoonPay 报价无 ID，下单时按金额重新定价
+            quote_id: format!("moonpay:{}", Utc::now().timestamp_millis()),
+            fiat_amount: parse_amount(&quote.base_currency_amount)
+                .context("MoonPay quote missing baseCurrencyAmount")?,
+            crypto_amount: parse_amount(&quote.quote_currency_amount)
+                .context("MoonPay quote missing quoteCurrencyAmount")?,
+            fee_amount,
+            estimated_arrival_minutes: None,
+            expires_at: Utc::now() + chrono::Duration::seconds(QUOTE_TTL_SECS),
+        })
+    }
+
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+        // MoonPay 买入通过签名后的托管页面完成，交易在用户支付后才生成；
+        // 以本地订单ID作为 externalTransactionId 关联后续查单与 Webhook。
+        let mut params = vec![
+            ("apiKey", self.api_key.clone()),
+            ("currencyCode", request.crypto_currency.to_lowercase()),
+            ("walletAddress", request.wallet_address.clone()),
+            ("baseCurrencyCode", request.fiat_currency.to_lowercase()),
+            ("baseCurrencyAmount", request.fiat_amount.to_string()),
+            ("paymentMethod", request.payment_method.clone()),
+            ("externalTransactionId", request.order_id.clone()),
+            ("externalCustomerId", request.user_id.clone()),
+        ];
+        if let Some(email) = &request.email {
+            params.push(("email", email.clone()));
+        }
+        if let Some(return_url) = &request.return_url {
+            params.push(("redirectURL", return_url.clone()));
+        }
+
+        let payment_url = self.signed_widget_url(&params)?;
+
+        tracing::info!("✅ MoonPay支付链接已生成: external_id={}", request.order_id);
+
+        Ok(FiatProviderOrder {
+            provider_order_id: request.order_id.clone(),
+            payment_url: Some(payment_url),
+            status: "pending".to_string(),
+            raw_status: "waitingPayment".to_string(),
+        })
+    }
+
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder> {
+        let url = format!(
+            "{}/v1/transactions/ext/{}",
+            self.base_url, provider_order_id
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Api-Key {}", self.secret_key))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to query MoonPay transaction")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ MoonPay查单失败 ({}): {}", status, error_text);
+            return Err(anyhow!("MoonPay查单失败: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse MoonPay transaction")?;
+        // 同一 externalTransactionId 可能对应多笔交易（用户重试支付），取最新一笔
+        let tx = body
+            .as_array()
+            .and_then(|txs| txs.last())
+            .ok_or_else(|| anyhow!("MoonPay未找到交易: {}", provider_order_id))?;
+        let raw_status = tx["status"].as_str().unwrap_or_default();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: provider_order_id.to_string(),
+            payment_url: None,
+            status: normalize_status(raw_status).to_string(),
+            raw_status: raw_status.to_string(),
+        })
+    }
+
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()> {
+        Err(anyhow!(
+            "MoonPay不支持通过API取消订单: {}",
+            provider_order_id
+        ))
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool {
+        self.verify_webhook_at(payload, signature, Utc::now().timestamp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, str::FromStr};
+
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    /// 录制的 MoonPay 报价：费用 = 服务费 + 额外费 + 网络费
+    #[tokio::test]
+    async fn test_moonpay_quote_from_fixture() {
+        use axum::{
+            extract::{Path, Query},
+            routing::get,
+            Json, Router,
+        };
+
+        let app = Router::new().route(
+            "/v3/currencies/:code/buy_quote",
+            get(
+                |Path(code): Path<String>, Query(q): Query<HashMap<String, String>>| async move {
+                    assert_eq!(code, "usdt");
+                    assert_eq!(q["apiKey"], "pk_test");
+                    assert_eq!(q["baseCurrencyCode"], "usd");
+                    Json(
+                        serde_json::from_str::<serde_json::Value>(include_str!(
+                            "../../../tests/fixtures/fiat/moonpay_buy_quote.json"
+                        ))
+                        .unwrap(),
+                    )
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = MoonPayClient::new("pk_test", "sk_test")
+            .unwrap()
+            .with_base_url(&format!("http://{}", addr));
+        let quote = client
+            .quote(&FiatQuoteRequest {
+                fiat_currency: "USD".to_string(),
+                crypto_currency: "USDT".to_string(),
+                fiat_amount: Decimal::from(100),
+                payment_method: "credit_card".to_string(),
+                country: "US".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(quote.provider, "moonpay");
+        assert_eq!(quote.crypto_amount, Decimal::from_str("95.12").unwrap());
+        assert_eq!(quote.fee_amount, Decimal::from_str("4.49").unwrap());
+    }
+
+    #[test]
+    fn test_moonpay_webhook_signature_v2() {
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+
+        let client = MoonPayClient::new("pk", "sk")
+            .unwrap()
+            .with_webhook_secret("whsec");
+        let body = br#"{"type":"transaction_updated"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec").unwrap();
+        mac.update(b"1760000000.");
+        mac.update(body);
+        let sig = hex::encode(mac.finalize().into_bytes());
+
+        let header = format!("t=1760000000,s={}", sig);
+        assert!(client.verify_webhook_at(body, &header, 1760000000));
+        assert!(client.verify_webhook_at(body, &header, 1760000000 + WEBHOOK_TOLERANCE_SECS));
+        assert!(!client.verify_webhook_at(body, &format!("t=1760000001,s={}", sig), 1760000000));
+        // 重放：签名正确但时间戳超出窗口
+        assert!(!client.verify_webhook_at(body, &header, 1760000000 + WEBHOOK_TOLERANCE_SECS + 1));
+        assert!(!client.verify_webhook(body, &header));
+        assert!(!MoonPayClient::new("pk", "sk")
+            .unwrap()
+            .verify_webhook_at(body, &header, 1760000000));
+    }
+}
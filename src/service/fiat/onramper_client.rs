@@ -12,12 +12,33 @@
 use std::collections::HashMap;
 
 use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use super::{
+    provider::{
+        verify_hmac_sha256_hex, FiatOrderRequest, FiatProvider, FiatProviderOrder,
+        FiatProviderQuote, FiatQuoteRequest,
+    },
+    reconciliation_source::{
+        day_bounds, parse_amount, parse_timestamp, ProviderOrder, ProviderOrderPage,
+        ProviderReconciliationSource,
+    },
+};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 对账分页大小
+const RECONCILIATION_PAGE_SIZE: usize = 100;
+
+/// 报价有效期（分钟）
+const QUOTE_TTL_MINUTES: i64 = 15;
+
 /// Onramper客户端配置
 pub struct OnramperClient {
     api_key: String,
+    webhook_secret: Option<String>,
     base_url: String,
     client: reqwest::Client,
 }
@@ -98,6 +119,7 @@ impl OnramperClient {
     pub fn new(api_key: &str) -> Result<Self> {
         Ok(Self {
             api_key: api_key.to_string(),
+            webhook_secret: None,
             base_url: "https://api.onramper.com/v1".to_string(),
             client: reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(30))
@@ -106,6 +128,18 @@ impl OnramperClient {
         })
     }
 
+    /// 覆盖 API 地址（沙箱 / 测试桩）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 设置 Webhook 签名密钥
+    pub fn with_webhook_secret(mut self, secret: &str) -> Self {
+        self.webhook_secret = Some(secret.to_string());
+        self
+    }
+
     /// 获取报价
     ///
     /// # 示例
@@ -144,6 +178,7 @@ impl OnramperClient {
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .query(&query_params)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send request to Onramper")?;
@@ -208,6 +243,7 @@ impl OnramperClient {
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&params)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to create order with Onramper")?;
@@ -250,6 +286,202 @@ impl OnramperClient {
     }
 }
 
+/// Onramper 交易状态 → 本地订单状态
+pub(crate) fn normalize_status(status: &str) -> &'static str {
+    match status.to_lowercase().as_str() {
+        "new" | "pending" => "pending",
+        "paid" | "processing" | "in_progress" => "processing",
+        "completed" | "success" => "completed",
+        "canceled" | "cancelled" => "cancelled",
+        "refunded" => "refunded",
+        "expired" => "expired",
+        _ => "failed",
+    }
+}
+
+#[async_trait::async_trait]
+impl FiatProvider for OnramperClient {
+    fn name(&self) -> &str {
+        "onramper"
+    }
+
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+        let quote = self
+            .get_quote(QuoteParams {
+                fiat_currency: request.fiat_currency.clone(),
+                crypto_currency: request.crypto_currency.clone(),
+                amount: request.fiat_amount,
+                payment_method: request.payment_method.clone(),
+                // 国家未知时按美国报价（聚合器默认区域）
+                country: match request.country.as_str() {
+                    "UNKNOWN" => "US".to_string(),
+                    country => country.to_string(),
+                },
+            })
+            .await?;
+
+        Ok(FiatProviderQuote {
+            provider: self.name().to_string(),
+            fiat_amount: request.fiat_amount,
+            crypto_amount: quote
+                .crypto_amount
+                .parse()
+                .context("Invalid Onramper crypto amount")?,
+            fee_amount: quote
+                .total_fee
+                .parse()
+                .context("Invalid Onramper fee amount")?,
+            estimated_arrival_minutes: Some(
+                quote.estimated_arrival_time_minutes.unwrap_or(30) as i64
+            ),
+            expires_at: Utc::now() + chrono::Duration::minutes(QUOTE_TTL_MINUTES),
+            quote_id: quote.quote_id,
+        })
+    }
+
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+        let order = self
+            .create_order(OrderParams {
+                quote_id: request.quote_id.clone(),
+                wallet_address: request.wallet_address.clone(),
+                email: request.email.clone(),
+                return_url: request.return_url.clone(),
+                webhook_url: request.webhook_url.clone(),
+            })
+            .await?;
+
+        Ok(FiatProviderOrder {
+            provider_order_id: order.order_id,
+            payment_url: Some(order.payment_url),
+            status: normalize_status(&order.status).to_string(),
+            raw_status: order.status,
+        })
+    }
+
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/transactions/{}",
+                self.base_url, provider_order_id
+            ))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to query Onramper transaction")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ Onramper查单失败 ({}): {}", status, error_text);
+            return Err(anyhow::anyhow!("Onramper查单失败: {}", status));
+        }
+
+        let tx: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse Onramper transaction")?;
+        let raw_status = tx["status"].as_str().unwrap_or_default();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: provider_order_id.to_string(),
+            payment_url: None,
+            status: normalize_status(raw_status).to_string(),
+            raw_status: raw_status.to_string(),
+        })
+    }
+
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()> {
+        // 聚合器订单由下游 ramp 处理，未支付订单到期自动失效
+        Err(anyhow::anyhow!(
+            "Onramper不支持通过API取消订单: {}",
+            provider_order_id
+        ))
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool {
+        match &self.webhook_secret {
+            Some(secret) => verify_hmac_sha256_hex(secret, payload, signature),
+            None => false,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderReconciliationSource for OnramperClient {
+    fn provider_name(&self) -> &str {
+        "onramper"
+    }
+
+    async fn fetch_orders_page(
+        &self,
+        date: NaiveDate,
+        cursor: Option<&str>,
+    ) -> Result<ProviderOrderPage> {
+        let (start, end) = day_bounds(date);
+        let mut query = vec![
+            ("startDateTime", start.to_rfc3339()),
+            ("endDateTime", end.to_rfc3339()),
+            ("limit", RECONCILIATION_PAGE_SIZE.to_string()),
+        ];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_string()));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/transactions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .query(&query)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch Onramper transactions")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ Onramper交易列表错误 ({}): {}", status, error_text);
+            return Err(anyhow::anyhow!("Onramper交易列表返回错误: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse Onramper transactions")?;
+
+        let mut orders = Vec::new();
+        for tx in body["transactions"].as_array().into_iter().flatten() {
+            let provider_order_id = tx["transactionId"]
+                .as_str()
+                .context("Onramper transaction missing transactionId")?;
+            let raw_status = tx["status"].as_str().unwrap_or_default();
+            orders.push(ProviderOrder {
+                provider_order_id: provider_order_id.to_string(),
+                fiat_amount: parse_amount(&tx["inAmount"]).with_context(|| {
+                    format!("Onramper transaction {} has no inAmount", provider_order_id)
+                })?,
+                fiat_currency: tx["sourceCurrency"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_uppercase(),
+                status: normalize_status(raw_status).to_string(),
+                raw_status: raw_status.to_string(),
+                created_at: parse_timestamp(&tx["createdAt"]),
+            });
+        }
+
+        Ok(ProviderOrderPage {
+            orders,
+            next_cursor: body["nextCursor"]
+                .as_str()
+                .filter(|c| !c.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -279,4 +511,50 @@ mod tests {
         assert!(!quote.crypto_amount.is_empty());
         assert!(!quote.quote_id.is_empty());
     }
+
+    /// 按游标翻页拉取录制的 Onramper 交易列表
+    #[tokio::test]
+    async fn test_onramper_reconciliation_paging() {
+        use axum::{extract::Query, routing::get, Json, Router};
+
+        let app = Router::new().route(
+            "/transactions",
+            get(|Query(q): Query<HashMap<String, String>>| async move {
+                assert_eq!(q["startDateTime"], "2026-10-16T00:00:00+00:00");
+                let body = match q.get("cursor").map(String::as_str) {
+                    None => include_str!(
+                        "../../../tests/fixtures/fiat/onramper_transactions_page1.json"
+                    ),
+                    Some("eyJvZmZzZXQiOjJ9") => {
+                        include_str!(
+                            "../../../tests/fixtures/fiat/onramper_transactions_page2.json"
+                        )
+                    }
+                    Some(other) => panic!("unexpected cursor {}", other),
+                };
+                Json(serde_json::from_str::<serde_json::Value>(body).unwrap())
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = OnramperClient::new("key")
+            .unwrap()
+            .with_base_url(&format!("http://{}", addr));
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let orders = crate::service::fiat::reconciliation_source::fetch_all_orders(&client, date)
+            .await
+            .unwrap();
+
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].status, "completed");
+        assert_eq!(orders[1].fiat_amount, Decimal::from_str("250.5").unwrap());
+        assert_eq!(orders[1].fiat_currency, "EUR");
+        assert_eq!(orders[1].status, "processing");
+        assert_eq!(orders[2].status, "cancelled");
+        assert_eq!(orders[2].raw_status, "canceled");
+    }
 }
@@ -0,0 +1,137 @@
+//! 法币服务商统一抽象
+//!
+//! 每个服务商客户端实现 [`FiatProvider`]，`FiatService` 只面向该 trait 做路由与故障转移：
+//! 报价、下单、查单、撤单与 Webhook 验签。金额一律使用 `Decimal`，状态归一化为本地
+//! `fiat.orders.status` 取值。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 报价请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatQuoteRequest {
+    /// 法币币种（如：USD, CNY, EUR）
+    pub fiat_currency: String,
+    /// 加密货币币种（如：USDT, ETH）
+    pub crypto_currency: String,
+    /// 法币金额
+    pub fiat_amount: Decimal,
+    /// 支付方式（credit_card, alipay, sepa ...）
+    pub payment_method: String,
+    /// 用户国家代码（ISO 3166-1 alpha-2，未知时为 `UNKNOWN`）
+    pub country: String,
+}
+
+/// 服务商报价
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatProviderQuote {
+    /// 服务商名称（与 `fiat.providers.name` 一致）
+    pub provider: String,
+    pub quote_id: String,
+    pub fiat_amount: Decimal,
+    pub crypto_amount: Decimal,
+    /// 总费用（法币计价）
+    pub fee_amount: Decimal,
+    pub estimated_arrival_minutes: Option<i64>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl FiatProviderQuote {
+    /// 每单位法币可得的加密货币数量
+    pub fn exchange_rate(&self) -> Decimal {
+        if self.fiat_amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.crypto_amount / self.fiat_amount
+        }
+    }
+
+    /// 费率（百分比）
+    pub fn fee_percentage(&self) -> Decimal {
+        if self.fiat_amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.fee_amount / self.fiat_amount * Decimal::from(100)
+        }
+    }
+}
+
+/// 下单请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatOrderRequest {
+    /// 本地订单ID（作为服务商侧的商户订单号）
+    pub order_id: String,
+    pub user_id: String,
+    pub quote_id: String,
+    pub fiat_amount: Decimal,
+    pub fiat_currency: String,
+    pub crypto_currency: String,
+    pub payment_method: String,
+    pub wallet_address: String,
+    pub email: Option<String>,
+    pub return_url: Option<String>,
+    pub webhook_url: Option<String>,
+}
+
+/// 服务商侧订单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatProviderOrder {
+    pub provider_order_id: String,
+    /// 支付链接（查单时服务商可能不返回）
+    pub payment_url: Option<String>,
+    /// 归一化后的状态（pending / processing / completed / failed / cancelled / refunded / expired）
+    pub status: String,
+    /// 服务商原始状态
+    pub raw_status: String,
+}
+
+/// 法币服务商
+#[async_trait::async_trait]
+pub trait FiatProvider: Send + Sync {
+    /// 服务商名称（与 `fiat.providers.name` 一致）
+    fn name(&self) -> &str;
+
+    /// 获取报价
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote>;
+
+    /// 创建订单，返回服务商订单号与支付链接
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder>;
+
+    /// 查询订单状态
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder>;
+
+    /// 取消订单（服务商不支持时返回错误）
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()>;
+
+    /// 验证 Webhook 签名；未配置签名密钥时一律拒绝
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool;
+}
+
+/// HMAC-SHA256 十六进制签名校验（常量时间比较，兼容 `sha256=` / `0x` 前缀）
+pub(crate) fn verify_hmac_sha256_hex(secret: &str, payload: &[u8], signature: &str) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use subtle::ConstantTimeEq;
+
+    let provided = signature
+        .trim()
+        .trim_start_matches("sha256=")
+        .trim_start_matches("0x");
+    let Ok(provided) = hex::decode(provided) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(payload);
+    let expected = mac.finalize().into_bytes();
+
+    provided.len() == expected.len() && bool::from(provided.ct_eq(expected.as_slice()))
+}
+
+/// 读取非空环境变量
+pub(crate) fn env_non_empty(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
@@ -0,0 +1,413 @@
+//! Ramp Network API客户端
+//!
+//! Ramp Network专注欧美市场（兜底通道）
+//! 优势：
+//! - SEPA/Faster Payments/ACH 银行转账费率低
+//! - 欧盟合规牌照
+//! - 托管支付页面，无需自建KYC
+//!
+//! API文档: https://docs.ramp.network/
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use rust_decimal::Decimal;
+
+use super::{
+    provider::{
+        FiatOrderRequest, FiatProvider, FiatProviderOrder, FiatProviderQuote, FiatQuoteRequest,
+    },
+    reconciliation_source::parse_amount,
+};
+
+use crate::{infrastructure::telemetry::TracePropagation, service::audit_ledger::canonical_json};
+
+/// 报价有效期（秒）
+const QUOTE_TTL_SECS: i64 = 60;
+
+/// Ramp Network客户端配置
+pub struct RampClient {
+    host_api_key: String,
+    /// Webhook 验签公钥（Ramp 以 ECDSA secp256k1 签名，`X-Body-Signature` 头）
+    webhook_key: Option<VerifyingKey>,
+    /// 资产所在链（Ramp 资产符号为 `<CHAIN>_<SYMBOL>`，如 `ETH_USDT`）
+    asset_chain: String,
+    base_url: String,
+    widget_url: String,
+    client: reqwest::Client,
+}
+
+impl RampClient {
+    /// 创建新的Ramp客户端
+    pub fn new(host_api_key: &str) -> Result<Self> {
+        Ok(Self {
+            host_api_key: host_api_key.to_string(),
+            webhook_key: None,
+            asset_chain: "ETH".to_string(),
+            base_url: "https://api.ramp.network".to_string(),
+            widget_url: "https://app.ramp.network".to_string(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .context("Failed to create HTTP client")?,
+        })
+    }
+
+    /// 覆盖 API 地址（沙箱 / 测试桩）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 设置 Webhook 验签公钥（PEM 或 Base64 DER 编码的 SubjectPublicKeyInfo）
+    pub fn with_webhook_public_key(mut self, key: &str) -> Result<Self> {
+        self.webhook_key = Some(parse_public_key(key)?);
+        Ok(self)
+    }
+
+    /// 设置资产所在链（默认 ETH）
+    pub fn with_asset_chain(mut self, chain: &str) -> Self {
+        self.asset_chain = chain.to_uppercase();
+        self
+    }
+
+    fn asset_symbol(&self, crypto: &str) -> String {
+        format!("{}_{}", self.asset_chain, crypto.to_uppercase())
+    }
+}
+
+/// 解析 Ramp 公钥：PEM（环境变量中的 `\n` 转义亦可）或 Base64 DER
+fn parse_public_key(key: &str) -> Result<VerifyingKey> {
+    use base64::Engine;
+    use k256::pkcs8::DecodePublicKey;
+
+    let body: String = key
+        .replace("\\n", "\n")
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .flat_map(|line| line.trim().chars())
+        .collect();
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .context("Ramp webhook public key is not valid base64")?;
+    VerifyingKey::from_public_key_der(&der).context("Invalid Ramp webhook public key")
+}
+
+/// 本地支付方式 → Ramp 报价中的支付方式键
+fn payment_method_key(payment_method: &str) -> &'static str {
+    match payment_method {
+        "apple_pay" => "APPLE_PAY",
+        "google_pay" => "GOOGLE_PAY",
+        "open_banking" | "instant_sepa" | "faster_payments" => "AUTO_BANK_TRANSFER",
+        "bank_transfer" | "sepa" | "ach" => "MANUAL_BANK_TRANSFER",
+        "pix" => "PIX",
+        _ => "CARD_PAYMENT",
+    }
+}
+
+/// Ramp 购买状态 → 本地订单状态
+fn normalize_status(status: &str) -> &'static str {
+    match status.to_uppercase().as_str() {
+        "INITIALIZED" | "PAYMENT_STARTED" => "pending",
+        "PAYMENT_IN_PROGRESS"
+        | "PAYMENT_EXECUTED"
+        | "FIAT_SENT"
+        | "FIAT_RECEIVED"
+        | "RELEASING" => "processing",
+        "RELEASED" => "completed",
+        "CANCELLED" => "cancelled",
+        "EXPIRED" => "expired",
+        "RETURNED" => "refunded",
+        _ => "failed",
+    }
+}
+
+#[async_trait::async_trait]
+impl FiatProvider for RampClient {
+    fn name(&self) -> &str {
+        "ramp"
+    }
+
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+        let url = format!("{}/api/host-api/v3/onramp/quote/all", self.base_url);
+
+        tracing::info!(
+            "🌐 调用Ramp API获取报价: {} {} → {}",
+            request.fiat_amount,
+            request.fiat_currency,
+            request.crypto_currency
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .query(&[("hostApiKey", &self.host_api_key)])
+            .json(&serde_json::json!({
+                "cryptoAssetSymbol": self.asset_symbol(&request.crypto_currency),
+                "fiatCurrency": request.fiat_currency.to_uppercase(),
+                "fiatValue": request.fiat_amount.to_string(),
+            }))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to send request to Ramp")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ Ramp API错误 ({}): {}", status, error_text);
+            return Err(anyhow!("Ramp API返回错误: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse Ramp response")?;
+
+        let method = payment_method_key(&request.payment_method);
+        let offer = body
+            .get(method)
+            .filter(|v| v.is_object())
+            .ok_or_else(|| anyhow!("Ramp不支持该支付方式: {}", request.payment_method))?;
+
+        // cryptoAmount 为链上最小单位
+        let decimals = body["asset"]["decimals"]
+            .as_u64()
+            .context("Ramp quote missing asset.decimals")?;
+        let base_units =
+            parse_amount(&offer["cryptoAmount"]).context("Ramp quote missing cryptoAmount")?;
+        let crypto_amount = base_units / Decimal::from(10u64.pow(decimals as u32));
+
+        let fee_amount = parse_amount(&offer["appliedFee"])
+            .or_else(|| parse_amount(&offer["baseRampFee"]))
+            .unwrap_or_default()
+            + parse_amount(&offer["networkFee"]).unwrap_or_default();
+
+        Ok(FiatProviderQuote {
+            provider: self.name().to_string(),
+            // Ramp 报价无 ID，托管页面按相同参数重新定价
+            quote_id: format!("ramp:{}", Utc::now().timestamp_millis()),
+            fiat_amount: parse_amount(&offer["fiatValue"]).unwrap_or(request.fiat_amount),
+            crypto_amount,
+            fee_amount,
+            estimated_arrival_minutes: None,
+            expires_at: Utc::now() + chrono::Duration::seconds(QUOTE_TTL_SECS),
+        })
+    }
+
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+        // Ramp 通过托管页面下单；本地订单ID随 webhookStatusUrl 回传，用于关联购买记录
+        let mut params = vec![
+            ("hostApiKey", self.host_api_key.clone()),
+            ("hostAppName", "IronForge".to_string()),
+            ("swapAsset", self.asset_symbol(&request.crypto_currency)),
+            ("fiatCurrency", request.fiat_currency.to_uppercase()),
+            ("fiatValue", request.fiat_amount.to_string()),
+            ("userAddress", request.wallet_address.clone()),
+            (
+                "paymentMethodType",
+                payment_method_key(&request.payment_method).to_string(),
+            ),
+        ];
+        if let Some(email) = &request.email {
+            params.push(("userEmailAddress", email.clone()));
+        }
+        if let Some(return_url) = &request.return_url {
+            params.push(("finalUrl", return_url.clone()));
+        }
+        if let Some(webhook_url) = &request.webhook_url {
+            params.push((
+                "webhookStatusUrl",
+                format!("{}?order_id={}", webhook_url, request.order_id),
+            ));
+        }
+
+        let payment_url = reqwest::Url::parse_with_params(&self.widget_url, &params)
+            .context("Invalid Ramp widget URL")?
+            .to_string();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: request.order_id.clone(),
+            payment_url: Some(payment_url),
+            status: "pending".to_string(),
+            raw_status: "INITIALIZED".to_string(),
+        })
+    }
+
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder> {
+        let url = format!(
+            "{}/api/host-api/v3/purchases/{}",
+            self.base_url, provider_order_id
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("hostApiKey", &self.host_api_key)])
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to query Ramp purchase")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ Ramp查单失败 ({}): {}", status, error_text);
+            return Err(anyhow!("Ramp查单失败: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse Ramp purchase")?;
+        let raw_status = body["status"].as_str().unwrap_or_default();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: body["id"].as_str().unwrap_or(provider_order_id).to_string(),
+            payment_url: None,
+            status: normalize_status(raw_status).to_string(),
+            raw_status: raw_status.to_string(),
+        })
+    }
+
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()> {
+        Err(anyhow!("Ramp不支持通过API取消订单: {}", provider_order_id))
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool {
+        // X-Body-Signature：Base64(DER ECDSA-SHA256)，签名内容为键排序后的 JSON 请求体
+        use base64::Engine;
+
+        let Some(key) = &self.webhook_key else {
+            return false;
+        };
+        let Ok(der) = base64::engine::general_purpose::STANDARD.decode(signature.trim()) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_der(&der) else {
+            return false;
+        };
+        // k256 只接受 low-S，服务商签名可能未规范化
+        let signature = signature.normalize_s().unwrap_or(signature);
+
+        if key.verify(payload, &signature).is_ok() {
+            return true;
+        }
+        serde_json::from_slice::<serde_json::Value>(payload).is_ok_and(|body| {
+            key.verify(canonical_json(&body).as_bytes(), &signature)
+                .is_ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_ramp_webhook_ecdsa_signature() {
+        use base64::Engine;
+        use k256::ecdsa::SigningKey;
+
+        let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        // SubjectPublicKeyInfo(id-ecPublicKey, secp256k1) + 未压缩公钥点
+        let mut der = hex::decode("3056301006072a8648ce3d020106052b8104000a034200").unwrap();
+        der.extend_from_slice(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        );
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\\n{}\\n-----END PUBLIC KEY-----",
+            b64.encode(&der)
+        );
+        let client = RampClient::new("host_key")
+            .unwrap()
+            .with_webhook_public_key(&pem)
+            .unwrap();
+
+        let sign = |message: &[u8]| {
+            let signature: Signature = k256::ecdsa::signature::Signer::sign(&signing_key, message);
+            b64.encode(signature.to_der().as_bytes())
+        };
+
+        // 请求体键顺序与签名时不同：按排序后的 JSON 校验
+        let body = br#"{"type":"RELEASED","purchase":{"id":"p1","cryptoAmount":"100"}}"#;
+        let signature = sign(br#"{"purchase":{"cryptoAmount":"100","id":"p1"},"type":"RELEASED"}"#);
+        assert!(client.verify_webhook(body, &signature));
+
+        // 篡改内容 / HMAC 风格签名 / 未配置公钥均拒绝
+        let forged = br#"{"type":"RELEASED","purchase":{"id":"p1","cryptoAmount":"9999"}}"#;
+        assert!(!client.verify_webhook(forged, &signature));
+        assert!(!client.verify_webhook(body, &"ab".repeat(32)));
+        assert!(!RampClient::new("host_key")
+            .unwrap()
+            .verify_webhook(body, &signature));
+        assert!(RampClient::new("host_key")
+            .unwrap()
+            .with_webhook_public_key("not a key")
+            .is_err());
+    }
+
+    /// 录制的 Ramp 报价：按支付方式取对应报价，cryptoAmount 按资产精度换算
+    #[tokio::test]
+    async fn test_ramp_quote_from_fixture() {
+        use axum::{routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/api/host-api/v3/onramp/quote/all",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(body["cryptoAssetSymbol"], "ETH_USDT");
+                assert_eq!(body["fiatCurrency"], "EUR");
+                Json(
+                    serde_json::from_str::<serde_json::Value>(include_str!(
+                        "../../../tests/fixtures/fiat/ramp_quote_all.json"
+                    ))
+                    .unwrap(),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = RampClient::new("host_key")
+            .unwrap()
+            .with_base_url(&format!("http://{}", addr));
+        let request = FiatQuoteRequest {
+            fiat_currency: "EUR".to_string(),
+            crypto_currency: "usdt".to_string(),
+            fiat_amount: Decimal::from(100),
+            payment_method: "sepa".to_string(),
+            country: "DE".to_string(),
+        };
+
+        let quote = client.quote(&request).await.unwrap();
+        assert_eq!(quote.crypto_amount, Decimal::from_str("98.75").unwrap());
+        assert_eq!(quote.fee_amount, Decimal::from_str("0.99").unwrap());
+
+        let card = client
+            .quote(&FiatQuoteRequest {
+                payment_method: "credit_card".to_string(),
+                ..request.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(card.crypto_amount, Decimal::from_str("96.512345").unwrap());
+        assert_eq!(card.fee_amount, Decimal::from_str("3.19").unwrap());
+
+        let pix = client
+            .quote(&FiatQuoteRequest {
+                payment_method: "pix".to_string(),
+                ..request
+            })
+            .await;
+        assert!(pix.is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! 服务商侧订单拉取（对账用）
+//!
+//! 每个服务商客户端实现 [`ProviderReconciliationSource`]，按页拉取某一天（UTC）的全部订单，
+//! 并把服务商状态归一化为本地 `fiat.orders.status` 取值。
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 单日最多拉取页数（防止服务商分页游标异常导致死循环）
+const MAX_PAGES: usize = 200;
+
+/// 服务商侧订单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderOrder {
+    pub provider_order_id: String,
+    pub fiat_amount: Decimal,
+    pub fiat_currency: String,
+    /// 归一化后的状态（pending / processing / completed / failed / cancelled / refunded / expired）
+    pub status: String,
+    /// 服务商原始状态
+    pub raw_status: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 分页结果
+#[derive(Debug, Clone, Default)]
+pub struct ProviderOrderPage {
+    pub orders: Vec<ProviderOrder>,
+    /// 下一页游标；`None` 表示已到末页
+    pub next_cursor: Option<String>,
+}
+
+/// 对账数据源：按页拉取服务商某日订单
+#[async_trait::async_trait]
+pub trait ProviderReconciliationSource: Send + Sync {
+    /// 服务商名称（与 `fiat.orders.provider` 一致）
+    fn provider_name(&self) -> &str;
+
+    /// 拉取一页订单；`cursor` 为上一页返回的 `next_cursor`
+    async fn fetch_orders_page(
+        &self,
+        date: NaiveDate,
+        cursor: Option<&str>,
+    ) -> Result<ProviderOrderPage>;
+}
+
+/// 拉取某日全部订单（自动翻页）
+pub async fn fetch_all_orders(
+    source: &dyn ProviderReconciliationSource,
+    date: NaiveDate,
+) -> Result<Vec<ProviderOrder>> {
+    let mut orders = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..MAX_PAGES {
+        let page = source.fetch_orders_page(date, cursor.as_deref()).await?;
+        orders.extend(page.orders);
+        match page.next_cursor {
+            Some(next) if Some(&next) != cursor.as_ref() => cursor = Some(next),
+            Some(next) => bail!(
+                "{} returned a repeated page cursor '{}'",
+                source.provider_name(),
+                next
+            ),
+            None => return Ok(orders),
+        }
+    }
+
+    bail!(
+        "{} returned more than {} pages of orders for {}",
+        source.provider_name(),
+        MAX_PAGES,
+        date
+    )
+}
+
+/// 当日的 UTC 时间窗口 [start, end)
+pub fn day_bounds(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (start, start + chrono::Duration::days(1))
+}
+
+/// 解析字符串或数字形式的金额
+pub(crate) fn parse_amount(value: &serde_json::Value) -> Option<Decimal> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
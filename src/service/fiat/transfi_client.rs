@@ -10,8 +10,25 @@
 //! API文档: https://docs.transfi.com/
 
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use super::{
+    provider::{
+        verify_hmac_sha256_hex, FiatOrderRequest, FiatProvider, FiatProviderOrder,
+        FiatProviderQuote, FiatQuoteRequest,
+    },
+    reconciliation_source::{
+        parse_amount, parse_timestamp, ProviderOrder, ProviderOrderPage,
+        ProviderReconciliationSource,
+    },
+};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 对账分页大小
+const RECONCILIATION_PAGE_SIZE: u32 = 100;
+
 /// TransFi客户端配置
 pub struct TransFiClient {
     api_key: String,
@@ -111,6 +128,12 @@ impl TransFiClient {
         })
     }
 
+    /// 覆盖 API 地址（沙箱 / 测试桩）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
     /// 获取报价
     pub async fn get_quote(&self, request: TransFiQuoteRequest) -> Result<TransFiQuoteResponse> {
         let url = format!("{}/quotes", self.base_url);
@@ -132,6 +155,7 @@ impl TransFiClient {
             .header("X-Timestamp", timestamp.to_string())
             .header("X-Signature", signature)
             .json(&request)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send request to TransFi")?;
@@ -180,6 +204,7 @@ impl TransFiClient {
             .header("X-Timestamp", timestamp.to_string())
             .header("X-Signature", signature)
             .json(&request)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to create order with TransFi")?;
@@ -224,6 +249,233 @@ impl TransFiClient {
     }
 }
 
+/// TransFi 订单状态 → 本地订单状态
+pub(crate) fn normalize_status(status: &str) -> &'static str {
+    match status.to_lowercase().as_str() {
+        "initiated" | "pending" => "pending",
+        "fund_received" | "fund_settled" | "processing" => "processing",
+        "completed" | "success" => "completed",
+        "cancelled" | "canceled" => "cancelled",
+        "refunded" => "refunded",
+        "expired" => "expired",
+        _ => "failed",
+    }
+}
+
+#[async_trait::async_trait]
+impl FiatProvider for TransFiClient {
+    fn name(&self) -> &str {
+        "transfi"
+    }
+
+    async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+        let quote = self
+            .get_quote(TransFiQuoteRequest {
+                source_currency: request.fiat_currency.clone(),
+                target_currency: request.crypto_currency.clone(),
+                amount: request.fiat_amount.to_string(),
+                payment_method: request.payment_method.clone(),
+                // 国家未知时按中国报价（TransFi 主力市场）
+                country_code: match request.country.as_str() {
+                    "UNKNOWN" => "CN".to_string(),
+                    country => country.to_string(),
+                },
+            })
+            .await?;
+
+        Ok(FiatProviderQuote {
+            provider: self.name().to_string(),
+            fiat_amount: request.fiat_amount,
+            crypto_amount: quote
+                .target_amount
+                .parse()
+                .context("Invalid TransFi target amount")?,
+            fee_amount: quote.fee.parse().context("Invalid TransFi fee")?,
+            // 支付宝/微信即时到账
+            estimated_arrival_minutes: Some(0),
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(quote.valid_for_seconds),
+            quote_id: quote.quote_id,
+        })
+    }
+
+    async fn place_order(&self, request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+        let order = self
+            .create_order(TransFiOrderRequest {
+                quote_id: request.quote_id.clone(),
+                wallet_address: request.wallet_address.clone(),
+                user_info: TransFiUserInfo {
+                    user_id: request.user_id.clone(),
+                    email: request.email.clone(),
+                    phone: None,
+                    name: None,
+                },
+                callback_url: request.webhook_url.clone(),
+            })
+            .await?;
+
+        Ok(FiatProviderOrder {
+            provider_order_id: order.order_id,
+            payment_url: Some(order.payment_url),
+            status: normalize_status(&order.status).to_string(),
+            raw_status: order.status,
+        })
+    }
+
+    async fn order_status(&self, provider_order_id: &str) -> Result<FiatProviderOrder> {
+        let payload = serde_json::json!({ "orderId": provider_order_id });
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self.generate_signature(&payload, timestamp)?;
+
+        let response = self
+            .client
+            .get(format!("{}/orders/{}", self.base_url, provider_order_id))
+            .header("X-API-Key", &self.api_key)
+            .header("X-Timestamp", timestamp.to_string())
+            .header("X-Signature", signature)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to query TransFi order")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ TransFi查单失败 ({}): {}", status, error_text);
+            return Err(anyhow::anyhow!("TransFi查单失败: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse TransFi order")?;
+        let raw_status = body["status"].as_str().unwrap_or_default();
+
+        Ok(FiatProviderOrder {
+            provider_order_id: provider_order_id.to_string(),
+            payment_url: body["paymentUrl"].as_str().map(str::to_string),
+            status: normalize_status(raw_status).to_string(),
+            raw_status: raw_status.to_string(),
+        })
+    }
+
+    async fn cancel_order(&self, provider_order_id: &str) -> Result<()> {
+        let payload = serde_json::json!({ "orderId": provider_order_id });
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self.generate_signature(&payload, timestamp)?;
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/orders/{}/cancel",
+                self.base_url, provider_order_id
+            ))
+            .header("X-API-Key", &self.api_key)
+            .header("X-Timestamp", timestamp.to_string())
+            .header("X-Signature", signature)
+            .json(&payload)
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to cancel TransFi order")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ TransFi撤单失败 ({}): {}", status, error_text);
+            return Err(anyhow::anyhow!("TransFi撤单失败: {}", status));
+        }
+
+        Ok(())
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> bool {
+        // 回调使用 API secret 做 HMAC-SHA256 签名
+        verify_hmac_sha256_hex(&self.secret, payload, signature)
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderReconciliationSource for TransFiClient {
+    fn provider_name(&self) -> &str {
+        "transfi"
+    }
+
+    async fn fetch_orders_page(
+        &self,
+        date: NaiveDate,
+        cursor: Option<&str>,
+    ) -> Result<ProviderOrderPage> {
+        // TransFi 使用页码分页：游标即页码
+        let page: u32 = match cursor {
+            Some(c) => c.parse().context("Invalid TransFi page cursor")?,
+            None => 1,
+        };
+        let query = serde_json::json!({
+            "date": date.to_string(),
+            "page": page,
+            "limit": RECONCILIATION_PAGE_SIZE,
+        });
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self.generate_signature(&query, timestamp)?;
+
+        let response = self
+            .client
+            .get(format!("{}/orders", self.base_url))
+            .header("X-API-Key", &self.api_key)
+            .header("X-Timestamp", timestamp.to_string())
+            .header("X-Signature", signature)
+            .query(&[
+                ("date", date.to_string()),
+                ("page", page.to_string()),
+                ("limit", RECONCILIATION_PAGE_SIZE.to_string()),
+            ])
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch TransFi orders")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            tracing::error!("❌ TransFi订单列表错误 ({}): {}", status, error_text);
+            return Err(anyhow::anyhow!("TransFi订单列表返回错误: {}", status));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse TransFi orders")?;
+
+        let mut orders = Vec::new();
+        for order in body["data"].as_array().into_iter().flatten() {
+            let provider_order_id = order["orderId"]
+                .as_str()
+                .context("TransFi order missing orderId")?;
+            let raw_status = order["status"].as_str().unwrap_or_default();
+            orders.push(ProviderOrder {
+                provider_order_id: provider_order_id.to_string(),
+                fiat_amount: parse_amount(&order["sourceAmount"]).with_context(|| {
+                    format!("TransFi order {} has no sourceAmount", provider_order_id)
+                })?,
+                fiat_currency: order["sourceCurrency"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_uppercase(),
+                status: normalize_status(raw_status).to_string(),
+                raw_status: raw_status.to_string(),
+                created_at: parse_timestamp(&order["createdAt"]),
+            });
+        }
+
+        let total_pages = body["totalPages"].as_u64().unwrap_or(page as u64);
+        Ok(ProviderOrderPage {
+            orders,
+            next_cursor: ((page as u64) < total_pages).then(|| (page + 1).to_string()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,4 +500,52 @@ mod tests {
 
         assert!(quote.is_ok(), "TransFi报价请求失败: {:?}", quote.err());
     }
+
+    /// 按页码翻页拉取录制的 TransFi 订单列表（请求需携带签名头）
+    #[tokio::test]
+    async fn test_transfi_reconciliation_paging() {
+        use std::collections::HashMap;
+
+        use axum::{extract::Query, http::HeaderMap, routing::get, Json, Router};
+        use rust_decimal::Decimal;
+
+        let app = Router::new().route(
+            "/orders",
+            get(
+                |headers: HeaderMap, Query(q): Query<HashMap<String, String>>| async move {
+                    assert!(headers.contains_key("X-Signature"));
+                    assert_eq!(q["date"], "2026-10-16");
+                    let body = match q["page"].as_str() {
+                        "1" => {
+                            include_str!("../../../tests/fixtures/fiat/transfi_orders_page1.json")
+                        }
+                        "2" => {
+                            include_str!("../../../tests/fixtures/fiat/transfi_orders_page2.json")
+                        }
+                        other => panic!("unexpected page {}", other),
+                    };
+                    Json(serde_json::from_str::<serde_json::Value>(body).unwrap())
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = TransFiClient::new("key", "secret")
+            .unwrap()
+            .with_base_url(&format!("http://{}", addr));
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let orders = crate::service::fiat::reconciliation_source::fetch_all_orders(&client, date)
+            .await
+            .unwrap();
+
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].provider_order_id, "TF-20261016-0001");
+        assert_eq!(orders[0].status, "processing");
+        assert_eq!(orders[1].fiat_amount, Decimal::from(500));
+        assert_eq!(orders[1].status, "refunded");
+    }
 }
@@ -1,6 +1,6 @@
 //! 法币充值和提现服务
 //! 企业级实现，禁止Mock数据，真实对接第三方服务商API
-use std::{fmt, str::FromStr, sync::Arc};
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
 
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
@@ -9,10 +9,16 @@ use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use crate::infrastructure::{
+    event_bus::DomainEvent, event_outbox::enqueue_event, telemetry::TracePropagation,
+};
 use crate::service::{
-    fiat::{OnramperClient, TransFiClient},
+    fiat::{
+        FiatOrderRequest, FiatProvider, FiatProviderOrder, FiatProviderQuote, FiatQuoteRequest,
+        OnramperClient, TransFiClient,
+    },
     price_service::PriceService,
-    provider_service::ProviderService,
+    provider_service::{ProviderConfig, ProviderService},
 };
 
 /// 法币订单状态
@@ -89,6 +95,30 @@ pub struct OnrampQuote {
     pub min_amount: Decimal,
     pub max_amount: Decimal,
     pub quote_id: String,
+    /// 报价服务商（与 `fiat.providers.name` 一致）
+    pub provider: String,
+}
+
+impl From<FiatProviderQuote> for OnrampQuote {
+    fn from(quote: FiatProviderQuote) -> Self {
+        Self {
+            exchange_rate: quote.exchange_rate(),
+            fee_percentage: quote.fee_percentage(),
+            estimated_arrival: match quote.estimated_arrival_minutes {
+                Some(0) => "Instant".to_string(),
+                Some(minutes) => format!("{} minutes", minutes),
+                None => "10-30 minutes".to_string(),
+            },
+            fiat_amount: quote.fiat_amount,
+            crypto_amount: quote.crypto_amount,
+            fee_amount: quote.fee_amount,
+            quote_expires_at: quote.expires_at,
+            min_amount: Decimal::from(10),
+            max_amount: Decimal::from(50_000),
+            quote_id: quote.quote_id,
+            provider: quote.provider,
+        }
+    }
 }
 
 /// 提现报价
@@ -117,8 +147,7 @@ pub struct FiatService {
     pool: PgPool,
     provider_service: Arc<ProviderService>,
     price_service: Arc<PriceService>, // ✅ 生产级：真实价格服务
-    onramper_client: Option<OnramperClient>, // ✅ 生产级：Onramper API客户端
-    transfi_client: Option<TransFiClient>, // ✅ 生产级：TransFi API客户端
+    providers: HashMap<String, Arc<dyn FiatProvider>>, // ✅ 生产级：已配置API密钥的服务商客户端
 }
 
 impl FiatService {
@@ -129,54 +158,63 @@ impl FiatService {
         transfi_api_key: Option<String>,
         transfi_secret: Option<String>,
     ) -> Result<Self> {
-        let provider_service = Arc::new(ProviderService::new(pool.clone()));
+        let mut providers: Vec<Arc<dyn FiatProvider>> = Vec::new();
 
         // 初始化Onramper客户端
-        let onramper_client = if let Some(api_key) = onramper_api_key {
+        if let Some(api_key) = onramper_api_key {
             match OnramperClient::new(&api_key) {
-                Ok(client) => {
+                Ok(mut client) => {
+                    if let Ok(secret) = std::env::var("ONRAMPER_WEBHOOK_SECRET") {
+                        client = client.with_webhook_secret(&secret);
+                    }
                     tracing::info!("✅ Onramper客户端初始化成功");
-                    Some(client)
-                }
-                Err(e) => {
-                    tracing::warn!("⚠️ Onramper客户端初始化失败: {}", e);
-                    None
+                    providers.push(Arc::new(client));
                 }
+                Err(e) => tracing::warn!("⚠️ Onramper客户端初始化失败: {}", e),
             }
         } else {
             tracing::warn!("⚠️ 未配置ONRAMPER_API_KEY，Onramper功能不可用");
-            None
-        };
+        }
 
         // 初始化TransFi客户端
-        let transfi_client =
-            if let (Some(api_key), Some(secret)) = (transfi_api_key, transfi_secret) {
-                match TransFiClient::new(&api_key, &secret) {
-                    Ok(client) => {
-                        tracing::info!("✅ TransFi客户端初始化成功");
-                        Some(client)
-                    }
-                    Err(e) => {
-                        tracing::warn!("⚠️ TransFi客户端初始化失败: {}", e);
-                        None
-                    }
+        if let (Some(api_key), Some(secret)) = (transfi_api_key, transfi_secret) {
+            match TransFiClient::new(&api_key, &secret) {
+                Ok(client) => {
+                    tracing::info!("✅ TransFi客户端初始化成功");
+                    providers.push(Arc::new(client));
                 }
-            } else {
-                tracing::warn!("⚠️ 未配置TRANSFI_API_KEY/SECRET，TransFi功能不可用");
-                None
-            };
+                Err(e) => tracing::warn!("⚠️ TransFi客户端初始化失败: {}", e),
+            }
+        } else {
+            tracing::warn!("⚠️ 未配置TRANSFI_API_KEY/SECRET，TransFi功能不可用");
+        }
+
+        // 直连服务商（MoonPay / Ramp / AlchemyPay）从环境变量读取
+        providers.extend(crate::service::fiat::direct_providers_from_env());
+
+        Ok(Self::with_providers(pool, price_service, providers))
+    }
 
-        Ok(Self {
+    /// 使用指定的服务商客户端构建（测试 / 自定义接入）
+    pub fn with_providers(
+        pool: PgPool,
+        price_service: Arc<PriceService>,
+        providers: Vec<Arc<dyn FiatProvider>>,
+    ) -> Self {
+        Self {
+            provider_service: Arc::new(ProviderService::new(pool.clone())),
             pool,
-            provider_service,
             price_service, // ✅ 注入价格服务
-            onramper_client,
-            transfi_client,
-        })
+            providers: providers
+                .into_iter()
+                .map(|p| (p.name().to_string(), p))
+                .collect(),
+        }
     }
 
     /// 获取充值报价
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(currency = %currency, token = %token, payment_method = %payment_method))]
     pub async fn get_onramp_quote(
         &self,
         _tenant_id: Uuid,
@@ -194,12 +232,15 @@ impl FiatService {
         );
 
         // ✅ 生产级：强制要求配置真实API，禁止Mock降级
-        if self.onramper_client.is_none() && self.transfi_client.is_none() {
+        if self.providers.is_empty() {
             tracing::error!("[FiatService] ❌ 生产环境必须配置支付API密钥");
             return Err(anyhow::anyhow!(
                 "系统未配置支付服务API密钥。请配置环境变量:\n\
                  - ONRAMPER_API_KEY (全球支付，推荐)\n\
                  - TRANSFI_API_KEY + TRANSFI_SECRET (中国市场)\n\
+                 - ALCHEMYPAY_APP_ID + ALCHEMYPAY_SECRET (亚洲市场)\n\
+                 - RAMP_API_KEY (欧洲市场)\n\
+                 - MOONPAY_API_KEY + MOONPAY_SECRET_KEY (美国市场)\n\
                  \n申请地址:\n\
                  - Onramper: https://onramper.com/developers\n\
                  - TransFi: https://transfi.com/contact"
@@ -222,191 +263,104 @@ impl FiatService {
             ));
         }
 
-        // 2. 检测用户国家并过滤服务商
+        // 2. 检测用户国家
         let user_country = self
             .detect_user_country(user_ip, payment_method, user_kyc_country)
             .await;
 
         tracing::info!("[FiatService] Detected user country: {}", user_country);
 
-        // 🎯 3层聚合架构智能路由（2025企业级优化）
-        // Step 1: 检查是否为中国地区 + 微信/支付宝支付
-        let is_china_payment = self.is_china_region(&user_country)
-            && (payment_method == "alipay" || payment_method == "wechat_pay");
-
-        if is_china_payment {
-            tracing::info!(
-                "[FiatService] 🇨🇳 China payment detected, prioritizing China-specialized providers"
-            );
-            // 中国支付专用通道（3层架构 - 主力2-3）：
-            // TransFi(优先级90) - 2024新增支付宝/微信，费率1.5%-3.5%
-            // AlchemyPay(优先级85) - Binance/OKX合作，支付宝+微信OTC
-            return self
-                .route_to_china_providers(amount, currency, token, payment_method)
-                .await;
-        }
-
-        // Step 2: 🎯 优先尝试Onramper聚合器（3层架构 - 主力1，优先级100）
-        // Onramper聚合25+ ramps，覆盖全球95%用户，自动选最优通道
-        if let Some(onramper) = providers
-            .iter()
-            .find(|p| p.name == "onramper" && p.is_enabled)
-        {
-            tracing::info!("[FiatService] 🎯 Routing to Onramper aggregator (priority 100, covers 95% scenarios)");
-            if let Ok(quote) = self
-                .fetch_provider_quote(
-                    onramper,
-                    &amount.to_string(),
-                    currency,
-                    token,
-                    payment_method,
-                )
-                .await
-            {
-                tracing::info!(
-                    "[FiatService] ✅ Onramper aggregator success - 聚合25+ ramps已完成最优选择"
-                );
-                return Ok(quote.1);
-            }
-            tracing::warn!("[FiatService] ⚠️ Onramper aggregator unavailable, falling back to 4 direct providers");
-        }
-
-        // Step 3: 降级到4个直连通道（TransFi→AlchemyPay→Ramp→MoonPay）
-        // 企业级兜底架构：主力2-3 + 兜底1-2
-        let healthy_providers: Vec<_> = providers
-            .into_iter()
-            .filter(|p| p.health_status == "healthy" && p.name != "onramper") // 排除已尝试的聚合器
-            .collect();
-
-        tracing::info!(
-            "[FiatService] Found {} healthy direct providers for fallback",
-            healthy_providers.len()
-        );
-
-        // 然后检查国家支持（顺序执行避免并发问题）
-        let mut supported_providers = Vec::new();
-        for p in healthy_providers {
-            let is_supported = self
-                .provider_service
-                .check_country_support(&p.name, &user_country)
-                .await
-                .unwrap_or(false);
-
-            if is_supported {
-                tracing::info!(
-                    "[FiatService] Provider {} supports country {}",
-                    p.name,
-                    user_country
-                );
-                supported_providers.push(p);
-            } else if user_country == "UNKNOWN" {
-                // 如果无法检测国家，允许尝试
-                tracing::warn!(
-                    "[FiatService] Country unknown, allowing provider {} to attempt",
-                    p.name
-                );
-                supported_providers.push(p);
-            } else {
-                tracing::debug!(
-                    "[FiatService] Provider {} does not support country {}",
-                    p.name,
-                    user_country
-                );
-            }
-        }
+        // 3. 按国家/支付方式/优先级路由（仅保留已配置客户端的服务商）
+        let candidates = route_providers(&providers, &user_country, payment_method, |name| {
+            self.providers.contains_key(name)
+        });
 
-        if supported_providers.is_empty() {
+        if candidates.is_empty() {
             tracing::error!(
-                "[FiatService] No providers support user country: {}",
-                user_country
+                "[FiatService] No providers support country={} payment_method={}",
+                user_country,
+                payment_method
             );
             return Err(anyhow::anyhow!(
-                "没有支持您所在国家的支付服务商，当前国家: {}",
-                user_country
+                "没有支持您所在国家和支付方式的支付服务商，当前国家: {}，支付方式: {}",
+                user_country,
+                payment_method
             ));
         }
 
         tracing::info!(
-            "[FiatService] {} providers support user country",
-            supported_providers.len()
+            "[FiatService] Routing order: {}",
+            candidates
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" → ")
         );
 
-        // 3. 顺序获取所有服务商报价（真实API调用）
-        let mut results = Vec::new();
-        let amount_str = amount.to_string();
-        for provider in &supported_providers {
-            tracing::info!(
-                "[FiatService] Fetching quote from provider: {}",
-                provider.name
-            );
-            let result = self
-                .fetch_provider_quote(provider, &amount_str, currency, token, payment_method)
-                .await;
-
-            match &result {
-                Ok((name, _)) => {
-                    tracing::info!("[FiatService] Successfully fetched quote from {}", name)
-                }
-                Err(e) => tracing::warn!(
-                    "[FiatService] Failed to fetch quote from {}: {}",
-                    provider.name,
-                    e
-                ),
-            }
-            results.push(result);
-        }
-
-        // 4. 选择最优报价（费用最低）
-        let mut best_quote: Option<(String, OnrampQuote)> = None;
-
-        for (idx, result) in results.into_iter().enumerate() {
-            if let Ok((provider_name, quote)) = result {
-                if let Some((_, ref current_best)) = best_quote {
-                    if quote.fee_percentage < current_best.fee_percentage {
-                        tracing::info!(
-                            "[FiatService] Provider {} has better rate: {}% vs {}%",
-                            provider_name,
-                            quote.fee_percentage,
-                            current_best.fee_percentage
-                        );
-                        best_quote = Some((provider_name, quote));
-                    }
-                } else {
-                    best_quote = Some((provider_name, quote));
-                }
+        // 4. 按路由顺序依次报价，失败自动切换下一家
+        let request = FiatQuoteRequest {
+            fiat_currency: currency.to_string(),
+            crypto_currency: token.to_string(),
+            fiat_amount: amount,
+            payment_method: payment_method.to_string(),
+            country: user_country,
+        };
+        let attempts: Vec<_> = candidates
+            .iter()
+            .filter_map(|p| {
+                self.providers.get(&p.name).map(|client| {
+                    (
+                        client.clone(),
+                        std::time::Duration::from_secs(p.timeout_seconds.max(1) as u64),
+                    )
+                })
+            })
+            .collect();
+        let outcome = quote_with_failover(&attempts, &request).await;
 
-                // 更新服务商统计
-                let _ = self
-                    .provider_service
-                    .update_stats(&supported_providers[idx].name, true, None)
-                    .await;
-            }
+        // 5. 更新服务商统计
+        for attempt in &outcome.attempts {
+            let _ = self
+                .provider_service
+                .update_stats(
+                    &attempt.provider,
+                    attempt.error.is_none(),
+                    Some(attempt.elapsed_ms),
+                )
+                .await;
         }
 
-        match &best_quote {
-            Some((provider, quote)) => {
+        match outcome.quote {
+            Some(quote) => {
                 tracing::info!(
-                    "[FiatService] Best quote from {}: {} {} for {} {}, fee: {}%",
-                    provider,
+                    "[FiatService] ✅ Quote from {}: {} {} for {} {}, fee: {}%",
+                    quote.provider,
                     quote.crypto_amount,
                     token,
                     quote.fiat_amount,
                     currency,
-                    quote.fee_percentage
+                    quote.fee_percentage()
                 );
+                Ok(OnrampQuote::from(quote))
             }
             None => {
-                tracing::error!("[FiatService] No valid quotes received from any provider");
+                let errors = outcome
+                    .attempts
+                    .iter()
+                    .filter_map(|a| a.error.as_ref().map(|e| format!("{}: {}", a.provider, e)))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                tracing::error!("[FiatService] All providers failed to quote: {}", errors);
+                Err(anyhow::anyhow!(
+                    "无法获取报价，所有支付服务商都返回错误，请稍后重试"
+                ))
             }
         }
-
-        best_quote
-            .map(|(_, quote)| quote)
-            .ok_or_else(|| anyhow::anyhow!("无法获取报价，所有支付服务商都返回错误，请稍后重试"))
     }
 
     /// 创建充值订单
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(tenant_id = %tenant_id, token = %token, payment_method = %payment_method))]
     pub async fn create_onramp_order(
         &self,
         tenant_id: Uuid,
@@ -443,7 +397,7 @@ impl FiatService {
         let order_id = Uuid::new_v4();
         let order_expires_at = Utc::now() + chrono::Duration::minutes(30);
 
-        let provider = quote.quote_id.split(':').next().unwrap_or("unknown");
+        let provider = quote.provider.as_str();
 
         let row = sqlx::query(
             r#"
@@ -479,7 +433,7 @@ impl FiatService {
         let order = self.row_to_fiat_order(&row)?;
 
         // 3. 调用第三方服务商API创建订单（真实API调用）
-        let payment_url = self.create_provider_order(provider, &order, &quote).await?;
+        let provider_order = self.place_provider_order(provider, &order, &quote).await?;
 
         // 4. 更新订单的payment_url和provider_order_id
         sqlx::query(
@@ -489,8 +443,8 @@ impl FiatService {
             WHERE id = $3
             "#,
         )
-        .bind(&payment_url)
-        .bind(order.id.to_string()) // 临时使用order_id作为provider_order_id
+        .bind(&provider_order.payment_url)
+        .bind(&provider_order.provider_order_id)
         .bind(order.id)
         .execute(&self.pool)
         .await
@@ -498,7 +452,8 @@ impl FiatService {
 
         // 5. 更新内存中的订单对象（重要：确保返回的订单包含payment_url）
         let mut updated_order = order;
-        updated_order.payment_url = Some(payment_url);
+        updated_order.payment_url = provider_order.payment_url;
+        updated_order.provider_order_id = Some(provider_order.provider_order_id);
 
         // 6. 记录审计日志
         let _ = self
@@ -517,6 +472,7 @@ impl FiatService {
     }
 
     /// 获取提现报价
+    #[tracing::instrument(skip_all, fields(chain = %chain, token = %token))]
     pub async fn get_offramp_quote(
         &self,
         _tenant_id: Uuid,
@@ -528,7 +484,7 @@ impl FiatService {
         _withdraw_method: &str,
     ) -> Result<OfframpQuote> {
         // ✅ 生产级：强制要求真实API配置
-        if self.onramper_client.is_none() && self.transfi_client.is_none() {
+        if self.providers.is_empty() {
             tracing::error!("[Offramp] ❌ 提现功能需要配置支付API密钥");
             return Err(anyhow::anyhow!(
                 "提现功能未配置。请设置环境变量：\n\
@@ -622,6 +578,7 @@ impl FiatService {
 
     /// 创建提现订单
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip_all, fields(tenant_id = %tenant_id, chain = %chain, token = %token))]
     pub async fn create_offramp_order(
         &self,
         tenant_id: Uuid,
@@ -854,283 +811,86 @@ impl FiatService {
     }
 
     async fn geoip_lookup(&self, ip: &str) -> Result<String> {
-        // 使用ipapi.co进行IP地理位置检测（真实API调用）
-        let url = format!("https://ipapi.co/{}/country_code/", ip);
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(3))
-            .build()?;
-
-        match client.get(&url).send().await {
-            Ok(resp) => {
-                let country = resp.text().await?.trim().to_string();
-                if country.len() == 2 {
-                    Ok(country)
-                } else {
-                    Err(anyhow::anyhow!("Invalid country code"))
-                }
-            }
-            Err(_) => Err(anyhow::anyhow!("GeoIP lookup failed")),
-        }
+        let ip = ip
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Invalid IP address"))?;
+        crate::utils::geoip::lookup_country(ip).await
     }
 
-    async fn fetch_provider_quote(
-        &self,
-        _provider: &crate::service::provider_service::ProviderConfig,
-        _amount: &str,
-        _currency: &str,
-        _token: &str,
-        _payment_method: &str,
-    ) -> Result<(String, OnrampQuote)> {
-        // ✅ 生产级：真实API对接
-        // 根据provider.name路由到不同的支付服务商
-
-        tracing::info!(
-            "🌐 调用真实支付API: provider={}, amount={} {}, token={}",
-            _provider.name,
-            _amount,
-            _currency,
-            _token
-        );
-
-        let amount_decimal = Decimal::from_str(_amount)?;
-
-        // 根据provider路由到真实API
-        match _provider.name.to_lowercase().as_str() {
-            "onramper" | "ramp" | "moonpay" | "transak" => {
-                // 使用Onramper聚合器（支持25+支付服务商）
-                if let Some(client) = &self.onramper_client {
-                    use crate::service::fiat::onramper_client::QuoteParams;
-
-                    let quote_result = client
-                        .get_quote(QuoteParams {
-                            fiat_currency: _currency.to_string(),
-                            crypto_currency: _token.to_string(),
-                            amount: amount_decimal,
-                            payment_method: _payment_method.to_string(),
-                            country: "US".to_string(), // 默认美国，可从用户IP推导
-                        })
-                        .await;
-
-                    match quote_result {
-                        Ok(onramper_quote) => {
-                            // 转换Onramper报价格式到内部格式
-                            let crypto_amount = Decimal::from_str(&onramper_quote.crypto_amount)
-                                .context("Invalid crypto amount")?;
-                            let fee_amount = Decimal::from_str(&onramper_quote.total_fee)
-                                .context("Invalid fee amount")?;
-                            let exchange_rate = crypto_amount / amount_decimal;
-
-                            tracing::info!(
-                                "✅ Onramper报价成功: {} {} → {} {}, 费用 {} {}",
-                                amount_decimal,
-                                _currency,
-                                crypto_amount,
-                                _token,
-                                fee_amount,
-                                _currency
-                            );
-
-                            Ok((
-                                _provider.name.clone(),
-                                OnrampQuote {
-                                    fiat_amount: amount_decimal,
-                                    crypto_amount,
-                                    exchange_rate,
-                                    fee_amount,
-                                    fee_percentage: (fee_amount / amount_decimal)
-                                        * Decimal::from(100),
-                                    estimated_arrival: format!(
-                                        "{} minutes",
-                                        onramper_quote.estimated_arrival_time_minutes.unwrap_or(30)
-                                    ),
-                                    quote_expires_at: Utc::now() + chrono::Duration::minutes(15),
-                                    min_amount: Decimal::from_str("10.0").unwrap(),
-                                    max_amount: Decimal::from_str("50000.0").unwrap(),
-                                    quote_id: onramper_quote.quote_id,
-                                },
-                            ))
-                        }
-                        Err(e) => {
-                            tracing::error!("❌ Onramper报价失败: {}", e);
-                            Err(anyhow!("Onramper报价失败: {}", e))
-                        }
-                    }
-                } else {
-                    Err(anyhow!("Onramper客户端未配置，无法获取报价"))
-                }
-            }
-
-            "transfi" => {
-                // 中国市场专用（支付宝/微信）
-                if let Some(client) = &self.transfi_client {
-                    use crate::service::fiat::transfi_client::TransFiQuoteRequest;
-
-                    let quote_result = client
-                        .get_quote(TransFiQuoteRequest {
-                            source_currency: _currency.to_string(),
-                            target_currency: _token.to_string(),
-                            amount: _amount.to_string(),
-                            payment_method: _payment_method.to_string(),
-                            country_code: "CN".to_string(), // 默认中国
-                        })
-                        .await;
-
-                    match quote_result {
-                        Ok(transfi_quote) => {
-                            let crypto_amount = Decimal::from_str(&transfi_quote.target_amount)
-                                .context("Invalid crypto amount")?;
-                            let fee_amount =
-                                Decimal::from_str(&transfi_quote.fee).context("Invalid fee")?;
-                            let exchange_rate = Decimal::from_str(&transfi_quote.exchange_rate)
-                                .context("Invalid exchange rate")?;
-
-                            tracing::info!(
-                                "✅ TransFi报价成功: {} {} → {} {}, 费用 {}",
-                                amount_decimal,
-                                _currency,
-                                crypto_amount,
-                                _token,
-                                fee_amount
-                            );
+    /// 服务商客户端是否已配置（按 `FiatProvider::name`）
+    pub fn has_provider(&self, provider: &str) -> bool {
+        self.providers.contains_key(provider)
+    }
 
-                            Ok((
-                                _provider.name.clone(),
-                                OnrampQuote {
-                                    fiat_amount: amount_decimal,
-                                    crypto_amount,
-                                    exchange_rate,
-                                    fee_amount,
-                                    fee_percentage: (fee_amount / amount_decimal)
-                                        * Decimal::from(100),
-                                    estimated_arrival: "Instant".to_string(),
-                                    quote_expires_at: Utc::now()
-                                        + chrono::Duration::seconds(
-                                            transfi_quote.valid_for_seconds,
-                                        ),
-                                    min_amount: Decimal::from_str("10.0").unwrap(),
-                                    max_amount: Decimal::from_str("50000.0").unwrap(),
-                                    quote_id: transfi_quote.quote_id,
-                                },
-                            ))
-                        }
-                        Err(e) => {
-                            tracing::error!("❌ TransFi报价失败: {}", e);
-                            Err(anyhow!("TransFi报价失败: {}", e))
-                        }
-                    }
-                } else {
-                    Err(anyhow!("TransFi客户端未配置，无法获取报价"))
-                }
-            }
+    /// 经服务商客户端校验 Webhook 签名；服务商未配置时一律拒绝
+    pub fn verify_provider_webhook(&self, provider: &str, payload: &[u8], signature: &str) -> bool {
+        self.providers
+            .get(provider)
+            .is_some_and(|client| client.verify_webhook(payload, signature))
+    }
 
-            _ => {
-                tracing::warn!("⚠️ 不支持的支付服务商: {}", _provider.name);
-                Err(anyhow!("不支持的支付服务商: {}", _provider.name))
-            }
-        }
+    fn provider_client(&self, provider: &str) -> Result<&Arc<dyn FiatProvider>> {
+        self.providers
+            .get(provider)
+            .ok_or_else(|| anyhow!("{}客户端未配置", provider))
     }
 
-    async fn create_provider_order(
+    async fn place_provider_order(
         &self,
         provider: &str,
-        _order: &FiatOrder,
-        _quote: &OnrampQuote,
-    ) -> Result<String> {
+        order: &FiatOrder,
+        quote: &OnrampQuote,
+    ) -> Result<FiatProviderOrder> {
         // ✅ 生产级：真实API创建订单
         tracing::info!(
             "🌐 调用真实支付API创建订单: provider={}, quote_id={}",
             provider,
-            _quote.quote_id
+            quote.quote_id
         );
 
-        // 根据provider路由到真实API
-        match provider.to_lowercase().as_str() {
-            "onramper" | "ramp" | "moonpay" | "transak" => {
-                // 使用Onramper聚合器
-                if let Some(client) = &self.onramper_client {
-                    use crate::service::fiat::onramper_client::OrderParams;
-
-                    let order_result = client
-                        .create_order(OrderParams {
-                            quote_id: _quote.quote_id.clone(),
-                            wallet_address: _order.wallet_address.clone().unwrap_or_default(),
-                            email: None, // 从用户profile获取
-                            return_url: Some(format!(
-                                "https://ironforge.io/orders/{}/complete",
-                                _order.id
-                            )),
-                            webhook_url: Some(
-                                "https://api.ironforge.io/webhooks/onramper".to_string(),
-                            ),
-                        })
-                        .await;
-
-                    match order_result {
-                        Ok(onramper_order) => {
-                            tracing::info!(
-                                "✅ Onramper订单创建成功: order_id={}, payment_url={}",
-                                onramper_order.order_id,
-                                onramper_order.payment_url
-                            );
-                            Ok(onramper_order.payment_url)
-                        }
-                        Err(e) => {
-                            tracing::error!("❌ Onramper订单创建失败: {}", e);
-                            Err(anyhow!("Onramper订单创建失败: {}", e))
-                        }
-                    }
-                } else {
-                    Err(anyhow!("Onramper客户端未配置，无法创建订单"))
-                }
-            }
+        let provider_order = self
+            .provider_client(provider)?
+            .place_order(&FiatOrderRequest {
+                order_id: order.id.to_string(),
+                user_id: order.user_id.to_string(),
+                quote_id: quote.quote_id.clone(),
+                fiat_amount: order.fiat_amount,
+                fiat_currency: order.fiat_currency.clone(),
+                crypto_currency: order.crypto_token.clone(),
+                payment_method: order.payment_method.clone(),
+                wallet_address: order.wallet_address.clone().unwrap_or_default(),
+                email: None, // 从用户profile获取
+                return_url: Some(format!("https://ironforge.io/orders/{}/complete", order.id)),
+                webhook_url: Some(format!("https://api.ironforge.io/webhooks/{}", provider)),
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("❌ {}订单创建失败: {}", provider, e);
+                anyhow!("{}订单创建失败: {}", provider, e)
+            })?;
 
-            "transfi" => {
-                // 中国市场专用
-                if let Some(client) = &self.transfi_client {
-                    use crate::service::fiat::transfi_client::{
-                        TransFiOrderRequest, TransFiUserInfo,
-                    };
-
-                    let order_result = client
-                        .create_order(TransFiOrderRequest {
-                            quote_id: _quote.quote_id.clone(),
-                            wallet_address: _order.wallet_address.clone().unwrap_or_default(),
-                            user_info: TransFiUserInfo {
-                                user_id: _order.user_id.to_string(),
-                                email: None, // 从用户profile获取
-                                phone: None,
-                                name: None,
-                            },
-                            callback_url: Some(
-                                "https://api.ironforge.io/webhooks/transfi".to_string(),
-                            ),
-                        })
-                        .await;
-
-                    match order_result {
-                        Ok(transfi_order) => {
-                            tracing::info!(
-                                "✅ TransFi订单创建成功: order_id={}, payment_url={}",
-                                transfi_order.order_id,
-                                transfi_order.payment_url
-                            );
-                            Ok(transfi_order.payment_url)
-                        }
-                        Err(e) => {
-                            tracing::error!("❌ TransFi订单创建失败: {}", e);
-                            Err(anyhow!("TransFi订单创建失败: {}", e))
-                        }
-                    }
-                } else {
-                    Err(anyhow!("TransFi客户端未配置，无法创建订单"))
-                }
-            }
+        tracing::info!(
+            "✅ {}订单创建成功: provider_order_id={}",
+            provider,
+            provider_order.provider_order_id
+        );
 
-            _ => {
-                tracing::warn!("⚠️ 不支持的支付服务商: {}", provider);
-                Err(anyhow!("不支持的支付服务商: {}", provider))
-            }
-        }
+        Ok(provider_order)
+    }
+
+    /// 向服务商查询订单最新状态（不修改本地订单）
+    #[tracing::instrument(skip_all, fields(order_id = %order_id))]
+    pub async fn fetch_provider_order_status(&self, order_id: Uuid) -> Result<FiatProviderOrder> {
+        let order = self.get_order_status(order_id).await?;
+        let provider_order_id = order
+            .provider_order_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("Order {} has no provider order id", order_id))?;
+
+        self.provider_client(&order.provider)?
+            .order_status(provider_order_id)
+            .await
     }
 
     fn row_to_fiat_order(&self, row: &sqlx::postgres::PgRow) -> Result<FiatOrder> {
@@ -1190,6 +950,7 @@ impl FiatService {
     }
 
     /// 取消订单
+    #[tracing::instrument(skip_all, fields(order_id = %order_id))]
     pub async fn cancel_order(&self, tenant_id: Uuid, user_id: Uuid, order_id: Uuid) -> Result<()> {
         use crate::service::order_state_machine::{OrderStateMachine, OrderStatus};
 
@@ -1203,6 +964,21 @@ impl FiatService {
         let current_status = OrderStatus::from_str(&order.status)?;
         OrderStateMachine::can_perform_action(current_status, "cancel")?;
 
+        // 通知服务商撤单；不支持撤单的服务商未支付订单会自动过期，不阻断本地取消
+        if let (Some(provider_order_id), Ok(client)) = (
+            order.provider_order_id.as_deref(),
+            self.provider_client(&order.provider),
+        ) {
+            if let Err(e) = client.cancel_order(provider_order_id).await {
+                tracing::warn!(
+                    "[FiatService] Provider {} cancel failed for order {}: {}",
+                    order.provider,
+                    order_id,
+                    e
+                );
+            }
+        }
+
         // 更新订单状态为cancelled
         sqlx::query(
             "UPDATE fiat.orders SET status = 'cancelled', updated_at = CURRENT_TIMESTAMP WHERE id = $1"
@@ -1381,101 +1157,135 @@ impl std::str::FromStr for FiatOrderStatus {
 // 智能路由辅助方法 (Enterprise-Grade Payment Optimization)
 // ============================================================================
 
-impl FiatService {
-    /// 检测是否为中国地区（含港澳台新）
-    fn is_china_region(&self, country_code: &str) -> bool {
-        matches!(country_code, "CN" | "HK" | "TW" | "SG")
-    }
+/// 聚合器服务商（中国地区微信/支付宝场景下降级为兜底）
+const AGGREGATOR_PROVIDERS: &[&str] = &["onramper"];
 
-    /// 中国专用支付路由（微信/支付宝优化）
-    ///
-    /// 优先级：TransFi (90) > Alchemy Pay (85) > Onramper聚合器
-    async fn route_to_china_providers(
-        &self,
-        amount: Decimal,
-        currency: &str,
-        token: &str,
-        payment_method: &str,
-    ) -> Result<OnrampQuote> {
-        tracing::info!("[FiatService] 🇨🇳 Routing to China-optimized providers");
+/// 欧盟成员国（`supported_countries` 中的 `EU` 覆盖以下国家）
+const EU_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT", "LV",
+    "LT", "LU", "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE",
+];
 
-        // 获取中国优化的服务商（按优先级排序）
-        let china_providers = vec!["transfi", "alchemypay"];
+/// 检测是否为中国地区（含港澳台新）
+fn is_china_region(country_code: &str) -> bool {
+    matches!(country_code, "CN" | "HK" | "TW" | "SG")
+}
 
-        for provider_name in china_providers {
-            // 从provider_service获取配置
-            let provider_opt = self
-                .provider_service
-                .get_provider_by_name(provider_name)
-                .await
-                .ok();
+fn supports_country(provider: &ProviderConfig, country: &str) -> bool {
+    // 无法检测国家时允许尝试
+    country == "UNKNOWN"
+        || provider.supported_countries.is_empty()
+        || provider.supported_countries.iter().any(|c| {
+            c.eq_ignore_ascii_case(country) || (c == "EU" && EU_COUNTRIES.contains(&country))
+        })
+}
 
-            if let Some(provider) = provider_opt {
-                tracing::info!(
-                    "[FiatService] Trying China provider: {} (priority: {})",
-                    provider.name,
-                    provider.priority
-                );
+fn supports_payment_method(provider: &ProviderConfig, payment_method: &str) -> bool {
+    provider.supported_payment_methods.is_empty()
+        || provider
+            .supported_payment_methods
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(payment_method))
+}
 
-                match self
-                    .fetch_provider_quote(
-                        &provider,
-                        &amount.to_string(),
-                        currency,
-                        token,
-                        payment_method,
-                    )
-                    .await
-                {
-                    Ok((name, quote)) => {
-                        tracing::info!("[FiatService] ✅ China provider {} quote successful", name);
-                        return Ok(quote);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "[FiatService] ⚠️ China provider {} failed: {}",
-                            provider.name,
-                            e
-                        );
-                        continue;
-                    }
-                }
-            }
-        }
+/// 按国家、支付方式与优先级筛选并排序服务商
+///
+/// - 过滤：已启用、非 `unhealthy`、已配置客户端、支持国家与支付方式
+/// - 排序：`priority` 降序，同优先级按最低费率升序
+/// - 中国地区 + 微信/支付宝：直连通道（TransFi / AlchemyPay）优先，聚合器兜底
+pub fn route_providers(
+    providers: &[ProviderConfig],
+    country: &str,
+    payment_method: &str,
+    is_configured: impl Fn(&str) -> bool,
+) -> Vec<ProviderConfig> {
+    let prefer_direct =
+        is_china_region(country) && (payment_method == "alipay" || payment_method == "wechat_pay");
+
+    let mut candidates: Vec<ProviderConfig> = providers
+        .iter()
+        .filter(|p| p.is_enabled && p.health_status != "unhealthy")
+        .filter(|p| is_configured(&p.name))
+        .filter(|p| supports_country(p, country))
+        .filter(|p| supports_payment_method(p, payment_method))
+        .cloned()
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        let demoted = |p: &ProviderConfig| {
+            prefer_direct
+                && (p.provider_type == "aggregator"
+                    || AGGREGATOR_PROVIDERS.contains(&p.name.as_str()))
+        };
+        demoted(a)
+            .cmp(&demoted(b))
+            .then_with(|| b.priority.cmp(&a.priority))
+            .then_with(|| a.fee_min_percent.cmp(&b.fee_min_percent))
+    });
 
-        // 降级到Onramper聚合器（可能通过P2P支持）
-        tracing::warn!(
-            "[FiatService] All China providers failed, falling back to Onramper aggregator"
-        );
+    candidates
+}
 
-        if let Ok(provider) = self.provider_service.get_provider_by_name("onramper").await {
-            match self
-                .fetch_provider_quote(
-                    &provider,
-                    &amount.to_string(),
-                    currency,
-                    token,
-                    payment_method,
-                )
-                .await
-            {
-                Ok((_, quote)) => {
-                    tracing::info!(
-                        "[FiatService] ✅ Onramper fallback successful for China payment"
-                    );
-                    return Ok(quote);
-                }
-                Err(e) => {
-                    tracing::error!("[FiatService] ❌ Onramper fallback also failed: {}", e);
-                }
+/// 单个服务商的报价尝试记录
+#[derive(Debug, Clone)]
+pub struct QuoteAttempt {
+    pub provider: String,
+    pub elapsed_ms: i32,
+    /// `None` 表示报价成功
+    pub error: Option<String>,
+}
+
+/// 故障转移报价结果
+#[derive(Debug, Clone, Default)]
+pub struct FailoverOutcome {
+    pub quote: Option<FiatProviderQuote>,
+    pub attempts: Vec<QuoteAttempt>,
+}
+
+/// 按顺序向服务商请求报价，出错或超时即切换下一家，首个成功报价即返回
+pub async fn quote_with_failover(
+    providers: &[(Arc<dyn FiatProvider>, std::time::Duration)],
+    request: &FiatQuoteRequest,
+) -> FailoverOutcome {
+    let mut outcome = FailoverOutcome::default();
+
+    for (provider, timeout) in providers {
+        let started = std::time::Instant::now();
+        let result = match tokio::time::timeout(*timeout, provider.quote(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("报价超时（{}秒）", timeout.as_secs())),
+        };
+        let elapsed_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        match result {
+            Ok(quote) => {
+                outcome.attempts.push(QuoteAttempt {
+                    provider: provider.name().to_string(),
+                    elapsed_ms,
+                    error: None,
+                });
+                outcome.quote = Some(quote);
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "[FiatService] ⚠️ Provider {} quote failed, failing over: {}",
+                    provider.name(),
+                    e
+                );
+                outcome.attempts.push(QuoteAttempt {
+                    provider: provider.name().to_string(),
+                    elapsed_ms,
+                    error: Some(e.to_string()),
+                });
             }
         }
-
-        Err(anyhow::anyhow!(
-            "无法为中国地区用户获取报价，请稍后重试或联系客服。所有中国优化通道（微信/支付宝）暂时不可用。"
-        ))
     }
 
+    outcome
+}
+
+impl FiatService {
     /// 更新订单状态（用于Webhook回调）
     /// 企业级实现：幂等性、状态机验证、审计日志
     pub async fn update_order_status(
@@ -1492,13 +1302,17 @@ impl FiatService {
         );
 
         // 1. 查询当前订单状态
-        let row = sqlx::query("SELECT id, status, provider_name FROM fiat.orders WHERE id = $1")
-            .bind(order_id)
-            .fetch_optional(&self.pool)
-            .await
-            .context("Failed to fetch order")?
-            .ok_or_else(|| anyhow!("Order not found: {}", order_id))?;
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, status, provider_name FROM fiat.orders WHERE id = $1",
+        )
+        .bind(order_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch order")?
+        .ok_or_else(|| anyhow!("Order not found: {}", order_id))?;
 
+        let tenant_id: Uuid = row.try_get("tenant_id")?;
+        let user_id: Uuid = row.try_get("user_id")?;
         let current_status: String = row.try_get("status")?;
         let provider_name: String = row.try_get("provider_name")?;
 
@@ -1543,9 +1357,10 @@ impl FiatService {
         query_builder.push(" WHERE id = ");
         query_builder.push_bind(order_id);
 
+        let mut tx = self.pool.begin().await?;
         let rows_affected = query_builder
             .build()
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await
             .context("Failed to update order status")?
             .rows_affected();
@@ -1554,6 +1369,21 @@ impl FiatService {
             return Err(anyhow!("Order not found or already updated: {}", order_id));
         }
 
+        // 状态变更与 outbox 事件同一事务提交（租户 Webhook 订阅）
+        enqueue_event(
+            &mut *tx,
+            &DomainEvent::FiatOrderStatusChanged {
+                order_id,
+                tenant_id,
+                user_id,
+                provider: provider_name.clone(),
+                from_status: current_status.clone(),
+                to_status: new_status.to_string(),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
         tracing::info!(
             "[FiatService] ✅ Order {} status updated: {} -> {:?} by provider {}",
             order_id,
@@ -1663,7 +1493,7 @@ impl FiatService {
             .timeout(std::time::Duration::from_secs(10))
             .build()?;
 
-        let response = client.get(&url).send().await?;
+        let response = client.get(&url).with_trace_context().send().await?;
         let json: serde_json::Value = response.json().await?;
 
         // 解析Kraken响应格式：{"result": {"USDTZUSD": {"c": ["1.0001", "123.45"]}}}
@@ -1719,6 +1549,7 @@ impl FiatService {
                     std::env::var("ONEINCH_API_KEY").unwrap_or_default()
                 ),
             )
+            .with_trace_context()
             .send()
             .await?;
 
@@ -1762,6 +1593,7 @@ impl FiatService {
         let response = client
             .get(&url)
             .header("Content-Type", "application/json")
+            .with_trace_context()
             .send()
             .await?;
 
@@ -1784,3 +1616,172 @@ impl FiatService {
         Ok(fiat_amount * Decimal::from_str("0.025")?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn provider(name: &str, priority: i64, countries: &[&str], methods: &[&str]) -> ProviderConfig {
+        ProviderConfig {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            display_name: name.to_string(),
+            provider_type: "direct".to_string(),
+            is_enabled: true,
+            priority,
+            fee_min_percent: Decimal::ONE,
+            fee_max_percent: Decimal::from(3),
+            api_url: format!("https://{}.example", name),
+            webhook_url: None,
+            timeout_seconds: 30,
+            supported_countries: countries.iter().map(|c| c.to_string()).collect(),
+            supported_payment_methods: methods.iter().map(|m| m.to_string()).collect(),
+            health_status: "healthy".to_string(),
+            last_health_check: None,
+            consecutive_failures: 0,
+            total_requests: 0,
+            successful_requests: 0,
+            average_response_time_ms: None,
+        }
+    }
+
+    fn seeded() -> Vec<ProviderConfig> {
+        vec![
+            provider("moonpay", 60, &["US", "GB", "EU"], &["credit_card", "sepa"]),
+            provider(
+                "ramp",
+                70,
+                &["US", "GB", "EU", "DE"],
+                &["sepa", "bank_transfer"],
+            ),
+            provider(
+                "onramper",
+                100,
+                &["US", "CN", "DE"],
+                &["credit_card", "alipay", "sepa"],
+            ),
+            provider("transfi", 90, &["CN", "HK"], &["alipay", "wechat_pay"]),
+            provider("alchemypay", 85, &["CN", "US"], &["alipay", "credit_card"]),
+        ]
+    }
+
+    fn names(providers: &[ProviderConfig]) -> Vec<&str> {
+        providers.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_route_providers_by_country_method_and_priority() {
+        let all = |_: &str| true;
+
+        let us_card = route_providers(&seeded(), "US", "credit_card", all);
+        assert_eq!(names(&us_card), vec!["onramper", "alchemypay", "moonpay"]);
+
+        // FR 仅通过 "EU" 覆盖
+        let fr_sepa = route_providers(&seeded(), "FR", "sepa", all);
+        assert_eq!(names(&fr_sepa), vec!["ramp", "moonpay"]);
+
+        // 中国地区支付宝：直连通道优先，聚合器兜底
+        let cn_alipay = route_providers(&seeded(), "CN", "alipay", all);
+        assert_eq!(names(&cn_alipay), vec!["transfi", "alchemypay", "onramper"]);
+
+        // 未配置客户端或不健康的服务商不参与路由
+        let mut providers = seeded();
+        providers[4].health_status = "unhealthy".to_string();
+        let routed = route_providers(&providers, "US", "credit_card", |name| name != "onramper");
+        assert_eq!(names(&routed), vec!["moonpay"]);
+    }
+
+    struct StubProvider {
+        name: &'static str,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl FiatProvider for StubProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn quote(&self, request: &FiatQuoteRequest) -> Result<FiatProviderQuote> {
+            if self.fail {
+                return Err(anyhow!("{} unavailable", self.name));
+            }
+            Ok(FiatProviderQuote {
+                provider: self.name.to_string(),
+                quote_id: format!("{}-q", self.name),
+                fiat_amount: request.fiat_amount,
+                crypto_amount: request.fiat_amount - Decimal::from(2),
+                fee_amount: Decimal::from(2),
+                estimated_arrival_minutes: Some(0),
+                expires_at: Utc::now(),
+            })
+        }
+
+        async fn place_order(&self, _request: &FiatOrderRequest) -> Result<FiatProviderOrder> {
+            unreachable!()
+        }
+
+        async fn order_status(&self, _provider_order_id: &str) -> Result<FiatProviderOrder> {
+            unreachable!()
+        }
+
+        async fn cancel_order(&self, _provider_order_id: &str) -> Result<()> {
+            unreachable!()
+        }
+
+        fn verify_webhook(&self, _payload: &[u8], _signature: &str) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_failover_to_next_provider() {
+        let providers: Vec<(Arc<dyn FiatProvider>, Duration)> = vec![
+            (
+                Arc::new(StubProvider {
+                    name: "onramper",
+                    fail: true,
+                }),
+                Duration::from_secs(1),
+            ),
+            (
+                Arc::new(StubProvider {
+                    name: "transfi",
+                    fail: false,
+                }),
+                Duration::from_secs(1),
+            ),
+            (
+                Arc::new(StubProvider {
+                    name: "moonpay",
+                    fail: false,
+                }),
+                Duration::from_secs(1),
+            ),
+        ];
+        let request = FiatQuoteRequest {
+            fiat_currency: "CNY".to_string(),
+            crypto_currency: "USDT".to_string(),
+            fiat_amount: Decimal::from(100),
+            payment_method: "alipay".to_string(),
+            country: "CN".to_string(),
+        };
+
+        let outcome = quote_with_failover(&providers, &request).await;
+        let quote = OnrampQuote::from(outcome.quote.expect("failover quote"));
+        assert_eq!(quote.provider, "transfi");
+        assert_eq!(quote.fee_percentage, Decimal::from(2));
+        assert_eq!(quote.estimated_arrival, "Instant");
+
+        // 成功后不再尝试后续服务商
+        assert_eq!(outcome.attempts.len(), 2);
+        assert!(outcome.attempts[0].error.is_some());
+        assert!(outcome.attempts[1].error.is_none());
+
+        let all_failed = quote_with_failover(&providers[..1], &request).await;
+        assert!(all_failed.quote.is_none());
+        assert_eq!(all_failed.attempts.len(), 1);
+    }
+}
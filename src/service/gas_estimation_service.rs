@@ -9,6 +9,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    infrastructure::telemetry::TracePropagation,
     infrastructure::{cache::RedisCtx, rpc_selector::RpcSelector},
     utils::chain_normalizer,
 };
@@ -75,6 +76,7 @@ impl GasEstimationService {
     /// 3. 调用链上估算（eth_estimateGas / Solana getRecentBlockhash等）
     /// 4. 添加安全缓冲（1.2x）
     /// 5. 缓存结果
+    #[tracing::instrument(skip_all)]
     pub async fn estimate_gas(&self, request: GasEstimationRequest) -> Result<GasEstimationResult> {
         // 1. 标准化链标识符
         let chain_normalized = chain_normalizer::normalize_chain_identifier(&request.chain)?;
@@ -175,6 +177,7 @@ impl GasEstimationService {
             .http_client
             .post(rpc_url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send eth_estimateGas request")?;
@@ -212,6 +215,7 @@ impl GasEstimationService {
             .http_client
             .post(rpc_url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send eth_gasPrice request")?;
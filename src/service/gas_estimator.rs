@@ -1,14 +1,34 @@
 // Gas 费预估服务 - 生产级 EIP-1559 实现
 // 支持 slow/normal/fast 三档速度，自动查询链上数据
 // 企业级实现：支持EVM链和非EVM链的动态费用获取
+//
+// EVM 链基于 `eth_feeHistory`：最近 N 个区块的优先费按三档百分位取中位数，
+// 基础费取下一区块 baseFee；OP-stack / Arbitrum 额外计入 L1 数据费
+// （GasPriceOracle / NodeInterface 预编译合约）
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::{Context, Result};
+use ethers::{
+    abi::{self, ParamType, Token},
+    types::{Address, U256},
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::infrastructure::rpc_selector::RpcSelector;
+use crate::{
+    infrastructure::rpc_selector::RpcSelector, infrastructure::telemetry::TracePropagation,
+    service::multi_node_verifier::EndpointProvider, utils::chain_normalizer,
+};
+
+/// OP-stack GasPriceOracle 预部署合约
+const OP_GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+/// Arbitrum NodeInterface 虚拟合约
+const ARBITRUM_NODE_INTERFACE: &str = "0x00000000000000000000000000000000000000C8";
+/// 原生币转账的参考 Gas 用量
+const REFERENCE_GAS_LIMIT: u64 = 21_000;
+/// 参考交易（已签名 EIP-1559 转账）的序列化长度
+const REFERENCE_TX_BYTES: usize = 120;
 
 /// Gas 费预估速度级别
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
@@ -19,6 +39,16 @@ pub enum GasSpeed {
     Fast,   // 快速（<1 分钟）
 }
 
+impl GasSpeed {
+    fn index(self) -> usize {
+        match self {
+            GasSpeed::Slow => 0,
+            GasSpeed::Normal => 1,
+            GasSpeed::Fast => 2,
+        }
+    }
+}
+
 /// EIP-1559 Gas 费预估结果
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct GasEstimate {
@@ -29,228 +59,139 @@ pub struct GasEstimate {
     pub base_fee_gwei: f64,          // 基础费用（Gwei，便于展示）
     pub max_priority_fee_gwei: f64,  // 优先费用（Gwei）
     pub max_fee_per_gas_gwei: f64,   // 最大费用（Gwei）
+    pub gas_limit: u64,              // 参考 Gas 用量（原生币转账）
+    pub l1_data_fee: String,         // L2 的 L1 数据费（Wei，十进制；L1 链为 0）
+    pub estimated_fee: String, // 预估总费用 = gas_limit × max_fee_per_gas + l1_data_fee（Wei，十进制）
+}
+
+/// L2 的 L1 数据费模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum L1FeeModel {
+    None,
+    /// OP-stack：GasPriceOracle.getL1Fee(bytes)
+    OpStack,
+    /// Arbitrum：NodeInterface.gasEstimateL1Component(address,bool,bytes)
+    Arbitrum,
 }
 
 /// 链 Gas 费配置（不同链有不同的策略）
+#[derive(Debug, Clone)]
 struct ChainGasConfig {
-    pub priority_multipliers: [f64; 3], // [slow, normal, fast] 的优先费用倍数
-    pub base_fee_multipliers: [f64; 3], // [slow, normal, fast] 的基础费用倍数
+    pub env_key: &'static str,          // 环境变量链标识（如 ETH）
+    pub reward_percentiles: [f64; 3],   // [slow, normal, fast] 的优先费百分位
+    pub fee_history_blocks: u64,        // eth_feeHistory 区块窗口
+    pub base_fee_multipliers: [f64; 3], // [slow, normal, fast] 的基础费用倍数（应对 baseFee 上涨）
     pub estimated_times: [u64; 3],      // [slow, normal, fast] 的预计时间（秒）
+    pub min_priority_fee: u64,          // 优先费下限（Wei）
+    l1_fee_model: L1FeeModel,
 }
 
 impl ChainGasConfig {
-    /// 企业级实现：从环境变量读取链配置（支持动态调整）
-    ///
-    /// 多级降级策略：
-    /// 1. 优先从环境变量读取链特定的配置
-    /// 2. 降级：从环境变量读取通用配置
-    /// 3. 最终降级：使用安全默认值（仅作为最后保障）
-    fn ethereum() -> Self {
-        // 企业级实现：优先从环境变量读取配置
-        let priority_slow = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_ETH_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (ETH_SLOW)，使用默认值 1.0。生产环境建议配置环境变量");
-                    1.0
-                }));
-        let priority_normal = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_ETH_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (ETH_NORMAL)，使用默认值 1.5。生产环境建议配置环境变量");
-                    1.5
-                }));
-        let priority_fast = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_ETH_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (ETH_FAST)，使用默认值 2.0。生产环境建议配置环境变量");
-                    2.0
-                }));
-
-        let base_slow = Self::get_env_f64("GAS_BASE_MULTIPLIER_ETH_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (ETH_SLOW)，使用默认值 1.0。生产环境建议配置环境变量");
-                    1.0
-                }));
-        let base_normal = Self::get_env_f64("GAS_BASE_MULTIPLIER_ETH_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (ETH_NORMAL)，使用默认值 1.2。生产环境建议配置环境变量");
-                    1.2
-                }));
-        let base_fast = Self::get_env_f64("GAS_BASE_MULTIPLIER_ETH_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (ETH_FAST)，使用默认值 1.5。生产环境建议配置环境变量");
-                    1.5
-                }));
-
-        let time_slow = Self::get_env_u64("GAS_ESTIMATED_TIME_ETH_SLOW")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (ETH_SLOW)，使用默认值 600秒。生产环境建议配置环境变量");
-                    600
-                }));
-        let time_normal = Self::get_env_u64("GAS_ESTIMATED_TIME_ETH_NORMAL")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (ETH_NORMAL)，使用默认值 180秒。生产环境建议配置环境变量");
-                    180
-                }));
-        let time_fast = Self::get_env_u64("GAS_ESTIMATED_TIME_ETH_FAST")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (ETH_FAST)，使用默认值 60秒。生产环境建议配置环境变量");
-                    60
-                }));
-
-        Self {
-            priority_multipliers: [priority_slow, priority_normal, priority_fast],
-            base_fee_multipliers: [base_slow, base_normal, base_fast],
-            estimated_times: [time_slow, time_normal, time_fast],
-        }
+    /// 链默认配置（覆盖 chain_normalizer 中的全部 EVM 链），环境变量可逐项覆盖：
+    /// - GAS_REWARD_PERCENTILES_<KEY> / GAS_REWARD_PERCENTILES（如 "10,50,90"）
+    /// - GAS_FEE_HISTORY_BLOCKS_<KEY> / GAS_FEE_HISTORY_BLOCKS
+    /// - GAS_BASE_MULTIPLIER_<KEY>_<SPEED> / GAS_BASE_MULTIPLIER_<SPEED>
+    /// - GAS_ESTIMATED_TIME_<KEY>_<SPEED> / GAS_ESTIMATED_TIME_<SPEED>
+    /// - GAS_MIN_PRIORITY_FEE_GWEI_<KEY>
+    fn for_chain(canonical: &str) -> Self {
+        let defaults = match canonical {
+            "bsc" => Self {
+                env_key: "BSC",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.1, 1.3],
+                estimated_times: [300, 90, 30],
+                min_priority_fee: 0,
+                l1_fee_model: L1FeeModel::None,
+            },
+            "polygon" => Self {
+                env_key: "POLYGON",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.2, 1.5],
+                estimated_times: [180, 60, 20],
+                // Polygon PoS 节点拒绝低于 30 Gwei 优先费的交易
+                min_priority_fee: gwei_to_wei(30.0),
+                l1_fee_model: L1FeeModel::None,
+            },
+            "arbitrum" => Self {
+                env_key: "ARBITRUM",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.2, 1.5],
+                estimated_times: [60, 15, 5],
+                min_priority_fee: 0,
+                l1_fee_model: L1FeeModel::Arbitrum,
+            },
+            "optimism" => Self {
+                env_key: "OPTIMISM",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.2, 1.5],
+                estimated_times: [60, 20, 6],
+                min_priority_fee: 1_000_000, // 0.001 Gwei
+                l1_fee_model: L1FeeModel::OpStack,
+            },
+            "avalanche" => Self {
+                env_key: "AVALANCHE",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.2, 1.5],
+                estimated_times: [30, 10, 3],
+                min_priority_fee: 0,
+                l1_fee_model: L1FeeModel::None,
+            },
+            _ => Self {
+                env_key: "ETH",
+                reward_percentiles: [10.0, 50.0, 90.0],
+                fee_history_blocks: 20,
+                base_fee_multipliers: [1.0, 1.2, 1.5],
+                estimated_times: [600, 180, 60],
+                min_priority_fee: 0,
+                l1_fee_model: L1FeeModel::None,
+            },
+        };
+        defaults.with_env_overrides()
     }
 
-    fn bsc() -> Self {
-        // 企业级实现：优先从环境变量读取配置
-        let priority_slow = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_BSC_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (BSC_SLOW)，使用默认值 0.8。生产环境建议配置环境变量");
-                    0.8
-                }));
-        let priority_normal = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_BSC_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (BSC_NORMAL)，使用默认值 1.2。生产环境建议配置环境变量");
-                    1.2
-                }));
-        let priority_fast = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_BSC_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (BSC_FAST)，使用默认值 1.8。生产环境建议配置环境变量");
-                    1.8
-                }));
-
-        let base_slow = Self::get_env_f64("GAS_BASE_MULTIPLIER_BSC_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (BSC_SLOW)，使用默认值 1.0。生产环境建议配置环境变量");
-                    1.0
-                }));
-        let base_normal = Self::get_env_f64("GAS_BASE_MULTIPLIER_BSC_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (BSC_NORMAL)，使用默认值 1.1。生产环境建议配置环境变量");
-                    1.1
-                }));
-        let base_fast = Self::get_env_f64("GAS_BASE_MULTIPLIER_BSC_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (BSC_FAST)，使用默认值 1.3。生产环境建议配置环境变量");
-                    1.3
-                }));
-
-        let time_slow = Self::get_env_u64("GAS_ESTIMATED_TIME_BSC_SLOW")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (BSC_SLOW)，使用默认值 300秒。生产环境建议配置环境变量");
-                    300
-                }));
-        let time_normal = Self::get_env_u64("GAS_ESTIMATED_TIME_BSC_NORMAL")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (BSC_NORMAL)，使用默认值 90秒。生产环境建议配置环境变量");
-                    90
-                }));
-        let time_fast = Self::get_env_u64("GAS_ESTIMATED_TIME_BSC_FAST")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (BSC_FAST)，使用默认值 30秒。生产环境建议配置环境变量");
-                    30
-                }));
+    fn with_env_overrides(mut self) -> Self {
+        let key = self.env_key;
 
-        Self {
-            priority_multipliers: [priority_slow, priority_normal, priority_fast],
-            base_fee_multipliers: [base_slow, base_normal, base_fast],
-            estimated_times: [time_slow, time_normal, time_fast],
+        if let Some(percentiles) = Self::get_env(&format!("GAS_REWARD_PERCENTILES_{}", key))
+            .or_else(|| Self::get_env("GAS_REWARD_PERCENTILES"))
+            .and_then(|v| parse_percentiles(&v))
+        {
+            self.reward_percentiles = percentiles;
+        }
+        if let Some(blocks) = Self::get_env_u64(&format!("GAS_FEE_HISTORY_BLOCKS_{}", key))
+            .or_else(|| Self::get_env_u64("GAS_FEE_HISTORY_BLOCKS"))
+        {
+            // 节点普遍限制 blockCount ≤ 1024
+            self.fee_history_blocks = blocks.min(1024);
+        }
+        if let Some(gwei) = Self::get_env_f64(&format!("GAS_MIN_PRIORITY_FEE_GWEI_{}", key)) {
+            self.min_priority_fee = gwei_to_wei(gwei);
         }
-    }
-
-    fn polygon() -> Self {
-        // 企业级实现：优先从环境变量读取配置
-        let priority_slow = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_POLYGON_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (POLYGON_SLOW)，使用默认值 1.0。生产环境建议配置环境变量");
-                    1.0
-                }));
-        let priority_normal = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_POLYGON_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (POLYGON_NORMAL)，使用默认值 1.5。生产环境建议配置环境变量");
-                    1.5
-                }));
-        let priority_fast = Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_POLYGON_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_PRIORITY_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas优先费用倍数 (POLYGON_FAST)，使用默认值 2.5。生产环境建议配置环境变量");
-                    2.5
-                }));
-
-        let base_slow = Self::get_env_f64("GAS_BASE_MULTIPLIER_POLYGON_SLOW")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (POLYGON_SLOW)，使用默认值 1.0。生产环境建议配置环境变量");
-                    1.0
-                }));
-        let base_normal = Self::get_env_f64("GAS_BASE_MULTIPLIER_POLYGON_NORMAL")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (POLYGON_NORMAL)，使用默认值 1.2。生产环境建议配置环境变量");
-                    1.2
-                }));
-        let base_fast = Self::get_env_f64("GAS_BASE_MULTIPLIER_POLYGON_FAST")
-            .unwrap_or_else(|| Self::get_env_f64("GAS_BASE_MULTIPLIER_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas基础费用倍数 (POLYGON_FAST)，使用默认值 1.5。生产环境建议配置环境变量");
-                    1.5
-                }));
-
-        let time_slow = Self::get_env_u64("GAS_ESTIMATED_TIME_POLYGON_SLOW")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_SLOW")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (POLYGON_SLOW)，使用默认值 180秒。生产环境建议配置环境变量");
-                    180
-                }));
-        let time_normal = Self::get_env_u64("GAS_ESTIMATED_TIME_POLYGON_NORMAL")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_NORMAL")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (POLYGON_NORMAL)，使用默认值 60秒。生产环境建议配置环境变量");
-                    60
-                }));
-        let time_fast = Self::get_env_u64("GAS_ESTIMATED_TIME_POLYGON_FAST")
-            .unwrap_or_else(|| Self::get_env_u64("GAS_ESTIMATED_TIME_FAST")
-                .unwrap_or_else(|| {
-                    tracing::warn!("未找到环境变量配置的Gas预估时间 (POLYGON_FAST)，使用默认值 20秒。生产环境建议配置环境变量");
-                    20
-                }));
 
-        Self {
-            priority_multipliers: [priority_slow, priority_normal, priority_fast],
-            base_fee_multipliers: [base_slow, base_normal, base_fast],
-            estimated_times: [time_slow, time_normal, time_fast],
+        for (index, speed) in ["SLOW", "NORMAL", "FAST"].iter().enumerate() {
+            if let Some(multiplier) =
+                Self::get_env_f64(&format!("GAS_BASE_MULTIPLIER_{}_{}", key, speed))
+                    .or_else(|| Self::get_env_f64(&format!("GAS_BASE_MULTIPLIER_{}", speed)))
+            {
+                self.base_fee_multipliers[index] = multiplier;
+            }
+            if let Some(seconds) =
+                Self::get_env_u64(&format!("GAS_ESTIMATED_TIME_{}_{}", key, speed))
+                    .or_else(|| Self::get_env_u64(&format!("GAS_ESTIMATED_TIME_{}", speed)))
+            {
+                self.estimated_times[index] = seconds;
+            }
         }
+        self
     }
 
-    #[allow(dead_code)]
-    fn for_chain(chain: &str) -> Self {
-        match chain.to_lowercase().as_str() {
-            "ethereum" | "eth" => Self::ethereum(),
-            "bsc" | "binance" => Self::bsc(),
-            "polygon" | "matic" => Self::polygon(),
-            _ => Self::ethereum(), // 默认使用以太坊配置
-        }
+    fn get_env(key: &str) -> Option<String> {
+        std::env::var(key).ok().filter(|v| !v.trim().is_empty())
     }
 
     /// 企业级实现：从环境变量读取f64值
@@ -270,132 +211,273 @@ impl ChainGasConfig {
     }
 }
 
+/// 链上费用市场快照（三档共用一次查询）
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FeeMarket {
+    /// 下一区块 baseFeePerGas（Wei）
+    next_base_fee: u64,
+    /// [slow, normal, fast] 优先费（Wei）
+    priority_fees: [u64; 3],
+}
+
 pub struct GasEstimator {
-    rpc_selector: Arc<RpcSelector>,
+    endpoint_provider: Arc<dyn EndpointProvider>,
     http_client: reqwest::Client,
-    // ✅ 缓存配置对象，避免每次请求都读环境变量和打印警告
-    eth_config: ChainGasConfig,
-    bsc_config: ChainGasConfig,
-    polygon_config: ChainGasConfig,
+    // ✅ 缓存配置对象，避免每次请求都读环境变量
+    chain_configs: HashMap<&'static str, ChainGasConfig>,
 }
 
 impl GasEstimator {
     pub fn new(rpc_selector: Arc<RpcSelector>) -> Self {
+        Self::with_endpoint_provider(rpc_selector)
+    }
+
+    pub fn with_endpoint_provider(endpoint_provider: Arc<dyn EndpointProvider>) -> Self {
         let http_client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(10))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
 
-        // ✅ 配置对象只创建一次，警告也只打印一次
-        let eth_config = ChainGasConfig::ethereum();
-        let bsc_config = ChainGasConfig::bsc();
-        let polygon_config = ChainGasConfig::polygon();
+        // ✅ 配置对象只创建一次
+        let chain_configs = [
+            "ethereum",
+            "bsc",
+            "polygon",
+            "arbitrum",
+            "optimism",
+            "avalanche",
+        ]
+        .into_iter()
+        .map(|chain| (chain, ChainGasConfig::for_chain(chain)))
+        .collect();
 
         Self {
-            rpc_selector,
+            endpoint_provider,
             http_client,
-            eth_config,
-            bsc_config,
-            polygon_config,
+            chain_configs,
         }
     }
 
     /// 预估 Gas 费用（主入口）
-    /// 企业级实现：支持EVM链（Ethereum/BSC/Polygon）和非EVM链（Solana/Bitcoin/TON）
+    /// 企业级实现：支持EVM链（含 L2）和非EVM链（Solana/Bitcoin/TON）
     pub async fn estimate_gas(&self, chain: &str, speed: GasSpeed) -> Result<GasEstimate> {
-        // 检查是否为EVM链（包括 L2）
-        let is_evm = matches!(
-            chain.to_lowercase().as_str(),
-            "ethereum"
-                | "eth"
-                | "bsc"
-                | "binance"
-                | "polygon"
-                | "matic"
-                | "arbitrum"
-                | "arb"
-                | "optimism"
-                | "op"
-                | "avalanche"
-                | "avax"
-        );
-
-        if !is_evm {
+        let Some(canonical) = evm_canonical_chain(chain) else {
             // 非EVM链：使用动态费用服务
             return self.estimate_non_evm_gas(chain, speed).await;
-        }
-
-        // EVM链：使用EIP-1559标准
-        // 1. 获取链上最新的 baseFeePerGas
-        let base_fee_wei = self.fetch_base_fee(chain).await?;
+        };
 
-        // 2. 获取推荐的 maxPriorityFeePerGas
-        let priority_fee_wei = self.fetch_priority_fee(chain).await?;
+        let config = self.get_chain_config(&canonical);
+        let market = self.fetch_fee_market(&canonical, config).await?;
+        let l1_fee = self.reference_l1_data_fee(&canonical, config).await?;
+        Ok(build_estimate(config, &market, speed, l1_fee))
+    }
 
-        // 3. 根据速度调整费用
-        // ✅ 使用缓存的配置对象，避免重复读取环境变量
-        let config = self.get_chain_config(chain);
-        let speed_index = match speed {
-            GasSpeed::Slow => 0,
-            GasSpeed::Normal => 1,
-            GasSpeed::Fast => 2,
+    /// 批量预估（返回三档速度，共用一次链上查询）
+    pub async fn estimate_all_speeds(&self, chain: &str) -> Result<GasEstimateResponse> {
+        let Some(canonical) = evm_canonical_chain(chain) else {
+            let slow = self.estimate_non_evm_gas(chain, GasSpeed::Slow).await?;
+            let normal = self.estimate_non_evm_gas(chain, GasSpeed::Normal).await?;
+            let fast = self.estimate_non_evm_gas(chain, GasSpeed::Fast).await?;
+            return Ok(GasEstimateResponse { slow, normal, fast });
         };
 
-        let adjusted_base_fee =
-            (base_fee_wei as f64 * config.base_fee_multipliers[speed_index]) as u64;
-        let adjusted_priority_fee =
-            (priority_fee_wei as f64 * config.priority_multipliers[speed_index]) as u64;
+        let config = self.get_chain_config(&canonical);
+        let market = self.fetch_fee_market(&canonical, config).await?;
+        let l1_fee = self.reference_l1_data_fee(&canonical, config).await?;
 
-        // 4. 计算 maxFeePerGas = baseFee * multiplier + maxPriorityFee
-        let max_fee_per_gas = adjusted_base_fee + adjusted_priority_fee;
+        Ok(GasEstimateResponse {
+            slow: build_estimate(config, &market, GasSpeed::Slow, l1_fee),
+            normal: build_estimate(config, &market, GasSpeed::Normal, l1_fee),
+            fast: build_estimate(config, &market, GasSpeed::Fast, l1_fee),
+        })
+    }
 
-        // 5. 转换为 Gwei 便于展示
-        let base_fee_gwei = wei_to_gwei(adjusted_base_fee);
-        let priority_fee_gwei = wei_to_gwei(adjusted_priority_fee);
-        let max_fee_gwei = wei_to_gwei(max_fee_per_gas);
+    /// L2 交易的 L1 数据费（Wei）；`tx_payload` 为序列化交易，L1 链返回 0
+    pub async fn l1_data_fee(&self, chain: &str, to: Address, tx_payload: &[u8]) -> Result<U256> {
+        let Some(canonical) = evm_canonical_chain(chain) else {
+            return Ok(U256::zero());
+        };
+        let model = self.get_chain_config(&canonical).l1_fee_model;
+        self.query_l1_data_fee(&canonical, model, to, tx_payload)
+            .await
+    }
 
-        Ok(GasEstimate {
-            base_fee: format!("0x{:x}", adjusted_base_fee),
-            max_priority_fee: format!("0x{:x}", adjusted_priority_fee),
-            max_fee_per_gas: format!("0x{:x}", max_fee_per_gas),
-            estimated_time_seconds: config.estimated_times[speed_index],
-            base_fee_gwei,
-            max_priority_fee_gwei: priority_fee_gwei,
-            max_fee_per_gas_gwei: max_fee_gwei,
-        })
+    /// 参考交易的 L1 数据费（用于三档预估的 estimated_fee）
+    async fn reference_l1_data_fee(&self, chain: &str, config: &ChainGasConfig) -> Result<U256> {
+        if config.l1_fee_model == L1FeeModel::None {
+            return Ok(U256::zero());
+        }
+        self.query_l1_data_fee(
+            chain,
+            config.l1_fee_model,
+            Address::zero(),
+            &reference_tx_payload(),
+        )
+        .await
     }
 
-    /// 获取链上最新区块的 baseFeePerGas
-    async fn fetch_base_fee(&self, chain: &str) -> Result<u64> {
-        let endpoint = self
-            .rpc_selector
-            .select(chain)
+    async fn query_l1_data_fee(
+        &self,
+        chain: &str,
+        model: L1FeeModel,
+        to: Address,
+        tx_payload: &[u8],
+    ) -> Result<U256> {
+        match model {
+            L1FeeModel::None => Ok(U256::zero()),
+            L1FeeModel::OpStack => {
+                let data = encode_call("getL1Fee(bytes)", &[Token::Bytes(tx_payload.to_vec())]);
+                let output = self.eth_call(chain, OP_GAS_PRICE_ORACLE, &data).await?;
+                let decoded = abi::decode(&[ParamType::Uint(256)], &output)
+                    .context("Failed to decode getL1Fee result")?;
+                decoded
+                    .into_iter()
+                    .next()
+                    .and_then(Token::into_uint)
+                    .context("Invalid getL1Fee result")
+            }
+            L1FeeModel::Arbitrum => {
+                let data = encode_call(
+                    "gasEstimateL1Component(address,bool,bytes)",
+                    &[
+                        Token::Address(to),
+                        Token::Bool(false),
+                        Token::Bytes(tx_payload.to_vec()),
+                    ],
+                );
+                let output = self.eth_call(chain, ARBITRUM_NODE_INTERFACE, &data).await?;
+                let decoded = abi::decode(
+                    &[
+                        ParamType::Uint(64),
+                        ParamType::Uint(256),
+                        ParamType::Uint(256),
+                    ],
+                    &output,
+                )
+                .context("Failed to decode gasEstimateL1Component result")?;
+                let mut values = decoded.into_iter().filter_map(Token::into_uint);
+                let (Some(l1_gas), Some(base_fee)) = (values.next(), values.next()) else {
+                    anyhow::bail!("Invalid gasEstimateL1Component result");
+                };
+                // L1 部分以 L2 gas 计价：gasEstimateForL1 × L2 baseFee
+                Ok(l1_gas.saturating_mul(base_fee))
+            }
+        }
+    }
+
+    /// JSON-RPC 调用
+    async fn rpc(
+        &self,
+        chain: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let url = self
+            .endpoint_provider
+            .distinct_endpoints(chain, 1)
             .await
+            .into_iter()
+            .next()
             .context("No healthy RPC endpoint available")?;
 
-        // JSON-RPC 请求：eth_getBlockByNumber("latest", false)
         let request_body = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "eth_getBlockByNumber",
-            "params": ["latest", false]
+            "method": method,
+            "params": params
         });
 
-        let response = self
+        let json: serde_json::Value = self
             .http_client
-            .post(&endpoint.url)
+            .post(&url)
             .json(&request_body)
+            .with_trace_context()
             .send()
             .await
-            .context("Failed to fetch latest block")?;
-
-        let json: serde_json::Value = response
+            .with_context(|| format!("Failed to send {} request", method))?
             .json()
             .await
-            .context("Failed to parse block response")?;
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        if let Some(error) = json.get("error") {
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown RPC error");
+            anyhow::bail!("RPC error ({}): {}", method, message);
+        }
+        Ok(json
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn eth_call(&self, chain: &str, to: &str, data: &[u8]) -> Result<Vec<u8>> {
+        let result = self
+            .rpc(
+                chain,
+                "eth_call",
+                serde_json::json!([{ "to": to, "data": format!("0x{}", hex::encode(data)) }, "latest"]),
+            )
+            .await?;
+        let hex_str = result.as_str().context("Missing eth_call result")?;
+        hex::decode(hex_str.trim_start_matches("0x")).context("Invalid eth_call result hex")
+    }
+
+    /// 获取费用市场：优先 eth_feeHistory，节点不支持时降级为
+    /// 最新区块 baseFee + eth_maxPriorityFeePerGas（三档相同）
+    async fn fetch_fee_market(&self, chain: &str, config: &ChainGasConfig) -> Result<FeeMarket> {
+        let history = self
+            .rpc(
+                chain,
+                "eth_feeHistory",
+                serde_json::json!([
+                    format!("0x{:x}", config.fee_history_blocks.max(1)),
+                    "latest",
+                    config.reward_percentiles
+                ]),
+            )
+            .await;
+
+        match history
+            .as_ref()
+            .ok()
+            .and_then(|h| fee_market_from_history(h, config.min_priority_fee))
+        {
+            Some(market) => {
+                tracing::debug!(chain=%chain, next_base_fee=%market.next_base_fee, priority_fees=?market.priority_fees, "fetched_fee_history");
+                Ok(market)
+            }
+            None => {
+                if let Err(e) = &history {
+                    tracing::warn!(chain=%chain, error=%e, "eth_feeHistory unavailable, falling back to eth_maxPriorityFeePerGas");
+                }
+                let next_base_fee = self.fetch_base_fee(chain).await?;
+                let priority_fee = self
+                    .fetch_priority_fee(chain)
+                    .await
+                    .max(config.min_priority_fee);
+                Ok(FeeMarket {
+                    next_base_fee,
+                    priority_fees: [priority_fee; 3],
+                })
+            }
+        }
+    }
+
+    /// 获取链上最新区块的 baseFeePerGas
+    async fn fetch_base_fee(&self, chain: &str) -> Result<u64> {
+        let block = self
+            .rpc(
+                chain,
+                "eth_getBlockByNumber",
+                serde_json::json!(["latest", false]),
+            )
+            .await
+            .context("Failed to fetch latest block")?;
 
         // 提取 baseFeePerGas 字段
-        let base_fee_hex = json["result"]["baseFeePerGas"]
+        let base_fee_hex = block["baseFeePerGas"]
             .as_str()
             .context("baseFeePerGas not found in block")?;
 
@@ -405,65 +487,29 @@ impl GasEstimator {
         Ok(base_fee)
     }
 
-    /// 获取推荐的 maxPriorityFeePerGas
-    async fn fetch_priority_fee(&self, chain: &str) -> Result<u64> {
-        let endpoint = self
-            .rpc_selector
-            .select(chain)
+    /// 获取推荐的 maxPriorityFeePerGas（RPC 不支持时使用默认值）
+    async fn fetch_priority_fee(&self, chain: &str) -> u64 {
+        let priority_fee = match self
+            .rpc(chain, "eth_maxPriorityFeePerGas", serde_json::json!([]))
             .await
-            .context("No healthy RPC endpoint available")?;
-
-        // JSON-RPC 请求：eth_maxPriorityFeePerGas
-        let request_body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "eth_maxPriorityFeePerGas",
-            "params": []
-        });
-
-        let response = self
-            .http_client
-            .post(&endpoint.url)
-            .json(&request_body)
-            .send()
-            .await;
-
-        // 降级策略：如果 RPC 不支持此方法，使用默认值
-        let priority_fee = match response {
-            Ok(resp) => match resp.json::<serde_json::Value>().await {
-                Ok(json) => {
-                    if let Some(result) = json["result"].as_str() {
-                        parse_hex_u64(result).unwrap_or_else(|_| default_priority_fee(chain))
-                    } else {
-                        default_priority_fee(chain)
-                    }
-                }
-                Err(_) => default_priority_fee(chain),
-            },
+        {
+            Ok(result) => result
+                .as_str()
+                .and_then(|r| parse_hex_u64(r).ok())
+                .unwrap_or_else(|| default_priority_fee(chain)),
             Err(_) => default_priority_fee(chain),
         };
 
         tracing::debug!(chain=%chain, priority_fee_wei=%priority_fee, "fetched_priority_fee");
-        Ok(priority_fee)
+        priority_fee
     }
 
     /// ✅ 获取缓存的链配置对象
-    fn get_chain_config(&self, chain: &str) -> &ChainGasConfig {
-        match chain.to_lowercase().as_str() {
-            "ethereum" | "eth" => &self.eth_config,
-            "bsc" | "binance" => &self.bsc_config,
-            "polygon" | "matic" => &self.polygon_config,
-            _ => &self.eth_config, // 默认使用以太坊配置
-        }
-    }
-
-    /// 批量预估（返回三档速度）
-    pub async fn estimate_all_speeds(&self, chain: &str) -> Result<GasEstimateResponse> {
-        let slow = self.estimate_gas(chain, GasSpeed::Slow).await?;
-        let normal = self.estimate_gas(chain, GasSpeed::Normal).await?;
-        let fast = self.estimate_gas(chain, GasSpeed::Fast).await?;
-
-        Ok(GasEstimateResponse { slow, normal, fast })
+    fn get_chain_config(&self, canonical: &str) -> &ChainGasConfig {
+        self.chain_configs
+            .get(canonical)
+            .or_else(|| self.chain_configs.get("ethereum"))
+            .expect("ethereum gas config is always registered")
     }
 
     /// 企业级实现：非EVM链费用预估（Solana/Bitcoin/TON）
@@ -511,6 +557,9 @@ impl GasEstimator {
             base_fee_gwei: adjusted_fee,
             max_priority_fee_gwei: 0.0,
             max_fee_per_gas_gwei: adjusted_fee,
+            gas_limit: 1, // 非EVM链按笔计费
+            l1_data_fee: "0".to_string(),
+            estimated_fee: fee_wei.to_string(),
         })
     }
 }
@@ -525,13 +574,129 @@ pub struct GasEstimateResponse {
 
 // ============ 辅助函数 ============
 
+/// EVM 链返回规范链名，非 EVM 链返回 None
+fn evm_canonical_chain(chain: &str) -> Option<String> {
+    let canonical = chain_normalizer::normalize_chain_identifier(chain).ok()?;
+    chain_normalizer::is_evm_chain(&canonical).then_some(canonical)
+}
+
+/// 按速度档位组装预估结果
+fn build_estimate(
+    config: &ChainGasConfig,
+    market: &FeeMarket,
+    speed: GasSpeed,
+    l1_data_fee: U256,
+) -> GasEstimate {
+    let index = speed.index();
+    let adjusted_base_fee =
+        (market.next_base_fee as f64 * config.base_fee_multipliers[index]) as u64;
+    let priority_fee = market.priority_fees[index];
+
+    // maxFeePerGas = baseFee * multiplier + maxPriorityFee
+    let max_fee_per_gas = adjusted_base_fee.saturating_add(priority_fee);
+    let estimated_fee = U256::from(REFERENCE_GAS_LIMIT)
+        .saturating_mul(U256::from(max_fee_per_gas))
+        .saturating_add(l1_data_fee);
+
+    GasEstimate {
+        base_fee: format!("0x{:x}", adjusted_base_fee),
+        max_priority_fee: format!("0x{:x}", priority_fee),
+        max_fee_per_gas: format!("0x{:x}", max_fee_per_gas),
+        estimated_time_seconds: config.estimated_times[index],
+        base_fee_gwei: wei_to_gwei(adjusted_base_fee),
+        max_priority_fee_gwei: wei_to_gwei(priority_fee),
+        max_fee_per_gas_gwei: wei_to_gwei(max_fee_per_gas),
+        gas_limit: REFERENCE_GAS_LIMIT,
+        l1_data_fee: l1_data_fee.to_string(),
+        estimated_fee: estimated_fee.to_string(),
+    }
+}
+
+/// 解析 eth_feeHistory 结果：
+/// - baseFeePerGas 最后一项为下一区块的基础费
+/// - 每档优先费取窗口内各区块该百分位奖励的中位数（跳过空块），不低于下限
+fn fee_market_from_history(
+    history: &serde_json::Value,
+    min_priority_fee: u64,
+) -> Option<FeeMarket> {
+    let next_base_fee = history["baseFeePerGas"]
+        .as_array()?
+        .last()
+        .and_then(|v| v.as_str())
+        .and_then(|v| parse_hex_u64(v).ok())?;
+
+    let rewards = history["reward"].as_array()?;
+    let gas_used = history["gasUsedRatio"].as_array();
+
+    let mut priority_fees = [0u64; 3];
+    for (index, fee) in priority_fees.iter_mut().enumerate() {
+        let mut samples: Vec<u64> = rewards
+            .iter()
+            .enumerate()
+            .filter(|(block, _)| {
+                // 空块的奖励恒为 0，会拉低中位数
+                gas_used
+                    .and_then(|ratios| ratios.get(*block))
+                    .and_then(|r| r.as_f64())
+                    .is_none_or(|ratio| ratio > 0.0)
+            })
+            .filter_map(|(_, block)| block.get(index)?.as_str())
+            .filter_map(|v| parse_hex_u64(v).ok())
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        *fee = samples[samples.len() / 2].max(min_priority_fee);
+    }
+
+    // 百分位单调：快档不低于正常档，正常档不低于慢档
+    priority_fees[1] = priority_fees[1].max(priority_fees[0]);
+    priority_fees[2] = priority_fees[2].max(priority_fees[1]);
+
+    Some(FeeMarket {
+        next_base_fee,
+        priority_fees,
+    })
+}
+
+/// 解析百分位配置（"10,50,90"），要求 3 项、0-100 且递增
+fn parse_percentiles(value: &str) -> Option<[f64; 3]> {
+    let parts: Vec<f64> = value
+        .split(',')
+        .map(|p| p.trim().parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let percentiles: [f64; 3] = parts.try_into().ok()?;
+    let valid = percentiles.iter().all(|p| (0.0..=100.0).contains(p))
+        && percentiles.windows(2).all(|w| w[0] <= w[1]);
+    valid.then_some(percentiles)
+}
+
+/// 函数选择器 + ABI 编码参数
+fn encode_call(signature: &str, args: &[Token]) -> Vec<u8> {
+    let mut data = ethers::utils::id(signature).to_vec();
+    data.extend(abi::encode(args));
+    data
+}
+
+/// 参考交易载荷：不可压缩的伪随机字节（OP Fjord 起 L1 费按 FastLZ 压缩后大小计价）
+fn reference_tx_payload() -> Vec<u8> {
+    let mut payload = Vec::with_capacity(REFERENCE_TX_BYTES);
+    let mut seed = ethers::utils::keccak256(b"gas-estimator-reference-tx");
+    while payload.len() < REFERENCE_TX_BYTES {
+        payload.extend_from_slice(&seed);
+        seed = ethers::utils::keccak256(seed);
+    }
+    payload.truncate(REFERENCE_TX_BYTES);
+    payload
+}
+
 /// Wei 转 Gwei（1 Gwei = 1e9 Wei）
 fn wei_to_gwei(wei: u64) -> f64 {
     wei as f64 / 1_000_000_000.0
 }
 
 /// Gwei 转 Wei
-#[allow(dead_code)]
 fn gwei_to_wei(gwei: f64) -> u64 {
     (gwei * 1_000_000_000.0) as u64
 }
@@ -657,6 +822,9 @@ fn default_priority_fee(chain: &str) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    use async_trait::async_trait;
+    use axum::{routing::post, Json, Router};
+
     use super::*;
 
     /// Test 1: Wei 转 Gwei 精度测试
@@ -684,16 +852,26 @@ mod tests {
         assert_eq!(parse_hex_u64("3b9aca00").unwrap(), 1_000_000_000); // 无 0x 前缀
     }
 
-    /// Test 4: 链配置测试
+    /// Test 4: 链配置测试（chain_normalizer 中每条 EVM 链都有独立配置）
     #[test]
     fn test_chain_gas_config() {
-        let eth_config = ChainGasConfig::ethereum();
-        assert_eq!(eth_config.priority_multipliers[0], 1.0); // slow
-        assert_eq!(eth_config.priority_multipliers[1], 1.5); // normal
-        assert_eq!(eth_config.priority_multipliers[2], 2.0); // fast
+        let eth_config = ChainGasConfig::for_chain("ethereum");
+        assert_eq!(eth_config.reward_percentiles, [10.0, 50.0, 90.0]);
+        assert_eq!(eth_config.fee_history_blocks, 20);
+        assert_eq!(eth_config.l1_fee_model, L1FeeModel::None);
 
-        let bsc_config = ChainGasConfig::bsc();
+        let bsc_config = ChainGasConfig::for_chain("bsc");
         assert_eq!(bsc_config.estimated_times[2], 30); // fast: 30秒
+
+        assert_eq!(
+            ChainGasConfig::for_chain("optimism").l1_fee_model,
+            L1FeeModel::OpStack
+        );
+        assert_eq!(
+            ChainGasConfig::for_chain("arbitrum").l1_fee_model,
+            L1FeeModel::Arbitrum
+        );
+        assert_eq!(ChainGasConfig::for_chain("avalanche").env_key, "AVALANCHE");
     }
 
     /// Test 5: 默认优先费用测试
@@ -715,18 +893,142 @@ mod tests {
         assert_eq!(wei_to_gwei(max_fee), 52.0);
     }
 
-    /// Test 7: 速度倍数应用测试
+    /// Test 7: feeHistory 百分位 → 三档优先费（中位数、跳过空块、下限）
     #[test]
-    fn test_speed_multipliers() {
-        let base = 100.0;
-        let config = ChainGasConfig::ethereum();
+    fn test_fee_market_from_history() {
+        let history = serde_json::json!({
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            "gasUsedRatio": [0.5, 0.0, 0.9],
+            "reward": [
+                ["0x1", "0x64", "0x3e8"],
+                ["0x0", "0x0", "0x0"],
+                ["0x3", "0xc8", "0x7d0"]
+            ]
+        });
 
-        let slow = base * config.priority_multipliers[0]; // 100 * 1.0 = 100
-        let normal = base * config.priority_multipliers[1]; // 100 * 1.5 = 150
-        let fast = base * config.priority_multipliers[2]; // 100 * 2.0 = 200
+        let market = fee_market_from_history(&history, 0).unwrap();
+        assert_eq!(market.next_base_fee, 2_000_000_000);
+        // 空块被跳过，两个样本取上中位数
+        assert_eq!(market.priority_fees, [3, 200, 2000]);
 
-        assert_eq!(slow, 100.0);
-        assert_eq!(normal, 150.0);
-        assert_eq!(fast, 200.0);
+        let floored = fee_market_from_history(&history, 500).unwrap();
+        assert_eq!(floored.priority_fees, [500, 500, 2000]);
+
+        assert!(fee_market_from_history(&serde_json::json!({ "reward": [] }), 0).is_none());
+    }
+
+    /// Test 8: 百分位配置解析
+    #[test]
+    fn test_parse_percentiles() {
+        assert_eq!(parse_percentiles("5, 50, 95"), Some([5.0, 50.0, 95.0]));
+        assert_eq!(parse_percentiles("50,10,90"), None);
+        assert_eq!(parse_percentiles("10,50"), None);
+        assert_eq!(parse_percentiles("10,50,101"), None);
+    }
+
+    struct StaticEndpoint(String);
+
+    #[async_trait]
+    impl EndpointProvider for StaticEndpoint {
+        async fn distinct_endpoints(&self, _chain: &str, _count: usize) -> Vec<String> {
+            vec![self.0.clone()]
+        }
+    }
+
+    /// 本地 JSON-RPC 桩：feeHistory + OP GasPriceOracle / Arbitrum NodeInterface
+    async fn mock_rpc() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|Json(req): Json<serde_json::Value>| async move {
+                let params = &req["params"];
+                let result = match req["method"].as_str().unwrap_or_default() {
+                    "eth_feeHistory" => serde_json::json!({
+                        "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+                        "gasUsedRatio": [0.5],
+                        "reward": [["0xa", "0x14", "0x1e"]]
+                    }),
+                    "eth_call" => {
+                        let to = params[0]["to"].as_str().unwrap_or_default().to_lowercase();
+                        let data = params[0]["data"].as_str().unwrap_or_default();
+                        if to == OP_GAS_PRICE_ORACLE.to_lowercase() {
+                            assert!(data.starts_with("0x49948e0e"), "getL1Fee selector");
+                            serde_json::json!(format!(
+                                "0x{}",
+                                hex::encode(abi::encode(&[Token::Uint(U256::from(
+                                    5_000_000_000_000u64
+                                ))]))
+                            ))
+                        } else {
+                            assert_eq!(to, ARBITRUM_NODE_INTERFACE.to_lowercase());
+                            serde_json::json!(format!(
+                                "0x{}",
+                                hex::encode(abi::encode(&[
+                                    Token::Uint(U256::from(1_500u64)),
+                                    Token::Uint(U256::from(10_000_000u64)),
+                                    Token::Uint(U256::from(30_000_000_000u64)),
+                                ]))
+                            ))
+                        }
+                    }
+                    _ => serde_json::Value::Null,
+                };
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Test 9: OP-stack 预估包含 L1 数据费
+    #[tokio::test]
+    async fn test_optimism_estimate_includes_l1_data_fee() {
+        let estimator =
+            GasEstimator::with_endpoint_provider(Arc::new(StaticEndpoint(mock_rpc().await)));
+
+        let estimates = estimator.estimate_all_speeds("op").await.unwrap();
+        // 奖励（10/20/30 wei）均低于 OP 下限 0.001 Gwei
+        assert_eq!(
+            estimates.slow.max_priority_fee,
+            format!("0x{:x}", 1_000_000)
+        );
+        assert_eq!(
+            estimates.fast.max_priority_fee,
+            format!("0x{:x}", 1_000_000)
+        );
+
+        let normal = &estimates.normal;
+        assert_eq!(normal.l1_data_fee, "5000000000000");
+        // 1 Gwei × 1.2 + 0.001 Gwei
+        let max_fee = 1_200_000_000u64 + 1_000_000;
+        assert_eq!(normal.max_fee_per_gas, format!("0x{:x}", max_fee));
+        assert_eq!(
+            normal.estimated_fee,
+            (U256::from(21_000u64) * U256::from(max_fee) + U256::from(5_000_000_000_000u64))
+                .to_string()
+        );
+    }
+
+    /// Test 10: Arbitrum L1 部分 = gasEstimateForL1 × L2 baseFee
+    #[tokio::test]
+    async fn test_arbitrum_l1_component() {
+        let estimator =
+            GasEstimator::with_endpoint_provider(Arc::new(StaticEndpoint(mock_rpc().await)));
+
+        let fee = estimator
+            .l1_data_fee("arbitrum", Address::zero(), &[0xab; 100])
+            .await
+            .unwrap();
+        assert_eq!(fee, U256::from(15_000_000_000u64));
+
+        let l1_chain_fee = estimator
+            .l1_data_fee("ethereum", Address::zero(), &[0xab; 100])
+            .await
+            .unwrap();
+        assert!(l1_chain_fee.is_zero());
     }
 }
@@ -0,0 +1,476 @@
+//! JWT 签名密钥管理服务
+//!
+//! 企业级实现：密钥持久化在 admin.jwt_signing_keys（私钥加密存储）。
+//! 轮换分两阶段：新密钥先以 pending 发布（进入 JWKS、仅验证），
+//! 等各实例至少完成一次密钥环刷新后才切换为签名密钥，旧密钥随之进入 retiring，
+//! 在宽限期内仍可验证；因此任何副本签发的 Token 在其他副本上都已可验证
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::Jwk;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::infrastructure::{
+    encryption::{decrypt_data, encrypt_data, get_encryption_key},
+    jwt::{
+        self, generate_signing_key, JwtKey, JwtKeyring, KeyPurpose, SigningAlgorithm,
+        REFRESH_TOKEN_EXPIRY_SECS,
+    },
+};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 签名密钥元数据（不含私钥）
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct JwtKeyInfo {
+    pub kid: String,
+    pub purpose: String,
+    pub algorithm: String,
+    pub status: String,
+    /// pending 密钥切换为签名密钥的时间
+    pub activate_after: Option<DateTime<Utc>>,
+    pub retire_after: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 启动时配置的签名算法（JWT_SIGNING_ALG=RS256|EdDSA；未设置则保持 HS256）
+pub fn configured_algorithm() -> Option<SigningAlgorithm> {
+    std::env::var("JWT_SIGNING_ALG")
+        .ok()
+        .and_then(|v| SigningAlgorithm::parse(&v))
+}
+
+/// 轮换后旧密钥的默认验证宽限期：覆盖该用途 Token 的最长有效期
+pub fn default_grace_period(purpose: KeyPurpose) -> chrono::Duration {
+    let secs = match purpose {
+        KeyPurpose::Access => std::env::var("JWT_TOKEN_EXPIRY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(3600),
+        KeyPurpose::Refresh => REFRESH_TOKEN_EXPIRY_SECS,
+    };
+    chrono::Duration::seconds(secs)
+}
+
+fn new_kid(purpose: KeyPurpose) -> String {
+    format!(
+        "{}-{}-{}",
+        purpose.as_str(),
+        Utc::now().format("%Y%m%d%H%M%S"),
+        &Uuid::new_v4().simple().to_string()[..8]
+    )
+}
+
+/// 从数据库加载的密钥（私钥已解密，仅 active 密钥携带）
+struct StoredKey {
+    kid: String,
+    purpose: KeyPurpose,
+    algorithm: SigningAlgorithm,
+    status: String,
+    public_jwk: Jwk,
+    private_der: Option<zeroize::Zeroizing<Vec<u8>>>,
+}
+
+/// 由已加载的密钥构建密钥环：只有 active 密钥用于签名，pending / retiring 仅验证
+fn build_keyring(keys: Vec<StoredKey>) -> Result<JwtKeyring> {
+    let mut keyring = JwtKeyring::new();
+    for key in keys {
+        let private_der = if key.status == "active" {
+            key.private_der.as_deref().map(|d| d.as_slice())
+        } else {
+            None
+        };
+        keyring.add_key(JwtKey::new(
+            key.kid,
+            key.purpose,
+            key.algorithm,
+            key.public_jwk,
+            private_der,
+        )?);
+    }
+    Ok(keyring)
+}
+
+pub struct JwtKeyManager {
+    pool: PgPool,
+    refresh_interval: Duration,
+}
+
+impl JwtKeyManager {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// 启动初始化：配置了非对称算法且某用途既无签名密钥也无待激活密钥时自动生成，然后加载密钥环
+    pub async fn bootstrap(&self) -> Result<()> {
+        if let Some(algorithm) = configured_algorithm() {
+            for purpose in KeyPurpose::ALL {
+                let exists: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM admin.jwt_signing_keys
+                     WHERE purpose = $1 AND status IN ('active', 'pending'))",
+                )
+                .bind(purpose.as_str())
+                .fetch_one(&self.pool)
+                .await?;
+
+                // 首个密钥没有需要等待的旧签名密钥，立即激活
+                if !exists {
+                    self.stage_key(
+                        purpose,
+                        algorithm,
+                        chrono::Duration::zero(),
+                        chrono::Duration::zero(),
+                        None,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        let loaded = self.reload().await?;
+        tracing::info!(keys = loaded, "JWT keyring loaded");
+        Ok(())
+    }
+
+    /// 激活到期的 pending 密钥、过期 retiring 密钥置为 retired，并从数据库重建密钥环
+    pub async fn reload(&self) -> Result<usize> {
+        self.promote_due_keys().await?;
+
+        sqlx::query(
+            "UPDATE admin.jwt_signing_keys SET status = 'retired', updated_at = NOW()
+             WHERE status = 'retiring' AND retire_after <= NOW()",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let rows =
+            sqlx::query_as::<_, (String, String, String, Vec<u8>, serde_json::Value, String)>(
+                "SELECT kid, purpose, algorithm, private_key_encrypted, public_jwk, status
+             FROM admin.jwt_signing_keys
+             WHERE status IN ('active', 'pending') OR (status = 'retiring' AND retire_after > NOW())",
+            )
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to load JWT signing keys")?;
+
+        if rows.is_empty() {
+            jwt::install_keyring(JwtKeyring::new());
+            return Ok(0);
+        }
+
+        let enc_key = get_encryption_key()?;
+        let mut keys = Vec::with_capacity(rows.len());
+        for (kid, purpose, algorithm, private_encrypted, public_jwk, status) in rows {
+            let purpose = KeyPurpose::parse(&purpose)
+                .ok_or_else(|| anyhow!("Invalid purpose for key {}", kid))?;
+            let algorithm = SigningAlgorithm::parse(&algorithm)
+                .ok_or_else(|| anyhow!("Invalid algorithm for key {}", kid))?;
+            let public_jwk: Jwk = serde_json::from_value(public_jwk)
+                .with_context(|| format!("Invalid public JWK for key {}", kid))?;
+
+            // 仅 active 密钥解密私钥用于签名
+            let private_der = if status == "active" {
+                Some(zeroize::Zeroizing::new(
+                    decrypt_data(&private_encrypted, &enc_key)
+                        .with_context(|| format!("Failed to decrypt key {}", kid))?,
+                ))
+            } else {
+                None
+            };
+
+            keys.push(StoredKey {
+                kid,
+                purpose,
+                algorithm,
+                status,
+                public_jwk,
+                private_der,
+            });
+        }
+
+        let keyring = build_keyring(keys)?;
+        let count = keyring.jwks().keys.len();
+        jwt::install_keyring(keyring);
+        Ok(count)
+    }
+
+    /// 将 activate_after 已到的 pending 密钥切换为签名密钥，同时旧 active → retiring
+    ///
+    /// 每个密钥单独事务；多实例并发时仅一个实例的更新生效
+    async fn promote_due_keys(&self) -> Result<()> {
+        let due = sqlx::query_as::<_, (String, String, Option<i64>)>(
+            "SELECT kid, purpose, grace_period_secs FROM admin.jwt_signing_keys
+             WHERE status = 'pending' AND activate_after <= NOW()",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (kid, purpose, grace_secs) in due {
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query(
+                "UPDATE admin.jwt_signing_keys
+                 SET status = 'retiring', retire_after = NOW() + make_interval(secs => $2), updated_at = NOW()
+                 WHERE purpose = $1 AND status = 'active'
+                   AND EXISTS(SELECT 1 FROM admin.jwt_signing_keys WHERE kid = $3 AND status = 'pending')",
+            )
+            .bind(&purpose)
+            .bind(grace_secs.unwrap_or(0) as f64)
+            .bind(&kid)
+            .execute(&mut *tx)
+            .await?;
+
+            let promoted = sqlx::query(
+                "UPDATE admin.jwt_signing_keys SET status = 'active', updated_at = NOW()
+                 WHERE kid = $1 AND status = 'pending'",
+            )
+            .bind(&kid)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            if promoted == 0 {
+                // 其他实例已完成激活（或该密钥已被新的轮换取代）
+                tx.rollback().await?;
+                continue;
+            }
+
+            if let Err(e) = tx.commit().await {
+                tracing::warn!(kid = %kid, error = ?e, "JWT signing key promotion lost a race");
+                continue;
+            }
+
+            tracing::info!(kid = %kid, purpose = %purpose, "JWT signing key activated");
+        }
+
+        Ok(())
+    }
+
+    /// 轮换指定用途的签名密钥：新密钥先以 pending 发布（仅验证），
+    /// 两个刷新周期后才切换为签名密钥，届时旧 active → retiring（宽限期后失效）
+    pub async fn rotate(
+        &self,
+        purpose: KeyPurpose,
+        algorithm: SigningAlgorithm,
+        grace_period: chrono::Duration,
+        operator_id: Option<Uuid>,
+    ) -> Result<JwtKeyInfo> {
+        let activation_delay = chrono::Duration::from_std(self.refresh_interval * 2)?;
+        self.stage_key(
+            purpose,
+            algorithm,
+            grace_period,
+            activation_delay,
+            operator_id,
+        )
+        .await
+    }
+
+    /// 生成新密钥并以 pending 状态写入；同用途尚未激活的旧 pending 密钥被取代
+    async fn stage_key(
+        &self,
+        purpose: KeyPurpose,
+        algorithm: SigningAlgorithm,
+        grace_period: chrono::Duration,
+        activation_delay: chrono::Duration,
+        operator_id: Option<Uuid>,
+    ) -> Result<JwtKeyInfo> {
+        let generated = tokio::task::spawn_blocking(move || generate_signing_key(algorithm))
+            .await
+            .map_err(|e| anyhow!("Key generation task failed: {}", e))??;
+        let private_encrypted = encrypt_data(&generated.private_der, &get_encryption_key()?)?;
+        let kid = new_kid(purpose);
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE admin.jwt_signing_keys SET status = 'retired', updated_at = NOW()
+             WHERE purpose = $1 AND status = 'pending'",
+        )
+        .bind(purpose.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        let (activate_after, created_at): (DateTime<Utc>, DateTime<Utc>) = sqlx::query_as(
+            "INSERT INTO admin.jwt_signing_keys
+                 (kid, purpose, algorithm, private_key_encrypted, public_jwk, status,
+                  activate_after, grace_period_secs, created_by)
+             VALUES ($1, $2, $3, $4, $5, 'pending', NOW() + make_interval(secs => $6), $7, $8)
+             RETURNING activate_after, created_at",
+        )
+        .bind(&kid)
+        .bind(purpose.as_str())
+        .bind(algorithm.as_str())
+        .bind(&private_encrypted)
+        .bind(serde_json::to_value(&generated.public_jwk)?)
+        .bind(activation_delay.num_seconds() as f64)
+        .bind(grace_period.num_seconds())
+        .bind(operator_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to store JWT signing key")?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            kid = %kid,
+            purpose = purpose.as_str(),
+            algorithm = algorithm.as_str(),
+            grace_secs = grace_period.num_seconds(),
+            activate_after = %activate_after,
+            "JWT signing key staged"
+        );
+
+        self.reload().await?;
+
+        Ok(JwtKeyInfo {
+            kid,
+            purpose: purpose.as_str().to_string(),
+            algorithm: algorithm.as_str().to_string(),
+            status: "pending".to_string(),
+            activate_after: Some(activate_after),
+            retire_after: None,
+            created_at,
+        })
+    }
+
+    /// 列出全部密钥元数据
+    pub async fn list_keys(&self) -> Result<Vec<JwtKeyInfo>> {
+        let rows = sqlx::query_as::<
+            _,
+            (
+                String,
+                String,
+                String,
+                String,
+                Option<DateTime<Utc>>,
+                Option<DateTime<Utc>>,
+                DateTime<Utc>,
+            ),
+        >(
+            "SELECT kid, purpose, algorithm, status, activate_after, retire_after, created_at
+             FROM admin.jwt_signing_keys
+             ORDER BY created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(kid, purpose, algorithm, status, activate_after, retire_after, created_at)| {
+                    JwtKeyInfo {
+                        kid,
+                        purpose,
+                        algorithm,
+                        status,
+                        activate_after,
+                        retire_after,
+                        created_at,
+                    }
+                },
+            )
+            .collect())
+    }
+
+    /// 后台定期重载密钥环（感知其他实例的轮换）
+    pub async fn start_background_refresh(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.refresh_interval);
+        ticker.tick().await; // 启动时已加载
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.reload().await {
+                tracing::error!(error = ?e, "Failed to reload JWT keyring");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_grace_covers_token_lifetime() {
+        assert_eq!(
+            default_grace_period(KeyPurpose::Refresh).num_seconds(),
+            REFRESH_TOKEN_EXPIRY_SECS
+        );
+        assert!(default_grace_period(KeyPurpose::Access).num_seconds() > 0);
+    }
+
+    #[test]
+    fn test_kid_is_unique_and_prefixed() {
+        let a = new_kid(KeyPurpose::Access);
+        let b = new_kid(KeyPurpose::Access);
+        assert!(a.starts_with("access-"));
+        assert!(new_kid(KeyPurpose::Refresh).starts_with("refresh-"));
+        assert_ne!(a, b);
+    }
+
+    fn stored(kid: &str, status: &str, generated: &jwt::GeneratedSigningKey) -> StoredKey {
+        StoredKey {
+            kid: kid.to_string(),
+            purpose: KeyPurpose::Access,
+            algorithm: SigningAlgorithm::EdDSA,
+            status: status.to_string(),
+            public_jwk: generated.public_jwk.clone(),
+            private_der: Some(zeroize::Zeroizing::new(generated.private_der.to_vec())),
+        }
+    }
+
+    #[test]
+    fn test_staged_rotation_keeps_replicas_in_sync() {
+        let old = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let new = generate_signing_key(SigningAlgorithm::EdDSA).unwrap();
+        let claims = jwt::Claims::new(Uuid::new_v4(), Uuid::new_v4(), "viewer".into(), 60);
+
+        // 轮换后两个副本都已加载 pending 密钥：仍用旧密钥签名，JWKS 已发布新密钥
+        let replica_a = build_keyring(vec![
+            stored("access-old", "active", &old),
+            stored("access-new", "pending", &new),
+        ])
+        .unwrap();
+        let replica_b = build_keyring(vec![
+            stored("access-old", "active", &old),
+            stored("access-new", "pending", &new),
+        ])
+        .unwrap();
+        for keyring in [&replica_a, &replica_b] {
+            let token = keyring.sign(&claims, KeyPurpose::Access).unwrap().unwrap();
+            let header = jsonwebtoken::decode_header(&token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some("access-old"));
+            assert_eq!(keyring.jwks().keys.len(), 2);
+        }
+
+        // 副本 A 激活新密钥；B 尚未刷新，仍能验证 A 签发的 Token
+        let promoted_a = build_keyring(vec![
+            stored("access-old", "retiring", &old),
+            stored("access-new", "active", &new),
+        ])
+        .unwrap();
+        let token = promoted_a
+            .sign(&claims, KeyPurpose::Access)
+            .unwrap()
+            .unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("access-new"));
+        let verified = replica_b.verify(&token, "access-new").unwrap();
+        assert_eq!(verified.jti, claims.jti);
+
+        // A 激活后旧密钥在宽限期内仍可验证 B 签发的 Token
+        let token_b = replica_b
+            .sign(&claims, KeyPurpose::Access)
+            .unwrap()
+            .unwrap();
+        assert!(promoted_a.verify(&token_b, "access-old").is_ok());
+    }
+}
@@ -0,0 +1,894 @@
+//! 限价单执行引擎
+//!
+//! 企业级实现：后台轮询 pending 限价单，价格触发后构建 1inch swap 交易
+//! - 行情：`PriceService` USD 参考价做预筛，`OneInchService::get_quote` 确认可成交价格
+//! - 触发：构建未签名 swap 交易，订单进入 `ready_to_sign` 等待客户端签名
+//! - 成交：客户端签名广播后经 `POST /api/v1/limit-orders/:id/submitted` 回报交易哈希，
+//!   订单 `ready_to_sign -> filled` 并写入成交数量 / 价格 / 交易哈希
+//! - 过期：`expires_at` 到期的 pending / ready_to_sign 订单标记为 `expired`（已回报交易的除外）
+//! - 所有状态转换经 `LimitOrderStateMachine` 校验，并以当前状态做乐观锁更新
+//! - 扫描：按 `(created_at, id)` 键集游标分页，跨轮次推进，扫完一遍后回到队首，
+//!   避免队首长期未触发的订单饿死其后的订单
+//!
+//! # 价格约定
+//! ```text
+//! sell：卖出 from_token，limit_price = 每 1 from_token 至少换得的 to_token 数量
+//!       触发条件：成交价 >= limit_price
+//! buy ：买入 to_token，  limit_price = 每 1 to_token 最多支付的 from_token 数量
+//!       触发条件：成交价 <= limit_price
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tokio::time::interval;
+use uuid::Uuid;
+
+use crate::{
+    domain::token_amount::TokenAmount,
+    service::{
+        notification_service::{NotificationService, PublishNotificationInput},
+        oneinch_service::{OneInchService, SwapQuote, TransactionData},
+        order_state_machine::{LimitOrderStateMachine, LimitOrderStatus},
+        price_service::PriceService,
+        token_service::TokenService,
+    },
+    utils::chain_normalizer::{get_chain_id, is_evm_chain},
+};
+
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 30;
+const DEFAULT_SLIPPAGE_PERCENT: f64 = 0.5;
+const DEFAULT_TRIGGER_BAND_BPS: u32 = 200; // 参考价距限价 2% 以内才请求 1inch 报价
+const DEFAULT_BATCH_SIZE: i64 = 200;
+
+/// 限价单方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitOrderSide {
+    Buy,
+    Sell,
+}
+
+impl LimitOrderSide {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "buy" => Some(LimitOrderSide::Buy),
+            "sell" => Some(LimitOrderSide::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// 按订单方向换算成交价格
+///
+/// `from_amount` / `to_amount` 为人类可读数量（已按精度换算）
+pub fn execution_price(
+    side: LimitOrderSide,
+    from_amount: Decimal,
+    to_amount: Decimal,
+) -> Option<Decimal> {
+    match side {
+        LimitOrderSide::Sell => to_amount.checked_div(from_amount),
+        LimitOrderSide::Buy => from_amount.checked_div(to_amount),
+    }
+    .filter(|p| *p > Decimal::ZERO)
+}
+
+/// 判断成交价格是否已越过限价
+pub fn is_limit_crossed(side: LimitOrderSide, limit_price: Decimal, price: Decimal) -> bool {
+    match side {
+        LimitOrderSide::Sell => price >= limit_price,
+        LimitOrderSide::Buy => price <= limit_price,
+    }
+}
+
+/// 参考价是否已接近限价（band_bps 容差内），用于减少 1inch 报价请求
+pub fn is_near_limit(
+    side: LimitOrderSide,
+    limit_price: Decimal,
+    reference_price: Decimal,
+    band_bps: u32,
+) -> bool {
+    let band = Decimal::from(band_bps) / Decimal::from(10_000u32);
+    match side {
+        LimitOrderSide::Sell => reference_price >= limit_price * (Decimal::ONE - band),
+        LimitOrderSide::Buy => reference_price <= limit_price * (Decimal::ONE + band),
+    }
+}
+
+/// 行情与 swap 数据源
+#[async_trait]
+pub trait LimitOrderMarket: Send + Sync {
+    /// 代币 USD 参考价
+    async fn reference_price(&self, symbol: &str) -> Result<Decimal>;
+
+    /// 可成交报价（amount 为最小单位）
+    async fn quote(
+        &self,
+        chain_id: u64,
+        from_token: &str,
+        to_token: &str,
+        amount: &str,
+    ) -> Result<SwapQuote>;
+
+    /// 构建未签名 swap 交易
+    async fn swap_tx(
+        &self,
+        chain_id: u64,
+        from_token: &str,
+        to_token: &str,
+        amount: &str,
+        from_address: &str,
+        slippage: f64,
+    ) -> Result<TransactionData>;
+}
+
+/// PriceService + 1inch 数据源
+pub struct OneInchMarket {
+    price_service: Arc<PriceService>,
+    oneinch: OneInchService,
+}
+
+impl OneInchMarket {
+    pub fn new(price_service: Arc<PriceService>) -> Self {
+        Self {
+            price_service,
+            oneinch: OneInchService::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl LimitOrderMarket for OneInchMarket {
+    async fn reference_price(&self, symbol: &str) -> Result<Decimal> {
+        self.price_service.get_price_decimal(symbol).await
+    }
+
+    async fn quote(
+        &self,
+        chain_id: u64,
+        from_token: &str,
+        to_token: &str,
+        amount: &str,
+    ) -> Result<SwapQuote> {
+        self.oneinch
+            .get_quote(chain_id, from_token, to_token, amount)
+            .await
+    }
+
+    async fn swap_tx(
+        &self,
+        chain_id: u64,
+        from_token: &str,
+        to_token: &str,
+        amount: &str,
+        from_address: &str,
+        slippage: f64,
+    ) -> Result<TransactionData> {
+        self.oneinch
+            .get_swap_tx(
+                chain_id,
+                from_token,
+                to_token,
+                amount,
+                from_address,
+                slippage,
+            )
+            .await
+    }
+}
+
+/// 引擎配置
+#[derive(Debug, Clone)]
+pub struct LimitOrderEngineConfig {
+    pub poll_interval: Duration,
+    pub slippage_percent: f64,
+    pub trigger_band_bps: u32,
+    pub batch_size: i64,
+}
+
+impl Default for LimitOrderEngineConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+            slippage_percent: DEFAULT_SLIPPAGE_PERCENT,
+            trigger_band_bps: DEFAULT_TRIGGER_BAND_BPS,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl LimitOrderEngineConfig {
+    /// 从环境变量读取（LIMIT_ORDER_POLL_INTERVAL_SECS / LIMIT_ORDER_SLIPPAGE_PERCENT /
+    /// LIMIT_ORDER_TRIGGER_BAND_BPS），缺失或非法时使用默认值
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            poll_interval: std::env::var("LIMIT_ORDER_POLL_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default.poll_interval),
+            slippage_percent: std::env::var("LIMIT_ORDER_SLIPPAGE_PERCENT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0 && *v <= 50.0)
+                .unwrap_or(default.slippage_percent),
+            trigger_band_bps: std::env::var("LIMIT_ORDER_TRIGGER_BAND_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default.trigger_band_bps),
+            batch_size: default.batch_size,
+        }
+    }
+}
+
+/// 待处理限价单
+#[derive(Debug, Clone)]
+struct PendingLimitOrder {
+    id: Uuid,
+    user_id: Uuid,
+    order_type: String,
+    from_token: String,
+    to_token: String,
+    amount: Decimal,
+    limit_price: Decimal,
+    network: String,
+    wallet_id: Option<Uuid>,
+}
+
+/// 交易对已解析的代币信息
+struct ResolvedPair {
+    chain_id: u64,
+    from_address: String,
+    to_address: String,
+    from_decimals: u32,
+    to_decimals: u32,
+}
+
+/// 限价单执行引擎
+/// pending 订单扫描游标：上一批最后一条的 `(created_at, id)`，`None` 表示从队首开始
+type ScanCursor = Option<(DateTime<Utc>, Uuid)>;
+
+/// 计算下一轮扫描游标：取满一批则从本批末尾继续，不足一批说明已扫到队尾，回到队首
+fn next_scan_cursor(last: ScanCursor, fetched: usize, batch_size: i64) -> ScanCursor {
+    if (fetched as i64) < batch_size {
+        None
+    } else {
+        last
+    }
+}
+
+pub struct LimitOrderEngine {
+    pool: PgPool,
+    market: Arc<dyn LimitOrderMarket>,
+    config: LimitOrderEngineConfig,
+    cursor: Mutex<ScanCursor>,
+}
+
+impl LimitOrderEngine {
+    pub fn new(pool: PgPool, market: Arc<dyn LimitOrderMarket>) -> Self {
+        Self {
+            pool,
+            market,
+            config: LimitOrderEngineConfig::from_env(),
+            cursor: Mutex::new(None),
+        }
+    }
+
+    pub fn with_config(mut self, config: LimitOrderEngineConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 启动后台执行任务（持续运行）
+    pub async fn start_background_engine(self: Arc<Self>) {
+        let mut ticker = interval(self.config.poll_interval);
+
+        tracing::info!(
+            "Limit order engine started, interval={}s",
+            self.config.poll_interval.as_secs()
+        );
+
+        loop {
+            ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
+
+            match self.expire_due_orders().await {
+                Ok(expired) => {
+                    if expired > 0 {
+                        tracing::info!(count = expired, "Expired limit orders");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e, "Failed to expire limit orders");
+                }
+            }
+
+            match self.process_pending_orders().await {
+                Ok(triggered) => {
+                    if triggered > 0 {
+                        tracing::info!(count = triggered, "Limit orders ready to sign");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(error = ?e, "Failed to process pending limit orders");
+                }
+            }
+        }
+    }
+
+    /// 将到期的 pending / ready_to_sign 订单标记为 expired
+    ///
+    /// 已回报交易哈希的订单交易可能已上链，不做过期处理
+    pub async fn expire_due_orders(&self) -> Result<usize> {
+        let due = sqlx::query_as::<_, (Uuid, Uuid, String)>(
+            r#"
+            SELECT id, user_id, status
+            FROM public.limit_orders
+            WHERE status IN ('pending', 'ready_to_sign')
+              AND tx_hash IS NULL
+              AND expires_at IS NOT NULL
+              AND expires_at <= NOW()
+            ORDER BY expires_at
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query expired limit orders")?;
+
+        let mut expired = 0;
+        for (order_id, user_id, status) in due {
+            let current = LimitOrderStatus::from_str(&status)?;
+            if let Err(e) =
+                LimitOrderStateMachine::validate_transition(current, LimitOrderStatus::Expired)
+            {
+                tracing::warn!(order_id = %order_id, error = %e, "Skipping limit order expiry");
+                continue;
+            }
+
+            let updated = sqlx::query(
+                r#"
+                UPDATE public.limit_orders
+                SET status = 'expired', swap_tx = NULL, status_reason = 'expired', updated_at = NOW()
+                WHERE id = $1 AND status = $2 AND tx_hash IS NULL
+                "#,
+            )
+            .bind(order_id)
+            .bind(current.as_str())
+            .execute(&self.pool)
+            .await
+            .context("Failed to expire limit order")?;
+
+            if updated.rows_affected() > 0 {
+                expired += 1;
+                self.notify(
+                    user_id,
+                    "Limit order expired",
+                    format!("Limit order {} expired without being filled", order_id),
+                )
+                .await;
+            }
+        }
+
+        Ok(expired)
+    }
+
+    /// 检查一批 pending 订单，价格触发的订单进入 ready_to_sign
+    ///
+    /// 每轮从游标处取 `batch_size` 条，多轮覆盖全部 pending 订单
+    pub async fn process_pending_orders(&self) -> Result<usize> {
+        let cursor = *self.cursor.lock().unwrap_or_else(|e| e.into_inner());
+        let rows = sqlx::query_as::<
+            _,
+            (
+                Uuid,
+                Uuid,
+                String,
+                String,
+                String,
+                Decimal,
+                Decimal,
+                String,
+                Option<Uuid>,
+                DateTime<Utc>,
+            ),
+        >(
+            r#"
+            SELECT id, user_id, order_type, from_token, to_token, amount, limit_price, network, wallet_id,
+                   created_at
+            FROM public.limit_orders
+            WHERE status = 'pending'
+              AND (expires_at IS NULL OR expires_at > NOW())
+              AND ($2::timestamptz IS NULL OR (created_at, id) > ($2, $3))
+            ORDER BY created_at, id
+            LIMIT $1
+            "#,
+        )
+        .bind(self.config.batch_size)
+        .bind(cursor.map(|(created_at, _)| created_at))
+        .bind(cursor.map(|(_, id)| id))
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query pending limit orders")?;
+
+        let last = rows.last().map(|r| (r.9, r.0));
+        *self.cursor.lock().unwrap_or_else(|e| e.into_inner()) =
+            next_scan_cursor(last, rows.len(), self.config.batch_size);
+
+        // 按 (network, from_token, to_token) 分组，每个交易对只解析一次代币与参考价
+        let mut pairs: BTreeMap<(String, String, String), Vec<PendingLimitOrder>> = BTreeMap::new();
+        for (
+            id,
+            user_id,
+            order_type,
+            from_token,
+            to_token,
+            amount,
+            limit_price,
+            network,
+            wallet_id,
+            _created_at,
+        ) in rows
+        {
+            pairs
+                .entry((network.clone(), from_token.clone(), to_token.clone()))
+                .or_default()
+                .push(PendingLimitOrder {
+                    id,
+                    user_id,
+                    order_type,
+                    from_token,
+                    to_token,
+                    amount,
+                    limit_price,
+                    network,
+                    wallet_id,
+                });
+        }
+
+        let mut triggered = 0;
+        for ((network, from_token, to_token), orders) in pairs {
+            match self
+                .process_pair(&network, &from_token, &to_token, &orders)
+                .await
+            {
+                Ok(count) => triggered += count,
+                Err(e) => {
+                    tracing::warn!(
+                        network = %network,
+                        from_token = %from_token,
+                        to_token = %to_token,
+                        error = ?e,
+                        "Failed to process limit order pair"
+                    );
+                }
+            }
+        }
+
+        Ok(triggered)
+    }
+
+    async fn process_pair(
+        &self,
+        network: &str,
+        from_token: &str,
+        to_token: &str,
+        orders: &[PendingLimitOrder],
+    ) -> Result<usize> {
+        let pair = match self.resolve_pair(network, from_token, to_token).await? {
+            Ok(pair) => pair,
+            Err(reason) => {
+                for order in orders {
+                    self.mark_failed(order, &reason).await?;
+                }
+                return Ok(0);
+            }
+        };
+
+        // 参考价（to_token / from_token）仅用于预筛；获取失败时对所有订单请求报价
+        let reference_rate = match (
+            self.market.reference_price(from_token).await,
+            self.market.reference_price(to_token).await,
+        ) {
+            (Ok(from_usd), Ok(to_usd)) => from_usd.checked_div(to_usd),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::warn!(error = %e, "Reference price unavailable, quoting all orders");
+                None
+            }
+        };
+
+        let mut triggered = 0;
+        for order in orders {
+            let Some(side) = LimitOrderSide::parse(&order.order_type) else {
+                self.mark_failed(order, &format!("unknown order type {}", order.order_type))
+                    .await?;
+                continue;
+            };
+
+            if let Some(rate) = reference_rate {
+                let reference_price = match side {
+                    LimitOrderSide::Sell => Some(rate),
+                    LimitOrderSide::Buy => Decimal::ONE.checked_div(rate),
+                };
+                if let Some(price) = reference_price {
+                    if !is_near_limit(side, order.limit_price, price, self.config.trigger_band_bps)
+                    {
+                        continue;
+                    }
+                }
+            }
+
+            match self.evaluate_order(order, side, &pair).await {
+                Ok(true) => triggered += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    // 报价/构建失败保持 pending，下一轮重试
+                    tracing::warn!(order_id = %order.id, error = ?e, "Limit order evaluation failed");
+                }
+            }
+        }
+
+        Ok(triggered)
+    }
+
+    /// 解析链与代币；外层错误为基础设施错误，内层错误为订单不可执行原因
+    async fn resolve_pair(
+        &self,
+        network: &str,
+        from_token: &str,
+        to_token: &str,
+    ) -> Result<std::result::Result<ResolvedPair, String>> {
+        if !is_evm_chain(network) {
+            return Ok(Err(format!(
+                "network {} is not supported by 1inch limit orders",
+                network
+            )));
+        }
+        let chain_id = match get_chain_id(network) {
+            Ok(id) => id as u64,
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+
+        let token_service = TokenService::new(self.pool.clone());
+        let from = token_service.get_token(from_token, chain_id).await?;
+        let to = token_service.get_token(to_token, chain_id).await?;
+
+        match (from, to) {
+            (Some(from), Some(to)) => Ok(Ok(ResolvedPair {
+                chain_id,
+                from_address: from.address,
+                to_address: to.address,
+                from_decimals: from.decimals as u32,
+                to_decimals: to.decimals as u32,
+            })),
+            (None, _) => Ok(Err(format!(
+                "token {} not supported on {}",
+                from_token, network
+            ))),
+            (_, None) => Ok(Err(format!(
+                "token {} not supported on {}",
+                to_token, network
+            ))),
+        }
+    }
+
+    /// 请求 1inch 报价确认价格；触发后构建 swap 交易并进入 ready_to_sign
+    async fn evaluate_order(
+        &self,
+        order: &PendingLimitOrder,
+        side: LimitOrderSide,
+        pair: &ResolvedPair,
+    ) -> Result<bool> {
+        let amount_units =
+            match TokenAmount::parse(&order.amount.normalize().to_string(), pair.from_decimals)
+                .and_then(TokenAmount::ensure_positive)
+            {
+                Ok(units) => units.base_units().to_string(),
+                Err(e) => {
+                    self.mark_failed(order, &format!("invalid amount: {}", e))
+                        .await?;
+                    return Ok(false);
+                }
+            };
+
+        let quote = self
+            .market
+            .quote(
+                pair.chain_id,
+                &pair.from_address,
+                &pair.to_address,
+                &amount_units,
+            )
+            .await?;
+        let to_amount = TokenAmount::parse_base_units(&quote.to_amount, pair.to_decimals)
+            .and_then(|a| a.to_decimal())
+            .map_err(|e| anyhow::anyhow!("Invalid 1inch quote amount: {}", e))?;
+
+        let Some(price) = execution_price(side, order.amount, to_amount) else {
+            return Ok(false);
+        };
+        if !is_limit_crossed(side, order.limit_price, price) {
+            return Ok(false);
+        }
+
+        let Some(wallet_address) = self.wallet_address(order).await? else {
+            self.mark_failed(order, "no wallet bound to limit order")
+                .await?;
+            return Ok(false);
+        };
+
+        let tx = self
+            .market
+            .swap_tx(
+                pair.chain_id,
+                &pair.from_address,
+                &pair.to_address,
+                &amount_units,
+                &wallet_address,
+                self.config.slippage_percent,
+            )
+            .await?;
+
+        self.mark_ready_to_sign(order, price, to_amount, &tx).await
+    }
+
+    async fn wallet_address(&self, order: &PendingLimitOrder) -> Result<Option<String>> {
+        let Some(wallet_id) = order.wallet_id else {
+            return Ok(None);
+        };
+        sqlx::query_scalar::<_, String>(
+            "SELECT address FROM wallets WHERE id = $1 AND user_id = $2",
+        )
+        .bind(wallet_id)
+        .bind(order.user_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load limit order wallet")
+    }
+
+    async fn mark_ready_to_sign(
+        &self,
+        order: &PendingLimitOrder,
+        price: Decimal,
+        to_amount: Decimal,
+        tx: &TransactionData,
+    ) -> Result<bool> {
+        LimitOrderStateMachine::validate_transition(
+            LimitOrderStatus::Pending,
+            LimitOrderStatus::ReadyToSign,
+        )?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE public.limit_orders
+            SET status = 'ready_to_sign', swap_tx = $2, triggered_price = $3, quoted_to_amount = $4,
+                ready_at = NOW(), status_reason = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(order.id)
+        .bind(serde_json::to_value(tx)?)
+        .bind(price)
+        .bind(to_amount)
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark limit order ready to sign")?;
+
+        if updated.rows_affected() == 0 {
+            // 期间已被取消或过期
+            return Ok(false);
+        }
+
+        tracing::info!(
+            order_id = %order.id,
+            network = %order.network,
+            price = %price,
+            limit_price = %order.limit_price,
+            "Limit order triggered, swap transaction ready to sign"
+        );
+        self.notify(
+            order.user_id,
+            "Limit order triggered",
+            format!(
+                "Limit order {} {} -> {} reached price {}; transaction is ready to sign",
+                order.id, order.from_token, order.to_token, price
+            ),
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    async fn mark_failed(&self, order: &PendingLimitOrder, reason: &str) -> Result<()> {
+        LimitOrderStateMachine::validate_transition(
+            LimitOrderStatus::Pending,
+            LimitOrderStatus::Failed,
+        )?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE public.limit_orders
+            SET status = 'failed', status_reason = $2, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(order.id)
+        .bind(reason)
+        .execute(&self.pool)
+        .await
+        .context("Failed to mark limit order failed")?;
+
+        if updated.rows_affected() > 0 {
+            tracing::warn!(order_id = %order.id, reason = %reason, "Limit order failed");
+            self.notify(
+                order.user_id,
+                "Limit order failed",
+                format!("Limit order {} cannot be executed: {}", order.id, reason),
+            )
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// 站内通知失败不影响订单状态
+    async fn notify(&self, user_id: Uuid, title: &str, body: String) {
+        let result = NotificationService::new(self.pool.clone())
+            .publish(PublishNotificationInput {
+                title: title.to_string(),
+                body,
+                category: "limit_order".to_string(),
+                severity: Some("info".to_string()),
+                scope: "user".to_string(),
+                creator_role: "system".to_string(),
+                user_ids: Some(vec![user_id]),
+            })
+            .await;
+
+        if let Err(e) = result {
+            tracing::warn!(user_id = %user_id, error = %e, "Failed to publish limit order notification");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_scan_cursor_advances_and_wraps() {
+        let ts = Utc::now();
+        let id = Uuid::new_v4();
+
+        // 取满一批：从本批末尾继续
+        assert_eq!(next_scan_cursor(Some((ts, id)), 200, 200), Some((ts, id)));
+        // 不足一批：已到队尾，下一轮回到队首
+        assert_eq!(next_scan_cursor(Some((ts, id)), 17, 200), None);
+        assert_eq!(next_scan_cursor(None, 0, 200), None);
+    }
+
+    #[test]
+    fn test_side_parse() {
+        assert_eq!(LimitOrderSide::parse("BUY"), Some(LimitOrderSide::Buy));
+        assert_eq!(LimitOrderSide::parse("sell"), Some(LimitOrderSide::Sell));
+        assert_eq!(LimitOrderSide::parse("stop"), None);
+    }
+
+    #[test]
+    fn test_execution_price() {
+        // 卖 2 ETH 得 7000 USDC：3500 USDC/ETH
+        assert_eq!(
+            execution_price(LimitOrderSide::Sell, dec("2"), dec("7000")),
+            Some(dec("3500"))
+        );
+        // 用 7000 USDC 买 2 ETH：3500 USDC/ETH
+        assert_eq!(
+            execution_price(LimitOrderSide::Buy, dec("7000"), dec("2")),
+            Some(dec("3500"))
+        );
+        assert_eq!(
+            execution_price(LimitOrderSide::Sell, dec("2"), Decimal::ZERO),
+            None
+        );
+        assert_eq!(
+            execution_price(LimitOrderSide::Buy, dec("7000"), Decimal::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_limit_crossed() {
+        // 卖单：价格涨到限价及以上触发
+        assert!(is_limit_crossed(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3500")
+        ));
+        assert!(is_limit_crossed(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3600")
+        ));
+        assert!(!is_limit_crossed(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3499.99")
+        ));
+
+        // 买单：价格跌到限价及以下触发
+        assert!(is_limit_crossed(
+            LimitOrderSide::Buy,
+            dec("3000"),
+            dec("2999")
+        ));
+        assert!(!is_limit_crossed(
+            LimitOrderSide::Buy,
+            dec("3000"),
+            dec("3000.01")
+        ));
+    }
+
+    #[test]
+    fn test_near_limit_band() {
+        // 2% 容差：卖单限价 3500，参考价 >= 3430 时请求报价
+        assert!(is_near_limit(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3430"),
+            200
+        ));
+        assert!(!is_near_limit(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3429"),
+            200
+        ));
+
+        // 买单限价 3000，参考价 <= 3060 时请求报价
+        assert!(is_near_limit(
+            LimitOrderSide::Buy,
+            dec("3000"),
+            dec("3060"),
+            200
+        ));
+        assert!(!is_near_limit(
+            LimitOrderSide::Buy,
+            dec("3000"),
+            dec("3061"),
+            200
+        ));
+
+        // 零容差退化为严格触发
+        assert!(!is_near_limit(
+            LimitOrderSide::Sell,
+            dec("3500"),
+            dec("3499"),
+            0
+        ));
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = LimitOrderEngineConfig::default();
+        assert_eq!(config.poll_interval, Duration::from_secs(30));
+        assert_eq!(config.trigger_band_bps, 200);
+        assert!(config.slippage_percent > 0.0);
+    }
+}
@@ -1,62 +1,158 @@
 //! 多节点验证服务（G项和P项修复）
 //! 企业级实现：防止恶意RPC节点欺骗
+//!
+//! - 端点：`RpcSelector::select_distinct` 选出 N 个不同服务商/主机的端点
+//! - 查询：并行请求所有端点，单节点超时/失败不阻塞其他节点
+//! - 共识：M-of-N，相同结果达到 M 个节点才采信
+//! - 分歧：节点返回不一致结果时记录指标并输出告警日志
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, hash::Hash, sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::types::U256;
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 
+use crate::infrastructure::rpc_selector::RpcSelector;
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 多节点端点来源
+#[async_trait]
+pub trait EndpointProvider: Send + Sync {
+    /// 返回至多 `count` 个不同的端点 URL
+    async fn distinct_endpoints(&self, chain: &str, count: usize) -> Vec<String>;
+}
+
+#[async_trait]
+impl EndpointProvider for RpcSelector {
+    async fn distinct_endpoints(&self, chain: &str, count: usize) -> Vec<String> {
+        self.select_distinct(chain, count)
+            .await
+            .into_iter()
+            .map(|e| e.url)
+            .collect()
+    }
+}
+
+/// M-of-N 共识配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuorumConfig {
+    /// 查询的节点数 N
+    pub nodes: usize,
+    /// 达成共识所需的一致节点数 M
+    pub min_agreement: usize,
+    /// 单节点请求超时
+    pub request_timeout: Duration,
+}
+
+impl QuorumConfig {
+    pub fn new(min_agreement: usize, nodes: usize) -> Result<Self> {
+        if min_agreement == 0 || min_agreement > nodes {
+            return Err(anyhow!(
+                "Invalid quorum: {}-of-{} (require 1 <= M <= N)",
+                min_agreement,
+                nodes
+            ));
+        }
+        Ok(Self {
+            nodes,
+            min_agreement,
+            request_timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// 从环境变量读取（`MULTI_NODE_QUORUM_M` / `MULTI_NODE_QUORUM_N`），缺省 2-of-3
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: usize| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        Self::new(
+            read("MULTI_NODE_QUORUM_M", 2),
+            read("MULTI_NODE_QUORUM_N", 3),
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Invalid multi-node quorum config, using 2-of-3");
+            Self::default()
+        })
+    }
+}
+
+impl Default for QuorumConfig {
+    fn default() -> Self {
+        Self {
+            nodes: 3,
+            min_agreement: 2,
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
 /// 多节点验证器
 pub struct MultiNodeVerifier {
-    rpc_selector: Arc<crate::infrastructure::rpc_selector::RpcSelector>,
-    min_consensus: usize, // 最少需要多少节点确认
+    endpoint_provider: Arc<dyn EndpointProvider>,
+    quorum: QuorumConfig,
+    http_client: reqwest::Client,
 }
 
 impl MultiNodeVerifier {
-    pub fn new(rpc_selector: Arc<crate::infrastructure::rpc_selector::RpcSelector>) -> Self {
-        Self {
-            rpc_selector,
-            min_consensus: 2, // 至少2个节点确认
-        }
+    pub fn new(rpc_selector: Arc<RpcSelector>) -> Self {
+        Self::with_endpoint_provider(rpc_selector, QuorumConfig::from_env())
     }
 
-    /// 多节点验证余额
-    pub async fn verify_balance(&self, chain: &str, address: &str) -> Result<String> {
-        // 查询多个节点
-        let endpoints = self.get_multiple_endpoints(chain, 3).await?;
-        let mut balances: HashMap<String, usize> = HashMap::new();
+    pub fn with_endpoint_provider(
+        endpoint_provider: Arc<dyn EndpointProvider>,
+        quorum: QuorumConfig,
+    ) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(quorum.request_timeout)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
 
-        for endpoint in &endpoints {
-            if let Ok(balance) = self.query_balance(endpoint, address, chain).await {
-                *balances.entry(balance).or_insert(0) += 1;
-            }
+        Self {
+            endpoint_provider,
+            quorum,
+            http_client,
         }
+    }
 
-        // 查找共识
-        self.find_consensus(balances, endpoints.len())
+    /// 多节点验证余额（wei，十进制字符串）
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
+    pub async fn verify_balance(&self, chain: &str, address: &str) -> Result<String> {
+        let endpoints = self.get_multiple_endpoints(chain).await?;
+        let results = join_all(
+            endpoints
+                .iter()
+                .map(|endpoint| self.query_balance(endpoint, address, chain)),
+        )
+        .await;
+
+        self.find_consensus("balance", chain, &endpoints, results)
     }
 
     /// 多节点验证交易状态
+    #[tracing::instrument(skip_all, fields(chain = %chain, tx_hash = %tx_hash))]
     pub async fn verify_transaction_status(
         &self,
         chain: &str,
         tx_hash: &str,
     ) -> Result<TransactionStatus> {
-        let endpoints = self.get_multiple_endpoints(chain, 3).await?;
-        let mut statuses: HashMap<String, usize> = HashMap::new();
-
-        for endpoint in &endpoints {
-            if let Ok(status) = self.query_tx_status(endpoint, tx_hash, chain).await {
-                let status_str = serde_json::to_string(&status)?;
-                *statuses.entry(status_str).or_insert(0) += 1;
-            }
-        }
-
-        let consensus_str = self.find_consensus(statuses, endpoints.len())?;
-        serde_json::from_str(&consensus_str).map_err(|e| anyhow!("Failed to parse status: {}", e))
+        let endpoints = self.get_multiple_endpoints(chain).await?;
+        let results = join_all(
+            endpoints
+                .iter()
+                .map(|endpoint| self.query_tx_status(endpoint, tx_hash, chain)),
+        )
+        .await;
+
+        self.find_consensus("tx_status", chain, &endpoints, results)
     }
 
     /// 多节点验证跨链事件
+    #[tracing::instrument(skip_all, fields(chain = %chain, block_number = block_number))]
     pub async fn verify_bridge_event(
         &self,
         chain: &str,
@@ -67,107 +163,114 @@ impl MultiNodeVerifier {
         // 验证合约地址在白名单
         self.verify_bridge_contract(contract_address)?;
 
-        let endpoints = self.get_multiple_endpoints(chain, 3).await?;
-        let mut events_list: Vec<Vec<BridgeEvent>> = Vec::new();
-
-        for endpoint in &endpoints {
-            if let Ok(events) = self
-                .query_events(
-                    endpoint,
-                    contract_address,
-                    event_signature,
-                    block_number,
-                    chain,
-                )
-                .await
-            {
-                events_list.push(events);
-            }
-        }
-
-        // 查找共识事件
-        self.find_event_consensus(events_list)
+        let endpoints = self.get_multiple_endpoints(chain).await?;
+        let results = join_all(endpoints.iter().map(|endpoint| {
+            self.query_events(
+                endpoint,
+                contract_address,
+                event_signature,
+                block_number,
+                chain,
+            )
+        }))
+        .await;
+
+        self.find_consensus("bridge_event", chain, &endpoints, results)
     }
 
-    /// 获取多个RPC端点
-    async fn get_multiple_endpoints(&self, chain: &str, count: usize) -> Result<Vec<RpcEndpoint>> {
-        let mut endpoints = Vec::new();
-
-        for _ in 0..count {
-            if let Some(endpoint) = self.rpc_selector.select(chain).await {
-                endpoints.push(RpcEndpoint {
-                    url: endpoint.url,
-                    priority: endpoint.priority as i32,
-                });
-            }
-        }
+    /// 获取 N 个不同的RPC端点
+    async fn get_multiple_endpoints(&self, chain: &str) -> Result<Vec<String>> {
+        let endpoints = self
+            .endpoint_provider
+            .distinct_endpoints(chain, self.quorum.nodes)
+            .await;
 
-        if endpoints.len() < self.min_consensus {
+        if endpoints.len() < self.quorum.min_agreement {
+            crate::metrics::inc_rpc_quorum_failure();
             return Err(anyhow!(
-                "Insufficient RPC endpoints: got {}, need {}",
+                "Insufficient distinct RPC endpoints for {}: got {}, need {}",
+                chain,
                 endpoints.len(),
-                self.min_consensus
+                self.quorum.min_agreement
             ));
         }
 
         Ok(endpoints)
     }
 
-    /// 查询余额
-    async fn query_balance(
+    async fn rpc_call(
         &self,
-        endpoint: &RpcEndpoint,
-        address: &str,
-        chain: &str,
-    ) -> Result<String> {
-        let client = reqwest::Client::new();
-
-        let payload = if crate::utils::chain_normalizer::is_evm_chain(chain) {
-            serde_json::json!({
-                "jsonrpc": "2.0",
-                "method": "eth_getBalance",
-                "params": [address, "latest"],
-                "id": 1
-            })
-        } else {
-            return Err(anyhow!("Unsupported chain for balance query"));
-        };
-
-        let response = client.post(&endpoint.url).json(&payload).send().await?;
-
-        let json: serde_json::Value = response.json().await?;
-
+        endpoint: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+
+        let json: serde_json::Value = self
+            .http_client
+            .post(endpoint)
+            .json(&payload)
+            .with_trace_context()
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = json.get("error") {
+            return Err(anyhow!("RPC error from {}: {}", endpoint, error));
+        }
         json.get("result")
-            .and_then(|r| r.as_str())
-            .map(|s| s.to_string())
+            .cloned()
             .ok_or_else(|| anyhow!("No result in response"))
     }
 
+    /// 查询余额
+    async fn query_balance(&self, endpoint: &str, address: &str, chain: &str) -> Result<String> {
+        if !crate::utils::chain_normalizer::is_evm_chain(chain) {
+            return Err(anyhow!("Unsupported chain for balance query"));
+        }
+
+        let result = self
+            .rpc_call(
+                endpoint,
+                "eth_getBalance",
+                serde_json::json!([address, "latest"]),
+            )
+            .await?;
+
+        // 规范化为十进制，避免 "0x0" / "0x00" 等等价表示被视为分歧
+        let hex_balance = result
+            .as_str()
+            .ok_or_else(|| anyhow!("Invalid balance result"))?;
+        let balance = U256::from_str_radix(hex_balance.trim_start_matches("0x"), 16)
+            .map_err(|_| anyhow!("Invalid balance hex: {}", hex_balance))?;
+        Ok(balance.to_string())
+    }
+
     /// 查询交易状态
     async fn query_tx_status(
         &self,
-        endpoint: &RpcEndpoint,
+        endpoint: &str,
         tx_hash: &str,
         chain: &str,
     ) -> Result<TransactionStatus> {
-        let client = reqwest::Client::new();
-
-        let payload = if crate::utils::chain_normalizer::is_evm_chain(chain) {
-            serde_json::json!({
-                "jsonrpc": "2.0",
-                "method": "eth_getTransactionReceipt",
-                "params": [tx_hash],
-                "id": 1
-            })
-        } else {
+        if !crate::utils::chain_normalizer::is_evm_chain(chain) {
             return Err(anyhow!("Unsupported chain"));
-        };
-
-        let response = client.post(&endpoint.url).json(&payload).send().await?;
-
-        let json: serde_json::Value = response.json().await?;
+        }
 
-        let receipt = json.get("result").ok_or_else(|| anyhow!("No result"))?;
+        let receipt = self
+            .rpc_call(
+                endpoint,
+                "eth_getTransactionReceipt",
+                serde_json::json!([tx_hash]),
+            )
+            .await?;
 
         Ok(TransactionStatus {
             block_number: receipt
@@ -184,43 +287,37 @@ impl MultiNodeVerifier {
     /// 查询事件
     async fn query_events(
         &self,
-        endpoint: &RpcEndpoint,
+        endpoint: &str,
         contract: &str,
         event_sig: &str,
         block: u64,
         chain: &str,
     ) -> Result<Vec<BridgeEvent>> {
-        let client = reqwest::Client::new();
+        if !crate::utils::chain_normalizer::is_evm_chain(chain) {
+            return Err(anyhow!("Unsupported chain"));
+        }
 
-        let payload = if crate::utils::chain_normalizer::is_evm_chain(chain) {
-            serde_json::json!({
-                "jsonrpc": "2.0",
-                "method": "eth_getLogs",
-                "params": [{
+        let result = self
+            .rpc_call(
+                endpoint,
+                "eth_getLogs",
+                serde_json::json!([{
                     "address": contract,
                     "fromBlock": format!("0x{:x}", block),
                     "toBlock": format!("0x{:x}", block),
                     "topics": [event_sig]
-                }],
-                "id": 1
-            })
-        } else {
-            return Err(anyhow!("Unsupported chain"));
-        };
+                }]),
+            )
+            .await?;
 
-        let response = client.post(&endpoint.url).json(&payload).send().await?;
+        let logs = result.as_array().ok_or_else(|| anyhow!("No result"))?;
 
-        let json: serde_json::Value = response.json().await?;
-
-        let logs = json
-            .get("result")
-            .and_then(|r| r.as_array())
-            .ok_or_else(|| anyhow!("No result"))?;
-
-        let events: Vec<BridgeEvent> = logs
+        let mut events: Vec<BridgeEvent> = logs
             .iter()
             .filter_map(|log| self.parse_bridge_event(log).ok())
             .collect();
+        // 排序后比较，避免节点返回顺序不同被视为分歧
+        events.sort();
 
         Ok(events)
     }
@@ -245,50 +342,56 @@ impl MultiNodeVerifier {
         })
     }
 
-    /// 查找共识值
-    fn find_consensus<T: Ord + Clone>(
+    /// 查找 M-of-N 共识值，分歧时记录指标和告警
+    fn find_consensus<T: Eq + Hash + Clone + std::fmt::Debug>(
         &self,
-        results: HashMap<T, usize>,
-        total_nodes: usize,
+        kind: &str,
+        chain: &str,
+        endpoints: &[String],
+        results: Vec<Result<T>>,
     ) -> Result<T> {
-        // 查找获得最多确认的值
-        let max_entry = results
-            .iter()
-            .max_by_key(|(_, count)| *count)
-            .ok_or_else(|| anyhow!("No results"))?;
-
-        let (value, count) = max_entry;
-
-        // 验证是否达到共识
-        if *count < self.min_consensus {
-            return Err(anyhow!(
-                "No consensus: {} out of {} nodes agree",
-                count,
-                total_nodes
-            ));
+        let mut tally: HashMap<T, Vec<&str>> = HashMap::new();
+        for (endpoint, result) in endpoints.iter().zip(results) {
+            match result {
+                Ok(value) => tally.entry(value).or_default().push(endpoint),
+                Err(e) => {
+                    tracing::warn!(%chain, kind, endpoint = %endpoint, error = %e, "multi_node_query_failed")
+                }
+            }
         }
 
-        // 要求至少2/3节点同意
-        if total_nodes >= 3 && *count < (total_nodes * 2 / 3) {
-            return Err(anyhow!(
-                "Insufficient consensus: {}/{} nodes (need 2/3)",
-                count,
-                total_nodes
-            ));
+        if tally.len() > 1 {
+            crate::metrics::inc_rpc_consensus_disagreement();
+            tracing::error!(
+                alert = "rpc_consensus_disagreement",
+                %chain,
+                kind,
+                results = ?tally,
+                "RPC nodes returned conflicting results"
+            );
         }
 
-        Ok(value.clone())
-    }
+        let (value, agreeing) = tally
+            .into_iter()
+            .max_by_key(|(_, nodes)| nodes.len())
+            .ok_or_else(|| {
+                crate::metrics::inc_rpc_quorum_failure();
+                anyhow!("No results from any of {} nodes", endpoints.len())
+            })?;
 
-    /// 查找事件共识
-    fn find_event_consensus(&self, events_list: Vec<Vec<BridgeEvent>>) -> Result<Vec<BridgeEvent>> {
-        if events_list.is_empty() {
-            return Err(anyhow!("No event data from any node"));
+        if agreeing.len() < self.quorum.min_agreement {
+            crate::metrics::inc_rpc_quorum_failure();
+            return Err(anyhow!(
+                "No consensus for {} on {}: {} of {} nodes agree (need {})",
+                kind,
+                chain,
+                agreeing.len(),
+                endpoints.len(),
+                self.quorum.min_agreement
+            ));
         }
 
-        // 简化实现：返回第一个节点的结果
-        // 实际应该比较所有节点的结果
-        Ok(events_list.into_iter().next().unwrap())
+        Ok(value)
     }
 
     /// 验证跨链桥合约（白名单）
@@ -311,20 +414,13 @@ impl MultiNodeVerifier {
 // 辅助结构
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
 
-#[derive(Debug, Clone)]
-struct RpcEndpoint {
-    url: String,
-    #[allow(dead_code)]
-    priority: i32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TransactionStatus {
     pub block_number: Option<u64>,
     pub status: Option<bool>, // true=success, false=failed
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BridgeEvent {
     pub from: String,
     pub to: String,
@@ -333,35 +429,167 @@ pub struct BridgeEvent {
 
 #[cfg(test)]
 mod tests {
+    use axum::{routing::post, Json, Router};
+
     use super::*;
 
-    #[test]
-    fn test_consensus_logic() {
-        let mut results: HashMap<String, usize> = HashMap::new();
-        results.insert("100".to_string(), 2);
-        results.insert("99".to_string(), 1);
+    const BRIDGE: &str = "0x1111111111111111111111111111111111111111";
+
+    /// 启动返回固定结果的 JSON-RPC 服务，返回其 URL
+    async fn mock_rpc(
+        balance: &'static str,
+        tx_status: &'static str,
+        from: &'static str,
+    ) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<serde_json::Value>| async move {
+                let result = match req["method"].as_str().unwrap_or_default() {
+                    "eth_getBalance" => serde_json::json!(balance),
+                    "eth_getTransactionReceipt" => {
+                        serde_json::json!({ "blockNumber": "0x10", "status": tx_status })
+                    }
+                    "eth_getLogs" => serde_json::json!([
+                        { "topics": ["0xsig", from, "0xto"] },
+                        { "topics": ["0xsig", "0xaaa", "0xbbb"] },
+                    ]),
+                    _ => serde_json::Value::Null,
+                };
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    struct StaticEndpoints(Vec<String>);
+
+    #[async_trait]
+    impl EndpointProvider for StaticEndpoints {
+        async fn distinct_endpoints(&self, _chain: &str, count: usize) -> Vec<String> {
+            self.0.iter().take(count).cloned().collect()
+        }
+    }
+
+    fn verifier(endpoints: Vec<String>, m: usize, n: usize) -> MultiNodeVerifier {
+        MultiNodeVerifier::with_endpoint_provider(
+            Arc::new(StaticEndpoints(endpoints)),
+            QuorumConfig::new(m, n).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_balance_consensus_normalizes_hex() {
+        let endpoints = vec![
+            mock_rpc("0x64", "0x1", "0xf").await,
+            mock_rpc("0x0064", "0x1", "0xf").await,
+            mock_rpc("0x63", "0x1", "0xf").await,
+        ];
+
+        let balance = verifier(endpoints, 2, 3)
+            .verify_balance("ethereum", "0xabc")
+            .await
+            .unwrap();
+        assert_eq!(balance, "100");
+    }
+
+    #[tokio::test]
+    async fn test_balance_without_quorum_fails() {
+        let endpoints = vec![
+            mock_rpc("0x1", "0x1", "0xf").await,
+            mock_rpc("0x2", "0x1", "0xf").await,
+            mock_rpc("0x3", "0x1", "0xf").await,
+        ];
+
+        let err = verifier(endpoints, 2, 3)
+            .verify_balance("ethereum", "0xabc")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No consensus"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_node_does_not_block_quorum() {
+        let endpoints = vec![
+            mock_rpc("0x5", "0x0", "0xf").await,
+            "http://127.0.0.1:1".to_string(), // 拒绝连接
+            mock_rpc("0x5", "0x0", "0xf").await,
+        ];
+
+        let status = verifier(endpoints, 2, 3)
+            .verify_transaction_status("ethereum", "0xhash")
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            TransactionStatus {
+                block_number: Some(16),
+                status: Some(false),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_quorum_requires_all_nodes() {
+        let endpoints = vec![
+            mock_rpc("0x5", "0x1", "0xf").await,
+            mock_rpc("0x5", "0x0", "0xf").await,
+        ];
+
+        assert!(verifier(endpoints, 2, 2)
+            .verify_transaction_status("ethereum", "0xhash")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bridge_event_consensus() {
+        let endpoints = vec![
+            mock_rpc("0x0", "0x1", "0xf").await,
+            mock_rpc("0x0", "0x1", "0xe").await, // 伪造事件
+            mock_rpc("0x0", "0x1", "0xf").await,
+        ];
+        let v = verifier(endpoints, 2, 3);
+
+        let events = v
+            .verify_bridge_event("ethereum", BRIDGE, "0xsig", 1)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().any(|e| e.from == "0xf"));
+
+        assert!(v
+            .verify_bridge_event(
+                "ethereum",
+                "0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef",
+                "0xsig",
+                1
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_endpoints() {
+        let endpoints = vec![mock_rpc("0x1", "0x1", "0xf").await];
 
-        // 模拟验证器
-        // 应该找到"100"（2个节点确认）
+        let err = verifier(endpoints, 2, 3)
+            .verify_balance("ethereum", "0xabc")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Insufficient"), "{}", err);
     }
 
     #[test]
-    #[ignore] // 需要数据库连接，跳过单元测试
-    fn test_bridge_whitelist() {
-        // 注意：此测试需要实际的数据库 pool
-        // 建议在集成测试中进行，或使用 mock
-        //
-        // let pool = PgPool::connect("...").await.unwrap();
-        // let verifier = MultiNodeVerifier::new(Arc::new(
-        //     crate::infrastructure::rpc_selector::RpcSelector::new(pool)
-        // ));
-        //
-        // assert!(verifier.verify_bridge_contract(
-        //     "0x1111111111111111111111111111111111111111"
-        // ).is_ok());
-        //
-        // assert!(verifier.verify_bridge_contract(
-        //     "0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef"
-        // ).is_err());
+    fn test_quorum_config_validation() {
+        assert!(QuorumConfig::new(0, 3).is_err());
+        assert!(QuorumConfig::new(4, 3).is_err());
+        assert_eq!(QuorumConfig::new(3, 5).unwrap().min_agreement, 3);
+        assert_eq!(QuorumConfig::default(), QuorumConfig::new(2, 3).unwrap());
     }
 }
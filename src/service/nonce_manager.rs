@@ -39,6 +39,7 @@ impl NonceManager {
 
     /// 获取下一个可用的nonce（企业级：分布式锁保护）
     /// 从链上获取当前nonce，并考虑本地pending的交易
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_next_nonce(
         &self,
         chain: &str,
@@ -139,6 +140,54 @@ impl NonceManager {
         Ok(())
     }
 
+    /// 记录已广播交易占用的nonce（客户端签名的交易在广播成功后才占用，构建时不预留）
+    ///
+    /// 只前进不回退：乱序广播时保留较大的nonce
+    pub async fn reserve_broadcast_nonce(
+        &self,
+        chain: &str,
+        address: &str,
+        nonce: u64,
+    ) -> Result<()> {
+        let key = format!("{}:{}", chain, address);
+        let lock_key = format!("nonce_lock:{}:{}", chain, address);
+
+        let _guard = self
+            .distributed_lock
+            .acquire(&lock_key, 30, Duration::from_secs(10))
+            .await
+            .map_err(|e| anyhow!("Failed to acquire nonce lock: {}", e))?;
+
+        {
+            let mut cache = self.cache.write().await;
+            let record = cache.entry(key).or_insert_with(|| NonceRecord {
+                address: address.to_string(),
+                chain: chain.to_string(),
+                current_nonce: nonce,
+                pending_nonces: Vec::new(),
+                last_updated: chrono::Utc::now(),
+            });
+            record.current_nonce = record.current_nonce.max(nonce);
+            record.pending_nonces.retain(|&n| n > nonce);
+            record.last_updated = chrono::Utc::now();
+        }
+
+        sqlx::query(
+            "INSERT INTO nonce_tracking (chain, address, last_nonce, updated_at)
+             VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+             ON CONFLICT (chain, address)
+             DO UPDATE SET last_nonce = GREATEST(nonce_tracking.last_nonce, EXCLUDED.last_nonce),
+                           updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(chain)
+        .bind(address)
+        .bind(nonce as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// 从数据库获取pending nonces
     async fn get_pending_nonces_from_db(&self, chain: &str, address: &str) -> Result<Vec<u64>> {
         // 查询pending状态的交易（从transactions表）
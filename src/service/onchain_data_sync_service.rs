@@ -10,6 +10,8 @@ use sqlx::PgPool;
 use tokio::time::interval;
 use uuid::Uuid;
 
+use crate::infrastructure::{event_bus::DomainEvent, event_outbox::enqueue_event};
+
 /// 链上数据同步服务
 pub struct OnchainDataSyncService {
     pool: PgPool,
@@ -68,9 +70,9 @@ impl OnchainDataSyncService {
         _address: &str,
     ) -> Result<()> {
         // 查询待确认的交易
-        let pending_txs = sqlx::query_as::<_, (Uuid, String)>(
+        let pending_txs = sqlx::query_as::<_, (Uuid, String, Uuid)>(
             r#"
-            SELECT id, tx_hash
+            SELECT id, tx_hash, user_id
             FROM transactions
             WHERE wallet_id = $1
               AND chain_id = (
@@ -89,24 +91,41 @@ impl OnchainDataSyncService {
         .await
         .context("Failed to query pending transactions")?;
 
-        for (tx_id, tx_hash) in pending_txs {
+        for (tx_id, tx_hash, user_id) in pending_txs {
             // 查询链上交易状态
             match self.query_transaction_status(chain, &tx_hash).await {
                 Ok(status) => {
-                    // 更新交易状态
-                    sqlx::query(
+                    // 更新交易状态；首次变为 confirmed 时同一事务写入 outbox 事件
+                    let mut db_tx = self.pool.begin().await?;
+                    let changed = sqlx::query(
                         r#"
                         UPDATE transactions
                         SET status = $1,
                             updated_at = CURRENT_TIMESTAMP
-                        WHERE id = $2
+                        WHERE id = $2 AND status <> $1
                         "#,
                     )
                     .bind(&status)
                     .bind(tx_id)
-                    .execute(&self.pool)
+                    .execute(&mut *db_tx)
                     .await
-                    .context("Failed to update transaction status")?;
+                    .context("Failed to update transaction status")?
+                    .rows_affected()
+                        > 0;
+
+                    if changed && status == "confirmed" {
+                        enqueue_event(
+                            &mut *db_tx,
+                            &DomainEvent::TransactionConfirmed {
+                                tx_id,
+                                tx_hash: tx_hash.clone(),
+                                chain_type: chain.to_string(),
+                                user_id,
+                            },
+                        )
+                        .await?;
+                    }
+                    db_tx.commit().await?;
 
                     tracing::debug!(
                         tx_id = %tx_id,
@@ -5,7 +5,9 @@ use std::time::Duration;
 
 use anyhow::{Context, Result};
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::telemetry::TracePropagation;
 
 /// 1inch API 客户端
 pub struct OneInchService {
@@ -74,7 +76,7 @@ struct OneInchSwapResponse {
 }
 
 /// 交易数据
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TransactionData {
     pub from: String,
     pub to: String,
@@ -123,6 +125,7 @@ impl OneInchService {
     }
 
     /// 获取交换报价
+    #[tracing::instrument(skip_all, fields(chain_id = chain_id, from_token = %from_token, to_token = %to_token))]
     pub async fn get_quote(
         &self,
         chain_id: u64,
@@ -142,7 +145,11 @@ impl OneInchService {
             request = request.header("Authorization", format!("Bearer {}", key));
         }
 
-        let response = request.send().await.context("1inch API 请求失败")?;
+        let response = request
+            .with_trace_context()
+            .send()
+            .await
+            .context("1inch API 请求失败")?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -159,6 +166,7 @@ impl OneInchService {
     }
 
     /// 获取交换交易数据
+    #[tracing::instrument(skip_all, fields(chain_id = chain_id, from_token = %from_token, to_token = %to_token))]
     pub async fn get_swap_tx(
         &self,
         chain_id: u64,
@@ -183,7 +191,11 @@ impl OneInchService {
             request = request.header("Authorization", format!("Bearer {}", key));
         }
 
-        let response = request.send().await.context("1inch swap API 请求失败")?;
+        let response = request
+            .with_trace_context()
+            .send()
+            .await
+            .context("1inch swap API 请求失败")?;
 
         if !response.status().is_success() {
             let status = response.status();
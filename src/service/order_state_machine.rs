@@ -196,6 +196,114 @@ impl OrderStateMachine {
     }
 }
 
+/// 限价单状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitOrderStatus {
+    Pending,
+    ReadyToSign,
+    Filled,
+    Cancelled,
+    Expired,
+    Failed,
+}
+
+impl LimitOrderStatus {
+    /// 从字符串解析状态
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(LimitOrderStatus::Pending),
+            "ready_to_sign" => Ok(LimitOrderStatus::ReadyToSign),
+            "filled" => Ok(LimitOrderStatus::Filled),
+            "cancelled" => Ok(LimitOrderStatus::Cancelled),
+            "expired" => Ok(LimitOrderStatus::Expired),
+            "failed" => Ok(LimitOrderStatus::Failed),
+            _ => Err(anyhow::anyhow!("Invalid limit order status: {}", s)),
+        }
+    }
+
+    /// 转换为字符串
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitOrderStatus::Pending => "pending",
+            LimitOrderStatus::ReadyToSign => "ready_to_sign",
+            LimitOrderStatus::Filled => "filled",
+            LimitOrderStatus::Cancelled => "cancelled",
+            LimitOrderStatus::Expired => "expired",
+            LimitOrderStatus::Failed => "failed",
+        }
+    }
+
+    /// 判断是否为终态
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            LimitOrderStatus::Filled
+                | LimitOrderStatus::Cancelled
+                | LimitOrderStatus::Expired
+                | LimitOrderStatus::Failed
+        )
+    }
+}
+
+/// 限价单状态机
+pub struct LimitOrderStateMachine;
+
+impl LimitOrderStateMachine {
+    /// 验证限价单状态转换是否合法
+    ///
+    /// # 状态转换规则
+    /// ```text
+    /// Pending -> ReadyToSign     ✅ (价格触发，已构建 swap 交易)
+    /// Pending -> Cancelled       ✅
+    /// Pending -> Expired         ✅
+    /// Pending -> Failed          ✅ (无法构建交易，如未绑定钱包)
+    /// ReadyToSign -> Filled      ✅ (客户端签名广播)
+    /// ReadyToSign -> Cancelled   ✅
+    /// ReadyToSign -> Expired     ✅
+    /// ReadyToSign -> Failed      ✅
+    ///
+    /// 终态不允许转换到其他状态
+    /// ```
+    pub fn validate_transition(from: LimitOrderStatus, to: LimitOrderStatus) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+
+        let valid = match from {
+            LimitOrderStatus::Pending => matches!(
+                to,
+                LimitOrderStatus::ReadyToSign
+                    | LimitOrderStatus::Cancelled
+                    | LimitOrderStatus::Expired
+                    | LimitOrderStatus::Failed
+            ),
+            LimitOrderStatus::ReadyToSign => matches!(
+                to,
+                LimitOrderStatus::Filled
+                    | LimitOrderStatus::Cancelled
+                    | LimitOrderStatus::Expired
+                    | LimitOrderStatus::Failed
+            ),
+            LimitOrderStatus::Filled
+            | LimitOrderStatus::Cancelled
+            | LimitOrderStatus::Expired
+            | LimitOrderStatus::Failed => false,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid limit order transition: {} -> {}",
+                from.as_str(),
+                to.as_str()
+            ))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,4 +410,40 @@ mod tests {
         let next = OrderStateMachine::get_next_states(OrderStatus::Failed);
         assert_eq!(next.len(), 0); // 终态无后续状态
     }
+
+    #[test]
+    fn test_limit_order_transitions() {
+        use LimitOrderStatus::*;
+
+        assert!(LimitOrderStateMachine::validate_transition(Pending, ReadyToSign).is_ok());
+        assert!(LimitOrderStateMachine::validate_transition(Pending, Expired).is_ok());
+        assert!(LimitOrderStateMachine::validate_transition(ReadyToSign, Filled).is_ok());
+        assert!(LimitOrderStateMachine::validate_transition(ReadyToSign, Expired).is_ok());
+
+        // 未经 ready_to_sign 不能直接成交；终态不可回退
+        assert!(LimitOrderStateMachine::validate_transition(Pending, Filled).is_err());
+        assert!(LimitOrderStateMachine::validate_transition(ReadyToSign, Pending).is_err());
+        assert!(LimitOrderStateMachine::validate_transition(Expired, ReadyToSign).is_err());
+        assert!(LimitOrderStateMachine::validate_transition(Filled, Cancelled).is_err());
+
+        // 同一成交交易重复回报保持幂等
+        assert!(LimitOrderStateMachine::validate_transition(Filled, Filled).is_ok());
+    }
+
+    #[test]
+    fn test_limit_order_status_roundtrip() {
+        for status in [
+            LimitOrderStatus::Pending,
+            LimitOrderStatus::ReadyToSign,
+            LimitOrderStatus::Filled,
+            LimitOrderStatus::Cancelled,
+            LimitOrderStatus::Expired,
+            LimitOrderStatus::Failed,
+        ] {
+            assert_eq!(LimitOrderStatus::from_str(status.as_str()).unwrap(), status);
+        }
+        assert!(LimitOrderStatus::from_str("processing").is_err());
+        assert_eq!(LimitOrderStatus::ReadyToSign.as_str(), "ready_to_sign");
+        assert!(!LimitOrderStatus::ReadyToSign.is_terminal());
+    }
 }
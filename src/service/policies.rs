@@ -2,7 +2,8 @@ use uuid::Uuid;
 
 use crate::{
     infrastructure::db::PgPool,
-    repository::policies::{self, CreatePolicyInput, Policy},
+    repository::policies::{self, CreatePolicyInput, Policy, PolicyVersion},
+    service::risk_rule_engine::compile_policy,
 };
 
 pub async fn create_policy(
@@ -19,6 +20,7 @@ pub async fn create_policy(
         version,
     };
     let p = policies::create(pool, input).await?;
+    policies::upsert_version(pool, p.id, p.version, &p.rules).await?;
     Ok(p)
 }
 
@@ -37,6 +39,10 @@ pub async fn list_policies_by_tenant(
     Ok(p)
 }
 
+/// 更新策略
+///
+/// - 修改 rules 且未指定 version 时自动递增版本号，并记录版本快照
+/// - 已启用（active）策略的新规则必须能通过编译校验
 pub async fn update_policy(
     pool: &PgPool,
     id: Uuid,
@@ -45,10 +51,86 @@ pub async fn update_policy(
     rules: Option<serde_json::Value>,
     version: Option<i32>,
 ) -> Result<Option<Policy>, anyhow::Error> {
-    let p = policies::update(pool, id, tenant_id, name, rules, version).await?;
+    let Some(current) = policies::get_by_id(pool, id)
+        .await?
+        .filter(|p| p.tenant_id == tenant_id)
+    else {
+        return Ok(None);
+    };
+
+    let version = match (&rules, version) {
+        (Some(_), None) => Some(current.version + 1),
+        (_, v) => v,
+    };
+    if let Some(rules) = &rules {
+        if current.status == "active" {
+            compile_policy(rules)?;
+        }
+    }
+
+    let p = policies::update(pool, id, tenant_id, name, rules.clone(), version).await?;
+    if let (Some(p), Some(_)) = (&p, &rules) {
+        policies::upsert_version(pool, p.id, p.version, &p.rules).await?;
+    }
+    Ok(p)
+}
+
+/// 启用策略（参与提现风控）；指定 version 时回滚/切换到该版本的规则
+pub async fn activate_policy(
+    pool: &PgPool,
+    id: Uuid,
+    tenant_id: Uuid,
+    version: Option<i32>,
+) -> Result<Option<Policy>, anyhow::Error> {
+    let Some(current) = policies::get_by_id(pool, id)
+        .await?
+        .filter(|p| p.tenant_id == tenant_id)
+    else {
+        return Ok(None);
+    };
+
+    let target = match version {
+        Some(v) if v != current.version => {
+            let snapshot = policies::get_version(pool, id, v)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Policy version {} not found", v))?;
+            Some((snapshot.rules, v))
+        }
+        _ => None,
+    };
+
+    compile_policy(target.as_ref().map(|(r, _)| r).unwrap_or(&current.rules))?;
+
+    let p = policies::set_status(pool, id, tenant_id, "active", target).await?;
+    if let Some(p) = &p {
+        tracing::info!(
+            policy_id = %p.id,
+            tenant_id = %p.tenant_id,
+            version = p.version,
+            "Risk policy activated"
+        );
+    }
+    Ok(p)
+}
+
+/// 停用策略
+pub async fn deactivate_policy(
+    pool: &PgPool,
+    id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<Policy>, anyhow::Error> {
+    let p = policies::set_status(pool, id, tenant_id, "inactive", None).await?;
     Ok(p)
 }
 
+pub async fn list_policy_versions(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Vec<PolicyVersion>, anyhow::Error> {
+    let v = policies::list_versions(pool, id).await?;
+    Ok(v)
+}
+
 pub async fn delete_policy(
     pool: &PgPool,
     id: Uuid,
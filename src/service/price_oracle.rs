@@ -0,0 +1,1144 @@
+//! 多源价格预言机
+//!
+//! 企业级实现：CoinGecko / Chainlink 聚合器 / Uniswap v3 TWAP / 管理员手动覆盖
+//! - 各源并发拉取，超过该源最大时效的报价直接丢弃
+//! - 中位数聚合：偏离中位数超过阈值的报价剔除后重新取中位数
+//! - 有效报价不足时返回 `PriceError::Unavailable`，不回退到过期数据
+//! - 每个源的 ok / error / stale / outlier 计入 Prometheus 指标
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ethers::{
+    abi::{self, ParamType, Token},
+    types::{Address, I256, U256},
+};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::infrastructure::telemetry::TracePropagation;
+use crate::service::multi_node_verifier::EndpointProvider;
+
+/// Chainlink `decimals()`
+const CHAINLINK_DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+/// Chainlink `latestRoundData()`
+const CHAINLINK_LATEST_ROUND_SELECTOR: [u8; 4] = [0xfe, 0xaf, 0x96, 0x8c];
+
+/// 默认 Chainlink USD 喂价（Ethereum 主网）
+const DEFAULT_CHAINLINK_FEEDS: &[(&str, &str, &str)] = &[
+    (
+        "ETH",
+        "ethereum",
+        "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419",
+    ),
+    (
+        "BTC",
+        "ethereum",
+        "0xF4030086522a5bEEa4988F8cA5B36dbC97BeE88c",
+    ),
+    (
+        "USDC",
+        "ethereum",
+        "0x8fFfFfd4AfB6115b954Bd326cbe7B4BA576818f6",
+    ),
+    (
+        "USDT",
+        "ethereum",
+        "0x3E7d1eAB13ad0104d2750B8863b2B6a4aEc0A1E1",
+    ),
+    (
+        "DAI",
+        "ethereum",
+        "0xAed0c38402a5d19df6E4c03F4E2DceD6e29c1ee9",
+    ),
+];
+
+/// 默认 Uniswap v3 TWAP 池（计价币为 USDC）：SYMBOL=chain:pool:base:dec0:dec1
+const DEFAULT_TWAP_POOLS: &[&str] =
+    &["ETH=ethereum:0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640:token1:6:18"];
+
+/// 价格获取错误
+#[derive(Debug, thiserror::Error)]
+pub enum PriceError {
+    #[error("Price unavailable for {symbol}: {reason}")]
+    Unavailable { symbol: String, reason: String },
+}
+
+/// 单个源的报价
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceQuote {
+    pub source: String,
+    /// USD 价格
+    pub price: Decimal,
+    /// 源数据的观测时间（Chainlink updatedAt / CoinGecko last_updated_at）
+    pub observed_at: DateTime<Utc>,
+}
+
+/// 可插拔价格源
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// 报价最大时效，超过即视为过期
+    fn max_age(&self) -> Duration;
+
+    /// 覆盖源：有报价时直接采用，跳过聚合
+    fn is_override(&self) -> bool {
+        false
+    }
+
+    /// 获取报价；不支持该币种时返回 `Ok(None)`
+    async fn fetch(&self, symbol: &str) -> Result<Option<SourceQuote>>;
+}
+
+/// 聚合参数
+#[derive(Debug, Clone)]
+pub struct AggregationConfig {
+    /// 偏离中位数的最大幅度（基点）
+    pub max_deviation_bps: u32,
+    /// 剔除离群值后至少需要的报价数
+    pub min_sources: usize,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation_bps: 200,
+            min_sources: 1,
+        }
+    }
+}
+
+impl AggregationConfig {
+    /// `PRICE_MAX_DEVIATION_BPS` / `PRICE_MIN_SOURCES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_deviation_bps: std::env::var("PRICE_MAX_DEVIATION_BPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_deviation_bps),
+            min_sources: std::env::var("PRICE_MIN_SOURCES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.min_sources),
+        }
+    }
+}
+
+/// 聚合结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatedPrice {
+    pub symbol: String,
+    pub price: Decimal,
+    /// 参与聚合的报价
+    pub quotes: Vec<SourceQuote>,
+    /// 是否来自管理员手动覆盖
+    pub overridden: bool,
+}
+
+impl AggregatedPrice {
+    /// 参与聚合的源名称（逗号分隔）
+    pub fn source_label(&self) -> String {
+        self.quotes
+            .iter()
+            .map(|q| q.source.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// 中位数聚合 + 离群值剔除
+#[derive(Debug, Clone, Default)]
+pub struct Aggregation {
+    pub price: Option<Decimal>,
+    pub accepted: Vec<SourceQuote>,
+    pub outliers: Vec<SourceQuote>,
+}
+
+pub fn aggregate(quotes: Vec<SourceQuote>, config: &AggregationConfig) -> Aggregation {
+    let Some(reference) = median(quotes.iter().map(|q| q.price).collect()) else {
+        return Aggregation::default();
+    };
+    if reference <= Decimal::ZERO {
+        return Aggregation {
+            price: None,
+            accepted: Vec::new(),
+            outliers: quotes,
+        };
+    }
+
+    let max_deviation = Decimal::from(config.max_deviation_bps) / Decimal::from(10_000);
+    let (accepted, outliers): (Vec<_>, Vec<_>) = quotes
+        .into_iter()
+        .partition(|q| ((q.price - reference) / reference).abs() <= max_deviation);
+
+    let price = if accepted.len() >= config.min_sources {
+        median(accepted.iter().map(|q| q.price).collect())
+    } else {
+        None
+    };
+    Aggregation {
+        price,
+        accepted,
+        outliers,
+    }
+}
+
+fn median(mut values: Vec<Decimal>) -> Option<Decimal> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid]) / Decimal::TWO
+    })
+}
+
+/// 价格预言机
+pub struct PriceOracle {
+    sources: Vec<Arc<dyn PriceSource>>,
+    config: AggregationConfig,
+}
+
+impl PriceOracle {
+    pub fn new(sources: Vec<Arc<dyn PriceSource>>, config: AggregationConfig) -> Self {
+        Self { sources, config }
+    }
+
+    /// 追加价格源
+    pub fn push_source(&mut self, source: Arc<dyn PriceSource>) {
+        self.sources.push(source);
+    }
+
+    /// 获取聚合价格
+    #[tracing::instrument(skip_all, fields(symbol = %symbol))]
+    pub async fn get_price(&self, symbol: &str) -> Result<AggregatedPrice, PriceError> {
+        let symbol = symbol.trim().to_uppercase();
+        let symbol_ref = symbol.as_str();
+        let results = futures::future::join_all(
+            self.sources
+                .iter()
+                .map(|source| async move { (source, source.fetch(symbol_ref).await) }),
+        )
+        .await;
+
+        let now = Utc::now();
+        let mut quotes = Vec::new();
+        let mut override_quote = None;
+        let mut stale = 0usize;
+        for (source, result) in results {
+            match result {
+                Ok(Some(quote))
+                    if !source.is_override() && now - quote.observed_at > source.max_age() =>
+                {
+                    stale += 1;
+                    crate::metrics::record_price_source(source.name(), "stale");
+                    tracing::warn!(
+                        source = source.name(),
+                        symbol = %symbol,
+                        observed_at = %quote.observed_at,
+                        "Discarding stale price quote"
+                    );
+                }
+                Ok(Some(quote)) if source.is_override() => {
+                    crate::metrics::record_price_source(source.name(), "ok");
+                    override_quote = Some(quote);
+                }
+                Ok(Some(quote)) => quotes.push(quote),
+                Ok(None) => {}
+                Err(e) => {
+                    crate::metrics::record_price_source(source.name(), "error");
+                    tracing::warn!(source = source.name(), symbol = %symbol, "Price source failed: {}", e);
+                }
+            }
+        }
+
+        if let Some(quote) = override_quote {
+            return Ok(AggregatedPrice {
+                symbol,
+                price: quote.price,
+                quotes: vec![quote],
+                overridden: true,
+            });
+        }
+
+        let fetched = quotes.len();
+        let aggregation = aggregate(quotes, &self.config);
+        for quote in &aggregation.accepted {
+            crate::metrics::record_price_source(&quote.source, "ok");
+        }
+        for quote in &aggregation.outliers {
+            crate::metrics::record_price_source(&quote.source, "outlier");
+            tracing::warn!(
+                source = %quote.source,
+                symbol = %symbol,
+                price = %quote.price,
+                "Price quote deviates from median, excluded"
+            );
+        }
+
+        match aggregation.price {
+            Some(price) => Ok(AggregatedPrice {
+                symbol,
+                price,
+                quotes: aggregation.accepted,
+                overridden: false,
+            }),
+            None => {
+                crate::metrics::inc_price_unavailable();
+                Err(PriceError::Unavailable {
+                    reason: format!(
+                        "{} fresh quote(s), {} stale, {} outlier(s), need {}",
+                        fetched,
+                        stale,
+                        aggregation.outliers.len(),
+                        self.config.min_sources
+                    ),
+                    symbol,
+                })
+            }
+        }
+    }
+}
+
+fn max_age_from_env(source: &str, default_secs: i64) -> Duration {
+    let secs = std::env::var(format!("PRICE_MAX_AGE_SECS_{}", source.to_uppercase()))
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default_secs);
+    Duration::seconds(secs)
+}
+
+// ============ CoinGecko ============
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoCoin {
+    usd: f64,
+    last_updated_at: Option<i64>,
+}
+
+/// CoinGecko simple/price
+pub struct CoinGeckoSource {
+    client: reqwest::Client,
+    base_url: String,
+    max_age: Duration,
+}
+
+impl CoinGeckoSource {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: std::env::var("COINGECKO_API_URL")
+                .unwrap_or_else(|_| "https://api.coingecko.com/api/v3".to_string()),
+            max_age: max_age_from_env("coingecko", 600),
+        }
+    }
+}
+
+/// 符号转 CoinGecko ID
+pub fn coingecko_id(symbol: &str) -> String {
+    match symbol.to_lowercase().as_str() {
+        // majors
+        "eth" => "ethereum".to_string(),
+        "btc" => "bitcoin".to_string(),
+        "sol" => "solana".to_string(),
+        "bnb" => "binancecoin".to_string(),
+        "matic" => "matic-network".to_string(),
+        "avax" => "avalanche-2".to_string(),
+        "dot" => "polkadot".to_string(),
+        "ada" => "cardano".to_string(),
+        "ton" => "the-open-network".to_string(),
+
+        // stablecoins (critical for bridge quote in prod)
+        "usdt" => "tether".to_string(),
+        "usdc" => "usd-coin".to_string(),
+        "dai" => "dai".to_string(),
+        "busd" => "binance-usd".to_string(),
+
+        // default: try lowercase symbol as id (best-effort)
+        other => other.to_string(),
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<Option<SourceQuote>> {
+        let coin_id = coingecko_id(symbol);
+        let url = format!(
+            "{}/simple/price?ids={}&vs_currencies=usd&include_last_updated_at=true",
+            self.base_url, coin_id
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", "IronForge/1.0")
+            .timeout(std::time::Duration::from_secs(10))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to fetch price from CoinGecko")?;
+        if !response.status().is_success() {
+            anyhow::bail!("CoinGecko API error: {}", response.status());
+        }
+
+        let data: HashMap<String, CoinGeckoCoin> = response
+            .json()
+            .await
+            .context("Failed to parse CoinGecko response")?;
+        let Some(coin) = data.get(&coin_id) else {
+            return Ok(None);
+        };
+
+        let price = Decimal::from_f64(coin.usd)
+            .ok_or_else(|| anyhow::anyhow!("Invalid price value: {}", coin.usd))?;
+        let observed_at = coin
+            .last_updated_at
+            .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+            .unwrap_or_else(Utc::now);
+        Ok(Some(SourceQuote {
+            source: self.name().to_string(),
+            price,
+            observed_at,
+        }))
+    }
+}
+
+// ============ 链上读取 ============
+
+/// `eth_call` 到选定 RPC
+async fn eth_call(
+    endpoint_provider: &dyn EndpointProvider,
+    client: &reqwest::Client,
+    chain: &str,
+    to: Address,
+    data: &[u8],
+) -> Result<Vec<u8>> {
+    let url = endpoint_provider
+        .distinct_endpoints(chain, 1)
+        .await
+        .into_iter()
+        .next()
+        .context("No healthy RPC endpoint available")?;
+
+    let json: serde_json::Value = client
+        .post(&url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": format!("{:?}", to), "data": format!("0x{}", hex::encode(data)) }, "latest"]
+        }))
+        .with_trace_context()
+        .send()
+        .await
+        .context("Failed to send eth_call request")?
+        .json()
+        .await
+        .context("Failed to parse eth_call response")?;
+
+    if let Some(error) = json.get("error") {
+        anyhow::bail!(
+            "RPC error (eth_call): {}",
+            error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown RPC error")
+        );
+    }
+    let hex_str = json
+        .get("result")
+        .and_then(|r| r.as_str())
+        .context("Missing eth_call result")?;
+    hex::decode(hex_str.trim_start_matches("0x")).context("Invalid eth_call result hex")
+}
+
+/// 链上合约位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedLocation {
+    pub chain: String,
+    pub address: Address,
+}
+
+/// 解析 `SYMBOL=chain:0xaddr,...`
+fn parse_feeds(spec: &str) -> Result<HashMap<String, FeedLocation>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (symbol, location) = entry
+                .split_once('=')
+                .with_context(|| format!("Invalid feed entry: {}", entry))?;
+            let (chain, address) = location
+                .split_once(':')
+                .with_context(|| format!("Invalid feed location: {}", location))?;
+            Ok((
+                symbol.trim().to_uppercase(),
+                FeedLocation {
+                    chain: chain.trim().to_lowercase(),
+                    address: address
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("Invalid feed address: {}", address))?,
+                },
+            ))
+        })
+        .collect()
+}
+
+// ============ Chainlink ============
+
+/// Chainlink 聚合器 `latestRoundData()`
+pub struct ChainlinkSource {
+    endpoint_provider: Arc<dyn EndpointProvider>,
+    client: reqwest::Client,
+    feeds: HashMap<String, FeedLocation>,
+    max_age: Duration,
+}
+
+impl ChainlinkSource {
+    pub fn new(
+        endpoint_provider: Arc<dyn EndpointProvider>,
+        feeds: HashMap<String, FeedLocation>,
+    ) -> Self {
+        Self {
+            endpoint_provider,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            feeds,
+            // 主流 USD 喂价心跳 3600s
+            max_age: max_age_from_env("chainlink", 3_900),
+        }
+    }
+
+    /// 内置喂价 + `CHAINLINK_FEEDS`（同名覆盖）
+    pub fn from_env(endpoint_provider: Arc<dyn EndpointProvider>) -> Self {
+        let mut feeds: HashMap<String, FeedLocation> = DEFAULT_CHAINLINK_FEEDS
+            .iter()
+            .filter_map(|(symbol, chain, address)| {
+                Some((
+                    symbol.to_string(),
+                    FeedLocation {
+                        chain: chain.to_string(),
+                        address: address.parse().ok()?,
+                    },
+                ))
+            })
+            .collect();
+        if let Ok(spec) = std::env::var("CHAINLINK_FEEDS") {
+            match parse_feeds(&spec) {
+                Ok(extra) => feeds.extend(extra),
+                Err(e) => tracing::warn!("Ignoring invalid CHAINLINK_FEEDS: {}", e),
+            }
+        }
+        Self::new(endpoint_provider, feeds)
+    }
+}
+
+#[async_trait]
+impl PriceSource for ChainlinkSource {
+    fn name(&self) -> &str {
+        "chainlink"
+    }
+
+    fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<Option<SourceQuote>> {
+        let Some(feed) = self.feeds.get(symbol) else {
+            return Ok(None);
+        };
+        let provider = self.endpoint_provider.as_ref();
+
+        let decimals = eth_call(
+            provider,
+            &self.client,
+            &feed.chain,
+            feed.address,
+            &CHAINLINK_DECIMALS_SELECTOR,
+        )
+        .await?;
+        let decimals = match abi::decode(&[ParamType::Uint(8)], &decimals)?.as_slice() {
+            [Token::Uint(d)] if *d <= U256::from(28) => d.as_u32(),
+            _ => anyhow::bail!("Invalid Chainlink decimals"),
+        };
+
+        let round = eth_call(
+            provider,
+            &self.client,
+            &feed.chain,
+            feed.address,
+            &CHAINLINK_LATEST_ROUND_SELECTOR,
+        )
+        .await?;
+        let tokens = abi::decode(
+            &[
+                ParamType::Uint(80),
+                ParamType::Int(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(80),
+            ],
+            &round,
+        )?;
+        let [Token::Uint(round_id), Token::Int(answer), _, Token::Uint(updated_at), Token::Uint(answered_in_round)] =
+            tokens.as_slice()
+        else {
+            anyhow::bail!("Invalid Chainlink latestRoundData");
+        };
+
+        let answer = I256::from_raw(*answer);
+        if answer <= I256::zero() {
+            anyhow::bail!("Chainlink answer is not positive: {}", answer);
+        }
+        if answered_in_round < round_id {
+            anyhow::bail!("Chainlink round {} not answered", round_id);
+        }
+        let raw_answer = answer.into_raw();
+        if raw_answer.bits() > 127 {
+            anyhow::bail!("Chainlink answer out of range");
+        }
+        let answer = raw_answer.low_u128() as i128;
+        let price = Decimal::try_from_i128_with_scale(answer, decimals)
+            .context("Chainlink answer out of range")?;
+        let observed_at = Utc
+            .timestamp_opt(updated_at.low_u64() as i64, 0)
+            .single()
+            .context("Invalid Chainlink updatedAt")?;
+
+        Ok(Some(SourceQuote {
+            source: self.name().to_string(),
+            price,
+            observed_at,
+        }))
+    }
+}
+
+// ============ Uniswap v3 TWAP ============
+
+/// Uniswap v3 池配置：基础币为 token0 或 token1，另一侧为 USD 稳定币
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwapPool {
+    pub chain: String,
+    pub pool: Address,
+    pub base_is_token0: bool,
+    pub token0_decimals: u32,
+    pub token1_decimals: u32,
+}
+
+/// 解析 `SYMBOL=chain:pool:token0|token1:dec0:dec1,...`
+fn parse_twap_pools(spec: &str) -> Result<HashMap<String, TwapPool>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (symbol, location) = entry
+                .split_once('=')
+                .with_context(|| format!("Invalid TWAP pool entry: {}", entry))?;
+            let parts: Vec<&str> = location.split(':').map(str::trim).collect();
+            let [chain, pool, base, dec0, dec1] = parts.as_slice() else {
+                anyhow::bail!("Invalid TWAP pool location: {}", location);
+            };
+            let base_is_token0 = match *base {
+                "token0" => true,
+                "token1" => false,
+                other => anyhow::bail!("TWAP base must be token0 or token1: {}", other),
+            };
+            Ok((
+                symbol.trim().to_uppercase(),
+                TwapPool {
+                    chain: chain.to_lowercase(),
+                    pool: pool
+                        .parse()
+                        .with_context(|| format!("Invalid pool: {}", pool))?,
+                    base_is_token0,
+                    token0_decimals: dec0.parse()?,
+                    token1_decimals: dec1.parse()?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// tickCumulative 差值 → 时间加权平均 tick（向负无穷取整，同 OracleLibrary.consult）
+fn average_tick(tick_cumulative_delta: i64, window_secs: u32) -> i64 {
+    let window = i64::from(window_secs);
+    let mut tick = tick_cumulative_delta / window;
+    if tick_cumulative_delta < 0 && tick_cumulative_delta % window != 0 {
+        tick -= 1;
+    }
+    tick
+}
+
+/// 平均 tick → 基础币的计价币价格
+fn tick_to_price(tick: i64, pool: &TwapPool) -> Option<Decimal> {
+    // 1.0001^tick 为 token1/token0 的最小单位比价
+    let raw = 1.0001f64.powi(i32::try_from(tick).ok()?);
+    let token1_per_token0 =
+        raw * 10f64.powi(pool.token0_decimals as i32 - pool.token1_decimals as i32);
+    let price = if pool.base_is_token0 {
+        token1_per_token0
+    } else {
+        1.0 / token1_per_token0
+    };
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    Decimal::from_f64(price).map(|p| p.round_dp(8))
+}
+
+/// Uniswap v3 `observe([window, 0])` 时间加权平均价
+pub struct UniswapTwapSource {
+    endpoint_provider: Arc<dyn EndpointProvider>,
+    client: reqwest::Client,
+    pools: HashMap<String, TwapPool>,
+    window_secs: u32,
+    max_age: Duration,
+}
+
+impl UniswapTwapSource {
+    pub fn new(
+        endpoint_provider: Arc<dyn EndpointProvider>,
+        pools: HashMap<String, TwapPool>,
+        window_secs: u32,
+    ) -> Self {
+        Self {
+            endpoint_provider,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            pools,
+            window_secs: window_secs.max(1),
+            max_age: max_age_from_env("uniswap_twap", 300),
+        }
+    }
+
+    /// 内置池 + `UNISWAP_TWAP_POOLS`，窗口 `PRICE_TWAP_WINDOW_SECS`（默认 1800）
+    pub fn from_env(endpoint_provider: Arc<dyn EndpointProvider>) -> Self {
+        let mut pools = parse_twap_pools(&DEFAULT_TWAP_POOLS.join(","))
+            .expect("built-in TWAP pools must parse");
+        if let Ok(spec) = std::env::var("UNISWAP_TWAP_POOLS") {
+            match parse_twap_pools(&spec) {
+                Ok(extra) => pools.extend(extra),
+                Err(e) => tracing::warn!("Ignoring invalid UNISWAP_TWAP_POOLS: {}", e),
+            }
+        }
+        let window_secs = std::env::var("PRICE_TWAP_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1_800);
+        Self::new(endpoint_provider, pools, window_secs)
+    }
+}
+
+#[async_trait]
+impl PriceSource for UniswapTwapSource {
+    fn name(&self) -> &str {
+        "uniswap_twap"
+    }
+
+    fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<Option<SourceQuote>> {
+        let Some(pool) = self.pools.get(symbol) else {
+            return Ok(None);
+        };
+
+        let mut data = ethers::utils::id("observe(uint32[])").to_vec();
+        data.extend(abi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(self.window_secs)),
+            Token::Uint(U256::zero()),
+        ])]));
+        let output = eth_call(
+            self.endpoint_provider.as_ref(),
+            &self.client,
+            &pool.chain,
+            pool.pool,
+            &data,
+        )
+        .await?;
+
+        let tokens = abi::decode(
+            &[
+                ParamType::Array(Box::new(ParamType::Int(56))),
+                ParamType::Array(Box::new(ParamType::Uint(160))),
+            ],
+            &output,
+        )?;
+        let cumulatives: Vec<i64> = match tokens.first() {
+            Some(Token::Array(items)) => items
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Int(raw) => i64::try_from(I256::from_raw(*raw)).ok(),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let [then, now] = cumulatives.as_slice() else {
+            anyhow::bail!("Invalid observe() result");
+        };
+
+        let tick = average_tick(now - then, self.window_secs);
+        let price = tick_to_price(tick, pool).context("TWAP price out of range")?;
+        Ok(Some(SourceQuote {
+            source: self.name().to_string(),
+            price,
+            observed_at: Utc::now(),
+        }))
+    }
+}
+
+// ============ 手动覆盖 ============
+
+/// 管理员设置的手动价格（`price_overrides`），生效期间直接采用
+pub struct ManualOverrideSource {
+    pool: PgPool,
+}
+
+impl ManualOverrideSource {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PriceSource for ManualOverrideSource {
+    fn name(&self) -> &str {
+        "manual"
+    }
+
+    /// 有效期由 `expires_at` 控制
+    fn max_age(&self) -> Duration {
+        Duration::MAX
+    }
+
+    fn is_override(&self) -> bool {
+        true
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<Option<SourceQuote>> {
+        let row = sqlx::query_as::<_, (Decimal, DateTime<Utc>)>(
+            "SELECT price_usd, updated_at FROM price_overrides
+             WHERE symbol = $1 AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)",
+        )
+        .bind(symbol)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|(price, updated_at)| SourceQuote {
+            source: self.name().to_string(),
+            price,
+            observed_at: updated_at,
+        }))
+    }
+}
+
+/// 手动价格覆盖记录
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct PriceOverride {
+    pub symbol: String,
+    #[schema(value_type = String)]
+    pub price_usd: Decimal,
+    pub reason: String,
+    pub set_by: uuid::Uuid,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 设置（或替换）手动覆盖
+pub async fn set_override(
+    pool: &PgPool,
+    symbol: &str,
+    price_usd: Decimal,
+    reason: &str,
+    set_by: uuid::Uuid,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<PriceOverride> {
+    let row = sqlx::query_as::<_, PriceOverride>(
+        "INSERT INTO price_overrides (symbol, price_usd, reason, set_by, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (symbol) DO UPDATE SET
+             price_usd = EXCLUDED.price_usd,
+             reason = EXCLUDED.reason,
+             set_by = EXCLUDED.set_by,
+             expires_at = EXCLUDED.expires_at,
+             updated_at = CURRENT_TIMESTAMP
+         RETURNING symbol, price_usd, reason, set_by, expires_at, updated_at",
+    )
+    .bind(symbol.trim().to_uppercase())
+    .bind(price_usd)
+    .bind(reason)
+    .bind(set_by)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// 删除手动覆盖，返回是否存在
+pub async fn clear_override(pool: &PgPool, symbol: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM price_overrides WHERE symbol = $1")
+        .bind(symbol.trim().to_uppercase())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 列出手动覆盖（含已过期）
+pub async fn list_overrides(pool: &PgPool) -> Result<Vec<PriceOverride>> {
+    let rows = sqlx::query_as::<_, PriceOverride>(
+        "SELECT symbol, price_usd, reason, set_by, expires_at, updated_at
+         FROM price_overrides ORDER BY symbol",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// 默认价格源：手动覆盖 + CoinGecko
+pub fn default_sources(pool: PgPool) -> Vec<Arc<dyn PriceSource>> {
+    vec![
+        Arc::new(ManualOverrideSource::new(pool)),
+        Arc::new(CoinGeckoSource::from_env()),
+    ]
+}
+
+/// 链上价格源：Chainlink 聚合器 + Uniswap v3 TWAP
+pub fn onchain_sources(endpoint_provider: Arc<dyn EndpointProvider>) -> Vec<Arc<dyn PriceSource>> {
+    vec![
+        Arc::new(ChainlinkSource::from_env(endpoint_provider.clone())),
+        Arc::new(UniswapTwapSource::from_env(endpoint_provider)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Json, Router};
+
+    use super::*;
+
+    fn quote(source: &str, price: &str) -> SourceQuote {
+        SourceQuote {
+            source: source.to_string(),
+            price: price.parse().unwrap(),
+            observed_at: Utc::now(),
+        }
+    }
+
+    /// Test 1: 中位数与离群值剔除
+    #[test]
+    fn test_aggregate_median_and_outliers() {
+        let config = AggregationConfig::default();
+
+        let result = aggregate(
+            vec![quote("a", "3000"), quote("b", "3010"), quote("c", "3500")],
+            &config,
+        );
+        assert_eq!(result.outliers.len(), 1);
+        assert_eq!(result.outliers[0].source, "c");
+        assert_eq!(result.price, Some("3005".parse().unwrap()));
+
+        let result = aggregate(vec![quote("a", "1.00"), quote("b", "0.999")], &config);
+        assert_eq!(result.price, Some("0.9995".parse().unwrap()));
+    }
+
+    /// Test 2: 报价互相矛盾或不足时不可用
+    #[test]
+    fn test_aggregate_unavailable() {
+        let config = AggregationConfig::default();
+        assert_eq!(aggregate(Vec::new(), &config).price, None);
+
+        // 两个源偏差 10%：各自偏离中位数 5% > 2%
+        let result = aggregate(vec![quote("a", "100"), quote("b", "110")], &config);
+        assert_eq!(result.price, None);
+        assert_eq!(result.outliers.len(), 2);
+
+        let strict = AggregationConfig {
+            min_sources: 2,
+            ..config
+        };
+        assert_eq!(aggregate(vec![quote("a", "100")], &strict).price, None);
+    }
+
+    /// Test 3: TWAP tick 数学与配置解析
+    #[test]
+    fn test_twap_tick_math() {
+        assert_eq!(average_tick(-7, 2), -4);
+        assert_eq!(average_tick(7, 2), 3);
+        assert_eq!(average_tick(-8, 2), -4);
+
+        let pools = parse_twap_pools(DEFAULT_TWAP_POOLS[0]).unwrap();
+        let pool = &pools["ETH"];
+        assert!(!pool.base_is_token0);
+        // USDC(6)/WETH(18) 池 tick ≈ 196256 对应 ETH ≈ 3000 USDC
+        let price = tick_to_price(196_256, pool).unwrap();
+        assert!(
+            price > Decimal::from(2_990) && price < Decimal::from(3_010),
+            "{}",
+            price
+        );
+
+        assert!(parse_twap_pools("ETH=ethereum:0x01:token2:6:18").is_err());
+        let feeds =
+            parse_feeds("link=Ethereum:0x2c1d072e956AFFC0D435Cb7AC38EF18d24d9127c").unwrap();
+        assert_eq!(feeds["LINK"].chain, "ethereum");
+    }
+
+    struct FixedSource {
+        name: &'static str,
+        price: Option<&'static str>,
+        age_secs: i64,
+        is_override: bool,
+    }
+
+    #[async_trait]
+    impl PriceSource for FixedSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn max_age(&self) -> Duration {
+            Duration::seconds(300)
+        }
+
+        fn is_override(&self) -> bool {
+            self.is_override
+        }
+
+        async fn fetch(&self, _symbol: &str) -> Result<Option<SourceQuote>> {
+            let price = self.price.context("source down")?;
+            Ok(Some(SourceQuote {
+                source: self.name.to_string(),
+                price: price.parse()?,
+                observed_at: Utc::now() - Duration::seconds(self.age_secs),
+            }))
+        }
+    }
+
+    fn fixed(
+        name: &'static str,
+        price: Option<&'static str>,
+        age_secs: i64,
+    ) -> Arc<dyn PriceSource> {
+        Arc::new(FixedSource {
+            name,
+            price,
+            age_secs,
+            is_override: false,
+        })
+    }
+
+    /// Test 4: 过期报价与失败源被排除，覆盖源优先
+    #[tokio::test]
+    async fn test_oracle_staleness_and_override() {
+        let mut oracle = PriceOracle::new(
+            vec![
+                fixed("fresh", Some("2000"), 10),
+                fixed("stale", Some("1500"), 3_600),
+                fixed("down", None, 0),
+            ],
+            AggregationConfig::default(),
+        );
+        let price = oracle.get_price("eth").await.unwrap();
+        assert_eq!(price.symbol, "ETH");
+        assert_eq!(price.price, Decimal::from(2_000));
+        assert_eq!(price.source_label(), "fresh");
+
+        let only_stale = PriceOracle::new(
+            vec![fixed("stale", Some("1500"), 3_600)],
+            AggregationConfig::default(),
+        );
+        let err = only_stale.get_price("ETH").await.unwrap_err();
+        assert!(err.to_string().contains("1 stale"), "{}", err);
+
+        oracle.push_source(Arc::new(FixedSource {
+            name: "manual",
+            price: Some("1999.5"),
+            age_secs: 86_400,
+            is_override: true,
+        }));
+        let price = oracle.get_price("ETH").await.unwrap();
+        assert!(price.overridden);
+        assert_eq!(price.price, "1999.5".parse().unwrap());
+    }
+
+    struct StaticEndpoint(String);
+
+    #[async_trait]
+    impl EndpointProvider for StaticEndpoint {
+        async fn distinct_endpoints(&self, _chain: &str, _count: usize) -> Vec<String> {
+            vec![self.0.clone()]
+        }
+    }
+
+    /// 本地 JSON-RPC 桩：Chainlink decimals = 8，answer = 3012.5 USD
+    async fn mock_rpc(updated_at: u64) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<serde_json::Value>| async move {
+                let data = req["params"][0]["data"].as_str().unwrap_or_default();
+                let output = if data == "0x313ce567" {
+                    abi::encode(&[Token::Uint(U256::from(8))])
+                } else {
+                    abi::encode(&[
+                        Token::Uint(U256::from(42)),
+                        Token::Int(U256::from(301_250_000_000u64)),
+                        Token::Uint(U256::from(updated_at)),
+                        Token::Uint(U256::from(updated_at)),
+                        Token::Uint(U256::from(42)),
+                    ])
+                };
+                Json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": format!("0x{}", hex::encode(output))
+                }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Test 5: Chainlink latestRoundData 解码
+    #[tokio::test]
+    async fn test_chainlink_latest_round() {
+        let updated_at = Utc::now().timestamp() as u64 - 60;
+        let source = ChainlinkSource::new(
+            Arc::new(StaticEndpoint(mock_rpc(updated_at).await)),
+            parse_feeds(&format!("ETH=ethereum:{}", DEFAULT_CHAINLINK_FEEDS[0].2)).unwrap(),
+        );
+
+        let quote = source.fetch("ETH").await.unwrap().unwrap();
+        assert_eq!(quote.price, "3012.5".parse().unwrap());
+        assert_eq!(quote.observed_at.timestamp() as u64, updated_at);
+        assert!(source.fetch("DOGE").await.unwrap().is_none());
+    }
+}
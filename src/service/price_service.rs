@@ -7,6 +7,14 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tokio::sync::RwLock;
 
+use crate::service::{
+    multi_node_verifier::EndpointProvider,
+    price_oracle::{self, AggregatedPrice, AggregationConfig, PriceError, PriceOracle},
+};
+
+/// `prices` 表中聚合价格的来源标记
+const ORACLE_SOURCE: &str = "oracle";
+
 /// 价格数据结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Price {
@@ -16,37 +24,51 @@ pub struct Price {
     pub last_updated: DateTime<Utc>,
 }
 
-/// CoinGecko API 响应
-#[derive(Debug, Deserialize)]
-struct CoinGeckoResponse {
-    #[serde(flatten)]
-    prices: HashMap<String, CoinGeckoCoin>,
-}
-
-#[derive(Debug, Deserialize)]
-struct CoinGeckoCoin {
-    usd: f64,
-}
-
 /// 价格服务
+///
+/// 企业级实现：多源预言机聚合（手动覆盖 / CoinGecko / Chainlink / Uniswap TWAP），
+/// 聚合结果按 `PRICE_CACHE_TTL_SECS` 缓存；无可信报价时返回 `PriceError::Unavailable`
 pub struct PriceService {
     pool: PgPool,
     #[allow(dead_code)]
     redis: Option<redis::Client>,
     cache: Arc<RwLock<HashMap<String, Price>>>,
-    client: reqwest::Client,
+    oracle: PriceOracle,
+    cache_ttl: chrono::Duration,
 }
 
 impl PriceService {
     pub fn new(pool: PgPool, redis_url: Option<String>) -> Self {
         let redis_client = redis_url.and_then(|url| redis::Client::open(url).ok());
+        let cache_ttl_secs = std::env::var("PRICE_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
 
         Self {
+            oracle: PriceOracle::new(
+                price_oracle::default_sources(pool.clone()),
+                AggregationConfig::from_env(),
+            ),
             pool,
             redis: redis_client,
             cache: Arc::new(RwLock::new(HashMap::new())),
-            client: reqwest::Client::new(),
+            cache_ttl: chrono::Duration::seconds(cache_ttl_secs),
+        }
+    }
+
+    /// 接入链上价格源（Chainlink 聚合器 + Uniswap v3 TWAP）
+    pub fn with_onchain_sources(mut self, endpoint_provider: Arc<dyn EndpointProvider>) -> Self {
+        for source in price_oracle::onchain_sources(endpoint_provider) {
+            self.oracle.push_source(source);
         }
+        self
+    }
+
+    /// 替换预言机（自定义价格源组合）
+    pub fn with_oracle(mut self, oracle: PriceOracle) -> Self {
+        self.oracle = oracle;
+        self
     }
 
     /// 获取单个币种价格（USDT）
@@ -61,43 +83,61 @@ impl PriceService {
 
     /// 获取单个币种价格（USDT），返回Decimal类型（推荐使用）
     pub async fn get_price_decimal(&self, symbol: &str) -> Result<Decimal> {
+        let symbol = symbol.trim().to_uppercase();
+
         // 1. 先查内存缓存
         {
             let cache = self.cache.read().await;
-            if let Some(price) = cache.get(symbol) {
-                let age = Utc::now() - price.last_updated;
-                if age.num_seconds() < 300 {
-                    // 5分钟内有效
+            if let Some(price) = cache.get(&symbol) {
+                if Utc::now() - price.last_updated < self.cache_ttl {
                     return Ok(price.price_usdt);
                 }
             }
         }
 
         // 2. 查 Redis 缓存
-        if let Ok(cached) = self.get_from_redis_decimal(symbol).await {
+        if let Ok(cached) = self.get_from_redis_decimal(&symbol).await {
             return Ok(cached);
         }
 
-        // 3. 查数据库
+        // 3. 查数据库（其他实例刚聚合的价格）
         // CockroachDB兼容：直接查询DECIMAL类型，无需类型转换
-        let db_price = sqlx::query_as::<_, (String, Decimal, String, DateTime<Utc>)>(
-            "SELECT symbol, price_usdt, source, last_updated FROM prices WHERE symbol = $1 ORDER BY last_updated DESC LIMIT 1"
+        let db_price = sqlx::query_as::<_, (Decimal, DateTime<Utc>)>(
+            "SELECT price_usdt, last_updated FROM prices WHERE symbol = $1 AND source = $2",
         )
-        .bind(symbol.to_uppercase())
+        .bind(&symbol)
+        .bind(ORACLE_SOURCE)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some((sym, price, source, updated)) = db_price {
-            let age = Utc::now() - updated;
-            if age.num_seconds() < 300 {
-                // 数据库数据也在5分钟内
-                self.update_cache(sym.clone(), price, source, updated).await;
+        if let Some((price, updated)) = db_price {
+            if Utc::now() - updated < self.cache_ttl {
+                self.update_cache(symbol, price, ORACLE_SOURCE.to_string(), updated)
+                    .await;
                 return Ok(price);
             }
         }
 
-        // 4. 从 CoinGecko 获取最新价格
-        self.fetch_and_update_price(symbol).await
+        // 4. 多源聚合
+        self.fetch_and_update_price(&symbol).await
+    }
+
+    /// 实时聚合（不读缓存），返回参与聚合的各源报价
+    pub async fn get_aggregated_price(&self, symbol: &str) -> Result<AggregatedPrice, PriceError> {
+        self.oracle.get_price(symbol).await
+    }
+
+    /// 清除缓存（手动覆盖变更后立即生效）
+    pub async fn invalidate(&self, symbol: &str) -> Result<()> {
+        let symbol = symbol.trim().to_uppercase();
+        self.cache.write().await.remove(&symbol);
+        sqlx::query("DELETE FROM prices WHERE symbol = $1 AND source = $2")
+            .bind(&symbol)
+            .bind(ORACLE_SOURCE)
+            .execute(&self.pool)
+            .await
+            .context("Failed to invalidate cached price")?;
+        Ok(())
     }
 
     /// 批量获取价格
@@ -122,88 +162,23 @@ impl PriceService {
         Ok(result)
     }
 
-    /// 从 CoinGecko 获取价格并更新
+    /// 多源聚合价格并更新缓存
     async fn fetch_and_update_price(&self, symbol: &str) -> Result<Decimal> {
         let symbol_upper = symbol.trim().to_uppercase();
-        let symbol_lower = symbol_upper.to_lowercase();
-        let is_stablecoin = matches!(symbol_lower.as_str(), "usdt" | "usdc" | "dai" | "busd");
-
-        let coin_id = self.symbol_to_coingecko_id(&symbol_upper);
-
-        let url = format!(
-            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies=usd",
-            coin_id
-        );
-
-        tracing::info!("Fetching price from CoinGecko: {}", url);
-
-        let response = match self
-            .client
-            .get(&url)
-            .header("User-Agent", "IronForge/1.0")
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                if is_stablecoin {
-                    tracing::warn!(
-                        symbol = %symbol_upper,
-                        "CoinGecko fetch failed; using static stablecoin price=1: {e}"
-                    );
-                    return self.store_static_price(&symbol_upper).await;
-                }
-                return Err(anyhow::Error::new(e)).context("Failed to fetch price from CoinGecko");
-            }
-        };
-
-        if !response.status().is_success() {
-            if is_stablecoin {
-                tracing::warn!(
-                    symbol = %symbol_upper,
-                    status = %response.status(),
-                    "CoinGecko returned error; using static stablecoin price=1"
-                );
-                return self.store_static_price(&symbol_upper).await;
-            }
-            anyhow::bail!("CoinGecko API error: {}", response.status());
-        }
-
-        let data: CoinGeckoResponse = response
-            .json()
-            .await
-            .context("Failed to parse CoinGecko response")?;
-
-        let price_f64 = match data.prices.get(&coin_id) {
-            Some(v) => v.usd,
-            None => {
-                if is_stablecoin {
-                    tracing::warn!(
-                        symbol = %symbol_upper,
-                        coingecko_id = %coin_id,
-                        "CoinGecko response missing id; using static stablecoin price=1"
-                    );
-                    return self.store_static_price(&symbol_upper).await;
-                }
-                anyhow::bail!("Price not found for {}", coin_id);
-            }
-        };
-
-        // 转换为Decimal以保持精度
-        let price = Decimal::from_f64_retain(price_f64)
-            .ok_or_else(|| anyhow::anyhow!("Invalid price value: {}", price_f64))?;
+        let aggregated = self.oracle.get_price(&symbol_upper).await?;
+        let price = aggregated.price;
 
         // 更新数据库
         // CockroachDB兼容：使用唯一约束，ON CONFLICT语法正确
         sqlx::query(
             "INSERT INTO prices (symbol, price_usdt, source, last_updated)
-             VALUES ($1, $2, 'coingecko', CURRENT_TIMESTAMP)
+             VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
              ON CONFLICT (symbol, source)
              DO UPDATE SET price_usdt = EXCLUDED.price_usdt, last_updated = CURRENT_TIMESTAMP",
         )
         .bind(&symbol_upper)
         .bind(price)
+        .bind(ORACLE_SOURCE)
         .execute(&self.pool)
         .await
         .context("Failed to update price in database")?;
@@ -212,7 +187,7 @@ impl PriceService {
         self.update_cache(
             symbol_upper.clone(),
             price,
-            "coingecko".to_string(),
+            aggregated.source_label(),
             Utc::now(),
         )
         .await;
@@ -223,56 +198,10 @@ impl PriceService {
         Ok(price)
     }
 
-    async fn store_static_price(&self, symbol_upper: &str) -> Result<Decimal> {
-        let price = Decimal::ONE;
-
-        sqlx::query(
-            "INSERT INTO prices (symbol, price_usdt, source, last_updated)
-             VALUES ($1, $2, 'static', CURRENT_TIMESTAMP)
-             ON CONFLICT (symbol, source)
-             DO UPDATE SET price_usdt = EXCLUDED.price_usdt, last_updated = CURRENT_TIMESTAMP",
-        )
-        .bind(symbol_upper)
-        .bind(price)
-        .execute(&self.pool)
-        .await
-        .context("Failed to update static price in database")?;
-
-        self.update_cache(
-            symbol_upper.to_string(),
-            price,
-            "static".to_string(),
-            Utc::now(),
-        )
-        .await;
-
-        let _ = self.set_to_redis_decimal(symbol_upper, price).await;
-
-        Ok(price)
-    }
-
     /// 符号转 CoinGecko ID
+    #[allow(dead_code)]
     fn symbol_to_coingecko_id(&self, symbol: &str) -> String {
-        match symbol.to_lowercase().as_str() {
-            // majors
-            "eth" => "ethereum".to_string(),
-            "btc" => "bitcoin".to_string(),
-            "sol" => "solana".to_string(),
-            "bnb" => "binancecoin".to_string(),
-            "matic" => "matic-network".to_string(),
-            "avax" => "avalanche-2".to_string(),
-            "dot" => "polkadot".to_string(),
-            "ada" => "cardano".to_string(),
-
-            // stablecoins (critical for bridge quote in prod)
-            "usdt" => "tether".to_string(),
-            "usdc" => "usd-coin".to_string(),
-            "dai" => "dai".to_string(),
-            "busd" => "binance-usd".to_string(),
-
-            // default: try lowercase symbol as id (best-effort)
-            other => other.to_string(),
-        }
+        price_oracle::coingecko_id(symbol)
     }
 
     /// 更新内存缓存
@@ -324,8 +253,8 @@ impl PriceService {
     /// 后台任务：定时更新所有支持的币种价格
     pub async fn start_price_updater(self: Arc<Self>) {
         let supported_symbols = vec![
-            "ETH", "SOL", "BTC", "BNB", "MATIC", "AVAX", "DOT", "ADA", "USDT", "USDC", "DAI",
-            "BUSD",
+            "ETH", "SOL", "BTC", "BNB", "MATIC", "AVAX", "DOT", "ADA", "TON", "USDT", "USDC",
+            "DAI", "BUSD",
         ];
 
         tokio::spawn(async move {
@@ -9,6 +9,8 @@ use sqlx::{PgPool, Row};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::infrastructure::telemetry::TracePropagation;
+
 /// 服务商配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
@@ -371,7 +373,7 @@ impl ProviderService {
             .build()
             .context("Failed to create HTTP client")?;
 
-        match client.get(&health_url).send().await {
+        match client.get(&health_url).with_trace_context().send().await {
             Ok(resp) => {
                 let elapsed_ms = start.elapsed().as_millis() as i32;
                 let is_healthy = resp.status().is_success();
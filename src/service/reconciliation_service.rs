@@ -1,13 +1,19 @@
 //! 对账和监控服务
 //! 企业级实现，真实执行每日对账、订单状态同步和异常监控
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use crate::service::fiat::{
+    reconciliation_source::fetch_all_orders, OnramperClient, ProviderOrder,
+    ProviderReconciliationSource, TransFiClient,
+};
+
 /// 对账记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReconciliationRecord {
@@ -28,7 +34,8 @@ pub struct ReconciliationRecord {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
     pub id: Uuid,
-    pub tenant_id: Uuid,
+    /// 全局告警（汇总对账、指标告警）无租户
+    pub tenant_id: Option<Uuid>,
     pub alert_type: String,
     pub severity: String,
     pub message: String,
@@ -38,16 +45,405 @@ pub struct Alert {
     pub created_at: DateTime<Utc>,
 }
 
+/// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    /// 服务商有、本地无
+    MissingLocally,
+    /// 本地有、服务商无
+    MissingRemotely,
+    /// 金额或币种不符（超出容差）
+    AmountMismatch,
+    /// 状态不符
+    StatusMismatch,
+}
+
+impl DiscrepancyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscrepancyKind::MissingLocally => "missing_locally",
+            DiscrepancyKind::MissingRemotely => "missing_remotely",
+            DiscrepancyKind::AmountMismatch => "amount_mismatch",
+            DiscrepancyKind::StatusMismatch => "status_mismatch",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "missing_locally" => Some(DiscrepancyKind::MissingLocally),
+            "missing_remotely" => Some(DiscrepancyKind::MissingRemotely),
+            "amount_mismatch" => Some(DiscrepancyKind::AmountMismatch),
+            "status_mismatch" => Some(DiscrepancyKind::StatusMismatch),
+            _ => None,
+        }
+    }
+}
+
+/// 告警级别（与 fiat.alerts.severity 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Low => "low",
+            AlertSeverity::Medium => "medium",
+            AlertSeverity::High => "high",
+            AlertSeverity::Critical => "critical",
+        }
+    }
+
+    pub fn parse(severity: &str) -> Option<Self> {
+        match severity {
+            "low" => Some(AlertSeverity::Low),
+            "medium" => Some(AlertSeverity::Medium),
+            "high" => Some(AlertSeverity::High),
+            "critical" => Some(AlertSeverity::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// 逐单对账差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationDiscrepancy {
+    pub reconciliation_date: NaiveDate,
+    pub provider: String,
+    pub kind: DiscrepancyKind,
+    pub severity: AlertSeverity,
+    pub tenant_id: Option<Uuid>,
+    pub order_id: Option<Uuid>,
+    pub provider_order_id: Option<String>,
+    pub local_amount: Option<Decimal>,
+    pub provider_amount: Option<Decimal>,
+    pub local_currency: Option<String>,
+    pub provider_currency: Option<String>,
+    pub local_status: Option<String>,
+    pub provider_status: Option<String>,
+    pub detail: String,
+}
+
+/// 本地订单（对账视图）
+#[derive(Debug, Clone)]
+pub struct LocalOrder {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub provider_order_id: Option<String>,
+    pub fiat_amount: Decimal,
+    pub fiat_currency: String,
+    pub status: String,
+}
+
+/// 金额容差：|差额| ≤ max(绝对容差, 本地金额 × 基点容差)
+#[derive(Debug, Clone)]
+pub struct ToleranceRules {
+    pub amount_abs: Decimal,
+    pub amount_bps: Decimal,
+}
+
+impl Default for ToleranceRules {
+    fn default() -> Self {
+        Self {
+            amount_abs: Decimal::new(1, 2), // 0.01
+            amount_bps: Decimal::ZERO,
+        }
+    }
+}
+
+impl ToleranceRules {
+    /// `RECONCILIATION_AMOUNT_TOLERANCE` / `RECONCILIATION_AMOUNT_TOLERANCE_BPS`
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: Decimal| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| Decimal::from_str(&v).ok())
+                .filter(|v| !v.is_sign_negative())
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            amount_abs: read("RECONCILIATION_AMOUNT_TOLERANCE", defaults.amount_abs),
+            amount_bps: read("RECONCILIATION_AMOUNT_TOLERANCE_BPS", defaults.amount_bps),
+        }
+    }
+
+    pub fn amounts_match(&self, local: Decimal, remote: Decimal) -> bool {
+        let relative = local.abs() * self.amount_bps / Decimal::from(10_000);
+        (local - remote).abs() <= self.amount_abs.max(relative)
+    }
+}
+
+/// 单个服务商的比对结果
+#[derive(Debug, Clone, Default)]
+pub struct ComparisonOutcome {
+    pub matched: i64,
+    /// 存在差异的订单数（同一订单多项差异只计一次）
+    pub unmatched: i64,
+    pub discrepancies: Vec<ReconciliationDiscrepancy>,
+}
+
+/// 进行中状态视为等价（服务商与本地状态推进存在时差）
+fn is_in_flight(status: &str) -> bool {
+    matches!(status, "pending" | "processing")
+}
+
+fn is_reversed(status: &str) -> bool {
+    matches!(status, "failed" | "cancelled" | "refunded" | "expired")
+}
+
+/// 比对本地与服务商订单
+pub fn compare_orders(
+    date: NaiveDate,
+    provider: &str,
+    local: &[LocalOrder],
+    remote: &[ProviderOrder],
+    rules: &ToleranceRules,
+) -> ComparisonOutcome {
+    let mut remaining: HashMap<&str, &ProviderOrder> = remote
+        .iter()
+        .map(|o| (o.provider_order_id.as_str(), o))
+        .collect();
+    let mut outcome = ComparisonOutcome::default();
+
+    let base = |kind, severity, detail: String| ReconciliationDiscrepancy {
+        reconciliation_date: date,
+        provider: provider.to_string(),
+        kind,
+        severity,
+        tenant_id: None,
+        order_id: None,
+        provider_order_id: None,
+        local_amount: None,
+        provider_amount: None,
+        local_currency: None,
+        provider_currency: None,
+        local_status: None,
+        provider_status: None,
+        detail,
+    };
+
+    for order in local {
+        let remote_order = order
+            .provider_order_id
+            .as_deref()
+            .filter(|id| !id.is_empty())
+            .and_then(|id| remaining.remove(id));
+
+        let Some(remote_order) = remote_order else {
+            let severity = match order.status.as_str() {
+                "completed" => AlertSeverity::Critical,
+                "processing" => AlertSeverity::High,
+                _ => AlertSeverity::Low,
+            };
+            outcome.unmatched += 1;
+            outcome.discrepancies.push(ReconciliationDiscrepancy {
+                tenant_id: Some(order.tenant_id),
+                order_id: Some(order.id),
+                provider_order_id: order.provider_order_id.clone(),
+                local_amount: Some(order.fiat_amount),
+                local_currency: Some(order.fiat_currency.clone()),
+                local_status: Some(order.status.clone()),
+                ..base(
+                    DiscrepancyKind::MissingRemotely,
+                    severity,
+                    format!("Local {} order not found at {}", order.status, provider),
+                )
+            });
+            continue;
+        };
+
+        let pair = |kind, severity, detail| ReconciliationDiscrepancy {
+            tenant_id: Some(order.tenant_id),
+            order_id: Some(order.id),
+            provider_order_id: Some(remote_order.provider_order_id.clone()),
+            local_amount: Some(order.fiat_amount),
+            provider_amount: Some(remote_order.fiat_amount),
+            local_currency: Some(order.fiat_currency.clone()),
+            provider_currency: Some(remote_order.fiat_currency.clone()),
+            local_status: Some(order.status.clone()),
+            provider_status: Some(remote_order.status.clone()),
+            ..base(kind, severity, detail)
+        };
+
+        let mut clean = true;
+        let currency_matches = order
+            .fiat_currency
+            .eq_ignore_ascii_case(&remote_order.fiat_currency);
+        if !currency_matches || !rules.amounts_match(order.fiat_amount, remote_order.fiat_amount) {
+            clean = false;
+            outcome.discrepancies.push(pair(
+                DiscrepancyKind::AmountMismatch,
+                AlertSeverity::High,
+                format!(
+                    "Local {} {} vs provider {} {}",
+                    order.fiat_amount,
+                    order.fiat_currency,
+                    remote_order.fiat_amount,
+                    remote_order.fiat_currency
+                ),
+            ));
+        }
+
+        let (local_status, remote_status) = (order.status.as_str(), remote_order.status.as_str());
+        if local_status != remote_status
+            && !(is_in_flight(local_status) && is_in_flight(remote_status))
+        {
+            clean = false;
+            let severity = if local_status == "completed" && is_reversed(remote_status) {
+                AlertSeverity::Critical
+            } else if remote_status == "completed" {
+                AlertSeverity::High
+            } else {
+                AlertSeverity::Medium
+            };
+            outcome.discrepancies.push(pair(
+                DiscrepancyKind::StatusMismatch,
+                severity,
+                format!(
+                    "Local status {} vs provider status {} ({})",
+                    local_status, remote_status, remote_order.raw_status
+                ),
+            ));
+        }
+
+        if clean {
+            outcome.matched += 1;
+        } else {
+            outcome.unmatched += 1;
+        }
+    }
+
+    let mut orphans: Vec<&ProviderOrder> = remaining.into_values().collect();
+    orphans.sort_by(|a, b| a.provider_order_id.cmp(&b.provider_order_id));
+    for order in orphans {
+        let severity = match order.status.as_str() {
+            "completed" => AlertSeverity::Critical,
+            "processing" => AlertSeverity::High,
+            _ => AlertSeverity::Low,
+        };
+        outcome.unmatched += 1;
+        outcome.discrepancies.push(ReconciliationDiscrepancy {
+            provider_order_id: Some(order.provider_order_id.clone()),
+            provider_amount: Some(order.fiat_amount),
+            provider_currency: Some(order.fiat_currency.clone()),
+            provider_status: Some(order.status.clone()),
+            ..base(
+                DiscrepancyKind::MissingLocally,
+                severity,
+                format!("Provider {} order has no local record", order.status),
+            )
+        });
+    }
+
+    outcome
+}
+
+/// 导出差异为 CSV（财务对账用）
+pub fn discrepancies_to_csv(discrepancies: &[ReconciliationDiscrepancy]) -> String {
+    fn cell(value: &str) -> String {
+        // 防止表格软件将 = + - @ 开头的内容当作公式执行
+        let value = if value.starts_with(['=', '+', '-', '@']) {
+            format!("'{}", value)
+        } else {
+            value.to_string()
+        };
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    }
+    fn opt<T: ToString>(value: &Option<T>) -> String {
+        value
+            .as_ref()
+            .map(|v| cell(&v.to_string()))
+            .unwrap_or_default()
+    }
+
+    let mut csv = String::from(
+        "reconciliation_date,provider,kind,severity,order_id,provider_order_id,local_amount,provider_amount,local_currency,provider_currency,local_status,provider_status,detail\n",
+    );
+    for d in discrepancies {
+        let row = [
+            d.reconciliation_date.to_string(),
+            cell(&d.provider),
+            d.kind.as_str().to_string(),
+            d.severity.as_str().to_string(),
+            opt(&d.order_id),
+            opt(&d.provider_order_id),
+            opt(&d.local_amount),
+            opt(&d.provider_amount),
+            opt(&d.local_currency),
+            opt(&d.provider_currency),
+            opt(&d.local_status),
+            opt(&d.provider_status),
+            cell(&d.detail),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// 已配置凭证的服务商对账数据源
+pub fn default_sources() -> Vec<Arc<dyn ProviderReconciliationSource>> {
+    let mut sources: Vec<Arc<dyn ProviderReconciliationSource>> = Vec::new();
+    if let Ok(api_key) = std::env::var("ONRAMPER_API_KEY") {
+        match OnramperClient::new(&api_key) {
+            Ok(client) => sources.push(Arc::new(client)),
+            Err(e) => tracing::warn!("Onramper reconciliation source disabled: {}", e),
+        }
+    }
+    if let (Ok(api_key), Ok(secret)) = (
+        std::env::var("TRANSFI_API_KEY"),
+        std::env::var("TRANSFI_SECRET"),
+    ) {
+        match TransFiClient::new(&api_key, &secret) {
+            Ok(client) => sources.push(Arc::new(client)),
+            Err(e) => tracing::warn!("TransFi reconciliation source disabled: {}", e),
+        }
+    }
+    sources
+}
+
 pub struct ReconciliationService {
     pool: PgPool,
+    sources: HashMap<String, Arc<dyn ProviderReconciliationSource>>,
+    rules: ToleranceRules,
 }
 
 impl ReconciliationService {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self::with_sources(pool, default_sources())
+    }
+
+    pub fn with_sources(pool: PgPool, sources: Vec<Arc<dyn ProviderReconciliationSource>>) -> Self {
+        Self {
+            pool,
+            sources: sources
+                .into_iter()
+                .map(|s| (s.provider_name().to_string(), s))
+                .collect(),
+            rules: ToleranceRules::from_env(),
+        }
+    }
+
+    pub fn with_rules(mut self, rules: ToleranceRules) -> Self {
+        self.rules = rules;
+        self
     }
 
     /// 执行每日对账
+    ///
+    /// 逐个服务商翻页拉取当日订单并与本地比对，差异逐单入库；
+    /// high / critical 差异逐条告警，另附一条汇总告警。
     pub async fn run_daily_reconciliation(
         &self,
         date: Option<NaiveDate>,
@@ -56,21 +452,28 @@ impl ReconciliationService {
         let reconciliation_date = date.unwrap_or_else(|| Utc::now().date_naive());
         let started_at = Utc::now();
 
-        // 获取所有或指定服务商
+        // 获取所有或指定服务商（本地有订单的 + 已配置数据源的）
         let providers: Vec<String> = if let Some(p) = provider {
             vec![p.to_string()]
         } else {
-            sqlx::query_scalar::<_, String>(
+            let mut providers = sqlx::query_scalar::<_, String>(
                 "SELECT DISTINCT provider FROM fiat.orders WHERE DATE(created_at) = $1",
             )
             .bind(reconciliation_date)
             .fetch_all(&self.pool)
-            .await?
+            .await?;
+            providers.extend(self.sources.keys().cloned());
+            providers.sort();
+            providers.dedup();
+            providers
         };
 
         let mut total_matched = 0;
         let mut total_unmatched = 0;
         let mut total_orders = 0;
+        let mut discrepancies = Vec::new();
+        let mut errors = Vec::new();
+        let mut summary = serde_json::Map::new();
 
         for provider_name in &providers {
             // 1. 从本地数据库获取订单
@@ -79,46 +482,85 @@ impl ReconciliationService {
                 .await?;
             total_orders += local_orders.len() as i64;
 
-            // 2. 从第三方服务商获取订单（真实API调用）
-            let provider_orders = self
-                .fetch_provider_orders(provider_name, reconciliation_date)
-                .await?;
+            // 2. 从第三方服务商翻页获取订单（真实API调用）
+            let provider_orders = match self.sources.get(provider_name) {
+                Some(source) => fetch_all_orders(source.as_ref(), reconciliation_date).await,
+                None => Err(anyhow::anyhow!("no reconciliation source configured")),
+            };
+            let provider_orders = match provider_orders {
+                Ok(orders) => orders,
+                Err(e) => {
+                    tracing::error!(
+                        provider = %provider_name,
+                        error = %e,
+                        "Failed to fetch provider orders for reconciliation"
+                    );
+                    // 无法核对的本地订单全部计为未匹配
+                    total_unmatched += local_orders.len() as i64;
+                    errors.push(format!("{}: {}", provider_name, e));
+                    summary.insert(
+                        provider_name.clone(),
+                        serde_json::json!({ "local": local_orders.len(), "error": e.to_string() }),
+                    );
+                    continue;
+                }
+            };
 
             // 3. 对比订单
-            let (matched, unmatched) = self.compare_orders(&local_orders, &provider_orders).await?;
-            total_matched += matched;
-            total_unmatched += unmatched;
+            let outcome = compare_orders(
+                reconciliation_date,
+                provider_name,
+                &local_orders,
+                &provider_orders,
+                &self.rules,
+            );
+            total_matched += outcome.matched;
+            total_unmatched += outcome.unmatched;
+            summary.insert(
+                provider_name.clone(),
+                serde_json::json!({
+                    "local": local_orders.len(),
+                    "remote": provider_orders.len(),
+                    "matched": outcome.matched,
+                    "discrepancies": outcome.discrepancies.len(),
+                }),
+            );
+            discrepancies.extend(outcome.discrepancies);
         }
 
-        // 4. 创建对账记录
-        let status = if total_unmatched == 0 {
+        // 4. 创建对账记录 + 差异明细（同一事务；重跑同日对账覆盖旧明细）
+        let status = if !providers.is_empty() && errors.len() == providers.len() {
+            "failed"
+        } else if total_unmatched == 0 && errors.is_empty() {
             "completed"
         } else {
             "completed_with_issues"
         };
+        let error_message = (!errors.is_empty()).then(|| errors.join("; "));
 
-        let reconciliation_id = Uuid::new_v4();
-        let completed_at = Utc::now();
-
-        sqlx::query(
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
             r#"
             INSERT INTO fiat.reconciliation_records (
                 id, reconciliation_date, provider, total_orders,
                 matched_orders, unmatched_orders, status,
-                started_at, completed_at
+                started_at, completed_at, error_message, metadata
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             ON CONFLICT (reconciliation_date, provider)
             DO UPDATE SET
                 total_orders = EXCLUDED.total_orders,
                 matched_orders = EXCLUDED.matched_orders,
                 unmatched_orders = EXCLUDED.unmatched_orders,
                 status = EXCLUDED.status,
-                completed_at = EXCLUDED.completed_at
+                started_at = EXCLUDED.started_at,
+                completed_at = EXCLUDED.completed_at,
+                error_message = EXCLUDED.error_message,
+                metadata = EXCLUDED.metadata
             RETURNING *
             "#,
         )
-        .bind(reconciliation_id)
+        .bind(Uuid::new_v4())
         .bind(reconciliation_date)
         .bind(provider.unwrap_or("all"))
         .bind(total_orders)
@@ -126,33 +568,147 @@ impl ReconciliationService {
         .bind(total_unmatched)
         .bind(status)
         .bind(started_at)
-        .bind(completed_at)
-        .fetch_one(&self.pool)
+        .bind(Utc::now())
+        .bind(&error_message)
+        .bind(serde_json::json!({ "providers": summary }))
+        .fetch_one(&mut *tx)
         .await
         .context("Failed to create reconciliation record")?;
+        let record = self.row_to_reconciliation_record(row)?;
+
+        sqlx::query("DELETE FROM fiat.reconciliation_discrepancies WHERE reconciliation_id = $1")
+            .bind(record.id)
+            .execute(&mut *tx)
+            .await?;
+        for d in &discrepancies {
+            sqlx::query(
+                r#"
+                INSERT INTO fiat.reconciliation_discrepancies (
+                    reconciliation_id, reconciliation_date, provider, kind, severity,
+                    order_id, provider_order_id, local_amount, provider_amount,
+                    local_currency, provider_currency, local_status, provider_status, detail
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                "#,
+            )
+            .bind(record.id)
+            .bind(d.reconciliation_date)
+            .bind(&d.provider)
+            .bind(d.kind.as_str())
+            .bind(d.severity.as_str())
+            .bind(d.order_id)
+            .bind(&d.provider_order_id)
+            .bind(d.local_amount)
+            .bind(d.provider_amount)
+            .bind(&d.local_currency)
+            .bind(&d.provider_currency)
+            .bind(&d.local_status)
+            .bind(&d.provider_status)
+            .bind(&d.detail)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record reconciliation discrepancy")?;
+        }
+        tx.commit().await?;
 
-        // 5. 如果有不匹配的订单，创建告警
-        if total_unmatched > 0 {
+        // 5. 告警：high / critical 差异逐单告警 + 汇总告警
+        for d in discrepancies
+            .iter()
+            .filter(|d| d.severity >= AlertSeverity::High)
+        {
             self.create_alert(
-                None, // tenant_id
-                "order_mismatch",
-                "high",
+                d.tenant_id,
+                &format!("reconciliation_{}", d.kind.as_str()),
+                d.severity.as_str(),
                 &format!(
-                    "Found {} unmatched orders in reconciliation",
-                    total_unmatched
+                    "{} {}: {}",
+                    d.provider,
+                    d.provider_order_id.as_deref().unwrap_or("-"),
+                    d.detail
                 ),
+                d.order_id,
+                Some(&d.provider),
+            )
+            .await?;
+        }
+        if total_unmatched > 0 || !errors.is_empty() {
+            let severity = discrepancies
+                .iter()
+                .map(|d| d.severity)
+                .chain((!errors.is_empty()).then_some(AlertSeverity::High))
+                .max()
+                .unwrap_or(AlertSeverity::Medium);
+            let mut message = format!(
+                "Reconciliation {}: {} unmatched orders, {} discrepancies",
+                reconciliation_date,
+                total_unmatched,
+                discrepancies.len()
+            );
+            if let Some(err) = &error_message {
+                message.push_str(&format!("; provider errors: {}", err));
+            }
+            self.create_alert(
+                None, // tenant_id
+                "order_mismatch",
+                severity.as_str(),
+                &message,
                 None,
                 provider,
             )
             .await?;
         }
 
-        let row = sqlx::query("SELECT * FROM fiat.reconciliation_records WHERE id = $1")
-            .bind(reconciliation_id)
-            .fetch_one(&self.pool)
-            .await?;
+        Ok(record)
+    }
+
+    /// 查询某日对账差异（`provider` 为空时返回全部服务商）
+    pub async fn list_discrepancies(
+        &self,
+        date: NaiveDate,
+        provider: Option<&str>,
+    ) -> Result<Vec<ReconciliationDiscrepancy>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.reconciliation_date, d.provider, d.kind, d.severity, o.tenant_id,
+                   d.order_id, d.provider_order_id, d.local_amount, d.provider_amount,
+                   d.local_currency, d.provider_currency, d.local_status, d.provider_status,
+                   d.detail
+            FROM fiat.reconciliation_discrepancies d
+            LEFT JOIN fiat.orders o ON o.id = d.order_id
+            WHERE d.reconciliation_date = $1
+                AND ($2::TEXT IS NULL OR d.provider = $2)
+            ORDER BY d.provider, d.created_at, d.id
+            "#,
+        )
+        .bind(date)
+        .bind(provider)
+        .fetch_all(&self.pool)
+        .await?;
 
-        self.row_to_reconciliation_record(row)
+        rows.into_iter()
+            .map(|row| {
+                let kind: String = row.try_get("kind")?;
+                let severity: String = row.try_get("severity")?;
+                Ok(ReconciliationDiscrepancy {
+                    reconciliation_date: row.try_get("reconciliation_date")?,
+                    provider: row.try_get("provider")?,
+                    kind: DiscrepancyKind::parse(&kind)
+                        .with_context(|| format!("Unknown discrepancy kind {}", kind))?,
+                    severity: AlertSeverity::parse(&severity)
+                        .with_context(|| format!("Unknown severity {}", severity))?,
+                    tenant_id: row.try_get("tenant_id")?,
+                    order_id: row.try_get("order_id")?,
+                    provider_order_id: row.try_get("provider_order_id")?,
+                    local_amount: row.try_get("local_amount")?,
+                    provider_amount: row.try_get("provider_amount")?,
+                    local_currency: row.try_get("local_currency")?,
+                    provider_currency: row.try_get("provider_currency")?,
+                    local_status: row.try_get("local_status")?,
+                    provider_status: row.try_get("provider_status")?,
+                    detail: row.try_get("detail")?,
+                })
+            })
+            .collect()
     }
 
     /// 同步订单状态
@@ -312,16 +868,33 @@ impl ReconciliationService {
         Ok(alert_id)
     }
 
+    /// 将未关闭的告警置为已恢复，返回是否有更新
+    pub async fn resolve_alert(&self, alert_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE fiat.alerts
+            SET status = 'resolved'
+            WHERE id = $1 AND status IN ('open', 'acknowledged')
+            "#,
+        )
+        .bind(alert_id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to resolve alert")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     // === 私有辅助方法 ===
 
     async fn get_local_orders_for_date(
         &self,
         date: NaiveDate,
         provider: &str,
-    ) -> Result<Vec<(Uuid, String, rust_decimal::Decimal)>> {
+    ) -> Result<Vec<LocalOrder>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, provider_order_id, fiat_amount
+            SELECT id, tenant_id, provider_order_id, fiat_amount, fiat_currency, status
             FROM fiat.orders
             WHERE DATE(created_at) = $1 AND provider = $2
             "#,
@@ -333,65 +906,17 @@ impl ReconciliationService {
 
         let mut orders = Vec::new();
         for row in rows {
-            orders.push((
-                row.try_get("id")?,
-                row.try_get::<Option<String>, _>("provider_order_id")?
-                    .unwrap_or_default(),
-                row.try_get("fiat_amount")?,
-            ));
-        }
-
-        Ok(orders)
-    }
-
-    async fn fetch_provider_orders(
-        &self,
-        provider: &str,
-        date: NaiveDate,
-    ) -> Result<Vec<(String, rust_decimal::Decimal, String)>> {
-        // 真实API调用第三方服务商获取订单列表
-        // 这里需要根据provider调用对应的API
-        // - Ramp: https://api.ramp.network/api/host-api/transactions
-        // - MoonPay: https://api.moonpay.com/v1/transactions
-        // - Transak: https://api.transak.com/api/v2/orders
-
-        // 简化实现：返回空列表（生产环境需要真实API调用）
-        tracing::info!(
-            "Fetching orders from provider {} for date {}",
-            provider,
-            date
-        );
-        Ok(Vec::new())
-    }
-
-    async fn compare_orders(
-        &self,
-        local: &[(Uuid, String, rust_decimal::Decimal)],
-        provider: &[(String, rust_decimal::Decimal, String)],
-    ) -> Result<(i32, i32)> {
-        let mut matched = 0;
-        let mut unmatched = 0;
-
-        for (local_id, local_po_id, local_amount) in local {
-            let found = provider.iter().any(|(po_id, amount, _)| {
-                po_id == local_po_id
-                    && (amount - local_amount).abs()
-                        < rust_decimal::Decimal::from_str("0.01").unwrap_or_default()
+            orders.push(LocalOrder {
+                id: row.try_get("id")?,
+                tenant_id: row.try_get("tenant_id")?,
+                provider_order_id: row.try_get("provider_order_id")?,
+                fiat_amount: row.try_get("fiat_amount")?,
+                fiat_currency: row.try_get("fiat_currency")?,
+                status: row.try_get("status")?,
             });
-
-            if found {
-                matched += 1;
-            } else {
-                unmatched += 1;
-                tracing::warn!(
-                    "Unmatched order: {} (provider_order_id: {})",
-                    local_id,
-                    local_po_id
-                );
-            }
         }
 
-        Ok((matched, unmatched))
+        Ok(orders)
     }
 
     async fn fetch_provider_order_status(
@@ -430,3 +955,115 @@ impl ReconciliationService {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(po_id: Option<&str>, amount: &str, currency: &str, status: &str) -> LocalOrder {
+        LocalOrder {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            provider_order_id: po_id.map(str::to_string),
+            fiat_amount: amount.parse().unwrap(),
+            fiat_currency: currency.to_string(),
+            status: status.to_string(),
+        }
+    }
+
+    fn remote(po_id: &str, amount: &str, currency: &str, status: &str) -> ProviderOrder {
+        ProviderOrder {
+            provider_order_id: po_id.to_string(),
+            fiat_amount: amount.parse().unwrap(),
+            fiat_currency: currency.to_string(),
+            status: status.to_string(),
+            raw_status: status.to_string(),
+            created_at: None,
+        }
+    }
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+    }
+
+    #[test]
+    fn test_compare_orders_classifies_discrepancies() {
+        let local_orders = vec![
+            // 容差内（0.01）且进行中状态视为一致
+            local(Some("A"), "100.00", "USD", "pending"),
+            local(Some("B"), "50.00", "USD", "completed"),
+            local(Some("C"), "20.00", "EUR", "completed"),
+            local(Some("D"), "10.00", "USD", "completed"),
+            local(None, "5.00", "USD", "pending"),
+        ];
+        let remote_orders = vec![
+            remote("A", "100.005", "usd", "processing"),
+            remote("B", "49.00", "USD", "completed"),
+            remote("C", "20.00", "EUR", "refunded"),
+            remote("E", "75.00", "USD", "completed"),
+        ];
+
+        let outcome = compare_orders(
+            date(),
+            "onramper",
+            &local_orders,
+            &remote_orders,
+            &ToleranceRules::default(),
+        );
+        assert_eq!(outcome.matched, 1);
+        assert_eq!(outcome.unmatched, 5);
+
+        let find = |kind: DiscrepancyKind, po: Option<&str>| {
+            outcome
+                .discrepancies
+                .iter()
+                .find(|d| d.kind == kind && d.provider_order_id.as_deref() == po)
+                .unwrap_or_else(|| panic!("missing {:?} {:?}", kind, po))
+        };
+        assert_eq!(
+            find(DiscrepancyKind::AmountMismatch, Some("B")).severity,
+            AlertSeverity::High
+        );
+        let reversed = find(DiscrepancyKind::StatusMismatch, Some("C"));
+        assert_eq!(reversed.severity, AlertSeverity::Critical);
+        assert_eq!(reversed.provider_status.as_deref(), Some("refunded"));
+        assert_eq!(
+            find(DiscrepancyKind::MissingRemotely, Some("D")).severity,
+            AlertSeverity::Critical
+        );
+        assert_eq!(
+            find(DiscrepancyKind::MissingRemotely, None).severity,
+            AlertSeverity::Low
+        );
+        let orphan = find(DiscrepancyKind::MissingLocally, Some("E"));
+        assert_eq!(orphan.severity, AlertSeverity::Critical);
+        assert!(orphan.order_id.is_none());
+
+        // 相对容差：1% 覆盖 B 的 1.00 差额
+        let relaxed = ToleranceRules {
+            amount_abs: Decimal::ZERO,
+            amount_bps: Decimal::from(200),
+        };
+        assert!(relaxed.amounts_match("50.00".parse().unwrap(), "49.00".parse().unwrap()));
+        assert!(!relaxed.amounts_match("50.00".parse().unwrap(), "48.00".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_discrepancies_csv_export() {
+        let outcome = compare_orders(
+            date(),
+            "transfi",
+            &[],
+            &[remote("=HYPERLINK(\"x\")", "1000.5", "CNY", "completed")],
+            &ToleranceRules::default(),
+        );
+        let csv = discrepancies_to_csv(&outcome.discrepancies);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("reconciliation_date,provider,kind,severity"));
+        assert_eq!(
+            lines[1],
+            "2026-10-16,transfi,missing_locally,critical,,\"'=HYPERLINK(\"\"x\"\")\",,1000.5,,CNY,,completed,Provider completed order has no local record"
+        );
+    }
+}
@@ -0,0 +1,714 @@
+//! 提现风控规则引擎
+//! 企业级实现：租户策略 JSON → 编译校验 → 对提现上下文求值（纯函数，可用于 dry-run）
+//!
+//! 策略格式：
+//! ```json
+//! {
+//!   "rules": [
+//!     { "id": "LARGE_AMOUNT", "level": "high", "when": { "amount_usd": { "gte": 10000 } } },
+//!     { "id": "NIGHT_BURST", "level": "medium", "when": { "all": [
+//!         { "time_window": { "start_hour": 22, "end_hour": 6, "utc_offset_minutes": 480 } },
+//!         { "velocity": { "window_hours": 1, "max_count": 3 } }
+//!     ] } },
+//!     { "id": "SANCTIONED_GEO", "level": "reject", "when": { "country": { "in": ["KP", "IR"] } } }
+//!   ],
+//!   "approval": { "required_approvals": 2, "approver_roles": ["admin", "operator"], "ttl_hours": 24 }
+//! }
+//! ```
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::service::withdrawal_risk_control::RiskLevel;
+
+/// 策略编译错误
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyCompileError {
+    #[error("Invalid policy document: {0}")]
+    Malformed(String),
+    #[error("Policy must contain at least one rule")]
+    Empty,
+    #[error("Duplicate rule id: {0}")]
+    DuplicateRule(String),
+    #[error("Rule {rule}: {reason}")]
+    InvalidRule { rule: String, reason: String },
+    #[error("Approval: {0}")]
+    InvalidApproval(String),
+}
+
+/// 策略文档（policies.rules）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyDocument {
+    pub rules: Vec<RuleSpec>,
+    /// 命中 High（人工审核）时的多方审批要求，未配置则使用平台默认
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<ApprovalSpec>,
+}
+
+/// 可投票的 RBAC 角色（viewer 无审批权）
+const APPROVER_ROLES: [&str; 2] = ["admin", "operator"];
+const MAX_REQUIRED_APPROVALS: u32 = 10;
+const MAX_APPROVAL_TTL_HOURS: u32 = 24 * 30;
+
+/// M-of-N 审批要求
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalSpec {
+    /// 需要的同意票数（M）
+    pub required_approvals: u32,
+    /// 有权投票的角色（N 为租户内持有这些角色的用户）
+    #[serde(default = "default_approver_roles")]
+    pub approver_roles: Vec<String>,
+    /// 超时未决则自动拒绝
+    #[serde(default = "default_approval_ttl_hours")]
+    pub ttl_hours: u32,
+}
+
+fn default_approver_roles() -> Vec<String> {
+    APPROVER_ROLES.iter().map(|r| r.to_string()).collect()
+}
+
+fn default_approval_ttl_hours() -> u32 {
+    24
+}
+
+impl ApprovalSpec {
+    /// 平台默认（APPROVAL_REQUIRED_COUNT / APPROVAL_APPROVER_ROLES / APPROVAL_TTL_HOURS）
+    pub fn platform_default() -> Self {
+        let env_u32 = |key: &str, default: u32| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default)
+        };
+        let approver_roles = std::env::var("APPROVAL_APPROVER_ROLES")
+            .ok()
+            .map(|v| {
+                v.split(',')
+                    .map(|r| r.trim().to_lowercase())
+                    .filter(|r| APPROVER_ROLES.contains(&r.as_str()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|roles| !roles.is_empty())
+            .unwrap_or_else(default_approver_roles);
+
+        Self {
+            required_approvals: env_u32("APPROVAL_REQUIRED_COUNT", 2)
+                .clamp(1, MAX_REQUIRED_APPROVALS),
+            approver_roles,
+            ttl_hours: env_u32("APPROVAL_TTL_HOURS", default_approval_ttl_hours())
+                .clamp(1, MAX_APPROVAL_TTL_HOURS),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_REQUIRED_APPROVALS).contains(&self.required_approvals) {
+            return Err(format!(
+                "required_approvals must be between 1 and {}",
+                MAX_REQUIRED_APPROVALS
+            ));
+        }
+        if self.approver_roles.is_empty() {
+            return Err("approver_roles must not be empty".into());
+        }
+        if let Some(role) = self
+            .approver_roles
+            .iter()
+            .find(|r| !APPROVER_ROLES.contains(&r.as_str()))
+        {
+            return Err(format!("role {} cannot approve", role));
+        }
+        if !(1..=MAX_APPROVAL_TTL_HOURS).contains(&self.ttl_hours) {
+            return Err(format!(
+                "ttl_hours must be between 1 and {}",
+                MAX_APPROVAL_TTL_HOURS
+            ));
+        }
+        Ok(())
+    }
+
+    /// 多个策略同时启用时取最严格的组合：票数取大、角色取交集、时限取小
+    pub fn merge(self, other: ApprovalSpec) -> ApprovalSpec {
+        let mut approver_roles: Vec<String> = self
+            .approver_roles
+            .into_iter()
+            .filter(|r| other.approver_roles.contains(r))
+            .collect();
+        if approver_roles.is_empty() {
+            approver_roles.push("admin".to_string());
+        }
+        ApprovalSpec {
+            required_approvals: self.required_approvals.max(other.required_approvals),
+            approver_roles,
+            ttl_hours: self.ttl_hours.min(other.ttl_hours),
+        }
+    }
+}
+
+/// 单条规则：条件成立时将风险等级提升到 `level`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSpec {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub level: RiskLevel,
+    pub when: Predicate,
+}
+
+/// 数值区间（至少指定一个边界）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Range {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gte: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lte: Option<f64>,
+}
+
+impl Range {
+    fn is_empty(&self) -> bool {
+        self.gt.is_none() && self.gte.is_none() && self.lt.is_none() && self.lte.is_none()
+    }
+
+    fn contains(&self, v: f64) -> bool {
+        self.gt.is_none_or(|b| v > b)
+            && self.gte.is_none_or(|b| v >= b)
+            && self.lt.is_none_or(|b| v < b)
+            && self.lte.is_none_or(|b| v <= b)
+    }
+}
+
+/// 频率限制：窗口内（含本笔）次数或金额超过上限时命中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VelocitySpec {
+    pub window_hours: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount_usd: Option<f64>,
+}
+
+/// 时间窗口 [start_hour, end_hour)，支持跨午夜（如 22 → 6）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TimeWindowSpec {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+/// 地理位置（ISO 3166-1 alpha-2）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CountrySpec {
+    #[serde(rename = "in")]
+    pub countries: Vec<String>,
+    /// 无法识别国家时是否视为命中（默认否）
+    #[serde(default)]
+    pub unknown_matches: bool,
+}
+
+/// 规则条件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Predicate {
+    All(Vec<Predicate>),
+    Any(Vec<Predicate>),
+    Not(Box<Predicate>),
+    AmountUsd(Range),
+    AccountAgeDays(Range),
+    Velocity(VelocitySpec),
+    TimeWindow(TimeWindowSpec),
+    ToAddressIn(Vec<String>),
+    ChainIn(Vec<String>),
+    Country(CountrySpec),
+    AddressBlacklisted(bool),
+    SuspiciousActivity(bool),
+}
+
+const MAX_VELOCITY_WINDOW_HOURS: u32 = 24 * 90;
+
+impl Predicate {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Predicate::All(items) | Predicate::Any(items) => {
+                if items.is_empty() {
+                    return Err("combinator must contain at least one condition".into());
+                }
+                items.iter().try_for_each(Predicate::validate)
+            }
+            Predicate::Not(inner) => inner.validate(),
+            Predicate::AmountUsd(range) | Predicate::AccountAgeDays(range) => {
+                if range.is_empty() {
+                    return Err("range requires at least one of gt/gte/lt/lte".into());
+                }
+                Ok(())
+            }
+            Predicate::Velocity(v) => {
+                if v.window_hours == 0 || v.window_hours > MAX_VELOCITY_WINDOW_HOURS {
+                    return Err(format!(
+                        "velocity.window_hours must be 1..={}",
+                        MAX_VELOCITY_WINDOW_HOURS
+                    ));
+                }
+                if v.max_count.is_none() && v.max_amount_usd.is_none() {
+                    return Err("velocity requires max_count or max_amount_usd".into());
+                }
+                Ok(())
+            }
+            Predicate::TimeWindow(t) => {
+                if t.start_hour > 23 || t.end_hour > 24 || t.start_hour == t.end_hour {
+                    return Err("time_window hours must be 0..=23 / 0..=24 and not equal".into());
+                }
+                if t.utc_offset_minutes.abs() > 14 * 60 {
+                    return Err("time_window.utc_offset_minutes out of range".into());
+                }
+                Ok(())
+            }
+            Predicate::ToAddressIn(list) | Predicate::ChainIn(list) => {
+                if list.is_empty() {
+                    return Err("list must not be empty".into());
+                }
+                Ok(())
+            }
+            Predicate::Country(c) => {
+                if c.countries.iter().any(|code| code.len() != 2) {
+                    return Err("country codes must be ISO 3166-1 alpha-2".into());
+                }
+                Ok(())
+            }
+            Predicate::AddressBlacklisted(_) | Predicate::SuspiciousActivity(_) => Ok(()),
+        }
+    }
+
+    fn collect_velocity_windows(&self, out: &mut BTreeSet<u32>) {
+        match self {
+            Predicate::All(items) | Predicate::Any(items) => {
+                items.iter().for_each(|p| p.collect_velocity_windows(out))
+            }
+            Predicate::Not(inner) => inner.collect_velocity_windows(out),
+            Predicate::Velocity(v) => {
+                out.insert(v.window_hours);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn evaluate(&self, ctx: &RiskContext) -> bool {
+        match self {
+            Predicate::All(items) => items.iter().all(|p| p.evaluate(ctx)),
+            Predicate::Any(items) => items.iter().any(|p| p.evaluate(ctx)),
+            Predicate::Not(inner) => !inner.evaluate(ctx),
+            Predicate::AmountUsd(range) => range.contains(ctx.amount_usd),
+            Predicate::AccountAgeDays(range) => range.contains(ctx.account_age_days as f64),
+            Predicate::Velocity(v) => {
+                let stats = ctx
+                    .velocity
+                    .get(&v.window_hours)
+                    .copied()
+                    .unwrap_or_default();
+                v.max_count.is_some_and(|max| stats.count + 1 > max)
+                    || v.max_amount_usd
+                        .is_some_and(|max| stats.total_usd + ctx.amount_usd > max)
+            }
+            Predicate::TimeWindow(t) => {
+                let local = ctx.at + Duration::minutes(t.utc_offset_minutes as i64);
+                let hour = local.hour();
+                if t.start_hour < t.end_hour {
+                    (t.start_hour..t.end_hour).contains(&hour)
+                } else {
+                    hour >= t.start_hour || hour < t.end_hour
+                }
+            }
+            Predicate::ToAddressIn(list) => list
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(ctx.to_address.trim())),
+            Predicate::ChainIn(list) => list.iter().any(|c| c.eq_ignore_ascii_case(&ctx.chain)),
+            Predicate::Country(c) => match ctx.country.as_deref() {
+                Some(country) => c.countries.iter().any(|x| x.eq_ignore_ascii_case(country)),
+                None => c.unknown_matches,
+            },
+            Predicate::AddressBlacklisted(expected) => ctx.address_blacklisted == *expected,
+            Predicate::SuspiciousActivity(expected) => ctx.suspicious_activity == *expected,
+        }
+    }
+}
+
+/// 编译并校验策略 JSON
+pub fn compile_policy(rules: &serde_json::Value) -> Result<PolicyDocument, PolicyCompileError> {
+    let doc: PolicyDocument = serde_json::from_value(rules.clone())
+        .map_err(|e| PolicyCompileError::Malformed(e.to_string()))?;
+
+    if doc.rules.is_empty() {
+        return Err(PolicyCompileError::Empty);
+    }
+
+    let mut seen = HashSet::new();
+    for rule in &doc.rules {
+        if rule.id.trim().is_empty() {
+            return Err(PolicyCompileError::InvalidRule {
+                rule: rule.id.clone(),
+                reason: "id must not be empty".into(),
+            });
+        }
+        if !seen.insert(rule.id.as_str()) {
+            return Err(PolicyCompileError::DuplicateRule(rule.id.clone()));
+        }
+        if rule.level == RiskLevel::Low {
+            return Err(PolicyCompileError::InvalidRule {
+                rule: rule.id.clone(),
+                reason: "level must be medium, high or reject".into(),
+            });
+        }
+        rule.when
+            .validate()
+            .map_err(|reason| PolicyCompileError::InvalidRule {
+                rule: rule.id.clone(),
+                reason,
+            })?;
+    }
+
+    if let Some(approval) = &doc.approval {
+        approval
+            .validate()
+            .map_err(PolicyCompileError::InvalidApproval)?;
+    }
+
+    Ok(doc)
+}
+
+/// 平台默认策略（租户未启用任何策略时使用，对应原三级风控阈值）
+pub fn default_policy_rules() -> serde_json::Value {
+    serde_json::json!({
+        "rules": [
+            { "id": "AMOUNT_EXCEEDS_10K", "level": "high", "when": { "amount_usd": { "gte": 10000.0 } } },
+            { "id": "AMOUNT_EXCEEDS_1K", "level": "medium", "when": { "amount_usd": { "gte": 1000.0, "lt": 10000.0 } } },
+            { "id": "DAILY_LIMIT_EXCEEDED", "level": "medium", "when": { "velocity": { "window_hours": 24, "max_amount_usd": 5000.0 } } },
+            { "id": "UNUSUAL_TIME", "level": "medium", "when": { "time_window": { "start_hour": 2, "end_hour": 6 } } },
+            { "id": "FREQUENT_WITHDRAWALS", "level": "medium", "when": { "velocity": { "window_hours": 24, "max_count": 5 } } },
+            { "id": "NEW_USER_ACCOUNT", "level": "medium", "when": { "account_age_days": { "lt": 7.0 } } }
+        ]
+    })
+}
+
+/// 平台基线规则（始终生效，租户策略不可覆盖）
+pub fn baseline_rules() -> Vec<RuleSpec> {
+    vec![
+        RuleSpec {
+            id: "BLACKLISTED_ADDRESS".into(),
+            description: None,
+            level: RiskLevel::Reject,
+            when: Predicate::AddressBlacklisted(true),
+        },
+        RuleSpec {
+            id: "SUSPICIOUS_ACTIVITY".into(),
+            description: None,
+            level: RiskLevel::High,
+            when: Predicate::SuspiciousActivity(true),
+        },
+    ]
+}
+
+/// 规则来源策略
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyRef {
+    pub policy_id: Uuid,
+    pub name: String,
+    pub version: i32,
+}
+
+/// 窗口内历史提现统计（不含本笔）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VelocityStats {
+    pub count: i64,
+    pub total_usd: f64,
+}
+
+/// 规则求值上下文
+#[derive(Debug, Clone)]
+pub struct RiskContext {
+    pub amount_usd: f64,
+    pub chain: String,
+    pub to_address: String,
+    pub at: DateTime<Utc>,
+    pub account_age_days: i64,
+    pub country: Option<String>,
+    pub address_blacklisted: bool,
+    pub suspicious_activity: bool,
+    /// window_hours → 统计
+    pub velocity: HashMap<u32, VelocityStats>,
+}
+
+/// 求值结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleOutcome {
+    pub risk_level: RiskLevel,
+    pub triggered_rules: Vec<String>,
+}
+
+/// 已编译规则集（基线 + 租户策略）
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<RuleSpec>,
+    pub policies: Vec<PolicyRef>,
+    /// 策略声明的审批要求（已合并）
+    pub approval: Option<ApprovalSpec>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self {
+            rules: baseline_rules(),
+            policies: Vec::new(),
+            approval: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: Option<PolicyRef>, doc: PolicyDocument) -> Self {
+        self.rules.extend(doc.rules);
+        if let Some(spec) = doc.approval {
+            self.approval = Some(match self.approval.take() {
+                Some(current) => current.merge(spec),
+                None => spec,
+            });
+        }
+        if let Some(policy) = policy {
+            self.policies.push(policy);
+        }
+        self
+    }
+
+    /// 追加一条规则（如策略编译失败时的兜底规则）
+    pub fn with_rule(mut self, rule: RuleSpec) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// 需要预先查询的频率窗口
+    pub fn velocity_windows(&self) -> BTreeSet<u32> {
+        let mut windows = BTreeSet::new();
+        for rule in &self.rules {
+            rule.when.collect_velocity_windows(&mut windows);
+        }
+        windows
+    }
+
+    pub fn evaluate(&self, ctx: &RiskContext) -> RuleOutcome {
+        let mut risk_level = RiskLevel::Low;
+        let mut triggered_rules = Vec::new();
+
+        for rule in &self.rules {
+            if rule.when.evaluate(ctx) {
+                risk_level = std::cmp::max(risk_level, rule.level);
+                if !triggered_rules.contains(&rule.id) {
+                    triggered_rules.push(rule.id.clone());
+                }
+            }
+        }
+
+        RuleOutcome {
+            risk_level,
+            triggered_rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn ctx(amount_usd: f64) -> RiskContext {
+        RiskContext {
+            amount_usd,
+            chain: "ETH".into(),
+            to_address: "0xAbC".into(),
+            at: Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap(),
+            account_age_days: 30,
+            country: Some("US".into()),
+            address_blacklisted: false,
+            suspicious_activity: false,
+            velocity: HashMap::new(),
+        }
+    }
+
+    fn default_rules() -> RuleSet {
+        RuleSet::new().with_policy(None, compile_policy(&default_policy_rules()).unwrap())
+    }
+
+    #[test]
+    fn test_default_policy_matches_legacy_thresholds() {
+        let rules = default_rules();
+        assert_eq!(rules.evaluate(&ctx(500.0)).risk_level, RiskLevel::Low);
+
+        let medium = rules.evaluate(&ctx(1_500.0));
+        assert_eq!(medium.risk_level, RiskLevel::Medium);
+        assert_eq!(medium.triggered_rules, vec!["AMOUNT_EXCEEDS_1K"]);
+
+        let high = rules.evaluate(&ctx(12_000.0));
+        assert_eq!(high.risk_level, RiskLevel::High);
+        assert!(high
+            .triggered_rules
+            .contains(&"AMOUNT_EXCEEDS_10K".to_string()));
+        assert!(high
+            .triggered_rules
+            .contains(&"DAILY_LIMIT_EXCEEDED".to_string()));
+        assert!(!high
+            .triggered_rules
+            .contains(&"AMOUNT_EXCEEDS_1K".to_string()));
+
+        let mut night = ctx(10.0);
+        night.at = Utc.with_ymd_and_hms(2026, 1, 1, 3, 0, 0).unwrap();
+        assert_eq!(rules.evaluate(&night).triggered_rules, vec!["UNUSUAL_TIME"]);
+
+        let mut frequent = ctx(10.0);
+        frequent.velocity.insert(
+            24,
+            VelocityStats {
+                count: 5,
+                total_usd: 100.0,
+            },
+        );
+        assert_eq!(
+            rules.evaluate(&frequent).triggered_rules,
+            vec!["FREQUENT_WITHDRAWALS"]
+        );
+
+        let mut blacklisted = ctx(10.0);
+        blacklisted.address_blacklisted = true;
+        assert_eq!(rules.evaluate(&blacklisted).risk_level, RiskLevel::Reject);
+    }
+
+    #[test]
+    fn test_combinators_time_window_and_geo() {
+        let doc = compile_policy(&serde_json::json!({
+            "rules": [{
+                "id": "NIGHT_FOREIGN",
+                "level": "high",
+                "when": { "all": [
+                    { "time_window": { "start_hour": 22, "end_hour": 6, "utc_offset_minutes": 480 } },
+                    { "not": { "country": { "in": ["CN"] } } }
+                ] }
+            }]
+        }))
+        .unwrap();
+        let rules = RuleSet::new().with_policy(None, doc);
+
+        // 15:00 UTC = 23:00 UTC+8
+        let mut c = ctx(10.0);
+        c.at = Utc.with_ymd_and_hms(2026, 1, 1, 15, 0, 0).unwrap();
+        assert_eq!(rules.evaluate(&c).risk_level, RiskLevel::High);
+
+        c.country = Some("cn".into());
+        assert_eq!(rules.evaluate(&c).risk_level, RiskLevel::Low);
+
+        c.country = None; // 未知国家不命中 country → not 成立
+        assert_eq!(rules.evaluate(&c).risk_level, RiskLevel::High);
+
+        c.at = Utc.with_ymd_and_hms(2026, 1, 1, 1, 0, 0).unwrap(); // 09:00 UTC+8
+        assert_eq!(rules.evaluate(&c).risk_level, RiskLevel::Low);
+    }
+
+    #[test]
+    fn test_address_list_and_velocity_windows() {
+        let doc = compile_policy(&serde_json::json!({
+            "rules": [
+                { "id": "WATCHLIST", "level": "reject", "when": { "to_address_in": ["0xabc"] } },
+                { "id": "BURST", "level": "medium", "when": { "any": [
+                    { "velocity": { "window_hours": 1, "max_count": 2 } },
+                    { "velocity": { "window_hours": 168, "max_amount_usd": 50000 } }
+                ] } }
+            ]
+        }))
+        .unwrap();
+        let rules = RuleSet::new().with_policy(None, doc);
+
+        assert_eq!(
+            rules.velocity_windows().into_iter().collect::<Vec<_>>(),
+            vec![1, 168]
+        );
+        assert_eq!(rules.evaluate(&ctx(1.0)).risk_level, RiskLevel::Reject);
+
+        let mut c = ctx(1.0);
+        c.to_address = "0xdef".into();
+        c.velocity.insert(
+            168,
+            VelocityStats {
+                count: 1,
+                total_usd: 49_999.5,
+            },
+        );
+        assert_eq!(rules.evaluate(&c).triggered_rules, vec!["BURST"]);
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_policies() {
+        let cases = [
+            serde_json::json!({ "rules": [] }),
+            serde_json::json!({ "thresholds": {} }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "low", "when": { "amount_usd": { "gt": 1 } } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "amount_usd": {} } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "any": [] } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "velocity": { "window_hours": 0, "max_count": 1 } } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "time_window": { "start_hour": 25, "end_hour": 3 } } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "country": { "in": ["USA"] } } }] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "ip_reputation": 1 } }] }),
+            serde_json::json!({ "rules": [
+                { "id": "A", "level": "high", "when": { "amount_usd": { "gt": 1 } } },
+                { "id": "A", "level": "high", "when": { "amount_usd": { "gt": 2 } } }
+            ] }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "amount_usd": { "gt": 1 } } }],
+                "approval": { "required_approvals": 0 } }),
+            serde_json::json!({ "rules": [{ "id": "A", "level": "high", "when": { "amount_usd": { "gt": 1 } } }],
+                "approval": { "required_approvals": 2, "approver_roles": ["viewer"] } }),
+        ];
+
+        for case in cases {
+            assert!(compile_policy(&case).is_err(), "should reject: {}", case);
+        }
+    }
+
+    #[test]
+    fn test_approval_spec_merges_to_strictest() {
+        let rule = serde_json::json!({ "id": "A", "level": "high", "when": { "amount_usd": { "gt": 1 } } });
+        let a = compile_policy(&serde_json::json!({
+            "rules": [rule],
+            "approval": { "required_approvals": 2, "ttl_hours": 48 }
+        }))
+        .unwrap();
+        let b = compile_policy(&serde_json::json!({
+            "rules": [rule],
+            "approval": { "required_approvals": 3, "approver_roles": ["admin"], "ttl_hours": 12 }
+        }))
+        .unwrap();
+        assert_eq!(
+            a.approval.as_ref().unwrap().approver_roles,
+            vec!["admin", "operator"]
+        );
+
+        let rules = RuleSet::new().with_policy(None, a).with_policy(None, b);
+        assert_eq!(
+            rules.approval,
+            Some(ApprovalSpec {
+                required_approvals: 3,
+                approver_roles: vec!["admin".to_string()],
+                ttl_hours: 12,
+            })
+        );
+
+        let default = ApprovalSpec::platform_default();
+        assert!(default.validate().is_ok());
+    }
+}
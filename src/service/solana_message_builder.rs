@@ -0,0 +1,585 @@
+//! Solana 交易消息构建
+//!
+//! 企业级实现：构建未签名的 legacy 交易消息，由客户端以 Ed25519 签名
+//! - 原生 SOL：System Program `Transfer`
+//! - SPL 代币：关联代币账户（ATA）派生、`CreateAssociatedTokenAccountIdempotent`、`TransferChecked`
+//! - 优先费：Compute Budget `SetComputeUnitLimit` / `SetComputeUnitPrice`
+//! - RPC：`getLatestBlockhash` / `getAccountInfo` / `getTokenSupply` / `getRecentPrioritizationFees`
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGqPFXCWuBvf9Ss623VQ5DA";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// 每个签名的基础费用（lamports）
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// SPL Token 账户（165字节）的免租金最低余额（lamports）
+pub const TOKEN_ACCOUNT_RENT_EXEMPT_LAMPORTS: u64 = 2_039_280;
+
+/// 默认计算单元上限
+pub const NATIVE_TRANSFER_COMPUTE_UNITS: u32 = 1_000;
+pub const SPL_TRANSFER_COMPUTE_UNITS: u32 = 30_000;
+pub const CREATE_ATA_COMPUTE_UNITS: u32 = 35_000;
+
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+/// Solana 公钥（32字节）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey(pub [u8; 32]);
+
+impl Pubkey {
+    pub fn from_base58(value: &str) -> Result<Self> {
+        let bytes = bs58::decode(value.trim())
+            .into_vec()
+            .map_err(|_| anyhow::anyhow!("Invalid Solana address: {}", value))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Solana address must be 32 bytes: {}", value))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn to_base58(&self) -> String {
+        bs58::encode(self.0).into_string()
+    }
+
+    /// 是否为 Ed25519 曲线上的点（PDA 必须不在曲线上）
+    pub fn is_on_curve(&self) -> bool {
+        ed25519_dalek::VerifyingKey::from_bytes(&self.0).is_ok()
+    }
+
+    /// 派生程序地址（`find_program_address`）
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Result<(Pubkey, u8)> {
+        for bump in (0..=u8::MAX).rev() {
+            let mut hasher = Sha256::new();
+            for seed in seeds {
+                hasher.update(seed);
+            }
+            hasher.update([bump]);
+            hasher.update(program_id.0);
+            hasher.update(PDA_MARKER);
+            let candidate = Pubkey(hasher.finalize().into());
+            if !candidate.is_on_curve() {
+                return Ok((candidate, bump));
+            }
+        }
+        anyhow::bail!("Unable to find a viable program address bump seed")
+    }
+}
+
+fn program_id(address: &str) -> Pubkey {
+    Pubkey::from_base58(address).expect("valid built-in program id")
+}
+
+/// 派生关联代币账户地址
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+    let token_program = program_id(TOKEN_PROGRAM_ID);
+    Pubkey::find_program_address(
+        &[&owner.0, &token_program.0, &mint.0],
+        &program_id(ASSOCIATED_TOKEN_PROGRAM_ID),
+    )
+    .map(|(address, _)| address)
+}
+
+/// 指令引用的账户
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn writable(pubkey: Pubkey, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    pub fn readonly(pubkey: Pubkey, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+/// 交易指令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// Compute Budget `SetComputeUnitLimit`
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2u8];
+    data.extend(units.to_le_bytes());
+    Instruction {
+        program_id: program_id(COMPUTE_BUDGET_PROGRAM_ID),
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Compute Budget `SetComputeUnitPrice`（micro-lamports / CU）
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3u8];
+    data.extend(micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: program_id(COMPUTE_BUDGET_PROGRAM_ID),
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// System Program `Transfer`
+pub fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend(lamports.to_le_bytes());
+    Instruction {
+        program_id: program_id(SYSTEM_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::writable(*from, true),
+            AccountMeta::writable(*to, false),
+        ],
+        data,
+    }
+}
+
+/// Associated Token Program `CreateIdempotent`（账户已存在时不报错）
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction> {
+    Ok(Instruction {
+        program_id: program_id(ASSOCIATED_TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::writable(*payer, true),
+            AccountMeta::writable(associated_token_address(owner, mint)?, false),
+            AccountMeta::readonly(*owner, false),
+            AccountMeta::readonly(*mint, false),
+            AccountMeta::readonly(program_id(SYSTEM_PROGRAM_ID), false),
+            AccountMeta::readonly(program_id(TOKEN_PROGRAM_ID), false),
+        ],
+        data: vec![1],
+    })
+}
+
+/// SPL Token `TransferChecked`（校验 mint 与精度）
+pub fn spl_transfer_checked(
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    let mut data = vec![12u8];
+    data.extend(amount.to_le_bytes());
+    data.push(decimals);
+    Instruction {
+        program_id: program_id(TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::writable(*source, false),
+            AccountMeta::readonly(*mint, false),
+            AccountMeta::writable(*destination, false),
+            AccountMeta::readonly(*owner, true),
+        ],
+        data,
+    }
+}
+
+/// 已编译的 legacy 消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed: u8,
+    pub num_readonly_unsigned: u8,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: [u8; 32],
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl Message {
+    /// 编译指令：账户去重并按
+    /// 可写签名者（付款人首位）→ 只读签名者 → 可写非签名者 → 只读非签名者 排序
+    pub fn compile(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        recent_blockhash: [u8; 32],
+    ) -> Result<Self> {
+        let mut metas: Vec<AccountMeta> = vec![AccountMeta::writable(*payer, true)];
+        let mut merge = |meta: AccountMeta| match metas.iter_mut().find(|m| m.pubkey == meta.pubkey)
+        {
+            Some(existing) => {
+                existing.is_signer |= meta.is_signer;
+                existing.is_writable |= meta.is_writable;
+            }
+            None => metas.push(meta),
+        };
+        for ix in instructions {
+            for meta in &ix.accounts {
+                merge(*meta);
+            }
+            merge(AccountMeta::readonly(ix.program_id, false));
+        }
+
+        // 稳定排序保持首次出现顺序，付款人始终在首位
+        metas.sort_by_key(|m| match (m.is_signer, m.is_writable) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        });
+
+        if metas.len() > u8::MAX as usize {
+            anyhow::bail!("Too many accounts in Solana message: {}", metas.len());
+        }
+
+        let count = |signer: bool, writable: bool| {
+            metas
+                .iter()
+                .filter(|m| m.is_signer == signer && m.is_writable == writable)
+                .count() as u8
+        };
+        let account_keys: Vec<Pubkey> = metas.iter().map(|m| m.pubkey).collect();
+        let index_of = |key: &Pubkey| account_keys.iter().position(|k| k == key).unwrap() as u8;
+
+        let compiled = instructions
+            .iter()
+            .map(|ix| CompiledInstruction {
+                program_id_index: index_of(&ix.program_id),
+                accounts: ix.accounts.iter().map(|m| index_of(&m.pubkey)).collect(),
+                data: ix.data.clone(),
+            })
+            .collect();
+
+        Ok(Self {
+            num_required_signatures: count(true, true) + count(true, false),
+            num_readonly_signed: count(true, false),
+            num_readonly_unsigned: count(false, false),
+            account_keys,
+            recent_blockhash,
+            instructions: compiled,
+        })
+    }
+
+    /// 序列化为待签名字节（签名对象即该字节串）
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![
+            self.num_required_signatures,
+            self.num_readonly_signed,
+            self.num_readonly_unsigned,
+        ];
+        write_compact_u16(&mut out, self.account_keys.len());
+        for key in &self.account_keys {
+            out.extend(key.0);
+        }
+        out.extend(self.recent_blockhash);
+        write_compact_u16(&mut out, self.instructions.len());
+        for ix in &self.instructions {
+            out.push(ix.program_id_index);
+            write_compact_u16(&mut out, ix.accounts.len());
+            out.extend(&ix.accounts);
+            write_compact_u16(&mut out, ix.data.len());
+            out.extend(&ix.data);
+        }
+        out
+    }
+}
+
+/// compact-u16（shortvec）编码
+fn write_compact_u16(out: &mut Vec<u8>, value: usize) {
+    let mut value = value as u16;
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
+
+/// 优先费（micro-lamports / CU）对应的 lamports，向上取整
+pub fn priority_fee_lamports(compute_units: u32, micro_lamports_per_cu: u64) -> u64 {
+    (compute_units as u128 * micro_lamports_per_cu as u128).div_ceil(1_000_000) as u64
+}
+
+/// Solana 链上数据源
+#[async_trait]
+pub trait SolanaRpcSource: Send + Sync {
+    /// 最新区块哈希（base58）
+    async fn latest_blockhash(&self) -> Result<String>;
+
+    /// 账户是否存在
+    async fn account_exists(&self, address: &str) -> Result<bool>;
+
+    /// 查询 mint 精度
+    async fn mint_decimals(&self, mint: &str) -> Result<u8>;
+
+    /// 最近的优先费（micro-lamports / CU，取中位数）
+    async fn priority_fee(&self, writable_accounts: &[String]) -> Result<u64>;
+}
+
+/// JSON-RPC 数据源
+pub struct JsonRpcSolanaSource {
+    http_client: reqwest::Client,
+    rpc_url: String,
+}
+
+impl JsonRpcSolanaSource {
+    pub fn new(rpc_url: String) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            http_client,
+            rpc_url,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let rpc_url = std::env::var("SOLANA_RPC_URL")
+            .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+        Self::new(rpc_url)
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        #[derive(Deserialize)]
+        struct RpcError {
+            message: String,
+        }
+
+        #[derive(Deserialize)]
+        struct RpcResponse<T> {
+            result: Option<T>,
+            error: Option<RpcError>,
+        }
+
+        let response: RpcResponse<T> = self
+            .http_client
+            .post(&self.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call Solana RPC")?
+            .json()
+            .await
+            .context("Failed to parse Solana RPC response")?;
+
+        if let Some(error) = response.error {
+            anyhow::bail!("Solana RPC {} error: {}", method, error.message);
+        }
+        response
+            .result
+            .with_context(|| format!("Solana RPC {} returned empty result", method))
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcContext<T> {
+    value: T,
+}
+
+#[async_trait]
+impl SolanaRpcSource for JsonRpcSolanaSource {
+    async fn latest_blockhash(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Blockhash {
+            blockhash: String,
+        }
+
+        let result: RpcContext<Blockhash> = self
+            .call("getLatestBlockhash", json!([{ "commitment": "finalized" }]))
+            .await?;
+        Ok(result.value.blockhash)
+    }
+
+    async fn account_exists(&self, address: &str) -> Result<bool> {
+        let result: RpcContext<Option<serde_json::Value>> = self
+            .call(
+                "getAccountInfo",
+                json!([address, { "encoding": "base64", "commitment": "confirmed" }]),
+            )
+            .await?;
+        Ok(result.value.is_some())
+    }
+
+    async fn mint_decimals(&self, mint: &str) -> Result<u8> {
+        #[derive(Deserialize)]
+        struct TokenSupply {
+            decimals: u8,
+        }
+
+        let result: RpcContext<TokenSupply> = self.call("getTokenSupply", json!([mint])).await?;
+        Ok(result.value.decimals)
+    }
+
+    async fn priority_fee(&self, writable_accounts: &[String]) -> Result<u64> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PrioritizationFee {
+            prioritization_fee: u64,
+        }
+
+        let fees: Vec<PrioritizationFee> = self
+            .call("getRecentPrioritizationFees", json!([writable_accounts]))
+            .await?;
+        let mut values: Vec<u64> = fees.into_iter().map(|f| f.prioritization_fee).collect();
+        if values.is_empty() {
+            return Ok(0);
+        }
+        values.sort_unstable();
+        Ok(values[values.len() / 2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey([byte; 32])
+    }
+
+    #[test]
+    fn test_pubkey_base58_roundtrip() {
+        let system = Pubkey::from_base58(SYSTEM_PROGRAM_ID).unwrap();
+        assert_eq!(system, Pubkey([0; 32]));
+        assert_eq!(system.to_base58(), SYSTEM_PROGRAM_ID);
+        assert!(Pubkey::from_base58("not-base58!").is_err());
+    }
+
+    #[test]
+    fn test_associated_token_address_is_off_curve_and_deterministic() {
+        let owner = Pubkey::from_base58("4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi").unwrap();
+        let mint = Pubkey::from_base58("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let ata = associated_token_address(&owner, &mint).unwrap();
+        assert!(!ata.is_on_curve());
+        assert_eq!(ata, associated_token_address(&owner, &mint).unwrap());
+        assert_ne!(ata, associated_token_address(&mint, &owner).unwrap());
+    }
+
+    #[test]
+    fn test_compact_u16() {
+        for (value, expected) in [
+            (0usize, vec![0x00]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x80, 0x01]),
+            (0x3fff, vec![0xff, 0x7f]),
+            (0x4000, vec![0x80, 0x80, 0x01]),
+        ] {
+            let mut out = Vec::new();
+            write_compact_u16(&mut out, value);
+            assert_eq!(out, expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_compile_native_transfer() {
+        let payer = key(1);
+        let to = key(2);
+        let message = Message::compile(
+            &payer,
+            &[
+                set_compute_unit_limit(1_000),
+                set_compute_unit_price(5_000),
+                system_transfer(&payer, &to, 42),
+            ],
+            [9; 32],
+        )
+        .unwrap();
+
+        assert_eq!(message.num_required_signatures, 1);
+        assert_eq!(message.num_readonly_signed, 0);
+        assert_eq!(message.num_readonly_unsigned, 2); // ComputeBudget + System
+        assert_eq!(message.account_keys[0], payer);
+        assert_eq!(message.account_keys[1], to);
+
+        let transfer = &message.instructions[2];
+        assert_eq!(transfer.accounts, vec![0, 1]);
+        assert_eq!(transfer.data[..4], [2, 0, 0, 0]);
+        assert_eq!(transfer.data[4..], 42u64.to_le_bytes());
+
+        let bytes = message.serialize();
+        assert_eq!(&bytes[..4], &[1, 0, 2, 4]);
+        assert_eq!(
+            bytes.len(),
+            3 + 1 + 4 * 32 + 32 + 1 + (3 + 5) + (3 + 9) + (5 + 12)
+        );
+    }
+
+    #[test]
+    fn test_compile_spl_transfer_with_ata_creation() {
+        let owner = key(1);
+        let recipient = key(2);
+        let mint = Pubkey::from_base58("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let source = associated_token_address(&owner, &mint).unwrap();
+        let destination = associated_token_address(&recipient, &mint).unwrap();
+
+        let message = Message::compile(
+            &owner,
+            &[
+                create_associated_token_account_idempotent(&owner, &recipient, &mint).unwrap(),
+                spl_transfer_checked(&source, &mint, &destination, &owner, 1_500_000, 6),
+            ],
+            [0; 32],
+        )
+        .unwrap();
+
+        assert_eq!(message.num_required_signatures, 1);
+        // owner, dest ATA, source ATA | recipient, mint, system, token, ata program
+        assert_eq!(message.account_keys.len(), 8);
+        assert_eq!(message.num_readonly_unsigned, 5);
+        assert_eq!(message.account_keys[1], destination);
+        assert_eq!(message.account_keys[2], source);
+
+        let transfer = &message.instructions[1];
+        assert_eq!(transfer.data[0], 12);
+        assert_eq!(transfer.data[9], 6);
+    }
+
+    #[test]
+    fn test_priority_fee_lamports() {
+        assert_eq!(priority_fee_lamports(200_000, 1), 1);
+        assert_eq!(priority_fee_lamports(200_000, 10_000), 2_000);
+        assert_eq!(priority_fee_lamports(0, 10_000), 0);
+    }
+}
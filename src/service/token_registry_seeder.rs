@@ -267,6 +267,37 @@ pub async fn seed_token_registry_if_empty(pool: &PgPool) -> Result<()> {
             is_stablecoin: true,
             priority: 3,
         },
+        // Solana (501)
+        TokenSeed {
+            symbol: "SOL",
+            name: "Solana",
+            chain_id: 501,
+            address: "So11111111111111111111111111111111111111112",
+            decimals: 9,
+            is_native: true,
+            is_stablecoin: false,
+            priority: 1,
+        },
+        TokenSeed {
+            symbol: "USDT",
+            name: "Tether USD",
+            chain_id: 501,
+            address: "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+            decimals: 6,
+            is_native: false,
+            is_stablecoin: true,
+            priority: 2,
+        },
+        TokenSeed {
+            symbol: "USDC",
+            name: "USD Coin",
+            chain_id: 501,
+            address: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            decimals: 6,
+            is_native: false,
+            is_stablecoin: true,
+            priority: 3,
+        },
     ];
 
     for s in seeds {
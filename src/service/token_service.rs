@@ -6,7 +6,10 @@ use std::sync::Arc;
 use anyhow::{Context, Result};
 use sqlx::PgPool;
 
-use crate::repository::{PgTokenRepository, Token, TokenRepository};
+use crate::{
+    domain::token_amount::TokenAmount,
+    repository::{PgTokenRepository, Token, TokenRepository},
+};
 
 /// 代币服务
 pub struct TokenService {
@@ -42,6 +45,25 @@ impl TokenService {
         Ok(token.map(|t| t.decimals as u32))
     }
 
+    /// 按代币精度将人类可读金额解析为最小单位（U256精确，拒绝浮点格式）
+    pub async fn parse_amount(
+        &self,
+        symbol: &str,
+        chain_id: u64,
+        amount: &str,
+    ) -> Result<TokenAmount> {
+        let decimals = self
+            .get_token_decimals(symbol, chain_id)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Token {} not supported on chain {}", symbol, chain_id)
+            })?;
+
+        TokenAmount::parse(amount, decimals)
+            .and_then(TokenAmount::ensure_positive)
+            .map_err(|e| anyhow::anyhow!("Invalid amount {}: {}", amount, e))
+    }
+
     /// 根据符号和链ID获取完整代币信息
     pub async fn get_token(&self, symbol: &str, chain_id: u64) -> Result<Option<Token>> {
         self.repository
@@ -0,0 +1,156 @@
+//! TON 账户状态查询
+//!
+//! - seqno：toncenter v2 `runGetMethod`（未部署钱包视为 0）
+//! - Jetton 钱包地址：toncenter v3 `/jetton/wallets`
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// TON 账户数据源
+#[async_trait]
+pub trait TonAccountSource: Send + Sync {
+    /// 查询钱包合约当前 seqno
+    async fn seqno(&self, address: &str) -> Result<u32>;
+
+    /// 查询 owner 在指定 Jetton master 下的 Jetton 钱包地址
+    async fn jetton_wallet(&self, owner: &str, jetton_master: &str) -> Result<String>;
+}
+
+/// toncenter API 数据源
+pub struct ToncenterAccountSource {
+    http_client: reqwest::Client,
+    api_url: String,
+    api_v3_url: String,
+}
+
+impl ToncenterAccountSource {
+    pub fn new(api_url: String, api_v3_url: String) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            http_client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_v3_url: api_v3_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let api_url = std::env::var("TON_API_URL")
+            .unwrap_or_else(|_| "https://toncenter.com/api/v2".to_string());
+        let api_v3_url = std::env::var("TON_API_V3_URL")
+            .unwrap_or_else(|_| "https://toncenter.com/api/v3".to_string());
+        Self::new(api_url, api_v3_url)
+    }
+}
+
+#[async_trait]
+impl TonAccountSource for ToncenterAccountSource {
+    async fn seqno(&self, address: &str) -> Result<u32> {
+        #[derive(Deserialize)]
+        struct RunGetMethodResult {
+            exit_code: i32,
+            stack: Vec<(String, serde_json::Value)>,
+        }
+
+        #[derive(Deserialize)]
+        struct RunGetMethodResponse {
+            ok: bool,
+            result: Option<RunGetMethodResult>,
+            error: Option<String>,
+        }
+
+        let url = format!("{}/runGetMethod", self.api_url);
+        let response: RunGetMethodResponse = self
+            .http_client
+            .post(&url)
+            .json(&serde_json::json!({
+                "address": address,
+                "method": "seqno",
+                "stack": [],
+            }))
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call TON API")?
+            .json()
+            .await
+            .context("Failed to parse TON API response")?;
+
+        if !response.ok {
+            anyhow::bail!(
+                "TON API returned error: {}",
+                response.error.unwrap_or_default()
+            );
+        }
+        let result = response.result.context("TON API returned empty result")?;
+
+        // 未部署（uninit）钱包执行 get 方法失败，首笔交易 seqno 为 0
+        if result.exit_code != 0 {
+            return Ok(0);
+        }
+
+        let value = result
+            .stack
+            .first()
+            .and_then(|(_, v)| v.as_str())
+            .context("TON seqno missing from stack")?;
+        let seqno = match value.strip_prefix("0x") {
+            Some(hex_str) => u32::from_str_radix(hex_str, 16),
+            None => value.parse(),
+        }
+        .with_context(|| format!("Invalid TON seqno: {}", value))?;
+
+        Ok(seqno)
+    }
+
+    async fn jetton_wallet(&self, owner: &str, jetton_master: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        struct JettonWallet {
+            address: String,
+        }
+
+        #[derive(Deserialize)]
+        struct JettonWalletsResponse {
+            jetton_wallets: Vec<JettonWallet>,
+        }
+
+        let url = format!("{}/jetton/wallets", self.api_v3_url);
+        let response: JettonWalletsResponse = self
+            .http_client
+            .get(&url)
+            .query(&[
+                ("owner_address", owner),
+                ("jetton_address", jetton_master),
+                ("limit", "1"),
+            ])
+            .with_trace_context()
+            .send()
+            .await
+            .context("Failed to call TON API")?
+            .error_for_status()
+            .context("TON API returned error status")?
+            .json()
+            .await
+            .context("Failed to parse TON jetton wallets response")?;
+
+        response
+            .jetton_wallets
+            .into_iter()
+            .next()
+            .map(|w| w.address)
+            .with_context(|| {
+                format!(
+                    "No jetton wallet for owner {} and master {}",
+                    owner, jetton_master
+                )
+            })
+    }
+}
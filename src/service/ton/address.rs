@@ -0,0 +1,189 @@
+//! TON 地址解析
+//!
+//! 支持原始格式（`0:<hex64>`）与用户友好格式（36字节 base64/base64url，
+//! 含 bounceable/testnet 标志位与 CRC16 校验）
+
+use anyhow::Result;
+use base64::Engine;
+
+use super::cell::CellBuilder;
+
+const FLAG_BOUNCEABLE: u8 = 0x11;
+const FLAG_NON_BOUNCEABLE: u8 = 0x51;
+const FLAG_TESTNET: u8 = 0x80;
+
+/// TON 标准地址（addr_std）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TonAddress {
+    pub workchain: i8,
+    pub hash: [u8; 32],
+    /// 来自用户友好格式的 bounce 标志；原始格式默认 bounceable
+    pub bounceable: bool,
+    pub testnet: bool,
+}
+
+impl TonAddress {
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim();
+        if address.contains(':') {
+            Self::parse_raw(address)
+        } else {
+            Self::parse_friendly(address)
+        }
+    }
+
+    fn parse_raw(address: &str) -> Result<Self> {
+        let (wc, hash_hex) = address
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid TON raw address: {}", address))?;
+        let workchain: i8 = wc
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid TON workchain: {}", wc))?;
+        let bytes = hex::decode(hash_hex)
+            .map_err(|_| anyhow::anyhow!("Invalid TON address hash: {}", address))?;
+        let hash: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("TON address hash must be 32 bytes: {}", address))?;
+
+        Ok(Self {
+            workchain,
+            hash,
+            bounceable: true,
+            testnet: false,
+        })
+    }
+
+    fn parse_friendly(address: &str) -> Result<Self> {
+        if address.len() != 48 {
+            anyhow::bail!("Invalid TON address length: {}", address);
+        }
+        let normalized = address.replace('-', "+").replace('_', "/");
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(normalized)
+            .map_err(|_| anyhow::anyhow!("Invalid TON address encoding: {}", address))?;
+        if bytes.len() != 36 {
+            anyhow::bail!("Invalid TON address length: {}", address);
+        }
+
+        let checksum = u16::from_be_bytes([bytes[34], bytes[35]]);
+        if crc16_xmodem(&bytes[..34]) != checksum {
+            anyhow::bail!("Invalid TON address checksum: {}", address);
+        }
+
+        let testnet = bytes[0] & FLAG_TESTNET != 0;
+        let bounceable = match bytes[0] & !FLAG_TESTNET {
+            FLAG_BOUNCEABLE => true,
+            FLAG_NON_BOUNCEABLE => false,
+            flag => anyhow::bail!("Invalid TON address flag: 0x{:02x}", flag),
+        };
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&bytes[2..34]);
+
+        Ok(Self {
+            workchain: bytes[1] as i8,
+            hash,
+            bounceable,
+            testnet,
+        })
+    }
+
+    /// 原始格式 `wc:hex`
+    pub fn to_raw(&self) -> String {
+        format!("{}:{}", self.workchain, hex::encode(self.hash))
+    }
+
+    /// 用户友好格式（base64url）
+    pub fn to_friendly(&self, bounceable: bool) -> String {
+        let mut bytes = Vec::with_capacity(36);
+        let mut flag = if bounceable {
+            FLAG_BOUNCEABLE
+        } else {
+            FLAG_NON_BOUNCEABLE
+        };
+        if self.testnet {
+            flag |= FLAG_TESTNET;
+        }
+        bytes.push(flag);
+        bytes.push(self.workchain as u8);
+        bytes.extend(self.hash);
+        bytes.extend(crc16_xmodem(&bytes).to_be_bytes());
+        base64::engine::general_purpose::URL_SAFE.encode(bytes)
+    }
+
+    /// 写入 `addr_std$10 anycast:(Maybe Anycast) workchain_id:int8 address:bits256`
+    pub fn store(&self, builder: &mut CellBuilder) -> Result<()> {
+        builder.store_uint(0b10, 2)?;
+        builder.store_bit(false)?;
+        builder.store_int(self.workchain as i64, 8)?;
+        builder.store_bytes(&self.hash)?;
+        Ok(())
+    }
+}
+
+/// 写入 `addr_none$00`
+pub fn store_addr_none(builder: &mut CellBuilder) -> Result<()> {
+    builder.store_uint(0, 2)?;
+    Ok(())
+}
+
+/// CRC16-XMODEM（多项式 0x1021）
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_friendly_address() {
+        let addr = TonAddress::parse("EQD__________________________________________0vo").unwrap();
+        assert_eq!(addr.workchain, 0);
+        assert_eq!(addr.hash, [0xff; 32]);
+        assert!(addr.bounceable);
+        assert!(!addr.testnet);
+        assert_eq!(
+            addr.to_friendly(true),
+            "EQD__________________________________________0vo"
+        );
+    }
+
+    #[test]
+    fn test_raw_and_friendly_roundtrip() {
+        let raw = format!("-1:{}", "ab".repeat(32));
+        let addr = TonAddress::parse(&raw).unwrap();
+        assert_eq!(addr.workchain, -1);
+        assert_eq!(addr.to_raw(), raw);
+
+        let non_bounceable = TonAddress::parse(&addr.to_friendly(false)).unwrap();
+        assert!(!non_bounceable.bounceable);
+        assert_eq!(non_bounceable.hash, addr.hash);
+    }
+
+    #[test]
+    fn test_rejects_bad_checksum() {
+        assert!(TonAddress::parse("EQD__________________________________________0vA").is_err());
+        assert!(TonAddress::parse("0:abcd").is_err());
+        assert!(TonAddress::parse("not-an-address").is_err());
+    }
+
+    #[test]
+    fn test_store_addr_std_bits() {
+        let addr = TonAddress::parse("EQD__________________________________________0vo").unwrap();
+        let mut builder = CellBuilder::new();
+        addr.store(&mut builder).unwrap();
+        assert_eq!(builder.build().bit_len(), 2 + 1 + 8 + 256);
+    }
+}
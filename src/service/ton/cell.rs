@@ -0,0 +1,349 @@
+//! TON Cell 与 BOC 序列化
+//!
+//! 实现 TVM Cell 的构建、表示哈希（representation hash）计算，
+//! 以及标准 Bag of Cells（`b5ee9c72`）序列化
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// 单个 Cell 最大数据位数
+pub const MAX_CELL_BITS: usize = 1023;
+/// 单个 Cell 最大引用数
+pub const MAX_CELL_REFS: usize = 4;
+
+/// BOC 魔数
+const BOC_MAGIC: [u8; 4] = [0xb5, 0xee, 0x9c, 0x72];
+
+/// 普通（非 exotic）Cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    data: Vec<u8>,
+    bit_len: usize,
+    refs: Vec<Arc<Cell>>,
+}
+
+impl Cell {
+    pub fn empty() -> Self {
+        Self {
+            data: Vec::new(),
+            bit_len: 0,
+            refs: Vec::new(),
+        }
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    pub fn refs(&self) -> &[Arc<Cell>] {
+        &self.refs
+    }
+
+    /// 描述符 d1（引用数，level 0，非 exotic）
+    fn d1(&self) -> u8 {
+        self.refs.len() as u8
+    }
+
+    /// 描述符 d2（数据字节数编码）
+    fn d2(&self) -> u8 {
+        ((self.bit_len / 8) + self.bit_len.div_ceil(8)) as u8
+    }
+
+    /// 补齐到字节边界的数据（不足8位时追加 `1` 后补 `0`）
+    fn padded_data(&self) -> Vec<u8> {
+        let mut data = self.data[..self.bit_len.div_ceil(8)].to_vec();
+        let rem = self.bit_len % 8;
+        if rem != 0 {
+            let last = data.len() - 1;
+            data[last] |= 1 << (7 - rem);
+        }
+        data
+    }
+
+    /// Cell 深度
+    pub fn depth(&self) -> u16 {
+        self.refs.iter().map(|r| r.depth() + 1).max().unwrap_or(0)
+    }
+
+    /// 表示哈希（钱包签名即对该哈希签名）
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([self.d1(), self.d2()]);
+        hasher.update(self.padded_data());
+        for r in &self.refs {
+            hasher.update(r.depth().to_be_bytes());
+        }
+        for r in &self.refs {
+            hasher.update(r.hash());
+        }
+        hasher.finalize().into()
+    }
+
+    /// 序列化为 BOC（单根，带 CRC32C 校验）
+    pub fn to_boc(&self) -> Vec<u8> {
+        // 拓扑排序：父节点在前，按哈希去重
+        let mut order: Vec<&Cell> = Vec::new();
+        let mut index: HashMap<[u8; 32], usize> = HashMap::new();
+        collect_cells(self, &mut order, &mut index);
+
+        let cell_count = order.len();
+        let size_bytes = bytes_needed(cell_count as u64);
+
+        let mut cells_data = Vec::new();
+        for cell in &order {
+            cells_data.push(cell.d1());
+            cells_data.push(cell.d2());
+            cells_data.extend(cell.padded_data());
+            for r in &cell.refs {
+                let idx = index[&r.hash()];
+                cells_data.extend(&(idx as u64).to_be_bytes()[8 - size_bytes..]);
+            }
+        }
+
+        let off_bytes = bytes_needed(cells_data.len() as u64);
+
+        let mut boc = Vec::with_capacity(cells_data.len() + 32);
+        boc.extend(BOC_MAGIC);
+        // has_idx=0, has_crc32c=1, has_cache_bits=0, flags=0, size_bytes
+        boc.push(0x40 | size_bytes as u8);
+        boc.push(off_bytes as u8);
+        boc.extend(&(cell_count as u64).to_be_bytes()[8 - size_bytes..]); // cells
+        boc.extend(&1u64.to_be_bytes()[8 - size_bytes..]); // roots
+        boc.extend(&0u64.to_be_bytes()[8 - size_bytes..]); // absent
+        boc.extend(&(cells_data.len() as u64).to_be_bytes()[8 - off_bytes..]);
+        boc.extend(&0u64.to_be_bytes()[8 - size_bytes..]); // root index
+        boc.extend(cells_data);
+
+        let crc = crc32c(&boc);
+        boc.extend(crc.to_le_bytes());
+        boc
+    }
+
+    /// BOC 的 base64 编码（`sendBoc` 所需格式）
+    pub fn to_boc_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_boc())
+    }
+}
+
+fn collect_cells<'a>(
+    cell: &'a Cell,
+    order: &mut Vec<&'a Cell>,
+    index: &mut HashMap<[u8; 32], usize>,
+) {
+    let hash = cell.hash();
+    if index.contains_key(&hash) {
+        return;
+    }
+    index.insert(hash, order.len());
+    order.push(cell);
+    for r in &cell.refs {
+        collect_cells(r, order, index);
+    }
+}
+
+fn bytes_needed(value: u64) -> usize {
+    (((64 - value.leading_zeros()) as usize).div_ceil(8)).max(1)
+}
+
+/// CRC32C（Castagnoli）
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Cell 构建器
+#[derive(Debug, Clone, Default)]
+pub struct CellBuilder {
+    data: Vec<u8>,
+    bit_len: usize,
+    refs: Vec<Arc<Cell>>,
+}
+
+impl CellBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bits_left(&self) -> usize {
+        MAX_CELL_BITS - self.bit_len
+    }
+
+    pub fn store_bit(&mut self, bit: bool) -> Result<&mut Self> {
+        if self.bit_len >= MAX_CELL_BITS {
+            anyhow::bail!("Cell overflow: more than {} bits", MAX_CELL_BITS);
+        }
+        if self.bit_len.is_multiple_of(8) {
+            self.data.push(0);
+        }
+        if bit {
+            let last = self.data.len() - 1;
+            self.data[last] |= 1 << (7 - self.bit_len % 8);
+        }
+        self.bit_len += 1;
+        Ok(self)
+    }
+
+    /// 写入无符号整数（高位在前）
+    pub fn store_uint(&mut self, value: u128, bits: usize) -> Result<&mut Self> {
+        if bits < 128 && value >> bits != 0 {
+            anyhow::bail!("Value {} does not fit in {} bits", value, bits);
+        }
+        for i in (0..bits).rev() {
+            self.store_bit(i < 128 && (value >> i) & 1 == 1)?;
+        }
+        Ok(self)
+    }
+
+    /// 写入有符号整数（补码）
+    pub fn store_int(&mut self, value: i64, bits: usize) -> Result<&mut Self> {
+        let unsigned = (value as i128 as u128) & ((1u128 << bits) - 1);
+        self.store_uint(unsigned, bits)
+    }
+
+    pub fn store_bytes(&mut self, bytes: &[u8]) -> Result<&mut Self> {
+        for byte in bytes {
+            self.store_uint(*byte as u128, 8)?;
+        }
+        Ok(self)
+    }
+
+    /// 写入 `Grams` / `VarUInteger 16`（4位长度 + 字节）
+    pub fn store_coins(&mut self, amount: u128) -> Result<&mut Self> {
+        if amount == 0 {
+            return self.store_uint(0, 4);
+        }
+        let len = bytes_needed_u128(amount);
+        if len > 15 {
+            anyhow::bail!("Coins amount too large: {}", amount);
+        }
+        self.store_uint(len as u128, 4)?;
+        self.store_uint(amount, len * 8)
+    }
+
+    pub fn store_ref(&mut self, cell: Cell) -> Result<&mut Self> {
+        if self.refs.len() >= MAX_CELL_REFS {
+            anyhow::bail!("Cell overflow: more than {} refs", MAX_CELL_REFS);
+        }
+        self.refs.push(Arc::new(cell));
+        Ok(self)
+    }
+
+    /// 写入 `Maybe ^Cell`
+    pub fn store_maybe_ref(&mut self, cell: Option<Cell>) -> Result<&mut Self> {
+        match cell {
+            Some(cell) => {
+                self.store_bit(true)?;
+                self.store_ref(cell)
+            }
+            None => self.store_bit(false),
+        }
+    }
+
+    pub fn build(&self) -> Cell {
+        Cell {
+            data: self.data.clone(),
+            bit_len: self.bit_len,
+            refs: self.refs.clone(),
+        }
+    }
+}
+
+fn bytes_needed_u128(value: u128) -> usize {
+    ((128 - value.leading_zeros()) as usize).div_ceil(8)
+}
+
+/// 将长字节串写成 snake 格式（溢出部分放入链式引用）
+pub fn snake_cell(prefix: &CellBuilder, bytes: &[u8]) -> Result<Cell> {
+    let first_len = (prefix.bits_left() / 8).min(bytes.len());
+    let (head, tail) = bytes.split_at(first_len);
+
+    let mut builder = prefix.clone();
+    builder.store_bytes(head)?;
+    if !tail.is_empty() {
+        builder.store_ref(snake_cell(&CellBuilder::new(), tail)?)?;
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_cell_hash_and_boc() {
+        let cell = Cell::empty();
+        assert_eq!(
+            hex::encode(cell.hash()),
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+        );
+        assert_eq!(cell.to_boc_base64(), "te6cckEBAQEAAgAAAEysuc0=");
+    }
+
+    #[test]
+    fn test_store_uint_and_padding() {
+        let mut builder = CellBuilder::new();
+        builder.store_uint(0b101, 3).unwrap();
+        let cell = builder.build();
+        assert_eq!(cell.bit_len(), 3);
+        // 101 + 补位 1 → 1011_0000
+        assert_eq!(cell.padded_data(), vec![0b1011_0000]);
+        assert_eq!(cell.d2(), 1);
+    }
+
+    #[test]
+    fn test_store_coins() {
+        let mut builder = CellBuilder::new();
+        builder.store_coins(1_000_000_000).unwrap(); // 0x3b9aca00, 4 字节
+        let cell = builder.build();
+        assert_eq!(cell.bit_len(), 4 + 32);
+
+        let mut zero = CellBuilder::new();
+        zero.store_coins(0).unwrap();
+        assert_eq!(zero.build().bit_len(), 4);
+    }
+
+    #[test]
+    fn test_cell_overflow() {
+        let mut builder = CellBuilder::new();
+        builder.store_bytes(&[0u8; 127]).unwrap();
+        assert!(builder.store_bytes(&[0u8; 1]).is_err());
+
+        let mut refs = CellBuilder::new();
+        for _ in 0..MAX_CELL_REFS {
+            refs.store_ref(Cell::empty()).unwrap();
+        }
+        assert!(refs.store_ref(Cell::empty()).is_err());
+    }
+
+    #[test]
+    fn test_snake_cell_splits_long_payload() {
+        let text = vec![b'a'; 300];
+        let cell = snake_cell(&CellBuilder::new(), &text).unwrap();
+        assert_eq!(cell.bit_len(), 127 * 8);
+        assert_eq!(cell.refs().len(), 1);
+        assert_eq!(cell.depth(), 2);
+    }
+
+    #[test]
+    fn test_boc_deduplicates_identical_cells() {
+        let mut builder = CellBuilder::new();
+        builder.store_ref(Cell::empty()).unwrap();
+        builder.store_ref(Cell::empty()).unwrap();
+        let boc = builder.build().to_boc();
+        // header(4+1+1) + cells/roots/absent(3) + tot_size(1) + root(1) → cells 数 = 2
+        assert_eq!(boc[6], 2);
+    }
+}
@@ -0,0 +1,16 @@
+//! TON 交易构建模块
+//!
+//! - cell：Cell 构建与 BOC 序列化
+//! - address：原始 / 用户友好地址解析
+//! - wallet：wallet v4r2 / v5r1 消息构建（原生转账、文本备注、Jetton）
+//! - account：seqno 与 Jetton 钱包地址查询
+
+pub mod account;
+pub mod address;
+pub mod cell;
+pub mod wallet;
+
+pub use account::{TonAccountSource, ToncenterAccountSource};
+pub use address::TonAddress;
+pub use cell::{Cell, CellBuilder};
+pub use wallet::{InternalMessage, JettonTransfer, TonWalletVersion, WalletTransfer};
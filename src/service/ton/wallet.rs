@@ -0,0 +1,277 @@
+//! TON 钱包消息构建
+//!
+//! 构建 wallet v4r2 / v5r1 的未签名外部消息：
+//! - 内部消息：原生 TON 转账（可附文本备注）或 Jetton 转账（TEP-74）
+//! - 签名载荷：钱包合约校验签名所用的 body cell，客户端对其表示哈希签名
+//! - 外部消息：`ext_in_msg_info` + 未签名 body，签名后由客户端替换 body 并 `sendBoc`
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    address::{store_addr_none, TonAddress},
+    cell::{snake_cell, Cell, CellBuilder},
+};
+
+/// wallet v4r2 默认 subwallet_id（workchain 0）
+pub const WALLET_V4_DEFAULT_SUBWALLET_ID: u32 = 698_983_191;
+/// wallet v5r1 主网默认 wallet_id（network_global_id = -239, workchain 0, subwallet 0）
+pub const WALLET_V5_MAINNET_WALLET_ID: u32 = 2_147_483_409;
+
+/// v5r1 外部签名请求前缀 `signed_external#7369676e`
+const WALLET_V5_EXTERNAL_SIGNED_OP: u32 = 0x7369_676e;
+/// v5r1 `action_send_msg#0ec3c86d`
+const ACTION_SEND_MSG_OP: u32 = 0x0ec3_c86d;
+/// Jetton `transfer#0f8a7ea5`（TEP-74）
+const JETTON_TRANSFER_OP: u32 = 0x0f8a_7ea5;
+
+/// 默认发送模式：PAY_GAS_SEPARATELY(1) + IGNORE_ERRORS(2)
+pub const DEFAULT_SEND_MODE: u8 = 3;
+
+/// 钱包合约版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TonWalletVersion {
+    /// wallet v4r2（签名前置于 body）
+    #[default]
+    V4r2,
+    /// wallet v5r1（签名追加于 body 末尾）
+    V5r1,
+}
+
+/// 待发送的内部消息
+#[derive(Debug, Clone)]
+pub struct InternalMessage {
+    pub dest: TonAddress,
+    /// 附带的 TON 数量（nanoTON）
+    pub value: u128,
+    pub bounce: bool,
+    pub body: Option<Cell>,
+}
+
+impl InternalMessage {
+    /// 序列化为 `MessageRelaxed`
+    pub fn to_cell(&self) -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        builder.store_bit(false)?; // int_msg_info$0
+        builder.store_bit(true)?; // ihr_disabled
+        builder.store_bit(self.bounce)?;
+        builder.store_bit(false)?; // bounced
+        store_addr_none(&mut builder)?; // src 由钱包合约填充
+        self.dest.store(&mut builder)?;
+        builder.store_coins(self.value)?;
+        builder.store_bit(false)?; // extra currencies
+        builder.store_coins(0)?; // ihr_fee
+        builder.store_coins(0)?; // fwd_fee
+        builder.store_uint(0, 64)?; // created_lt
+        builder.store_uint(0, 32)?; // created_at
+        builder.store_bit(false)?; // init
+        match &self.body {
+            Some(body) => {
+                builder.store_bit(true)?; // body 以引用存放
+                builder.store_ref(body.clone())?;
+            }
+            None => {
+                builder.store_bit(false)?;
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+/// 文本备注 body（op = 0）
+pub fn comment_body(text: &str) -> Result<Cell> {
+    let mut prefix = CellBuilder::new();
+    prefix.store_uint(0, 32)?;
+    snake_cell(&prefix, text.as_bytes())
+}
+
+/// Jetton 转账参数
+#[derive(Debug, Clone)]
+pub struct JettonTransfer {
+    pub query_id: u64,
+    /// Jetton 数量（最小单位）
+    pub amount: u128,
+    pub destination: TonAddress,
+    /// 多余 TON 退回地址（通常为发送方）
+    pub response_destination: TonAddress,
+    /// 转发给接收方的 TON（nanoTON），>0 时接收方收到 transfer_notification
+    pub forward_ton_amount: u128,
+    pub comment: Option<String>,
+}
+
+impl JettonTransfer {
+    /// 序列化为 Jetton 钱包的 transfer body
+    pub fn to_body(&self) -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        builder.store_uint(JETTON_TRANSFER_OP as u128, 32)?;
+        builder.store_uint(self.query_id as u128, 64)?;
+        builder.store_coins(self.amount)?;
+        self.destination.store(&mut builder)?;
+        self.response_destination.store(&mut builder)?;
+        builder.store_bit(false)?; // custom_payload
+        builder.store_coins(self.forward_ton_amount)?;
+        match &self.comment {
+            Some(text) => {
+                builder.store_bit(true)?; // forward_payload 以引用存放
+                builder.store_ref(comment_body(text)?)?;
+            }
+            None => {
+                builder.store_bit(false)?;
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+/// 钱包签名载荷参数
+#[derive(Debug, Clone)]
+pub struct WalletTransfer {
+    pub version: TonWalletVersion,
+    /// v4r2 为 subwallet_id，v5r1 为 wallet_id
+    pub wallet_id: u32,
+    pub seqno: u32,
+    /// 过期时间（Unix 秒）
+    pub valid_until: u32,
+    pub send_mode: u8,
+    pub message: InternalMessage,
+}
+
+impl WalletTransfer {
+    /// 钱包合约对其哈希验签的 body（不含签名）
+    pub fn signing_payload(&self) -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        match self.version {
+            TonWalletVersion::V4r2 => {
+                builder.store_uint(self.wallet_id as u128, 32)?;
+                builder.store_uint(self.valid_until as u128, 32)?;
+                builder.store_uint(self.seqno as u128, 32)?;
+                builder.store_uint(0, 8)?; // op = simple send
+                builder.store_uint(self.send_mode as u128, 8)?;
+                builder.store_ref(self.message.to_cell()?)?;
+            }
+            TonWalletVersion::V5r1 => {
+                // out_list$_ prev:^(OutList 0) action_send_msg#0ec3c86d mode:uint8 out_msg:^MessageRelaxed
+                let mut out_list = CellBuilder::new();
+                out_list.store_ref(Cell::empty())?;
+                out_list.store_uint(ACTION_SEND_MSG_OP as u128, 32)?;
+                out_list.store_uint(self.send_mode as u128, 8)?;
+                out_list.store_ref(self.message.to_cell()?)?;
+
+                builder.store_uint(WALLET_V5_EXTERNAL_SIGNED_OP as u128, 32)?;
+                builder.store_uint(self.wallet_id as u128, 32)?;
+                builder.store_uint(self.valid_until as u128, 32)?;
+                builder.store_uint(self.seqno as u128, 32)?;
+                builder.store_maybe_ref(Some(out_list.build()))?;
+                builder.store_bit(false)?; // 无扩展动作
+            }
+        }
+        Ok(builder.build())
+    }
+
+    /// 外部消息（body 为未签名载荷）
+    pub fn external_message(&self, wallet: &TonAddress) -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        builder.store_uint(0b10, 2)?; // ext_in_msg_info$10
+        store_addr_none(&mut builder)?; // src
+        wallet.store(&mut builder)?;
+        builder.store_coins(0)?; // import_fee
+        builder.store_bit(false)?; // init（未部署钱包需由客户端附带 StateInit）
+        builder.store_bit(true)?; // body 以引用存放
+        builder.store_ref(self.signing_payload()?)?;
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address() -> TonAddress {
+        TonAddress::parse("EQD__________________________________________0vo").unwrap()
+    }
+
+    fn transfer(version: TonWalletVersion) -> WalletTransfer {
+        WalletTransfer {
+            version,
+            wallet_id: match version {
+                TonWalletVersion::V4r2 => WALLET_V4_DEFAULT_SUBWALLET_ID,
+                TonWalletVersion::V5r1 => WALLET_V5_MAINNET_WALLET_ID,
+            },
+            seqno: 7,
+            valid_until: 1_700_000_000,
+            send_mode: DEFAULT_SEND_MODE,
+            message: InternalMessage {
+                dest: address(),
+                value: 1_000_000_000,
+                bounce: true,
+                body: Some(comment_body("hello").unwrap()),
+            },
+        }
+    }
+
+    #[test]
+    fn test_internal_message_layout() {
+        let cell = transfer(TonWalletVersion::V4r2).message.to_cell().unwrap();
+        // 4 flags + addr_none(2) + addr_std(267) + coins(4+32) + extra(1)
+        // + ihr/fwd(4+4) + lt(64) + at(32) + init(1) + body(1)
+        assert_eq!(cell.bit_len(), 4 + 2 + 267 + 36 + 1 + 8 + 64 + 32 + 1 + 1);
+        assert_eq!(cell.refs().len(), 1);
+    }
+
+    #[test]
+    fn test_v4_signing_payload() {
+        let payload = transfer(TonWalletVersion::V4r2).signing_payload().unwrap();
+        assert_eq!(payload.bit_len(), 32 * 3 + 8 + 8);
+        assert_eq!(payload.refs().len(), 1);
+    }
+
+    #[test]
+    fn test_v5_signing_payload() {
+        let payload = transfer(TonWalletVersion::V5r1).signing_payload().unwrap();
+        assert_eq!(payload.bit_len(), 32 * 4 + 1 + 1);
+        let out_list = &payload.refs()[0];
+        assert_eq!(out_list.bit_len(), 32 + 8);
+        assert_eq!(out_list.refs().len(), 2);
+        assert_eq!(*out_list.refs()[0].as_ref(), Cell::empty());
+    }
+
+    #[test]
+    fn test_signing_hash_depends_on_seqno() {
+        let a = transfer(TonWalletVersion::V4r2);
+        let mut b = a.clone();
+        b.seqno += 1;
+        assert_ne!(
+            a.signing_payload().unwrap().hash(),
+            b.signing_payload().unwrap().hash()
+        );
+    }
+
+    #[test]
+    fn test_jetton_transfer_body() {
+        let body = JettonTransfer {
+            query_id: 1,
+            amount: 5_000_000,
+            destination: address(),
+            response_destination: address(),
+            forward_ton_amount: 1,
+            comment: Some("invoice-42".to_string()),
+        }
+        .to_body()
+        .unwrap();
+        // op + query_id + coins(4+24) + 2×addr + custom_payload + coins(4+8) + either
+        assert_eq!(body.bit_len(), 32 + 64 + 28 + 267 * 2 + 1 + 12 + 1);
+        assert_eq!(body.refs().len(), 1);
+    }
+
+    #[test]
+    fn test_external_message_boc() {
+        let boc = transfer(TonWalletVersion::V5r1)
+            .external_message(&address())
+            .unwrap()
+            .to_boc();
+        assert_eq!(&boc[..4], &[0xb5, 0xee, 0x9c, 0x72]);
+        // ext msg → payload → out_list → (empty, msg → comment)
+        assert_eq!(boc[6], 6);
+    }
+}
@@ -8,7 +8,10 @@ use sqlx::PgPool;
 use tokio::time::interval;
 use uuid::Uuid;
 
-use crate::service::{blockchain_client::BlockchainClient, nonce_manager::NonceManager};
+use crate::{
+    infrastructure::worker_supervisor::ChainShard,
+    service::{blockchain_client::BlockchainClient, nonce_manager::NonceManager},
+};
 
 const MONITOR_INTERVAL_SECS: u64 = 300; // 每5分钟检查一次
 const STUCK_THRESHOLD_MINUTES: i64 = 30; // 30分钟未确认视为卡住
@@ -19,6 +22,8 @@ pub struct TransactionAutoRecovery {
     blockchain_client: Arc<BlockchainClient>,
     #[allow(dead_code)]
     nonce_manager: Arc<NonceManager>,
+    /// 负责的链分片（多实例部署时由 WorkerSupervisor 分配，避免重复 RBF）
+    shard: ChainShard,
 }
 
 impl TransactionAutoRecovery {
@@ -31,20 +36,31 @@ impl TransactionAutoRecovery {
             pool,
             blockchain_client,
             nonce_manager,
+            shard: ChainShard::All,
         }
     }
 
+    /// 仅处理指定分片的链
+    pub fn with_shard(mut self, shard: ChainShard) -> Self {
+        self.shard = shard;
+        self
+    }
+
     /// 启动后台监控任务
     pub async fn start_background_monitor(self: Arc<Self>) {
         let mut ticker = interval(Duration::from_secs(MONITOR_INTERVAL_SECS));
 
         tracing::info!(
+            shard = %self.shard.key(),
             "Transaction auto-recovery monitor started, interval={}s",
             MONITOR_INTERVAL_SECS
         );
 
         loop {
             ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
 
             match self.process_stuck_transactions().await {
                 Ok(recovered) => {
@@ -62,6 +78,7 @@ impl TransactionAutoRecovery {
     /// 处理卡住的交易
     async fn process_stuck_transactions(&self) -> Result<usize> {
         // 查询卡住的交易（pending状态 >= 30分钟）
+        let (shard_chains, shard_include) = self.shard.sql_filter();
         let stuck_txs = sqlx::query_as::<
             _,
             (
@@ -81,10 +98,13 @@ impl TransactionAutoRecovery {
                AND chain IN ('ETH', 'BSC', 'POLYGON') -- 只处理EVM链
                AND created_at < NOW() - INTERVAL '1 minute' * $1
                AND (retry_count IS NULL OR retry_count < 3) -- 最多重试3次
+               AND ($2::TEXT[] IS NULL OR (UPPER(chain) = ANY($2)) = $3)
              ORDER BY created_at ASC
              LIMIT 20",
         )
         .bind(STUCK_THRESHOLD_MINUTES)
+        .bind(shard_chains)
+        .bind(shard_include)
         .fetch_all(&self.pool)
         .await
         .context("Failed to query stuck transactions")?;
@@ -3,13 +3,66 @@
 //! 企业级实现：为所有链提供统一的交易构建接口
 //! 确保交易格式标准化和一致性
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
+use base64::Engine;
+use ethers::types::{
+    transaction::{
+        eip2718::TypedTransaction,
+        eip2930::{AccessList, AccessListItem, Eip2930TransactionRequest},
+    },
+    Address, Bytes, Eip1559TransactionRequest, TransactionRequest, H256, U256,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    domain::token_amount::TokenAmount,
+    service::{
+        bitcoin_psbt_builder::{
+            self, BitcoinPsbtBuilder, EsploraUtxoSource, PsbtRequest, UtxoSource,
+        },
+        blockchain_client::BlockchainClient,
+        gas_estimator::{GasEstimator, GasSpeed},
+        solana_message_builder::{
+            self as solana, JsonRpcSolanaSource, Message as SolanaMessage, Pubkey, SolanaRpcSource,
+        },
+        token_service::TokenService,
+        ton::{
+            wallet::{
+                comment_body, DEFAULT_SEND_MODE, WALLET_V4_DEFAULT_SUBWALLET_ID,
+                WALLET_V5_MAINNET_WALLET_ID,
+            },
+            InternalMessage, JettonTransfer, TonAccountSource, TonAddress, TonWalletVersion,
+            ToncenterAccountSource, WalletTransfer,
+        },
+        tx_simulator::{SimulationCall, TxSimulator},
+    },
+};
+
+/// EVM 原生代币精度（wei）
+const EVM_NATIVE_DECIMALS: u32 = 18;
+
+/// TON 精度（nanoTON）
+const TON_DECIMALS: u32 = 9;
+
+/// Solana 原生代币精度（lamports）
+const SOLANA_NATIVE_DECIMALS: u32 = 9;
+
+/// Jetton 默认精度（TEP-64）
+const JETTON_DEFAULT_DECIMALS: u32 = 9;
+
+/// Jetton 转账附带的 TON（nanoTON），多余部分退回发送方
+const JETTON_TRANSFER_ATTACHED_NANOTON: u128 = 50_000_000;
+
+/// TON 外部消息有效期（秒）
+const TON_MESSAGE_TTL_SECS: u64 = 300;
+
+/// Bitcoin 默认确认目标（区块数，约1小时）
+const BITCOIN_DEFAULT_TARGET_BLOCKS: u16 = 6;
+
 /// 交易构建请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BuildTransactionRequest {
     /// 链标识 (ETH, BSC, SOL, BTC, TON等)
     pub chain: String,
@@ -22,10 +75,10 @@ pub struct BuildTransactionRequest {
     /// 交易数据 (可选，用于智能合约调用)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<String>,
-    /// Gas价格 (可选，由服务端估算)
+    /// Gas价格 (可选，由服务端估算；Bitcoin 为 sat/vB 费率，Solana 为 micro-lamports/CU 优先费)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<String>,
-    /// Gas限制 (可选，由服务端估算)
+    /// Gas限制 (可选，由服务端估算；Solana 为计算单元上限)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<String>,
     /// Nonce (可选，由服务端获取)
@@ -34,6 +87,64 @@ pub struct BuildTransactionRequest {
     /// 链ID (可选，由服务端从配置获取)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chain_id: Option<u64>,
+    /// 找零地址 (UTXO链使用；由客户端按change路径派生，产生找零时必填)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+    /// 发送地址公钥 (可选，十六进制；Bitcoin P2SH-P2WPKH / P2TR 必填)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// 公钥的 BIP32 来源 (可选，`指纹/路径`；Bitcoin P2TR 必填)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_origin: Option<String>,
+    /// EIP-1559 最大总费用 (可选，wei)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<String>,
+    /// EIP-1559 最大优先费用 (可选，wei)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
+    /// EIP-2930 访问列表 (可选)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<AccessListEntry>>,
+    /// 代币合约地址 (可选，TON 为 Jetton master 地址，Solana 为 SPL mint 地址)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_address: Option<String>,
+    /// 代币精度 (可选，缺省 Jetton 为 9)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_decimals: Option<u32>,
+    /// 代币符号 (可选，未提供 token_address 时从代币注册表解析，如 Solana USDC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_symbol: Option<String>,
+    /// Solana 最近区块哈希 (可选，缺省从 RPC 获取)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_blockhash: Option<String>,
+    /// 文本备注 (可选，TON 转账 comment)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// TON 钱包合约版本 (可选，缺省 v4r2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_version: Option<TonWalletVersion>,
+}
+
+/// EIP-2930 访问列表条目
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessListEntry {
+    /// 合约地址
+    pub address: String,
+    /// 预热的存储槽 (32字节十六进制)
+    #[serde(default, alias = "storageKeys")]
+    pub storage_keys: Vec<String>,
+}
+
+/// EVM 交易类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvmTxType {
+    /// EIP-155 legacy (type 0x00)
+    Legacy,
+    /// EIP-2930 access list (type 0x01)
+    AccessList,
+    /// EIP-1559 dynamic fee (type 0x02)
+    DynamicFee,
 }
 
 /// 交易构建响应
@@ -44,6 +155,12 @@ pub struct BuildTransactionResponse {
     /// 交易哈希 (签名后计算)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tx_hash: Option<String>,
+    /// 待签名哈希 (EVM: keccak256(raw_transaction)；TON: 签名载荷 cell 哈希)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_hash: Option<String>,
+    /// 交易类型 (仅EVM)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_type: Option<EvmTxType>,
     /// 交易详情 (用于显示)
     pub transaction_details: TransactionDetails,
 }
@@ -59,8 +176,11 @@ pub struct TransactionDetails {
     pub to: String,
     /// 金额
     pub amount: String,
-    /// Gas价格
+    /// Gas价格 (EIP-1559 交易为 maxFeePerGas)
     pub gas_price: String,
+    /// EIP-1559 最大优先费用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
     /// Gas限制
     pub gas_limit: String,
     /// 预估Gas费用
@@ -75,6 +195,26 @@ pub struct TransactionDetails {
 pub struct TransactionBuilder {
     /// 链配置映射
     chain_configs: HashMap<String, ChainConfig>,
+    /// Bitcoin PSBT 构建器
+    bitcoin_builder: BitcoinPsbtBuilder,
+    /// EVM nonce/费用数据源 (可选，缺省时要求请求显式提供)
+    evm_services: Option<EvmServices>,
+    /// TON 账户数据源 (seqno / Jetton 钱包)
+    ton_account_source: Arc<dyn TonAccountSource>,
+    /// Solana RPC 数据源 (区块哈希 / ATA / 优先费)
+    solana_source: Arc<dyn SolanaRpcSource>,
+    /// 代币注册表 (可选，按符号解析 SPL mint)
+    token_service: Option<Arc<TokenService>>,
+    /// EVM 交易模拟器 (可选，配置后签名前拒绝会回滚的交易)
+    simulator: Option<Arc<TxSimulator>>,
+}
+
+/// EVM 交易构建依赖的服务
+///
+/// 构建只读取链上 pending nonce，不占用；nonce 在广播成功后由 `NonceManager` 记录
+struct EvmServices {
+    blockchain_client: Arc<BlockchainClient>,
+    gas_estimator: Arc<GasEstimator>,
 }
 
 /// 链配置
@@ -140,7 +280,68 @@ impl TransactionBuilder {
             },
         );
 
-        Self { chain_configs }
+        Self {
+            chain_configs,
+            bitcoin_builder: BitcoinPsbtBuilder::new(
+                Arc::new(EsploraUtxoSource::from_env()),
+                bitcoin_psbt_builder::network_from_env(),
+            ),
+            evm_services: None,
+            ton_account_source: Arc::new(ToncenterAccountSource::from_env()),
+            solana_source: Arc::new(JsonRpcSolanaSource::from_env()),
+            token_service: None,
+            simulator: None,
+        }
+    }
+
+    /// 接入 EVM 链上 nonce 查询和 Gas 估算（请求未提供 nonce/费用时使用）
+    pub fn with_evm_services(
+        mut self,
+        blockchain_client: Arc<BlockchainClient>,
+        gas_estimator: Arc<GasEstimator>,
+    ) -> Self {
+        self.evm_services = Some(EvmServices {
+            blockchain_client,
+            gas_estimator,
+        });
+        self
+    }
+
+    /// 替换 Bitcoin UTXO 数据源（保留当前网络）
+    pub fn with_utxo_source(mut self, utxo_source: Arc<dyn UtxoSource>) -> Self {
+        let network = self.bitcoin_builder.network();
+        self.bitcoin_builder = BitcoinPsbtBuilder::new(utxo_source, network);
+        self
+    }
+
+    /// 指定 Bitcoin 网络（缺省取 `BITCOIN_NETWORK`）
+    pub fn with_bitcoin_network(mut self, network: bitcoin::Network) -> Self {
+        self.bitcoin_builder = self.bitcoin_builder.with_network(network);
+        self
+    }
+
+    /// 替换 TON 账户数据源
+    pub fn with_ton_account_source(mut self, source: Arc<dyn TonAccountSource>) -> Self {
+        self.ton_account_source = source;
+        self
+    }
+
+    /// 替换 Solana RPC 数据源
+    pub fn with_solana_source(mut self, source: Arc<dyn SolanaRpcSource>) -> Self {
+        self.solana_source = source;
+        self
+    }
+
+    /// 接入代币注册表（按 `token_symbol` 解析代币地址和精度）
+    pub fn with_token_service(mut self, token_service: Arc<TokenService>) -> Self {
+        self.token_service = Some(token_service);
+        self
+    }
+
+    /// 接入交易模拟器（Gas Limit 取 estimateGas + 安全余量，回滚交易直接拒绝）
+    pub fn with_simulator(mut self, simulator: Arc<TxSimulator>) -> Self {
+        self.simulator = Some(simulator);
+        self
     }
 
     /// 构建交易
@@ -175,7 +376,13 @@ impl TransactionBuilder {
     }
 
     /// 构建 Ethereum 系列交易 (ETH, BSC, Polygon)
-    /// ✅企业级:EIP155+EIP1559支持
+    /// ✅企业级:EIP-155 legacy / EIP-2930 / EIP-1559 类型化交易
+    ///
+    /// 类型选择：
+    /// - 显式 `gas_price` 且无 EIP-1559 费用字段：legacy，带 `access_list` 时为 type 0x01
+    /// - 其他情况：type 0x02，费用缺省时由 `GasEstimator` 估算
+    ///
+    /// `raw_transaction` 为待签名载荷（类型前缀 + RLP），`signing_hash` 为其 keccak256
     async fn build_ethereum_like_transaction(
         &self,
         request: BuildTransactionRequest,
@@ -195,123 +402,224 @@ impl TransactionBuilder {
             anyhow::bail!("Invalid to address: {}", request.to);
         }
 
-        // ✅金额验证
-        let amount = request.amount.trim();
-        if amount.is_empty() || (amount.parse::<f64>().is_err() && amount.parse::<u128>().is_err())
-        {
-            anyhow::bail!("Invalid amount: must be valid number");
-        }
+        let from: Address = request
+            .from
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid from address hex: {}", e))?;
+        let to: Address = request
+            .to
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid to address hex: {}", e))?;
+
+        // ✅金额验证（wei，十进制整数，U256精度）
+        let value = TokenAmount::parse_base_units(&request.amount, EVM_NATIVE_DECIMALS)
+            .map_err(|e| anyhow::anyhow!("Invalid amount {}: {}", request.amount, e))?
+            .base_units();
+
+        let data = match request.data.as_deref() {
+            Some(d) if !d.is_empty() && d != "0x" => Bytes::from(
+                hex::decode(d.strip_prefix("0x").unwrap_or(d))
+                    .map_err(|e| anyhow::anyhow!("Invalid data hex: {}", e))?,
+            ),
+            _ => Bytes::default(),
+        };
 
-        // 获取或使用默认值
         let chain_id = request.chain_id.unwrap_or(config.chain_id);
-        let nonce = request.nonce.unwrap_or(0);
+        let rpc_chain = crate::utils::chain_normalizer::normalize_chain_identifier(&request.chain)?;
 
-        // 企业级实现：使用字符串处理Gas价格和限制，支持大数
-        let gas_price = request
-            .gas_price
-            .clone()
-            .unwrap_or_else(|| "20000000000".to_string()); // 20 Gwei default
-                                                           // 企业级实现：根据交易类型智能估算Gas Limit
-        let gas_limit = request.gas_limit.clone().unwrap_or_else(|| {
-            // 检查是否是ERC20转账（transfer函数调用）
-            if let Some(data) = &request.data {
-                if !data.is_empty() && data != "0x" {
-                    if data.starts_with("0xa9059cbb") {
-                        "65000".to_string() // ERC20转账
-                    } else {
-                        "200000".to_string() // 其他合约调用，使用更高的gas limit
-                    }
-                } else {
-                    "21000".to_string() // 简单转账
-                }
-            } else {
-                "21000".to_string() // 简单转账
+        // 签名前模拟：会回滚的交易直接拒绝，基础设施不可用时按配置放行
+        let simulation = match &self.simulator {
+            Some(simulator) => {
+                let call = SimulationCall {
+                    from,
+                    to,
+                    value,
+                    data: data.clone(),
+                };
+                simulator.preflight(&rpc_chain, &call).await?
             }
-        });
+            None => None,
+        };
+        if let Some(reason) = simulation.as_ref().and_then(|s| s.revert_reason.as_ref()) {
+            anyhow::bail!("Transaction would revert: {}", reason);
+        }
 
-        // 构建交易数据 (EIP-155格式)
-        // 企业级实现：使用完整的RLP编码
-        // 注意：这是未签名的交易数据，签名将在前端完成
-        use hex;
-        use rlp::RlpStream;
+        // 企业级实现：优先使用模拟得到的 Gas Limit，否则按交易类型推断
+        let gas_limit = match request.gas_limit.as_deref() {
+            Some(limit) => parse_u256(limit, "gas_limit")?,
+            None => match simulation.as_ref().and_then(|s| s.gas_limit) {
+                Some(limit) => U256::from(limit),
+                None => U256::from(default_gas_limit(&data)),
+            },
+        };
 
-        // 解析地址和金额
-        let to_bytes = hex::decode(request.to.strip_prefix("0x").unwrap_or(&request.to))
-            .map_err(|e| anyhow::anyhow!("Invalid to address hex: {}", e))?;
+        let access_list = request
+            .access_list
+            .as_deref()
+            .map(parse_access_list)
+            .transpose()?;
 
-        // 解析金额（支持大数，使用字符串解析）
-        let value_bytes = {
-            // 尝试解析为u128，如果失败则尝试使用ethers库处理大数
-            // 简化实现：使用u128，生产环境应使用U256
-            let amount_u128 = request
-                .amount
-                .parse::<u128>()
-                .map_err(|_| anyhow::anyhow!("Invalid amount format: {}", request.amount))?;
-            // 转换为32字节大端序
-            let mut bytes = vec![0u8; 32];
-            let amount_bytes = amount_u128.to_be_bytes();
-            bytes[32 - amount_bytes.len()..].copy_from_slice(&amount_bytes);
-            bytes
-        };
-
-        // 解析data字段（可选）
-        let data_bytes = request
-            .data
-            .as_ref()
-            .map(|d| hex::decode(d.strip_prefix("0x").unwrap_or(d)).unwrap_or_default())
-            .unwrap_or_default();
-
-        // 解析gas价格和限制
-        let gas_price_u128 = gas_price.parse::<u128>().unwrap_or(20_000_000_000);
-        let gas_limit_u128 = gas_limit.parse::<u128>().unwrap_or(21_000);
-
-        // 构建RLP编码的交易数据（EIP-155格式，未签名）
-        // 格式: [nonce, gasPrice, gasLimit, to, value, data, chainId, 0, 0]
-        // 注意：r, s, v字段在签名时填充，这里使用0作为占位符
-        let mut rlp_stream = RlpStream::new();
-        rlp_stream.begin_list(9);
-        rlp_stream.append(&nonce);
-        rlp_stream.append(&gas_price_u128);
-        rlp_stream.append(&gas_limit_u128);
-        rlp_stream.append(&to_bytes);
-        rlp_stream.append(&value_bytes);
-        rlp_stream.append(&data_bytes);
-        rlp_stream.append(&chain_id);
-        rlp_stream.append(&0u8); // r (签名后填充)
-        rlp_stream.append(&0u8); // s (签名后填充)
-        rlp_stream.append(&0u8); // v (签名后根据chain_id计算)
-
-        // 将RLP编码转换为十六进制字符串
-        let rlp_bytes = rlp_stream.out();
-        let tx_data = format!("0x{}", hex::encode(rlp_bytes));
-
-        // 计算预估费用（企业级实现：使用字符串乘法或简化计算）
-        // 注意：这里使用简化计算，生产环境应使用大数库
-        let estimated_fee = format!(
-            "{}",
-            gas_price.parse::<u64>().unwrap_or(20_000_000_000)
-                * gas_limit.parse::<u64>().unwrap_or(21_000)
-        );
+        let nonce = match request.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let services = self.evm_services.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("Nonce is required when blockchain client is not configured")
+                })?;
+                services
+                    .blockchain_client
+                    .get_pending_transaction_count(&rpc_chain, &request.from)
+                    .await?
+            }
+        };
+
+        let use_legacy_fees = request.gas_price.is_some()
+            && request.max_fee_per_gas.is_none()
+            && request.max_priority_fee_per_gas.is_none();
+
+        let (typed_tx, tx_type, fee_cap, priority_fee) = if use_legacy_fees {
+            let gas_price = parse_u256(
+                request.gas_price.as_deref().unwrap_or_default(),
+                "gas_price",
+            )?;
+            let legacy = TransactionRequest::new()
+                .from(from)
+                .to(to)
+                .value(value)
+                .data(data)
+                .nonce(nonce)
+                .gas(gas_limit)
+                .gas_price(gas_price)
+                .chain_id(chain_id);
+
+            match access_list {
+                Some(list) => (
+                    TypedTransaction::Eip2930(Eip2930TransactionRequest::new(legacy, list)),
+                    EvmTxType::AccessList,
+                    gas_price,
+                    None,
+                ),
+                None => (
+                    TypedTransaction::Legacy(legacy),
+                    EvmTxType::Legacy,
+                    gas_price,
+                    None,
+                ),
+            }
+        } else {
+            let (max_fee, max_priority_fee) =
+                self.resolve_eip1559_fees(&request, &rpc_chain).await?;
+            let mut tx = Eip1559TransactionRequest::new()
+                .from(from)
+                .to(to)
+                .value(value)
+                .data(data)
+                .nonce(nonce)
+                .gas(gas_limit)
+                .max_fee_per_gas(max_fee)
+                .max_priority_fee_per_gas(max_priority_fee)
+                .chain_id(chain_id);
+            if let Some(list) = access_list {
+                tx = tx.access_list(list);
+            }
+            (
+                TypedTransaction::Eip1559(tx),
+                EvmTxType::DynamicFee,
+                max_fee,
+                Some(max_priority_fee),
+            )
+        };
+
+        let payload = typed_tx.rlp();
+        let signing_hash: H256 = typed_tx.sighash();
+
+        // 最坏情况费用 = gasLimit × gasPrice(或maxFeePerGas)
+        let estimated_fee = gas_limit
+            .checked_mul(fee_cap)
+            .ok_or_else(|| anyhow::anyhow!("Fee overflow: gas_limit × gas_price"))?;
 
         Ok(BuildTransactionResponse {
-            raw_transaction: tx_data,
+            raw_transaction: format!("0x{}", hex::encode(&payload)),
             tx_hash: None, // 将在签名后计算
+            signing_hash: Some(format!("{:?}", signing_hash)),
+            tx_type: Some(tx_type),
             transaction_details: TransactionDetails {
                 chain: config.symbol.clone(),
                 from: request.from,
                 to: request.to,
                 amount: request.amount,
-                gas_price,
-                gas_limit,
-                estimated_fee,
+                gas_price: fee_cap.to_string(),
+                max_priority_fee_per_gas: priority_fee.map(|f| f.to_string()),
+                gas_limit: gas_limit.to_string(),
+                estimated_fee: estimated_fee.to_string(),
                 nonce,
                 chain_id,
             },
         })
     }
 
+    /// 解析 EIP-1559 费用，缺省部分由 GasEstimator（normal 档）补齐
+    async fn resolve_eip1559_fees(
+        &self,
+        request: &BuildTransactionRequest,
+        rpc_chain: &str,
+    ) -> Result<(U256, U256)> {
+        let max_fee = request
+            .max_fee_per_gas
+            .as_deref()
+            .map(|v| parse_u256(v, "max_fee_per_gas"))
+            .transpose()?;
+        let max_priority_fee = request
+            .max_priority_fee_per_gas
+            .as_deref()
+            .map(|v| parse_u256(v, "max_priority_fee_per_gas"))
+            .transpose()?;
+
+        let (max_fee, max_priority_fee) = match (max_fee, max_priority_fee) {
+            (Some(max_fee), Some(priority)) => (max_fee, priority),
+            (max_fee, priority) => {
+                let services = self.evm_services.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "max_fee_per_gas and max_priority_fee_per_gas are required when gas \
+                         estimator is not configured"
+                    )
+                })?;
+                let estimate = services
+                    .gas_estimator
+                    .estimate_gas(rpc_chain, GasSpeed::Normal)
+                    .await?;
+                (
+                    match max_fee {
+                        Some(v) => v,
+                        None => parse_u256(&estimate.max_fee_per_gas, "max_fee_per_gas")?,
+                    },
+                    match priority {
+                        Some(v) => v,
+                        None => parse_u256(&estimate.max_priority_fee, "max_priority_fee")?,
+                    },
+                )
+            }
+        };
+
+        if max_priority_fee > max_fee {
+            anyhow::bail!(
+                "max_priority_fee_per_gas ({}) exceeds max_fee_per_gas ({})",
+                max_priority_fee,
+                max_fee
+            );
+        }
+
+        Ok((max_fee, max_priority_fee))
+    }
+
     /// 构建 Solana 交易
-    /// ✅ 企业级实现：完整的Solana交易构建
+    /// ✅企业级:未签名 legacy 消息（原生 SOL / SPL 代币），由客户端 Ed25519 签名
+    ///
+    /// - 金额为最小单位（lamports / 代币最小单位）
+    /// - SPL：mint 取自 `token_address`，或按 `token_symbol` 从代币注册表解析；
+    ///   接收方 ATA 不存在时前置 `CreateAssociatedTokenAccountIdempotent`（发送方支付租金）
+    /// - 指令前置 Compute Budget 上限与优先费，优先费缺省取最近优先费中位数
+    ///
+    /// `raw_transaction` 为消息字节的 base64，签名对象即该字节串
     async fn build_solana_transaction(
         &self,
         request: BuildTransactionRequest,
@@ -330,42 +638,107 @@ impl TransactionBuilder {
         )? {
             anyhow::bail!("Invalid to address: {}", request.to);
         }
+        let from = Pubkey::from_base58(&request.from)?;
+        let to = Pubkey::from_base58(&request.to)?;
 
-        // 解析金额（Solana使用lamports）
-        let amount_lamports = request
-            .amount
-            .parse::<u64>()
-            .map_err(|_| anyhow::anyhow!("Invalid Solana amount format: {}", request.amount))?;
-
-        // Solana交易使用bincode序列化
-        // 简化实现：返回JSON格式，实际生产环境应使用solana-sdk构建完整交易
-        // 格式: {type: "transfer", from: pubkey, to: pubkey, amount: lamports, recent_blockhash:
-        // ...}
-        let tx_data = serde_json::json!({
-            "type": "transfer",
-            "from": request.from,
-            "to": request.to,
-            "amount": amount_lamports,
-            "chain": config.symbol
-        });
+        let mint = self.resolve_spl_mint(&request, config.chain_id).await?;
+
+        let mut instructions = Vec::new();
+        let mut rent_lamports = 0u64;
+        let default_compute_units = match &mint {
+            None => {
+                let lamports = parse_solana_amount(&request.amount, SOLANA_NATIVE_DECIMALS)?;
+                instructions.push(solana::system_transfer(&from, &to, lamports));
+                solana::NATIVE_TRANSFER_COMPUTE_UNITS
+            }
+            Some((mint, decimals)) => {
+                let amount = parse_solana_amount(&request.amount, u32::from(*decimals))?;
+                let source = solana::associated_token_address(&from, mint)?;
+                let destination = solana::associated_token_address(&to, mint)?;
+
+                let mut units = solana::SPL_TRANSFER_COMPUTE_UNITS;
+                if !self
+                    .solana_source
+                    .account_exists(&destination.to_base58())
+                    .await?
+                {
+                    instructions.push(solana::create_associated_token_account_idempotent(
+                        &from, &to, mint,
+                    )?);
+                    rent_lamports = solana::TOKEN_ACCOUNT_RENT_EXEMPT_LAMPORTS;
+                    units += solana::CREATE_ATA_COMPUTE_UNITS;
+                }
+                instructions.push(solana::spl_transfer_checked(
+                    &source,
+                    mint,
+                    &destination,
+                    &from,
+                    amount,
+                    *decimals,
+                ));
+                units
+            }
+        };
+
+        let compute_units = match request.gas_limit.as_deref() {
+            Some(limit) => limit
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("Invalid compute unit limit: {}", limit))?,
+            None => default_compute_units,
+        };
+        let compute_unit_price = match request.gas_price.as_deref() {
+            Some(price) => price
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("Invalid compute unit price: {}", price))?,
+            None => {
+                let writable: Vec<String> = instructions
+                    .iter()
+                    .flat_map(|ix| ix.accounts.iter())
+                    .filter(|m| m.is_writable)
+                    .map(|m| m.pubkey.to_base58())
+                    .collect();
+                self.solana_source.priority_fee(&writable).await?
+            }
+        };
+
+        let mut all_instructions = vec![
+            solana::set_compute_unit_limit(compute_units),
+            solana::set_compute_unit_price(compute_unit_price),
+        ];
+        all_instructions.extend(instructions);
 
-        // 获取动态费用（如果可用）
-        let estimated_fee = {
-            // 尝试从动态费用服务获取，如果失败则使用默认值
-            // 注意：这里需要传入DynamicFeeService实例，简化实现使用默认值
-            "0.000005".to_string() // Solana基础费用约5000 lamports = 0.000005 SOL
+        let blockhash = match request.recent_blockhash.clone() {
+            Some(blockhash) => blockhash,
+            None => self.solana_source.latest_blockhash().await?,
         };
+        let blockhash = Pubkey::from_base58(&blockhash)
+            .map_err(|_| anyhow::anyhow!("Invalid recent blockhash: {}", blockhash))?;
+
+        let message = SolanaMessage::compile(&from, &all_instructions, blockhash.0)?;
+
+        let fee_lamports = solana::LAMPORTS_PER_SIGNATURE
+            * u64::from(message.num_required_signatures)
+            + solana::priority_fee_lamports(compute_units, compute_unit_price)
+            + rent_lamports;
+        let estimated_fee =
+            TokenAmount::from_base_units(U256::from(fee_lamports), SOLANA_NATIVE_DECIMALS)
+                .to_human_string();
 
         Ok(BuildTransactionResponse {
-            raw_transaction: serde_json::to_string(&tx_data)?,
+            raw_transaction: base64::engine::general_purpose::STANDARD.encode(message.serialize()),
             tx_hash: None,
+            signing_hash: None,
+            tx_type: None,
             transaction_details: TransactionDetails {
                 chain: config.symbol.clone(),
                 from: request.from,
                 to: request.to,
                 amount: request.amount,
-                gas_price: "0".to_string(), // Solana 使用不同的费用模型（按lamports计算）
-                gas_limit: "0".to_string(),
+                gas_price: compute_unit_price.to_string(), // micro-lamports/CU
+                max_priority_fee_per_gas: None,
+                gas_limit: compute_units.to_string(), // 计算单元
                 estimated_fee,
                 nonce: 0,
                 chain_id: config.chain_id,
@@ -373,53 +746,98 @@ impl TransactionBuilder {
         })
     }
 
+    /// 解析 SPL mint 及精度；原生 SOL 返回 `None`
+    async fn resolve_spl_mint(
+        &self,
+        request: &BuildTransactionRequest,
+        chain_id: u64,
+    ) -> Result<Option<(Pubkey, u8)>> {
+        let symbol = request
+            .token_symbol
+            .as_deref()
+            .filter(|s| !s.eq_ignore_ascii_case("SOL"));
+
+        let (mint, registry_decimals) = match (&request.token_address, symbol) {
+            (Some(address), _) => (address.clone(), None),
+            (None, Some(symbol)) => {
+                let token_service = self.token_service.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("Token registry is not configured to resolve {}", symbol)
+                })?;
+                let token = token_service
+                    .get_token(&symbol.to_uppercase(), chain_id)
+                    .await?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Token {} not supported on chain {}", symbol, chain_id)
+                    })?;
+                (token.address, Some(token.decimals as u32))
+            }
+            (None, None) => return Ok(None),
+        };
+
+        let mint_key = Pubkey::from_base58(&mint)
+            .map_err(|_| anyhow::anyhow!("Invalid SPL mint address: {}", mint))?;
+        let decimals = match request.token_decimals.or(registry_decimals) {
+            Some(decimals) => u8::try_from(decimals)
+                .map_err(|_| anyhow::anyhow!("Invalid token decimals: {}", decimals))?,
+            None => self.solana_source.mint_decimals(&mint).await?,
+        };
+
+        Ok(Some((mint_key, decimals)))
+    }
+
     /// 构建 Bitcoin 交易
+    /// ✅企业级:UTXO选币+找零+按sat/vB计费，返回未签名PSBT（BIP-174, base64）
     async fn build_bitcoin_transaction(
         &self,
         request: BuildTransactionRequest,
         config: &ChainConfig,
     ) -> Result<BuildTransactionResponse> {
-        // Bitcoin 交易格式不同，需要UTXO模型
-        // 企业级实现：构建符合Bitcoin标准的交易结构
-        // 注意：实际生产环境需要查询UTXO并构建完整交易
-
-        // 解析金额（BTC转sat）
-        let amount_btc = request
-            .amount
-            .parse::<f64>()
-            .map_err(|_| anyhow::anyhow!("Invalid Bitcoin amount format: {}", request.amount))?;
-        let amount_sat = (amount_btc * 100_000_000.0) as u64;
-
-        // Bitcoin交易使用自定义格式
-        // 简化实现：返回JSON格式，实际生产环境应使用bitcoin库构建完整交易
-        // 格式: {version: 2, inputs: [...], outputs: [...], locktime: 0}
-        let tx_data = serde_json::json!({
-            "version": 2,
-            "inputs": [], // 需要从UTXO查询填充
-            "outputs": [{
-                "value": amount_sat,
-                "script_pubkey": request.to // 需要转换为script
-            }],
-            "locktime": 0
-        });
+        // 解析金额（BTC转sat，精确十进制解析，拒绝超过8位小数）
+        let amount =
+            bitcoin::Amount::from_str_in(request.amount.trim(), bitcoin::Denomination::Bitcoin)
+                .map_err(|e| anyhow::anyhow!("Invalid Bitcoin amount {}: {}", request.amount, e))?;
 
-        // 获取动态费用（如果可用）
-        let estimated_fee = {
-            // 尝试从动态费用服务获取，如果失败则使用默认值
-            // 注意：这里需要传入DynamicFeeService实例，简化实现使用默认值
-            "0.00001".to_string() // Bitcoin默认费用约1000 sat = 0.00001 BTC
-        };
+        // gas_price 在 Bitcoin 上表示 sat/vB 费率
+        let fee_rate = request
+            .gas_price
+            .as_deref()
+            .map(|r| {
+                r.trim()
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("Invalid Bitcoin fee rate: {}", r))
+            })
+            .transpose()?;
+
+        let psbt = self
+            .bitcoin_builder
+            .build(&PsbtRequest {
+                from: request.from.clone(),
+                to: request.to.clone(),
+                change_address: request.change_address.clone(),
+                public_key: request.public_key.clone(),
+                key_origin: request.key_origin.clone(),
+                amount: amount.to_sat(),
+                fee_rate,
+                target_blocks: BITCOIN_DEFAULT_TARGET_BLOCKS,
+            })
+            .await?;
+
+        let estimated_fee =
+            bitcoin::Amount::from_sat(psbt.fee).to_string_in(bitcoin::Denomination::Bitcoin);
 
         Ok(BuildTransactionResponse {
-            raw_transaction: serde_json::to_string(&tx_data)?,
+            raw_transaction: psbt.psbt_base64,
             tx_hash: None,
+            signing_hash: None,
+            tx_type: None,
             transaction_details: TransactionDetails {
                 chain: config.symbol.clone(),
                 from: request.from,
                 to: request.to,
                 amount: request.amount,
-                gas_price: "0".to_string(), // Bitcoin 使用不同的费用模型（按sat/vbyte计算）
-                gas_limit: "0".to_string(),
+                gas_price: psbt.fee_rate.to_string(), // sat/vB
+                max_priority_fee_per_gas: None,
+                gas_limit: psbt.vsize.to_string(), // 虚拟大小 vB
                 estimated_fee,
                 nonce: 0,
                 chain_id: config.chain_id,
@@ -428,57 +846,176 @@ impl TransactionBuilder {
     }
 
     /// 构建 TON 交易
+    /// ✅企业级:wallet v4r2 / v5r1 未签名外部消息（BOC）
+    ///
+    /// - 原生转账：内部消息发往 `to`，bounce 标志取自接收地址格式，可附文本备注
+    /// - Jetton 转账（`token_address` 为 master）：内部消息发往发送方的 Jetton 钱包，
+    ///   附带 0.05 TON 作为手续费，多余部分退回发送方
+    ///
+    /// `raw_transaction` 为外部消息的 base64 BOC，body 为未签名载荷；
+    /// 客户端对 `signing_hash` 签名后，v4r2 将签名前置、v5r1 将签名追加到 body 再广播
     async fn build_ton_transaction(
         &self,
         request: BuildTransactionRequest,
         config: &ChainConfig,
     ) -> Result<BuildTransactionResponse> {
-        // TON 交易格式不同
-        // 企业级实现：构建符合TON标准的交易结构
-        // 注意：实际生产环境需要使用TON SDK构建完整交易
-
-        // 解析金额（TON转nanoTON）
-        let amount_ton = request
-            .amount
-            .parse::<f64>()
-            .map_err(|_| anyhow::anyhow!("Invalid TON amount format: {}", request.amount))?;
-        let amount_nano = (amount_ton * 1_000_000_000.0) as u64;
-
-        // TON交易使用自定义格式
-        // 简化实现：返回JSON格式，实际生产环境应使用TON SDK构建完整交易
-        // 格式: {from: address, to: address, amount: nanotons, ...}
-        let tx_data = serde_json::json!({
-            "from": request.from,
-            "to": request.to,
-            "amount": amount_nano,
-            "chain": config.symbol
-        });
+        let wallet = TonAddress::parse(&request.from)
+            .map_err(|e| anyhow::anyhow!("Invalid from address for TON: {}", e))?;
+        let recipient = TonAddress::parse(&request.to)
+            .map_err(|e| anyhow::anyhow!("Invalid to address for TON: {}", e))?;
+
+        let seqno = match request.nonce {
+            Some(nonce) => {
+                u32::try_from(nonce).map_err(|_| anyhow::anyhow!("Invalid TON seqno: {}", nonce))?
+            }
+            None => self.ton_account_source.seqno(&request.from).await?,
+        };
+
+        let (message, estimated_fee) = match &request.token_address {
+            Some(jetton_master) => {
+                let decimals = request.token_decimals.unwrap_or(JETTON_DEFAULT_DECIMALS);
+                let amount = parse_ton_amount(&request.amount, decimals)?;
+                let jetton_wallet = self
+                    .ton_account_source
+                    .jetton_wallet(&request.from, jetton_master)
+                    .await?;
+                let body = JettonTransfer {
+                    query_id: u64::from(seqno),
+                    amount,
+                    destination: recipient,
+                    response_destination: wallet,
+                    forward_ton_amount: if request.comment.is_some() { 1 } else { 0 },
+                    comment: request.comment.clone(),
+                }
+                .to_body()?;
+
+                let message = InternalMessage {
+                    dest: TonAddress::parse(&jetton_wallet)?,
+                    value: JETTON_TRANSFER_ATTACHED_NANOTON,
+                    bounce: true,
+                    body: Some(body),
+                };
+                (message, "0.05".to_string())
+            }
+            None => {
+                let message = InternalMessage {
+                    dest: recipient,
+                    value: parse_ton_amount(&request.amount, TON_DECIMALS)?,
+                    bounce: recipient.bounceable,
+                    body: request.comment.as_deref().map(comment_body).transpose()?,
+                };
+                (message, "0.01".to_string()) // TON默认费用约0.01 TON
+            }
+        };
 
-        // 获取动态费用（如果可用）
-        let estimated_fee = {
-            // 尝试从动态费用服务获取，如果失败则使用默认值
-            // 注意：这里需要传入DynamicFeeService实例，简化实现使用默认值
-            "0.01".to_string() // TON默认费用约0.01 TON
+        let version = request.wallet_version.unwrap_or_default();
+        let valid_until = chrono::Utc::now().timestamp() as u64 + TON_MESSAGE_TTL_SECS;
+        let transfer = WalletTransfer {
+            version,
+            wallet_id: match version {
+                TonWalletVersion::V4r2 => WALLET_V4_DEFAULT_SUBWALLET_ID,
+                TonWalletVersion::V5r1 => WALLET_V5_MAINNET_WALLET_ID,
+            },
+            seqno,
+            valid_until: valid_until as u32,
+            send_mode: DEFAULT_SEND_MODE,
+            message,
         };
 
+        let signing_hash = transfer.signing_payload()?.hash();
+        let external = transfer.external_message(&wallet)?;
+
         Ok(BuildTransactionResponse {
-            raw_transaction: serde_json::to_string(&tx_data)?,
+            raw_transaction: external.to_boc_base64(),
             tx_hash: None,
+            signing_hash: Some(format!("0x{}", hex::encode(signing_hash))),
+            tx_type: None,
             transaction_details: TransactionDetails {
                 chain: config.symbol.clone(),
                 from: request.from,
                 to: request.to,
                 amount: request.amount,
                 gas_price: "0".to_string(), // TON 使用不同的费用模型（按gas计算）
+                max_priority_fee_per_gas: None,
                 gas_limit: "0".to_string(),
                 estimated_fee,
-                nonce: 0,
+                nonce: u64::from(seqno),
                 chain_id: config.chain_id,
             },
         })
     }
 }
 
+/// 解析 Solana 最小单位金额（u64）
+fn parse_solana_amount(amount: &str, decimals: u32) -> Result<u64> {
+    let units = TokenAmount::parse_base_units(amount, decimals)
+        .and_then(TokenAmount::ensure_positive)
+        .map_err(|e| anyhow::anyhow!("Invalid Solana amount {}: {}", amount, e))?
+        .base_units();
+    if units > U256::from(u64::MAX) {
+        anyhow::bail!("Solana amount too large: {}", amount);
+    }
+    Ok(units.as_u64())
+}
+
+/// 解析 TON / Jetton 金额为最小单位（coins 字段上限 120 位）
+fn parse_ton_amount(amount: &str, decimals: u32) -> Result<u128> {
+    let units = TokenAmount::parse(amount, decimals)
+        .and_then(TokenAmount::ensure_positive)
+        .map_err(|e| anyhow::anyhow!("Invalid TON amount {}: {}", amount, e))?
+        .base_units();
+    if units.bits() > 120 {
+        anyhow::bail!("TON amount too large: {}", amount);
+    }
+    Ok(units.as_u128())
+}
+
+/// 解析十进制或 0x 十六进制整数
+fn parse_u256(value: &str, field: &str) -> Result<U256> {
+    let value = value.trim();
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex_str) => U256::from_str_radix(hex_str, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+    parsed.ok_or_else(|| anyhow::anyhow!("Invalid {}: {}", field, value))
+}
+
+/// 根据调用数据推断默认 Gas Limit
+fn default_gas_limit(data: &Bytes) -> u64 {
+    if data.is_empty() {
+        21_000 // 简单转账
+    } else if data.starts_with(&[0xa9, 0x05, 0x9c, 0xbb]) {
+        65_000 // ERC20 transfer
+    } else {
+        200_000 // 其他合约调用，使用更高的gas limit
+    }
+}
+
+fn parse_access_list(entries: &[AccessListEntry]) -> Result<AccessList> {
+    entries
+        .iter()
+        .map(|entry| {
+            let address: Address = entry
+                .address
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid access list address: {}", e))?;
+            let storage_keys = entry
+                .storage_keys
+                .iter()
+                .map(|key| {
+                    key.parse::<H256>()
+                        .map_err(|e| anyhow::anyhow!("Invalid storage key {}: {}", key, e))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(AccessListItem {
+                address,
+                storage_keys,
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(AccessList)
+}
+
 impl Default for TransactionBuilder {
     fn default() -> Self {
         Self::new()
@@ -489,46 +1026,244 @@ impl Default for TransactionBuilder {
 mod tests {
     use super::*;
 
+    fn eth_request() -> BuildTransactionRequest {
+        BuildTransactionRequest {
+            chain: "ETH".to_string(),
+            from: "0x742d35cc6634c0532925a3b844bc9e7595f0beb6".to_string(),
+            to: "0x3535353535353535353535353535353535353535".to_string(),
+            amount: "1000000000000000000".to_string(), // 1 ETH
+            nonce: Some(9),
+            ..Default::default()
+        }
+    }
+
     #[tokio::test]
     async fn test_build_ethereum_transaction() {
         let builder = TransactionBuilder::new();
 
         let request = BuildTransactionRequest {
-            chain: "ETH".to_string(),
-            from: "0x742d35cc6634c0532925a3b844bc9e7595f0beb6".to_string(),
-            to: "0x1234567890123456789012345678901234567890".to_string(),
-            amount: "1000000000000000000".to_string(), // 1 ETH
-            data: None,
-            gas_price: None,
-            gas_limit: None,
-            nonce: None,
-            chain_id: None,
+            max_fee_per_gas: Some("30000000000".to_string()),
+            max_priority_fee_per_gas: Some("0x77359400".to_string()), // 2 gwei
+            ..eth_request()
         };
 
         let response = builder.build_transaction(request).await.unwrap();
         assert_eq!(response.transaction_details.chain, "ETH");
         assert_eq!(response.transaction_details.amount, "1000000000000000000");
-        // 验证RLP编码格式
-        assert!(response.raw_transaction.starts_with("0x"));
+        assert_eq!(response.tx_type, Some(EvmTxType::DynamicFee));
+        // EIP-2718 类型前缀 0x02
+        assert!(response.raw_transaction.starts_with("0x02"));
+        assert_eq!(
+            response
+                .transaction_details
+                .max_priority_fee_per_gas
+                .as_deref(),
+            Some("2000000000")
+        );
+        assert_eq!(
+            response.transaction_details.estimated_fee,
+            "630000000000000"
+        );
+
+        let payload = hex::decode(&response.raw_transaction[2..]).unwrap();
+        let fields = rlp::Rlp::new(&payload[1..]);
+        assert_eq!(fields.item_count().unwrap(), 9);
+        assert_eq!(fields.val_at::<u64>(0).unwrap(), 1); // chainId
+        assert_eq!(fields.val_at::<u64>(1).unwrap(), 9); // nonce
+        assert_eq!(
+            response.signing_hash.unwrap(),
+            format!("0x{}", hex::encode(ethers::utils::keccak256(&payload)))
+        );
     }
 
     #[tokio::test]
-    async fn test_build_solana_transaction() {
+    async fn test_build_legacy_eip155_signing_hash() {
+        // EIP-155 规范示例
+        let builder = TransactionBuilder::new();
+
+        let request = BuildTransactionRequest {
+            gas_price: Some("20000000000".to_string()),
+            gas_limit: Some("21000".to_string()),
+            ..eth_request()
+        };
+
+        let response = builder.build_transaction(request).await.unwrap();
+        assert_eq!(response.tx_type, Some(EvmTxType::Legacy));
+        assert_eq!(
+            response.raw_transaction,
+            "0xec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            response.signing_hash.as_deref(),
+            Some("0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_eip2930_transaction() {
         let builder = TransactionBuilder::new();
 
         let request = BuildTransactionRequest {
+            gas_price: Some("20000000000".to_string()),
+            access_list: Some(vec![AccessListEntry {
+                address: "0xdac17f958d2ee523a2206206994597c13d831ec7".to_string(),
+                storage_keys: vec![format!("0x{}", "00".repeat(31) + "01")],
+            }]),
+            ..eth_request()
+        };
+
+        let response = builder.build_transaction(request).await.unwrap();
+        assert_eq!(response.tx_type, Some(EvmTxType::AccessList));
+        assert!(response.raw_transaction.starts_with("0x01"));
+
+        let payload = hex::decode(&response.raw_transaction[2..]).unwrap();
+        let fields = rlp::Rlp::new(&payload[1..]);
+        // [chainId, nonce, gasPrice, gas, to, value, data, accessList]
+        assert_eq!(fields.item_count().unwrap(), 8);
+        assert_eq!(fields.at(7).unwrap().item_count().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_build_ethereum_transaction_requires_nonce_source() {
+        let builder = TransactionBuilder::new();
+
+        let request = BuildTransactionRequest {
+            nonce: None,
+            gas_price: Some("20000000000".to_string()),
+            ..eth_request()
+        };
+
+        assert!(builder.build_transaction(request).await.is_err());
+    }
+
+    /// 本地 JSON-RPC 桩：发往 0x…dead 的调用回滚，其余 estimateGas = 30000
+    async fn mock_simulation_rpc() -> String {
+        use axum::{routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/",
+            post(|Json(req): Json<serde_json::Value>| async move {
+                let to = req["params"][0]["to"].as_str().unwrap_or_default();
+                Json(match req["method"].as_str().unwrap_or_default() {
+                    _ if to.ends_with("dead") => serde_json::json!({
+                        "jsonrpc": "2.0", "id": 1,
+                        "error": { "code": 3, "message": "execution reverted: paused" }
+                    }),
+                    "eth_estimateGas" => {
+                        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x7530" })
+                    }
+                    "eth_call" => serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }),
+                    _ => serde_json::json!({
+                        "jsonrpc": "2.0", "id": 1,
+                        "error": { "code": -32601, "message": "method not found" }
+                    }),
+                })
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    struct StaticEndpoint(String);
+
+    #[async_trait::async_trait]
+    impl crate::service::multi_node_verifier::EndpointProvider for StaticEndpoint {
+        async fn distinct_endpoints(&self, _chain: &str, _count: usize) -> Vec<String> {
+            vec![self.0.clone()]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_ethereum_transaction_uses_simulation() {
+        let simulator = TxSimulator::with_endpoint_provider(Arc::new(StaticEndpoint(
+            mock_simulation_rpc().await,
+        )))
+        .with_gas_margin_bps(2_000);
+        let builder = TransactionBuilder::new().with_simulator(Arc::new(simulator));
+
+        let request = BuildTransactionRequest {
+            gas_price: Some("20000000000".to_string()),
+            ..eth_request()
+        };
+        let response = builder.build_transaction(request.clone()).await.unwrap();
+        assert_eq!(response.transaction_details.gas_limit, "36000");
+
+        let reverting = BuildTransactionRequest {
+            to: "0x000000000000000000000000000000000000dead".to_string(),
+            ..request
+        };
+        let err = builder.build_transaction(reverting).await.unwrap_err();
+        assert!(err.to_string().contains("paused"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_build_ethereum_transaction_rejects_priority_above_cap() {
+        let builder = TransactionBuilder::new();
+
+        let request = BuildTransactionRequest {
+            max_fee_per_gas: Some("1000000000".to_string()),
+            max_priority_fee_per_gas: Some("2000000000".to_string()),
+            ..eth_request()
+        };
+
+        assert!(builder.build_transaction(request).await.is_err());
+    }
+
+    struct MockSolanaSource {
+        recipient_ata_exists: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl SolanaRpcSource for MockSolanaSource {
+        async fn latest_blockhash(&self) -> Result<String> {
+            Ok("EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N".to_string())
+        }
+
+        async fn account_exists(&self, _address: &str) -> Result<bool> {
+            Ok(self.recipient_ata_exists)
+        }
+
+        async fn mint_decimals(&self, _mint: &str) -> Result<u8> {
+            Ok(6)
+        }
+
+        async fn priority_fee(&self, _writable_accounts: &[String]) -> Result<u64> {
+            Ok(10_000)
+        }
+    }
+
+    fn solana_builder(recipient_ata_exists: bool) -> TransactionBuilder {
+        TransactionBuilder::new().with_solana_source(Arc::new(MockSolanaSource {
+            recipient_ata_exists,
+        }))
+    }
+
+    fn solana_request() -> BuildTransactionRequest {
+        BuildTransactionRequest {
             chain: "SOL".to_string(),
             from: "11111111111111111111111111111112".to_string(), // 有效的Base58
             to: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi".to_string(), // 有效的Base58
             amount: "1000000000".to_string(),                     // 1 SOL
-            data: None,
-            gas_price: None,
-            gas_limit: None,
-            nonce: None,
-            chain_id: None,
-        };
+            ..Default::default()
+        }
+    }
 
-        let response = builder.build_transaction(request).await.unwrap();
+    fn decode_solana_message(raw: &str) -> Vec<u8> {
+        base64::engine::general_purpose::STANDARD
+            .decode(raw)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_solana_transaction() {
+        let builder = solana_builder(true);
+
+        let response = builder.build_transaction(solana_request()).await.unwrap();
         assert_eq!(response.transaction_details.chain, "SOL");
         assert!(
             response
@@ -538,54 +1273,206 @@ mod tests {
                 .unwrap()
                 > 0.0
         );
+        // 5000 签名费 + ceil(1000 CU × 10000 µlamports) = 5010 lamports
+        assert_eq!(response.transaction_details.estimated_fee, "0.00000501");
+        assert_eq!(response.transaction_details.gas_price, "10000");
+
+        let message = decode_solana_message(&response.raw_transaction);
+        // header: 1 签名者，0 只读签名者，2 只读非签名者（ComputeBudget、System）
+        assert_eq!(&message[..4], &[1, 0, 2, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_build_spl_transfer_creates_recipient_ata() {
+        let builder = solana_builder(false);
+
+        let request = BuildTransactionRequest {
+            amount: "2500000".to_string(), // 2.5 USDC
+            token_address: Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+            ..solana_request()
+        };
+
+        let response = builder.build_transaction(request).await.unwrap();
+        assert_eq!(
+            response.transaction_details.gas_limit,
+            (solana::SPL_TRANSFER_COMPUTE_UNITS + solana::CREATE_ATA_COMPUTE_UNITS).to_string()
+        );
+        // 签名费 + 优先费 + ATA 租金
+        assert_eq!(response.transaction_details.estimated_fee, "0.00204493");
+
+        let message = decode_solana_message(&response.raw_transaction);
+        // 9 个账户：owner、接收方ATA、发送方ATA | ComputeBudget、接收方、mint、System、Token、ATA程序
+        assert_eq!(&message[..4], &[1, 0, 6, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_build_spl_transfer_existing_ata_and_overrides() {
+        let builder = solana_builder(true);
+
+        let request = BuildTransactionRequest {
+            amount: "2500000".to_string(),
+            token_address: Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+            token_decimals: Some(6),
+            gas_price: Some("0".to_string()),
+            gas_limit: Some("40000".to_string()),
+            recent_blockhash: Some("11111111111111111111111111111111".to_string()),
+            ..solana_request()
+        };
+
+        let response = builder.build_transaction(request).await.unwrap();
+        assert_eq!(response.transaction_details.gas_limit, "40000");
+        assert_eq!(response.transaction_details.estimated_fee, "0.000005");
+
+        let message = decode_solana_message(&response.raw_transaction);
+        assert_eq!(&message[..4], &[1, 0, 3, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_build_solana_transaction_rejects_invalid_amount() {
+        let builder = solana_builder(true);
+
+        for amount in ["1.5", "0", "18446744073709551616"] {
+            let request = BuildTransactionRequest {
+                amount: amount.to_string(),
+                ..solana_request()
+            };
+            assert!(
+                builder.build_transaction(request).await.is_err(),
+                "{}",
+                amount
+            );
+        }
+
+        // 未接入代币注册表时无法按符号解析
+        let request = BuildTransactionRequest {
+            token_symbol: Some("USDC".to_string()),
+            ..solana_request()
+        };
+        assert!(builder.build_transaction(request).await.is_err());
+    }
+
+    struct MockUtxoSource;
+
+    #[async_trait::async_trait]
+    impl UtxoSource for MockUtxoSource {
+        async fn list_utxos(
+            &self,
+            _address: &str,
+        ) -> Result<Vec<crate::service::bitcoin_psbt_builder::Utxo>> {
+            Ok(vec![crate::service::bitcoin_psbt_builder::Utxo {
+                txid: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+                    .to_string(),
+                vout: 0,
+                value: 1_000_000,
+                confirmed: true,
+            }])
+        }
+
+        async fn fee_rate(&self, _target_blocks: u16) -> Result<u64> {
+            Ok(10)
+        }
+
+        async fn raw_transaction(&self, _txid: &str) -> Result<Vec<u8>> {
+            anyhow::bail!("not needed for segwit inputs")
+        }
     }
 
     #[tokio::test]
     async fn test_build_bitcoin_transaction() {
-        let builder = TransactionBuilder::new();
+        let builder = TransactionBuilder::new()
+            .with_bitcoin_network(bitcoin::Network::Bitcoin)
+            .with_utxo_source(Arc::new(MockUtxoSource));
 
         let request = BuildTransactionRequest {
             chain: "BTC".to_string(),
             from: "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh".to_string(),
             to: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_string(),
             amount: "0.001".to_string(), // 0.001 BTC
-            data: None,
-            gas_price: None,
-            gas_limit: None,
-            nonce: None,
-            chain_id: None,
+            ..Default::default()
         };
+        // 产生找零但未提供找零地址：拒绝（不找零回发送地址）
+        assert!(builder.build_transaction(request.clone()).await.is_err());
 
+        let request = BuildTransactionRequest {
+            change_address: Some("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string()),
+            ..request
+        };
         let response = builder.build_transaction(request).await.unwrap();
         assert_eq!(response.transaction_details.chain, "BTC");
-        assert!(
-            response
-                .transaction_details
-                .estimated_fee
-                .parse::<f64>()
-                .unwrap()
-                > 0.0
+        // 1输入 + 收款 + 找零 = 11 + 68 + 31 + 31 = 141 vB，10 sat/vB
+        assert_eq!(response.transaction_details.gas_price, "10");
+        assert_eq!(response.transaction_details.gas_limit, "141");
+        assert_eq!(response.transaction_details.estimated_fee, "0.0000141");
+
+        let psbt_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &response.raw_transaction,
+        )
+        .unwrap();
+        let psbt = bitcoin::psbt::Psbt::deserialize(&psbt_bytes).unwrap();
+        assert_eq!(psbt.unsigned_tx.input.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output.len(), 2);
+        assert_eq!(psbt.unsigned_tx.output[0].value.to_sat(), 100_000);
+        assert_eq!(
+            psbt.unsigned_tx.output[1].value.to_sat(),
+            1_000_000 - 100_000 - 1_410
         );
+        assert!(psbt.inputs[0].witness_utxo.is_some());
     }
 
     #[tokio::test]
-    async fn test_build_ton_transaction() {
-        let builder = TransactionBuilder::new();
+    async fn test_build_bitcoin_transaction_rejects_excess_precision() {
+        let builder = TransactionBuilder::new()
+            .with_bitcoin_network(bitcoin::Network::Bitcoin)
+            .with_utxo_source(Arc::new(MockUtxoSource));
 
         let request = BuildTransactionRequest {
+            chain: "BTC".to_string(),
+            from: "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh".to_string(),
+            to: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_string(),
+            amount: "0.000000001".to_string(),
+            ..Default::default()
+        };
+
+        assert!(builder.build_transaction(request).await.is_err());
+    }
+
+    struct MockTonAccountSource;
+
+    #[async_trait::async_trait]
+    impl TonAccountSource for MockTonAccountSource {
+        async fn seqno(&self, _address: &str) -> Result<u32> {
+            Ok(42)
+        }
+
+        async fn jetton_wallet(&self, _owner: &str, _jetton_master: &str) -> Result<String> {
+            Ok(format!("0:{}", "11".repeat(32)))
+        }
+    }
+
+    fn ton_request() -> BuildTransactionRequest {
+        BuildTransactionRequest {
             chain: "TON".to_string(),
             from: "EQD__________________________________________0vo".to_string(),
             to: "EQD__________________________________________0vo".to_string(),
             amount: "1.0".to_string(), // 1 TON
-            data: None,
-            gas_price: None,
-            gas_limit: None,
-            nonce: None,
-            chain_id: None,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_ton_transaction() {
+        let builder =
+            TransactionBuilder::new().with_ton_account_source(Arc::new(MockTonAccountSource));
+
+        let request = BuildTransactionRequest {
+            comment: Some("hello".to_string()),
+            ..ton_request()
         };
 
         let response = builder.build_transaction(request).await.unwrap();
         assert_eq!(response.transaction_details.chain, "TON");
+        assert_eq!(response.transaction_details.nonce, 42);
         assert!(
             response
                 .transaction_details
@@ -594,5 +1481,70 @@ mod tests {
                 .unwrap()
                 > 0.0
         );
+
+        let boc = base64::engine::general_purpose::STANDARD
+            .decode(&response.raw_transaction)
+            .unwrap();
+        assert_eq!(&boc[..4], &[0xb5, 0xee, 0x9c, 0x72]);
+        assert_eq!(response.signing_hash.unwrap().len(), 66);
+    }
+
+    #[tokio::test]
+    async fn test_build_ton_transaction_v5_differs_from_v4() {
+        let builder =
+            TransactionBuilder::new().with_ton_account_source(Arc::new(MockTonAccountSource));
+
+        let request = BuildTransactionRequest {
+            nonce: Some(3),
+            ..ton_request()
+        };
+        let v4 = builder.build_transaction(request.clone()).await.unwrap();
+        let v5 = builder
+            .build_transaction(BuildTransactionRequest {
+                wallet_version: Some(TonWalletVersion::V5r1),
+                ..request
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(v4.transaction_details.nonce, 3);
+        assert_ne!(v4.signing_hash, v5.signing_hash);
+    }
+
+    #[tokio::test]
+    async fn test_build_jetton_transfer() {
+        let builder =
+            TransactionBuilder::new().with_ton_account_source(Arc::new(MockTonAccountSource));
+
+        let request = BuildTransactionRequest {
+            amount: "12.5".to_string(),
+            token_address: Some("EQD__________________________________________0vo".to_string()),
+            token_decimals: Some(6),
+            ..ton_request()
+        };
+
+        let response = builder.build_transaction(request).await.unwrap();
+        assert_eq!(response.transaction_details.estimated_fee, "0.05");
+
+        // 精度超出 Jetton decimals 时拒绝
+        let request = BuildTransactionRequest {
+            amount: "0.0000001".to_string(),
+            token_address: Some("EQD__________________________________________0vo".to_string()),
+            token_decimals: Some(6),
+            ..ton_request()
+        };
+        assert!(builder.build_transaction(request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_build_ton_transaction_rejects_invalid_address() {
+        let builder =
+            TransactionBuilder::new().with_ton_account_source(Arc::new(MockTonAccountSource));
+
+        let request = BuildTransactionRequest {
+            to: "EQD__________________________________________0vA".to_string(),
+            ..ton_request()
+        };
+        assert!(builder.build_transaction(request).await.is_err());
     }
 }
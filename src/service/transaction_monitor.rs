@@ -8,7 +8,7 @@ use anyhow::{Context, Result};
 use sqlx::PgPool;
 use tokio::time::interval;
 
-use crate::repository::SwapTransactionRepository;
+use crate::{infrastructure::worker_supervisor::ChainShard, repository::SwapTransactionRepository};
 
 const MONITOR_INTERVAL_SECS: u64 = 30; // 每30秒检查一次
 const MAX_RETRIES_PER_TX: i32 = 20; // 最多重试20次
@@ -17,6 +17,8 @@ const BATCH_SIZE: i64 = 50; // 每批处理50笔交易
 pub struct TransactionMonitor {
     pool: PgPool,
     blockchain_client: Arc<crate::service::blockchain_client::BlockchainClient>,
+    /// 负责的链分片（多实例部署时由 WorkerSupervisor 分配）
+    shard: ChainShard,
 }
 
 impl TransactionMonitor {
@@ -27,20 +29,31 @@ impl TransactionMonitor {
         Self {
             pool,
             blockchain_client,
+            shard: ChainShard::All,
         }
     }
 
+    /// 仅处理指定分片的链
+    pub fn with_shard(mut self, shard: ChainShard) -> Self {
+        self.shard = shard;
+        self
+    }
+
     /// 启动后台监控任务（持续运行）
     pub async fn start_background_monitor(self: Arc<Self>) {
         let mut ticker = interval(Duration::from_secs(MONITOR_INTERVAL_SECS));
 
         tracing::info!(
+            shard = %self.shard.key(),
             "Transaction monitor started, interval={}s",
             MONITOR_INTERVAL_SECS
         );
 
         loop {
             ticker.tick().await;
+            if !crate::infrastructure::worker_supervisor::lease_held().await {
+                continue;
+            }
 
             // 处理fee_audit表的交易
             match self.process_pending_transactions().await {
@@ -71,6 +84,7 @@ impl TransactionMonitor {
     /// 处理待确认的swap交易（企业级实现）
     async fn process_pending_swap_transactions(&self) -> Result<usize> {
         let swap_repo = SwapTransactionRepository::new(self.pool.clone());
+        let (shard_chains, shard_include) = self.shard.sql_filter();
 
         // 查询需要更新确认数的swap交易（有tx_hash但状态为executing或pending）
         let pending_swaps = sqlx::query_as::<_, (String, String, String, Option<String>)>(
@@ -81,11 +95,14 @@ impl TransactionMonitor {
               AND tx_hash != ''
               AND status IN ('executing', 'pending')
               AND created_at > CURRENT_TIMESTAMP - INTERVAL '1 hour'
+              AND ($2::TEXT[] IS NULL OR (UPPER(network) = ANY($2)) = $3)
             ORDER BY created_at ASC
             LIMIT $1
             "#,
         )
         .bind(BATCH_SIZE)
+        .bind(shard_chains)
+        .bind(shard_include)
         .fetch_all(&self.pool)
         .await
         .context("Failed to query pending swap transactions")?;
@@ -198,6 +215,7 @@ impl TransactionMonitor {
     /// 处理待确认的交易（单次批处理）
     async fn process_pending_transactions(&self) -> Result<usize> {
         // 查询需要回填的交易（tx_hash 不为空但 gas_used 为空）
+        let (shard_chains, shard_include) = self.shard.sql_filter();
         let pending_txs = sqlx::query_as::<
             _,
             (
@@ -214,11 +232,14 @@ impl TransactionMonitor {
                AND gas_used IS NULL
                AND (retry_count IS NULL OR retry_count < $1)
                AND created_at > CURRENT_TIMESTAMP - INTERVAL '1 hour'
+               AND ($3::TEXT[] IS NULL OR (UPPER(chain) = ANY($3)) = $4)
              ORDER BY created_at ASC
              LIMIT $2",
         )
         .bind(MAX_RETRIES_PER_TX)
         .bind(BATCH_SIZE)
+        .bind(shard_chains)
+        .bind(shard_include)
         .fetch_all(&self.pool)
         .await
         .context("Failed to query pending transactions")?;
@@ -0,0 +1,1013 @@
+//! 交易预执行模拟
+//!
+//! 企业级实现：签名前在选定 RPC 上对 EVM 载荷执行 `eth_call` + `eth_estimateGas`
+//! - Gas Limit = estimateGas × (1 + 安全余量)
+//! - 回滚原因解码：`Error(string)` / `Panic(uint256)` / 已知 ABI 注册表中的自定义错误
+//! - 余额变化：优先 `debug_traceCall`（callTracer + 日志），节点不支持时按调用数据推断
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use anyhow::{Context, Result};
+use ethers::{
+    abi::{self, ethabi::AbiError, HumanReadableParser, ParamType, Token},
+    types::{Address, Bytes, U256},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    infrastructure::rpc_selector::RpcSelector,
+    infrastructure::telemetry::TracePropagation,
+    service::{deposit_indexer::TRANSFER_TOPIC, multi_node_verifier::EndpointProvider},
+    utils::chain_normalizer,
+};
+
+/// `Error(string)` 选择器
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// `Panic(uint256)` 选择器
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+/// ERC-20 `transfer(address,uint256)`
+const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// ERC-20 `transferFrom(address,address,uint256)`
+const ERC20_TRANSFER_FROM_SELECTOR: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// 默认 Gas 安全余量（基点，2000 = 20%）
+const DEFAULT_GAS_MARGIN_BPS: u64 = 2_000;
+
+/// 内置的常见自定义错误（OpenZeppelin v5 / Permit2 / Uniswap / 1inch）
+const DEFAULT_KNOWN_ERRORS: &[&str] = &[
+    "error ERC20InsufficientBalance(address sender, uint256 balance, uint256 needed)",
+    "error ERC20InsufficientAllowance(address spender, uint256 allowance, uint256 needed)",
+    "error ERC20InvalidSender(address sender)",
+    "error ERC20InvalidReceiver(address receiver)",
+    "error ERC20InvalidApprover(address approver)",
+    "error ERC20InvalidSpender(address spender)",
+    "error OwnableUnauthorizedAccount(address account)",
+    "error EnforcedPause()",
+    "error SafeERC20FailedOperation(address token)",
+    "error AddressInsufficientBalance(address account)",
+    "error FailedInnerCall()",
+    "error AllowanceExpired(uint256 deadline)",
+    "error InsufficientAllowance(uint256 amount)",
+    "error SignatureExpired(uint256 signatureDeadline)",
+    "error InvalidNonce()",
+    "error V3TooLittleReceived()",
+    "error V3TooMuchRequested()",
+    "error V2TooLittleReceived()",
+    "error V2TooMuchRequested()",
+    "error TransactionDeadlinePassed()",
+    "error ReturnAmountIsNotEnough()",
+    "error ReturnAmountIsNotEnough(uint256 result, uint256 minReturn)",
+    "error SwapWithZeroAmount()",
+    "error BadPool()",
+];
+
+/// 待模拟的 EVM 调用
+#[derive(Debug, Clone)]
+pub struct SimulationCall {
+    pub from: Address,
+    pub to: Address,
+    /// 原生币数量（Wei）
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// 解码后的回滚原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RevertReason {
+    /// `require(cond, "message")` / `revert("message")`
+    Error { message: String },
+    /// 编译器插入的 `Panic(uint256)`（溢出、除零、越界等）
+    Panic { code: u64, description: String },
+    /// 注册表中的自定义错误
+    Custom {
+        name: String,
+        selector: String,
+        args: Vec<String>,
+    },
+    /// 节点在执行前拒绝（余额不足以支付 value + gas 等）
+    Rejected { message: String },
+    /// 无法识别的回滚数据（`0x` 表示无原因的 `revert()`）
+    Unknown { data: String },
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevertReason::Error { message } => write!(f, "execution reverted: {}", message),
+            RevertReason::Panic { code, description } => {
+                write!(f, "panic 0x{:02x}: {}", code, description)
+            }
+            RevertReason::Custom { name, args, .. } => write!(f, "{}({})", name, args.join(", ")),
+            RevertReason::Rejected { message } => write!(f, "{}", message),
+            RevertReason::Unknown { data } => write!(f, "execution reverted ({})", data),
+        }
+    }
+}
+
+/// 余额变化的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceChangeSource {
+    /// `debug_traceCall` 的调用帧与 Transfer 日志
+    Trace,
+    /// 由 value 与 ERC-20 transfer/transferFrom 调用数据推断
+    Calldata,
+}
+
+/// 单个地址在某资产上的模拟余额变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BalanceChange {
+    /// 代币合约地址，原生币为 None
+    pub token: Option<String>,
+    pub address: String,
+    /// 带符号的最小单位变化量（十进制，如 "-1000"）
+    pub delta: String,
+}
+
+/// 模拟结果
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SimulationResult {
+    pub success: bool,
+    /// eth_estimateGas 结果
+    pub gas_used: Option<u64>,
+    /// 含安全余量的 Gas Limit
+    pub gas_limit: Option<u64>,
+    pub revert_reason: Option<RevertReason>,
+    pub balance_changes: Vec<BalanceChange>,
+    pub balance_changes_source: BalanceChangeSource,
+}
+
+impl SimulationResult {
+    fn reverted(reason: RevertReason) -> Self {
+        Self {
+            success: false,
+            gas_used: None,
+            gas_limit: None,
+            revert_reason: Some(reason),
+            balance_changes: Vec::new(),
+            balance_changes_source: BalanceChangeSource::Calldata,
+        }
+    }
+}
+
+/// 已知自定义错误注册表（按 4 字节选择器索引）
+#[derive(Debug, Clone, Default)]
+pub struct KnownErrorRegistry {
+    errors: HashMap<[u8; 4], AbiError>,
+}
+
+impl KnownErrorRegistry {
+    /// 内置常见错误
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::default();
+        for signature in DEFAULT_KNOWN_ERRORS {
+            registry
+                .register(signature)
+                .expect("built-in error signature must parse");
+        }
+        registry
+    }
+
+    /// 内置错误 + `TX_SIMULATION_KNOWN_ERRORS`（分号分隔的人类可读签名）
+    pub fn from_env() -> Self {
+        let mut registry = Self::with_defaults();
+        if let Ok(extra) = std::env::var("TX_SIMULATION_KNOWN_ERRORS") {
+            for signature in extra.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                if let Err(e) = registry.register(signature) {
+                    tracing::warn!("Ignoring invalid TX_SIMULATION_KNOWN_ERRORS entry: {}", e);
+                }
+            }
+        }
+        registry
+    }
+
+    /// 注册人类可读错误签名，如 `error Unauthorized(address caller)`（`error` 前缀可省略）
+    pub fn register(&mut self, signature: &str) -> Result<()> {
+        let signature = signature.trim();
+        let normalized = if signature.starts_with("error ") {
+            signature.to_string()
+        } else {
+            format!("error {}", signature)
+        };
+        let error = HumanReadableParser::parse_error(&normalized)
+            .map_err(|e| anyhow::anyhow!("Invalid error signature '{}': {}", signature, e))?;
+        let hash = error.signature();
+        self.errors
+            .insert([hash[0], hash[1], hash[2], hash[3]], error);
+        Ok(())
+    }
+
+    /// 解码回滚数据
+    pub fn decode(&self, data: &[u8]) -> RevertReason {
+        let unknown = || RevertReason::Unknown {
+            data: format!("0x{}", hex::encode(data)),
+        };
+        if data.len() < 4 {
+            return unknown();
+        }
+        let (selector, payload) = data.split_at(4);
+
+        if selector == ERROR_STRING_SELECTOR {
+            return match abi::decode(&[ParamType::String], payload) {
+                Ok(tokens) => match tokens.into_iter().next() {
+                    Some(Token::String(message)) => RevertReason::Error { message },
+                    _ => unknown(),
+                },
+                Err(_) => unknown(),
+            };
+        }
+        if selector == PANIC_SELECTOR {
+            return match abi::decode(&[ParamType::Uint(256)], payload) {
+                Ok(tokens) => match tokens.into_iter().next() {
+                    Some(Token::Uint(code)) if code <= U256::from(u64::MAX) => {
+                        let code = code.as_u64();
+                        RevertReason::Panic {
+                            code,
+                            description: panic_description(code).to_string(),
+                        }
+                    }
+                    _ => unknown(),
+                },
+                Err(_) => unknown(),
+            };
+        }
+
+        let key = [selector[0], selector[1], selector[2], selector[3]];
+        match self.errors.get(&key) {
+            Some(error) => match error.decode(payload) {
+                Ok(tokens) => RevertReason::Custom {
+                    name: error.name.clone(),
+                    selector: format!("0x{}", hex::encode(selector)),
+                    args: tokens.iter().map(format_token).collect(),
+                },
+                Err(_) => unknown(),
+            },
+            None => unknown(),
+        }
+    }
+}
+
+/// 交易模拟器
+pub struct TxSimulator {
+    endpoint_provider: Arc<dyn EndpointProvider>,
+    http_client: reqwest::Client,
+    registry: KnownErrorRegistry,
+    gas_margin_bps: u64,
+    /// 模拟基础设施失败时是否拒绝（`TX_SIMULATION_REQUIRED`）
+    required: bool,
+}
+
+impl TxSimulator {
+    pub fn new(rpc_selector: Arc<RpcSelector>) -> Self {
+        Self::with_endpoint_provider(rpc_selector)
+    }
+
+    pub fn with_endpoint_provider(endpoint_provider: Arc<dyn EndpointProvider>) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        let gas_margin_bps = std::env::var("TX_SIMULATION_GAS_MARGIN_BPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_GAS_MARGIN_BPS);
+        let required = std::env::var("TX_SIMULATION_REQUIRED")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        Self {
+            endpoint_provider,
+            http_client,
+            registry: KnownErrorRegistry::from_env(),
+            gas_margin_bps,
+            required,
+        }
+    }
+
+    /// 替换已知错误注册表
+    pub fn with_registry(mut self, registry: KnownErrorRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// 设置 Gas 安全余量（基点）
+    pub fn with_gas_margin_bps(mut self, gas_margin_bps: u64) -> Self {
+        self.gas_margin_bps = gas_margin_bps;
+        self
+    }
+
+    /// 模拟执行
+    ///
+    /// # 流程
+    /// 1. `eth_call`：回滚时解码原因并直接返回
+    /// 2. `eth_estimateGas`：加安全余量得到 Gas Limit
+    /// 3. `debug_traceCall` 汇总余额变化，不支持时按调用数据推断
+    ///
+    /// 回滚以 `success = false` 返回；只有 RPC 不可用等基础设施错误返回 `Err`
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
+    pub async fn simulate(&self, chain: &str, call: &SimulationCall) -> Result<SimulationResult> {
+        let canonical = chain_normalizer::normalize_chain_identifier(chain)?;
+        if !chain_normalizer::is_evm_chain(&canonical) {
+            anyhow::bail!(
+                "Transaction simulation is only supported on EVM chains: {}",
+                chain
+            );
+        }
+
+        let tx = call_object(call);
+
+        if let RpcOutcome::Reverted(reason) = self
+            .rpc(&canonical, "eth_call", serde_json::json!([tx, "latest"]))
+            .await?
+        {
+            return Ok(SimulationResult::reverted(reason));
+        }
+
+        let gas_used = match self
+            .rpc(&canonical, "eth_estimateGas", serde_json::json!([tx]))
+            .await?
+        {
+            RpcOutcome::Reverted(reason) => return Ok(SimulationResult::reverted(reason)),
+            RpcOutcome::Ok(value) => parse_quantity(&value)
+                .filter(|gas| *gas <= U256::from(u64::MAX))
+                .context("Invalid eth_estimateGas result")?
+                .as_u64(),
+        };
+        let gas_limit = apply_gas_margin(gas_used, self.gas_margin_bps);
+
+        let trace = self
+            .rpc(
+                &canonical,
+                "debug_traceCall",
+                serde_json::json!([
+                    tx,
+                    "latest",
+                    { "tracer": "callTracer", "tracerConfig": { "withLog": true } }
+                ]),
+            )
+            .await;
+        let (balance_changes, balance_changes_source) = match trace {
+            Ok(RpcOutcome::Ok(frame)) if frame.is_object() => {
+                (trace_balance_changes(&frame), BalanceChangeSource::Trace)
+            }
+            other => {
+                if let Err(e) = other {
+                    tracing::debug!("debug_traceCall unavailable on {}: {}", canonical, e);
+                }
+                (
+                    calldata_balance_changes(call),
+                    BalanceChangeSource::Calldata,
+                )
+            }
+        };
+
+        Ok(SimulationResult {
+            success: true,
+            gas_used: Some(gas_used),
+            gas_limit: Some(gas_limit),
+            revert_reason: None,
+            balance_changes,
+            balance_changes_source,
+        })
+    }
+
+    /// 签名前检查：基础设施失败时按 `TX_SIMULATION_REQUIRED` 决定放行（None）或报错
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
+    pub async fn preflight(
+        &self,
+        chain: &str,
+        call: &SimulationCall,
+    ) -> Result<Option<SimulationResult>> {
+        match self.simulate(chain, call).await {
+            Ok(result) => Ok(Some(result)),
+            Err(e) if !self.required => {
+                tracing::warn!("Transaction simulation skipped on {}: {}", chain, e);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// JSON-RPC 调用，执行类错误解码为回滚原因
+    async fn rpc(
+        &self,
+        chain: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<RpcOutcome> {
+        let url = self
+            .endpoint_provider
+            .distinct_endpoints(chain, 1)
+            .await
+            .into_iter()
+            .next()
+            .context("No healthy RPC endpoint available")?;
+
+        let request_body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params
+        });
+
+        let json: serde_json::Value = self
+            .http_client
+            .post(&url)
+            .json(&request_body)
+            .with_trace_context()
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        match json.get("error") {
+            Some(error) => match self.execution_failure(error) {
+                Some(reason) => Ok(RpcOutcome::Reverted(reason)),
+                None => anyhow::bail!(
+                    "RPC error ({}): {}",
+                    method,
+                    error
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Unknown RPC error")
+                ),
+            },
+            None => Ok(RpcOutcome::Ok(
+                json.get("result")
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null),
+            )),
+        }
+    }
+
+    /// 区分执行失败（回滚 / 节点拒绝）与其他 RPC 错误
+    fn execution_failure(&self, error: &serde_json::Value) -> Option<RevertReason> {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        let lower = message.to_lowercase();
+
+        // 回滚数据：`data` 为十六进制串，部分节点嵌套在 `data.data`
+        let data = error
+            .get("data")
+            .and_then(|d| {
+                d.as_str()
+                    .or_else(|| d.get("data").and_then(|x| x.as_str()))
+            })
+            .and_then(|d| hex::decode(d.trim_start_matches("0x")).ok());
+
+        let is_revert =
+            error.get("code").and_then(|c| c.as_i64()) == Some(3) || lower.contains("revert");
+        if is_revert {
+            return Some(match data {
+                Some(bytes) if !bytes.is_empty() => self.registry.decode(&bytes),
+                _ => match message.split_once("execution reverted: ") {
+                    Some((_, reason)) if !reason.is_empty() => RevertReason::Error {
+                        message: reason.to_string(),
+                    },
+                    _ => RevertReason::Unknown {
+                        data: "0x".to_string(),
+                    },
+                },
+            });
+        }
+
+        let rejected = [
+            "insufficient funds",
+            "gas required exceeds",
+            "intrinsic gas too low",
+        ];
+        rejected
+            .iter()
+            .any(|pattern| lower.contains(pattern))
+            .then(|| RevertReason::Rejected {
+                message: message.to_string(),
+            })
+    }
+}
+
+enum RpcOutcome {
+    Ok(serde_json::Value),
+    Reverted(RevertReason),
+}
+
+// ============ 辅助函数 ============
+
+fn call_object(call: &SimulationCall) -> serde_json::Value {
+    serde_json::json!({
+        "from": format!("{:?}", call.from),
+        "to": format!("{:?}", call.to),
+        "value": format!("0x{:x}", call.value),
+        "data": format!("0x{}", hex::encode(&call.data)),
+    })
+}
+
+fn parse_quantity(value: &serde_json::Value) -> Option<U256> {
+    let hex_str = value.as_str()?;
+    U256::from_str_radix(hex_str.trim_start_matches("0x"), 16).ok()
+}
+
+fn apply_gas_margin(gas_used: u64, margin_bps: u64) -> u64 {
+    let scaled = u128::from(gas_used) * u128::from(10_000 + margin_bps) / 10_000;
+    u64::try_from(scaled).unwrap_or(u64::MAX)
+}
+
+/// Solidity Panic 错误码说明
+fn panic_description(code: u64) -> &'static str {
+    match code {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "corrupted storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to uninitialized function",
+        _ => "unknown panic code",
+    }
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => ethers::types::I256::from_raw(*value).to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Array(items) | Token::FixedArray(items) => {
+            format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(format_token)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        Token::Tuple(items) => {
+            format!(
+                "({})",
+                items
+                    .iter()
+                    .map(format_token)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
+/// 按 (资产, 地址) 累计收支后得到净变化
+#[derive(Default)]
+struct DeltaBook {
+    entries: Vec<((Option<Address>, Address), U256, U256)>,
+}
+
+impl DeltaBook {
+    fn transfer(&mut self, token: Option<Address>, from: Address, to: Address, amount: U256) {
+        if amount.is_zero() || from == to {
+            return;
+        }
+        self.entry(token, from).2 += amount;
+        self.entry(token, to).1 += amount;
+    }
+
+    fn entry(
+        &mut self,
+        token: Option<Address>,
+        address: Address,
+    ) -> &mut ((Option<Address>, Address), U256, U256) {
+        let key = (token, address);
+        let index = match self.entries.iter().position(|(k, _, _)| *k == key) {
+            Some(index) => index,
+            None => {
+                self.entries.push((key, U256::zero(), U256::zero()));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[index]
+    }
+
+    fn into_changes(self) -> Vec<BalanceChange> {
+        self.entries
+            .into_iter()
+            .filter(|(_, incoming, outgoing)| incoming != outgoing)
+            .map(|((token, address), incoming, outgoing)| BalanceChange {
+                token: token.map(|t| format!("{:?}", t)),
+                address: format!("{:?}", address),
+                delta: if incoming > outgoing {
+                    (incoming - outgoing).to_string()
+                } else {
+                    format!("-{}", outgoing - incoming)
+                },
+            })
+            .collect()
+    }
+}
+
+/// 从 callTracer 调用树汇总余额变化（跳过失败的子调用）
+fn trace_balance_changes(frame: &serde_json::Value) -> Vec<BalanceChange> {
+    let mut book = DeltaBook::default();
+    collect_frame(frame, &mut book);
+    book.into_changes()
+}
+
+fn collect_frame(frame: &serde_json::Value, book: &mut DeltaBook) {
+    if frame.get("error").is_some() {
+        return;
+    }
+    let address = |key: &str| {
+        frame
+            .get(key)
+            .and_then(|v| v.as_str()?.parse::<Address>().ok())
+    };
+
+    let moves_value = matches!(
+        frame.get("type").and_then(|t| t.as_str()),
+        Some("CALL" | "CREATE" | "CREATE2")
+    );
+    if moves_value {
+        if let (Some(from), Some(to), Some(value)) = (
+            address("from"),
+            address("to"),
+            frame.get("value").and_then(parse_quantity),
+        ) {
+            book.transfer(None, from, to, value);
+        }
+    }
+
+    for log in frame
+        .get("logs")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let topics: Vec<&str> = log
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| t.iter().filter_map(|x| x.as_str()).collect())
+            .unwrap_or_default();
+        if topics.len() != 3 || !topics[0].eq_ignore_ascii_case(TRANSFER_TOPIC) {
+            continue;
+        }
+        let token = log
+            .get("address")
+            .and_then(|a| a.as_str()?.parse::<Address>().ok());
+        let topic_address = |topic: &str| {
+            hex::decode(topic.trim_start_matches("0x"))
+                .ok()
+                .filter(|b| b.len() == 32)
+                .map(|b| Address::from_slice(&b[12..]))
+        };
+        let amount = log
+            .get("data")
+            .and_then(|d| d.as_str())
+            .and_then(|d| hex::decode(d.trim_start_matches("0x")).ok())
+            .filter(|b| b.len() == 32)
+            .map(|b| U256::from_big_endian(&b));
+        if let (Some(token), Some(from), Some(to), Some(amount)) = (
+            token,
+            topic_address(topics[1]),
+            topic_address(topics[2]),
+            amount,
+        ) {
+            book.transfer(Some(token), from, to, amount);
+        }
+    }
+
+    for child in frame
+        .get("calls")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+    {
+        collect_frame(child, book);
+    }
+}
+
+/// 无追踪能力时：value 转账 + ERC-20 transfer / transferFrom
+fn calldata_balance_changes(call: &SimulationCall) -> Vec<BalanceChange> {
+    let mut book = DeltaBook::default();
+    book.transfer(None, call.from, call.to, call.value);
+
+    if call.data.len() >= 4 {
+        let (selector, payload) = call.data.split_at(4);
+        if selector == ERC20_TRANSFER_SELECTOR {
+            if let Ok(tokens) = abi::decode(&[ParamType::Address, ParamType::Uint(256)], payload) {
+                if let [Token::Address(to), Token::Uint(amount)] = tokens.as_slice() {
+                    book.transfer(Some(call.to), call.from, *to, *amount);
+                }
+            }
+        } else if selector == ERC20_TRANSFER_FROM_SELECTOR {
+            if let Ok(tokens) = abi::decode(
+                &[ParamType::Address, ParamType::Address, ParamType::Uint(256)],
+                payload,
+            ) {
+                if let [Token::Address(from), Token::Address(to), Token::Uint(amount)] =
+                    tokens.as_slice()
+                {
+                    book.transfer(Some(call.to), *from, *to, *amount);
+                }
+            }
+        }
+    }
+    book.into_changes()
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use axum::{routing::post, Json, Router};
+
+    use super::*;
+
+    const TOKEN: &str = "0x00000000000000000000000000000000000000aa";
+    const REVERTING: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn addr(s: &str) -> Address {
+        s.parse().unwrap()
+    }
+
+    fn sender() -> Address {
+        addr("0x1111111111111111111111111111111111111111")
+    }
+
+    fn recipient() -> Address {
+        addr("0x2222222222222222222222222222222222222222")
+    }
+
+    fn erc20_transfer_call(amount: u64) -> SimulationCall {
+        let mut data = ERC20_TRANSFER_SELECTOR.to_vec();
+        data.extend(abi::encode(&[
+            Token::Address(recipient()),
+            Token::Uint(U256::from(amount)),
+        ]));
+        SimulationCall {
+            from: sender(),
+            to: addr(TOKEN),
+            value: U256::zero(),
+            data: Bytes::from(data),
+        }
+    }
+
+    /// Test 1: Error(string) 与 Panic(uint256)
+    #[test]
+    fn test_decode_builtin_reverts() {
+        let registry = KnownErrorRegistry::with_defaults();
+
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::String(
+            "ERC20: transfer amount exceeds balance".into(),
+        )]));
+        assert_eq!(
+            registry.decode(&data),
+            RevertReason::Error {
+                message: "ERC20: transfer amount exceeds balance".into()
+            }
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::Uint(U256::from(0x11))]));
+        let reason = registry.decode(&data);
+        assert_eq!(
+            reason,
+            RevertReason::Panic {
+                code: 0x11,
+                description: "arithmetic overflow or underflow".into()
+            }
+        );
+        assert_eq!(
+            reason.to_string(),
+            "panic 0x11: arithmetic overflow or underflow"
+        );
+    }
+
+    /// Test 2: 注册表自定义错误、未知选择器
+    #[test]
+    fn test_decode_custom_errors() {
+        let mut registry = KnownErrorRegistry::with_defaults();
+
+        let error = HumanReadableParser::parse_error(
+            "error ERC20InsufficientBalance(address sender, uint256 balance, uint256 needed)",
+        )
+        .unwrap();
+        let mut data = error.signature()[..4].to_vec();
+        data.extend(abi::encode(&[
+            Token::Address(sender()),
+            Token::Uint(U256::from(5)),
+            Token::Uint(U256::from(10)),
+        ]));
+        assert_eq!(
+            registry.decode(&data).to_string(),
+            format!("ERC20InsufficientBalance({:?}, 5, 10)", sender())
+        );
+
+        let unknown = [0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            registry.decode(&unknown),
+            RevertReason::Unknown {
+                data: "0xdeadbeef".into()
+            }
+        );
+
+        registry.register("Unauthorized(address caller)").unwrap();
+        let hash = ethers::utils::id("Unauthorized(address)");
+        let mut data = hash.to_vec();
+        data.extend(abi::encode(&[Token::Address(sender())]));
+        assert!(matches!(
+            registry.decode(&data),
+            RevertReason::Custom { ref name, .. } if name == "Unauthorized"
+        ));
+        assert!(registry.register("not a signature").is_err());
+    }
+
+    /// Test 3: 调用数据推断余额变化
+    #[test]
+    fn test_calldata_balance_changes() {
+        let changes = calldata_balance_changes(&erc20_transfer_call(1_000));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].token.as_deref(), Some(TOKEN));
+        assert_eq!(changes[0].address, format!("{:?}", sender()));
+        assert_eq!(changes[0].delta, "-1000");
+        assert_eq!(changes[1].address, format!("{:?}", recipient()));
+        assert_eq!(changes[1].delta, "1000");
+
+        assert_eq!(apply_gas_margin(50_000, 2_000), 60_000);
+    }
+
+    struct StaticEndpoint(String);
+
+    #[async_trait]
+    impl EndpointProvider for StaticEndpoint {
+        async fn distinct_endpoints(&self, _chain: &str, _count: usize) -> Vec<String> {
+            vec![self.0.clone()]
+        }
+    }
+
+    /// 本地 JSON-RPC 桩：REVERTING 地址回滚，`trace = false` 时不支持 debug_traceCall
+    async fn mock_rpc(trace: bool) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(req): Json<serde_json::Value>| async move {
+                let params = &req["params"];
+                let to = params[0]["to"].as_str().unwrap_or_default().to_lowercase();
+                let reply = |result: serde_json::Value| {
+                    Json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+                };
+                let fail = |code: i64, message: &str, data: Option<String>| {
+                    Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "error": { "code": code, "message": message, "data": data }
+                    }))
+                };
+
+                match req["method"].as_str().unwrap_or_default() {
+                    "eth_call" if to == REVERTING => {
+                        let error = HumanReadableParser::parse_error(
+                            "error ERC20InsufficientAllowance(address spender, uint256 allowance, uint256 needed)",
+                        )
+                        .unwrap();
+                        let mut data = error.signature()[..4].to_vec();
+                        data.extend(abi::encode(&[
+                            Token::Address(addr(REVERTING)),
+                            Token::Uint(U256::zero()),
+                            Token::Uint(U256::from(7)),
+                        ]));
+                        fail(
+                            3,
+                            "execution reverted",
+                            Some(format!("0x{}", hex::encode(data))),
+                        )
+                    }
+                    "eth_call" => reply(serde_json::json!("0x")),
+                    "eth_estimateGas" => reply(serde_json::json!("0xc350")),
+                    "debug_traceCall" if !trace => fail(-32601, "method not found", None),
+                    "debug_traceCall" => reply(serde_json::json!({
+                        "type": "CALL",
+                        "from": format!("{:?}", sender()),
+                        "to": TOKEN,
+                        "value": "0x0",
+                        "logs": [{
+                            "address": TOKEN,
+                            "topics": [
+                                TRANSFER_TOPIC,
+                                format!("0x{:0>64}", hex::encode(sender())),
+                                format!("0x{:0>64}", hex::encode(recipient())),
+                            ],
+                            "data": format!("0x{:064x}", 1_000)
+                        }],
+                        "calls": [{
+                            "type": "CALL",
+                            "from": TOKEN,
+                            "to": REVERTING,
+                            "value": "0x5",
+                            "error": "execution reverted"
+                        }]
+                    })),
+                    _ => reply(serde_json::Value::Null),
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Test 4: 成功模拟：Gas 余量 + 追踪日志余额变化（失败子调用被忽略）
+    #[tokio::test]
+    async fn test_simulate_success_with_trace() {
+        let simulator =
+            TxSimulator::with_endpoint_provider(Arc::new(StaticEndpoint(mock_rpc(true).await)))
+                .with_gas_margin_bps(2_000);
+
+        let result = simulator
+            .simulate("eth", &erc20_transfer_call(1_000))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.gas_used, Some(50_000));
+        assert_eq!(result.gas_limit, Some(60_000));
+        assert_eq!(result.balance_changes_source, BalanceChangeSource::Trace);
+        assert_eq!(result.balance_changes.len(), 2);
+        assert!(result
+            .balance_changes
+            .iter()
+            .all(|c| c.token.as_deref() == Some(TOKEN)));
+    }
+
+    /// Test 5: 回滚解码为已知自定义错误；不支持追踪时降级为调用数据推断
+    #[tokio::test]
+    async fn test_simulate_revert_and_calldata_fallback() {
+        let simulator =
+            TxSimulator::with_endpoint_provider(Arc::new(StaticEndpoint(mock_rpc(false).await)));
+
+        let mut call = erc20_transfer_call(1_000);
+        call.to = addr(REVERTING);
+        let result = simulator.simulate("bsc", &call).await.unwrap();
+        assert!(!result.success);
+        assert!(matches!(
+            result.revert_reason,
+            Some(RevertReason::Custom { ref name, ref args, .. })
+                if name == "ERC20InsufficientAllowance" && args[2] == "7"
+        ));
+
+        let result = simulator
+            .simulate("polygon", &erc20_transfer_call(1_000))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.balance_changes_source, BalanceChangeSource::Calldata);
+        assert_eq!(result.balance_changes[0].delta, "-1000");
+
+        assert!(simulator
+            .simulate("solana", &erc20_transfer_call(1))
+            .await
+            .is_err());
+    }
+
+    /// Test 6: 无回滚数据时从 message 提取原因，余额不足视为节点拒绝
+    #[test]
+    fn test_execution_failure_classification() {
+        let simulator = TxSimulator::with_endpoint_provider(Arc::new(StaticEndpoint(
+            "http://127.0.0.1:1".into(),
+        )));
+
+        let reason = simulator.execution_failure(&serde_json::json!({
+            "code": -32000,
+            "message": "execution reverted: Ownable: caller is not the owner"
+        }));
+        assert_eq!(
+            reason,
+            Some(RevertReason::Error {
+                message: "Ownable: caller is not the owner".into()
+            })
+        );
+
+        let reason = simulator.execution_failure(&serde_json::json!({
+            "code": -32000,
+            "message": "insufficient funds for gas * price + value"
+        }));
+        assert!(matches!(reason, Some(RevertReason::Rejected { .. })));
+
+        assert_eq!(
+            simulator.execution_failure(&serde_json::json!({
+                "code": -32601,
+                "message": "the method eth_call does not exist"
+            })),
+            None
+        );
+    }
+}
@@ -7,7 +7,8 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    infrastructure::rpc_selector::RpcSelector, service::blockchain_client::BlockchainClient,
+    infrastructure::rpc_selector::RpcSelector, infrastructure::telemetry::TracePropagation,
+    service::blockchain_client::BlockchainClient,
 };
 
 /// 余额信息
@@ -65,6 +66,7 @@ impl UnifiedBalanceService {
     /// - Solana: SOL
     /// - Bitcoin: BTC
     /// - TON: TON
+    #[tracing::instrument(skip_all, fields(chain = %chain))]
     pub async fn get_balance(&self, chain: &str, address: &str) -> Result<BalanceInfo> {
         let chain_normalized = crate::utils::chain_normalizer::normalize_chain_identifier(chain)?;
 
@@ -137,6 +139,7 @@ impl UnifiedBalanceService {
         let response = client
             .post(&endpoint.url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send RPC request")?;
@@ -195,6 +198,7 @@ impl UnifiedBalanceService {
         let response = client
             .post(&endpoint.url)
             .json(&payload)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to send Solana RPC request")?;
@@ -237,6 +241,7 @@ impl UnifiedBalanceService {
         let client = reqwest::Client::new();
         let response = client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to query Bitcoin balance")?;
@@ -281,6 +286,7 @@ impl UnifiedBalanceService {
         let client = reqwest::Client::new();
         let response = client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .context("Failed to query TON balance")?;
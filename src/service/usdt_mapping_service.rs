@@ -8,7 +8,10 @@ use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::service::cross_chain_bridge_service::{CrossChainBridgeService, CrossChainSwapRequest};
+use crate::service::{
+    bridge_aggregator,
+    cross_chain_bridge_service::{CrossChainBridgeService, CrossChainSwapRequest},
+};
 
 /// USDT映射服务
 pub struct UsdtMappingService {
@@ -56,14 +59,12 @@ impl UsdtMappingService {
             user_id,
             source_chain: "ethereum".to_string(), // USDT默认在Ethereum主网
             source_token: "USDT".to_string(),
-            source_amount: order
-                .crypto_amount
-                .parse::<f64>()
-                .map_err(|e| anyhow::anyhow!("Invalid crypto amount: {}", e))?,
+            source_amount: bridge_aggregator::decimal_amount(&order.crypto_amount)?,
             source_wallet_id: source_wallet.id,
             target_chain: target_chain.clone(),
             target_token: "USDT".to_string(), // 目标链也使用USDT
             target_wallet_id: Some(wallet.id),
+            bridge_protocol: None, // 使用综合排名第一的桥
         };
 
         // 4. 执行跨链桥接
@@ -5,6 +5,8 @@ use anyhow::Result;
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+use crate::infrastructure::{event_bus::DomainEvent, event_outbox::enqueue_event};
+
 /// 批量钱包注册请求
 pub struct BatchWalletRegisterRequest {
     pub user_id: Uuid,
@@ -166,6 +168,18 @@ impl WalletBatchRegisterService {
         .execute(&mut **tx)
         .await?;
 
+        // Outbox：事件与钱包写入同一事务提交
+        enqueue_event(
+            &mut **tx,
+            &DomainEvent::WalletCreated {
+                wallet_id,
+                user_id,
+                chain_type: wallet.chain.clone(),
+                address: wallet.address.clone(),
+            },
+        )
+        .await?;
+
         Ok(wallet_id)
     }
 
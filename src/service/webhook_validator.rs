@@ -1,6 +1,7 @@
 //! Webhook签名验证服务
 //!
 //! 企业级实现：验证第三方支付服务商的Webhook签名
+//! 直连服务商（MoonPay / Ramp）由各自的 `FiatProvider::verify_webhook` 按服务商协议验签，不经此处
 //! 解决问题：E.2 - Webhook验证机制未实现
 
 use anyhow::{Context, Result};
@@ -21,12 +22,6 @@ impl WebhookValidator {
         let mut secrets = std::collections::HashMap::new();
 
         // 从环境变量加载各服务商的密钥
-        if let Ok(ramp_secret) = std::env::var("RAMP_WEBHOOK_SECRET") {
-            secrets.insert("ramp".to_string(), ramp_secret);
-        }
-        if let Ok(moonpay_secret) = std::env::var("MOONPAY_WEBHOOK_SECRET") {
-            secrets.insert("moonpay".to_string(), moonpay_secret);
-        }
         if let Ok(transak_secret) = std::env::var("TRANSAK_WEBHOOK_SECRET") {
             secrets.insert("transak".to_string(), transak_secret);
         }
@@ -37,7 +32,7 @@ impl WebhookValidator {
     /// 验证Webhook签名
     ///
     /// # 参数
-    /// - `provider`: 服务商名称（ramp/moonpay/transak）
+    /// - `provider`: 服务商名称（transak）
     /// - `body`: 请求体（原始字符串）
     /// - `signature`: 签名值（从HTTP头提取）
     ///
@@ -48,47 +43,11 @@ impl WebhookValidator {
         let provider_lower = provider.to_lowercase();
 
         match provider_lower.as_str() {
-            "ramp" => self.verify_ramp_signature(body, signature),
-            "moonpay" => self.verify_moonpay_signature(body, signature),
             "transak" => self.verify_transak_signature(body, signature),
             _ => anyhow::bail!("Unsupported webhook provider: {}", provider),
         }
     }
 
-    /// 验证Ramp签名
-    /// Ramp使用HMAC-SHA256
-    fn verify_ramp_signature(&self, body: &str, signature: &str) -> Result<()> {
-        let secret = self
-            .secrets
-            .get("ramp")
-            .context("Ramp webhook secret not configured")?;
-
-        let expected_signature = self.compute_hmac_sha256(secret, body)?;
-
-        if constant_time_compare(&expected_signature, signature) {
-            Ok(())
-        } else {
-            anyhow::bail!("Invalid Ramp webhook signature")
-        }
-    }
-
-    /// 验证MoonPay签名
-    /// MoonPay使用HMAC-SHA256，签名格式为 hex
-    fn verify_moonpay_signature(&self, body: &str, signature: &str) -> Result<()> {
-        let secret = self
-            .secrets
-            .get("moonpay")
-            .context("MoonPay webhook secret not configured")?;
-
-        let expected_signature = self.compute_hmac_sha256(secret, body)?;
-
-        if constant_time_compare(&expected_signature, signature) {
-            Ok(())
-        } else {
-            anyhow::bail!("Invalid MoonPay webhook signature")
-        }
-    }
-
     /// 验证Transak签名
     /// Transak使用HMAC-SHA256
     fn verify_transak_signature(&self, body: &str, signature: &str) -> Result<()> {
@@ -1,24 +1,51 @@
 //! 提现三级风控系统
-//! 企业级实现：金额限制 + 行为分析 + 人工审核
+//! 企业级实现：租户策略规则引擎（金额/频率/时间/地址/地理位置）+ 平台基线规则 + 人工审核
+
+use std::collections::HashMap;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::service::risk_rule_engine::{
+    compile_policy, default_policy_rules, ApprovalSpec, PolicyRef, Predicate, RiskContext, RuleSet,
+    RuleSpec, VelocityStats,
+};
+
 /// 风控等级
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
 pub enum RiskLevel {
     /// 低风险：自动通过
+    #[serde(alias = "low")]
     Low,
     /// 中风险：延迟处理 + 额外验证
+    #[serde(alias = "medium")]
     Medium,
     /// 高风险：人工审核
+    #[serde(alias = "high")]
     High,
     /// 拒绝：直接拒绝
+    #[serde(alias = "reject")]
     Reject,
 }
 
+impl RiskLevel {
+    /// 解析 withdrawal_requests.risk_level（`{:?}` 格式）
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "low" => Some(RiskLevel::Low),
+            "medium" => Some(RiskLevel::Medium),
+            "high" => Some(RiskLevel::High),
+            "reject" => Some(RiskLevel::Reject),
+            _ => None,
+        }
+    }
+}
+
 /// 风控决策结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskControlDecision {
@@ -32,6 +59,12 @@ pub struct RiskControlDecision {
     pub suggestion: String,
     /// 需要人工审核
     pub requires_manual_review: bool,
+    /// 参与评估的租户策略（为空表示使用平台默认策略）
+    #[serde(default)]
+    pub policies: Vec<PolicyRef>,
+    /// 需要人工审核时的多方审批要求
+    #[serde(default)]
+    pub approval: Option<ApprovalSpec>,
 }
 
 /// 提现请求
@@ -43,8 +76,39 @@ pub struct WithdrawalRequest {
     pub to_address: String,
     pub amount_usd: f64,
     pub wallet_id: Uuid,
+    /// 客户端 IP（审计用）
+    pub client_ip: Option<String>,
+    /// 客户端所在国家（ISO 3166-1 alpha-2，由边缘网关注入）
+    pub country: Option<String>,
+}
+
+/// 策略 dry-run 报告
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PolicyDryRunReport {
+    /// 评估的历史提现数量
+    pub evaluated: usize,
+    /// 模拟结果的风险等级分布
+    pub by_risk_level: HashMap<String, usize>,
+    /// 各规则命中次数
+    pub rule_hits: HashMap<String, usize>,
+    /// 与当时记录的风险等级不同的数量
+    pub changed: usize,
+    /// 结果变化的样本（最多 100 条）
+    pub changed_samples: Vec<PolicyDryRunItem>,
 }
 
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PolicyDryRunItem {
+    pub withdrawal_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub amount_usd: f64,
+    pub recorded_risk_level: Option<String>,
+    pub simulated_risk_level: RiskLevel,
+    pub triggered_rules: Vec<String>,
+}
+
+const DRY_RUN_SAMPLE_LIMIT: usize = 100;
+
 /// 提现风控服务
 pub struct WithdrawalRiskControl {
     pool: PgPool,
@@ -57,91 +121,30 @@ impl WithdrawalRiskControl {
 
     /// 评估提现风险（三级风控）
     ///
-    /// # 三级风控规则
-    ///
-    /// ## Level 1: 金额限制（自动通过）
-    /// - < $1,000: 立即通过
-    /// - 每日总额 < $5,000: 立即通过
-    ///
-    /// ## Level 2: 行为分析（延迟 + 验证）
-    /// - $1,000 - $10,000: 延迟10分钟 + 邮件验证
-    /// - 异常时间（凌晨2-6点）: 额外验证
-    /// - 频繁提现（24小时内>5次）: 延迟处理
-    /// - 新注册用户（<7天）: 额外验证
+    /// 规则来源：
+    /// - 平台基线（始终生效）：黑名单地址 → Reject，账户可疑活动 → High
+    /// - 租户已启用的策略（policies.status = 'active'），多个策略取最高风险等级
+    /// - 租户未启用策略时使用平台默认策略（`default_policy_rules`）：
+    ///   单笔 ≥$10k → High，$1k-$10k → Medium，24h 总额 >$5k、凌晨 2-6 点(UTC)、
+    ///   24h 内 >5 次、注册 <7 天 → Medium
     ///
-    /// ## Level 3: 人工审核（高风险）
-    /// - > $10,000: 必须人工审核
-    /// - 异常IP（国外IP）: 人工审核
-    /// - 账户异常行为: 人工审核
-    /// - 黑名单地址: 直接拒绝
+    /// 决策：Low 自动通过；Medium 延迟 + 额外验证；High 挂起等待多方审批；Reject 拒绝
     pub async fn evaluate(&self, request: &WithdrawalRequest) -> Result<RiskControlDecision> {
-        let mut triggered_rules = Vec::new();
-        let mut risk_level = RiskLevel::Low;
-
-        // ===== Level 1: 金额限制 =====
-
-        // 规则1.1: 单笔金额检查
-        if request.amount_usd >= 10_000.0 {
-            triggered_rules.push("AMOUNT_EXCEEDS_10K".to_string());
-            risk_level = RiskLevel::High;
-        } else if request.amount_usd >= 1_000.0 {
-            triggered_rules.push("AMOUNT_EXCEEDS_1K".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::Medium);
-        }
-
-        // 规则1.2: 每日总额检查
-        let daily_total = self.get_daily_withdrawal_total(request.user_id).await?;
-        if daily_total + request.amount_usd > 5_000.0 {
-            triggered_rules.push("DAILY_LIMIT_EXCEEDED".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::Medium);
-        }
-
-        // ===== Level 2: 行为分析 =====
-
-        // 规则2.1: 异常时间检查（凌晨2-6点）
-        use chrono::Timelike;
-        let hour = chrono::Utc::now().hour();
-        if (2..6).contains(&hour) {
-            triggered_rules.push("UNUSUAL_TIME".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::Medium);
-        }
-
-        // 规则2.2: 频繁提现检查（24小时内>5次）
-        let recent_count = self
-            .get_recent_withdrawal_count(request.user_id, 24)
+        let rule_set = self.load_rule_set(request.tenant_id).await?;
+        let ctx = self
+            .build_context(
+                &rule_set,
+                request.user_id,
+                &request.chain,
+                &request.to_address,
+                request.amount_usd,
+                request.country.clone(),
+                Utc::now(),
+            )
             .await?;
-        if recent_count >= 5 {
-            triggered_rules.push("FREQUENT_WITHDRAWALS".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::Medium);
-        }
-
-        // 规则2.3: 新用户检查（注册<7天）
-        let account_age_days = self.get_account_age_days(request.user_id).await?;
-        if account_age_days < 7 {
-            triggered_rules.push("NEW_USER_ACCOUNT".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::Medium);
-        }
-
-        // ===== Level 3: 人工审核规则 =====
 
-        // 规则3.1: 黑名单地址检查
-        if self.is_blacklisted_address(&request.to_address).await? {
-            triggered_rules.push("BLACKLISTED_ADDRESS".to_string());
-            risk_level = RiskLevel::Reject;
-        }
-
-        // 规则3.2: 账户异常行为
-        if self.has_suspicious_activity(request.user_id).await? {
-            triggered_rules.push("SUSPICIOUS_ACTIVITY".to_string());
-            risk_level = std::cmp::max(risk_level, RiskLevel::High);
-        }
-
-        // 规则3.3: 异常IP检查（简化实现）
-        // 实际应该从请求上下文获取IP并检查
-        // if self.is_unusual_ip(&request.ip).await? {
-        //     triggered_rules.push("UNUSUAL_IP".to_string());
-        //     risk_level = std::cmp::max(risk_level, RiskLevel::High);
-        // }
+        let outcome = rule_set.evaluate(&ctx);
+        let risk_level = outcome.risk_level;
 
         // ===== 生成决策 =====
 
@@ -167,52 +170,217 @@ impl WithdrawalRiskControl {
         Ok(RiskControlDecision {
             risk_level,
             allow,
-            triggered_rules,
+            triggered_rules: outcome.triggered_rules,
             suggestion,
             requires_manual_review,
+            approval: requires_manual_review.then(|| {
+                rule_set
+                    .approval
+                    .clone()
+                    .unwrap_or_else(ApprovalSpec::platform_default)
+            }),
+            policies: rule_set.policies,
         })
     }
 
-    /// 获取用户24小时内的提现总额
-    async fn get_daily_withdrawal_total(&self, user_id: Uuid) -> Result<f64> {
-        let total: Option<f64> = sqlx::query_scalar(
-            "SELECT COALESCE(SUM(amount_usd), 0)
+    /// 在历史提现上试运行策略（不影响线上决策）
+    ///
+    /// 每笔提现按其创建时间重建上下文（频率窗口、账户年龄）；黑名单取当前状态
+    pub async fn dry_run_policy(
+        &self,
+        tenant_id: Uuid,
+        rules: &serde_json::Value,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<PolicyDryRunReport> {
+        let doc = compile_policy(rules)?;
+        let rule_set = RuleSet::new().with_policy(None, doc);
+
+        let history = sqlx::query_as::<
+            _,
+            (
+                Uuid,
+                Uuid,
+                String,
+                String,
+                Option<f64>,
+                Option<String>,
+                Option<String>,
+                DateTime<Utc>,
+            ),
+        >(
+            "SELECT id, user_id, chain, to_address, amount_usd::FLOAT8, risk_level, country, created_at
              FROM withdrawal_requests
-             WHERE user_id = $1
-               AND status IN ('completed', 'pending')
-               AND created_at > NOW() - INTERVAL '24 hours'",
+             WHERE tenant_id = $1
+               AND ($2::TIMESTAMPTZ IS NULL OR created_at >= $2)
+               AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+             ORDER BY created_at DESC
+             LIMIT $4",
         )
-        .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(tenant_id)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(total.unwrap_or(0.0))
+        let mut report = PolicyDryRunReport {
+            evaluated: 0,
+            by_risk_level: HashMap::new(),
+            rule_hits: HashMap::new(),
+            changed: 0,
+            changed_samples: Vec::new(),
+        };
+
+        for (id, user_id, chain, to_address, amount_usd, recorded, country, created_at) in history {
+            let amount_usd = amount_usd.unwrap_or(0.0);
+            let ctx = self
+                .build_context(
+                    &rule_set,
+                    user_id,
+                    &chain,
+                    &to_address,
+                    amount_usd,
+                    country,
+                    created_at,
+                )
+                .await?;
+            let outcome = rule_set.evaluate(&ctx);
+
+            report.evaluated += 1;
+            *report
+                .by_risk_level
+                .entry(format!("{:?}", outcome.risk_level))
+                .or_default() += 1;
+            for rule in &outcome.triggered_rules {
+                *report.rule_hits.entry(rule.clone()).or_default() += 1;
+            }
+
+            if recorded.as_deref().and_then(RiskLevel::parse) != Some(outcome.risk_level) {
+                report.changed += 1;
+                if report.changed_samples.len() < DRY_RUN_SAMPLE_LIMIT {
+                    report.changed_samples.push(PolicyDryRunItem {
+                        withdrawal_id: id,
+                        created_at,
+                        amount_usd,
+                        recorded_risk_level: recorded,
+                        simulated_risk_level: outcome.risk_level,
+                        triggered_rules: outcome.triggered_rules,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 加载租户已启用策略；编译失败的策略按 High 兜底（fail closed）
+    async fn load_rule_set(&self, tenant_id: Uuid) -> Result<RuleSet> {
+        let policies =
+            crate::repository::policies::list_active_by_tenant(&self.pool, tenant_id).await?;
+
+        if policies.is_empty() {
+            let doc = compile_policy(&default_policy_rules())?;
+            return Ok(RuleSet::new().with_policy(None, doc));
+        }
+
+        let mut rule_set = RuleSet::new();
+        for policy in policies {
+            let policy_ref = PolicyRef {
+                policy_id: policy.id,
+                name: policy.name.clone(),
+                version: policy.version,
+            };
+            match compile_policy(&policy.rules) {
+                Ok(doc) => rule_set = rule_set.with_policy(Some(policy_ref), doc),
+                Err(e) => {
+                    tracing::error!(
+                        policy_id = %policy.id,
+                        version = policy.version,
+                        error = %e,
+                        "Active risk policy failed to compile, forcing manual review"
+                    );
+                    rule_set = rule_set.with_rule(RuleSpec {
+                        id: format!("POLICY_COMPILE_ERROR:{}", policy.id),
+                        description: None,
+                        level: RiskLevel::High,
+                        when: Predicate::All(Vec::new()), // 空 all 恒成立
+                    });
+                }
+            }
+        }
+
+        Ok(rule_set)
+    }
+
+    /// 构建规则上下文（as_of 之前的历史数据）
+    #[allow(clippy::too_many_arguments)]
+    async fn build_context(
+        &self,
+        rule_set: &RuleSet,
+        user_id: Uuid,
+        chain: &str,
+        to_address: &str,
+        amount_usd: f64,
+        country: Option<String>,
+        as_of: DateTime<Utc>,
+    ) -> Result<RiskContext> {
+        let mut velocity = HashMap::new();
+        for window_hours in rule_set.velocity_windows() {
+            velocity.insert(
+                window_hours,
+                self.get_velocity_stats(user_id, window_hours, as_of)
+                    .await?,
+            );
+        }
+
+        Ok(RiskContext {
+            amount_usd,
+            chain: chain.to_string(),
+            to_address: to_address.to_string(),
+            at: as_of,
+            account_age_days: self.get_account_age_days(user_id, as_of).await?,
+            country,
+            address_blacklisted: self.is_blacklisted_address(to_address).await?,
+            suspicious_activity: self.has_suspicious_activity(user_id, as_of).await?,
+            velocity,
+        })
     }
 
-    /// 获取用户最近N小时内的提现次数
-    async fn get_recent_withdrawal_count(&self, user_id: Uuid, hours: i32) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*)
+    /// 获取 as_of 之前 N 小时内的提现次数与金额（金额不含已拒绝/失败）
+    async fn get_velocity_stats(
+        &self,
+        user_id: Uuid,
+        window_hours: u32,
+        as_of: DateTime<Utc>,
+    ) -> Result<VelocityStats> {
+        let (count, total_usd): (i64, f64) = sqlx::query_as(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(amount_usd) FILTER (WHERE status NOT IN ('rejected', 'failed', 'cancelled')), 0)::FLOAT8
              FROM withdrawal_requests
              WHERE user_id = $1
-               AND created_at > NOW() - INTERVAL '1 hour' * $2",
+               AND created_at > $2 - INTERVAL '1 hour' * $3
+               AND created_at < $2",
         )
         .bind(user_id)
-        .bind(hours)
+        .bind(as_of)
+        .bind(window_hours as i32)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(count)
+        Ok(VelocityStats { count, total_usd })
     }
 
-    /// 获取账户年龄（天数）
-    async fn get_account_age_days(&self, user_id: Uuid) -> Result<i64> {
+    /// 获取 as_of 时的账户年龄（天数）
+    async fn get_account_age_days(&self, user_id: Uuid, as_of: DateTime<Utc>) -> Result<i64> {
         let age: Option<i64> = sqlx::query_scalar(
-            "SELECT EXTRACT(DAY FROM NOW() - created_at)::BIGINT
+            "SELECT EXTRACT(DAY FROM $2 - created_at)::BIGINT
              FROM users
              WHERE id = $1",
         )
         .bind(user_id)
+        .bind(as_of)
         .fetch_one(&self.pool)
         .await?;
 
@@ -236,18 +404,20 @@ impl WithdrawalRiskControl {
     }
 
     /// 检查账户是否有可疑活动
-    async fn has_suspicious_activity(&self, user_id: Uuid) -> Result<bool> {
-        // 简化实现：检查是否有最近的安全告警
+    async fn has_suspicious_activity(&self, user_id: Uuid, as_of: DateTime<Utc>) -> Result<bool> {
+        // 简化实现：检查 as_of 前 7 天内未关闭的高危安全告警
         let has_alerts: bool = sqlx::query_scalar(
             "SELECT EXISTS(
                 SELECT 1 FROM security_alerts
                 WHERE user_id = $1
                   AND severity IN ('high', 'critical')
-                  AND created_at > NOW() - INTERVAL '7 days'
+                  AND created_at > $2 - INTERVAL '7 days'
+                  AND created_at <= $2
                   AND status = 'open'
             )",
         )
         .bind(user_id)
+        .bind(as_of)
         .fetch_one(&self.pool)
         .await
         .unwrap_or(false);
@@ -266,8 +436,9 @@ impl WithdrawalRiskControl {
         sqlx::query(
             "INSERT INTO withdrawal_risk_logs
              (id, user_id, tenant_id, amount_usd, chain, to_address,
-              risk_level, allow, triggered_rules, suggestion, requires_manual_review, created_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())",
+              risk_level, allow, triggered_rules, suggestion, requires_manual_review,
+              policy_refs, client_ip, country, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW())",
         )
         .bind(log_id)
         .bind(request.user_id)
@@ -280,6 +451,9 @@ impl WithdrawalRiskControl {
         .bind(serde_json::to_value(&decision.triggered_rules)?)
         .bind(&decision.suggestion)
         .bind(decision.requires_manual_review)
+        .bind(serde_json::to_value(&decision.policies)?)
+        .bind(&request.client_ip)
+        .bind(&request.country)
         .execute(&self.pool)
         .await?;
 
@@ -296,4 +470,14 @@ mod tests {
         assert!(RiskLevel::Low < RiskLevel::Medium);
         assert!(RiskLevel::Medium < RiskLevel::High);
     }
+
+    #[test]
+    fn test_risk_level_parse_recorded_format() {
+        assert_eq!(
+            RiskLevel::parse(&format!("{:?}", RiskLevel::High)),
+            Some(RiskLevel::High)
+        );
+        assert_eq!(RiskLevel::parse("reject"), Some(RiskLevel::Reject));
+        assert_eq!(RiskLevel::parse("unknown"), None);
+    }
 }
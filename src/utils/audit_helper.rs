@@ -1,18 +1,21 @@
 //! 审计日志辅助函数
-//! 提供统一的审计日志写入接口
+//! 提供统一的审计日志写入接口：先追加哈希链账本（权威记录），再双写 immudb（最佳努力）
 
 use std::sync::Arc;
 
 use anyhow::Result;
-use chrono::Utc;
-use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::infrastructure::audit::ImmuCtx;
+use crate::{
+    infrastructure::audit::{AuditEvent, ImmuCtx},
+    service::audit_ledger::{AuditLedger, NewLedgerEntry},
+};
 
 /// 写入审计事件
 ///
 /// # Arguments
+/// * `pool` - 数据库连接池（审计账本）
 /// * `immu` - Immudb客户端
 /// * `event` - 事件名称（如 "wallet.create"）
 /// * `tenant_id` - 租户ID
@@ -21,8 +24,9 @@ use crate::infrastructure::audit::ImmuCtx;
 /// * `payload` - 事件负载（JSON对象）
 ///
 /// # Returns
-/// 返回Result，失败时返回错误（但不阻断主流程）
+/// 账本追加失败时返回错误；immudb 写入为最佳努力
 pub async fn write_audit_event(
+    pool: &PgPool,
     immu: &Arc<ImmuCtx>,
     event: &str,
     tenant_id: Uuid,
@@ -30,60 +34,58 @@ pub async fn write_audit_event(
     resource: Uuid,
     payload: serde_json::Value,
 ) -> Result<()> {
-    // 序列化payload
-    let payload_str = payload.to_string();
-
-    // 计算payload的SHA256哈希
-    let mut hasher = Sha256::new();
-    hasher.update(payload_str.as_bytes());
-    let payload_hash = faster_hex::hex_string(&hasher.finalize());
-
-    // 创建审计事件
-    let audit_event = crate::infrastructure::audit::AuditEvent {
-        event: event.into(),
-        tenant_id: tenant_id.to_string(),
-        actor: actor.to_string(),
-        resource: resource.to_string(),
-        payload_hash,
-        ts: Utc::now().to_rfc3339(),
-    };
-
-    // 写入审计日志（最佳努力，不阻断主流程）
-    immu.write_event(&audit_event).await?;
-
-    Ok(())
+    write_audit_event_str(
+        pool,
+        immu,
+        event,
+        tenant_id,
+        &actor.to_string(),
+        &resource.to_string(),
+        payload,
+    )
+    .await
 }
 
-/// 写入审计事件（简化版本，使用字符串作为resource）
+/// 写入审计事件（字符串 actor / resource，用于 "system" 等非 UUID 操作者）
 pub async fn write_audit_event_str(
+    pool: &PgPool,
     immu: &Arc<ImmuCtx>,
     event: &str,
     tenant_id: Uuid,
-    actor: Uuid,
+    actor: &str,
     resource: &str,
     payload: serde_json::Value,
 ) -> Result<()> {
-    let payload_str = payload.to_string();
-    let mut hasher = Sha256::new();
-    hasher.update(payload_str.as_bytes());
-    let payload_hash = faster_hex::hex_string(&hasher.finalize());
+    // 1. 追加哈希链账本（immudb 不可用时仍可由检查点事后证明）
+    let entry = AuditLedger::new(pool.clone())
+        .append(&NewLedgerEntry {
+            id: Uuid::new_v4(),
+            tenant_id,
+            event: event.into(),
+            actor: actor.into(),
+            resource: resource.into(),
+            payload,
+        })
+        .await?;
 
-    let audit_event = crate::infrastructure::audit::AuditEvent {
-        event: event.into(),
+    // 2. 双写 immudb，承诺账本记录哈希（与 AuditService 一致）
+    let audit_event = AuditEvent {
+        event: entry.event,
         tenant_id: tenant_id.to_string(),
-        actor: actor.to_string(),
-        resource: resource.into(),
-        payload_hash,
-        ts: Utc::now().to_rfc3339(),
+        actor: entry.actor,
+        resource: entry.resource,
+        payload_hash: entry.entry_hash,
+        ts: entry.created_at.to_rfc3339(),
     };
-
     immu.write_event(&audit_event).await?;
+
     Ok(())
 }
 
 /// 写入审计事件（异步，不等待结果）
 /// 用于不需要等待审计日志写入完成的场景
 pub fn write_audit_event_async(
+    pool: PgPool,
     immu: Arc<ImmuCtx>,
     event: String,
     tenant_id: Uuid,
@@ -91,22 +93,21 @@ pub fn write_audit_event_async(
     resource: Uuid,
     payload: serde_json::Value,
 ) {
-    tokio::spawn(async move {
-        if let Err(e) = write_audit_event(&immu, &event, tenant_id, actor, resource, payload).await
-        {
-            tracing::warn!("Failed to write audit event {}: {}", event, e);
-            // 记录审计日志失败metrics（使用静态字符串）
-            crate::metrics::count_err("audit.write.failed");
-        } else {
-            // 记录审计日志成功metrics（使用静态字符串）
-            crate::metrics::count_ok("audit.write.success");
-        }
-    });
+    write_audit_event_async_str_actor(
+        pool,
+        immu,
+        event,
+        tenant_id,
+        actor.to_string(),
+        resource,
+        payload,
+    );
 }
 
 /// 写入审计事件（使用字符串actor，异步）
 /// 用于actor不是UUID的场景（如"system"）
 pub fn write_audit_event_async_str_actor(
+    pool: PgPool,
     immu: Arc<ImmuCtx>,
     event: String,
     tenant_id: Uuid,
@@ -115,18 +116,18 @@ pub fn write_audit_event_async_str_actor(
     payload: serde_json::Value,
 ) {
     tokio::spawn(async move {
-        let actor_uuid = Uuid::parse_str(&actor).unwrap_or_else(|_| Uuid::nil());
         if let Err(e) = write_audit_event_str(
+            &pool,
             &immu,
             &event,
             tenant_id,
-            actor_uuid,
+            &actor,
             &resource.to_string(),
             payload,
         )
         .await
         {
-            tracing::warn!("Failed to write audit event {}: {}", event, e);
+            tracing::error!("Failed to write audit event {}: {:#}", event, e);
             // 记录审计日志失败metrics（使用静态字符串）
             crate::metrics::count_err("audit.write.failed");
         } else {
@@ -0,0 +1,30 @@
+//! GeoIP 国家查询
+//! 按 IP 推断国家代码（ipapi.co），供风控与法币服务商选择使用
+
+use std::net::IpAddr;
+
+use anyhow::{anyhow, Result};
+
+use crate::infrastructure::telemetry::TracePropagation;
+
+/// 查询 IP 所属国家（ISO 3166-1 alpha-2，大写）
+pub async fn lookup_country(ip: IpAddr) -> Result<String> {
+    let url = format!("https://ipapi.co/{}/country_code/", ip);
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(3))
+        .build()?;
+
+    let resp = client
+        .get(&url)
+        .with_trace_context()
+        .send()
+        .await
+        .map_err(|_| anyhow!("GeoIP lookup failed"))?;
+
+    let country = resp.text().await?.trim().to_uppercase();
+    if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(country)
+    } else {
+        Err(anyhow!("Invalid country code"))
+    }
+}
@@ -3,6 +3,7 @@ pub mod audit_helper;
 pub mod chain_normalizer;
 pub mod error_codes;
 pub mod error_tracking;
+pub mod geoip;
 pub mod string_utils;
 pub mod time_utils; // ✅ R项修复: 统一错误代码标准
 